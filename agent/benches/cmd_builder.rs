@@ -0,0 +1,219 @@
+//! Benchmarks for building the command the agent executes for each job
+//!
+//! Run these with `cargo bench -p thorium-agent` and compare against a saved
+//! baseline with `--save-baseline`/`--baseline` to catch regressions. The number
+//! of allocations each build makes is also printed before it is benchmarked.
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+use thorium::models::{
+    ArgStrategy, GenericJobArgs, ImageArgs, KwargDependency, RepoDependency,
+    ResultDependencySettings,
+};
+use thorium::test_utilities::CountingAllocator;
+use thorium::test_utilities::fixtures::{ImageBuilder, JobBuilder};
+use thorium_agent::CmdBuilder;
+
+/// The number of tools a result dependency map has keys for
+const MAPPED_TOOLS: usize = 8;
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Print the number of allocations it takes to build a command
+///
+/// # Arguments
+///
+/// * `name` - The name of the benchmark we are counting allocations for
+/// * `build` - Builds the command to count allocations for
+fn report_allocations<F: FnMut() -> Vec<String>>(name: &str, mut build: F) {
+    let start = CountingAllocator::allocations();
+    let cmd = black_box(build());
+    let allocations = CountingAllocator::allocations() - start;
+    println!("{name}: {allocations} allocations for {} args", cmd.len());
+}
+
+/// Build a list of owned strings
+///
+/// # Arguments
+///
+/// * `items` - The strings to own
+fn owned(items: &[&str]) -> Vec<String> {
+    items.iter().map(|item| (*item).to_owned()).collect()
+}
+
+/// Build numbered paths under a directory
+///
+/// # Arguments
+///
+/// * `dir` - The directory to build paths in
+/// * `count` - The number of paths to build
+fn paths(dir: &Path, count: usize) -> Vec<PathBuf> {
+    (0..count).map(|i| dir.join(format!("item{i}"))).collect()
+}
+
+/// Benchmark a barebones job with no overlays
+fn minimal(c: &mut Criterion) {
+    let image = ImageBuilder::default().build();
+    let job = JobBuilder::default().build();
+    let entrypoint = owned(&["/usr/bin/python3"]);
+    let cmd = owned(&["corn.py"]);
+    let build = || {
+        CmdBuilder::new(
+            black_box(&image),
+            black_box(&job),
+            black_box(&entrypoint),
+            black_box(&cmd),
+        )
+        .build(&image, None, None)
+        .unwrap()
+    };
+    report_allocations("build/minimal", build);
+    c.bench_function("build/minimal", |b| b.iter(build));
+}
+
+/// Benchmark a job that overrides many of the kwargs in an image's original command
+fn many_kwargs(c: &mut Criterion) {
+    // pass results and result files in with kwargs
+    let args = ImageArgs {
+        output: ArgStrategy::Kwarg("--output".to_owned()),
+        output_files: ArgStrategy::Kwarg("--output-files".to_owned()),
+        ..ImageArgs::default()
+    };
+    let image = ImageBuilder::default().args(args).build();
+    // build job args that override half of the kwargs in the original command
+    let mut job_args = GenericJobArgs::default().positionals(vec!["field1", "field2"]);
+    for i in 0..64 {
+        job_args = job_args.kwarg(format!("--kwarg{i}"), vec![format!("{i}"), format!("x{i}")]);
+    }
+    for i in 0..32 {
+        job_args = job_args.ordered_kwarg(format!("--ordered{i}"), vec![format!("{i}")]);
+    }
+    for i in 0..16 {
+        job_args = job_args.switch(format!("--switch{i}"));
+    }
+    let job = JobBuilder::default().args(job_args).build();
+    // build an original command with its own kwargs and switches
+    let entrypoint = owned(&["/usr/bin/python3"]);
+    let mut cmd = owned(&["corn.py"]);
+    for i in (0..128).step_by(2) {
+        cmd.extend([format!("--kwarg{i}"), format!("original{i}")]);
+    }
+    cmd.extend(owned(&["--verbose", "--debug", "input"]));
+    let build = || {
+        CmdBuilder::new(
+            black_box(&image),
+            black_box(&job),
+            black_box(&entrypoint),
+            black_box(&cmd),
+        )
+        .build(&image, None, None)
+        .unwrap()
+    };
+    report_allocations("build/many_kwargs", build);
+    c.bench_function("build/many_kwargs", |b| b.iter(build));
+}
+
+/// Benchmark a job that passes in many samples, repos, and ephemeral files
+fn dependencies(c: &mut Criterion) {
+    // pass in samples and ephemeral files with kwargs and repos as positionals
+    let mut image = ImageBuilder::default().build();
+    image.dependencies.samples.kwarg = Some("--sample".to_owned());
+    image.dependencies.ephemeral.kwarg = Some("--ephemeral".to_owned());
+    let repos = (0..16)
+        .map(|i| RepoDependency {
+            url: format!("github.com/corn/repo{i}"),
+            commitish: Some("main".to_owned()),
+            kind: None,
+        })
+        .collect::<Vec<RepoDependency>>();
+    let ephemeral = (0..16).map(|i| format!("ephemeral{i}.txt")).collect();
+    let job = JobBuilder::default()
+        .args(GenericJobArgs::default().kwarg("--rows", vec!["12"]))
+        .repos(repos)
+        .ephemeral(ephemeral)
+        .build();
+    // build the paths our dependencies were downloaded to
+    let root = PathBuf::from("/tmp/thorium");
+    let sample_paths = paths(&root.join("samples"), 256);
+    let repo_paths = paths(&root.join("repos"), 16);
+    let ephemeral_paths = paths(&root.join("ephemeral"), 16);
+    let entrypoint = owned(&["/usr/bin/python3"]);
+    let cmd = owned(&["corn.py"]);
+    let build = || {
+        CmdBuilder::new(
+            black_box(&image),
+            black_box(&job),
+            black_box(&entrypoint),
+            black_box(&cmd),
+        )
+        .add_samples(&sample_paths, &image.dependencies.samples)
+        .add_repos(&image, &job.repos, &repo_paths)
+        .add_ephemeral(
+            &job.ephemeral,
+            &ephemeral_paths,
+            &image.dependencies.ephemeral,
+        )
+        .build(&image, None, None)
+        .unwrap()
+    };
+    report_allocations("build/dependencies", build);
+    c.bench_function("build/dependencies", |b| b.iter(build));
+}
+
+/// Benchmark passing in result dependencies with a kwarg for each tool
+///
+/// This checks the filesystem for each tool in each result path so results are
+/// written to a real temporary directory with only half of the tools present.
+fn results_map(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    // write results for half of our tools to a temporary directory
+    let root = std::env::temp_dir().join(format!("thorium-bench-{}", std::process::id()));
+    let result_paths = paths(&root, 32);
+    for path in &result_paths {
+        for tool in 0..MAPPED_TOOLS / 2 {
+            std::fs::create_dir_all(path.join(format!("tool{tool}"))).unwrap();
+        }
+    }
+    // map each tool to its own kwarg
+    let map = (0..MAPPED_TOOLS)
+        .map(|tool| (format!("tool{tool}"), format!("--tool{tool}-results")))
+        .collect::<HashMap<String, String>>();
+    let tools = map.keys().cloned().collect::<Vec<String>>();
+    let settings = ResultDependencySettings::default()
+        .images(tools.clone())
+        .kwarg(KwargDependency::Map(map));
+    let image = ImageBuilder::default().build();
+    let job = JobBuilder::default().build();
+    let entrypoint = owned(&["/usr/bin/python3"]);
+    let cmd = owned(&["corn.py"]);
+    // borrow everything our runs need so each run's future can own these references
+    let (tools, result_paths, settings, image) = (&tools, &result_paths, &settings, &image);
+    c.bench_function("add_results/map", |b| {
+        b.to_async(&runtime).iter_batched(
+            // give each run its own log channel so missing tool logs don't pile up
+            || {
+                let builder = CmdBuilder::new(image, &job, &entrypoint, &cmd);
+                (builder, crossbeam::channel::unbounded::<String>())
+            },
+            |(builder, (mut logs_tx, logs_rx))| async move {
+                let cmd = builder
+                    .add_results(tools, result_paths, settings, &mut logs_tx)
+                    .await
+                    .unwrap()
+                    .build(image, None, None)
+                    .unwrap();
+                // keep our receiver alive until we are done logging
+                (cmd, logs_rx)
+            },
+            BatchSize::SmallInput,
+        );
+    });
+    // clean up our results
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+criterion_group!(benches, minimal, many_kwargs, dependencies, results_map);
+criterion_main!(benches);
@@ -0,0 +1,153 @@
+//! Fuzzes how the agent overlays job args onto an image's original command
+//!
+//! Inputs are JSON so the seed corpus can be written by hand from the `CmdBuilder`
+//! unit tests. Run this with:
+//!
+//! ```text
+//! cargo +nightly fuzz run cmd_builder fuzz/corpus/cmd_builder fuzz/seeds/cmd_builder
+//! ```
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use serde::Deserialize;
+use std::path::PathBuf;
+use thorium::models::{
+    ArgStrategy, DependencyPassStrategy, GenericJobArgs, ImageArgs, SampleDependencySettings,
+};
+use thorium::test_utilities::fixtures::{ImageBuilder, JobBuilder};
+use thorium_agent::CmdBuilder;
+
+/// A single command to build
+#[derive(Deserialize, Debug)]
+struct Case {
+    /// The entrypoint from the container
+    #[serde(default)]
+    entrypoint: Vec<String>,
+    /// The command from the container
+    #[serde(default)]
+    cmd: Vec<String>,
+    /// The arg settings for our image
+    #[serde(default)]
+    image: ImageArgs,
+    /// The args set by the job
+    #[serde(default)]
+    args: GenericJobArgs,
+    /// Whether this job is a generator
+    #[serde(default)]
+    generator: bool,
+    /// The paths to any samples to pass in
+    #[serde(default)]
+    samples: Vec<PathBuf>,
+    /// The settings to use when passing in samples
+    #[serde(default)]
+    sample_settings: SampleDependencySettings,
+}
+
+impl Case {
+    /// Get the kwargs that the image injects and so can override the job's kwargs
+    fn injected_kwargs(&self) -> Vec<&str> {
+        let mut injected = Vec::new();
+        // generators are always passed their job and reaction ids
+        if self.generator {
+            injected.extend(["--job", "--reaction"]);
+        }
+        if let Some(key) = &self.image.reaction {
+            injected.push(key.as_str());
+        }
+        for strategy in [&self.image.output, &self.image.output_files] {
+            if let ArgStrategy::Kwarg(key) = strategy {
+                injected.push(key.as_str());
+            }
+        }
+        // samples are added as sorted kwargs which take precedence over ordered kwargs
+        if self.sample_settings.strategy != DependencyPassStrategy::Disabled {
+            injected.extend(self.sample_settings.kwarg.as_deref());
+        }
+        injected
+    }
+}
+
+/// Check if a built command passes a kwarg with a specific value
+///
+/// # Arguments
+///
+/// * `built` - The built command to check
+/// * `key` - The key for the kwarg
+/// * `value` - The value that should be passed with this key
+fn passes(built: &[String], key: &str, value: &str) -> bool {
+    // check for the value either after its key or joined to it with an '='
+    built
+        .windows(2)
+        .any(|pair| pair[0] == key && pair[1] == value)
+        || built.contains(&format!("{key}={value}"))
+}
+
+fuzz_target!(|data: &[u8]| {
+    // skip any inputs that are not valid cases
+    let Ok(case) = serde_json::from_slice::<Case>(data) else {
+        return;
+    };
+    // build the image and job for this case
+    let mut image = ImageBuilder::default().args(case.image.clone()).build();
+    image.dependencies.samples = case.sample_settings.clone();
+    let job = JobBuilder::default()
+        .args(case.args.clone())
+        .generator(case.generator)
+        .build();
+    // build our command and make sure we never panic while doing it
+    let built = CmdBuilder::new(&image, &job, &case.entrypoint, &case.cmd)
+        .add_samples(&case.samples, &image.dependencies.samples)
+        .build(&image, None, None);
+    // invalid entrypoints are allowed to be rejected
+    let Ok(built) = built else {
+        return;
+    };
+    // an override cmd should always be used as is
+    if let Some(override_cmd) = &case.args.opts.override_cmd {
+        assert_eq!(&built, override_cmd);
+        return;
+    }
+    // get the program we are executing
+    let program = image
+        .args
+        .entrypoint
+        .as_ref()
+        .unwrap_or(&case.entrypoint)
+        .iter()
+        .chain(image.args.command.as_ref().unwrap_or(&case.cmd))
+        .next();
+    // the program we are executing should never be dropped unless it looks like a kwarg
+    if let Some(program) = program.filter(|program| !program.starts_with('-')) {
+        assert_eq!(built.first(), Some(program), "{case:#?} built {built:?}");
+    }
+    // the job's kwargs should always win over any kwargs in the original command
+    let injected = case.injected_kwargs();
+    let ordered = case
+        .args
+        .ordered_kwargs
+        .iter()
+        .filter(|(key, _)| !case.args.kwargs.contains_key(key))
+        .map(|kwarg| (&kwarg.0, &kwarg.1));
+    for (key, values) in case.args.kwargs.iter().chain(ordered) {
+        // skip any kwargs that are replaced by the image
+        if injected.contains(&key.as_str()) {
+            continue;
+        }
+        for value in values {
+            assert!(
+                passes(&built, key, value),
+                "{key} {value} is missing in {case:#?} built {built:?}"
+            );
+        }
+    }
+    // all of the job's switches and positionals should be passed in
+    for switch in &case.args.switches {
+        assert!(built.contains(switch), "{switch} is missing in {built:?}");
+    }
+    for positional in &case.args.positionals {
+        assert!(
+            built.contains(positional),
+            "{positional} is missing in {built:?}"
+        );
+    }
+});
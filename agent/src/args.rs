@@ -40,6 +40,9 @@ pub struct Args {
     /// How long should this agent sit limbo before exiting without a job to work on
     #[clap(short, long, default_value = "5")]
     pub limbo: usize,
+    /// How many job dependencies to download at once
+    #[clap(long, default_value = "3")]
+    pub download_concurrency: usize,
 }
 
 impl Args {
@@ -68,6 +71,7 @@ impl Args {
             user,
             thorium: thorium.clone(),
             pool: worker.pool,
+            download_concurrency: self.download_concurrency.max(1),
         };
         //log this new target
         event!(
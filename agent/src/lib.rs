@@ -0,0 +1,9 @@
+//! The Thorium agent responsible for executing jobs
+//!
+//! The agent is run as a binary but its command building is exposed so it can be
+//! fuzzed without a running worker.
+
+pub mod args;
+mod libs;
+
+pub use libs::{CmdBuilder, DownloadedCache, Target, Worker, dry_run_args};
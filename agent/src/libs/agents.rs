@@ -1,5 +1,6 @@
 //! A trait for executing a Thorium job in a specific environment
 
+use chrono::{DateTime, Utc};
 use crossbeam::channel::{Receiver, Sender};
 use std::collections::HashMap;
 #[cfg(target_os = "linux")]
@@ -19,6 +20,7 @@ pub mod k8s;
 mod setup;
 
 pub use baremetal::BareMetal;
+pub use cmd::{CmdBuilder, dry_run_args};
 pub use k8s::K8s;
 
 use crate::args::Envs;
@@ -33,6 +35,8 @@ use super::tags::TagBundle;
 // log at most .10 mebibytes
 const MAX_LOG: usize = 104_858;
 const MAX_BATCHES: usize = 10;
+// send a heartbeat when a job is within 30 seconds of its execution deadline
+const HEARTBEAT_MARGIN: Duration = Duration::from_secs(30);
 
 /// Check if a subprocess child has completed or not
 ///
@@ -320,8 +324,13 @@ impl Agent {
     ) -> Result<JobStatus, Error> {
         // get timestamps to track how long this job has been running for
         let start = Instant::now();
-        // get time job should be killed at if we have a timeout set
-        let timeout = self.image.timeout.map(|seconds| from_now!(start, seconds));
+        // get time job should be killed at from our execution deadline or our images timeout
+        let mut timeout = match self.job.execution_deadline {
+            Some(deadline) => Some(start + (deadline - Utc::now()).to_std().unwrap_or_default()),
+            None => self.image.timeout.map(|seconds| from_now!(start, seconds)),
+        };
+        // only send heartbeats for jobs that have an execution deadline to extend
+        let mut heartbeats = self.job.execution_deadline.is_some();
         // get the duration to sleep between checks
         let sleep = Duration::from_millis(100);
         // wait for this job to finish exeucting
@@ -345,6 +354,28 @@ impl Agent {
                 }
                 JobStatus::OnGoing => (),
             }
+            // push back our deadline if we are close to timing out
+            if heartbeats
+                && timeout.is_some_and(|timeout| Instant::now() + HEARTBEAT_MARGIN > timeout)
+            {
+                match self.heartbeat().await {
+                    Ok(Some(deadline)) => {
+                        let remaining = (deadline - Utc::now()).to_std().unwrap_or_default();
+                        timeout = Some(Instant::now() + remaining);
+                    }
+                    // this job no longer has a deadline to extend
+                    Ok(None) => heartbeats = false,
+                    // stop sending heartbeats once this job can't be extended anymore
+                    Err(error) => {
+                        event!(
+                            Level::WARN,
+                            msg = "Failed to send heartbeat",
+                            error = error.to_string()
+                        );
+                        heartbeats = false;
+                    }
+                }
+            }
             // check if we this job should be timed out or not
             if timeout.is_some() && Some(Instant::now()) > timeout {
                 // log this timeout
@@ -360,6 +391,28 @@ impl Agent {
         }
     }
 
+    /// Tell Thorium this job has started executing
+    ///
+    /// This starts our execution deadline now so time spent in a queue or setting up
+    /// doesn't count against this job's timeout.
+    #[instrument(name = "agents::start", skip_all, err(Debug))]
+    pub async fn start(&mut self) -> Result<(), Error> {
+        let started = self.thorium.jobs.start(&self.job.id).await?;
+        // track our new execution deadline
+        self.job.started = Some(started.started);
+        self.job.execution_deadline = started.execution_deadline;
+        Ok(())
+    }
+
+    /// Tell Thorium this job is still running and push back its execution deadline
+    #[instrument(name = "agents::heartbeat", skip_all, err(Debug))]
+    async fn heartbeat(&mut self) -> Result<Option<DateTime<Utc>>, Error> {
+        let heartbeat = self.thorium.jobs.heartbeat(&self.job.id).await?;
+        // track our new execution deadline
+        self.job.execution_deadline = heartbeat.execution_deadline;
+        Ok(heartbeat.execution_deadline)
+    }
+
     /// Tell Thorium this job completed and to proceed
     #[instrument(name = "agents::proceed", skip_all, err(Debug))]
     pub async fn proceed(&self) -> Result<(), Error> {
@@ -381,6 +434,36 @@ impl Agent {
         Ok(())
     }
 
+    /// Check if this job will not run again under the same job id
+    ///
+    /// Generators stay in their reactions generator set until a run completes without
+    /// sleeping, so a generator is only finished once it has been removed from that set.
+    #[instrument(name = "agents::finished", skip_all)]
+    async fn finished(&self) -> bool {
+        // jobs that are not generators never run again
+        if !self.job.generator {
+            return true;
+        }
+        // get this jobs reaction to see if it is still a generator
+        match self
+            .thorium
+            .reactions
+            .get(&self.job.group, self.job.reaction)
+            .await
+        {
+            Ok(reaction) => !reaction.generators.contains(&self.job.id),
+            Err(error) => {
+                // assume we may run again so our submitted tags are kept
+                event!(
+                    Level::INFO,
+                    msg = "Failed to check if generator finished",
+                    error = error.to_string()
+                );
+                false
+            }
+        }
+    }
+
     /// Tell Thorium this job failed with an error message
     ///
     /// # Arguments
@@ -557,6 +640,16 @@ pub async fn sub_execute(
         .executor
         .setup(&agent.image, &agent.job, &mut agent.commits)
         .await?;
+    // start our execution clock now that we are done setting up
+    if let Err(error) = agent.start().await {
+        // this only means our images timeout is used as is so just log it
+        event!(
+            Level::WARN,
+            msg = "Failed to report job start",
+            job = agent.job.id.to_string(),
+            error = error.to_string()
+        );
+    }
     // start executing this job
     let in_flight = agent
         .executor
@@ -592,8 +685,21 @@ pub async fn sub_execute(
             .await?;
             // send any logs in our logs channel
             agent.send_channel_logs().await?;
-            // submit any tags we found
-            tags::submit(&agent.thorium, tag_bundle, &agent.job, &mut agent.sender).await?;
+            // only submit the tags that earlier runs of this job haven't already submitted
+            let submitted_path = TagBundle::submitted_path(&agent.job);
+            let mut submitted = TagBundle::load(&submitted_path).await;
+            let delta = tag_bundle.diff(&submitted);
+            tags::submit(&agent.thorium, delta, &agent.job, &mut agent.sender).await?;
+            submitted.merge(tag_bundle);
+            // save what we submitted so this jobs next run can skip it
+            if let Err(error) = submitted.save(&submitted_path).await {
+                // this only means our next run resubmits some tags so just log it
+                event!(
+                    Level::INFO,
+                    msg = "Failed to save submitted tags",
+                    error = error.to_string()
+                );
+            }
             // send any logs in our logs channel
             agent.send_channel_logs().await?;
             // collect any children files
@@ -674,6 +780,11 @@ pub async fn execute(mut agent: Agent, log_path: String) {
         Ok(()) => {
             event!(Level::INFO, msg = "Proceeding with reaction");
             check!(agent.proceed().await);
+            // sleeping generators will run again under this job id so keep their submitted tags
+            if agent.finished().await {
+                // ignore errors since there may never have been any tags to save
+                let _ = tokio::fs::remove_file(TagBundle::submitted_path(&agent.job)).await;
+            }
             // delete this jobs log file
             if let Err(error) = tokio::fs::remove_file(log_path).await {
                 // log this error but continue on since it probably doesn't matter
@@ -687,6 +798,8 @@ pub async fn execute(mut agent: Agent, log_path: String) {
             check!(agent.executor.clean_up(&agent.image, &agent.job).await);
             // error out this job
             check!(agent.error(&mut reader, &error).await);
+            // failed jobs fail their reaction and never run again so drop their submitted tags
+            let _ = tokio::fs::remove_file(TagBundle::submitted_path(&agent.job)).await;
             // delete this jobs log file
             if let Err(error) = tokio::fs::remove_file(log_path).await {
                 // log this error but continue on since it probably doesn't matter
@@ -5,10 +5,9 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use thorium::models::{GenericJob, Image};
 use thorium::{Error, Thorium};
-use tokio::process::Command;
 use tracing::{Level, event, instrument};
 
-use super::cmd::CmdBuilder;
+use super::cmd::{self, CmdBuilder};
 use super::{AgentExecutor, InFlight, setup};
 use crate::libs::children::{self, Children};
 use crate::libs::{DownloadedCache, RawResults, TagBundle, Target, results, tags};
@@ -68,6 +67,8 @@ pub struct BareMetal {
     pub children_path: PathBuf,
     /// The path to write cache info to
     pub cache_path: PathBuf,
+    /// The directory any working directories for this job can be created in
+    pub job_root: PathBuf,
     /// The paths to any downloaded sample files
     samples: Vec<PathBuf>,
     /// The paths to any downloaded ephemeral files
@@ -82,6 +83,8 @@ pub struct BareMetal {
     children: Vec<PathBuf>,
     /// The paths to any downloaded cache info
     cache: DownloadedCache,
+    /// How many job dependencies to download at once
+    download_concurrency: usize,
 }
 
 impl BareMetal {
@@ -101,6 +104,7 @@ impl BareMetal {
         let tags_path = isolate(&target.image.output_collection.files.tags, &id)?;
         let children_path = isolate(&target.image.output_collection.children, &id)?;
         let cache_path = isolate(&target.image.dependencies.cache.location, &id)?;
+        let job_root = isolate("/tmp/thorium", &id)?;
         // build our baremetal object
         let bare_metal = BareMetal {
             thorium: target.thorium.clone(),
@@ -116,6 +120,7 @@ impl BareMetal {
             tags_path,
             children_path,
             cache_path,
+            job_root,
             samples: Vec::default(),
             ephemerals: Vec::default(),
             repos: Vec::default(),
@@ -123,6 +128,7 @@ impl BareMetal {
             tags: Vec::default(),
             children: Vec::default(),
             cache: DownloadedCache::default(),
+            download_concurrency: target.download_concurrency,
         };
         Ok(bare_metal)
     }
@@ -204,6 +210,7 @@ impl AgentExecutor for BareMetal {
             image,
             job,
             &self.samples_path,
+            self.download_concurrency,
             &mut self.logs,
         )
         .await?;
@@ -229,6 +236,7 @@ impl AgentExecutor for BareMetal {
             image,
             job,
             &self.repos_path,
+            self.download_concurrency,
             commits,
             &mut self.logs,
         )
@@ -293,7 +301,7 @@ impl AgentExecutor for BareMetal {
         // get a ref to our dependency settings
         let dep_conf = &image.dependencies;
         // build our initial command based on our configured entrypoints
-        let cmd = match (&image.args.entrypoint, &image.args.command) {
+        let builder = match (&image.args.entrypoint, &image.args.command) {
             (Some(ep), Some(cmd)) => CmdBuilder::new(image, job, ep, cmd),
             (Some(ep), None) => CmdBuilder::new(image, job, ep, &[]),
             (None, Some(cmd)) => CmdBuilder::new(image, job, &[], cmd),
@@ -304,7 +312,7 @@ impl AgentExecutor for BareMetal {
             }
         };
         // build the command this worker should execute
-        let cmd = cmd
+        let builder = builder
             .add_ephemeral(&job.ephemeral, &self.ephemerals, &dep_conf.ephemeral)
             .add_samples(&self.samples, &dep_conf.samples)
             .add_repos(image, &job.repos, &self.repos)
@@ -317,8 +325,10 @@ impl AgentExecutor for BareMetal {
             .await?
             .add_tags(&self.tags, &dep_conf.tags)
             .add_children(&self.children, &dep_conf.children)
-            .add_cache(&self.cache, &dep_conf.cache)
-            .build(image, Some(&results), Some(&result_files))?;
+            .add_cache(&self.cache, &dep_conf.cache);
+        // get the directory to launch this job in only creating it under this jobs root
+        let working_dir = builder.working_dir(Some(&self.job_root))?;
+        let cmd = builder.build(image, Some(&results), Some(&result_files))?;
         // cast our command to a str
         let built_str = cmd.join(" ");
         // log the command we are executing
@@ -326,13 +336,8 @@ impl AgentExecutor for BareMetal {
         event!(Level::INFO, cmd = built_str);
         // open a file handle to this file
         let log_file = std::fs::File::create(log_path)?;
-        // build the comamnd to execute
-        let mut cmd_builder = Command::new(cmd[0].clone());
-        // if we have more then just one arg then add those
-        if cmd.len() > 1 {
-            // add any remaining args
-            cmd_builder.args(&cmd[1..]);
-        }
+        // build the comamnd to execute in our working directory
+        let mut cmd_builder = cmd::spawn_config(&cmd, working_dir.as_deref());
         // setup our stdout/stderr
         cmd_builder.stdout(log_file.try_clone()?);
         cmd_builder.stderr(log_file);
@@ -436,6 +441,7 @@ impl AgentExecutor for BareMetal {
         purge_parent!(self.tags_path);
         purge_parent!(self.children_path);
         purge_parent!(self.cache_path);
+        purge_parent!(self.job_root);
         Ok(())
     }
 }
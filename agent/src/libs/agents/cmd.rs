@@ -2,17 +2,19 @@
 
 use crossbeam::channel::Sender;
 use path_clean::PathClean;
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use thorium::{
     Error,
     models::{
         ArgStrategy, ChildrenDependencySettings, DependencyPassStrategy,
-        EphemeralDependencySettings, GenericJob, GenericJobKwargs, GenericJobOpts, Image,
-        KwargDependency, OutputHandler, RepoDependency, RepoDependencySettings,
-        ResultDependencySettings, SampleDependencySettings, TagDependencySettings,
-        images::CacheDependencySettings,
+        EphemeralDependencySettings, GenericJob, GenericJobOpts, Image, KwargDependency,
+        OutputHandler, RepoDependency, RepoDependencySettings, ResultDependencySettings,
+        SampleDependencySettings, TagDependencySettings, images::CacheDependencySettings,
     },
 };
+use tokio::process::Command;
 use tracing::instrument;
 
 use crate::libs::DownloadedCache;
@@ -20,43 +22,128 @@ use crate::log;
 
 /// Expands a string into a key/value if it is a joint kwarg
 ///
-/// A joint kwarg is a key=value string
+/// A joint kwarg is a key=value string. The returned key and value borrow from
+/// the original arg so nothing is allocated.
 ///
 /// # Arguments
 ///
 /// * `arg` - The string to expand if its a joint kwarg
-fn expander(arg: String) -> (String, Option<String>) {
-    // check if this is a joint arg
-    if arg.contains('=') {
-        // split into a tuple containing the key and the value
-        let (key, value) = arg.split_at(arg.find('=').unwrap());
-        (key.to_owned(), Some(value[1..].to_owned()))
-    } else {
+fn expander(arg: &str) -> (&str, Option<&str>) {
+    // split into a tuple containing the key and the value if this is a joint arg
+    match arg.split_once('=') {
+        Some((key, value)) => (key, Some(value)),
         // this isn't a joint arg
-        (arg, None)
+        None => (arg, None),
+    }
+}
+
+/// Get owned copies of some arg values
+///
+/// Values that are already owned are moved instead of copied.
+///
+/// # Arguments
+///
+/// * `values` - The values to get owned copies of
+fn owned_values(values: Cow<'_, [String]>) -> impl Iterator<Item = String> + '_ {
+    // split our values based on whether we need to copy them or not
+    let (borrowed, owned) = match values {
+        Cow::Borrowed(borrowed) => (borrowed, Vec::new()),
+        Cow::Owned(owned) => (&[][..], owned),
+    };
+    borrowed.iter().cloned().chain(owned)
+}
+
+/// Make sure a working directory we are about to create stays under a root directory
+///
+/// # Arguments
+///
+/// * `working_dir` - The working directory to create
+/// * `root` - The directory working directories must be created under
+fn contained_working_dir(working_dir: &Path, root: &Path) -> Result<(), Error> {
+    let escaped = || {
+        Error::new(format!(
+            "The working directory {} must be under {} to be created",
+            working_dir.display(),
+            root.display()
+        ))
+    };
+    // resolve any '..' segments before checking where this directory is
+    let cleaned = working_dir.clean();
+    if !cleaned.starts_with(root) {
+        return Err(escaped());
+    }
+    // make sure no existing links lead us outside of our root
+    std::fs::create_dir_all(root)?;
+    let root = root.canonicalize()?;
+    let linked_out = match cleaned.ancestors().find(|path| path.exists()) {
+        Some(existing) => !existing.canonicalize()?.starts_with(&root),
+        None => false,
+    };
+    if linked_out {
+        return Err(escaped());
+    }
+    Ok(())
+}
+
+/// Describe why a working directory is not an absolute path if it isn't one
+///
+/// # Arguments
+///
+/// * `working_dir` - The working directory to check
+fn relative_working_dir(working_dir: &Path) -> Option<String> {
+    // relative paths would depend on wherever the agent happened to be started
+    (!working_dir.is_absolute()).then(|| {
+        format!(
+            "The working directory {} must be an absolute path",
+            working_dir.display()
+        )
+    })
+}
+
+/// Build the process to spawn for a built command
+///
+/// # Arguments
+///
+/// * `cmd` - The built command to execute
+/// * `working_dir` - The directory to launch this command in if one was set
+pub fn spawn_config(cmd: &[String], working_dir: Option<&Path>) -> Command {
+    // build the comamnd to execute
+    let mut cmd_builder = Command::new(cmd[0].clone());
+    // if we have more then just one arg then add those
+    if cmd.len() > 1 {
+        // add any remaining args
+        cmd_builder.args(&cmd[1..]);
+    }
+    // launch this command in the requested directory if one was set
+    if let Some(working_dir) = working_dir {
+        cmd_builder.current_dir(working_dir);
     }
+    cmd_builder
 }
 
 /// A builder for commands in Thorium
-#[derive(Debug)]
-pub struct CmdBuilder {
+///
+/// The job's args are borrowed until the command is built so only the args that
+/// actually end up in the final command are copied.
+#[derive(Debug, Clone)]
+pub struct CmdBuilder<'a> {
     /// The job specified positional args
-    positionals: Vec<String>,
+    positionals: Vec<Cow<'a, str>>,
     /// The job specified keyword args
-    kwargs: GenericJobKwargs,
+    kwargs: BTreeMap<Cow<'a, str>, Cow<'a, [String]>>,
+    /// The job specified keyword args in the order they were added
+    ordered_kwargs: Vec<&'a (String, Vec<String>)>,
     /// The job specified switch args
-    switches: Vec<String>,
+    switches: &'a [String],
     /// The job specified options
-    opts: GenericJobOpts,
-    /// The original entry point for this worker
+    opts: Cow<'a, GenericJobOpts>,
+    /// The original entry point for this worker followed by its original command
     entrypoint: Vec<String>,
-    /// The original command for this worker
-    cmd: Vec<String>,
     /// The number of positional args to skip when overriding positionals
     allowable_positionals: u8,
 }
 
-impl CmdBuilder {
+impl<'a> CmdBuilder<'a> {
     /// Build a new command object
     ///
     /// # Arguments
@@ -65,37 +152,60 @@ impl CmdBuilder {
     /// * `job` - The job to build a command to execute from
     /// * `entrypoint` - The original entrypoint for our container
     /// * `cmd` - The original command for our container
-    pub fn new(image: &Image, job: &GenericJob, entrypoint: &[String], command: &[String]) -> Self {
-        // clone our kwargs so we can add generator info to them if needed
-        let mut kwargs = job.args.kwargs.clone();
-        // use our images entrypoint if we have an override set
-        let entrypoint = match &image.args.entrypoint {
-            Some(entrypoint) => entrypoint.clone(),
-            None => entrypoint.to_owned(),
-        };
-        // use our images cmd if we have an override set
-        let cmd = match &image.args.command {
-            Some(command) => command.clone(),
-            None => command.to_owned(),
-        };
+    pub fn new(
+        image: &'a Image,
+        job: &'a GenericJob,
+        entrypoint: &[String],
+        command: &[String],
+    ) -> Self {
+        // borrow our kwargs so we can add generator info to them if needed
+        let mut kwargs = job
+            .args
+            .kwargs
+            .iter()
+            .map(|(key, values)| {
+                (
+                    Cow::Borrowed(key.as_str()),
+                    Cow::Borrowed(values.as_slice()),
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        // use our images entrypoint and cmd if we have overrides set
+        let entrypoint = image.args.entrypoint.as_deref().unwrap_or(entrypoint);
+        let command = image.args.command.as_deref().unwrap_or(command);
+        // copy our entrypoint and cmd into a single list since they are always built together
+        let mut original = Vec::with_capacity(entrypoint.len() + command.len());
+        original.extend(entrypoint.iter().cloned());
+        original.extend(command.iter().cloned());
         // if this job is a generator then inject in the job and reaction id kwarg
         if job.generator {
-            kwargs.insert("--job".into(), vec![job.id.to_string()]);
-            kwargs.insert("--reaction".into(), vec![job.reaction.to_string()]);
+            kwargs.insert(Cow::Borrowed("--job"), Cow::Owned(vec![job.id.to_string()]));
+            kwargs.insert(
+                Cow::Borrowed("--reaction"),
+                Cow::Owned(vec![job.reaction.to_string()]),
+            );
         }
         // if this job has a reaction kwarg set then add that
         if let Some(reaction_kwarg) = &image.args.reaction {
             // inject and replace any existing reaction id args
-            kwargs.insert(reaction_kwarg.into(), vec![job.reaction.to_string()]);
+            kwargs.insert(
+                Cow::Borrowed(reaction_kwarg.as_str()),
+                Cow::Owned(vec![job.reaction.to_string()]),
+            );
         }
         // build our command object
         CmdBuilder {
-            positionals: job.args.positionals.clone(),
+            positionals: job
+                .args
+                .positionals
+                .iter()
+                .map(|arg| Cow::Borrowed(arg.as_str()))
+                .collect(),
             kwargs,
-            switches: job.args.switches.clone(),
-            opts: job.args.opts.clone(),
-            entrypoint,
-            cmd,
+            ordered_kwargs: job.args.ordered_kwargs.iter().collect(),
+            switches: &job.args.switches,
+            opts: Cow::Borrowed(&job.args.opts),
+            entrypoint: original,
             allowable_positionals: 1,
         }
     }
@@ -107,12 +217,12 @@ impl CmdBuilder {
             // since we have a kwarg arg add these args under that kwarg
             Some(kwarg) => {
                 // get an entry to this kwargs values
-                let entry = self.kwargs.entry(kwarg.clone()).or_default();
+                let entry = self.kwargs.entry(Cow::Owned(kwarg.clone())).or_default();
                 // add the values for this kwarg
-                entry.append(&mut args);
+                entry.to_mut().append(&mut args);
             }
             None => {
-                self.positionals.append(&mut args);
+                self.positionals.extend(args.into_iter().map(Cow::Owned));
             }
         }
     }
@@ -122,9 +232,12 @@ impl CmdBuilder {
         // determine if we should set an output arg or not
         match strategy {
             ArgStrategy::None => (),
-            ArgStrategy::Append => self.positionals.push(value.to_owned()),
+            ArgStrategy::Append => self.positionals.push(Cow::Owned(value.to_owned())),
             ArgStrategy::Kwarg(key) => {
-                self.kwargs.insert(key.to_owned(), vec![value.to_owned()]);
+                self.kwargs.insert(
+                    Cow::Owned(key.to_owned()),
+                    Cow::Owned(vec![value.to_owned()]),
+                );
             }
         }
     }
@@ -224,16 +337,26 @@ impl CmdBuilder {
         // if we have a repo kwarg set then add that
         if let Some(repo_kwarg) = &image.args.repo {
             // get an entry to this repo_kwargs values
-            let entry = self.kwargs.entry(repo_kwarg.clone()).or_default();
+            let entry = self
+                .kwargs
+                .entry(Cow::Owned(repo_kwarg.clone()))
+                .or_default();
             // add each repo url to our kwargs
-            entry.extend(repos.iter().map(|repo| repo.url.clone()));
+            entry
+                .to_mut()
+                .extend(repos.iter().map(|repo| repo.url.clone()));
         }
         // if we have a commit kwarg set then add that
         if let Some(commit_kwarg) = &image.args.commit {
             // get an entry to this commit_kwargs values
-            let entry = self.kwargs.entry(commit_kwarg.clone()).or_default();
+            let entry = self
+                .kwargs
+                .entry(Cow::Owned(commit_kwarg.clone()))
+                .or_default();
             // add each commit url to our kwargs
-            entry.extend(repos.iter().filter_map(|repo| repo.commitish.clone()));
+            entry
+                .to_mut()
+                .extend(repos.iter().filter_map(|repo| repo.commitish.clone()));
         }
         self
     }
@@ -273,8 +396,8 @@ impl CmdBuilder {
         match &settings.kwarg {
             KwargDependency::List(key) => {
                 // get an entry to insert our result args into so we can append to user passed args
-                let entry = self.kwargs.entry(key.to_owned()).or_default();
-                entry.append(&mut args);
+                let entry = self.kwargs.entry(Cow::Owned(key.to_owned())).or_default();
+                entry.to_mut().append(&mut args);
             }
             KwargDependency::Map(map) => {
                 // we have specific kwargs for each result
@@ -299,13 +422,13 @@ impl CmdBuilder {
                     }
                     // add the paths were found to the kwargs only if we found any
                     if !found_paths.is_empty() {
-                        let entry = self.kwargs.entry(key.to_owned()).or_default();
-                        entry.append(&mut found_paths);
+                        let entry = self.kwargs.entry(Cow::Owned(key.to_owned())).or_default();
+                        entry.to_mut().append(&mut found_paths);
                     }
                 }
             }
             KwargDependency::None => {
-                self.positionals.append(&mut args);
+                self.positionals.extend(args.into_iter().map(Cow::Owned));
             }
         }
         Ok(self)
@@ -431,7 +554,9 @@ impl CmdBuilder {
             return self;
         }
         // if we have an override command then just modify that
-        if let Some(override_cmd) = &mut self.opts.override_cmd {
+        if self.opts.override_cmd.is_some() {
+            // copy our options so we can modify our override command
+            let override_cmd = self.opts.to_mut().override_cmd.get_or_insert_default();
             // build the windows prepend command
             let mut prepended = vec!["C:\\Windows\\system32\\cmd.exe".to_owned(), "/C".to_owned()];
             // add our built commands back in
@@ -450,6 +575,42 @@ impl CmdBuilder {
         self
     }
 
+    /// Get the directory this job should be launched in
+    ///
+    /// The directory must be an absolute path and must exist unless this job asked
+    /// for it to be created. If no directory was set then [`None`] is returned and
+    /// the job is launched in the agent's working directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `create_root` - The directory working directories must be created under if restricted
+    pub fn working_dir(&self, create_root: Option<&Path>) -> Result<Option<PathBuf>, Error> {
+        // if no working dir was set then use the agent's working directory
+        let Some(working_dir) = &self.opts.working_dir else {
+            return Ok(None);
+        };
+        // make sure our working directory is an absolute path
+        if let Some(problem) = relative_working_dir(working_dir) {
+            return Err(Error::new(problem));
+        }
+        // create our working directory if it doesn't exist and we were asked to
+        if self.opts.create_working_dir {
+            // only create directories under our root if we have one
+            if let Some(root) = create_root {
+                contained_working_dir(working_dir, root)?;
+            }
+            std::fs::create_dir_all(working_dir)?;
+        }
+        // make sure our working directory exists
+        if !working_dir.is_dir() {
+            return Err(Error::new(format!(
+                "The working directory {} does not exist",
+                working_dir.display()
+            )));
+        }
+        Ok(Some(working_dir.clone()))
+    }
+
     /// Check if the source command is empty or only invokes a shell
     #[instrument(name = "Cmd::not_empty_or_just_shell", skip_all)]
     fn not_empty_or_just_shell(&self) -> Result<(), Error> {
@@ -488,6 +649,24 @@ impl CmdBuilder {
         }
     }
 
+    /// Take the values to override a kwarg with if this kwarg was set by the job
+    ///
+    /// Sorted kwargs take precedence over ordered kwargs with the same key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The kwarg to get override values for
+    fn take_kwarg(&mut self, key: &str) -> Option<Cow<'a, [String]>> {
+        // remove this kwarg from our ordered kwargs if it exists
+        let ordered = self
+            .ordered_kwargs
+            .iter()
+            .position(|(existing, _)| existing == key)
+            .map(|index| Cow::Borrowed(self.ordered_kwargs.remove(index).1.as_slice()));
+        // prefer our sorted kwargs if this key was set there too
+        self.kwargs.remove(key).or(ordered)
+    }
+
     /// Overlays kwargs from the job and source into the built command
     fn scan_args(&mut self, built: &mut Vec<String>) {
         // track if we are in a kwarg or not
@@ -495,30 +674,28 @@ impl CmdBuilder {
         // if we are currently wiping args we have replaced
         let mut wipe = false;
         // add all args and overlay any user specified kwargs
-        for arg in self.entrypoint.drain(0..) {
+        for arg in std::mem::take(&mut self.entrypoint) {
             // check if this is a key or a value
             if arg.starts_with('-') {
                 // expand this arg if its a joint arg
-                let (key, value) = expander(arg);
+                let (key, value) = expander(&arg);
                 // update our in kwarg tracker
                 in_kwarg = value.is_none();
-                // check if this is a kwarg that we want to replace
-                if self.kwargs.contains_key(&key) {
+                // check if this is a kwarg that we want to replace and get the values to override it with
+                if let Some(new_values) = self.take_kwarg(key) {
                     // this is a kwarg that we want to replace
                     // if this is a concatenated "<key>=<value>" kwarg then we don't need to set wipe
                     wipe = value.is_none();
-                    // get the values we want to override this with
-                    let new_values = self.kwargs.remove(&key).unwrap();
                     // for each of our values add our kwarg
                     // if there are none then we want to wipe this kwarg when its found
-                    for new_value in new_values {
+                    for new_value in owned_values(new_values) {
                         // if our old key/value was joined with an '=' then do that here as well
                         if value.is_some() {
                             // build out concatenated key/value and add it
                             built.push(format!("{key}={new_value}"));
                         } else {
                             // add our key
-                            built.push(key.clone());
+                            built.push(key.to_owned());
                             // override value with our own value
                             built.push(new_value);
                         }
@@ -533,17 +710,8 @@ impl CmdBuilder {
                     }
                     // reset wipe as this is a kwarg that we don't want to change
                     wipe = false;
-                    // readd our kwarg
-                    // if our old key/value was joined with an '=' then do that here as well
-                    match value {
-                        // rebuild our concatenated key/value and add it
-                        Some(value) => built.push(format!("{key}={value}")),
-                        // this was originally two seperate values so keep it that way
-                        None => {
-                            // add our keywarg/switch
-                            built.push(key.clone());
-                        }
-                    }
+                    // readd our kwarg as is since its key and value are unchanged
+                    built.push(arg);
                 }
             } else {
                 // if wipe is false then add this other arg
@@ -565,20 +733,32 @@ impl CmdBuilder {
         }
         // swap our kwargs btree map with an empty one
         let kwarg_map = std::mem::take(&mut self.kwargs);
+        // get any left over ordered kwargs that were not also set as sorted kwargs
+        let ordered_kwargs = std::mem::take(&mut self.ordered_kwargs)
+            .into_iter()
+            .filter(|(key, _)| !kwarg_map.contains_key(key.as_str()))
+            .map(|(key, values)| {
+                (
+                    Cow::Borrowed(key.as_str()),
+                    Cow::Borrowed(values.as_slice()),
+                )
+            })
+            .collect::<Vec<_>>();
         // append all left over custom kwargs args if any were set
-        for (key, values) in kwarg_map {
+        // sorted kwargs come first followed by ordered kwargs in the order they were added
+        for (key, values) in kwarg_map.into_iter().chain(ordered_kwargs) {
             // add our kwargs
-            for value in values {
+            for value in owned_values(values) {
                 // add our key
-                built.push(key.clone());
+                built.push(key.to_string());
                 // add our value
                 built.push(value);
             }
         }
         // append any switches that we still have
-        built.append(&mut self.switches);
+        built.extend_from_slice(self.switches);
         // append any positional args that we have
-        built.append(&mut self.positionals);
+        built.extend(self.positionals.drain(..).map(Cow::into_owned));
     }
 
     /// Build the final command to execute
@@ -595,8 +775,8 @@ impl CmdBuilder {
         isolated_result_files: Option<&String>,
     ) -> Result<Vec<String>, Error> {
         // if our command is overridden then just return that
-        if let Some(override_cmd) = self.opts.override_cmd {
-            return Ok(override_cmd);
+        if let Some(override_cmd) = &self.opts.override_cmd {
+            return Ok(override_cmd.clone());
         }
         // add our output arg at the end to make sure it comes last if its a positional
         match image.output_collection.handler {
@@ -617,17 +797,14 @@ impl CmdBuilder {
         let kwarg_capacity = self
             .kwargs
             .values()
-            .fold(0, |acc, vals| acc + (vals.len() * 2));
+            .map(|vals| vals.len())
+            .chain(self.ordered_kwargs.iter().map(|(_, vals)| vals.len()))
+            .fold(0, |acc, len| acc + (len * 2));
         // calculate how large our command vec will be
-        let capacity = self.entrypoint.len()
-            + self.cmd.len()
-            + kwarg_capacity
-            + self.switches.len()
-            + self.positionals.len();
+        let capacity =
+            self.entrypoint.len() + kwarg_capacity + self.switches.len() + self.positionals.len();
         // if we have a command then increment our allowed positionals to 2
         self.allowable_positionals += 1;
-        // add our cmd onto our entrypoint
-        self.entrypoint.append(&mut self.cmd);
         // throw an error if the src command is empty to avoid simply running the sample naively
         self.not_empty_or_just_shell()?;
         // instance a command with approximately enough space for our fully built command
@@ -637,87 +814,135 @@ impl CmdBuilder {
         self.scan_args(&mut cmd);
         Ok(cmd)
     }
+
+    /// Render the final command without consuming this builder
+    ///
+    /// This is useful for inspecting what would be executed when debugging an
+    /// image's arg strategies without actually spawning anything.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image we are building a command for
+    /// * `isolated_results` - The path write isolated results too
+    /// * `isolated_result_files` - The path write isolated result files too
+    pub fn dry_run(
+        &self,
+        image: &Image,
+        isolated_results: Option<&String>,
+        isolated_result_files: Option<&String>,
+    ) -> Result<Vec<String>, Error> {
+        // build a copy of this command so we can keep adding to the original
+        self.clone()
+            .build(image, isolated_results, isolated_result_files)
+    }
+}
+
+/// The value passed in place of anything the agent injects when validating a job's args
+const PLACEHOLDER: &str = "thorium-placeholder";
+
+/// Build a command for a job with placeholders for everything the agent would pass to it
+///
+/// Result dependencies are skipped since they are only passed in once they exist on disk.
+///
+/// # Arguments
+///
+/// * `image` - The image we are building a command for
+/// * `job` - The job to build a command for
+fn placeholder_builder<'a>(image: &'a Image, job: &'a GenericJob) -> CmdBuilder<'a> {
+    let deps = &image.dependencies;
+    // build a placeholder for each kind of dependency we can pass in
+    let placeholder = PathBuf::from(PLACEHOLDER);
+    let placeholders = std::slice::from_ref(&placeholder);
+    let repo = RepoDependency {
+        url: PLACEHOLDER.to_owned(),
+        commitish: Some(PLACEHOLDER.to_owned()),
+        kind: None,
+    };
+    // add our dependencies the same way the agent does
+    let mut builder = CmdBuilder::new(image, job, &[], &[])
+        .add_ephemeral(&[PLACEHOLDER.to_owned()], placeholders, &deps.ephemeral)
+        .add_samples(placeholders, &deps.samples)
+        .add_repos(image, &[repo], placeholders);
+    // only add the dependencies that are enabled for this image
+    if deps.tags.enabled {
+        builder = builder.add_tags(placeholders, &deps.tags);
+    }
+    if deps.children.enabled {
+        builder = builder.add_children(placeholders, &deps.children);
+    }
+    if deps.cache.enabled {
+        let cache = DownloadedCache {
+            generic: Some(placeholder.clone()),
+            ..DownloadedCache::default()
+        };
+        builder = builder.add_cache(&cache, &deps.cache);
+    }
+    // add our output args like we do when building our command
+    builder.add_arg_by_strategy(PLACEHOLDER, &image.args.output);
+    builder.add_arg_by_strategy(PLACEHOLDER, &image.args.output_files);
+    builder
+}
+
+/// Check if a job's args are compatible with an image by building its command
+///
+/// This runs [`Image::validate_args`] and then builds the job's command in memory with
+/// placeholders for anything the agent would pass in. Any other kwargs whose values the
+/// agent would replace or mix with its own are reported as well.
+///
+/// # Arguments
+///
+/// * `image` - The image to check this job's args against
+/// * `job` - The job whose args to check
+///
+/// # Errors
+///
+/// Returns a human readable description of each problem with this job's args.
+pub fn dry_run_args(image: &Image, job: &GenericJob) -> Result<(), Vec<String>> {
+    let mut problems = image.validate_args(&job.args).err().unwrap_or_default();
+    // an override command ignores any other args so there is nothing left to check
+    if job.args.opts.override_cmd.is_none() {
+        let builder = placeholder_builder(image, job);
+        // make sure the agent doesn't change the values for any of our other kwargs
+        for (key, values) in &job.args.kwargs {
+            // skip any kwargs we already found problems with
+            let quoted = format!("'{key}'");
+            if problems.iter().any(|problem| problem.contains(&quoted)) {
+                continue;
+            }
+            match builder.kwargs.get(key.as_str()) {
+                Some(built) if built[..] == values[..] => (),
+                Some(built) if built.starts_with(values) => problems.push(format!(
+                    "The kwarg {quoted} is used to pass this image's dependencies so its values would be mixed with them"
+                )),
+                _ => problems.push(format!(
+                    "The kwarg {quoted} is set by this image so its values would be replaced"
+                )),
+            }
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::prelude::*;
-    use std::collections::{HashMap, HashSet};
-    use thorium::models::{
-        ChildFilters, CommitishKinds, Dependencies, GenericJob, GenericJobArgs, Image, ImageArgs,
-        ImageVersion, JobStatus, OutputCollection, OutputDisplayType, RepoDependency, Resources,
-        ResultDependencySettings, SecurityContext,
-    };
+    use std::collections::HashSet;
+    use thorium::models::{GenericJob, GenericJobArgs, Image, ResultDependencySettings};
+    use thorium::test_utilities::fixtures::{ImageBuilder, JobBuilder};
     use uuid::Uuid;
 
     fn generate_job() -> GenericJob {
         // generate a test job with empty args
-        GenericJob {
-            reaction: Uuid::new_v4(),
-            id: Uuid::new_v4(),
-            group: "TestGroup".into(),
-            pipeline: "TestPipeline".into(),
-            stage: "TestStage".into(),
-            creator: "mcarson".into(),
-            args: GenericJobArgs::default(),
-            status: JobStatus::Running,
-            deadline: Utc::now(),
-            parent: None,
-            generator: false,
-            samples: vec!["sample1".into(), "sample2".into()],
-            ephemeral: vec!["file.txt".into(), "other.txt".into()],
-            parent_ephemeral: HashMap::default(),
-            repos: vec![
-                RepoDependency {
-                    url: "github.com/curl/curl".into(),
-                    commitish: Some("main".into()),
-                    kind: Some(CommitishKinds::Branch),
-                },
-                RepoDependency {
-                    url: "github.com/notcurl/notcurl".into(),
-                    commitish: Some("main".into()),
-                    kind: Some(CommitishKinds::Branch),
-                },
-            ],
-            trigger_depth: None,
-        }
+        JobBuilder::default().build()
     }
 
     fn generate_image() -> Image {
         // generate a test image
-        Image {
-            group: "TestGroup".into(),
-            name: "TestImage".into(),
-            version: Some(ImageVersion::SemVer(
-                semver::Version::parse("1.0.0").unwrap(),
-            )),
-            creator: "mcarson".into(),
-            image: Some("alpine:latest".into()),
-            scaler: thorium::models::ImageScaler::K8s,
-            lifetime: None,
-            timeout: None,
-            resources: Resources::default(),
-            spawn_limit: thorium::models::SpawnLimits::Unlimited,
-            env: HashMap::default(),
-            args: ImageArgs::default(),
-            runtime: 600.0,
-            volumes: Vec::default(),
-            modifiers: None,
-            description: None,
-            security_context: SecurityContext::default(),
-            used_by: Vec::default(),
-            collect_logs: true,
-            generator: false,
-            dependencies: Dependencies::default(),
-            display_type: OutputDisplayType::default(),
-            output_collection: OutputCollection::default(),
-            child_filters: ChildFilters::default(),
-            clean_up: None,
-            kvm: None,
-            bans: HashMap::default(),
-            network_policies: HashSet::default(),
-        }
+        ImageBuilder::default().build()
     }
 
     /// Initializes a Vec of String
@@ -1612,6 +1837,49 @@ mod tests {
         assert_eq!(built_set, args_set);
     }
 
+    /// Test that ordered kwargs keep their order through serialization and into the command
+    #[tokio::test]
+    async fn ordered_kwargs() {
+        // generate an image
+        let image = generate_image();
+        // generate a job
+        let mut job = generate_job();
+        // build stage args with ordered kwargs that are not sorted by key
+        let args = GenericJobArgs::default()
+            .ordered_kwarg("--zebra", vec!["1"])
+            .ordered_kwarg("--apple", vec!["2"])
+            .ordered_kwarg("--mango", vec!["3", "4"]);
+        // round trip our args through serialization like the API and agent do
+        let serialized = serde_json::to_string(&args).unwrap();
+        let deserialized: GenericJobArgs = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(args, deserialized);
+        job.args = deserialized;
+        // build the command to execute
+        let cmd = CmdBuilder::new(
+            &image,
+            &job,
+            slice_string!["/usr/bin/python3"],
+            slice_string!["corn.py", "--apple=0"],
+        )
+        .build(&image, None, None)
+        .unwrap();
+        // validate our overlayed command kept the order our kwargs were added in
+        assert_eq!(
+            cmd,
+            vec_string!(
+                "/usr/bin/python3",
+                "corn.py",
+                "--apple=2",
+                "--zebra",
+                "1",
+                "--mango",
+                "3",
+                "--mango",
+                "4"
+            )
+        );
+    }
+
     /// Test a job with switch overlays
     #[tokio::test]
     async fn switches() {
@@ -2083,4 +2351,270 @@ mod tests {
             )
         );
     }
+
+    /// Test that a dry run renders the same command as a real build
+    #[tokio::test]
+    async fn dry_run() {
+        // generate an image
+        let image = generate_image();
+        // generate a job
+        let mut job = generate_job();
+        // build stage args with positionals that override the original ones
+        job.args = job.args.positionals(vec!["pos1"]);
+        job.args.opts.override_positionals = true;
+        // build paths to our samples
+        let sample_paths = vec![PathBuf::from("/tmp/sample1")];
+        // build our command builder
+        let builder = CmdBuilder::new(
+            &image,
+            &job,
+            slice_string!["/usr/bin/python3"],
+            slice_string!["corn.py", "old1"],
+        )
+        .add_samples(&sample_paths, &image.dependencies.samples);
+        // render our command without consuming the builder
+        let rendered = builder.dry_run(&image, None, None).unwrap();
+        // validate our rendered command
+        assert_eq!(
+            rendered,
+            vec_string!["/usr/bin/python3", "corn.py", "pos1", "/tmp/sample1"]
+        );
+        // a dry run should be repeatable and match the real build
+        assert_eq!(rendered, builder.dry_run(&image, None, None).unwrap());
+        assert_eq!(rendered, builder.build(&image, None, None).unwrap());
+    }
+
+    /// Test that a job's working directory flows through to the spawned process
+    #[tokio::test]
+    async fn working_dir() {
+        // generate an image
+        let image = generate_image();
+        // generate a job
+        let mut job = generate_job();
+        // jobs without a working dir are launched in the agent's working directory
+        let builder = CmdBuilder::new(
+            &image,
+            &job,
+            slice_string!["/usr/bin/python3"],
+            slice_string!["corn.py"],
+        );
+        assert_eq!(builder.working_dir(None).unwrap(), None);
+        let cmd = builder.build(&image, None, None).unwrap();
+        assert_eq!(spawn_config(&cmd, None).as_std().get_current_dir(), None);
+        // launch this job in an existing directory
+        let existing = std::env::temp_dir();
+        job.args.opts = GenericJobOpts::default().working_dir(&existing);
+        let builder = CmdBuilder::new(
+            &image,
+            &job,
+            slice_string!["/usr/bin/python3"],
+            slice_string!["corn.py"],
+        );
+        let working_dir = builder.working_dir(None).unwrap();
+        assert_eq!(working_dir.as_ref(), Some(&existing));
+        let cmd = builder.build(&image, None, None).unwrap();
+        let spawn = spawn_config(&cmd, working_dir.as_deref());
+        assert_eq!(spawn.as_std().get_current_dir(), Some(existing.as_path()));
+        assert_eq!(spawn.as_std().get_program(), "/usr/bin/python3");
+        // relative working directories are rejected
+        job.args.opts = GenericJobOpts::default().working_dir("fields/west-3");
+        let builder = CmdBuilder::new(&image, &job, slice_string!["/usr/bin/python3"], &[]);
+        assert!(builder.working_dir(None).is_err());
+        // working directories that don't exist are rejected unless they should be created
+        let missing = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        job.args.opts = GenericJobOpts::default().working_dir(&missing);
+        let builder = CmdBuilder::new(&image, &job, slice_string!["/usr/bin/python3"], &[]);
+        assert!(builder.working_dir(None).is_err());
+        job.args.opts = job.args.opts.create_working_dir();
+        let builder = CmdBuilder::new(&image, &job, slice_string!["/usr/bin/python3"], &[]);
+        assert_eq!(builder.working_dir(None).unwrap(), Some(missing.clone()));
+        assert!(missing.is_dir());
+        std::fs::remove_dir(&missing).unwrap();
+        // restricted working directories can only be created under their root
+        let root = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let nested = root.join("fields").join("west-3");
+        job.args.opts = GenericJobOpts::default()
+            .working_dir(&nested)
+            .create_working_dir();
+        let builder = CmdBuilder::new(&image, &job, slice_string!["/usr/bin/python3"], &[]);
+        assert_eq!(
+            builder.working_dir(Some(&root)).unwrap(),
+            Some(nested.clone())
+        );
+        assert!(nested.is_dir());
+        // working directories can't escape their root
+        let escaped = root.join("..").join(Uuid::new_v4().to_string());
+        job.args.opts = GenericJobOpts::default()
+            .working_dir(&escaped)
+            .create_working_dir();
+        let builder = CmdBuilder::new(&image, &job, slice_string!["/usr/bin/python3"], &[]);
+        assert!(builder.working_dir(Some(&root)).is_err());
+        assert!(!escaped.clean().exists());
+        // or follow links out of their root
+        #[cfg(unix)]
+        {
+            let outside = std::env::temp_dir().join(Uuid::new_v4().to_string());
+            std::fs::create_dir(&outside).unwrap();
+            std::os::unix::fs::symlink(&outside, root.join("barn")).unwrap();
+            job.args.opts = GenericJobOpts::default()
+                .working_dir(root.join("barn").join("loft"))
+                .create_working_dir();
+            let builder = CmdBuilder::new(&image, &job, slice_string!["/usr/bin/python3"], &[]);
+            assert!(builder.working_dir(Some(&root)).is_err());
+            assert!(!outside.join("loft").exists());
+            std::fs::remove_dir(&outside).unwrap();
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// Test that joint kwargs we don't override are passed through exactly as they were
+    #[tokio::test]
+    async fn joint_kwargs_unchanged() {
+        // generate an image with joint kwargs whose values contain or are missing values
+        let mut image = generate_image();
+        image.args.command = Some(vec_string![
+            "corn.py",
+            "--filter=size=large",
+            "--empty=",
+            "--field=north"
+        ]);
+        // generate a job that only overrides one of these kwargs
+        let mut job = generate_job();
+        job.args = job.args.kwarg("--field", vec!["south=west"]);
+        let cmd = CmdBuilder::new(&image, &job, slice_string!["/usr/bin/python3"], &[])
+            .build(&image, None, None)
+            .unwrap();
+        assert_eq!(
+            cmd,
+            vec_string![
+                "/usr/bin/python3",
+                "corn.py",
+                "--filter=size=large",
+                "--empty=",
+                "--field=south=west"
+            ]
+        );
+    }
+
+    /// Test that appending dependencies to a job's kwargs doesn't change the job's args
+    #[tokio::test]
+    async fn borrowed_kwargs_appended() {
+        // generate an image that passes samples in with the same kwarg as our job
+        let mut image = generate_image();
+        image.dependencies.samples.kwarg = Some("--sample".to_owned());
+        // generate a job that already sets this kwarg
+        let mut job = generate_job();
+        job.args = job
+            .args
+            .kwarg("--sample", vec!["/tmp/user"])
+            .ordered_kwarg("--rows", vec!["12"])
+            .switch("--verbose")
+            .positionals(vec!["pos1"]);
+        let original = job.args.clone();
+        // build paths to our samples
+        let sample_paths = vec![PathBuf::from("/tmp/sample1")];
+        let builder = CmdBuilder::new(
+            &image,
+            &job,
+            slice_string!["/usr/bin/python3"],
+            slice_string!["corn.py"],
+        )
+        .add_samples(&sample_paths, &image.dependencies.samples);
+        // a dry run and a real build should render the same command
+        let dry_run = builder.dry_run(&image, None, None).unwrap();
+        let cmd = builder.build(&image, None, None).unwrap();
+        assert_eq!(dry_run, cmd);
+        assert_eq!(
+            cmd,
+            vec_string![
+                "/usr/bin/python3",
+                "corn.py",
+                "--sample",
+                "/tmp/user",
+                "--sample",
+                "/tmp/sample1",
+                "--rows",
+                "12",
+                "--verbose",
+                "pos1"
+            ]
+        );
+        // our job's args should be untouched
+        assert_eq!(job.args, original);
+    }
+
+    /// Test that validating args catches kwargs that are dropped when overriding kwargs
+    #[tokio::test]
+    async fn validate_args_missing_required_kwarg() {
+        // generate an image with kwargs in its command
+        let mut image = generate_image();
+        image.args.command = Some(vec_string!["corn.py", "--field", "north", "--crop=corn"]);
+        // generate a job that overrides kwargs without setting all of the image's kwargs
+        let mut job = generate_job();
+        job.args = job.args.kwarg("--field", vec!["south"]);
+        job.args.opts.override_kwargs = true;
+        // make sure the missing kwarg is reported
+        let problems = image.validate_args(&job.args).unwrap_err();
+        assert_eq!(dry_run_args(&image, &job).unwrap_err(), problems);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("'--crop'"), "{problems:?}");
+        // make sure our command really drops this kwarg
+        let cmd = CmdBuilder::new(&image, &job, slice_string!["/usr/bin/python3"], &[])
+            .build(&image, None, None)
+            .unwrap();
+        assert_eq!(
+            cmd,
+            vec_string!["/usr/bin/python3", "corn.py", "--field", "south"]
+        );
+        // setting the missing kwarg makes these args valid
+        job.args = job.args.kwarg("--crop", vec!["soy"]);
+        assert!(image.validate_args(&job.args).is_ok());
+        assert!(dry_run_args(&image, &job).is_ok());
+        let cmd = CmdBuilder::new(&image, &job, slice_string!["/usr/bin/python3"], &[])
+            .build(&image, None, None)
+            .unwrap();
+        assert_eq!(
+            cmd,
+            vec_string![
+                "/usr/bin/python3",
+                "corn.py",
+                "--field",
+                "south",
+                "--crop=soy"
+            ]
+        );
+    }
+
+    /// Test that validating args catches args that conflict with overrides or the image
+    #[tokio::test]
+    async fn validate_args_conflicting_override() {
+        // generate an image that passes its output path as a kwarg
+        let mut image = generate_image();
+        image.args.output = ArgStrategy::Kwarg("-o".to_owned());
+        // generate a job that sets kwargs along with an override command
+        let mut job = generate_job();
+        job.args = job.args.kwarg("--field", vec!["north"]);
+        job.args.opts.override_kwargs = true;
+        job.args.opts.override_cmd = Some(vec_string!["harvest.sh"]);
+        // make sure both conflicts are reported
+        let problems = image.validate_args(&job.args).unwrap_err();
+        assert_eq!(dry_run_args(&image, &job).unwrap_err(), problems);
+        assert_eq!(problems.len(), 2, "{problems:?}");
+        // make sure our command really ignores these args
+        let cmd = CmdBuilder::new(&image, &job, slice_string!["/usr/bin/python3"], &[])
+            .build(&image, None, None)
+            .unwrap();
+        assert_eq!(cmd, vec_string!["harvest.sh"]);
+        // setting our image's output kwarg is reported since it would be replaced
+        job.args.opts = GenericJobOpts::default();
+        job.args = job.args.kwarg("-o", vec!["/tmp/mine"]);
+        let problems = image.validate_args(&job.args).unwrap_err();
+        assert_eq!(dry_run_args(&image, &job).unwrap_err(), problems);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("'-o'"), "{problems:?}");
+        let cmd = CmdBuilder::new(&image, &job, slice_string!["/usr/bin/python3"], &[])
+            .build(&image, None, None)
+            .unwrap();
+        assert!(!cmd.contains(&"/tmp/mine".to_owned()));
+    }
 }
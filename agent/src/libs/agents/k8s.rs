@@ -5,10 +5,9 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use thorium::models::{GenericJob, Image};
 use thorium::{Error, Thorium};
-use tokio::process::Command;
 use tracing::{Level, event, instrument};
 
-use super::cmd::CmdBuilder;
+use super::cmd::{self, CmdBuilder};
 use super::{AgentExecutor, InFlight, setup};
 use crate::libs::children::{self, Children};
 use crate::libs::{DownloadedCache, RawResults, TagBundle, Target, results, tags};
@@ -41,6 +40,8 @@ pub struct K8s {
     cache: DownloadedCache,
     /// whether this is a windows container or not
     pub windows: bool,
+    /// How many job dependencies to download at once
+    download_concurrency: usize,
 }
 
 impl K8s {
@@ -68,6 +69,7 @@ impl K8s {
             children: Vec::default(),
             cache: DownloadedCache::default(),
             windows: false,
+            download_concurrency: target.download_concurrency,
         };
         Ok(k8s)
     }
@@ -102,6 +104,7 @@ impl K8s {
             children: Vec::default(),
             cache: DownloadedCache::default(),
             windows: true,
+            download_concurrency: target.download_concurrency,
         };
         Ok(k8s)
     }
@@ -137,6 +140,7 @@ impl K8s {
             children: Vec::default(),
             cache: DownloadedCache::default(),
             windows: true,
+            download_concurrency: target.download_concurrency,
         };
         Ok(k8s)
     }
@@ -241,6 +245,7 @@ impl AgentExecutor for K8s {
             image,
             job,
             &image.dependencies.samples.location,
+            self.download_concurrency,
             &mut self.logs,
         )
         .await?;
@@ -266,6 +271,7 @@ impl AgentExecutor for K8s {
             image,
             job,
             &image.dependencies.repos.location,
+            self.download_concurrency,
             commits,
             &mut self.logs,
         )
@@ -326,7 +332,7 @@ impl AgentExecutor for K8s {
         // get a ref to our dependency settings
         let dep_conf = &image.dependencies;
         // build the command this worker should execute
-        let cmd = CmdBuilder::new(image, job, &self.entrypoint, &self.cmd)
+        let builder = CmdBuilder::new(image, job, &self.entrypoint, &self.cmd)
             .add_ephemeral(&job.ephemeral, &self.ephemerals, &dep_conf.ephemeral)
             .add_samples(&self.samples, &dep_conf.samples)
             .add_repos(image, &job.repos, &self.repos)
@@ -341,8 +347,10 @@ impl AgentExecutor for K8s {
             .add_children(&self.children, &dep_conf.children)
             .add_cache(&self.cache, &dep_conf.cache)
             // if this is a windows job then change our command to run in windows
-            .windows(self.windows)
-            .build(image, None, None)?;
+            .windows(self.windows);
+        // get the directory to launch this job in since each pod runs a single job
+        let working_dir = builder.working_dir(None)?;
+        let cmd = builder.build(image, None, None)?;
         // cast our command to a str
         let built_str = cmd.join(" ");
         // log our built command
@@ -350,13 +358,8 @@ impl AgentExecutor for K8s {
         event!(Level::INFO, cmd = built_str);
         // create a file to buffer our logs in
         let log_file = std::fs::File::create(log_path)?;
-        // build the comamnd to execute
-        let mut cmd_builder = Command::new(cmd[0].clone());
-        // if we have more then just one arg then add those
-        if cmd.len() > 1 {
-            // add any remaining args
-            cmd_builder.args(&cmd[1..]);
-        }
+        // build the comamnd to execute in our working directory
+        let mut cmd_builder = cmd::spawn_config(&cmd, working_dir.as_deref());
         // setup our stdout/stderr
         cmd_builder.stdout(log_file.try_clone()?);
         cmd_builder.stderr(log_file);
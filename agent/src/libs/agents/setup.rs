@@ -10,7 +10,7 @@ use thorium::Thorium;
 use thorium::client::ResultsClient;
 use thorium::models::{
     DependencyPassStrategy, FileDownloadOpts, FileNamingStrategy, GenericJob, Image, ReactionCache,
-    RepoDownloadOpts, ResultGetParams,
+    RepoDependency, RepoDownloadOpts, ResultGetParams,
 };
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
@@ -308,6 +308,137 @@ pub async fn build_sample_path(
     }
 }
 
+/// Make sure a downloaded sample was not corrupted or truncated
+///
+/// # Arguments
+///
+/// * `path` - The path the sample was downloaded to
+/// * `sha256` - The sha256 the sample is expected to have
+#[instrument(name = "setup::verify_sample", skip(path), err(Debug))]
+async fn verify_sample(path: &Path, sha256: &str) -> Result<(), Error> {
+    // hash the sample we downloaded
+    let downloaded = thorium::utils::helpers::sha256_file(path).await?;
+    // make sure it matches the hash we expected
+    if !downloaded.eq_ignore_ascii_case(sha256) {
+        return Err(Error::new(format!(
+            "Downloaded sample {} is corrupt: expected sha256 {sha256} but got {downloaded}",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Download a list of dependencies in parallel while keeping them in the order they were requested
+///
+/// The paths passed to our tools must be in a stable order so our results are sorted by the
+/// order their futures were submitted in after they all complete.
+///
+/// # Arguments
+///
+/// * `futs` - The futures downloading each dependency
+/// * `concurrency` - The max number of dependencies to download at once
+pub async fn download_ordered<T, F>(
+    futs: impl IntoIterator<Item = F>,
+    concurrency: usize,
+) -> Result<Vec<T>, Error>
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    // tag each download with its position so we can restore our order later
+    let tagged = futs
+        .into_iter()
+        .enumerate()
+        .map(|(index, fut)| async move { fut.await.map(|downloaded| (index, downloaded)) });
+    // download our dependencies in parallel
+    let mut downloaded = stream::iter(tagged)
+        .buffer_unordered(concurrency.max(1))
+        .collect::<Vec<Result<(usize, T), Error>>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<(usize, T)>, Error>>()?;
+    // put our downloaded dependencies back in the order they were requested in
+    downloaded.sort_unstable_by_key(|(index, _)| *index);
+    Ok(downloaded
+        .into_iter()
+        .map(|(_, downloaded)| downloaded)
+        .collect())
+}
+
+/// Download a single sample from Thorium
+///
+/// # Arguments
+///
+/// * `thorium` - A client for Thorium
+/// * `image` - The image our job is based on
+/// * `target` - The target folder to write this sample too
+/// * `sha256` - The sha256 of the sample to download
+/// * `logs` - The channel to use when sending logs to Thorium
+#[instrument(
+    name = "setup::download_sample_helper",
+    skip(thorium, image, target, logs),
+    err(Debug)
+)]
+async fn download_sample_helper(
+    thorium: &Thorium,
+    image: &Image,
+    target: &Path,
+    sha256: &str,
+    mut logs: Sender<String>,
+) -> Result<PathBuf, Error> {
+    // keep track of how many times we have tried to download this sample
+    let mut attempts = 0;
+    // build the options for downloading this file
+    let mut opts = FileDownloadOpts::default().uncart();
+    // build the path to download our files too
+    let dl_target = build_sample_path(thorium, image, target, sha256, &mut logs).await?;
+    // retry this sample until it works or we have tried 3 times
+    loop {
+        // download and uncart this file to disk
+        let dl_attempt = thorium.files.download(sha256, &dl_target, &mut opts).await;
+        // if this download ran into an IO or 500 error then try again
+        match dl_attempt {
+            // this download worked so make sure it wasn't corrupted if verification is enabled
+            Ok(_) if image.dependencies.samples.verify => {
+                if let Err(error) = verify_sample(&dl_target, sha256).await {
+                    // log that this sample was corrupted
+                    log!(logs, "{}", error.msg().unwrap_or_default());
+                    // delete this corrupted file so it can't be used
+                    purge!(dl_target);
+                    return Err(error);
+                }
+                break;
+            }
+            // this download worked so continue
+            Ok(_) => break,
+            // An error occured check if we should retry or fail out this job
+            Err(error) => {
+                // increment our attempt count
+                attempts += 1;
+                // if we have made three attempts then fail this job
+                if attempts >= 3 {
+                    return Err(error);
+                }
+                // check what kind of error this was
+                match error {
+                    Error::IO(error) => {
+                        // log that this download failed
+                        log!(logs, "Downloading {sha256} failed with {error:?}");
+                    }
+                    Error::Thorium { code, msg, .. } => {
+                        // log that this download failed
+                        log!(logs, "Downloading {sha256} failed with {code}: {msg:?}");
+                    }
+                    // treat all other errors as fatal
+                    error => return Err(error),
+                }
+            }
+        }
+        // delete this incorrectly downloaded file
+        purge!(dl_target);
+    }
+    Ok(dl_target)
+}
+
 /// Downloads any requested samples or ephemeral files from Thorium
 ///
 /// # Arguments
@@ -316,6 +447,7 @@ pub async fn build_sample_path(
 /// * `image` - The image our job is based on
 /// * `job` - The job we are downloading samples for
 /// * `target` - The target folder to write these samples too
+/// * `concurrency` - The max number of samples to download at once
 /// * `logs` - The channel to use when sending logs to Thorium
 #[instrument(name = "setup::download_samples", skip_all, err(Debug))]
 pub async fn download_samples<P: AsRef<Path>>(
@@ -323,59 +455,21 @@ pub async fn download_samples<P: AsRef<Path>>(
     image: &Image,
     job: &GenericJob,
     target: P,
+    concurrency: usize,
     logs: &mut Sender<String>,
 ) -> Result<Vec<PathBuf>, Error> {
     // build the path to save these samples too
-    let target = target.as_ref().to_path_buf();
-    // create a list to the paths to our downloaded samples
-    let mut samples = Vec::with_capacity(job.samples.len());
-    // build the options for downloading this file
-    let mut opts = FileDownloadOpts::default().uncart();
-    // crawl over any samples and try to download them
-    for sha256 in &job.samples {
-        // keep track of how many times we have tried to download this sample
-        let mut attempts = 0;
-        // build the path to download our files too
-        let dl_target = build_sample_path(thorium, image, &target, sha256, logs).await?;
-        // retry this sample until it works or we have tried 3 times
-        loop {
-            // download and uncart this file to disk
-            let dl_attempt = thorium.files.download(sha256, &dl_target, &mut opts).await;
-            // if this download ran into an IO or 500 error then try again
-            match dl_attempt {
-                // this download worked so continue
-                Ok(_) => break,
-                // An error occured check if we should retry or fail out this job
-                Err(error) => {
-                    // increment our attempt count
-                    attempts += 1;
-                    // if we have made three attempts then fail this job
-                    if attempts >= 3 {
-                        return Err(error);
-                    }
-                    // check what kind of error this was
-                    match error {
-                        Error::IO(error) => {
-                            // log that this download failed
-                            log!(logs, "Downloading {sha256} failed with {error:?}");
-                        }
-                        Error::Thorium { code, msg } => {
-                            // log that this download failed
-                            log!(logs, "Downloading {sha256} failed with {code}: {msg:?}");
-                        }
-                        // treat all other errors as fatal
-                        error => return Err(error),
-                    }
-                }
-            }
-            // delete this incorrectly downloaded file
-            purge!(dl_target);
-        }
-        // only pass in downloaded samples if its enabled
-        if image.dependencies.samples.strategy != DependencyPassStrategy::Disabled {
-            // add this downloaded sample to our list
-            samples.push(dl_target);
-        }
+    let target = target.as_ref();
+    // build a future for downloading each of our samples
+    let futs = job
+        .samples
+        .iter()
+        .map(|sha256| download_sample_helper(thorium, image, target, sha256, logs.clone()));
+    // download our samples in parallel
+    let samples = download_ordered(futs, concurrency).await?;
+    // only pass in downloaded samples if its enabled
+    if image.dependencies.samples.strategy == DependencyPassStrategy::Disabled {
+        return Ok(Vec::default());
     }
     Ok(samples)
 }
@@ -494,6 +588,44 @@ pub async fn download_parent_ephemeral<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Download and unpack a single repo from Thorium
+///
+/// # Arguments
+///
+/// * `thorium` - A client for Thorium
+/// * `repo` - The repo to download
+/// * `target` - The target folder to write this repo too
+/// * `logs` - The channel to use when sending logs to Thorium
+#[instrument(name = "setup::download_repo_helper", skip_all, err(Debug))]
+async fn download_repo_helper<'a>(
+    thorium: &Thorium,
+    repo: &'a RepoDependency,
+    target: &Path,
+    mut logs: Sender<String>,
+) -> Result<(&'a str, String, PathBuf), Error> {
+    // log that we are downloading this repo
+    event!(Level::INFO, repo = repo.url);
+    log!(logs, "Downloading repo {}", repo.url);
+    // build our download options
+    let mut opts = RepoDownloadOpts::default();
+    // if we have a commitish then set that
+    if let Some(commitish) = &repo.commitish {
+        opts.commitish = Some(commitish.clone());
+    }
+    // set our commitish kind if it exists
+    if let Some(kind) = repo.kind {
+        opts.kinds.push(kind);
+    }
+    // download and unpack this repo to disk
+    let untarred = thorium
+        .repos
+        .download_unpack(&repo.url, &opts, target)
+        .await?;
+    // get this repos commit
+    let commit = untarred.commit()?;
+    Ok((&repo.url, commit, untarred.path))
+}
+
 /// Downloads any requested repos from Thorium
 ///
 /// # Arguments
@@ -503,6 +635,7 @@ pub async fn download_parent_ephemeral<P: AsRef<Path>>(
 /// * `job` - The job we are downloading repos for
 /// * `target` - The target folder to write these repos too
 /// * `commits` - The commit that each repo is checked out too
+/// * `concurrency` - The max number of repos to download at once
 /// * `logs` - The channel to use when sending logs to Thorium
 #[instrument(name = "setup::download_repos", skip_all, err(Debug))]
 pub async fn download_repos<P: AsRef<Path>>(
@@ -511,38 +644,28 @@ pub async fn download_repos<P: AsRef<Path>>(
     job: &GenericJob,
     target: P,
     commits: &mut HashMap<String, String>,
+    concurrency: usize,
     logs: &mut Sender<String>,
 ) -> Result<Vec<PathBuf>, Error> {
     // build the path to save these repos too
-    let target = target.as_ref().to_path_buf();
+    let target = target.as_ref();
+    // build a future for downloading each of our repos
+    let futs = job
+        .repos
+        .iter()
+        .map(|repo| download_repo_helper(thorium, repo, target, logs.clone()));
+    // download our repos in parallel
+    let downloaded = download_ordered(futs, concurrency).await?;
     // create a list to the paths to our downloaded repos
-    let mut repos = Vec::with_capacity(job.repos.len());
-    // crawl over any samples and try to download them
-    for repo in &job.repos {
-        // log that we are downloading this repo
-        event!(Level::INFO, repo = repo.url);
-        log!(logs, "Downloading repo {}", repo.url);
-        // build our download options
-        let mut opts = RepoDownloadOpts::default();
-        // if we have a commitish then set that
-        if let Some(commitish) = &repo.commitish {
-            opts.commitish = Some(commitish.clone());
-        }
-        // set our commitish kind if it exists
-        if let Some(kind) = repo.kind {
-            opts.kinds.push(kind);
-        }
-        // download and unpack this repo to disk
-        let untarred = thorium
-            .repos
-            .download_unpack(&repo.url, &opts, &target)
-            .await?;
-        // get this repos commit
-        commits.insert(repo.url.clone(), untarred.commit()?);
-        // only pass in downloaded parent ephemeral files if its enabled
-        if image.dependencies.repos.strategy != DependencyPassStrategy::Disabled {
-            repos.push(untarred.path);
-        }
+    let mut repos = Vec::with_capacity(downloaded.len());
+    // track the commit each repo was checked out too
+    for (url, commit, path) in downloaded {
+        commits.insert(url.to_owned(), commit);
+        repos.push(path);
+    }
+    // only pass in downloaded repos if its enabled
+    if image.dependencies.repos.strategy == DependencyPassStrategy::Disabled {
+        return Ok(Vec::default());
     }
     Ok(repos)
 }
@@ -934,3 +1057,60 @@ pub async fn download_children<P: Into<PathBuf>>(
     }
     Ok(downloaded)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{download_ordered, verify_sample};
+    use std::time::{Duration, Instant};
+    use thorium::Error;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn verify_corrupt_sample() {
+        // write a sample to a temp path
+        let path = std::env::temp_dir().join(format!("thorium-verify-{}", Uuid::new_v4()));
+        tokio::fs::write(&path, b"corn").await.unwrap();
+        // get the real hash of our sample
+        let sha256 = thorium::utils::helpers::sha256_file(&path).await.unwrap();
+        // an intact sample should verify
+        verify_sample(&path, &sha256).await.unwrap();
+        // corrupt our sample by truncating it
+        tokio::fs::write(&path, b"co").await.unwrap();
+        // our corrupted sample should fail verification with a clear message
+        let error = verify_sample(&path, &sha256).await.unwrap_err();
+        let msg = error.msg().unwrap();
+        assert!(msg.contains("is corrupt"), "{msg}");
+        assert!(msg.contains(&format!("expected sha256 {sha256}")), "{msg}");
+        // clean up our sample
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn download_ordered_parallel() {
+        // build downloads that complete in the reverse order they were requested in
+        let futs = (0..4u64).map(|i| async move {
+            tokio::time::sleep(Duration::from_millis(200 - i * 50)).await;
+            Ok::<u64, Error>(i)
+        });
+        let start = Instant::now();
+        let downloaded = download_ordered(futs, 4).await.unwrap();
+        // downloading these serially would take 500ms
+        assert!(start.elapsed() < Duration::from_millis(400));
+        // our downloads should still be in the order they were requested in
+        assert_eq!(downloaded, vec![0, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn download_ordered_error() {
+        // build downloads where one of them fails
+        let futs = (0..4u64).map(|i| async move {
+            match i {
+                2 => Err(Error::new("download failed")),
+                _ => Ok(i),
+            }
+        });
+        // a single failed download should fail the whole set
+        let error = download_ordered(futs, 2).await.unwrap_err();
+        assert_eq!(error.msg().unwrap(), "download failed");
+    }
+}
@@ -4,16 +4,16 @@ use async_walkdir::WalkDir as AsyncWalkDir;
 use crossbeam::channel::Sender;
 use futures::stream::{self, StreamExt};
 use itertools::Itertools;
-use regex::Regex;
 use reqwest::StatusCode;
 use serde::Deserialize;
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::net::IpAddr;
 use std::path::{Path, PathBuf};
 use std::sync::{LazyLock, Mutex};
 use thorium::models::{
-    CarvedOrigin, ChildFilters, FileSystemEntityBuilder, GenericJob, Image, OriginRequest,
-    PcapNetworkProtocol, RepoDependency, SampleRequest, SampleSubmissionResponse,
+    CarvedOrigin, ChildFilterMatcher, ChildFilters, FileSystemEntityBuilder, GenericJob, Image,
+    OriginRequest, PcapNetworkProtocol, RepoDependency, SampleRequest, SampleSubmissionResponse,
 };
 use thorium::{Error, Thorium};
 use tracing::{Level, event, instrument};
@@ -23,12 +23,12 @@ use walkdir::WalkDir;
 use super::helpers;
 use crate::log;
 
-/// A cache of compiled child filter regular expressions mapped to their raw
+/// A cache of compiled child filter regular expressions and globs mapped to their raw
 /// String representation
 // only one agent is running at a time, so we don't really need a Mutex here;
 // unfortunately, we can't make this thread-local because the Agent is run on a tokio
 // task an can be on any thread
-static CHILD_FILTERS_CACHE: LazyLock<Mutex<HashMap<String, Regex>>> =
+static CHILD_FILTERS_CACHE: LazyLock<Mutex<HashMap<String, ChildFilterMatcher>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
 /// Create all directories to look for children in
@@ -140,7 +140,7 @@ fn is_supporting(child: &Path) -> bool {
 ///
 /// * `haystack` - The haystack we're trying to match to the set of filters
 /// * `filters` - The set of filters we're matching on
-/// * `filters_cache` - A cache of compiled regexes from our filters
+/// * `filters_cache` - A cache of compiled regexes and globs from our filters
 #[instrument(
     name = "children::matches_any_filter",
     skip(filters, filters_cache),
@@ -149,15 +149,15 @@ fn is_supporting(child: &Path) -> bool {
 fn matches_any_filter(
     haystack: &str,
     filters: &HashSet<String>,
-    filters_cache: &mut HashMap<String, Regex>,
+    filters_cache: &mut HashMap<String, ChildFilterMatcher>,
 ) -> Result<bool, Error> {
     for raw_filter in filters {
-        let compiled_filter = filters_cache
-            // get a compiled regex from the cache or
-            // compile a regex and add it to the cache
-            .entry(raw_filter.clone())
-            // error out if the regex is invalid
-            .or_insert(Regex::new(raw_filter)?);
+        // get a compiled filter from the cache or compile it and add it to the cache
+        let compiled_filter = match filters_cache.entry(raw_filter.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            // error out if the regex or glob is invalid
+            Entry::Vacant(entry) => entry.insert(ChildFilterMatcher::compile(raw_filter)?),
+        };
         if compiled_filter.is_match(haystack) {
             // we have a match, so short-circuit and return true
             return Ok(true);
@@ -172,7 +172,7 @@ fn matches_any_filter(
 ///
 /// * `child` - The child we're matching
 /// * `child_filters` - The child filters we're matching on
-/// * `filters_cache` - A cache of compiled regexes from our filters
+/// * `filters_cache` - A cache of compiled regexes and globs from our filters
 /// * `logs` - The logs to send to the API
 #[instrument(
     name = "children::child_matches_any",
@@ -182,7 +182,7 @@ fn matches_any_filter(
 fn child_matches_any(
     child: &Path,
     filters: &ChildFilters,
-    filters_cache: &mut HashMap<String, Regex>,
+    filters_cache: &mut HashMap<String, ChildFilterMatcher>,
     logs: &mut Sender<String>,
 ) -> Result<bool, Error> {
     let mime = if filters.mime.is_empty() {
@@ -413,7 +413,7 @@ impl ChildrenFiles {
     pub fn filter(
         &mut self,
         filters: &ChildFilters,
-        filters_cache: &mut HashMap<String, Regex>,
+        filters_cache: &mut HashMap<String, ChildFilterMatcher>,
         logs: &mut Sender<String>,
     ) -> Result<(), Error> {
         // save a list of children that matched/didn't match
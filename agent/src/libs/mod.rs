@@ -8,7 +8,8 @@ mod tags;
 mod target;
 mod worker;
 
-pub(crate) use cache::DownloadedCache;
+pub use agents::{CmdBuilder, dry_run_args};
+pub use cache::DownloadedCache;
 use lifetime::Lifetime;
 pub(crate) use results::RawResults;
 pub(crate) use tags::TagBundle;
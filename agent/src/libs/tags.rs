@@ -2,9 +2,10 @@
 
 use crossbeam::channel::Sender;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use thorium::models::backends::TagSupport;
 use thorium::models::{
     AutoTag, AutoTagLogic, GenericJob, OutputCollection, Repo, Sample, TagRequest,
@@ -17,7 +18,7 @@ use crate::{fail, log};
 use super::results::RawResults;
 
 /// A bundle of different tag types from this job
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct TagBundle {
     /// The tags to apply to some samples
     samples: Option<TagRequest<Sample>>,
@@ -25,6 +26,151 @@ pub struct TagBundle {
     repos: Option<TagRequest<Repo>>,
 }
 
+impl TagBundle {
+    /// Merge another bundle's tags into this one without duplicating values
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The bundle to merge into this one
+    pub fn merge(&mut self, other: TagBundle) {
+        self.samples = merge_req(self.samples.take(), other.samples);
+        self.repos = merge_req(self.repos.take(), other.repos);
+    }
+
+    /// Get only the tags in this bundle that are not in a previous bundle
+    ///
+    /// # Arguments
+    ///
+    /// * `previous` - The bundle to compare against
+    #[must_use]
+    pub fn diff(&self, previous: &TagBundle) -> TagBundle {
+        TagBundle {
+            samples: diff_req(self.samples.as_ref(), previous.samples.as_ref()),
+            repos: diff_req(self.repos.as_ref(), previous.repos.as_ref()),
+        }
+    }
+
+    /// Get the path the tags already submitted for a job are saved at
+    ///
+    /// Generators reuse the same job id each time they wake up so this lets a later run of
+    /// this job only submit the tags its earlier runs didn't. This state lives in the local
+    /// temp dir so it is per node; on K8s each run gets a fresh pod and this does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job to get a submitted tags path for
+    pub fn submitted_path(job: &GenericJob) -> PathBuf {
+        std::env::temp_dir()
+            .join("thorium-submitted-tags")
+            .join(format!("{}.json", job.id))
+    }
+
+    /// Load the tags a previous run of a job already submitted
+    ///
+    /// Missing or unreadable state is treated as nothing having been submitted, which at worst
+    /// resubmits tags the API already has.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to load submitted tags from
+    pub async fn load(path: &Path) -> Self {
+        match tokio::fs::read(path).await {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_default(),
+            Err(_) => TagBundle::default(),
+        }
+    }
+
+    /// Save the tags that have been submitted for a job
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to save our submitted tags to
+    pub async fn save(&self, path: &Path) -> Result<(), Error> {
+        // make sure the directory for our submitted tags exists
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, serde_json::to_vec(self)?).await?;
+        Ok(())
+    }
+}
+
+/// Check if two tag requests are visible to the same groups
+///
+/// # Arguments
+///
+/// * `left` - The first tag request to compare
+/// * `right` - The second tag request to compare
+fn same_groups<T: TagSupport>(left: &TagRequest<T>, right: &TagRequest<T>) -> bool {
+    let left: HashSet<&String> = left.groups.iter().collect();
+    let right: HashSet<&String> = right.groups.iter().collect();
+    left == right
+}
+
+/// Union two tag requests together
+///
+/// # Arguments
+///
+/// * `base` - The tag request to merge into
+/// * `other` - The tag request to merge
+fn merge_req<T: TagSupport>(
+    base: Option<TagRequest<T>>,
+    other: Option<TagRequest<T>>,
+) -> Option<TagRequest<T>> {
+    match (base, other) {
+        (Some(mut base), Some(other)) => {
+            // tags are only the same tags if they are in the same groups so a request for
+            // different groups replaces our old one instead of claiming its tags are in both
+            if !same_groups(&base, &other) {
+                return Some(other);
+            }
+            // add the other requests tag values
+            for (key, values) in other.tags {
+                base.tags.entry(key).or_default().extend(values);
+            }
+            // use the deepest trigger depth of either request
+            base.trigger_depth = base.trigger_depth.max(other.trigger_depth);
+            Some(base)
+        }
+        (base, other) => base.or(other),
+    }
+}
+
+/// Get the tags in a tag request that are not in a previous one
+///
+/// # Arguments
+///
+/// * `current` - The tag request to get new tags from
+/// * `previous` - The tag request to compare against
+fn diff_req<T: TagSupport>(
+    current: Option<&TagRequest<T>>,
+    previous: Option<&TagRequest<T>>,
+) -> Option<TagRequest<T>> {
+    // if we have no previous tags then all of our tags are new
+    let Some(previous) = previous else {
+        return current.cloned();
+    };
+    let mut delta = current?.clone();
+    // tags submitted to other groups haven't been submitted to ours yet
+    if !same_groups(&delta, previous) {
+        return Some(delta);
+    }
+    // drop any values that were already in our previous request
+    for (key, values) in &mut delta.tags {
+        if let Some(old) = previous.tags.get(key) {
+            values.retain(|value| !old.contains(value));
+        }
+    }
+    // drop any keys that have no new values
+    delta.tags.retain(|_, values| !values.is_empty());
+    // only return a request if it has new tags
+    if delta.tags.is_empty() {
+        None
+    } else {
+        Some(delta)
+    }
+}
+
 /// determine if the target output value exists at the root or not
 ///
 /// # Arguments
@@ -428,3 +574,119 @@ pub async fn submit(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a bundle with the same sample and repo tags
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - The key/value pairs to add
+    fn bundle(tags: &[(&str, &str)]) -> TagBundle {
+        let mut raw = RawTags::default();
+        for (key, value) in tags {
+            raw.add_ref(*key, *value);
+        }
+        TagBundle {
+            samples: Some(raw.to_req(0)),
+            repos: Some(raw.to_req(0)),
+        }
+    }
+
+    /// Get the values for a key in a bundle's sample tags
+    ///
+    /// # Arguments
+    ///
+    /// * `bundle` - The bundle to get values from
+    /// * `key` - The key to get values for
+    fn values(bundle: &TagBundle, key: &str) -> HashSet<String> {
+        bundle.samples.as_ref().unwrap().tags[key].clone()
+    }
+
+    /// Merge bundles with overlapping tags
+    #[test]
+    fn merge_overlapping() {
+        let mut base = bundle(&[("plant", "corn"), ("plant", "soy"), ("healthy", "yes")]);
+        base.merge(bundle(&[
+            ("plant", "soy"),
+            ("plant", "wheat"),
+            ("color", "green"),
+        ]));
+        // overlapping values should only show up once
+        let plants = values(&base, "plant");
+        assert_eq!(plants.len(), 3);
+        assert!(plants.contains("corn") && plants.contains("soy") && plants.contains("wheat"));
+        assert_eq!(values(&base, "healthy").len(), 1);
+        assert_eq!(values(&base, "color").len(), 1);
+        // repo tags should be merged too
+        assert_eq!(base.repos.as_ref().unwrap().tags["plant"].len(), 3);
+    }
+
+    /// Merge bundles where only one side has tags for a target
+    #[test]
+    fn merge_missing() {
+        let mut base = TagBundle::default();
+        base.merge(bundle(&[("plant", "corn")]));
+        assert!(values(&base, "plant").contains("corn"));
+        // merging an empty bundle should leave our tags alone
+        base.merge(TagBundle::default());
+        assert_eq!(values(&base, "plant").len(), 1);
+    }
+
+    /// Diff a bundle against a prior state
+    #[test]
+    fn diff_prior() {
+        let previous = bundle(&[("plant", "corn"), ("healthy", "yes")]);
+        let current = bundle(&[("plant", "corn"), ("plant", "soy"), ("healthy", "yes")]);
+        let delta = current.diff(&previous);
+        // only our new value should be left
+        let req = delta.samples.as_ref().unwrap();
+        assert_eq!(req.tags.len(), 1);
+        assert_eq!(values(&delta, "plant"), HashSet::from(["soy".to_owned()]));
+        assert!(delta.repos.is_some());
+        // a bundle with no new tags should have nothing to submit
+        let delta = current.diff(&current);
+        assert!(delta.samples.is_none());
+        assert!(delta.repos.is_none());
+        // everything is new if we have no prior state
+        let delta = current.diff(&TagBundle::default());
+        assert_eq!(values(&delta, "plant").len(), 2);
+    }
+
+    /// Diff and merge bundles that are visible to different groups
+    #[test]
+    fn diff_groups() {
+        let mut previous = bundle(&[("plant", "corn")]);
+        previous.samples.as_mut().unwrap().groups = vec!["farm".to_owned()];
+        let mut current = bundle(&[("plant", "corn")]);
+        current.samples.as_mut().unwrap().groups = vec!["field".to_owned()];
+        // tags submitted to another group are still new for ours
+        let delta = current.diff(&previous);
+        assert_eq!(values(&delta, "plant").len(), 1);
+        // merging a different groups request replaces instead of claiming both groups
+        previous.merge(current);
+        let req = previous.samples.as_ref().unwrap();
+        assert_eq!(req.groups, vec!["field".to_owned()]);
+    }
+
+    /// Submitted tags survive being saved and loaded by a new agent
+    #[tokio::test]
+    async fn submitted_round_trip() {
+        let path = std::env::temp_dir()
+            .join("thorium-submitted-tags-test")
+            .join(format!("{}.json", uuid::Uuid::new_v4()));
+        // nothing has been submitted before we save anything
+        let loaded = TagBundle::load(&path).await;
+        assert!(loaded.samples.is_none() && loaded.repos.is_none());
+        // save some submitted tags and load them back
+        let submitted = bundle(&[("plant", "corn")]);
+        submitted.save(&path).await.unwrap();
+        let loaded = TagBundle::load(&path).await;
+        // tags we already submitted shouldn't be submitted again
+        let delta = bundle(&[("plant", "corn"), ("plant", "soy")]).diff(&loaded);
+        assert_eq!(values(&delta, "plant"), HashSet::from(["soy".to_owned()]));
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
@@ -22,6 +22,8 @@ pub struct Target {
     pub thorium: Thorium,
     /// What pool of resources this worker was spawned under
     pub pool: Pools,
+    /// How many job dependencies to download at once
+    pub download_concurrency: usize,
 }
 
 impl Target {
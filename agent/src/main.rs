@@ -1,15 +1,13 @@
 use clap::Parser;
-
-mod args;
-mod libs;
-use libs::Worker;
+use thorium_agent::Worker;
+use thorium_agent::args::Args;
 use tracing::{event, span, Level};
 
 /// The Thorium agent main loop
 #[tokio::main]
 async fn main() {
     // load command line args
-    let args = args::Args::parse();
+    let args = Args::parse();
     // build our agent name by what scaler we are claiming jobs for
     let trace_name = format!("Thorium{}Agent", args.env.kind());
     // setup our tracers/subscribers
@@ -0,0 +1,99 @@
+//! Benchmarks for buffering the parts of multipart uploads to s3
+//!
+//! Run these with `cargo bench -p thorium-api --features test-utilities --bench buffer_pool`.
+//! The number of allocations each strategy makes is also printed before it is benchmarked.
+
+use bytes::{Bytes, BytesMut};
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+use thorium::test_utilities::CountingAllocator;
+use thorium::utils::buffers::BufferPool;
+use thorium::utils::s3::MULTIPART_BUFFER_SIZE;
+
+/// The number of uploads to buffer in each run
+const UPLOADS: usize = 4;
+
+/// The number of parts in each upload
+const PARTS: usize = 3;
+
+/// The size of the chunks we get from each multipart field
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The number of bytes we buffer before uploading a part
+const PART_SIZE: usize = 5_242_880;
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Print the number of allocations it takes to buffer our uploads
+///
+/// # Arguments
+///
+/// * `name` - The name of the benchmark we are counting allocations for
+/// * `upload` - Buffers our uploads
+fn report_allocations<F: FnMut()>(name: &str, mut upload: F) {
+    let start = CountingAllocator::allocations();
+    upload();
+    let allocations = CountingAllocator::allocations() - start;
+    println!("{name}: {allocations} allocations for {UPLOADS} uploads of {PARTS} parts");
+}
+
+/// Fill a buffer with enough chunks for a part
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer to fill
+/// * `chunk` - The chunk to fill our buffer with
+fn fill(buffer: &mut BytesMut, chunk: &[u8]) {
+    while buffer.len() < PART_SIZE {
+        buffer.extend_from_slice(chunk);
+    }
+}
+
+/// Buffer uploads by allocating a new buffer for each upload and copying each part
+///
+/// # Arguments
+///
+/// * `chunk` - The chunk each upload is made of
+fn fresh(chunk: &[u8]) {
+    for _ in 0..UPLOADS {
+        let mut buffer = BytesMut::with_capacity(MULTIPART_BUFFER_SIZE);
+        for _ in 0..PARTS {
+            fill(&mut buffer, chunk);
+            // copy our part like building a body from a slice does
+            black_box(Bytes::copy_from_slice(&buffer[..]));
+            buffer.clear();
+        }
+    }
+}
+
+/// Buffer uploads with pooled buffers and hand each part off without copying it
+///
+/// # Arguments
+///
+/// * `pool` - The pool to get buffers from
+/// * `chunk` - The chunk each upload is made of
+fn pooled(pool: &BufferPool, chunk: &[u8]) {
+    for _ in 0..UPLOADS {
+        let mut buffer = pool.get();
+        for _ in 0..PARTS {
+            fill(&mut buffer, chunk);
+            // hand our part off and reclaim our buffer once its been sent
+            black_box(buffer.split().freeze());
+            let _ = buffer.try_reclaim(pool.buffer_size());
+        }
+    }
+}
+
+/// Benchmark buffering uploads with and without a buffer pool
+fn buffers(c: &mut Criterion) {
+    let chunk = vec![7; CHUNK_SIZE];
+    let pool = BufferPool::new(MULTIPART_BUFFER_SIZE, 4);
+    report_allocations("upload/fresh", || fresh(&chunk));
+    report_allocations("upload/pooled", || pooled(&pool, &chunk));
+    c.bench_function("upload/fresh", |b| b.iter(|| fresh(&chunk)));
+    c.bench_function("upload/pooled", |b| b.iter(|| pooled(&pool, &chunk)));
+}
+
+criterion_group!(benches, buffers);
+criterion_main!(benches);
@@ -0,0 +1,87 @@
+//! Benchmarks for hashing uploads serially and on background threads
+//!
+//! Run these with `cargo bench -p thorium-api --bench hashing`. Each benchmark
+//! also waits on a simulated upload of every chunk so hashing has something to
+//! overlap with like it does when streaming a file to s3.
+
+use bytes::Bytes;
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use thorium::utils::s3::{ParallelHashers, StandardHashers};
+
+/// The size of the payload to hash
+const PAYLOAD_SIZE: usize = 64 * 1024 * 1024;
+
+/// The size of the chunks we get from each multipart field
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// How long it takes to upload a single chunk
+const UPLOAD_LATENCY: Duration = Duration::from_micros(50);
+
+/// Simulate uploading a chunk to s3
+///
+/// Tokio's timers only have millisecond granularity so we yield until our
+/// latency has passed instead of sleeping.
+async fn upload() {
+    let start = Instant::now();
+    while start.elapsed() < UPLOAD_LATENCY {
+        tokio::task::yield_now().await;
+    }
+}
+
+/// Hash our chunks on the upload task before uploading each of them
+///
+/// # Arguments
+///
+/// * `chunks` - The chunks to hash
+async fn serial(chunks: &[Bytes]) {
+    let mut hashers = StandardHashers::default();
+    for chunk in chunks {
+        hashers.digest(chunk);
+        upload().await;
+    }
+    black_box(hashers.finish());
+}
+
+/// Hash our chunks on background threads while uploading them
+///
+/// # Arguments
+///
+/// * `chunks` - The chunks to hash
+async fn parallel(chunks: &[Bytes]) {
+    let mut hashers = ParallelHashers::default();
+    for chunk in chunks {
+        hashers.digest(chunk).await.unwrap();
+        upload().await;
+    }
+    black_box(hashers.finish().await.unwrap());
+}
+
+/// Benchmark hashing a large payload serially and in parallel
+fn hashing(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    // split our payload into chunks like a multipart upload would
+    let payload = Bytes::from(
+        (0..PAYLOAD_SIZE)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<u8>>(),
+    );
+    let chunks = (0..PAYLOAD_SIZE)
+        .step_by(CHUNK_SIZE)
+        .map(|start| payload.slice(start..start + CHUNK_SIZE))
+        .collect::<Vec<_>>();
+    let mut group = c.benchmark_group("hashing");
+    group.throughput(Throughput::Bytes(PAYLOAD_SIZE as u64));
+    group.sample_size(10);
+    group.bench_function("serial", |b| {
+        b.to_async(&runtime).iter(|| serial(&chunks));
+    });
+    group.bench_function("parallel", |b| {
+        b.to_async(&runtime).iter(|| parallel(&chunks));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, hashing);
+criterion_main!(benches);
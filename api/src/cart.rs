@@ -0,0 +1,440 @@
+//! Cart and uncart files without going through the Thorium API
+//!
+//! These wrap the same [`cart_rs`] streams the API uses so files are never
+//! buffered into memory in their entirety. Files are carted and uncarted with
+//! the resumable primitives in [`resume`] so a failed attempt can pick up from
+//! its last checkpoint.
+
+use cart_rs::{CartStream, UncartStream};
+use generic_array::{GenericArray, typenum::U16};
+use std::path::{Path, PathBuf};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::BufReader;
+
+use crate::Error;
+
+pub mod resume;
+
+/// The password Thorium uses to cart files by default
+pub const DEFAULT_PASSWORD: &str = "SecretCornIsBest";
+
+/// The max length of a cart password in bytes
+const MAX_PASSWORD_LEN: usize = 16;
+
+/// The options for carting a file
+#[derive(Debug, Clone)]
+pub struct CartOpts {
+    /// The password to encrypt our carted file with
+    pub password: String,
+    /// Whether to copy the permissions and modified time of our source file to our carted file
+    pub preserve_metadata: bool,
+    /// Where to save our progress instead of a hidden file next to our carted file
+    pub progress: Option<PathBuf>,
+}
+
+impl Default for CartOpts {
+    /// Create default cart options
+    fn default() -> Self {
+        CartOpts {
+            password: DEFAULT_PASSWORD.to_owned(),
+            preserve_metadata: false,
+            progress: None,
+        }
+    }
+}
+
+impl CartOpts {
+    /// Set the password to encrypt our carted file with
+    ///
+    /// Cart passwords are only used to keep carted files from being flagged by
+    /// malware scanners. They are stored in plaintext in the cart header.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - The password to use (no longer than 16 bytes)
+    #[must_use]
+    pub fn password<T: Into<String>>(mut self, password: T) -> Self {
+        self.password = password.into();
+        self
+    }
+
+    /// Copy the permissions and modified time of our source file to our carted file
+    #[must_use]
+    pub fn preserve_metadata(mut self) -> Self {
+        self.preserve_metadata = true;
+        self
+    }
+
+    /// Save our progress somewhere other than next to our carted file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to save our progress to
+    #[must_use]
+    pub fn progress<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.progress = Some(path.into());
+        self
+    }
+}
+
+/// The options for uncarting a file
+#[derive(Debug, Clone, Default)]
+pub struct UncartOpts {
+    /// Where to save our progress instead of a hidden file next to our uncarted file
+    pub progress: Option<PathBuf>,
+}
+
+impl UncartOpts {
+    /// Save our progress somewhere other than next to our uncarted file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to save our progress to
+    #[must_use]
+    pub fn progress<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.progress = Some(path.into());
+        self
+    }
+}
+
+/// Build the key used to encrypt carted files from a password
+///
+/// Passwords shorter than 16 bytes are padded with zeroes.
+///
+/// # Arguments
+///
+/// * `password` - The password to build a key from
+pub fn key(password: &str) -> Result<GenericArray<u8, U16>, Error> {
+    let raw = password.as_bytes();
+    // make sure our password can fit in our key
+    if raw.len() > MAX_PASSWORD_LEN {
+        return Err(Error::new("Password is greater than 16 characters!"));
+    }
+    // pad our password with zeroes
+    let mut key = GenericArray::default();
+    key[..raw.len()].copy_from_slice(raw);
+    Ok(key)
+}
+
+/// Open a stream that carts a file as it is read
+///
+/// # Arguments
+///
+/// * `src` - The path to the file to cart
+/// * `key` - The key to encrypt our carted file with
+pub async fn cart_stream<P: AsRef<Path>>(
+    src: P,
+    key: &GenericArray<u8, U16>,
+) -> Result<CartStream<BufReader<File>, U16>, Error> {
+    let input = File::open(src).await?;
+    Ok(CartStream::new(BufReader::new(input), key)?)
+}
+
+/// Open a stream that uncarts a file as it is read
+///
+/// # Arguments
+///
+/// * `src` - The path to the carted file
+pub async fn uncart_stream<P: AsRef<Path>>(src: P) -> Result<UncartStream<BufReader<File>>, Error> {
+    let input = File::open(src).await?;
+    Ok(UncartStream::new(BufReader::new(input)))
+}
+
+/// Cart a file
+///
+/// Returns the number of bytes written to our carted file. If this fails after
+/// progress has been saved, our carted file and progress are left in place so
+/// carting it again picks up from the last checkpoint.
+///
+/// # Arguments
+///
+/// * `src` - The path to the file to cart
+/// * `dest` - The path to write our carted file to
+/// * `opts` - The options to use when carting this file
+pub async fn cart_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dest: Q,
+    opts: &CartOpts,
+) -> Result<u64, Error> {
+    let key = key(&opts.password)?;
+    // save our progress next to our carted file unless we were told otherwise
+    let progress = opts
+        .progress
+        .clone()
+        .unwrap_or_else(|| resume::progress_path(dest.as_ref()));
+    let written = resume::cart(src.as_ref(), dest.as_ref(), &progress, &key).await?;
+    // copy our source file's metadata if requested
+    if opts.preserve_metadata {
+        copy_metadata(src.as_ref(), dest.as_ref()).await?;
+    }
+    Ok(written)
+}
+
+/// Uncart a file
+///
+/// Returns the number of bytes written to our uncarted file. If this fails after
+/// progress has been saved, our uncarted file and progress are left in place so
+/// uncarting it again picks up from the last checkpoint.
+///
+/// # Arguments
+///
+/// * `src` - The path to the carted file
+/// * `dest` - The path to write our uncarted file to
+/// * `opts` - The options to use when uncarting this file
+pub async fn uncart_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dest: Q,
+    opts: &UncartOpts,
+) -> Result<u64, Error> {
+    // save our progress next to our uncarted file unless we were told otherwise
+    let progress = opts
+        .progress
+        .clone()
+        .unwrap_or_else(|| resume::progress_path(dest.as_ref()));
+    resume::uncart(src.as_ref(), dest.as_ref(), &progress).await
+}
+
+/// Copy the permissions and modified time of one file to another
+///
+/// # Arguments
+///
+/// * `src` - The file to copy metadata from
+/// * `dest` - The file to copy metadata to
+async fn copy_metadata(src: &Path, dest: &Path) -> Result<(), Error> {
+    let metadata = tokio::fs::metadata(src).await?;
+    tokio::fs::set_permissions(dest, metadata.permissions()).await?;
+    let modified = metadata.modified()?;
+    // setting the modified time needs an open handle to our file
+    let dest = OpenOptions::new()
+        .write(true)
+        .open(dest)
+        .await?
+        .into_std()
+        .await;
+    tokio::task::spawn_blocking(move || dest.set_modified(modified)).await??;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cart_rs::{CartStream, UncartStream};
+    use rand::rngs::SmallRng;
+    use rand::{RngCore, SeedableRng};
+    use std::path::PathBuf;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncRead, AsyncWrite, BufReader, ReadBuf};
+
+    use super::{CartOpts, DEFAULT_PASSWORD, UncartOpts, cart_file, key, uncart_file};
+
+    /// Generates a large file on the fly and counts how much of it has been read
+    struct SyntheticReader {
+        /// The rng to generate data with
+        rng: SmallRng,
+        /// The number of bytes left to generate
+        remaining: usize,
+        /// The number of bytes that have been generated so far
+        generated: Arc<AtomicU64>,
+    }
+
+    impl AsyncRead for SyntheticReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            let len = buf.remaining().min(self.remaining);
+            let chunk = buf.initialize_unfilled_to(len);
+            // generate somewhat compressible data like most real files are
+            self.rng.fill_bytes(chunk);
+            chunk
+                .iter_mut()
+                .for_each(|byte| *byte = b'a' + (*byte & 0x0f));
+            buf.advance(len);
+            self.remaining -= len;
+            self.generated.fetch_add(len as u64, Ordering::Relaxed);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Discards uncarted data while tracking how much data is buffered between it and a reader
+    struct CountingWriter {
+        /// The number of bytes that have been generated by our reader
+        generated: Arc<AtomicU64>,
+        /// The number of bytes written so far
+        written: u64,
+        /// The most bytes that have been read but not yet written at once
+        peak_buffered: u64,
+    }
+
+    impl AsyncWrite for CountingWriter {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.written += buf.len() as u64;
+            // anything that was generated but hasn't reached us yet is being buffered
+            let buffered = self.generated.load(Ordering::Relaxed) - self.written;
+            self.peak_buffered = self.peak_buffered.max(buffered);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Build a temporary directory for a test
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("thorium-{name}-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Write a random binary blob to disk
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to write our blob to
+    fn write_blob(dir: &std::path::Path) -> (PathBuf, Vec<u8>) {
+        let mut blob = vec![0; 3 * 1024 * 1024 + 17];
+        SmallRng::seed_from_u64(1551).fill_bytes(&mut blob);
+        let path = dir.join("blob");
+        std::fs::write(&path, &blob).unwrap();
+        (path, blob)
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let dir = temp_dir("cart-round-trip");
+        let (src, blob) = write_blob(&dir);
+        let carted = dir.join("blob.cart");
+        let uncarted = dir.join("blob.uncarted");
+        cart_file(&src, &carted, &CartOpts::default())
+            .await
+            .unwrap();
+        // our carted file shouldn't contain our raw data
+        assert_ne!(std::fs::read(&carted).unwrap(), blob);
+        let written = uncart_file(&carted, &uncarted, &UncartOpts::default())
+            .await
+            .unwrap();
+        assert_eq!(written, blob.len() as u64);
+        assert_eq!(std::fs::read(&uncarted).unwrap(), blob);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn round_trip_password() {
+        let dir = temp_dir("cart-round-trip-password");
+        let (src, blob) = write_blob(&dir);
+        let carted = dir.join("blob.cart");
+        let uncarted = dir.join("blob.uncarted");
+        let opts = CartOpts::default().password("corn");
+        cart_file(&src, &carted, &opts).await.unwrap();
+        // a different password should produce a different carted file
+        let default_carted = dir.join("blob.default.cart");
+        cart_file(&src, &default_carted, &CartOpts::default())
+            .await
+            .unwrap();
+        assert_ne!(
+            std::fs::read(&carted).unwrap(),
+            std::fs::read(&default_carted).unwrap()
+        );
+        // our password is stored in our cart header so we can uncart without it
+        uncart_file(&carted, &uncarted, &UncartOpts::default())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&uncarted).unwrap(), blob);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn preserve_metadata() {
+        let dir = temp_dir("cart-preserve-metadata");
+        let (src, _) = write_blob(&dir);
+        // backdate our source file so we can tell its modified time was copied
+        let modified =
+            std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&src)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let carted = dir.join("blob.cart");
+        cart_file(&src, &carted, &CartOpts::default().preserve_metadata())
+            .await
+            .unwrap();
+        let metadata = std::fs::metadata(&carted).unwrap();
+        assert_eq!(metadata.modified().unwrap(), modified);
+        assert_eq!(
+            metadata.permissions(),
+            std::fs::metadata(&src).unwrap().permissions()
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn uncart_invalid() {
+        let dir = temp_dir("uncart-invalid");
+        let src = dir.join("corn.cart");
+        std::fs::write(&src, b"not a cart file").unwrap();
+        let dest = dir.join("corn");
+        assert!(
+            uncart_file(&src, &dest, &UncartOpts::default())
+                .await
+                .is_err()
+        );
+        // a failed uncart shouldn't leave a partial file behind
+        assert!(!dest.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn uncart_bounded_memory() {
+        const SIZE: usize = 32 * 1024 * 1024;
+        let generated = Arc::new(AtomicU64::new(0));
+        let reader = SyntheticReader {
+            rng: SmallRng::seed_from_u64(1551),
+            remaining: SIZE,
+            generated: generated.clone(),
+        };
+        // cart and then uncart our synthetic file without it ever existing in full
+        let key = key(DEFAULT_PASSWORD).unwrap();
+        let carted = CartStream::new(BufReader::new(reader), &key).unwrap();
+        let mut uncarted = UncartStream::new(BufReader::new(carted));
+        let mut writer = CountingWriter {
+            generated,
+            written: 0,
+            peak_buffered: 0,
+        };
+        let written = tokio::io::copy(&mut uncarted, &mut writer).await.unwrap();
+        assert_eq!(written, SIZE as u64);
+        assert_eq!(writer.written, SIZE as u64);
+        // we should only ever be buffering a small window of our file
+        assert!(
+            writer.peak_buffered < 4 * 1024 * 1024,
+            "buffered {} bytes while uncarting",
+            writer.peak_buffered
+        );
+    }
+
+    #[test]
+    fn password_too_long() {
+        assert!(key("SecretCornIsBest").is_ok());
+        assert!(key("SecretCornIsTheBest").is_err());
+        // short passwords are padded with zeroes
+        assert_eq!(&key("corn").unwrap()[..], b"corn\0\0\0\0\0\0\0\0\0\0\0\0");
+    }
+}
@@ -0,0 +1,777 @@
+//! Utility functions for carting and uncarting files in a way that can be resumed
+//!
+//! Carting and uncarting periodically sync their output to disk and record a
+//! checkpoint in a sidecar progress file. Cart checkpoints are full zlib flushes and
+//! uncart checkpoints are deflate block boundaries, so the `CaRT` stream is never
+//! split partway through a block. When a failed file is retried, its input and output
+//! are seeked to the saved checkpoint and carting or uncarting continues from there
+//! instead of reading the input from the beginning again.
+
+use cart_rs::header::{HEADER_LEN, Header};
+use cart_rs::resume::{CartCheckpoint, ResumableCart, ResumableUncart, UncartCheckpoint};
+use data_encoding::HEXLOWER;
+use generic_array::{GenericArray, typenum::U16};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWriteExt};
+
+use crate::Error;
+
+/// The number of bytes to write between saving our progress
+pub const CHECKPOINT_BYTES: u64 = 8 * 1024 * 1024;
+
+/// The size of the buffer to read our input with
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The point in a stream that we can resume from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Checkpoint {
+    /// A point we can resume carting from
+    Cart(CartCheckpoint),
+    /// A point we can resume uncarting from
+    Uncart(UncartCheckpoint),
+}
+
+/// The input and key that a file's progress belongs to
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct Source {
+    /// The size of the input file
+    input_len: u64,
+    /// When the input file was last modified
+    input_modified: Option<SystemTime>,
+    /// The sha256 of the cart key our output is carted or uncarted with
+    key: String,
+}
+
+impl Source {
+    /// Describe the input and key we are carting or uncarting with
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The path to the input file
+    /// * `key` - The cart key being used
+    async fn new(input: &Path, key: &[u8]) -> Result<Self, Error> {
+        let metadata = tokio::fs::metadata(input).await?;
+        Ok(Source {
+            input_len: metadata.len(),
+            input_modified: metadata.modified().ok(),
+            key: fingerprint(key),
+        })
+    }
+
+    /// Load the checkpoint saved by a previous attempt if it can be resumed
+    ///
+    /// Returns an error if our output was started with a different key since
+    /// resuming would corrupt it and starting over would throw it away.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress_path` - The path to the saved progress
+    /// * `out_path` - The path to the output file
+    async fn load(
+        &self,
+        progress_path: &Path,
+        out_path: &Path,
+    ) -> Result<Option<Checkpoint>, Error> {
+        // a missing or torn progress file just means we start over
+        let Ok(raw) = tokio::fs::read(progress_path).await else {
+            return Ok(None);
+        };
+        let Ok(saved) = serde_json::from_slice::<Progress>(&raw) else {
+            return Ok(None);
+        };
+        // don't resume if our input has changed since our last attempt
+        if saved.source.input_len != self.input_len
+            || saved.source.input_modified != self.input_modified
+        {
+            return Ok(None);
+        }
+        if saved.source.key != self.key {
+            return Err(Error::new(format!(
+                "'{}' was partially written with a different key! Rerun with the original \
+                 password or delete '{}' to start over",
+                out_path.display(),
+                progress_path.display(),
+            )));
+        }
+        // make sure all of our synced bytes are still in our output file
+        let written = match &saved.checkpoint {
+            Checkpoint::Cart(checkpoint) => checkpoint.written,
+            Checkpoint::Uncart(checkpoint) => checkpoint.written,
+        };
+        match tokio::fs::metadata(out_path).await {
+            Ok(metadata) if metadata.len() >= written => Ok(Some(saved.checkpoint)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Save a checkpoint to disk
+    ///
+    /// # Arguments
+    ///
+    /// * `checkpoint` - The checkpoint to save
+    /// * `progress_path` - The path to save our progress to
+    async fn save(&self, checkpoint: Checkpoint, progress_path: &Path) -> Result<(), Error> {
+        let progress = Progress {
+            source: self.clone(),
+            checkpoint,
+        };
+        // write our progress to a temp file first so a crash never leaves it torn
+        let mut temp_path = progress_path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        tokio::fs::write(&temp_path, serde_json::to_vec(&progress)?).await?;
+        tokio::fs::rename(&temp_path, progress_path).await?;
+        Ok(())
+    }
+}
+
+/// The progress made carting or uncarting a file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct Progress {
+    /// The input and key this progress belongs to
+    #[serde(flatten)]
+    source: Source,
+    /// The point we can resume from
+    checkpoint: Checkpoint,
+}
+
+/// Get the fingerprint of a cart key to save instead of the key itself
+///
+/// # Arguments
+///
+/// * `key` - The key to fingerprint
+fn fingerprint(key: &[u8]) -> String {
+    HEXLOWER.encode(&Sha256::digest(key))
+}
+
+/// Get the path to the progress file for an output file
+///
+/// Progress files are hidden so they are skipped when walking directories.
+///
+/// # Arguments
+///
+/// * `final_path` - The path our output will end up at once it's complete
+pub fn progress_path(final_path: &Path) -> PathBuf {
+    let mut file_name = std::ffi::OsString::from(".");
+    if let Some(name) = final_path.file_name() {
+        file_name.push(name);
+    }
+    file_name.push(".progress");
+    final_path.with_file_name(file_name)
+}
+
+/// Get a stable name for the temporary output of an in-place conversion
+///
+/// The name is the same every time the same input is converted so failed
+/// in-place conversions can be resumed.
+///
+/// # Arguments
+///
+/// * `input` - The path to the input file being converted in-place
+pub fn temp_name(input: &Path) -> String {
+    let input = std::path::absolute(input).unwrap_or_else(|_| input.to_path_buf());
+    let digest = Sha256::digest(input.as_os_str().as_encoded_bytes());
+    format!(".temp-{}", &HEXLOWER.encode(&digest)[..16])
+}
+
+/// Remove a temporary directory unless it still holds partial output to resume
+///
+/// # Arguments
+///
+/// * `path` - The path to the directory to remove
+pub async fn remove_temp_dir(path: &Path) -> Result<(), Error> {
+    let Err(err) = tokio::fs::remove_dir(path).await else {
+        return Ok(());
+    };
+    match err.kind() {
+        ErrorKind::DirectoryNotEmpty | ErrorKind::NotFound => Ok(()),
+        _ => Err(Error::from(err)),
+    }
+}
+
+/// Cart a file, resuming a previous attempt if possible
+///
+/// Returns the total number of bytes in the carted file. If this fails after
+/// progress has been saved, the output and progress files are left in place so
+/// the next attempt can pick up from the last checkpoint.
+///
+/// # Arguments
+///
+/// * `input` - The path to the file to cart
+/// * `out_path` - The path to write the carted file to
+/// * `progress_path` - The path to save our progress to
+/// * `key` - The key to encrypt the carted file with
+pub async fn cart(
+    input: &Path,
+    out_path: &Path,
+    progress_path: &Path,
+    key: &GenericArray<u8, U16>,
+) -> Result<u64, Error> {
+    let mut reader = File::open(input).await?;
+    let result = cart_from(
+        &mut reader,
+        input,
+        out_path,
+        progress_path,
+        key,
+        CHECKPOINT_BYTES,
+    )
+    .await;
+    cleanup(result, out_path, progress_path).await
+}
+
+/// Uncart a file, resuming a previous attempt if possible
+///
+/// Returns the total number of bytes in the uncarted file. If this fails after
+/// progress has been saved, the output and progress files are left in place so
+/// the next attempt can pick up from the last checkpoint.
+///
+/// # Arguments
+///
+/// * `input` - The path to the file to uncart
+/// * `out_path` - The path to write the uncarted file to
+/// * `progress_path` - The path to save our progress to
+pub async fn uncart(input: &Path, out_path: &Path, progress_path: &Path) -> Result<u64, Error> {
+    let mut reader = File::open(input).await?;
+    let result = uncart_from(
+        &mut reader,
+        input,
+        out_path,
+        progress_path,
+        CHECKPOINT_BYTES,
+    )
+    .await;
+    cleanup(result, out_path, progress_path).await
+}
+
+/// Clean up after carting or uncarting a file
+///
+/// # Arguments
+///
+/// * `result` - The result of carting or uncarting our file
+/// * `out_path` - The path to the output file
+/// * `progress_path` - The path to our saved progress
+async fn cleanup(
+    result: Result<u64, Error>,
+    out_path: &Path,
+    progress_path: &Path,
+) -> Result<u64, Error> {
+    match result {
+        Ok(total) => {
+            // we're done so we don't need our progress anymore
+            remove_if_exists(progress_path).await?;
+            Ok(total)
+        }
+        Err(err) => {
+            // only keep our output if we have progress to resume from
+            if !tokio::fs::try_exists(progress_path).await.unwrap_or(false) {
+                remove_if_exists(out_path).await?;
+            }
+            Err(err)
+        }
+    }
+}
+
+/// Cart a stream to an output file, saving our progress along the way
+///
+/// # Arguments
+///
+/// * `reader` - The input to cart
+/// * `input` - The path to the input file
+/// * `out_path` - The path to the output file
+/// * `progress_path` - The path to save our progress to
+/// * `key` - The key to encrypt the carted file with
+/// * `checkpoint` - The number of bytes to write between saving our progress
+async fn cart_from<R: AsyncRead + AsyncSeek + Unpin>(
+    reader: &mut R,
+    input: &Path,
+    out_path: &Path,
+    progress_path: &Path,
+    key: &GenericArray<u8, U16>,
+    checkpoint: u64,
+) -> Result<u64, Error> {
+    let source = Source::new(input, key).await?;
+    // pick up from our last checkpoint if we have one
+    let (mut cart, mut output) = match source.load(progress_path, out_path).await? {
+        Some(Checkpoint::Cart(saved)) => {
+            reader.seek(SeekFrom::Start(saved.read)).await?;
+            let output = open_output(out_path, saved.written).await?;
+            (ResumableCart::resume(key, &saved)?, output)
+        }
+        _ => (ResumableCart::new(key)?, open_output(out_path, 0).await?),
+    };
+    let mut buffer = vec![0; READ_BUFFER_SIZE];
+    let mut carted = Vec::with_capacity(READ_BUFFER_SIZE);
+    let mut since_checkpoint = 0;
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        cart.update(&buffer[..read], &mut carted)?;
+        output.write_all(&carted).await?;
+        since_checkpoint += carted.len() as u64;
+        carted.clear();
+        // sync our output and save our progress once we've written enough
+        if since_checkpoint >= checkpoint {
+            let saved = cart.checkpoint(&mut carted)?;
+            output.write_all(&carted).await?;
+            carted.clear();
+            output.sync_data().await?;
+            source.save(Checkpoint::Cart(saved), progress_path).await?;
+            since_checkpoint = 0;
+        }
+    }
+    let total = cart.finish(&mut carted)?;
+    output.write_all(&carted).await?;
+    output.flush().await?;
+    output.sync_data().await?;
+    Ok(total)
+}
+
+/// Uncart a stream to an output file, saving our progress along the way
+///
+/// # Arguments
+///
+/// * `reader` - The cart file to uncart
+/// * `input` - The path to the cart file
+/// * `out_path` - The path to the output file
+/// * `progress_path` - The path to save our progress to
+/// * `checkpoint` - The number of bytes to write between saving our progress
+async fn uncart_from<R: AsyncRead + AsyncSeek + Unpin>(
+    reader: &mut R,
+    input: &Path,
+    out_path: &Path,
+    progress_path: &Path,
+    checkpoint: u64,
+) -> Result<u64, Error> {
+    // our header has the key our data was carted with
+    let mut header = [0; HEADER_LEN];
+    if reader.read_exact(&mut header).await.is_err() {
+        return Err(Error::new(
+            "Invalid CaRT file! CaRT header is malformed or missing.",
+        ));
+    }
+    let key = Header::get(&header)?.key;
+    let source = Source::new(input, &key).await?;
+    // pick up from our last checkpoint if we have one
+    let (mut uncart, mut output) = match source.load(progress_path, out_path).await? {
+        Some(Checkpoint::Uncart(saved)) => {
+            // our decompressor needs the data right before our checkpoint
+            let window = read_window(out_path, &saved).await?;
+            let uncart = ResumableUncart::resume(&header, &saved, &window)?;
+            reader.seek(SeekFrom::Start(saved.read)).await?;
+            (uncart, open_output(out_path, saved.written).await?)
+        }
+        _ => {
+            let mut uncart = ResumableUncart::new();
+            uncart.update(&header, &mut Vec::new())?;
+            (uncart, open_output(out_path, 0).await?)
+        }
+    };
+    let mut buffer = vec![0; READ_BUFFER_SIZE];
+    let mut uncarted = Vec::with_capacity(READ_BUFFER_SIZE);
+    let mut last_saved = 0;
+    let mut since_checkpoint = 0;
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        uncart.update(&buffer[..read], &mut uncarted)?;
+        output.write_all(&uncarted).await?;
+        since_checkpoint += uncarted.len() as u64;
+        uncarted.clear();
+        // sync our output and save the last block boundary once we've written enough
+        if since_checkpoint >= checkpoint
+            && let Some(saved) = uncart.checkpoint()
+            && saved.written > last_saved
+        {
+            last_saved = saved.written;
+            let saved = Checkpoint::Uncart(saved.clone());
+            output.sync_data().await?;
+            source.save(saved, progress_path).await?;
+            since_checkpoint = 0;
+        }
+    }
+    let total = uncart.finish(&mut uncarted)?;
+    output.write_all(&uncarted).await?;
+    output.flush().await?;
+    output.sync_data().await?;
+    Ok(total)
+}
+
+/// Open our output file and drop anything written after our checkpoint
+///
+/// # Arguments
+///
+/// * `out_path` - The path to the output file
+/// * `written` - The number of bytes that were synced before our checkpoint
+async fn open_output(out_path: &Path, written: u64) -> Result<File, Error> {
+    let mut output = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(out_path)
+        .await?;
+    // anything written after our last checkpoint was never synced
+    output.set_len(written).await?;
+    output.seek(SeekFrom::Start(written)).await?;
+    Ok(output)
+}
+
+/// Read the uncarted data right before a checkpoint back from our output file
+///
+/// # Arguments
+///
+/// * `out_path` - The path to the output file
+/// * `checkpoint` - The checkpoint we are resuming from
+async fn read_window(out_path: &Path, checkpoint: &UncartCheckpoint) -> Result<Vec<u8>, Error> {
+    let mut output = File::open(out_path).await?;
+    output
+        .seek(SeekFrom::Start(checkpoint.written - checkpoint.window()))
+        .await?;
+    let mut window = vec![0; usize::try_from(checkpoint.window())?];
+    output.read_exact(&mut window).await?;
+    Ok(window)
+}
+
+/// Remove a file if it exists
+///
+/// # Arguments
+///
+/// * `path` - The path to the file to remove
+async fn remove_if_exists(path: &Path) -> Result<(), Error> {
+    match tokio::fs::remove_file(path).await {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(Error::from(err)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cart_rs::header::HEADER_LEN;
+    use cart_rs::{CartStream, UncartStream};
+    use generic_array::{GenericArray, typenum::U16};
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+    use std::io::ErrorKind;
+    use std::path::{Path, PathBuf};
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::fs::File;
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, BufReader, ReadBuf};
+
+    use crate::Error;
+
+    use super::{Checkpoint, Progress, cart_from, cleanup, progress_path, uncart_from};
+
+    /// The number of bytes to write between checkpoints in tests
+    const CHECKPOINT: u64 = 64 * 1024;
+
+    /// A reader that counts the bytes it reads and fails after reading a set number of them
+    struct Interrupted<R> {
+        /// The reader to read from
+        inner: R,
+        /// The number of bytes left to return before failing
+        remaining: usize,
+        /// The number of bytes that have been read
+        read: usize,
+    }
+
+    impl<R> Interrupted<R> {
+        /// Wrap a reader so it fails after reading some number of bytes
+        ///
+        /// # Arguments
+        ///
+        /// * `inner` - The reader to wrap
+        /// * `remaining` - The number of bytes to read before failing
+        fn new(inner: R, remaining: usize) -> Self {
+            Interrupted {
+                inner,
+                remaining,
+                read: 0,
+            }
+        }
+    }
+
+    impl<R: AsyncRead + Unpin> AsyncRead for Interrupted<R> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            if self.remaining == 0 {
+                return Poll::Ready(Err(std::io::Error::new(
+                    ErrorKind::Interrupted,
+                    "Interrupted",
+                )));
+            }
+            // only read up to the number of bytes we have left
+            let limit = buf.remaining().min(self.remaining);
+            let mut limited = buf.take(limit);
+            let poll = Pin::new(&mut self.inner).poll_read(cx, &mut limited);
+            let read = limited.filled().len();
+            // mark the bytes we read into our limited buffer as filled
+            unsafe { buf.assume_init(read) };
+            buf.advance(read);
+            self.remaining -= read;
+            self.read += read;
+            poll
+        }
+    }
+
+    impl<R: AsyncSeek + Unpin> AsyncSeek for Interrupted<R> {
+        fn start_seek(
+            mut self: Pin<&mut Self>,
+            position: std::io::SeekFrom,
+        ) -> std::io::Result<()> {
+            Pin::new(&mut self.inner).start_seek(position)
+        }
+
+        fn poll_complete(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<std::io::Result<u64>> {
+            Pin::new(&mut self.inner).poll_complete(cx)
+        }
+    }
+
+    /// Build a temporary directory for a test
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("thorium-{name}-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Build a cart key from a password
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - The 16 byte password to use
+    fn key(password: &[u8; 16]) -> GenericArray<u8, U16> {
+        GenericArray::clone_from_slice(password)
+    }
+
+    /// Write some random data to disk
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to write our data to
+    fn write_data(path: &Path) -> Vec<u8> {
+        let mut rng = SmallRng::seed_from_u64(1550);
+        // make our data compressible enough to span many zlib blocks
+        let data = (0..4 * 1024 * 1024)
+            .map(|_| rng.random_range(b'a'..=b'p'))
+            .collect::<Vec<u8>>();
+        std::fs::write(path, &data).unwrap();
+        data
+    }
+
+    /// Cart some random data and write it to disk
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to write our cart file to
+    async fn write_cart(path: &Path) -> Vec<u8> {
+        let data = write_data(&path.with_extension("raw"));
+        let mut carted = Vec::new();
+        let mut stream =
+            CartStream::new(BufReader::new(&data[..]), &key(b"SecretCornIsBest")).unwrap();
+        tokio::io::copy(&mut stream, &mut carted).await.unwrap();
+        tokio::fs::write(path, carted).await.unwrap();
+        data
+    }
+
+    /// Uncart a file with the uncart stream the API uses
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the cart file to uncart
+    async fn uncart(path: &Path) -> Vec<u8> {
+        let file = File::open(path).await.unwrap();
+        let mut uncarted = Vec::new();
+        UncartStream::new(BufReader::new(file))
+            .read_to_end(&mut uncarted)
+            .await
+            .unwrap();
+        uncarted
+    }
+
+    /// Cart a file with our test checkpoint size
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The input to cart
+    /// * `input` - The path to the input file
+    /// * `out_path` - The path to write the carted file to
+    /// * `password` - The password to cart with
+    async fn cart_to<R: AsyncRead + AsyncSeek + Unpin>(
+        reader: &mut R,
+        input: &Path,
+        out_path: &Path,
+        password: &[u8; 16],
+    ) -> Result<u64, Error> {
+        let progress = progress_path(out_path);
+        let key = key(password);
+        let result = cart_from(reader, input, out_path, &progress, &key, CHECKPOINT).await;
+        cleanup(result, out_path, &progress).await
+    }
+
+    /// Uncart a file with our test checkpoint size
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The cart file to uncart
+    /// * `input` - The path to the cart file
+    /// * `out_path` - The path to write the uncarted file to
+    async fn uncart_to<R: AsyncRead + AsyncSeek + Unpin>(
+        reader: &mut R,
+        input: &Path,
+        out_path: &Path,
+    ) -> Result<u64, Error> {
+        let progress = progress_path(out_path);
+        let result = uncart_from(reader, input, out_path, &progress, CHECKPOINT).await;
+        cleanup(result, out_path, &progress).await
+    }
+
+    /// Load the progress saved for an output file
+    ///
+    /// # Arguments
+    ///
+    /// * `out_path` - The path to the output file
+    fn saved(out_path: &Path) -> Progress {
+        let progress = std::fs::read(progress_path(out_path)).unwrap();
+        serde_json::from_slice(&progress).unwrap()
+    }
+
+    #[tokio::test]
+    async fn resume_uncart() {
+        let dir = temp_dir("resume-uncart");
+        let cart_path = dir.join("corn.cart");
+        let data = write_cart(&cart_path).await;
+        let cart_len = std::fs::metadata(&cart_path).unwrap().len() as usize;
+        // uncart our file in one go to get a baseline
+        let baseline_path = dir.join("baseline");
+        let mut reader = File::open(&cart_path).await.unwrap();
+        uncart_to(&mut reader, &cart_path, &baseline_path)
+            .await
+            .unwrap();
+        let baseline = std::fs::read(&baseline_path).unwrap();
+        assert_eq!(baseline, data);
+        assert!(!progress_path(&baseline_path).exists());
+        // interrupt our uncart partway through
+        let out_path = dir.join("corn");
+        let mut reader = Interrupted::new(File::open(&cart_path).await.unwrap(), cart_len / 2);
+        assert!(uncart_to(&mut reader, &cart_path, &out_path).await.is_err());
+        // we should have saved a block boundary to resume from
+        let Checkpoint::Uncart(checkpoint) = saved(&out_path).checkpoint else {
+            panic!("Expected an uncart checkpoint");
+        };
+        assert!(checkpoint.written > 0 && checkpoint.read <= cart_len as u64 / 2);
+        // resume our uncart and make sure it matches our baseline
+        let mut reader = Interrupted::new(File::open(&cart_path).await.unwrap(), usize::MAX);
+        let total = uncart_to(&mut reader, &cart_path, &out_path).await.unwrap();
+        assert_eq!(total, baseline.len() as u64);
+        assert_eq!(std::fs::read(&out_path).unwrap(), baseline);
+        assert!(!progress_path(&out_path).exists());
+        // we should have seeked past everything before our checkpoint instead of reading it again
+        let skipped = usize::try_from(checkpoint.read).unwrap() - HEADER_LEN;
+        assert_eq!(reader.read, cart_len - skipped);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resume_cart() {
+        let dir = temp_dir("resume-cart");
+        let input = dir.join("corn");
+        let data = write_data(&input);
+        // interrupt our cart partway through
+        let out_path = dir.join("corn.cart");
+        let mut reader = Interrupted::new(File::open(&input).await.unwrap(), data.len() / 2);
+        let result = cart_to(&mut reader, &input, &out_path, b"SecretCornIsBest").await;
+        assert!(result.is_err());
+        // we should have saved a flush point to resume from
+        let Checkpoint::Cart(checkpoint) = saved(&out_path).checkpoint else {
+            panic!("Expected a cart checkpoint");
+        };
+        assert!(checkpoint.read > 0 && checkpoint.read <= data.len() as u64 / 2);
+        // resume our cart and make sure it uncarts back to our data
+        let mut reader = Interrupted::new(File::open(&input).await.unwrap(), usize::MAX);
+        let total = cart_to(&mut reader, &input, &out_path, b"SecretCornIsBest")
+            .await
+            .unwrap();
+        assert_eq!(total, std::fs::metadata(&out_path).unwrap().len());
+        assert_eq!(uncart(&out_path).await, data);
+        assert!(!progress_path(&out_path).exists());
+        // we should have seeked past everything before our checkpoint instead of reading it again
+        let skipped = usize::try_from(checkpoint.read).unwrap();
+        assert_eq!(reader.read, data.len() - skipped);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn refuse_different_key() {
+        let dir = temp_dir("refuse-different-key");
+        let input = dir.join("corn");
+        write_data(&input);
+        // interrupt a cart with one password
+        let out_path = dir.join("corn.cart");
+        let mut reader = Interrupted::new(File::open(&input).await.unwrap(), 1_000_000);
+        let result = cart_to(&mut reader, &input, &out_path, b"SecretCornIsBest").await;
+        assert!(result.is_err());
+        let partial = std::fs::read(&out_path).unwrap();
+        // resuming with a different password would corrupt our output so it should be refused
+        let mut reader = File::open(&input).await.unwrap();
+        let result = cart_to(&mut reader, &input, &out_path, b"CornIsNotSecret!").await;
+        assert!(result.is_err());
+        // our partial output and progress should be left alone
+        assert_eq!(std::fs::read(&out_path).unwrap(), partial);
+        assert!(progress_path(&out_path).exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn restart_changed_input() {
+        let dir = temp_dir("restart-changed");
+        let cart_path = dir.join("corn.cart");
+        let data = write_cart(&cart_path).await;
+        // save progress from an attempt on an input that has since changed
+        let out_path = dir.join("corn");
+        std::fs::write(&out_path, vec![0; 1024]).unwrap();
+        std::fs::write(
+            progress_path(&out_path),
+            r#"{"input_len":7,"input_modified":null,"key":"","checkpoint":{"kind":"cart","read":7,"written":1024,"adler32":1}}"#,
+        )
+        .unwrap();
+        // our stale progress should be ignored
+        let mut reader = File::open(&cart_path).await.unwrap();
+        uncart_to(&mut reader, &cart_path, &out_path).await.unwrap();
+        assert_eq!(std::fs::read(&out_path).unwrap(), data);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_without_progress() {
+        let dir = temp_dir("failed-without-progress");
+        // a file that isn't a cart file should fail before any progress is saved
+        let cart_path = dir.join("corn.cart");
+        std::fs::write(&cart_path, b"not a cart file").unwrap();
+        let out_path = dir.join("corn");
+        let mut reader = File::open(&cart_path).await.unwrap();
+        assert!(uncart_to(&mut reader, &cart_path, &out_path).await.is_err());
+        // there's nothing to resume so our output shouldn't be left behind
+        assert!(!out_path.exists());
+        assert!(!progress_path(&out_path).exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
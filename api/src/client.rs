@@ -19,6 +19,7 @@ mod helpers;
 mod images;
 mod jobs;
 mod keys;
+mod mcp;
 mod network_policies;
 mod pipelines;
 mod reactions;
@@ -34,16 +35,20 @@ mod utils;
 
 pub use associations::Associations;
 pub use basic::Basic;
-pub use conf::{ClientSettings, CtlConf};
-pub use cursors::{Cursor, LogsCursor, SearchDate};
+pub use conf::{ClientSettings, CtlConf, OperationClass, RetryPolicy};
+pub use cursors::{AdaptivePageSize, Cursor, LogsCursor, NdjsonStream, SearchDate, SearchRange};
 pub use entities::Entities;
 pub use error::Error;
 pub use events::Events;
 pub use files::Files;
 pub use groups::Groups;
+pub use helpers::HttpClient;
+#[doc(hidden)]
+pub use helpers::{VERSION_HEADER, execute, versions_compatible};
 pub use images::Images;
 pub use jobs::Jobs;
 pub use keys::Keys;
+pub use mcp::Mcp;
 pub use network_policies::NetworkPolicies;
 pub use pipelines::Pipelines;
 pub use reactions::Reactions;
@@ -67,6 +72,7 @@ cfg_if::cfg_if! {
         pub use groups::GroupsBlocking;
         pub use images::ImagesBlocking;
         pub use jobs::JobsBlocking;
+        pub use mcp::McpBlocking;
         pub use pipelines::PipelinesBlocking;
         pub use reactions::ReactionsBlocking;
         pub use repos::ReposBlocking;
@@ -243,7 +249,7 @@ impl ThoriumClientBuilder {
     /// ```
     pub async fn build(self) -> Result<Thorium, Error> {
         // build a client
-        let client = helpers::build_reqwest_client(&self.settings).await?;
+        let client = helpers::build_reqwest_client(&self.host, &self.settings).await?;
         // get token if we have a username/password and no token
         let (token, expires) = match (self.token, self.username, self.password) {
             // we already have a token, so use the existing one
@@ -264,7 +270,7 @@ impl ThoriumClientBuilder {
         // build token auth string
         let auth_str = format!("token {encoded}");
         // build handlers
-        let basic = Basic::new(&self.host, &client);
+        let basic = Basic::new(&self.host, &client).token(&auth_str);
         let jobs = Jobs::new(&self.host, &auth_str, &client);
         let reactions = Reactions::new(&self.host, &auth_str, &client);
         let pipelines = Pipelines::new(&self.host, &auth_str, &client);
@@ -282,6 +288,7 @@ impl ThoriumClientBuilder {
         let events = Events::new(&self.host, &auth_str, &client);
         let network_policies = NetworkPolicies::new(&self.host, &auth_str, &client);
         let trees = Trees::new(&self.host, &auth_str, &client);
+        let mcp = Mcp::new(&self.host, &auth_str, &client);
         // build Thorium client
         let client = Thorium {
             basic,
@@ -301,6 +308,7 @@ impl ThoriumClientBuilder {
             events,
             network_policies,
             trees,
+            mcp,
             host: self.host,
             auth_str,
             expires,
@@ -350,6 +358,8 @@ pub struct Thorium {
     pub network_policies: NetworkPolicies,
     /// Handles tree routes in Thorium
     pub trees: Trees,
+    /// Handles MCP routes in Thorium
+    pub mcp: Mcp,
     /// The host/url to reach Thorium at
     pub host: String,
     /// The auth str to use when reverting from a masquerade
@@ -357,7 +367,7 @@ pub struct Thorium {
     /// When our token expires if we have a token
     pub expires: Option<DateTime<Utc>>,
     // keep a copy of our client for faster masquerades and refreshes
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 // define the synchronous, blocking client
@@ -381,6 +391,9 @@ cfg_if::cfg_if! {
             /// Handles reactions routes in Thorium
             #[pyo3(get)]
             pub files: FilesBlocking,
+            /// Handles MCP routes in Thorium
+            #[pyo3(get)]
+            pub mcp: McpBlocking,
             /// The host/url to reach Thorium at
             pub host: String,
             /// The auth str to use when reverting from a masquerade
@@ -388,7 +401,7 @@ cfg_if::cfg_if! {
             /// When our token expires if we have a token
             pub expires: Option<DateTime<Utc>>,
             // keep a copy of our client for faster masquerades and refreshes
-            _client: reqwest::Client,
+            client: HttpClient,
         }
 
         impl ThoriumClientBuilder {
@@ -411,7 +424,7 @@ cfg_if::cfg_if! {
             /// ```
             pub fn build_blocking(self) -> Result<ThoriumBlocking, Error> {
                 // build a client
-                let client = helpers::build_blocking_reqwest_client(&self.settings)?;
+                let client = helpers::build_blocking_reqwest_client(&self.host, &self.settings)?;
                 // get token if we have a username/password and no token
                 let (token, expires) = match (self.token, self.username, self.password) {
                     // we already have a token, so use the existing one
@@ -434,16 +447,18 @@ cfg_if::cfg_if! {
                 let jobs = JobsBlocking::new(&self.host, &auth_str, &client);
                 let reactions = ReactionsBlocking::new(&self.host, &auth_str, &client);
                 let files = FilesBlocking::new(&self.host, &auth_str, &client);
+                let mcp = McpBlocking::new(&self.host, &auth_str, &client);
                 // build Thorium client
                 let client = ThoriumBlocking {
                     basic,
                     jobs,
                     reactions,
                     files,
+                    mcp,
                     host: self.host,
                     _auth_str: auth_str,
                     expires,
-                    _client: client,
+                    client,
                 };
                 Ok(client)
             }
@@ -490,6 +505,8 @@ cfg_if::cfg_if! {
             pub network_policies: NetworkPoliciesBlocking,
             /// Handles tree routes in Thorium
             pub trees: TreesBlocking,
+            /// Handles MCP routes in Thorium
+            pub mcp: McpBlocking,
             /// The host/url to reach Thorium at
             pub host: String,
             /// The auth str to use when reverting from a masquerade
@@ -497,7 +514,7 @@ cfg_if::cfg_if! {
             /// When our token expires if we have a token
             pub expires: Option<DateTime<Utc>>,
             // keep a copy of our client for faster masquerades and refreshes
-            _client: reqwest::Client,
+            client: HttpClient,
         }
 
         impl ThoriumClientBuilder {
@@ -520,7 +537,7 @@ cfg_if::cfg_if! {
             /// ```
             pub fn build_blocking(self) -> Result<ThoriumBlocking, Error> {
                 // build a client
-                let client = helpers::build_blocking_reqwest_client(&self.settings)?;
+                let client = helpers::build_blocking_reqwest_client(&self.host, &self.settings)?;
                 // get token if we have a username/password and no token
                 let (token, expires) = match (self.token, self.username, self.password) {
                     // we already have a token, so use the existing one
@@ -557,6 +574,7 @@ cfg_if::cfg_if! {
                 let events = EventsBlocking::new(&self.host, &auth_str, &client);
                 let network_policies = NetworkPoliciesBlocking::new(&self.host, &auth_str, &client);
                 let trees = TreesBlocking::new(&self.host, &auth_str, &client);
+                let mcp = McpBlocking::new(&self.host, &auth_str, &client);
                 // build Thorium client
                 let client = ThoriumBlocking {
                     basic,
@@ -577,10 +595,11 @@ cfg_if::cfg_if! {
                     events,
                     network_policies,
                     trees,
+                    mcp,
                     host: self.host,
                     _auth_str: auth_str,
                     expires,
-                    _client: client,
+                    client,
                 };
                 Ok(client)
             }
@@ -639,10 +658,11 @@ impl Thorium {
     ///
     /// ```
     /// use thorium::Thorium;
+    /// use thorium::client::HttpClient;
     /// # use thorium::Error;
     ///
     /// # async fn exec() -> Result<(), Error> {
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let (token, expriation) =
     ///     Thorium::auth(
     ///         "http://127.0.0.1",
@@ -661,7 +681,7 @@ impl Thorium {
         host: &str,
         username: &str,
         password: &str,
-        client: &reqwest::Client,
+        client: &HttpClient,
     ) -> Result<(String, Option<DateTime<Utc>>), Error> {
         // create auth handler and get token
         let resp = Users::auth_basic(host, username, password, client).await?;
@@ -709,6 +729,39 @@ impl Thorium {
             .skip_verification();
         Users::create(host, bp, Some(key), settings).await
     }
+
+    /// Get the version of the Thorium API this client is talking to
+    ///
+    /// The version is read from the first response the API sends so this is `None`
+    /// until a request has been made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// let thorium = Thorium::build("http://127.0.0.1")
+    ///     .token("<token>")
+    ///     .build()
+    ///     .await?;
+    /// // make a request so we learn the version of the API
+    /// thorium.basic.ping().await?;
+    /// if let Some(version) = thorium.server_version() {
+    ///     println!("Thorium is running version {version}");
+    /// }
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn server_version(&self) -> Option<semver::Version> {
+        self.client.server_version()
+    }
 }
 
 impl Thorium {
@@ -816,7 +869,7 @@ impl Thorium {
         // build token auth string
         let auth_str = format!("token {encoded}");
         // update handlers
-        self.basic = Basic::new(&self.host, &self.client);
+        self.basic = Basic::new(&self.host, &self.client).token(&auth_str);
         self.jobs = Jobs::new(&self.host, &auth_str, &self.client);
         self.reactions = Reactions::new(&self.host, &auth_str, &self.client);
         self.pipelines = Pipelines::new(&self.host, &auth_str, &self.client);
@@ -830,6 +883,7 @@ impl Thorium {
         self.events = Events::new(&self.host, &auth_str, &self.client);
         self.network_policies = NetworkPolicies::new(&self.host, &auth_str, &self.client);
         self.trees = Trees::new(&self.host, &auth_str, &self.client);
+        self.mcp = Mcp::new(&self.host, &auth_str, &self.client);
         Ok(())
     }
 
@@ -844,7 +898,7 @@ impl Thorium {
         // build token auth string
         let auth_str = format!("token {encoded}");
         // update handlers
-        self.basic = Basic::new(&self.host, &self.client);
+        self.basic = Basic::new(&self.host, &self.client).token(&auth_str);
         self.jobs = Jobs::new(&self.host, &auth_str, &self.client);
         self.reactions = Reactions::new(&self.host, &auth_str, &self.client);
         self.pipelines = Pipelines::new(&self.host, &auth_str, &self.client);
@@ -858,12 +912,13 @@ impl Thorium {
         self.events = Events::new(&self.host, &auth_str, &self.client);
         self.network_policies = NetworkPolicies::new(&self.host, &auth_str, &self.client);
         self.trees = Trees::new(&self.host, &auth_str, &self.client);
+        self.mcp = Mcp::new(&self.host, &auth_str, &self.client);
     }
 
     /// Revert back to our original user from a masquerade
     pub fn revert_masquerade(&mut self) {
         // update handlers
-        self.basic = Basic::new(&self.host, &self.client);
+        self.basic = Basic::new(&self.host, &self.client).token(&self.auth_str);
         self.jobs = Jobs::new(&self.host, &self.auth_str, &self.client);
         self.reactions = Reactions::new(&self.host, &self.auth_str, &self.client);
         self.pipelines = Pipelines::new(&self.host, &self.auth_str, &self.client);
@@ -877,6 +932,7 @@ impl Thorium {
         self.events = Events::new(&self.host, &self.auth_str, &self.client);
         self.network_policies = NetworkPolicies::new(&self.host, &self.auth_str, &self.client);
         self.trees = Trees::new(&self.host, &self.auth_str, &self.client);
+        self.mcp = Mcp::new(&self.host, &self.auth_str, &self.client);
     }
 }
 
@@ -928,13 +984,22 @@ impl ThoriumBlocking {
         host: &str,
         username: &str,
         password: &str,
-        client: &reqwest::Client,
+        client: &HttpClient,
     ) -> Result<(String, Option<DateTime<Utc>>), Error> {
         // create auth handler and get token
         let resp = UsersBlocking::auth_basic(host, username, password, client)?;
         Ok((resp.token, Some(resp.expires)))
     }
 
+    /// Get the version of the Thorium API this client is talking to
+    ///
+    /// The version is read from the first response the API sends so this is `None`
+    /// until a request has been made.
+    #[must_use]
+    pub fn server_version(&self) -> Option<semver::Version> {
+        self.client.server_version()
+    }
+
     /// Create a blocking Thorium client from a path on disk
     ///
     /// # Arguments
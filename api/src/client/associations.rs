@@ -3,7 +3,7 @@
 #[cfg(feature = "trace")]
 use tracing::instrument;
 
-use super::Error;
+use super::{Error, HttpClient};
 use crate::models::AssociationRequest;
 use crate::send;
 
@@ -20,7 +20,7 @@ pub struct Associations {
     /// token to use for auth
     token: String,
     /// A reqwest client for reqwests
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -39,13 +39,13 @@ impl Associations {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Associations;
+    /// use thorium::client::{Associations, HttpClient};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let associations = Associations::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build associations route handler
         Associations {
             host: host.to_owned(),
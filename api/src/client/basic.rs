@@ -1,5 +1,8 @@
-use super::Error;
-use crate::send;
+use std::time::{Duration, Instant};
+
+use super::{Error, HttpClient};
+use crate::models::{Capabilities, ScrubbedUser, UserSummary};
+use crate::{send, send_build};
 
 // import our static runtime if we need a blocking client
 #[cfg(feature = "sync")]
@@ -13,7 +16,9 @@ use pyo3::{pyclass, pymethods};
 #[derive(Clone)]
 pub struct Basic {
     host: String,
-    client: reqwest::Client,
+    /// The token to use for routes that require auth if we have one
+    token: Option<String>,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -28,19 +33,40 @@ impl Basic {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Basic;
+    /// use thorium::client::{Basic, HttpClient};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let basic = Basic::new("http://127.0.0.1", &client);
     /// ```
     #[must_use]
-    pub fn new<T: Into<String>>(host: T, client: &reqwest::Client) -> Self {
+    pub fn new<T: Into<String>>(host: T, client: &HttpClient) -> Self {
         // build basic route handler
         Basic {
             host: host.into(),
+            token: None,
             client: client.clone(),
         }
     }
+
+    /// Set the token to use for the basic routes that require auth
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::client::{Basic, HttpClient};
+    ///
+    /// let client = HttpClient::from(reqwest::Client::new());
+    /// let basic = Basic::new("http://127.0.0.1", &client).token("token");
+    /// ```
+    #[must_use]
+    pub fn token<T: Into<String>>(mut self, token: T) -> Self {
+        self.token = Some(token.into());
+        self
+    }
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct(python))]
@@ -97,4 +123,100 @@ impl Basic {
         // send this request and build a string
         Ok(send!(self.client, req)?.status().is_success())
     }
+
+    /// Get the features and limits of the Thorium API
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// let capabilities = thorium.basic.capabilities().await?;
+    /// // only build lineage trees if the server supports them
+    /// if capabilities.supports("lineage-trees") {
+    ///     // ...
+    /// }
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn capabilities(&self) -> Result<Capabilities, Error> {
+        // build request
+        let req = self
+            .client
+            .get(format!("{}/api/basic/capabilities", self.host));
+        // send this request and build our capabilities
+        send_build!(self.client, req, Capabilities)
+    }
+
+    /// Check that the Thorium API is reachable and get the round trip latency to it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// let latency = thorium.basic.ping().await?;
+    /// println!("Thorium responded in {}ms", latency.as_millis());
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn ping(&self) -> Result<Duration, Error> {
+        // build request
+        let req = self.client.get(format!("{}/api/basic/ping", self.host));
+        // time how long it takes for this request to complete
+        let start = Instant::now();
+        send!(self.client, req)?;
+        Ok(start.elapsed())
+    }
+
+    /// Get a summary of the user we are authenticated as
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// let user = thorium.basic.whoami().await?;
+    /// println!("Authenticated as {}", user.username);
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn whoami(&self) -> Result<UserSummary, Error> {
+        // make sure we have a token to authenticate with
+        let Some(token) = &self.token else {
+            return Err(Error::new("A token is required to get the current user"));
+        };
+        // build request
+        let req = self
+            .client
+            .get(format!("{}/api/users/whoami", self.host))
+            .header("authorization", token);
+        // send this request and summarize the user we got back
+        let user = send_build!(self.client, req, ScrubbedUser)?;
+        Ok(UserSummary::from(user))
+    }
 }
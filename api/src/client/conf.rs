@@ -1,4 +1,7 @@
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::{Method, StatusCode};
 
 use super::Keys;
 use crate::Error;
@@ -32,6 +35,129 @@ pub fn default_client_timeout() -> u64 {
     600
 }
 
+/// Help serde default our max attempts per request to 3
+pub fn default_retry_attempts() -> u32 {
+    3
+}
+
+/// Help serde default our initial retry delay to 250 milliseconds
+pub fn default_retry_base_delay() -> u64 {
+    250
+}
+
+/// Help serde default our max retry delay to 10 seconds
+pub fn default_retry_max_delay() -> u64 {
+    10_000
+}
+
+/// How to retry requests that fail with a transient error
+///
+/// Only idempotent requests (GET/HEAD) are retried unless `retry_non_idempotent` is set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", thorium_derive::pyclass(get_all))]
+pub struct RetryPolicy {
+    /// The max number of attempts to make for a single request including the first one
+    #[serde(default = "default_retry_attempts")]
+    pub max_attempts: u32,
+    /// The number of milliseconds to wait before the first retry
+    #[serde(default = "default_retry_base_delay")]
+    pub base_delay: u64,
+    /// The max number of milliseconds to wait between retries
+    #[serde(default = "default_retry_max_delay")]
+    pub max_delay: u64,
+    /// Whether to also retry non-idempotent requests (POST/PATCH/PUT/DELETE)
+    #[serde(default)]
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    /// Default to a few quick retries of idempotent requests
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: default_retry_attempts(),
+            base_delay: default_retry_base_delay(),
+            max_delay: default_retry_max_delay(),
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Build a policy that never retries
+    #[must_use]
+    pub fn disabled() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Check if a response status is a transient error worth retrying
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status code to check
+    #[must_use]
+    pub fn retryable_status(status: StatusCode) -> bool {
+        matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        )
+    }
+
+    /// Check if requests with a specific method can be retried under this policy
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The method to check
+    #[must_use]
+    pub fn retryable_method(&self, method: &Method) -> bool {
+        self.retry_non_idempotent || *method == Method::GET || *method == Method::HEAD
+    }
+
+    /// Get how long to wait before a retry
+    ///
+    /// This doubles our base delay for each failed attempt up to our max delay and then
+    /// picks a random delay between half and all of that to avoid retrying in lockstep
+    /// with other clients.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - The attempt that just failed starting at 1
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        // double our delay for each failed attempt without overflowing
+        let exp = attempt.saturating_sub(1).min(31);
+        let delay = self.base_delay.saturating_mul(1 << exp).min(self.max_delay);
+        // get some cheap jitter from the current time
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| u64::from(now.subsec_nanos()))
+            .unwrap_or_default();
+        let half = delay / 2;
+        Duration::from_millis(half + nanos % (delay - half + 1))
+    }
+}
+
+/// The number of seconds to wait for uploads if no upload timeout was set
+pub const DEFAULT_UPLOAD_TIMEOUT: u64 = 86_400;
+
+/// The different classes of operations that can have their own timeouts
+///
+/// Each client method sends its requests as one of these classes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationClass {
+    /// Cheap requests for metadata like getting a reaction
+    Metadata,
+    /// Requests that upload files with a multipart form
+    Upload,
+    /// Requests that download files or archives
+    Download,
+}
+
 /// The config options for our [`reqwest::Client`]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(feature = "python", thorium_derive::pyclass(get_all))]
@@ -48,6 +174,36 @@ pub struct ClientSettings {
     /// The number of seconds to wait before timing out
     #[serde(default = "default_client_timeout")]
     pub timeout: u64,
+    /// The number of seconds to wait for metadata requests (defaults to `timeout`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_timeout: Option<u64>,
+    /// The number of seconds to wait for file uploads (defaults to a day)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_timeout: Option<u64>,
+    /// The number of seconds to wait for file downloads (defaults to `timeout`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_timeout: Option<u64>,
+    /// How to retry requests that fail with a transient error
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Talk HTTP/2 without negotiating it first (only valid for plain http hosts)
+    #[serde(default)]
+    pub http2_prior_knowledge: bool,
+    /// The number of seconds between HTTP/2 keep-alive pings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http2_keep_alive_interval: Option<u64>,
+    /// The number of seconds between TCP keep-alive probes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_keepalive: Option<u64>,
+    /// The number of seconds to keep idle connections open for reuse
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_idle_timeout: Option<u64>,
+    /// The max number of idle connections to keep open per host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Fail requests to a Thorium API whose version is not compatible with this client
+    #[serde(default)]
+    pub strict_version: bool,
 }
 
 impl Default for ClientSettings {
@@ -58,10 +214,81 @@ impl Default for ClientSettings {
             invalid_hostnames: false,
             certificate_authorities: Vec::default(),
             timeout: default_client_timeout(),
+            metadata_timeout: None,
+            upload_timeout: None,
+            download_timeout: None,
+            retry: RetryPolicy::default(),
+            http2_prior_knowledge: false,
+            http2_keep_alive_interval: None,
+            tcp_keepalive: None,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            strict_version: false,
         }
     }
 }
 
+impl ClientSettings {
+    /// Get the timeout that was explicitly set for a class of operation
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - The class of operation to get a timeout for
+    #[must_use]
+    pub fn granular_timeout(&self, class: OperationClass) -> Option<Duration> {
+        let secs = match class {
+            OperationClass::Metadata => self.metadata_timeout,
+            OperationClass::Upload => self.upload_timeout,
+            OperationClass::Download => self.download_timeout,
+        };
+        secs.map(Duration::from_secs)
+    }
+
+    /// Make sure our transport settings can be used together to talk to a host
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The host we will be talking to
+    pub fn validate_transport(&self, host: &str) -> Result<(), Error> {
+        // https hosts negotiate HTTP/2 with ALPN during the TLS handshake
+        if self.http2_prior_knowledge && host.starts_with("https://") {
+            return Err(Error::new(format!(
+                "HTTP/2 prior knowledge can't be used with the TLS host '{host}'; \
+                 HTTP/2 is negotiated automatically over TLS"
+            )));
+        }
+        // keep-alive intervals of zero would flood the API with probes
+        if self.tcp_keepalive == Some(0) || self.http2_keep_alive_interval == Some(0) {
+            return Err(Error::new("Keep-alive intervals must be greater than 0"));
+        }
+        // an idle timeout is meaningless if we never keep idle connections around
+        if self.pool_max_idle_per_host == Some(0) && self.pool_idle_timeout.is_some() {
+            return Err(Error::new(
+                "pool_idle_timeout can't be set when pool_max_idle_per_host is 0",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Get the timeout to use for a class of operation
+    ///
+    /// This falls back to a day for uploads and our single `timeout` for everything else
+    /// if no granular timeout was set.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - The class of operation to get a timeout for
+    #[must_use]
+    pub fn timeout_for(&self, class: OperationClass) -> Duration {
+        let fallback = match class {
+            OperationClass::Upload => DEFAULT_UPLOAD_TIMEOUT,
+            OperationClass::Metadata | OperationClass::Download => self.timeout,
+        };
+        self.granular_timeout(class)
+            .unwrap_or_else(|| Duration::from_secs(fallback))
+    }
+}
+
 /// Provide a default default editor for serde
 #[must_use]
 pub fn default_default_editor() -> String {
@@ -1,7 +1,17 @@
+use chrono::TimeDelta;
 use chrono::prelude::*;
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt, TryStreamExt};
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio_util::codec::{FramedRead, LinesCodec};
+use tokio_util::io::StreamReader;
 
-use super::Error;
+use super::{Error, HttpClient};
 use crate::models::StageLogs;
 use crate::send_build;
 
@@ -26,7 +36,7 @@ where
     /// The url used to build/rehydrate this cursor
     pub url: String,
     /// The reqwest client used get data
-    client: reqwest::Client,
+    client: HttpClient,
     /// token to use for auth
     token: String,
     /// The cursor we will use for the next hydration requestion
@@ -40,12 +50,18 @@ where
     /// Whether the cursor has reached its limit or the API has run out of
     /// items to return
     pub exhausted: bool,
+    /// Whether this cursor hit its limit before the API ran out of items to return
+    pub truncated: bool,
     /// The names returned by this cursor
     pub names: Vec<String>,
     /// The details returned by this cursor if details is enabled
     pub details: Vec<T>,
     /// Whether this cursor should retry on transient errors
     pub retry: bool,
+    /// A json body to POST with each request instead of using GET
+    body: Option<serde_json::Value>,
+    /// Adapts our page size to how quickly the API responds if set
+    pub adaptive: Option<AdaptivePageSize>,
 }
 
 impl<T> Cursor<T>
@@ -63,7 +79,7 @@ where
     /// * `token` - The authentication token used for this cursor
     /// * `client` - The client this cursor should use
     #[must_use]
-    pub fn new(url: String, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(url: String, token: &str, client: &HttpClient) -> Self {
         Cursor {
             url,
             client: client.clone(),
@@ -73,12 +89,26 @@ where
             retrieved: 0,
             limit: None,
             exhausted: false,
+            truncated: false,
             names: Vec::default(),
             details: Vec::default(),
             retry: true,
+            body: None,
+            adaptive: None,
         }
     }
 
+    /// Set a json body to POST with each request for this cursor
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The body to send with each request
+    #[must_use]
+    pub(crate) fn body(mut self, body: serde_json::Value) -> Self {
+        self.body = Some(body);
+        self
+    }
+
     /// Sets the new cursor value to use in the next request
     ///
     /// # Arguments
@@ -98,6 +128,31 @@ where
     #[must_use]
     pub fn page_size(mut self, page_size: u64) -> Self {
         self.page_size = page_size;
+        // our page size is the largest page an adaptive cursor can grow to
+        if let Some(adaptive) = self.adaptive.take() {
+            self.adaptive = Some(AdaptivePageSize::new(
+                page_size,
+                adaptive.min,
+                adaptive.slow,
+            ));
+        }
+        self
+    }
+
+    /// Adapt the size of this cursors pages to how quickly the API responds
+    ///
+    /// Pages start at this cursors page size and shrink when requests time out, fail,
+    /// or take longer than `slow`. They grow back to our page size once the API is
+    /// responding quickly again. Pages that time out are retried at a smaller size if
+    /// retries are enabled and a smaller page can still be requested.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - The smallest page size to shrink to
+    /// * `slow` - How long a page can take before we shrink our page size
+    #[must_use]
+    pub fn adaptive(mut self, min: u64, slow: Duration) -> Self {
+        self.adaptive = Some(AdaptivePageSize::new(self.page_size, min, slow));
         self
     }
 
@@ -145,30 +200,54 @@ where
     /// Get the next page of data for this cursor
     #[cfg_attr(feature = "sync", thorium_derive::blocking_fn)]
     pub async fn next(&mut self) -> Result<(), Error> {
-        // make sure our page is not larger then our limit
-        let page_size = match self.limit {
-            Some(limit) => std::cmp::min(self.page_size, limit - self.retrieved),
-            None => self.page_size,
-        };
         // retry sending our request for new data on transient errors if enabled
         let mut raw = loop {
-            // build request
-            let req = self
-                .client
-                .get(&self.url)
+            // use our adaptive page size if we have one
+            let page_size = self
+                .adaptive
+                .as_ref()
+                .map_or(self.page_size, AdaptivePageSize::current);
+            // make sure our page is not larger then our limit
+            let page_size = match self.limit {
+                Some(limit) => std::cmp::min(page_size, limit - self.retrieved),
+                None => page_size,
+            };
+            // build request posting our body if we have one
+            let req = match &self.body {
+                Some(body) => self.client.post(&self.url).json(body),
+                None => self.client.get(&self.url),
+            };
+            let req = req
                 .header("authorization", &self.token)
                 .query(&[("cursor", self.cursor), ("limit", page_size)]);
             // send request and build a raw cursor
+            let start = Instant::now();
             match send_build!(self.client, req, RawCursorData<T>) {
-                Ok(raw) => break raw,
+                Ok(raw) => {
+                    // adapt our page size to how long this page took
+                    if let Some(adaptive) = self.adaptive.as_mut() {
+                        adaptive.observe(start.elapsed());
+                    }
+                    break raw;
+                }
                 Err(error) => {
                     // if retry is enabled then check if we should retry or just fail
                     if self.retry {
                         // determine if this error could be transient or not
-                        if error
+                        let server_error = error
                             .status()
-                            .is_some_and(|status| status.is_server_error())
-                        {
+                            .is_some_and(|status| status.is_server_error());
+                        // only pages that timed out after reaching the API can be helped by
+                        // requesting less data
+                        let page_timeout = error.is_timeout() && !error.is_connect();
+                        // shrink our page size if the API is struggling and retry only if
+                        // our next page will actually be smaller
+                        let shrunk = (server_error || page_timeout)
+                            && self
+                                .adaptive
+                                .as_mut()
+                                .is_some_and(|adaptive| adaptive.shrink_below(page_size));
+                        if server_error || shrunk {
                             continue;
                         }
                     }
@@ -186,13 +265,117 @@ where
         match (raw.cursor, self.limit) {
             // only set the new cursor if we haven't hit our limit
             (Some(cursor), Some(limit)) if self.retrieved < limit => self.cursor = cursor,
+            // we hit our limit but the API has more data so save where it can be resumed from
+            (Some(cursor), Some(_)) => {
+                self.cursor = cursor;
+                self.truncated = true;
+                self.exhausted = true;
+            }
             // we have no limit, so set the new cursor
             (Some(cursor), None) => self.cursor = cursor,
             // otherwise this cursor is exhausted
-            (_, _) => self.exhausted = true,
+            (None, _) => self.exhausted = true,
         }
         Ok(())
     }
+
+    /// Get the cursor value to resume listing from if the API has more data
+    ///
+    /// This is set either when this cursor still has pages left or when it was cut off by its
+    /// limit before the API ran out of data.
+    #[must_use]
+    pub fn next_cursor(&self) -> Option<u64> {
+        if !self.exhausted || self.truncated {
+            Some(self.cursor)
+        } else {
+            None
+        }
+    }
+}
+
+/// Adapts the size of a cursor's pages to how quickly the API responds
+///
+/// Page sizes are halved when a page fails or is slow and doubled once pages
+/// are fast again. Page sizes always stay between a min and max size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdaptivePageSize {
+    /// The page size to use for the next page
+    current: u64,
+    /// The smallest page size to shrink to
+    min: u64,
+    /// The largest page size to grow to
+    max: u64,
+    /// How long a page can take before we shrink our page size
+    slow: Duration,
+}
+
+impl AdaptivePageSize {
+    /// Create a new adaptive page size starting at its max size
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The largest page size to grow to
+    /// * `min` - The smallest page size to shrink to
+    /// * `slow` - How long a page can take before we shrink our page size
+    #[must_use]
+    pub fn new(max: u64, min: u64, slow: Duration) -> Self {
+        // always request at least one item
+        let max = max.max(1);
+        AdaptivePageSize {
+            current: max,
+            min: min.clamp(1, max),
+            max,
+            slow,
+        }
+    }
+
+    /// Get the page size to use for the next page
+    #[must_use]
+    pub fn current(&self) -> u64 {
+        self.current
+    }
+
+    /// Shrink our page size after a page failed
+    ///
+    /// Returns false if we were already at our min page size.
+    pub fn shrink(&mut self) -> bool {
+        let shrunk = (self.current / 2).max(self.min);
+        let changed = shrunk != self.current;
+        self.current = shrunk;
+        changed
+    }
+
+    /// Shrink our page size until it is smaller then a page that failed
+    ///
+    /// Returns false if we can't shrink below the failed page without going past our
+    /// min page size.
+    ///
+    /// # Arguments
+    ///
+    /// * `failed` - The size of the page that failed
+    pub fn shrink_below(&mut self, failed: u64) -> bool {
+        while self.current >= failed {
+            if !self.shrink() {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Adapt our page size to how long our last page took
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - How long our last page took
+    pub fn observe(&mut self, elapsed: Duration) {
+        if elapsed >= self.slow {
+            // this page was slow so make our next page smaller
+            self.shrink();
+        } else if elapsed <= self.slow / 2 {
+            // the API is responding quickly so start recovering our page size
+            self.current = self.current.saturating_mul(2).min(self.max);
+        }
+    }
 }
 
 /// A cursor for basic searches with of stage logs
@@ -200,7 +383,7 @@ pub struct LogsCursor {
     /// The url used to build/rehydrate this cursor
     pub url: String,
     /// The reqwest client used get data
-    client: reqwest::Client,
+    client: HttpClient,
     /// token to use for auth
     token: String,
     /// The cursor we will use for the next hydration requestion
@@ -228,7 +411,7 @@ impl LogsCursor {
     /// * `url` - The url we will be using to build/rehydrate this cursor
     /// * `token` - The authentication token used for this cursor
     /// * `client` - The client this cursor should use
-    pub fn new(url: String, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(url: String, token: &str, client: &HttpClient) -> Self {
         Self {
             url,
             client: client.clone(),
@@ -310,7 +493,71 @@ impl LogsCursor {
     }
 }
 
+/// A stream of items sent by the API as newline delimited JSON
+///
+/// Items are deserialized as they arrive instead of waiting for an entire page. Cursors
+/// are numbers by default but can be any type that is parsed from a string like a uuid.
+pub struct NdjsonStream<T, C = usize> {
+    /// The cursor to use to get the next page of items if more exist
+    pub cursor: Option<C>,
+    /// The items being streamed from the API
+    items: BoxStream<'static, Result<T, Error>>,
+}
+
+impl<T: DeserializeOwned + Send + 'static, C: FromStr> NdjsonStream<T, C> {
+    /// Create a new stream from a newline delimited JSON response
+    ///
+    /// # Arguments
+    ///
+    /// * `resp` - The response to stream items from
+    pub(crate) fn new(resp: reqwest::Response) -> Self {
+        // get the cursor for the next page if one was returned
+        let cursor = resp
+            .headers()
+            .get("x-thorium-cursor")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        // split our body into lines as it arrives
+        let body = resp.bytes_stream().map_err(std::io::Error::other);
+        let lines = FramedRead::new(StreamReader::new(body), LinesCodec::new());
+        // deserialize each line into an item
+        let items = lines
+            .map(|line| {
+                let line = line.map_err(|err| Error::new(format!("Failed to read line: {err}")))?;
+                Ok(serde_json::from_str(&line)?)
+            })
+            .boxed();
+        NdjsonStream { cursor, items }
+    }
+}
+
+impl<T, C: Unpin> Stream for NdjsonStream<T, C> {
+    type Item = Result<T, Error>;
+
+    /// Get the next item from this stream
+    ///
+    /// # Arguments
+    ///
+    /// * `cx` - The context for this task
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.items.poll_next_unpin(cx)
+    }
+}
+
+/// A range of time to restrict a search to
+///
+/// Cursors list data from newest to oldest so `start` is always the newer bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "python", thorium_derive::pyclass(get_all))]
+pub struct SearchRange {
+    /// The newest date to start listing data from
+    pub start: DateTime<Utc>,
+    /// The oldest date to stop listing data at
+    pub end: DateTime<Utc>,
+}
+
 /// Build a specific date for a file search restriction
+#[cfg_attr(feature = "python", pyo3::pyclass(skip_from_py_object))]
 pub struct SearchDate;
 
 impl SearchDate {
@@ -347,4 +594,367 @@ impl SearchDate {
         };
         Ok(DateTime::parse_from_rfc3339(&raw)?.with_timezone(&Utc))
     }
+
+    /// Build a search range covering the last number of days
+    ///
+    /// # Arguments
+    ///
+    /// * `days` - The number of days to search back from now
+    pub fn last_days(days: i64) -> Result<SearchRange, Error> {
+        Self::last_days_with(Utc::now, days)
+    }
+
+    /// Build a search range covering the last number of days using a specific clock
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock to get the current time from
+    /// * `days` - The number of days to search back from now
+    pub fn last_days_with<C: Fn() -> DateTime<Utc>>(
+        clock: C,
+        days: i64,
+    ) -> Result<SearchRange, Error> {
+        // make sure we aren't searching into the future
+        if days < 0 {
+            return Err(Error::new(format!(
+                "Cannot search the last {days} days; the number of days must be positive"
+            )));
+        }
+        Ok(Self::back_from(clock(), TimeDelta::try_days(days)))
+    }
+
+    /// Build a search range covering the last number of hours
+    ///
+    /// # Arguments
+    ///
+    /// * `hours` - The number of hours to search back from now
+    pub fn last_hours(hours: i64) -> Result<SearchRange, Error> {
+        Self::last_hours_with(Utc::now, hours)
+    }
+
+    /// Build a search range covering the last number of hours using a specific clock
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock to get the current time from
+    /// * `hours` - The number of hours to search back from now
+    pub fn last_hours_with<C: Fn() -> DateTime<Utc>>(
+        clock: C,
+        hours: i64,
+    ) -> Result<SearchRange, Error> {
+        // make sure we aren't searching into the future
+        if hours < 0 {
+            return Err(Error::new(format!(
+                "Cannot search the last {hours} hours; the number of hours must be positive"
+            )));
+        }
+        Ok(Self::back_from(clock(), TimeDelta::try_hours(hours)))
+    }
+
+    /// Build a search range between two dates
+    ///
+    /// # Arguments
+    ///
+    /// * `oldest` - The oldest date to search
+    /// * `newest` - The newest date to search
+    pub fn between(oldest: DateTime<Utc>, newest: DateTime<Utc>) -> Result<SearchRange, Error> {
+        // make sure our range isn't backwards
+        if newest < oldest {
+            return Err(Error::new(format!(
+                "Cannot search between {oldest} and {newest}; the oldest date must come first"
+            )));
+        }
+        // no data in Thorium can be older then the unix epoch
+        Ok(SearchRange {
+            start: newest.max(DateTime::UNIX_EPOCH),
+            end: oldest.max(DateTime::UNIX_EPOCH),
+        })
+    }
+
+    /// Build a search range going back some amount of time from now
+    ///
+    /// Ranges that would go back past the unix epoch are clamped to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    /// * `delta` - How far back to search if it could be represented
+    fn back_from(now: DateTime<Utc>, delta: Option<TimeDelta>) -> SearchRange {
+        let end = delta
+            .and_then(|delta| now.checked_sub_signed(delta))
+            .map_or(DateTime::UNIX_EPOCH, |end| end.max(DateTime::UNIX_EPOCH));
+        SearchRange { start: now, end }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use chrono::prelude::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::{AdaptivePageSize, Cursor, SearchDate, SearchRange};
+    use crate::client::HttpClient;
+
+    /// A fixed clock to compute our ranges from
+    fn clock() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 15, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn last_days() {
+        let range = SearchDate::last_days_with(clock, 7).unwrap();
+        assert_eq!(
+            range,
+            SearchRange {
+                start: clock(),
+                end: Utc.with_ymd_and_hms(2025, 6, 8, 12, 0, 0).unwrap(),
+            }
+        );
+        // a zero day range is just the current time
+        let range = SearchDate::last_days_with(clock, 0).unwrap();
+        assert_eq!(range.start, range.end);
+    }
+
+    #[test]
+    fn last_hours() {
+        let range = SearchDate::last_hours_with(clock, 36).unwrap();
+        assert_eq!(range.start, clock());
+        assert_eq!(range.end, clock() - TimeDelta::hours(36));
+    }
+
+    #[test]
+    fn clamped() {
+        // ranges going back before the unix epoch are clamped to it
+        let range = SearchDate::last_days_with(clock, 1_000_000).unwrap();
+        assert_eq!(range.end, DateTime::UNIX_EPOCH);
+        let range = SearchDate::last_hours_with(clock, i64::MAX).unwrap();
+        assert_eq!(range.end, DateTime::UNIX_EPOCH);
+        let oldest = Utc.with_ymd_and_hms(1960, 1, 1, 0, 0, 0).unwrap();
+        let range = SearchDate::between(oldest, clock()).unwrap();
+        assert_eq!(range.end, DateTime::UNIX_EPOCH);
+    }
+
+    #[test]
+    fn negative() {
+        assert!(SearchDate::last_days_with(clock, -1).is_err());
+        assert!(SearchDate::last_hours_with(clock, -1).is_err());
+        // ranges that end before they start are negative too
+        let oldest = clock() - TimeDelta::days(1);
+        assert!(SearchDate::between(clock(), oldest).is_err());
+        let range = SearchDate::between(oldest, clock()).unwrap();
+        assert_eq!(range.start, clock());
+        assert_eq!(range.end, oldest);
+    }
+
+    /// A stub backend that times out on pages larger then it can serve
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The largest page this backend can serve before timing out
+    /// * `page_size` - The size of the page being requested
+    fn stub_backend(capacity: u64, page_size: u64) -> Option<Duration> {
+        if page_size > capacity {
+            None
+        } else {
+            Some(Duration::from_millis(page_size / 10))
+        }
+    }
+
+    /// Get a page from our stub backend and adapt to how it responded
+    ///
+    /// # Arguments
+    ///
+    /// * `adaptive` - The adaptive page size to use
+    /// * `capacity` - The largest page our backend can serve before timing out
+    fn get_page(adaptive: &mut AdaptivePageSize, capacity: u64) -> bool {
+        match stub_backend(capacity, adaptive.current()) {
+            Some(elapsed) => {
+                adaptive.observe(elapsed);
+                true
+            }
+            None => {
+                adaptive.shrink();
+                false
+            }
+        }
+    }
+
+    #[test]
+    fn adaptive_shrink() {
+        let mut adaptive = AdaptivePageSize::new(1000, 50, Duration::from_secs(1));
+        assert_eq!(adaptive.current(), 1000);
+        // our backend is struggling so it can only serve small pages
+        assert!(!get_page(&mut adaptive, 200));
+        assert!(!get_page(&mut adaptive, 200));
+        assert!(!get_page(&mut adaptive, 200));
+        assert_eq!(adaptive.current(), 125);
+        assert!(get_page(&mut adaptive, 200));
+        // we never shrink past our min page size
+        for _ in 0..10 {
+            get_page(&mut adaptive, 0);
+        }
+        assert_eq!(adaptive.current(), 50);
+        assert!(!adaptive.shrink());
+    }
+
+    #[test]
+    fn adaptive_recover() {
+        let mut adaptive = AdaptivePageSize::new(1000, 50, Duration::from_secs(1));
+        for _ in 0..3 {
+            adaptive.shrink();
+        }
+        assert_eq!(adaptive.current(), 125);
+        // our backend has recovered so our page size should grow back to its max
+        for _ in 0..5 {
+            assert!(get_page(&mut adaptive, 10_000));
+        }
+        assert_eq!(adaptive.current(), 1000);
+        // slow pages should shrink our page size without a timeout
+        adaptive.observe(Duration::from_secs(2));
+        assert_eq!(adaptive.current(), 500);
+        // pages that are neither fast or slow leave our page size alone
+        adaptive.observe(Duration::from_millis(750));
+        assert_eq!(adaptive.current(), 500);
+    }
+
+    #[test]
+    fn adaptive_bounds() {
+        // our min can't be larger then our max and we always request something
+        let adaptive = AdaptivePageSize::new(10, 100, Duration::from_secs(1));
+        assert_eq!(adaptive.current(), 10);
+        assert_eq!(adaptive.min, 10);
+        let mut adaptive = AdaptivePageSize::new(0, 0, Duration::from_secs(1));
+        assert_eq!(adaptive.current(), 1);
+        assert!(!adaptive.shrink());
+    }
+
+    #[test]
+    fn adaptive_shrink_below() {
+        let mut adaptive = AdaptivePageSize::new(1000, 50, Duration::from_secs(1));
+        // pages clamped by a limit need to shrink until they are smaller then that page
+        assert!(adaptive.shrink_below(300));
+        assert_eq!(adaptive.current(), 250);
+        // we can't shrink below a page that is already at our min
+        assert!(!adaptive.shrink_below(50));
+        assert_eq!(adaptive.current(), 50);
+    }
+
+    /// Spawn a stub Thorium that times out on pages larger then it can serve
+    ///
+    /// Returns the url to list from and the page sizes that were requested.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The largest page this stub can serve before timing out
+    /// * `total` - The total number of items to list
+    async fn timeout_server(capacity: u64, total: u64) -> (String, Arc<Mutex<Vec<u64>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let tracker = requested.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let tracker = tracker.clone();
+                tokio::spawn(async move {
+                    let mut buff = [0; 4096];
+                    let read = stream.read(&mut buff).await.unwrap_or(0);
+                    let req = String::from_utf8_lossy(&buff[..read]);
+                    // get our cursor and page size from the query in our request line
+                    let query = req
+                        .split_whitespace()
+                        .nth(1)
+                        .and_then(|path| path.split_once('?'))
+                        .map(|(_, query)| query.to_owned())
+                        .unwrap_or_default();
+                    let param = |key: &str| {
+                        query
+                            .split('&')
+                            .filter_map(|pair| pair.split_once('='))
+                            .find(|(name, _)| *name == key)
+                            .and_then(|(_, value)| value.parse::<u64>().ok())
+                            .unwrap_or_default()
+                    };
+                    let (cursor, limit) = (param("cursor"), param("limit"));
+                    tracker.lock().unwrap().push(limit);
+                    // hang on pages larger then we can serve until the client times out
+                    if limit > capacity {
+                        tokio::time::sleep(Duration::from_secs(30)).await;
+                        return;
+                    }
+                    let end = (cursor + limit).min(total);
+                    let names = (cursor..end).map(|i| i.to_string()).collect::<Vec<_>>();
+                    let next = (end < total).then_some(end);
+                    let body = serde_json::json!({"cursor": next, "names": names}).to_string();
+                    let resp = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    let _ = stream.write_all(resp.as_bytes()).await;
+                });
+            }
+        });
+        (format!("http://{addr}/api/files/"), requested)
+    }
+
+    /// Build a client that times out quickly so our tests don't take forever
+    fn impatient_client() -> HttpClient {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(250))
+            .build()
+            .unwrap();
+        HttpClient::from(client)
+    }
+
+    #[tokio::test]
+    async fn cursor_shrinks_on_timeout() {
+        let (url, requested) = timeout_server(100, 300).await;
+        let client = impatient_client();
+        let mut cursor = Cursor::<String>::new(url, "token", &client)
+            .page_size(400)
+            .adaptive(25, Duration::from_secs(5));
+        let mut names = Vec::new();
+        while !cursor.exhausted {
+            cursor.next().await.unwrap();
+            names.append(&mut cursor.names);
+        }
+        let expected = (0..300).map(|i| i.to_string()).collect::<Vec<_>>();
+        assert_eq!(names, expected);
+        // pages that timed out are retried smaller and fast pages grow back
+        assert_eq!(
+            *requested.lock().unwrap(),
+            vec![400, 200, 100, 200, 100, 200, 100]
+        );
+    }
+
+    #[tokio::test]
+    async fn cursor_timeout_not_retried() {
+        // cursors that don't adapt their page size don't retry timeouts
+        let (url, requested) = timeout_server(10, 300).await;
+        let client = impatient_client();
+        let mut cursor = Cursor::<String>::new(url, "token", &client).page_size(100);
+        let err = cursor.next().await.unwrap_err();
+        assert!(err.is_timeout());
+        assert_eq!(*requested.lock().unwrap(), vec![100]);
+        // cursors give up once they can't shrink their pages any further
+        let (url, requested) = timeout_server(10, 300).await;
+        let mut cursor = Cursor::<String>::new(url, "token", &client)
+            .page_size(100)
+            .adaptive(25, Duration::from_secs(5));
+        let err = cursor.next().await.unwrap_err();
+        assert!(err.is_timeout());
+        assert_eq!(*requested.lock().unwrap(), vec![100, 50, 25]);
+        // cursors don't retry timeouts when retries are disabled
+        let (url, requested) = timeout_server(10, 300).await;
+        let mut cursor = Cursor::<String>::new(url, "token", &client)
+            .page_size(100)
+            .adaptive(25, Duration::from_secs(5));
+        cursor.retry = false;
+        assert!(cursor.next().await.unwrap_err().is_timeout());
+        assert_eq!(*requested.lock().unwrap(), vec![100]);
+    }
 }
@@ -1,11 +1,16 @@
 //! The client for entities in Thorium
 
+use http::StatusCode;
 #[cfg(feature = "trace")]
 use tracing::instrument;
 use uuid::Uuid;
 
-use super::Error;
-use crate::models::{Cursor, Entity, EntityListOpts, EntityRequest, EntityResponse, EntityUpdate};
+use super::{Error, HttpClient, NdjsonStream, OperationClass};
+use crate::models::{
+    Association, AssociationListOpts, BulkEntityResponse, Cursor, DeviceListOpts, Entity,
+    EntityDuplicateOpts, EntityDuplicateReport, EntityGraph, EntityGraphOpts, EntityListLine,
+    EntityListOpts, EntityRequest, EntityResponse, EntityUpdate, GraphFormat,
+};
 use crate::{
     add_date, add_query, add_query_bool, add_query_list, add_query_list_clone, send, send_build,
 };
@@ -14,6 +19,59 @@ use crate::{
 #[cfg(feature = "sync")]
 use super::RUNTIME;
 
+/// Build the query params for exporting an entity graph
+///
+/// # Arguments
+///
+/// * `opts` - The options for this graph export
+/// * `format` - The format to export this graph in
+fn graph_query(opts: &EntityGraphOpts, format: GraphFormat) -> Vec<(String, String)> {
+    let mut query = vec![
+        ("format".to_owned(), format.to_string()),
+        ("max_nodes".to_owned(), opts.max_nodes.to_string()),
+        ("max_links".to_owned(), opts.max_links.to_string()),
+    ];
+    add_query_list!(query, "entities[]".to_owned(), opts.entities);
+    add_query_list!(query, "groups[]".to_owned(), opts.groups);
+    add_query_list!(query, "kinds[]".to_owned(), opts.kinds);
+    query
+}
+
+/// Build the query params for listing entity details
+///
+/// # Arguments
+///
+/// * `opts` - The options for this entity list
+fn list_details_query(opts: &EntityListOpts) -> Vec<(String, String)> {
+    // get the correct page size if our limit is smaller then our page_size
+    let page_size = opts.limit.map_or_else(
+        || opts.page_size,
+        |limit| std::cmp::min(opts.page_size, limit),
+    );
+    // build our query params
+    let mut query = vec![("limit".to_owned(), page_size.to_string())];
+    add_query_list!(query, "groups[]".to_owned(), opts.groups);
+    add_query!(query, "start".to_owned(), opts.start);
+    add_query!(query, "end".to_owned(), opts.end);
+    add_query!(query, "cursor".to_owned(), opts.cursor);
+    // add our tag query params
+    for (key, values) in &opts.tags {
+        // build the key for this tag param
+        let query_key = format!("tags[{key}][]");
+        // add this tag keys filters to our query params
+        add_query_list_clone!(query, query_key, values);
+    }
+    add_query_bool!(
+        query,
+        "tags_case_insensitive".to_owned(),
+        opts.tags_case_insensitive
+    );
+    add_query!(query, "partition_hint".to_owned(), opts.partition_hint);
+    add_query!(query, "submitter".to_owned(), opts.submitter);
+    add_query_bool!(query, "include_archived".to_owned(), opts.include_archived);
+    query
+}
+
 /// A handler for the entities routes in Thorium
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
 #[derive(Clone)]
@@ -23,7 +81,7 @@ pub struct Entities {
     /// token to use for auth
     token: String,
     /// A reqwest client for reqwests
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -42,13 +100,13 @@ impl Entities {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Entities;
+    /// use thorium::client::{Entities, HttpClient};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let entities = Entities::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Entities {
             host: host.to_owned(),
@@ -100,7 +158,221 @@ impl Entities {
             .multipart(entity_req.to_form()?)
             .header("authorization", &self.token);
         // send this request
-        send_build!(self.client, req, EntityResponse)
+        send_build!(self.client, req, EntityResponse, OperationClass::Upload)
+    }
+
+    /// Creates [`Entity`]s in bulk in Thorium
+    ///
+    /// Entities with images must be created one at a time with [`Entities::create`].
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_reqs` - The entity requests to use to add entities to Thorium
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use thorium::models::{EntityRequest, EntityMetadataRequest};
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create a Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build the entity requests
+    /// let reqs = vec![
+    ///     EntityRequest::new("sponge", EntityMetadataRequest::Other, vec!("bob".to_owned())),
+    ///     EntityRequest::new("squid", EntityMetadataRequest::Other, vec!("bob".to_owned())),
+    /// ];
+    /// // try to create our entities in Thorium
+    /// let resp = thorium.entities.create_bulk(&reqs).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Entities::create_bulk", skip_all, err(Debug))
+    )]
+    pub async fn create_bulk(
+        &self,
+        entity_reqs: &[EntityRequest],
+    ) -> Result<BulkEntityResponse, Error> {
+        // build url for creating entities in bulk
+        let url = format!("{base}/api/entities/bulk/", base = self.host);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .json(entity_reqs)
+            .header("authorization", &self.token);
+        // send this request
+        send_build!(self.client, req, BulkEntityResponse)
+    }
+
+    /// Gets an [`Entity`] from Thorium
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the entity to get
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use uuid::Uuid;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create a Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // try to get an entity from Thorium
+    /// thorium.entities.get(Uuid::new_v4()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Entities::get", skip_all, err(Debug))
+    )]
+    pub async fn get(&self, id: Uuid) -> Result<Entity, Error> {
+        // build url for getting an entity
+        let url = format!("{base}/api/entities/{id}", base = self.host);
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request
+        send_build!(self.client, req, Entity)
+    }
+
+    /// Archives an [`Entity`] in Thorium
+    ///
+    /// Archived entities are hidden from listings but can still be retrieved and restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the entity to archive
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use uuid::Uuid;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create a Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // try to archive an entity in Thorium
+    /// thorium.entities.delete(Uuid::new_v4()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Entities::delete", skip_all, err(Debug))
+    )]
+    pub async fn delete(&self, id: Uuid) -> Result<reqwest::Response, Error> {
+        // build url for archiving an entity
+        let url = format!("{base}/api/entities/{id}", base = self.host);
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
+
+    /// Restores an archived [`Entity`] in Thorium
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the entity to restore
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use uuid::Uuid;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create a Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // try to restore an archived entity in Thorium
+    /// thorium.entities.restore(Uuid::new_v4()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Entities::restore", skip_all, err(Debug))
+    )]
+    pub async fn restore(&self, id: Uuid) -> Result<reqwest::Response, Error> {
+        // build url for restoring an entity
+        let url = format!("{base}/api/entities/{id}/restore", base = self.host);
+        // build request
+        let req = self.client.post(&url).header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
+
+    /// Permanently deletes an [`Entity`] and its associations from Thorium
+    ///
+    /// Only admins can purge entities.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the entity to purge
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use uuid::Uuid;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create a Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // try to purge an entity from Thorium
+    /// thorium.entities.purge(Uuid::new_v4()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Entities::purge", skip_all, err(Debug))
+    )]
+    pub async fn purge(&self, id: Uuid) -> Result<reqwest::Response, Error> {
+        // build url for purging an entity
+        let url = format!("{base}/api/entities/{id}/purge", base = self.host);
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
     }
 
     /// Updates an [`Entity`] in Thorium
@@ -146,7 +418,7 @@ impl Entities {
             .multipart(update.to_form()?)
             .header("authorization", &self.token);
         // send this request
-        send!(self.client, req)
+        send!(self.client, req, OperationClass::Upload)
     }
 
     /// Lists all entities that meet some search criteria
@@ -184,7 +456,7 @@ impl Entities {
         feature = "trace",
         instrument(name = "Thorium::entities::list", skip_all, err(Debug))
     )]
-    pub async fn list(&self, opts: &EntityListOpts) -> Result<Cursor<Entity>, Error> {
+    pub async fn list(&self, opts: &EntityListOpts) -> Result<Cursor<EntityListLine>, Error> {
         // build the url for listing entities
         let url = format!("{}/api/entities/", self.host);
         // get the correct page size if our limit is smaller then our page_size
@@ -210,6 +482,9 @@ impl Entities {
             "tags_case_insensitive".to_owned(),
             opts.tags_case_insensitive
         );
+        add_query!(query, "partition_hint".to_owned(), opts.partition_hint);
+        add_query!(query, "submitter".to_owned(), opts.submitter);
+        add_query_bool!(query, "include_archived".to_owned(), opts.include_archived);
         // get the data for this request and create our cursor
         Cursor::new(
             &url,
@@ -260,28 +535,76 @@ impl Entities {
     pub async fn list_details(&self, opts: &EntityListOpts) -> Result<Cursor<Entity>, Error> {
         // build the url for listing entities
         let url = format!("{}/api/entities/details/", self.host);
+        // build our query params
+        let query = list_details_query(opts);
+        // get the data for this request and create our cursor
+        Cursor::new(
+            &url,
+            opts.page_size,
+            opts.limit,
+            &self.token,
+            &query,
+            &self.client,
+        )
+        .await
+    }
+
+    /// Lists devices and their details in a specific critical sector
+    ///
+    /// # Arguments
+    ///
+    /// * `opts` - The options for this cursor
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::{CriticalSector, DeviceListOpts};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build a search for critical energy sector devices
+    /// let search = DeviceListOpts::new(CriticalSector::Energy)
+    ///     .critical_system(true)
+    ///     // limit it to 100 devices
+    ///     .limit(100);
+    /// // list up to 100 critical energy sector devices
+    /// thorium.entities.list_devices(&search).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::entities::list_devices", skip_all, err(Debug))
+    )]
+    pub async fn list_devices(&self, opts: &DeviceListOpts) -> Result<Cursor<Entity>, Error> {
+        // build the url for listing devices
+        let url = format!("{}/api/entities/devices/", self.host);
         // get the correct page size if our limit is smaller then our page_size
         let page_size = opts.limit.map_or_else(
             || opts.page_size,
             |limit| std::cmp::min(opts.page_size, limit),
         );
         // build our query params
-        let mut query = vec![("limit".to_owned(), page_size.to_string())];
+        let mut query = vec![
+            ("limit".to_owned(), page_size.to_string()),
+            ("sector".to_owned(), opts.sector.to_string()),
+        ];
         add_query_list!(query, "groups[]".to_owned(), opts.groups);
-        add_query!(query, "start".to_owned(), opts.start);
-        add_query!(query, "end".to_owned(), opts.end);
+        add_date!(query, "start".to_owned(), opts.start);
+        add_date!(query, "end".to_owned(), opts.end);
         add_query!(query, "cursor".to_owned(), opts.cursor);
-        // add our tag query params
-        for (key, values) in &opts.tags {
-            // build the key for this tag param
-            let query_key = format!("tags[{key}][]");
-            // add this tag keys filters to our query params
-            add_query_list_clone!(query, query_key, values);
-        }
-        add_query_bool!(
+        add_query!(query, "critical_system".to_owned(), opts.critical_system);
+        add_query!(
             query,
-            "tags_case_insensitive".to_owned(),
-            opts.tags_case_insensitive
+            "sensitive_location".to_owned(),
+            opts.sensitive_location
         );
         // get the data for this request and create our cursor
         Cursor::new(
@@ -294,4 +617,291 @@ impl Entities {
         )
         .await
     }
+
+    /// Exports the association graph around some entities as node-link JSON
+    ///
+    /// # Arguments
+    ///
+    /// * `opts` - The options for this graph export
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::{AssociationKind, EntityGraphOpts};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // export the device to vendor graph for a group
+    /// let opts = EntityGraphOpts::default()
+    ///     .groups(vec!["Corn"])
+    ///     .kind(AssociationKind::DevelopedBy);
+    /// let graph = thorium.entities.export_graph(&opts).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::entities::export_graph", skip_all, err(Debug))
+    )]
+    pub async fn export_graph(&self, opts: &EntityGraphOpts) -> Result<EntityGraph, Error> {
+        // build the url for exporting a graph
+        let url = format!("{}/api/entities/graph/", self.host);
+        // build our query params
+        let query = graph_query(opts, GraphFormat::NodeLink);
+        // build request
+        let req = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .query(&query);
+        // send this request
+        send_build!(self.client, req, EntityGraph)
+    }
+
+    /// Exports the association graph around some entities as a GraphML document
+    ///
+    /// # Arguments
+    ///
+    /// * `opts` - The options for this graph export
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::EntityGraphOpts;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // export the graph for a group as GraphML
+    /// let opts = EntityGraphOpts::default().groups(vec!["Corn"]);
+    /// let graphml = thorium.entities.export_graphml(&opts).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::entities::export_graphml", skip_all, err(Debug))
+    )]
+    pub async fn export_graphml(&self, opts: &EntityGraphOpts) -> Result<String, Error> {
+        // build the url for exporting a graph
+        let url = format!("{}/api/entities/graph/", self.host);
+        // build our query params
+        let query = graph_query(opts, GraphFormat::GraphMl);
+        // build request
+        let req = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .query(&query);
+        // send this request and get our document
+        let resp = send!(self.client, req)?;
+        Ok(resp.text().await?)
+    }
+
+    /// Scans entities for likely duplicates based on the similarity of their names
+    ///
+    /// This is an admin only read-only scan and does not change any entities.
+    ///
+    /// # Arguments
+    ///
+    /// * `opts` - The options for this duplicate scan
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::{EntityDuplicateOpts, EntityKinds};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // find vendors in a group that are likely duplicates of each other
+    /// let opts = EntityDuplicateOpts::default()
+    ///     .groups(vec!["Corn"])
+    ///     .kind(EntityKinds::Vendor)
+    ///     .threshold(0.9);
+    /// let report = thorium.entities.find_duplicates(&opts).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::entities::find_duplicates", skip_all, err(Debug))
+    )]
+    pub async fn find_duplicates(
+        &self,
+        opts: &EntityDuplicateOpts,
+    ) -> Result<EntityDuplicateReport, Error> {
+        // build the url for scanning for duplicates
+        let url = format!("{}/api/entities/duplicates/", self.host);
+        // build our query params
+        let mut query = vec![("limit".to_owned(), opts.limit.to_string())];
+        add_query_list!(query, "groups[]".to_owned(), opts.groups);
+        add_query_list!(query, "kinds[]".to_owned(), opts.kinds);
+        add_query!(query, "threshold".to_owned(), opts.threshold);
+        // build request
+        let req = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .query(&query);
+        // send this request
+        send_build!(self.client, req, EntityDuplicateReport)
+    }
+
+    /// Lists the associations for a specific entity
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the entity to list associations for
+    /// * `opts` - The options for this cursor
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::AssociationListOpts;
+    /// use chrono::{Duration, Utc};
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build a search for associations added in the last 30 days
+    /// let now = Utc::now();
+    /// let search = AssociationListOpts::default()
+    ///     .created_between(now - Duration::days(30), now)
+    ///     .limit(100);
+    /// // list the up to 100 recent associations for this entity
+    /// let id = Uuid::parse_str("0b4c3d32-c8f4-4d56-8b0e-1c0e0d3b0c8a").unwrap();
+    /// thorium.entities.list_associations(id, &search).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::entities::list_associations",
+            skip(self, opts),
+            err(Debug)
+        )
+    )]
+    pub async fn list_associations(
+        &self,
+        id: Uuid,
+        opts: &AssociationListOpts,
+    ) -> Result<Cursor<Association>, Error> {
+        // build the url for listing this entities associations
+        let url = format!("{}/api/entities/associations/{id}", self.host);
+        // get the correct page size if our limit is smaller then our page_size
+        let page_size = opts.limit.map_or_else(
+            || opts.page_size,
+            |limit| std::cmp::min(opts.page_size, limit),
+        );
+        // build our query params
+        let mut query = vec![("limit".to_owned(), page_size.to_string())];
+        add_query_list!(query, "groups[]".to_owned(), opts.groups);
+        add_date!(query, "start".to_owned(), opts.start);
+        add_date!(query, "end".to_owned(), opts.end);
+        add_query!(query, "cursor".to_owned(), opts.cursor);
+        add_query_bool!(query, "include_archived".to_owned(), opts.include_archived);
+        // get the data for this request and create our cursor
+        Cursor::new(
+            &url,
+            opts.page_size,
+            opts.limit,
+            &self.token,
+            &query,
+            &self.client,
+        )
+        .await
+    }
+}
+
+// streams can't be returned from the blocking client so these are async only
+impl Entities {
+    /// Streams a single page of [`Entity`] details as they are retrieved
+    ///
+    /// Unlike listing details with a [`Cursor`], the API does not build the entire page
+    /// of details in memory before responding. The cursor for the next page is set on the
+    /// returned stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `opts` - The search criteria for this query
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::TryStreamExt;
+    /// use thorium::Thorium;
+    /// use thorium::models::EntityListOpts;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // stream the details for up to 50 entities in the Corn group
+    /// let search = EntityListOpts::default().groups(vec!["Corn"]);
+    /// let mut stream = thorium.entities.list_details_stream(&search).await?;
+    /// while let Some(entity) = stream.try_next().await? {
+    ///     println!("{}", entity.name);
+    /// }
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::entities::list_details_stream", skip_all, err(Debug))
+    )]
+    pub async fn list_details_stream(
+        &self,
+        opts: &EntityListOpts,
+    ) -> Result<NdjsonStream<Entity, Uuid>, Error> {
+        // build the url for listing entities
+        let url = format!("{}/api/entities/details/", self.host);
+        // build our query params
+        let query = list_details_query(opts);
+        // build and send our request asking for newline delimited json
+        let resp = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .header("accept", "application/x-ndjson")
+            .query(&query)
+            .send()
+            .await?;
+        // make sure we got a 200 before streaming our details
+        match resp.status() {
+            StatusCode::OK => Ok(NdjsonStream::new(resp)),
+            _ => Err(Error::from(resp)),
+        }
+    }
 }
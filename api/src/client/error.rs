@@ -10,6 +10,8 @@ pub enum Error {
     Thorium {
         code: StatusCode,
         msg: Option<String>,
+        /// The path of the request that failed if we know it
+        path: Option<String>,
     },
     /// A generic error with a message
     Generic(String),
@@ -139,6 +141,51 @@ impl Error {
         }
     }
 
+    /// Get the status code from this error if one exists
+    ///
+    /// This is an alias for [`Error::status`].
+    pub fn status_code(&self) -> Option<StatusCode> {
+        self.status()
+    }
+
+    /// Check if this error is a 404 from the server
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// Check if this error is a 401 from the server
+    pub fn is_unauthorized(&self) -> bool {
+        self.status() == Some(StatusCode::UNAUTHORIZED)
+    }
+
+    /// Check if this error is from a request timing out
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            Error::Reqwest(err) => err.is_timeout(),
+            _ => matches!(
+                self.status(),
+                Some(StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT)
+            ),
+        }
+    }
+
+    /// Check if this error is from failing to connect to the server
+    pub fn is_connect(&self) -> bool {
+        match self {
+            Error::Reqwest(err) => err.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// Get the path of the request that caused this error if one exists
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Error::Thorium { path, .. } => path.as_deref(),
+            Error::Reqwest(err) => err.url().map(reqwest::Url::path),
+            _ => None,
+        }
+    }
+
     /// Get the error message for this error if one exists
     pub fn msg(&self) -> Option<String> {
         // get the msg from any error types that support it
@@ -273,6 +320,17 @@ impl Error {
 impl std::fmt::Display for Error {
     /// display this error in a easy readble format
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // Thorium errors get a one line summary of what request failed and why
+        if let Error::Thorium { code, msg, path } = self {
+            write!(f, "{code}")?;
+            if let Some(path) = path {
+                write!(f, " from {path}")?;
+            }
+            if let Some(msg) = msg {
+                write!(f, ": {msg}")?;
+            }
+            return Ok(());
+        }
         match (self.status(), self.msg()) {
             (Some(code), Some(msg)) => write!(f, "Code: {} Error: {}", code, msg),
             (None, Some(msg)) => write!(f, "Error: {}", msg),
@@ -287,9 +345,13 @@ impl std::error::Error for Error {}
 
 impl From<reqwest::Response> for Error {
     fn from(resp: reqwest::Response) -> Self {
+        // get our status and path before consuming the response body
+        let code = resp.status();
+        let path = Some(resp.url().path().to_owned());
         Error::Thorium {
-            code: resp.status(),
+            code,
             msg: block_on(resp.text()).ok().filter(|s| !s.is_empty()),
+            path,
         }
     }
 }
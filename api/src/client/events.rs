@@ -1,5 +1,6 @@
 //! Exposes events routes in Thorium
 
+use super::HttpClient;
 use crate::models::{
     Event, EventCacheStatus, EventCacheStatusOpts, EventIds, EventPopOpts, EventType,
 };
@@ -15,7 +16,7 @@ pub struct Events {
     host: String,
     /// token to use for auth
     token: String,
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -34,13 +35,13 @@ impl Events {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Events;
+    /// use thorium::client::{Events, HttpClient};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let events = Events::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Events {
             host: host.to_owned(),
@@ -3,7 +3,9 @@
 use cart_rs::UncartStream;
 use futures::{StreamExt, TryStreamExt};
 use reqwest::StatusCode;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt;
 use tokio_util::io::StreamReader;
@@ -12,15 +14,18 @@ use uuid::Uuid;
 #[cfg(feature = "trace")]
 use tracing::instrument;
 
-use super::Error;
 use super::traits::{GenericClient, ResultsClient, ResultsClientHelper, TransferProgress};
+use super::{Error, HttpClient, OperationClass};
+use crate::models::files::lock_hashers;
 use crate::models::{
-    Attachment, CartedFile, CommentRequest, CommentResponse, CountCursor, Cursor,
-    DeleteCommentParams, DownloadedFile, FileDeleteOpts, FileDownloadOpts, FileListOpts, OutputMap,
-    OutputRequest, OutputResponse, ResultGetParams, Sample, SampleCheck, SampleCheckResponse,
-    SampleListLine, SampleRequest, SampleSubmissionResponse, SubmissionUpdate, TagCounts,
-    TagDeleteRequest, TagRequest, UncartedFile,
+    Association, AssociationListOpts, Attachment, BulkTagResponse, ByteRange, CartedFile,
+    CommentRequest, CommentResponse, CountCursor, Cursor, DeleteCommentParams, DownloadedFile,
+    FileDeleteOpts, FileDownloadOpts, FileListOpts, OutputMap, OutputRequest, OutputResponse,
+    ResultGetParams, Sample, SampleCheck, SampleCheckResponse, SampleListLine, SampleRequest,
+    SampleSubmissionResponse, SubmissionUpdate, TagCounts, TagDeleteRequest, TagRequest,
+    UncartedFile,
 };
+use crate::utils::hashes::{StandardHashers, StandardHashes};
 use crate::{
     add_date, add_query, add_query_bool, add_query_list, add_query_list_clone, send, send_build,
     send_bytes,
@@ -48,7 +53,7 @@ pub struct Files {
     /// token to use for auth
     token: String,
     /// A reqwest client for reqwests
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(
@@ -73,13 +78,13 @@ impl Files {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Files;
+    /// use thorium::client::{Files, HttpClient};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let files = Files::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Files {
             host: host.to_owned(),
@@ -127,13 +132,82 @@ impl Files {
             .client
             .post(&url)
             .multipart(file_req.to_form().await?)
-            .header("authorization", &self.token)
-            // use a really long timeout for really large files
-            // this is probably done better some otherway
-            // 86,400 seconds == a day
-            .timeout(std::time::Duration::from_secs(86_400));
-        // send this request
-        send_build!(self.client, req, SampleSubmissionResponse)
+            .header("authorization", &self.token);
+        // send this request as an upload so it gets our long upload timeout
+        send_build!(
+            self.client,
+            req,
+            SampleSubmissionResponse,
+            OperationClass::Upload
+        )
+    }
+
+    /// Creates an [`Sample`] in Thorium while hashing it locally to verify Thorium received it intact
+    ///
+    /// Our file is hashed as it is streamed to Thorium so it is never read into memory all at
+    /// once. If the hashes Thorium computed don't match ours then an error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_req` - The file request to use to add an file to Thorium
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use thorium::models::SampleRequest;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build the file request
+    /// let file_req = SampleRequest::new("corn.txt", vec!("plants".to_owned()));
+    /// // try to create file in Thorium and get the hashes we computed locally
+    /// let (_, hashes) = thorium.files.create_verified(file_req).await?;
+    /// println!("uploaded {}", hashes.sha256);
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Files::create_verified", skip_all, err(Debug))
+    )]
+    pub async fn create_verified(
+        &self,
+        file_req: SampleRequest,
+    ) -> Result<(SampleSubmissionResponse, StandardHashes), Error> {
+        // build url for claiming a job
+        let url = format!("{base}/api/files/", base = self.host);
+        // build the hashers to hash our file with as it's uploaded
+        let hashers = Arc::new(Mutex::new(StandardHashers::default()));
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .multipart(file_req.to_hashed_form(&hashers).await?)
+            .header("authorization", &self.token);
+        // send this request as an upload so it gets our long upload timeout
+        let resp = send_build!(
+            self.client,
+            req,
+            SampleSubmissionResponse,
+            OperationClass::Upload
+        )?;
+        // our file has been completely sent so finish hashing it
+        let hashes = std::mem::take(&mut *lock_hashers(&hashers)).finish();
+        // make sure Thorium got the same file we sent
+        if resp.sha256 != hashes.sha256 || resp.sha1 != hashes.sha1 || resp.md5 != hashes.md5 {
+            return Err(Error::new(format!(
+                "File was corrupted during upload! Thorium hashed it as {} but we sent {}",
+                resp.sha256, hashes.sha256
+            )));
+        }
+        Ok((resp, hashes))
     }
 
     /// Gets details about a specific [`Sample`] in Thorium
@@ -293,13 +367,14 @@ impl Files {
             base = self.host,
             sha256 = sha256
         );
-        // build and send the request
-        let resp = self
+        // build the request
+        let req = self
             .client
             .get(&url)
             .header("authorization", &self.token)
-            .send()
-            .await?;
+            .build()?;
+        // send this request as a download so it gets our download timeout
+        let resp = super::execute(&self.client, req, OperationClass::Download).await?;
         // make sure we got a 200
         match resp.status() {
             StatusCode::OK => {
@@ -362,6 +437,137 @@ impl Files {
         }
     }
 
+    /// Downloads a range of uncarted bytes from a file without downloading all of it
+    ///
+    /// The range is of the uncarted file and both `start` and `end` are inclusive.
+    /// Ranges that go past the end of the file return the bytes up to the end of
+    /// the file, ranges can be at most 64 MiB, and ranges must start within the
+    /// first GiB of the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the file to download a range from
+    /// * `start` - The offset of the first byte to download
+    /// * `end` - The offset of the last byte to download
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // get the first kibibyte of this file
+    /// let sha256 = "325030adff0665689b0360ac9c8398cd62a2377e98e06ad7d3914fabacb0daef";
+    /// let header = thorium.files.download_range(sha256, 0, 1023).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Files::download_range", skip(self), err(Debug))
+    )]
+    pub async fn download_range(
+        &self,
+        sha256: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<u8>, Error> {
+        // build url for downloading this sample
+        let url = format!(
+            "{base}/api/files/sample/{sha256}/download/uncarted",
+            base = self.host,
+            sha256 = sha256
+        );
+        // build request
+        let req = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .header("range", ByteRange::new(start, end).header());
+        // send this request
+        let resp = send!(self.client, req, OperationClass::Download)?;
+        // make sure we got just our range back and not the whole carted file
+        if resp.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(Error::new(format!(
+                "Expected a partial download but got a {} instead",
+                resp.status()
+            )));
+        }
+        Ok(resp.bytes().await?.to_vec())
+    }
+
+    /// Downloads a range of bytes from a carted file without downloading all of it
+    ///
+    /// The range is of the carted file as it is stored in Thorium and both `start`
+    /// and `end` are inclusive. Ranges that go past the end of the file return the
+    /// bytes up to the end of the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the file to download a range from
+    /// * `start` - The offset of the first carted byte to download
+    /// * `end` - The offset of the last carted byte to download
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // get the first kibibyte of this carted file
+    /// let sha256 = "325030adff0665689b0360ac9c8398cd62a2377e98e06ad7d3914fabacb0daef";
+    /// let header = thorium.files.download_carted_range(sha256, 0, 1023).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Files::download_carted_range", skip(self), err(Debug))
+    )]
+    pub async fn download_carted_range(
+        &self,
+        sha256: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<u8>, Error> {
+        // build url for downloading this sample
+        let url = format!(
+            "{base}/api/files/sample/{sha256}/download",
+            base = self.host,
+            sha256 = sha256
+        );
+        // build request
+        let req = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .header("range", ByteRange::new(start, end).header());
+        // send this request
+        let resp = send!(self.client, req, OperationClass::Download)?;
+        // make sure we got just our range back and not the whole carted file
+        if resp.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(Error::new(format!(
+                "Expected a partial download but got a {} instead",
+                resp.status()
+            )));
+        }
+        Ok(resp.bytes().await?.to_vec())
+    }
+
     /// Checks if a sample or submission exists
     ///
     /// # Arguments
@@ -470,6 +676,7 @@ impl Files {
             "tags_case_insensitive".to_owned(),
             opts.tags_case_insensitive
         );
+        add_query!(query, "partition_hint".to_owned(), opts.partition_hint);
         // get the data for this request and create our cursor
         Cursor::new(
             &url,
@@ -543,6 +750,75 @@ impl Files {
             "tags_case_insensitive".to_owned(),
             opts.tags_case_insensitive
         );
+        add_query!(query, "partition_hint".to_owned(), opts.partition_hint);
+        // get the data for this request and create our cursor
+        Cursor::new(
+            &url,
+            opts.page_size,
+            opts.limit,
+            &self.token,
+            &query,
+            &self.client,
+        )
+        .await
+    }
+
+    /// Lists the associations for a specific file
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the file to list associations for
+    /// * `opts` - The options for this cursor
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::AssociationListOpts;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build a search to list up to 100 associations
+    /// let search = AssociationListOpts::default().limit(100);
+    /// // list the up to 100 associations for this file
+    /// let sha256 = "63b9acab1e5e5a7e6e9b2b1fd8ae3e6e0f5f0c1bbef0ea1b7a6e8a6c03de3c2b";
+    /// thorium.files.list_associations(sha256, &search).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Files::list_associations",
+            skip(self, opts),
+            err(Debug)
+        )
+    )]
+    pub async fn list_associations(
+        &self,
+        sha256: &str,
+        opts: &AssociationListOpts,
+    ) -> Result<Cursor<Association>, Error> {
+        // build the url for listing this files associations
+        let url = format!("{}/api/files/associations/{sha256}", self.host);
+        // get the correct page size if our limit is smaller then our page_size
+        let page_size = opts.limit.map_or_else(
+            || opts.page_size,
+            |limit| std::cmp::min(opts.page_size, limit),
+        );
+        // build our query params
+        let mut query = vec![("limit".to_owned(), page_size.to_string())];
+        add_query_list!(query, "groups[]".to_owned(), opts.groups);
+        add_date!(query, "start".to_owned(), opts.start);
+        add_date!(query, "end".to_owned(), opts.end);
+        add_query!(query, "cursor".to_owned(), opts.cursor);
+        add_query_bool!(query, "include_archived".to_owned(), opts.include_archived);
         // get the data for this request and create our cursor
         Cursor::new(
             &url,
@@ -729,6 +1005,64 @@ impl Files {
         send!(self.client, req)
     }
 
+    /// Adds new tags to many samples in one request
+    ///
+    /// Samples that could not be tagged are returned in the response's errors
+    /// instead of failing the whole request.
+    ///
+    /// # Arguments
+    ///
+    /// * `reqs` - The tag requests to send by sha256
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use thorium::{Thorium, models::TagRequest};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build the tags to add to each sample
+    /// let mut reqs = HashMap::default();
+    /// reqs.insert(
+    ///     "856926b48a936b50e92682807bdae12d5ce39abf509d4c0be82e1327b548705f".to_owned(),
+    ///     TagRequest::default().add("plant", "corn"),
+    /// );
+    /// reqs.insert(
+    ///     "63b9c9c5d5e3b5e3dcda9b4e5a5b5b3d2bfa48ae0a7e2e8d3f4e3fce1a5c7c5a".to_owned(),
+    ///     TagRequest::default().add("plant", "soy"),
+    /// );
+    /// // add tags to these files
+    /// let response = thorium.files.tag_bulk(&reqs).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Files::tag_bulk", skip(self, reqs), err(Debug))
+    )]
+    pub async fn tag_bulk(
+        &self,
+        reqs: &HashMap<String, TagRequest<Sample>>,
+    ) -> Result<BulkTagResponse, Error> {
+        // build url for tagging files in bulk
+        let url = format!("{}/api/files/tags/bulk/", self.host);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .json(reqs)
+            .header("authorization", &self.token);
+        // send this request and build our bulk tag response
+        send_build!(self.client, req, BulkTagResponse)
+    }
+
     /// Deletes tags from a sample
     ///
     /// # Arguments
@@ -817,7 +1151,7 @@ impl Files {
             .multipart(comment_req.to_form().await?)
             .header("authorization", &self.token);
         // send this request
-        send_build!(self.client, req, CommentResponse)
+        send_build!(self.client, req, CommentResponse, OperationClass::Upload)
     }
 
     /// Deletes a comment for a sample
@@ -923,7 +1257,7 @@ impl Files {
         // build request
         let req = self.client.get(&url).header("authorization", &self.token);
         // send this request and read it as bytes
-        let data = send_bytes!(self.client, req)?;
+        let data = send_bytes!(self.client, req, OperationClass::Download)?;
         // build our attachment object from the bytes
         Ok(Attachment { data })
     }
@@ -936,7 +1270,7 @@ impl GenericClient for Files {
     }
 
     /// Provide the configured client from `self`
-    fn client(&self) -> &reqwest::Client {
+    fn client(&self) -> &HttpClient {
         &self.client
     }
 
@@ -5,8 +5,8 @@
 //! Before you can create anything in Thorium you need to either create or be apart of
 //! the group you wish those images, pipelines, or reactions in.
 
-use super::{Cursor, Error};
-use crate::models::{Group, GroupRequest, GroupUpdate};
+use super::{Cursor, Error, HttpClient};
+use crate::models::{CacheWarmResponse, Group, GroupRequest, GroupUpdate};
 use crate::{send, send_build};
 
 // import our static runtime if we need a blocking client
@@ -22,7 +22,7 @@ pub struct Groups {
     /// token to use for auth
     token: String,
     /// reqwest client object
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -41,13 +41,13 @@ impl Groups {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Groups;
+    /// use thorium::client::{Groups, HttpClient};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let groups = Groups::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new<T: Into<String>>(host: T, token: T, client: &reqwest::Client) -> Self {
+    pub fn new<T: Into<String>>(host: T, token: T, client: &HttpClient) -> Self {
         // build basic route handler
         Groups {
             host: host.into(),
@@ -269,4 +269,40 @@ impl Groups {
         // send this request
         send!(self.client, req)
     }
+
+    /// Load a [`Group`]'s images and pipelines into the API's caches
+    ///
+    /// This is an admin only action. Nothing is cached unless the API has its definition
+    /// cache enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The name of the group to warm caches for
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // cache the images and pipelines in the Corn group
+    /// let warmed = thorium.groups.warm_cache("Corn").await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn warm_cache(&self, group: &str) -> Result<CacheWarmResponse, Error> {
+        // build url for warming this groups caches
+        let url = format!("{}/api/groups/{}/cache/warm", self.host, group);
+        // build request
+        let req = self.client.post(&url).header("authorization", &self.token);
+        // send this request and build a cache warm response from the response
+        send_build!(self.client, req, CacheWarmResponse)
+    }
 }
@@ -1,21 +1,402 @@
-use reqwest::Certificate;
+use reqwest::{Certificate, StatusCode};
+use semver::{Comparator, Op, Prerelease, Version};
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+#[cfg(feature = "trace")]
+use tracing::{Level, event};
 
-use super::{ClientSettings, Error};
+use super::conf::OperationClass;
+use super::{ClientSettings, Error, RetryPolicy};
+
+/// The settings applied to each request sent to Thorium
+#[derive(Debug, Clone, Default)]
+struct RequestSettings {
+    /// How to retry requests that fail with a transient error
+    retry: RetryPolicy,
+    /// The timeout for metadata requests if one was set
+    metadata: Option<Duration>,
+    /// The timeout for uploads if one was set
+    upload: Option<Duration>,
+    /// The timeout for downloads if one was set
+    download: Option<Duration>,
+    /// Whether to fail requests to incompatible versions of Thorium
+    strict_version: bool,
+}
+
+impl RequestSettings {
+    /// Set a requests timeout to the timeout for its class if it didn't set its own
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The request to set a timeout for
+    /// * `class` - The class of operation this request is for
+    fn fill_timeout(&self, req: &mut reqwest::Request, class: OperationClass) {
+        if req.timeout().is_none() {
+            *req.timeout_mut() = match class {
+                OperationClass::Metadata => self.metadata,
+                OperationClass::Upload => self.upload,
+                OperationClass::Download => self.download,
+            };
+        }
+    }
+}
+
+impl From<&ClientSettings> for RequestSettings {
+    /// Get the per request settings from our client settings
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The client settings to use
+    fn from(settings: &ClientSettings) -> Self {
+        RequestSettings {
+            retry: settings.retry.clone(),
+            metadata: settings.granular_timeout(OperationClass::Metadata),
+            upload: Some(settings.timeout_for(OperationClass::Upload)),
+            download: settings.granular_timeout(OperationClass::Download),
+            strict_version: settings.strict_version,
+        }
+    }
+}
+
+/// A reqwest client and the settings to send each of its requests with
+///
+/// Each [`crate::Thorium`] client builds its own so clients with different settings can be
+/// used in the same process.
+#[derive(Debug, Clone, Default)]
+pub struct HttpClient {
+    /// The reqwest client to send requests with
+    inner: reqwest::Client,
+    /// The settings to apply to each request we send
+    settings: Arc<RequestSettings>,
+    /// The version of the Thorium API we are talking to once we know it
+    server: Arc<OnceLock<Version>>,
+}
+
+impl HttpClient {
+    /// Wrap a reqwest client with the request settings from some client settings
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The reqwest client to send requests with
+    /// * `settings` - The client settings to get our request settings from
+    #[must_use]
+    pub fn new(inner: reqwest::Client, settings: &ClientSettings) -> Self {
+        HttpClient {
+            inner,
+            settings: Arc::new(RequestSettings::from(settings)),
+            server: Arc::default(),
+        }
+    }
+
+    /// Get the version of the Thorium API we are talking to if we have talked to it yet
+    #[must_use]
+    pub fn server_version(&self) -> Option<Version> {
+        self.server.get().cloned()
+    }
+}
+
+impl From<reqwest::Client> for HttpClient {
+    /// Wrap a reqwest client using the default request settings
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The reqwest client to send requests with
+    fn from(inner: reqwest::Client) -> Self {
+        HttpClient {
+            inner,
+            settings: Arc::default(),
+            server: Arc::default(),
+        }
+    }
+}
+
+impl Deref for HttpClient {
+    type Target = reqwest::Client;
+
+    /// Get the reqwest client to build requests with
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// Send a request to Thorium using a clients request settings
+///
+/// Requests that didn't set their own timeout get the timeout for their class of
+/// operation if one was set. Otherwise they fall back to the clients single timeout.
+///
+/// # Arguments
+///
+/// * `client` - The client to send this request with
+/// * `req` - The request to send
+/// * `class` - The class of operation this request is for
+#[doc(hidden)]
+pub async fn execute(
+    client: &HttpClient,
+    mut req: reqwest::Request,
+    class: OperationClass,
+) -> Result<reqwest::Response, Error> {
+    let settings = &client.settings;
+    // use the timeout for this class of request if we have one
+    settings.fill_timeout(&mut req, class);
+    let resp = execute_with_policy(&client.inner, req, &settings.retry).await?;
+    // make sure the version of Thorium we are talking to is compatible with us
+    check_server_version(client, &resp, settings.strict_version)?;
+    Ok(resp)
+}
+
+/// The header the API returns its version in
+pub const VERSION_HEADER: &str = "thorium-version";
+
+/// Check if a version of the Thorium API is compatible with a client version
+///
+/// Compatibility is derived from the API's version with the same caret rules cargo
+/// uses for dependencies. This means the major versions must match or, for pre 1.0
+/// versions, the minor versions must match as well.
+///
+/// # Arguments
+///
+/// * `client` - The version of the client
+/// * `server` - The version of the API
+#[must_use]
+pub fn versions_compatible(client: &Version, server: &Version) -> bool {
+    // build the versions of clients this API is compatible with
+    let compatible = Comparator {
+        op: Op::Caret,
+        major: server.major,
+        minor: (server.major == 0).then_some(server.minor),
+        patch: None,
+        pre: Prerelease::EMPTY,
+    };
+    // ignore any pre-release tags on our client
+    compatible.matches(&Version::new(client.major, client.minor, client.patch))
+}
+
+/// Check that the version of the API that sent a response is compatible with us
+///
+/// A warning is logged the first time an incompatible version is seen by a client.
+/// Returns whether a warning was logged.
+///
+/// # Arguments
+///
+/// * `client` - The client that received this response
+/// * `resp` - The response to check
+/// * `strict` - Whether to return an error instead of warning if the versions are incompatible
+pub(super) fn check_server_version(
+    client: &HttpClient,
+    resp: &reqwest::Response,
+    strict: bool,
+) -> Result<bool, Error> {
+    // use the version we already saw for this API if we have one
+    let (server, first) = match client.server.get() {
+        Some(server) => (server.clone(), false),
+        None => {
+            // skip any APIs that don't tell us their version
+            let Some(server) = resp
+                .headers()
+                .get(VERSION_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| Version::parse(value.trim()).ok())
+            else {
+                return Ok(false);
+            };
+            // only treat this as our first time seeing this API if nobody beat us to it
+            let first = client.server.set(server.clone()).is_ok();
+            (server, first)
+        }
+    };
+    // get our own version
+    let Ok(ours) = Version::parse(env!("CARGO_PKG_VERSION")) else {
+        return Ok(false);
+    };
+    if versions_compatible(&ours, &server) {
+        return Ok(false);
+    }
+    let msg = format!(
+        "The Thorium API at {} is version {server} which is not compatible with \
+         this client's version {ours}",
+        resp.url().origin().ascii_serialization()
+    );
+    if strict {
+        return Err(Error::new(msg));
+    }
+    // only warn once per client
+    #[cfg(feature = "trace")]
+    if first {
+        event!(Level::WARN, "{msg}. Update this client to match the API.");
+    }
+    Ok(first)
+}
+
+/// Send a request to Thorium retrying it based on a specific retry policy
+///
+/// Requests with streaming bodies can't be cloned and so are never retried.
+///
+/// # Arguments
+///
+/// * `client` - The client to send this request with
+/// * `req` - The request to send
+/// * `policy` - The retry policy to use
+pub(super) async fn execute_with_policy(
+    client: &reqwest::Client,
+    mut req: reqwest::Request,
+    policy: &RetryPolicy,
+) -> Result<reqwest::Response, Error> {
+    // don't bother tracking attempts for requests we won't retry
+    if !policy.retryable_method(req.method()) {
+        return Ok(client.execute(req).await?);
+    }
+    let mut attempt = 1;
+    loop {
+        // keep a copy of this request if we are allowed to try it again
+        let next = if attempt < policy.max_attempts {
+            req.try_clone()
+        } else {
+            None
+        };
+        let resp = client.execute(req).await?;
+        // check if this response is a transient error
+        if !RetryPolicy::retryable_status(resp.status()) {
+            return Ok(resp);
+        }
+        match next {
+            // wait and then try this request again
+            Some(next) => {
+                tokio::time::sleep(policy.backoff(attempt)).await;
+                req = next;
+                attempt += 1;
+            }
+            // we are out of attempts so return our last error
+            None if attempt > 1 => return Err(exhausted(resp, attempt).await),
+            None => return Ok(resp),
+        }
+    }
+}
+
+/// Send a request that the API may shed before handling it
+///
+/// The API sheds requests it is too backed up to handle with a 429 before doing any
+/// work for them, so these are retried with backoff even if they are not idempotent.
+///
+/// # Arguments
+///
+/// * `client` - The client to send this request with
+/// * `req` - The request to send
+pub(super) async fn execute_sheddable(
+    client: &HttpClient,
+    req: reqwest::Request,
+) -> Result<reqwest::Response, Error> {
+    retry_shed(client, req, &client.settings.retry).await
+}
+
+/// Send a request retrying it with a specific retry policy if it is shed
+///
+/// # Arguments
+///
+/// * `client` - The client to send this request with
+/// * `req` - The request to send
+/// * `policy` - The retry policy to use
+async fn retry_shed(
+    client: &HttpClient,
+    mut req: reqwest::Request,
+    policy: &RetryPolicy,
+) -> Result<reqwest::Response, Error> {
+    let mut attempt = 1;
+    loop {
+        // keep a copy of this request if we are allowed to try it again
+        let next = if attempt < policy.max_attempts {
+            req.try_clone()
+        } else {
+            None
+        };
+        let resp = execute(client, req, OperationClass::Metadata).await?;
+        // only retry requests that were shed
+        if resp.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(resp);
+        }
+        match next {
+            // wait and then try this request again
+            Some(next) => {
+                tokio::time::sleep(policy.backoff(attempt)).await;
+                req = next;
+                attempt += 1;
+            }
+            // we are out of attempts so return our last error
+            None if attempt > 1 => return Err(exhausted(resp, attempt).await),
+            None => return Ok(resp),
+        }
+    }
+}
+
+/// Build an error for a request that failed on every attempt
+///
+/// # Arguments
+///
+/// * `resp` - The last response we got
+/// * `attempts` - The number of attempts that were made
+async fn exhausted(resp: reqwest::Response, attempts: u32) -> Error {
+    let code: StatusCode = resp.status();
+    let path = Some(resp.url().path().to_owned());
+    // add our attempt count to whatever message the API gave us
+    let msg = match resp.text().await.ok().filter(|msg| !msg.is_empty()) {
+        Some(msg) => format!("{msg} (failed after {attempts} attempts)"),
+        None => format!("Failed after {attempts} attempts"),
+    };
+    Error::Thorium {
+        code,
+        msg: Some(msg),
+        path,
+    }
+}
+
+/// Apply our connection keep-alive and HTTP/2 settings to a client builder
+///
+/// # Arguments
+///
+/// * `builder` - The client builder to apply settings to
+/// * `settings` - The settings for building a client
+fn apply_transport(
+    mut builder: reqwest::ClientBuilder,
+    settings: &ClientSettings,
+) -> reqwest::ClientBuilder {
+    if settings.http2_prior_knowledge {
+        builder = builder.http2_prior_knowledge();
+    }
+    if let Some(interval) = settings.http2_keep_alive_interval {
+        builder = builder.http2_keep_alive_interval(Duration::from_secs(interval));
+    }
+    if let Some(keepalive) = settings.tcp_keepalive {
+        builder = builder.tcp_keepalive(Duration::from_secs(keepalive));
+    }
+    if let Some(idle) = settings.pool_idle_timeout {
+        builder = builder.pool_idle_timeout(Duration::from_secs(idle));
+    }
+    if let Some(max_idle) = settings.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    builder
+}
 
 /// Build a reqwest client for thorctl
 ///
 /// # Arguments
 ///
+/// * `host` - The host this client will talk to
 /// * `settings` - The settings for building a client
 pub(super) async fn build_reqwest_client(
+    host: &str,
     settings: &ClientSettings,
-) -> Result<reqwest::Client, Error> {
+) -> Result<HttpClient, Error> {
+    // make sure our transport settings make sense for this host
+    settings.validate_transport(host)?;
     // start building our client
-    let mut builder = reqwest::Client::builder()
+    let builder = reqwest::Client::builder()
         .no_proxy()
         .danger_accept_invalid_certs(settings.invalid_certs)
         .danger_accept_invalid_hostnames(settings.invalid_hostnames)
         .timeout(std::time::Duration::from_secs(settings.timeout));
+    // apply our keep-alive and HTTP/2 settings
+    let mut builder = apply_transport(builder, settings);
     // crawl over any custom CAs and add them to our trust store
     for ca_path in &settings.certificate_authorities {
         // try to load this CA from disk
@@ -44,25 +425,31 @@ pub(super) async fn build_reqwest_client(
         // add this cert to our clients trust store
         builder = builder.add_root_certificate(cert);
     }
-    // build our client
-    Ok(builder.build()?)
+    // build our client and keep our retry policy and timeouts with it
+    Ok(HttpClient::new(builder.build()?, settings))
 }
 
 /// Build a reqwest client for thorctl
 ///
 /// # Arguments
 ///
+/// * `host` - The host this client will talk to
 /// * `settings` - The settings for building a client
 #[cfg(feature = "sync")]
 pub(super) fn build_blocking_reqwest_client(
+    host: &str,
     settings: &ClientSettings,
-) -> Result<reqwest::Client, Error> {
+) -> Result<HttpClient, Error> {
+    // make sure our transport settings make sense for this host
+    settings.validate_transport(host)?;
     // start building our client
-    let mut builder = reqwest::Client::builder()
+    let builder = reqwest::Client::builder()
         .no_proxy()
         .danger_accept_invalid_certs(settings.invalid_certs)
         .danger_accept_invalid_hostnames(settings.invalid_hostnames)
         .timeout(std::time::Duration::from_secs(settings.timeout));
+    // apply our keep-alive and HTTP/2 settings
+    let mut builder = apply_transport(builder, settings);
     // crawl over any custom CAs and add them to our trust store
     for ca_path in &settings.certificate_authorities {
         // try to load this CA from disk
@@ -85,16 +472,20 @@ pub(super) fn build_blocking_reqwest_client(
         // add this cert to our clients trust store
         builder = builder.add_root_certificate(cert);
     }
-    // build our client
-    Ok(builder.build()?)
+    // build our client and keep our retry policy and timeouts with it
+    Ok(HttpClient::new(builder.build()?, settings))
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! send {
+    // requests are metadata requests unless told otherwise
     ($client:expr, $req:expr) => {
+        $crate::send!($client, $req, $crate::client::OperationClass::Metadata)
+    };
+    ($client:expr, $req:expr, $class:expr) => {
         // attempt to send request
-        match $client.execute($req.build()?).await {
+        match $crate::client::execute(&$client, $req.build()?, $class).await {
             // response was received
             Ok(resp) => {
                 // check if a response has an error status or not
@@ -106,7 +497,7 @@ macro_rules! send {
                     Err(Error::from(resp))
                 }
             }
-            Err(e) => Err(Error::from(e)),
+            Err(e) => Err(e),
         }
     };
 }
@@ -114,9 +505,18 @@ macro_rules! send {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! send_build {
+    // requests are metadata requests unless told otherwise
     ($client:expr, $req:expr, $build:ty) => {
+        $crate::send_build!(
+            $client,
+            $req,
+            $build,
+            $crate::client::OperationClass::Metadata
+        )
+    };
+    ($client:expr, $req:expr, $build:ty, $class:expr) => {
         // attempt to send request
-        match $client.execute($req.build()?).await {
+        match $crate::client::execute(&$client, $req.build()?, $class).await {
             // response was received
             Ok(resp) => {
                 // check if a response has an error status or not
@@ -133,7 +533,7 @@ macro_rules! send_build {
                     Err(Error::from(resp))
                 }
             }
-            Err(e) => Err(Error::from(e)),
+            Err(e) => Err(e),
         }
     };
 }
@@ -142,9 +542,13 @@ macro_rules! send_build {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! send_bytes {
+    // requests are metadata requests unless told otherwise
     ($client:expr, $req:expr) => {
+        $crate::send_bytes!($client, $req, $crate::client::OperationClass::Metadata)
+    };
+    ($client:expr, $req:expr, $class:expr) => {
         // attempt to send request
-        match $client.execute($req.build()?).await {
+        match $crate::client::execute(&$client, $req.build()?, $class).await {
             // response was received
             Ok(resp) => {
                 // check if a response has an error status or not
@@ -161,7 +565,7 @@ macro_rules! send_bytes {
                     Err(Error::from(resp))
                 }
             }
-            Err(e) => Err(Error::from(e)),
+            Err(e) => Err(e),
         }
     };
 }
@@ -372,3 +776,388 @@ macro_rules! multipart_file {
         form
     }};
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use semver::Version;
+
+    use super::{
+        HttpClient, check_server_version, execute, execute_with_policy, retry_shed,
+        versions_compatible,
+    };
+    use crate::client::conf::DEFAULT_UPLOAD_TIMEOUT;
+    use crate::client::{ClientSettings, OperationClass, RetryPolicy};
+
+    /// Spawn a mock Thorium that returns a 503 for its first few requests
+    ///
+    /// # Arguments
+    ///
+    /// * `failures` - The number of requests to fail before succeeding
+    async fn flaky_server(failures: usize) -> (String, Arc<AtomicUsize>) {
+        failing_server(failures, "503 Service Unavailable").await
+    }
+
+    /// Spawn a mock Thorium that returns an error for its first few requests
+    ///
+    /// # Arguments
+    ///
+    /// * `failures` - The number of requests to fail before succeeding
+    /// * `status` - The status line to fail requests with
+    async fn failing_server(failures: usize, status: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let served = Arc::new(AtomicUsize::new(0));
+        let counter = served.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                // read this request but we don't care what it contains
+                let mut buff = [0; 4096];
+                let _ = stream.read(&mut buff).await;
+                let resp = if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                    format!(
+                        "HTTP/1.1 {status}\r\ncontent-length: 4\r\nconnection: close\r\n\r\nbusy"
+                    )
+                } else {
+                    "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok".to_owned()
+                };
+                stream.write_all(resp.as_bytes()).await.unwrap();
+            }
+        });
+        (format!("http://{addr}/api/basic/ping"), served)
+    }
+
+    /// Spawn a mock Thorium that reports a specific version
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version to report
+    async fn versioned_server(version: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                // read this request but we don't care what it contains
+                let mut buff = [0; 4096];
+                let _ = stream.read(&mut buff).await;
+                let resp = format!(
+                    "HTTP/1.1 200 OK\r\nthorium-version: {version}\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok"
+                );
+                stream.write_all(resp.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    /// Build a policy that retries quickly so our tests don't take forever
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: 1,
+            max_delay: 5,
+            retry_non_idempotent: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_get_until_success() {
+        let (url, served) = flaky_server(2).await;
+        let client = reqwest::Client::new();
+        let req = client.get(&url).build().unwrap();
+        let resp = execute_with_policy(&client, req, &fast_policy())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::OK);
+        assert_eq!(served.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_get_gives_up() {
+        let (url, served) = flaky_server(10).await;
+        let client = reqwest::Client::new();
+        let req = client.get(&url).build().unwrap();
+        let err = execute_with_policy(&client, req, &fast_policy())
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+        assert!(err.msg().unwrap().contains("3 attempts"));
+        assert_eq!(served.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn no_retry_post() {
+        let (url, served) = flaky_server(2).await;
+        let client = reqwest::Client::new();
+        let req = client.post(&url).build().unwrap();
+        let resp = execute_with_policy(&client, req, &fast_policy())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(served.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_post_opt_in() {
+        let (url, served) = flaky_server(2).await;
+        let client = reqwest::Client::new();
+        let req = client.post(&url).body("corn").build().unwrap();
+        let policy = RetryPolicy {
+            retry_non_idempotent: true,
+            ..fast_policy()
+        };
+        let resp = execute_with_policy(&client, req, &policy).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::OK);
+        assert_eq!(served.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_shed_post() {
+        let (url, served) = failing_server(2, "429 Too Many Requests").await;
+        let client = HttpClient::from(reqwest::Client::new());
+        // shed posts were never handled so they are retried
+        let req = client.post(&url).body("corn").build().unwrap();
+        let resp = retry_shed(&client, req, &fast_policy()).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::OK);
+        assert_eq!(served.load(Ordering::SeqCst), 3);
+        // we give up once we are out of attempts
+        let (url, served) = failing_server(10, "429 Too Many Requests").await;
+        let req = client.post(&url).body("corn").build().unwrap();
+        let err = retry_shed(&client, req, &fast_policy()).await.unwrap_err();
+        assert_eq!(err.status(), Some(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(served.load(Ordering::SeqCst), 3);
+        // other errors may have been handled so they are not retried
+        let (url, served) = flaky_server(2).await;
+        let req = client.post(&url).body("corn").build().unwrap();
+        let resp = retry_shed(&client, req, &fast_policy()).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(served.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn incompatible_server_version() {
+        let host = versioned_server("999.0.0").await;
+        let settings = ClientSettings {
+            strict_version: true,
+            ..Default::default()
+        };
+        let client = HttpClient::new(reqwest::Client::new(), &settings);
+        // we don't know the version of an API until we talk to it
+        assert_eq!(client.server_version(), None);
+        // strict clients fail requests to incompatible APIs
+        let req = client
+            .get(format!("{host}/api/basic/ping"))
+            .build()
+            .unwrap();
+        let err = execute(&client, req, OperationClass::Metadata)
+            .await
+            .unwrap_err();
+        assert!(err.msg().unwrap().contains("999.0.0"));
+        assert_eq!(client.server_version(), Some(Version::new(999, 0, 0)));
+        // strict clients keep failing requests after the first one
+        let req = client
+            .get(format!("{host}/api/basic/ping"))
+            .build()
+            .unwrap();
+        let resp = execute_with_policy(&client, req, &fast_policy())
+            .await
+            .unwrap();
+        assert!(check_server_version(&client, &resp, true).is_err());
+        // other clients only warn once
+        let client = HttpClient::from(reqwest::Client::new());
+        assert!(check_server_version(&client, &resp, false).unwrap());
+        assert!(!check_server_version(&client, &resp, false).unwrap());
+        // each client tracks the version of the API it talks to on its own
+        let other = HttpClient::from(reqwest::Client::new());
+        assert_eq!(other.server_version(), None);
+        // clones of a client share what they know about the API
+        assert_eq!(client.clone().server_version(), client.server_version());
+    }
+
+    #[tokio::test]
+    async fn compatible_server_version() {
+        let host = versioned_server(env!("CARGO_PKG_VERSION")).await;
+        let client = HttpClient::from(reqwest::Client::new());
+        let req = client
+            .get(format!("{host}/api/basic/ping"))
+            .build()
+            .unwrap();
+        let resp = execute_with_policy(&client, req, &fast_policy())
+            .await
+            .unwrap();
+        assert!(!check_server_version(&client, &resp, true).unwrap());
+        let expected = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+        assert_eq!(client.server_version(), Some(expected));
+    }
+
+    #[test]
+    fn version_compatibility() {
+        let compatible = |client: &str, server: &str| {
+            versions_compatible(
+                &Version::parse(client).unwrap(),
+                &Version::parse(server).unwrap(),
+            )
+        };
+        assert!(compatible("1.7.0", "1.7.3"));
+        assert!(compatible("1.7.0", "1.2.0"));
+        assert!(compatible("1.2.0", "1.7.0"));
+        assert!(!compatible("1.7.0", "2.0.0"));
+        assert!(!compatible("2.0.0", "1.7.0"));
+        // pre 1.0 minor versions are breaking
+        assert!(compatible("0.3.0", "0.3.9"));
+        assert!(!compatible("0.3.0", "0.4.0"));
+        assert!(!compatible("0.3.0", "1.3.0"));
+    }
+
+    #[test]
+    fn granular_timeouts_default_to_timeout() {
+        let settings = ClientSettings {
+            timeout: 30,
+            download_timeout: Some(3600),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.timeout_for(OperationClass::Download).as_secs(),
+            3600
+        );
+        assert_eq!(settings.timeout_for(OperationClass::Metadata).as_secs(), 30);
+        // uploads get a long timeout since files can be really large
+        assert_eq!(
+            settings.timeout_for(OperationClass::Upload).as_secs(),
+            DEFAULT_UPLOAD_TIMEOUT
+        );
+    }
+
+    #[test]
+    fn timeouts_per_client() {
+        let base = "http://127.0.0.1";
+        // build two clients with different metadata timeouts
+        let short = HttpClient::new(
+            reqwest::Client::new(),
+            &ClientSettings {
+                metadata_timeout: Some(5),
+                ..Default::default()
+            },
+        );
+        let long = HttpClient::new(
+            reqwest::Client::new(),
+            &ClientSettings {
+                metadata_timeout: Some(60),
+                ..Default::default()
+            },
+        );
+        // each client should keep using its own timeout
+        let mut req = short.get(format!("{base}/api/groups/")).build().unwrap();
+        short
+            .settings
+            .fill_timeout(&mut req, OperationClass::Metadata);
+        assert_eq!(req.timeout(), Some(&Duration::from_secs(5)));
+        let mut req = long.get(format!("{base}/api/groups/")).build().unwrap();
+        long.settings
+            .fill_timeout(&mut req, OperationClass::Metadata);
+        assert_eq!(req.timeout(), Some(&Duration::from_secs(60)));
+        // requests use the timeout for the class they were sent as no matter their route
+        let mut req = short
+            .get(format!("{base}/api/files/sample/abc/download"))
+            .build()
+            .unwrap();
+        short
+            .settings
+            .fill_timeout(&mut req, OperationClass::Metadata);
+        assert_eq!(req.timeout(), Some(&Duration::from_secs(5)));
+        let mut req = short.post(format!("{base}/api/files/")).build().unwrap();
+        short
+            .settings
+            .fill_timeout(&mut req, OperationClass::Upload);
+        assert_eq!(
+            req.timeout(),
+            Some(&Duration::from_secs(DEFAULT_UPLOAD_TIMEOUT))
+        );
+        // requests that set their own timeout keep it
+        let mut req = short
+            .get(format!("{base}/api/groups/"))
+            .timeout(Duration::from_secs(600))
+            .build()
+            .unwrap();
+        short
+            .settings
+            .fill_timeout(&mut req, OperationClass::Metadata);
+        assert_eq!(req.timeout(), Some(&Duration::from_secs(600)));
+    }
+
+    #[tokio::test]
+    async fn build_http2_client() {
+        let settings = ClientSettings {
+            http2_prior_knowledge: true,
+            http2_keep_alive_interval: Some(30),
+            tcp_keepalive: Some(60),
+            pool_idle_timeout: Some(90),
+            pool_max_idle_per_host: Some(32),
+            ..Default::default()
+        };
+        // HTTP/2 prior knowledge works for plain http hosts
+        assert!(
+            super::build_reqwest_client("http://127.0.0.1", &settings)
+                .await
+                .is_ok()
+        );
+        // but TLS hosts should negotiate HTTP/2 instead
+        assert!(
+            super::build_reqwest_client("https://127.0.0.1", &settings)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn invalid_keep_alive() {
+        let settings = ClientSettings {
+            pool_idle_timeout: Some(90),
+            pool_max_idle_per_host: Some(0),
+            ..Default::default()
+        };
+        assert!(settings.validate_transport("http://127.0.0.1").is_err());
+        let settings = ClientSettings {
+            tcp_keepalive: Some(0),
+            ..Default::default()
+        };
+        assert!(settings.validate_transport("http://127.0.0.1").is_err());
+    }
+
+    #[cfg(feature = "sync")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn blocking_in_runtime() {
+        let client = HttpClient::from(reqwest::Client::new());
+        let basic = crate::client::BasicBlocking::new("http://127.0.0.1:1", &client);
+        // blocking on a worker thread should block in place instead of panicking
+        assert!(basic.ping().is_err());
+    }
+
+    #[cfg(feature = "sync")]
+    #[tokio::test]
+    #[should_panic(expected = "cannot be called from within a current thread async runtime")]
+    async fn blocking_in_current_thread_runtime() {
+        let client = HttpClient::from(reqwest::Client::new());
+        let basic = crate::client::BasicBlocking::new("http://127.0.0.1:1", &client);
+        // a current thread runtime has no other worker to move its tasks to
+        basic.ping().unwrap();
+    }
+
+    #[cfg(feature = "sync")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn blocking_in_spawn_blocking() {
+        // blocking threads can block on our runtime directly
+        let pinged = tokio::task::spawn_blocking(|| {
+            let client = HttpClient::from(reqwest::Client::new());
+            let basic = crate::client::BasicBlocking::new("http://127.0.0.1:1", &client);
+            basic.ping().is_err()
+        });
+        assert!(pinged.await.unwrap());
+    }
+}
@@ -1,7 +1,7 @@
 use uuid::Uuid;
 
 use super::traits::{GenericClient, NotificationsClient};
-use super::{Cursor, Error};
+use super::{Cursor, Error, HttpClient};
 use crate::models::{
     Image, ImageKey, ImageRequest, ImageUpdate, Notification, NotificationParams,
     NotificationRequest,
@@ -27,7 +27,7 @@ pub struct Images {
     /// token to use for auth
     token: String,
     /// A reqwest client for reqwests
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -46,13 +46,13 @@ impl Images {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Images;
+    /// use thorium::client::{HttpClient, Images};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let images = Images::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Images {
             host: host.to_owned(),
@@ -437,7 +437,7 @@ impl GenericClient for Images {
     }
 
     /// Provide the configured client from `self`
-    fn client(&self) -> &reqwest::Client {
+    fn client(&self) -> &HttpClient {
         &self.client
     }
 
@@ -4,10 +4,10 @@ use uuid::Uuid;
 #[cfg(feature = "trace")]
 use tracing::instrument;
 
-use super::Error;
+use super::{Error, HttpClient};
 use crate::models::{
-    Checkpoint, Deadline, GenericJob, HandleJobResponse, ImageScaler, JobResets, RunningJob,
-    StageLogsAdd,
+    Checkpoint, Deadline, GenericJob, HandleJobResponse, ImageScaler, JobHeartbeat, JobResets,
+    JobStarted, RunningJob, StageLogsAdd,
 };
 use crate::{send, send_build};
 
@@ -25,7 +25,7 @@ pub struct Jobs {
     host: String,
     /// token to use for auth
     token: String,
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -44,13 +44,13 @@ impl Jobs {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Jobs;
+    /// use thorium::client::{HttpClient, Jobs};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let jobs = Jobs::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Jobs {
             host: host.to_owned(),
@@ -117,6 +117,102 @@ impl Jobs {
         send_build!(self.client, req, Vec<GenericJob>)
     }
 
+    /// Tell Thorium a claimed job has started executing
+    ///
+    /// This starts the job's execution deadline so time it spent waiting in a queue or
+    /// setting up doesn't count against its image's timeout.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the job that started
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // claim a job from Thorium
+    /// let jobs = thorium.jobs.claim("Corn", "Harvest", "CornHarvester", "prod0", "node0", "esoteria", 1).await?;
+    /// for job in jobs.iter() {
+    ///     // start our execution clock right before we start working on this job
+    ///     let started = thorium.jobs.start(&job.id).await?;
+    /// }
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Jobs::start",
+            skip_all,
+            fields(job = id.to_string()),
+            err(Debug)
+        )
+    )]
+    pub async fn start(&self, id: &Uuid) -> Result<JobStarted, Error> {
+        // build url for starting a job
+        let url = format!("{base}/api/jobs/handle/{id}/start", base = &self.host);
+        // build request
+        let req = self.client.post(&url).header("authorization", &self.token);
+        // send this request and build a json value from the response
+        send_build!(self.client, req, JobStarted)
+    }
+
+    /// Tell Thorium a started job is still running and push back its execution deadline
+    ///
+    /// Each heartbeat extends the job's deadline by its image's timeout up to the
+    /// max extension set in the Thorium config.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the job to send a heartbeat for
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // keep a long running job from being reclaimed
+    /// let id = Uuid::parse_str("b2a58dc9-8f9a-4f4e-bb2a-b7ed0a11b4c8")?;
+    /// let heartbeat = thorium.jobs.heartbeat(&id).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Jobs::heartbeat",
+            skip_all,
+            fields(job = id.to_string()),
+            err(Debug)
+        )
+    )]
+    pub async fn heartbeat(&self, id: &Uuid) -> Result<JobHeartbeat, Error> {
+        // build url for sending a heartbeat for a job
+        let url = format!("{base}/api/jobs/handle/{id}/heartbeat", base = &self.host);
+        // build request
+        let req = self.client.post(&url).header("authorization", &self.token);
+        // send this request and build a json value from the response
+        send_build!(self.client, req, JobHeartbeat)
+    }
+
     /// Tell Thorium this job has succeeded and to proceed with it
     ///
     /// # Arguments
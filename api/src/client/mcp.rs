@@ -0,0 +1,241 @@
+//! Support the MCP routes in the Thorium client
+
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+#[cfg(feature = "trace")]
+use tracing::instrument;
+
+use super::{Error, HttpClient};
+use crate::models::mcp::{MCP_PROTOCOL_VERSION, MCP_SESSION_HEADER};
+use crate::models::{McpRequest, McpResponse, McpTool, McpToolList};
+use crate::send;
+
+// import our static runtime if we need a blocking client
+#[cfg(feature = "sync")]
+use super::RUNTIME;
+
+// import python bindings
+#[cfg(feature = "python")]
+use pyo3::{pyclass, pymethods};
+
+/// The content types MCP servers require clients to accept
+const MCP_ACCEPT: &str = "application/json, text/event-stream";
+
+#[cfg_attr(feature = "sync", thorium_derive::blocking_struct(python))]
+#[derive(Clone)]
+pub struct Mcp {
+    /// The host to talk to the Thorium api at
+    host: String,
+    /// The token to use for auth
+    token: String,
+    /// A client to use when making requests
+    client: HttpClient,
+}
+
+#[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
+impl Mcp {
+    /// Creates a new MCP handler
+    ///
+    /// Instead of directly creating this handler you likely want to simply create a
+    /// `thorium::Thorium` and use the handler within that instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - url/ip of the Thorium api
+    /// * `token` - The token used for authentication
+    /// * `client` - The reqwest client to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::client::{HttpClient, Mcp};
+    ///
+    /// let client = HttpClient::from(reqwest::Client::new());
+    /// let mcp = Mcp::new("http://127.0.0.1", "token", &client);
+    /// ```
+    #[must_use]
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
+        // build mcp route handler
+        Mcp {
+            host: host.to_owned(),
+            token: token.to_owned(),
+            client: client.clone(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "sync", thorium_derive::blocking_struct(python))]
+impl Mcp {
+    /// List the tools exposed by Thorium's MCP server
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // list the tools our MCP server exposes
+    /// let tools = thorium.mcp.list_tools().await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Mcp::list_tools", skip_all, err(Debug))
+    )]
+    pub async fn list_tools(&self) -> Result<Vec<McpTool>, Error> {
+        // start a new session with our MCP server
+        let session = self.initialize().await?;
+        // list all of our tools and then close our session even if listing failed
+        let tools = self.list_tools_in(&session).await;
+        self.close(&session).await?;
+        tools
+    }
+}
+
+impl Mcp {
+    /// Build a request to our MCP server
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to send
+    /// * `session` - The session to send this message in if we have one
+    fn build_req(&self, msg: &McpRequest, session: Option<&str>) -> reqwest::RequestBuilder {
+        // build the request for this message
+        let req = self
+            .client
+            .post(format!("{}/api/mcp", self.host))
+            .header("authorization", &self.token)
+            .header(ACCEPT, MCP_ACCEPT)
+            .header(CONTENT_TYPE, "application/json")
+            .json(msg);
+        // add our session if we have one
+        match session {
+            Some(session) => req.header(MCP_SESSION_HEADER, session),
+            None => req,
+        }
+    }
+
+    /// Start a new session with our MCP server
+    async fn initialize(&self) -> Result<String, Error> {
+        // build our initialize request
+        let msg = McpRequest::new(0, "initialize").params(json!({
+            "protocolVersion": MCP_PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": {
+                "name": "Thorium",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        }));
+        let resp = send!(self.client, self.build_req(&msg, None))?;
+        // get the session our server started for us
+        let session = match resp
+            .headers()
+            .get(MCP_SESSION_HEADER)
+            .and_then(|session| session.to_str().ok())
+        {
+            Some(session) => session.to_owned(),
+            None => return Err(Error::new("MCP server did not start a session")),
+        };
+        // make sure our server accepted our initialize request
+        parse_response::<serde_json::Value>(&resp.text().await?, 0)?;
+        // tell our server that we are ready to start making requests
+        let msg = McpRequest::notification("notifications/initialized");
+        send!(self.client, self.build_req(&msg, Some(&session)))?;
+        Ok(session)
+    }
+
+    /// List all tools in an MCP session
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The session to list tools in
+    async fn list_tools_in(&self, session: &str) -> Result<Vec<McpTool>, Error> {
+        let mut tools = Vec::default();
+        let mut cursor = None;
+        // keep getting pages of tools until we have them all
+        for id in 1.. {
+            // build the request for this page of tools
+            let mut msg = McpRequest::new(id, "tools/list");
+            if let Some(cursor) = cursor.take() {
+                msg = msg.params(json!({ "cursor": cursor }));
+            }
+            let resp = send!(self.client, self.build_req(&msg, Some(session)))?;
+            let page = parse_response::<McpToolList>(&resp.text().await?, id)?;
+            tools.extend(page.tools);
+            // stop once there are no more pages
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        Ok(tools)
+    }
+
+    /// Close an MCP session
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The session to close
+    async fn close(&self, session: &str) -> Result<(), Error> {
+        // build our request to close this session
+        let req = self
+            .client
+            .delete(format!("{}/api/mcp", self.host))
+            .header("authorization", &self.token)
+            .header(MCP_SESSION_HEADER, session);
+        send!(self.client, req)?;
+        Ok(())
+    }
+}
+
+/// Get the result of a request from an MCP response body
+///
+/// MCP servers can respond with either plain JSON or a stream of server sent events.
+///
+/// # Arguments
+///
+/// * `body` - The body of the response
+/// * `id` - The id of the request to get the result for
+fn parse_response<T: DeserializeOwned>(body: &str, id: u64) -> Result<T, Error> {
+    // get the JSON messages in this body
+    let msgs: Vec<&str> = if body.trim_start().starts_with('{') {
+        vec![body]
+    } else {
+        body.lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(str::trim)
+            .filter(|data| !data.is_empty())
+            .collect()
+    };
+    // find the response to our request
+    for msg in msgs {
+        // skip any messages that are not responses to our request
+        let Ok(resp) = serde_json::from_str::<McpResponse<serde_json::Value>>(msg) else {
+            continue;
+        };
+        if resp.id != Some(id) {
+            continue;
+        }
+        // return our result or the error our server responded with
+        return match (resp.result, resp.error) {
+            (Some(result), _) => Ok(serde_json::from_value(result)?),
+            (None, Some(err)) => Err(Error::new(format!(
+                "MCP error {}: {}",
+                err.code, err.message
+            ))),
+            (None, None) => Err(Error::new("MCP response is missing a result")),
+        };
+    }
+    Err(Error::new(format!(
+        "MCP server did not respond to request {id}"
+    )))
+}
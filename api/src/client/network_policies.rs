@@ -2,7 +2,7 @@
 
 use uuid::Uuid;
 
-use super::Error;
+use super::{Error, HttpClient};
 use crate::models::{
     Cursor, NetworkPolicy, NetworkPolicyListLine, NetworkPolicyListOpts, NetworkPolicyRequest,
     NetworkPolicyUpdate,
@@ -25,7 +25,7 @@ pub struct NetworkPolicies {
     /// token to use for auth
     token: String,
     /// A reqwest client for reqwests
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 /// Create a new list cursor; helpful because the list and `list_details` routes
@@ -64,13 +64,13 @@ impl NetworkPolicies {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::NetworkPolicies;
+    /// use thorium::client::{HttpClient, NetworkPolicies};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let network_policies = NetworkPolicies::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build network policies route handler
         NetworkPolicies {
             host: host.to_owned(),
@@ -1,10 +1,10 @@
 use uuid::Uuid;
 
 use super::traits::{GenericClient, NotificationsClient};
-use super::{Cursor, Error};
+use super::{Cursor, Error, HttpClient};
 use crate::models::{
-    Notification, NotificationParams, NotificationRequest, Pipeline, PipelineKey, PipelineRequest,
-    PipelineUpdate,
+    Notification, NotificationParams, NotificationRequest, Pipeline, PipelineKey, PipelinePlan,
+    PipelineRequest, PipelineTriggerRequest, PipelineTriggers, PipelineUpdate,
 };
 use crate::{send, send_build};
 
@@ -18,7 +18,7 @@ pub struct Pipelines {
     host: String,
     /// token to use for auth
     token: String,
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -37,13 +37,13 @@ impl Pipelines {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Pipelines;
+    /// use thorium::client::{HttpClient, Pipelines};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let pipelines = Pipelines::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Pipelines {
             host: host.to_owned(),
@@ -132,6 +132,49 @@ impl Pipelines {
         send_build!(self.client, req, Pipeline)
     }
 
+    /// Plans out the jobs a [`Pipeline`] would create without creating any of them
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this pipeline is in
+    /// * `pipeline` - The name of the pipeline to plan
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // plan out a pipeline in Thorium
+    /// let plan = thorium.pipelines.plan("Corn", "CornHarvest").await?;
+    /// // print any problems with this pipeline
+    /// for warning in &plan.warnings {
+    ///     println!("{warning}");
+    /// }
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn plan(&self, group: &str, pipeline: &str) -> Result<PipelinePlan, Error> {
+        // build url for planning a pipeline
+        let url = format!(
+            "{base}/api/pipelines/plan/{group}/{pipeline}",
+            base = self.host,
+            group = group,
+            pipeline = pipeline
+        );
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request and build a pipeline plan from the response
+        send_build!(self.client, req, PipelinePlan)
+    }
+
     /// Updates a [`Pipeline`] in Thorium
     ///
     /// # Arguments
@@ -233,6 +276,158 @@ impl Pipelines {
         send!(self.client, req)
     }
 
+    /// Lists the triggers for a [`Pipeline`]
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this pipeline is in
+    /// * `pipeline` - The name of the pipeline to list triggers for
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // list the triggers for a pipeline in Thorium
+    /// let triggers = thorium.pipelines.list_triggers("Corn", "CornHarvest").await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn list_triggers(
+        &self,
+        group: &str,
+        pipeline: &str,
+    ) -> Result<PipelineTriggers, Error> {
+        // build url for listing a pipeline's triggers
+        let url = format!(
+            "{base}/api/pipelines/triggers/{group}/{pipeline}",
+            base = self.host,
+            group = group,
+            pipeline = pipeline
+        );
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request and build the triggers from the response
+        send_build!(self.client, req, PipelineTriggers)
+    }
+
+    /// Adds a trigger to a [`Pipeline`]
+    ///
+    /// The group and pipeline must already exist and the trigger's name must not
+    /// already be in use in this pipeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this pipeline is in
+    /// * `pipeline` - The name of the pipeline to add a trigger to
+    /// * `req` - The trigger to add
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use thorium::Thorium;
+    /// use thorium::models::{EventTrigger, PipelineTriggerRequest, TagType};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // run this pipeline whenever a file is tagged with 'Crop=Corn'
+    /// let trigger = EventTrigger::Tag {
+    ///     tag_types: vec![TagType::Files],
+    ///     required: HashMap::from([("Crop".to_owned(), vec!["Corn".to_owned()])]),
+    ///     not: HashMap::default(),
+    /// };
+    /// let req = PipelineTriggerRequest::new("corn-files", trigger);
+    /// thorium.pipelines.create_trigger("Corn", "CornHarvest", &req).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn create_trigger(
+        &self,
+        group: &str,
+        pipeline: &str,
+        req: &PipelineTriggerRequest,
+    ) -> Result<reqwest::Response, Error> {
+        // build url for adding a trigger to a pipeline
+        let url = format!(
+            "{base}/api/pipelines/triggers/{group}/{pipeline}",
+            base = self.host,
+            group = group,
+            pipeline = pipeline
+        );
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .json(req)
+            .header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
+
+    /// Deletes a trigger from a [`Pipeline`]
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this pipeline is in
+    /// * `pipeline` - The name of the pipeline to delete a trigger from
+    /// * `trigger` - The name of the trigger to delete
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // delete a trigger from a pipeline in Thorium
+    /// thorium.pipelines.delete_trigger("Corn", "CornHarvest", "corn-files").await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn delete_trigger(
+        &self,
+        group: &str,
+        pipeline: &str,
+        trigger: &str,
+    ) -> Result<reqwest::Response, Error> {
+        // build url for deleting a trigger from a pipeline
+        let url = format!(
+            "{base}/api/pipelines/triggers/{group}/{pipeline}/{trigger}",
+            base = self.host,
+            group = group,
+            pipeline = pipeline,
+            trigger = trigger
+        );
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
+
     /// Lists [`Pipeline`]s in a group
     ///
     /// # Arguments
@@ -405,7 +600,7 @@ impl GenericClient for Pipelines {
     }
 
     /// Provide the configured client from `self`
-    fn client(&self) -> &reqwest::Client {
+    fn client(&self) -> &HttpClient {
         &self.client
     }
 
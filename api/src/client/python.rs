@@ -3,6 +3,7 @@
 //! The actual Python module is exported/built in the `thorpy` crate which has
 //! this crate as a dependency.
 
+mod cursors;
 mod files;
 
 use base64::Engine;
@@ -11,8 +12,12 @@ use std::path::PathBuf;
 
 use crate::{
     client::{
-        conf::default_client_timeout, helpers, BasicBlocking, ClientSettings, FilesBlocking,
-        JobsBlocking, ReactionsBlocking,
+        conf::{
+            default_client_timeout, default_retry_attempts, default_retry_base_delay,
+            default_retry_max_delay,
+        },
+        helpers, BasicBlocking, ClientSettings, FilesBlocking, JobsBlocking, McpBlocking,
+        ReactionsBlocking, RetryPolicy,
     },
     Error, ThoriumBlocking,
 };
@@ -41,7 +46,7 @@ impl ThoriumBlocking {
         settings: ClientSettings,
     ) -> Result<Self, Error> {
         // build a client
-        let client = helpers::build_blocking_reqwest_client(&settings)?;
+        let client = helpers::build_blocking_reqwest_client(host, &settings)?;
         // authenticate if needed
         let (token, expires) = match (token, username, password) {
             (None, Some(username), Some(password)) => {
@@ -54,19 +59,21 @@ impl ThoriumBlocking {
         let encoded = base64::engine::general_purpose::STANDARD.encode(token.as_bytes());
         // build token auth string
         let auth_str = format!("token {encoded}");
-        let basic = BasicBlocking::new(host, &client);
+        let basic = BasicBlocking::new(host, &client).token(&auth_str);
         let jobs = JobsBlocking::new(host, &auth_str, &client);
         let reactions = ReactionsBlocking::new(host, &auth_str, &client);
         let files = FilesBlocking::new(host, &auth_str, &client);
+        let mcp = McpBlocking::new(host, &auth_str, &client);
         Ok(Self {
             basic,
             jobs,
             reactions,
             files,
+            mcp,
             host: host.to_string(),
             _auth_str: auth_str,
             expires,
-            _client: client,
+            client,
         })
     }
 
@@ -102,20 +109,78 @@ impl ClientSettings {
             invalid_certs=false,
             invalid_hostnames=false,
             certificate_authorities=Vec::new(),
-            timeout=default_client_timeout()
+            timeout=default_client_timeout(),
+            metadata_timeout=None,
+            upload_timeout=None,
+            download_timeout=None,
+            retry=RetryPolicy::default(),
+            http2_prior_knowledge=false,
+            http2_keep_alive_interval=None,
+            tcp_keepalive=None,
+            pool_idle_timeout=None,
+            pool_max_idle_per_host=None,
+            strict_version=false
         )
     )]
+    #[allow(clippy::too_many_arguments)]
     fn new_py(
         invalid_certs: bool,
         invalid_hostnames: bool,
         certificate_authorities: Vec<PathBuf>,
         timeout: u64,
+        metadata_timeout: Option<u64>,
+        upload_timeout: Option<u64>,
+        download_timeout: Option<u64>,
+        retry: RetryPolicy,
+        http2_prior_knowledge: bool,
+        http2_keep_alive_interval: Option<u64>,
+        tcp_keepalive: Option<u64>,
+        pool_idle_timeout: Option<u64>,
+        pool_max_idle_per_host: Option<usize>,
+        strict_version: bool,
     ) -> Self {
         Self {
             invalid_certs,
             invalid_hostnames,
             certificate_authorities,
             timeout,
+            metadata_timeout,
+            upload_timeout,
+            download_timeout,
+            retry,
+            http2_prior_knowledge,
+            http2_keep_alive_interval,
+            tcp_keepalive,
+            pool_idle_timeout,
+            pool_max_idle_per_host,
+            strict_version,
+        }
+    }
+}
+
+#[pymethods]
+impl RetryPolicy {
+    /// Create a new retry policy
+    #[new]
+    #[pyo3(signature =
+        (
+            max_attempts=default_retry_attempts(),
+            base_delay=default_retry_base_delay(),
+            max_delay=default_retry_max_delay(),
+            retry_non_idempotent=false
+        )
+    )]
+    fn new_py(
+        max_attempts: u32,
+        base_delay: u64,
+        max_delay: u64,
+        retry_non_idempotent: bool,
+    ) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+            max_delay,
+            retry_non_idempotent,
         }
     }
 }
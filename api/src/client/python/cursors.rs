@@ -0,0 +1,44 @@
+//! Python-specific helpers for restricting cursors to a range of time
+
+use chrono::{DateTime, Utc};
+use pyo3::pymethods;
+
+use crate::Error;
+use crate::client::{SearchDate, SearchRange};
+
+#[pymethods]
+impl SearchDate {
+    /// Build a search range covering the last number of days
+    ///
+    /// # Arguments
+    ///
+    /// * `days` - The number of days to search back from now
+    #[staticmethod]
+    #[pyo3(name = "last_days")]
+    fn last_days_py(days: i64) -> Result<SearchRange, Error> {
+        Self::last_days(days)
+    }
+
+    /// Build a search range covering the last number of hours
+    ///
+    /// # Arguments
+    ///
+    /// * `hours` - The number of hours to search back from now
+    #[staticmethod]
+    #[pyo3(name = "last_hours")]
+    fn last_hours_py(hours: i64) -> Result<SearchRange, Error> {
+        Self::last_hours(hours)
+    }
+
+    /// Build a search range between two dates
+    ///
+    /// # Arguments
+    ///
+    /// * `oldest` - The oldest date to search
+    /// * `newest` - The newest date to search
+    #[staticmethod]
+    #[pyo3(name = "between")]
+    fn between_py(oldest: DateTime<Utc>, newest: DateTime<Utc>) -> Result<SearchRange, Error> {
+        Self::between(oldest, newest)
+    }
+}
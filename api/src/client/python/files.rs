@@ -7,13 +7,13 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
-use pyo3::pymethods;
+use pyo3::{Python, pymethods};
 use uuid::Uuid;
 
 use crate::Error;
 use crate::client::{FilesBlocking, ResultsClientBlocking};
 use crate::models::python::{SampleCursor, SampleListLineCursor, TagCountsCursor};
-use crate::models::{Attachment, FileListOpts, OutputMap, ResultGetParams};
+use crate::models::{Attachment, FileDownloadOpts, FileListOpts, OutputMap, ResultGetParams};
 
 #[pymethods]
 impl FileListOpts {
@@ -29,6 +29,7 @@ impl FileListOpts {
             groups = Vec::new(),
             tags = HashMap::new(),
             tags_case_insensitive = false,
+            partition_hint = None,
         )
     )]
     #[allow(clippy::too_many_arguments)]
@@ -41,6 +42,7 @@ impl FileListOpts {
         groups: Vec<String>,
         tags: HashMap<String, Vec<String>>,
         tags_case_insensitive: bool,
+        partition_hint: Option<u32>,
     ) -> Self {
         Self {
             cursor,
@@ -51,6 +53,7 @@ impl FileListOpts {
             groups,
             tags,
             tags_case_insensitive,
+            partition_hint,
         }
     }
 }
@@ -98,4 +101,40 @@ impl FilesBlocking {
     ) -> Result<Attachment, Error> {
         self.download_result_file(sha256, tool, &result_id, path)
     }
+
+    /// Stream a file straight to a path on disk without loading it into memory
+    ///
+    /// The GIL is released while the file is downloaded so other Python threads
+    /// can keep running.
+    ///
+    /// # Arguments
+    ///
+    /// * `py` - The Python interpreter token
+    /// * `sha256` - The sha256 of the file to download
+    /// * `dest` - The path to write this file to
+    /// * `uncart` - Whether to uncart this file as it is written to disk
+    #[pyo3(
+        name = "download_to_path",
+        signature = (
+            sha256: "str",
+            dest: "str | PathLike",
+            uncart: "bool" = false
+        ) -> "Path"
+    )]
+    fn download_to_path_py(
+        &self,
+        py: Python<'_>,
+        sha256: &str,
+        dest: PathBuf,
+        uncart: bool,
+    ) -> Result<PathBuf, Error> {
+        // release the GIL while we stream this file to disk
+        py.detach(|| {
+            // only uncart this file if we were asked to
+            let mut opts = FileDownloadOpts::default().uncart_by_value(uncart);
+            // stream this file to disk and drop our handle to it
+            self.download(sha256, &dest, &mut opts)?;
+            Ok(dest)
+        })
+    }
 }
@@ -10,12 +10,12 @@ use tokio_util::io::StreamReader;
 use uuid::Uuid;
 
 use super::traits::TransferProgress;
-use super::{Cursor, Error, LogsCursor};
+use super::{Cursor, Error, HttpClient, LogsCursor, NdjsonStream, OperationClass};
 use crate::models::{
     BulkReactionResponse, CartedFile, DownloadedFile, FileDownloadOpts, Reaction, ReactionCache,
     ReactionCacheFileUpdate, ReactionCacheUpdate, ReactionCreation, ReactionListParams,
-    ReactionRequest, ReactionStatus, ReactionUpdate, StageLogs, StageLogsAdd, StatusUpdate,
-    UncartedFile,
+    ReactionRequest, ReactionSamplesAdd, ReactionStatus, ReactionUpdate, StageLogs, StageLogsAdd,
+    StatusUpdate, UncartedFile, ZipDownloadParams,
 };
 use crate::{send, send_build, send_bytes};
 
@@ -27,6 +27,9 @@ use super::RUNTIME;
 #[cfg(feature = "python")]
 use pyo3::{pyclass, pymethods};
 
+/// The max number of chunks to submit at once when streaming bulk reaction creation
+const BULK_STREAMING_CONCURRENCY: usize = 4;
+
 /// An async Reactions handler for the Thorium client
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct(python))]
 #[derive(Clone)]
@@ -34,7 +37,7 @@ pub struct Reactions {
     host: String,
     /// token to use for auth
     token: String,
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -53,13 +56,13 @@ impl Reactions {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Reactions;
+    /// use thorium::client::{HttpClient, Reactions};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let reactions = Reactions::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Reactions {
             host: host.to_owned(),
@@ -74,6 +77,9 @@ impl Reactions {
 impl Reactions {
     /// Creates a [`Reaction`] in Thorium
     ///
+    /// If the request has no group then the reaction is created in the current
+    /// user's default group.
+    ///
     /// # Arguments
     ///
     /// * `data` - The reaction request to use to create a reaction
@@ -101,6 +107,9 @@ impl Reactions {
     ///     .args("SoyBeanHarvest", soy_args);
     /// // create a reaction in Thorium
     /// let react_create = thorium.reactions.create(&react_req).await?;
+    /// // create a reaction in our default group
+    /// let default_req = ReactionRequest::in_default_group("Harvest");
+    /// let react_create = thorium.reactions.create(&default_req).await?;
     /// # // allow test code to be compiled but don't unwrap as no API instance would be up
     /// # Ok(())
     /// # }
@@ -232,6 +241,101 @@ impl Reactions {
         send_build!(self.client, req, BulkReactionResponse)
     }
 
+    /// Create a large number of [`Reaction`]s in bulk by submitting them in chunks
+    ///
+    /// Chunks are submitted a few at a time and their responses are merged together with
+    /// the indexes in `errors` and `deduplicated` pointing at each request's place in
+    /// `reqs`. If `continue_on_error` is false then no more chunks are submitted once a
+    /// chunk has any errors and the error for any chunk that could not be submitted is
+    /// returned. Otherwise a chunk that could not be submitted has its error set for each
+    /// of its requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `reqs` - The reaction requests to create reactions for
+    /// * `chunk_size` - The max number of reactions to create in a single request
+    /// * `continue_on_error` - Whether to keep submitting chunks after a chunk fails
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::ReactionRequest;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build a reaction request for each field that needs to be harvested
+    /// let reqs = (0..5000)
+    ///     .map(|field| ReactionRequest::new("Corn", "Harvest").tag(format!("field-{field}")))
+    ///     .collect::<Vec<ReactionRequest>>();
+    /// // create these reactions 500 at a time
+    /// let react_creates = thorium.reactions.create_bulk_streaming(&reqs, 500, true).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            name = "Thorium::Reactions::create_bulk_streaming",
+            skip(self, reqs),
+            fields(count = reqs.len()),
+            err(Debug)
+        )
+    )]
+    pub async fn create_bulk_streaming(
+        &self,
+        reqs: &[ReactionRequest],
+        chunk_size: usize,
+        continue_on_error: bool,
+    ) -> Result<BulkReactionResponse, Error> {
+        // make sure our chunk size is valid
+        if chunk_size == 0 {
+            return Err(Error::new("The chunk size must be greater than 0"));
+        }
+        // split our requests into chunks along with the index each chunk starts at
+        let chunks = reqs
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(index, chunk)| (index * chunk_size, chunk))
+            .collect::<Vec<(usize, &[ReactionRequest])>>();
+        let mut merged = BulkReactionResponse::with_capacity(reqs.len());
+        // submit our chunks in waves so no in flight chunks are lost if we stop early
+        for wave in chunks.chunks(BULK_STREAMING_CONCURRENCY) {
+            let responses =
+                futures::future::join_all(wave.iter().map(|(_, chunk)| self.create_bulk(chunk)))
+                    .await;
+            // merge the responses for this wave of chunks
+            let mut failed = false;
+            for ((offset, chunk), response) in wave.iter().zip(responses) {
+                match response {
+                    Ok(response) => {
+                        failed |= !response.errors.is_empty();
+                        merged.merge(response, *offset);
+                    }
+                    Err(error) if continue_on_error => {
+                        // set this error for every request in this chunk
+                        let msg = error.to_string();
+                        merged.errors.extend(
+                            (*offset..*offset + chunk.len()).map(|index| (index, msg.clone())),
+                        );
+                    }
+                    Err(error) => return Err(error),
+                }
+            }
+            // stop submitting chunks if one failed and we are not continuing on errors
+            if failed && !continue_on_error {
+                break;
+            }
+        }
+        Ok(merged)
+    }
+
     /// Create [`Reaction`]s in bulk for multiple users
     ///
     /// # Arguments
@@ -455,13 +559,9 @@ impl Reactions {
             .client
             .patch(&url)
             .multipart(update.to_form().await?)
-            .header("authorization", &self.token)
-            // use a really long timeout for really large files
-            // this is probably done better some otherway
-            // 86,400 seconds == a day
-            .timeout(std::time::Duration::from_secs(86_400));
-        // send request and build a reaction
-        send!(self.client, req)
+            .header("authorization", &self.token);
+        // send request as an upload so it gets our long upload timeout
+        send!(self.client, req, OperationClass::Upload)
     }
 
     /// Downloads a specific cache file
@@ -518,13 +618,14 @@ impl Reactions {
             "{base}/api/reactions/{group}/{id}/cache/files/{file}",
             base = self.host,
         );
-        // build and send the request
-        let resp = self
+        // build the request
+        let req = self
             .client
             .get(&url)
             .header("authorization", &self.token)
-            .send()
-            .await?;
+            .build()?;
+        // send this request as a download so it gets our download timeout
+        let resp = super::execute(&self.client, req, OperationClass::Download).await?;
         // make sure we got a 200
         match resp.status() {
             StatusCode::OK => {
@@ -649,8 +750,14 @@ impl Reactions {
             .post(&url)
             .header("authorization", &self.token)
             .json(&logs);
-        // send request
-        send!(self.client, req)
+        // send request retrying it if the API is too backed up to write our logs
+        let resp = super::helpers::execute_sheddable(&self.client, req.build()?).await?;
+        // check if this response has an error status or not
+        if resp.status().is_success() {
+            Ok(resp)
+        } else {
+            Err(Error::from(resp))
+        }
     }
 
     /// Gets logs from a specific stage of a [`Reaction`]
@@ -827,6 +934,71 @@ impl Reactions {
         send_build!(self.client, req, Vec<StatusUpdate>)
     }
 
+    /// Appends a batch of status logs to a [`Reaction`] in a single request
+    ///
+    /// The updates are saved in the order they are given with their timestamps preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this reaction is in
+    /// * `id` - The id of the reaction to add status logs to
+    /// * `updates` - The status updates to append
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::{Actions, StatusUpdate};
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // get the reaction to add status logs to
+    /// let id = Uuid::parse_str("d86ce41a-4a5b-43b5-aef9-bf90ff5d09ba")?;
+    /// let reaction = thorium.reactions.get("Corn", id).await?;
+    /// // build our status updates
+    /// let updates = vec![
+    ///     StatusUpdate::for_reaction(&reaction, Actions::ReactionProceeding).msg("Unpacked"),
+    ///     StatusUpdate::for_reaction(&reaction, Actions::ReactionProceeding).msg("Scanned"),
+    /// ];
+    /// // send all of our status updates at once
+    /// thorium.reactions.add_status_logs("Corn", &id, &updates).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            name = "Thorium::Reactions::add_status_logs",
+            skip(self, updates),
+            fields(id = id.to_string(), count = updates.len()),
+            err(Debug)
+        )
+    )]
+    pub async fn add_status_logs(
+        &self,
+        group: &str,
+        id: &Uuid,
+        updates: &[StatusUpdate],
+    ) -> Result<reqwest::Response, Error> {
+        // build url
+        let url = format!("{host}/api/reactions/logs/{group}/{id}", host = &self.host);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .header("authorization", &self.token)
+            .json(updates);
+        // send request
+        send!(self.client, req)
+    }
+
     /// Lists [`Reaction`] names in a group for a specific pipeline
     ///
     /// # Arguments
@@ -960,6 +1132,54 @@ impl Reactions {
         Cursor::new(url, &self.token, &self.client)
     }
 
+    /// Lists [`Reaction`] names that have all of the specified tags in a group
+    ///
+    /// Reaction tags are flat so a tag key without values matches the tag `key` while each
+    /// value matches the tag `key=value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group to list reactions from
+    /// * `tags` - The tags reactions must have
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use std::collections::HashMap;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build the tags our reactions must have
+    /// let mut tags = HashMap::default();
+    /// tags.insert("woot".to_owned(), vec![]);
+    /// tags.insert("crop".to_owned(), vec!["corn".to_owned()]);
+    /// // list up to 50 reaction names from Thorium (limit is weakly enforced) with both tags
+    /// let reactions = thorium.reactions.list_tags("Corn", &tags).limit(50).next().await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(name = "Thorium::Reactions::list_tags", skip(self))
+    )]
+    #[must_use]
+    pub fn list_tags(&self, group: &str, tags: &HashMap<String, Vec<String>>) -> Cursor<Reaction> {
+        // build url for listing reactions
+        let url = format!(
+            "{base}/api/reactions/tag/{group}/",
+            base = self.host,
+            group = group,
+        );
+        Cursor::new(url, &self.token, &self.client).body(serde_json::json!(tags))
+    }
+
     /// Lists [`Reaction`] names with a set status in an entire group
     ///
     /// # Arguments
@@ -1158,6 +1378,61 @@ impl Reactions {
         send_build!(self.client, req, Reaction)
     }
 
+    /// Adds samples to a [`Reaction`] that has not finished yet
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group the reaction is in
+    /// * `id` - The reaction to add samples to
+    /// * `add` - The samples to add
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uuid::Uuid;
+    /// use thorium::{Thorium, models::ReactionSamplesAdd};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // add a sample we unpacked to our reaction
+    /// let add = ReactionSamplesAdd::default()
+    ///     .sample("63b0490d4736e740f26ea9483d55c254abe032845b70ba84ea463ca6582d106f");
+    /// let reaction = Uuid::parse_str("e0ca2720-50e0-4103-a412-344bbb714240")?;
+    /// let details = thorium.reactions.add_samples("Corn", &reaction, &add).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(name = "Thorium::Reactions::add_samples", skip(self, add), fields(id = id.to_string()), err(Debug))
+    )]
+    pub async fn add_samples(
+        &self,
+        group: &str,
+        id: &Uuid,
+        add: &ReactionSamplesAdd,
+    ) -> Result<Reaction, Error> {
+        // build url for adding samples to a reaction
+        let url = format!(
+            "{base}/api/reactions/{group}/{id}/samples",
+            base = self.host
+        );
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .header("authorization", &self.token)
+            .json(add);
+        // send request and build a reaction
+        send_build!(self.client, req, Reaction)
+    }
+
     /// Deletes a [`Reaction`]
     ///
     /// # Arguments
@@ -1255,7 +1530,137 @@ impl Reactions {
         // build request
         let req = self.client.get(&url).header("authorization", &self.token);
         // send request
-        send_bytes!(self.client, req)
+        send_bytes!(self.client, req, OperationClass::Download)
+    }
+
+    /// Downloads all cache files for a [`Reaction`] as a single encrypted zip
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this reaction is from
+    /// * `id` - The reaction to download cache files for
+    /// * `params` - The params to use when building this zip
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::ZipDownloadParams;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // download all of this reactions cache files as a zip encrypted with a custom password
+    /// let reaction = Uuid::parse_str("e0ca2720-50e0-4103-a412-344bbb714240")?;
+    /// let params = ZipDownloadParams::default().password("corn");
+    /// let zip = thorium.reactions.download_cache_zip("Corn", &reaction, &params).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(name = "Thorium::Reactions::download_cache_zip", skip(self, params), fields(id = id.to_string()), err(Debug))
+    )]
+    pub async fn download_cache_zip(
+        &self,
+        group: &str,
+        id: &Uuid,
+        params: &ZipDownloadParams,
+    ) -> Result<Bytes, Error> {
+        // build url for downloading this reactions cache files as a zip
+        let url = format!(
+            "{base}/api/reactions/{group}/{id}/cache/files/zip",
+            base = self.host,
+        );
+        // build request
+        let req = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .query(params);
+        // send request
+        send_bytes!(self.client, req, OperationClass::Download)
+    }
+}
+
+// streams can't be returned from the blocking client so these are async only
+impl Reactions {
+    /// Streams [`Reaction`] details for a specific pipeline as they are retrieved
+    ///
+    /// Unlike listing details with a [`Cursor`], the API does not build the entire page
+    /// of details in memory before responding. The cursor for the next page is set on the
+    /// returned stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group to list reactions from
+    /// * `pipeline` - The pipeline to list reactions from
+    /// * `params` - The cursor and limit to use when listing reactions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::TryStreamExt;
+    /// use thorium::Thorium;
+    /// use thorium::models::ReactionListParams;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // stream the details for up to 50 reactions
+    /// let mut stream = thorium.reactions
+    ///     .list_details_stream("Corn", "CornHarvest", &ReactionListParams::default())
+    ///     .await?;
+    /// while let Some(reaction) = stream.try_next().await? {
+    ///     println!("{}", reaction.id);
+    /// }
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            name = "Thorium::Reactions::list_details_stream",
+            skip(self, params),
+            err(Debug)
+        )
+    )]
+    pub async fn list_details_stream(
+        &self,
+        group: &str,
+        pipeline: &str,
+        params: &ReactionListParams,
+    ) -> Result<NdjsonStream<Reaction>, Error> {
+        // build url for listing reaction details
+        let url = format!(
+            "{base}/api/reactions/list/{group}/{pipeline}/details/",
+            base = self.host,
+        );
+        // build and send our request asking for newline delimited json
+        let resp = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .header("accept", "application/x-ndjson")
+            .query(params)
+            .send()
+            .await?;
+        // make sure we got a 200 before streaming our details
+        match resp.status() {
+            StatusCode::OK => Ok(NdjsonStream::new(resp)),
+            _ => Err(Error::from(resp)),
+        }
     }
 }
 
@@ -13,8 +13,8 @@ use uuid::Uuid;
 #[cfg(feature = "trace")]
 use tracing::instrument;
 
-use super::Error;
 use super::traits::{GenericClient, ResultsClient, ResultsClientHelper, TransferProgress};
+use super::{Error, HttpClient, OperationClass};
 use crate::models::{
     Attachment, CommitListOpts, Commitish, CommitishDetails, CommitishMapRequest, Cursor,
     OutputMap, OutputRequest, OutputResponse, Repo, RepoCreateResponse, RepoDataUploadResponse,
@@ -38,7 +38,7 @@ pub struct Repos {
     /// token to use for auth
     token: String,
     /// reqwest client object
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -57,13 +57,13 @@ impl Repos {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Repos;
+    /// use thorium::client::{HttpClient, Repos};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let repos = Repos::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Repos {
             host: host.to_owned(),
@@ -146,7 +146,12 @@ impl Repos {
             .header("authorization", &self.token)
             .multipart(tar.to_form(groups).await?);
         // send this request
-        send_build!(self.client, req, RepoDataUploadResponse)
+        send_build!(
+            self.client,
+            req,
+            RepoDataUploadResponse,
+            OperationClass::Upload
+        )
     }
 
     /// Adds commits to a repository in Thorium
@@ -327,13 +332,14 @@ impl Repos {
         add_query!(query, "commitish", opts.commitish);
         add_query_list!(query, "kinds[]", opts.kinds);
         // build request
-        let resp = self
+        let req = self
             .client
             .get(&url)
             .header("authorization", &self.token)
             .query(&query)
-            .send()
-            .await?;
+            .build()?;
+        // send this request as a download so it gets our download timeout
+        let resp = super::execute(&self.client, req, OperationClass::Download).await?;
         // make sure we got a 200
         match resp.status() {
             StatusCode::OK => {
@@ -400,13 +406,14 @@ impl Repos {
         add_query!(query, "commitish", opts.commitish);
         add_query_list!(query, "kinds[]", opts.kinds);
         // build request
-        let resp = self
+        let req = self
             .client
             .get(&url)
             .header("authorization", &self.token)
             .query(&query)
-            .send()
-            .await?;
+            .build()?;
+        // send this request as a download so it gets our download timeout
+        let resp = super::execute(&self.client, req, OperationClass::Download).await?;
         // convert our path to a path buf
         let path = path.into();
         // get our response as a stream of bytes
@@ -635,7 +642,7 @@ impl GenericClient for Repos {
     }
 
     /// Provide the configured client from `self`
-    fn client(&self) -> &reqwest::Client {
+    fn client(&self) -> &HttpClient {
         &self.client
     }
 
@@ -1,8 +1,8 @@
 //! The search support for the Thorium client
 
-use super::{Error, SearchEvents};
+use super::{Error, HttpClient, SearchEvents};
 use crate::models::{Cursor, ElasticDoc, ElasticSearchOpts};
-use crate::{add_date, add_query, add_query_list};
+use crate::{add_date, add_query, add_query_bool, add_query_list};
 
 // import our static runtime if we need a blocking client
 #[cfg(feature = "sync")]
@@ -19,7 +19,7 @@ pub struct Search {
     /// token to use for auth
     token: String,
     /// A reqwest client for reqwests
-    client: reqwest::Client,
+    client: HttpClient,
     /// The search events handler
     pub events: events::SearchEvents,
 }
@@ -40,13 +40,13 @@ impl Search {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Search;
+    /// use thorium::client::{HttpClient, Search};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let results = Search::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Search {
             host: host.to_owned(),
@@ -101,6 +101,12 @@ impl Search {
         add_date!(query, "start", opts.start);
         add_date!(query, "end", opts.end);
         add_query!(query, "cursor", opts.cursor);
+        add_query_bool!(query, "snippets", opts.snippets);
+        add_query!(query, "snippet_size", opts.snippet_size);
+        if let Some((pre_tag, post_tag)) = &opts.snippet_tags {
+            query.push(("snippet_pre_tag", pre_tag.clone()));
+            query.push(("snippet_post_tag", post_tag.clone()));
+        }
         // get the data for this request and create our cursor
         Cursor::new(
             &url,
@@ -4,7 +4,7 @@ use results::ResultSearchEvents;
 use tags::TagSearchEvents;
 
 use crate::{
-    client::traits::GenericClient,
+    client::{traits::GenericClient, HttpClient},
     models::{SearchEvent, SearchEventPopOpts, SearchEventStatus},
     send, send_build, Error,
 };
@@ -33,13 +33,13 @@ impl SearchEvents {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::SearchEvents;
+    /// use thorium::client::{HttpClient, SearchEvents};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let events = SearchEvents::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Self {
             tags: TagSearchEvents::new(host, token, client),
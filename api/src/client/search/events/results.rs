@@ -1,5 +1,6 @@
 //! Interacts with result search events routes in Thorium
 
+use crate::client::HttpClient;
 use crate::client::traits::GenericClient;
 use crate::models::{ResultSearchEvent, SearchEventPopOpts, SearchEventStatus};
 
@@ -10,7 +11,7 @@ pub struct ResultSearchEvents {
     host: String,
     /// token to use for auth
     token: String,
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 impl ResultSearchEvents {
@@ -28,13 +29,13 @@ impl ResultSearchEvents {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::ResultSearchEvents;
+    /// use thorium::client::{HttpClient, ResultSearchEvents};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let events = ResultSearchEvents::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         Self {
             host: host.to_owned(),
             token: token.to_owned(),
@@ -48,7 +49,7 @@ impl GenericClient for ResultSearchEvents {
         format!("{}/api/search/events", self.host)
     }
 
-    fn client(&self) -> &reqwest::Client {
+    fn client(&self) -> &HttpClient {
         &self.client
     }
 
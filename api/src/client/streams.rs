@@ -1,6 +1,6 @@
 use chrono::prelude::*;
 
-use super::Error;
+use super::{Error, HttpClient};
 use crate::models::StreamDepth;
 use crate::send_build;
 
@@ -14,7 +14,7 @@ pub struct Streams {
     host: String,
     /// token to use for auth
     token: String,
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -33,13 +33,13 @@ impl Streams {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Streams;
+    /// use thorium::client::{HttpClient, Streams};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let streams = Streams::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Streams {
             host: host.to_owned(),
@@ -1,4 +1,4 @@
-use super::Error;
+use super::{Error, HttpClient};
 use crate::models::{
     Backup, Cursor, ImageScaler, Node, NodeGetParams, NodeListLine, NodeListParams,
     NodeRegistration, NodeUpdate, SystemInfo, SystemSettings, SystemSettingsResetParams,
@@ -20,7 +20,7 @@ pub struct System {
     /// token to use for auth
     token: String,
     /// reqwest client object
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -39,13 +39,13 @@ impl System {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::System;
+    /// use thorium::client::{HttpClient, System};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let systems = System::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build system route handler
         System {
             host: host.to_owned(),
@@ -1,5 +1,5 @@
 //! Traits defining shared behavior between elements of the Thorium client
-use reqwest::Client;
+use super::HttpClient;
 
 mod notifications;
 mod progress;
@@ -19,7 +19,7 @@ pub(super) trait GenericClient {
     fn base_url(&self) -> String;
 
     /// Get a configured client from the implementor for this route in the API
-    fn client(&self) -> &Client;
+    fn client(&self) -> &HttpClient;
 
     /// Get an auth token from the implementor
     fn token(&self) -> &str;
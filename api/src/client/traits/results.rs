@@ -5,7 +5,7 @@ use uuid::Uuid;
 use super::GenericClient;
 use crate::{
     add_query_bool, add_query_list,
-    client::Error,
+    client::{Error, OperationClass},
     models::{
         Attachment, KeySupport, OutputMap, OutputRequest, OutputResponse, ResultGetParams,
         backends::OutputSupport,
@@ -42,7 +42,7 @@ pub trait ResultsClientHelper: GenericClient {
             .multipart(output_req.to_form().await?)
             .header("authorization", self.token());
         // send this request
-        send_build!(self.client(), req, OutputResponse)
+        send_build!(self.client(), req, OutputResponse, OperationClass::Upload)
     }
 
     /// Gets results for the `Self::OutputSupport`
@@ -111,7 +111,7 @@ pub trait ResultsClientHelper: GenericClient {
             .header("authorization", self.token())
             .query(&query);
         // send this request and get the result as bytes
-        let data = send_bytes!(self.client(), req)?;
+        let data = send_bytes!(self.client(), req, OperationClass::Download)?;
         // build our attachment object from the bytes
         Ok(Attachment { data })
     }
@@ -4,8 +4,11 @@
 use tracing::instrument;
 use uuid::Uuid;
 
-use super::Error;
-use crate::models::{Tree, TreeGrowQuery, TreeOpts, TreeQuery};
+use super::{Error, HttpClient};
+use crate::models::{
+    LineageParams, LineageTree, ReactionTree, ReactionTreeParams, Tree, TreeGrowQuery, TreeOpts,
+    TreeQuery,
+};
 use crate::{add_query, send_build};
 
 // import our static runtime if we need a blocking client
@@ -20,7 +23,7 @@ pub struct Trees {
     /// The token to use for auth
     token: String,
     /// A client to use when making requests
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -39,13 +42,13 @@ impl Trees {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Trees;
+    /// use thorium::client::{HttpClient, Trees};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let trees = Trees::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build trees route handler
         Trees {
             host: host.to_owned(),
@@ -182,4 +185,120 @@ impl Trees {
         // send this request and build a generic job from the response
         send_build!(self.client, req, Tree)
     }
+
+    /// Get a reaction and all of its sub reactions as a nested tree
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group the root reaction is in
+    /// * `id` - The id of the root reaction
+    /// * `params` - The params to use when building this tree
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::ReactionTreeParams;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // only gather sub reactions 3 levels deep
+    /// let params = ReactionTreeParams::default().depth(3);
+    /// // get the sub reaction tree for a reaction
+    /// let tree = thorium.trees.reactions("Corn", &Uuid::new_v4(), &params).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Trees::reactions",
+            skip_all,
+            fields(reaction = id.to_string()),
+            err(Debug)
+        )
+    )]
+    pub async fn reactions(
+        &self,
+        group: &str,
+        id: &Uuid,
+        params: &ReactionTreeParams,
+    ) -> Result<ReactionTree, Error> {
+        // build url for getting a reaction tree
+        let url = format!(
+            "{base}/api/trees/reactions/{group}/{id}",
+            base = &self.host,
+            group = group,
+            id = id
+        );
+        // build request
+        let req = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .query(&[("depth", params.depth)]);
+        // send this request and build a reaction tree from the response
+        send_build!(self.client, req, ReactionTree)
+    }
+
+    /// Get the lineage of reactions and children for a sample
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the root sample
+    /// * `params` - The params to use when building this lineage
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::LineageParams;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // only gather 2 generations of children
+    /// let params = LineageParams::default().depth(2);
+    /// // get the lineage for a sample
+    /// let sha256 = "63b8c2a1c1ac9a0e5e5dc4ee4f5d9b6bc0c5ed2aa1c3b1b1f1b7c6bd0dc1fd5d";
+    /// let lineage = thorium.trees.lineage(sha256, &params).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Trees::lineage", skip(self, params), err(Debug))
+    )]
+    pub async fn lineage(
+        &self,
+        sha256: &str,
+        params: &LineageParams,
+    ) -> Result<LineageTree, Error> {
+        // build url for getting a samples lineage
+        let url = format!(
+            "{base}/api/trees/lineage/{sha256}",
+            base = &self.host,
+            sha256 = sha256
+        );
+        // build request
+        let req = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .query(&[("depth", params.depth)]);
+        // send this request and build a lineage from the response
+        send_build!(self.client, req, LineageTree)
+    }
 }
@@ -6,7 +6,7 @@ use std::path::Path;
 use tokio::fs::{File, OpenOptions};
 use tokio_util::io::StreamReader;
 
-use super::Error;
+use super::{Error, HttpClient, OperationClass};
 use crate::models::{Arch, Component, Os, Version};
 use crate::send_build;
 
@@ -23,7 +23,7 @@ pub struct Updates {
     /// token to use for auth
     token: String,
     /// A reqwest client for reqwests
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -42,13 +42,13 @@ impl Updates {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Updates;
+    /// use thorium::client::{HttpClient, Updates};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let updates = Updates::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Updates {
             host: host.to_owned(),
@@ -122,13 +122,14 @@ impl Updates {
             arch = arch,
             component = component.to_file_name(os)
         );
-        // build and send the request
-        let resp = self
+        // build the request
+        let req = self
             .client
             .get(&url)
             .header("authorization", &self.token)
-            .send()
-            .await?;
+            .build()?;
+        // send this request as a download so it gets our download timeout
+        let resp = super::execute(&self.client, req, OperationClass::Download).await?;
         // make sure we got a 200
         match resp.status() {
             StatusCode::OK => {
@@ -1,6 +1,6 @@
 use base64::Engine as _;
 
-use super::{ClientSettings, Error, helpers};
+use super::{ClientSettings, Error, HttpClient, helpers};
 use crate::models::{
     AiSettings, AiSettingsUpdate, AuthResponse, ScrubbedUser, UserCreate, UserUpdate,
 };
@@ -19,7 +19,7 @@ pub struct Users {
     /// token to use for auth
     token: String,
     /// reqwest client object
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -38,13 +38,13 @@ impl Users {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Users;
+    /// use thorium::client::{HttpClient, Users};
     ///
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// let users = Users::new("http://127.0.0.1", "token", &client);
     /// ```
     #[must_use]
-    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+    pub fn new(host: &str, token: &str, client: &HttpClient) -> Self {
         // build basic route handler
         Users {
             host: host.to_owned(),
@@ -95,7 +95,7 @@ impl Users {
         // build url for creating a user
         let url = format!("{host}/api/users/");
         // get client
-        let client = helpers::build_reqwest_client(settings).await?;
+        let client = helpers::build_reqwest_client(host, settings).await?;
         // build request
         let mut req = client.post(&url).json(&blueprint);
         // inject key header if it exists
@@ -172,11 +172,11 @@ impl Users {
     /// # Examples
     ///
     /// ```
-    /// use thorium::client::Users;
+    /// use thorium::client::{HttpClient, Users};
     /// # use thorium::Error;
     ///
     /// # async fn exec() -> Result<(), Error> {
-    /// let client = reqwest::Client::new();
+    /// let client = HttpClient::from(reqwest::Client::new());
     /// // authenticate to Thorium
     /// let auth_resp = Users::auth_basic("http://127.0.0.1", "mcarson", "secretCorn", &client).await?;
     /// # // allow test code to be compiled but don't unwrap as no API instance would be up
@@ -190,7 +190,7 @@ impl Users {
         host: &str,
         username: &str,
         password: &str,
-        client: &reqwest::Client,
+        client: &HttpClient,
     ) -> Result<AuthResponse, Error> {
         // build url for listing groups
         let url = format!("{host}/api/users/auth");
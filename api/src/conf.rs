@@ -11,7 +11,7 @@ use base64::Engine as _;
 
 use crate::models::{
     Image, ImageScaler, NetworkPolicyCustomK8sRule, NetworkPolicyCustomLabel, NetworkPolicyRuleRaw,
-    NetworkProtocol, Pools, TagType, UnixInfo,
+    NetworkProtocol, OutputRenderers, Pools, TagType, UnixInfo,
 };
 
 /// Helps serde default a value to false
@@ -51,6 +51,11 @@ fn default_retention() -> u64 {
     604_800
 }
 
+/// Helps serde default how long reaction idempotency keys are retained to 1 day
+fn default_idempotency_retention() -> u64 {
+    86_400
+}
+
 /// Helps serde default how many results to retain for each group to 3
 fn default_results_versions() -> usize {
     3
@@ -72,6 +77,10 @@ pub struct Retention {
     /// How many results to retain for each group
     #[serde(default = "default_results_versions")]
     pub results: usize,
+    /// How long reaction idempotency keys are retained for; retries after this
+    /// window create a new reaction
+    #[serde(default = "default_idempotency_retention")]
+    pub idempotency_keys: u64,
 }
 
 impl Default for Retention {
@@ -81,6 +90,7 @@ impl Default for Retention {
             logs: default_retention(),
             notifications: default_retention(),
             results: default_results_versions(),
+            idempotency_keys: default_idempotency_retention(),
         }
     }
 }
@@ -1731,6 +1741,9 @@ pub struct Results {
     /// The number of seconds each partition in the database should cover
     #[serde(default = "default_results_partition_size")]
     pub partition_size: u16,
+    /// Any overrides for the renderers to use for each display type
+    #[serde(default)]
+    pub renderers: OutputRenderers,
 }
 
 impl Default for Results {
@@ -1739,6 +1752,7 @@ impl Default for Results {
             bucket: default_results_bucket(),
             earliest: default_results_earliest(),
             partition_size: default_results_partition_size(),
+            renderers: OutputRenderers::default(),
         }
     }
 }
@@ -1899,6 +1913,96 @@ fn default_entities_earliest() -> i64 {
     1_748_736_000
 }
 
+/// Helps serde default the entity duplicate similarity threshold to 0.85
+fn default_entities_duplicate_threshold() -> f64 {
+    0.85
+}
+
+/// Helps serde default how many entities to populate with association data at once to 20
+fn default_entities_association_buffer() -> NonZeroUsize {
+    NonZeroUsize::new(20).unwrap()
+}
+
+/// Helps serde default how many graphics to delete from s3 at once to 10
+fn default_entities_graphic_delete_buffer() -> NonZeroUsize {
+    NonZeroUsize::new(10).unwrap()
+}
+
+/// Helps serde default the country aliases to common names for countries
+fn default_country_aliases() -> HashMap<String, String> {
+    [
+        ("United States", "US"),
+        ("America", "US"),
+        ("UK", "GB"),
+        ("United Kingdom", "GB"),
+        ("Great Britain", "GB"),
+        ("Britain", "GB"),
+        ("England", "GB"),
+        ("South Korea", "KR"),
+        ("North Korea", "KP"),
+        ("Russia", "RU"),
+        ("Iran", "IR"),
+        ("Syria", "SY"),
+        ("Taiwan", "TW"),
+        ("Vietnam", "VN"),
+        ("Laos", "LA"),
+        ("Bolivia", "BO"),
+        ("Venezuela", "VE"),
+        ("Tanzania", "TZ"),
+        ("Moldova", "MD"),
+        ("Czech Republic", "CZ"),
+        ("Holland", "NL"),
+        ("The Netherlands", "NL"),
+    ]
+    .into_iter()
+    .map(|(alias, code)| (alias.to_owned(), code.to_owned()))
+    .collect()
+}
+
+/// Helps serde default the critical sector aliases to common names for sectors
+fn default_sector_aliases() -> HashMap<String, String> {
+    [
+        ("IT", "InformationTechnology"),
+        ("Telecom", "Communications"),
+        ("Telecommunications", "Communications"),
+        ("Finance", "FinancialServices"),
+        ("Banking", "FinancialServices"),
+        ("Healthcare", "HealthcarePublicHealth"),
+        ("Government", "GovernmentServicesFacilities"),
+        ("Defense", "DefenseIndustrialBase"),
+        ("Manufacturing", "CriticalManufacturing"),
+        ("Agriculture", "FoodAgriculture"),
+        ("Nuclear", "NuclearReactorsMaterialsWaste"),
+        ("Transportation", "TransportSystems"),
+        ("Water", "WaterWasteWater"),
+    ]
+    .into_iter()
+    .map(|(alias, sector)| (alias.to_owned(), sector.to_owned()))
+    .collect()
+}
+
+/// The synonyms to accept for entity metadata values
+///
+/// Aliases are matched ignoring case, punctuation, and whitespace.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct EntityAliases {
+    /// Aliases for countries mapped to their ISO 3166 alpha-2 or alpha-3 code
+    #[serde(default = "default_country_aliases")]
+    pub countries: HashMap<String, String>,
+    /// Aliases for critical sectors mapped to their canonical sector name
+    #[serde(default = "default_sector_aliases")]
+    pub sectors: HashMap<String, String>,
+}
+
+impl Default for EntityAliases {
+    fn default() -> Self {
+        Self {
+            countries: default_country_aliases(),
+            sectors: default_sector_aliases(),
+        }
+    }
+}
+
 /// The settings for entities in Thorium
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct Entities {
@@ -1908,6 +2012,18 @@ pub struct Entities {
     /// The earliest we'll see an entity as a Unix timestamp
     #[serde(default = "default_entities_earliest")]
     pub earliest: i64,
+    /// How similar two normalized entity names must be from 0.0 to 1.0 to be reported as duplicates
+    #[serde(default = "default_entities_duplicate_threshold")]
+    pub duplicate_threshold: f64,
+    /// The synonyms to accept for countries and critical sectors
+    #[serde(default)]
+    pub aliases: EntityAliases,
+    /// How many entities to populate with association data from Scylla at once
+    #[serde(default = "default_entities_association_buffer")]
+    pub association_buffer: NonZeroUsize,
+    /// How many stale graphics to delete from s3 at once when updating an entity
+    #[serde(default = "default_entities_graphic_delete_buffer")]
+    pub graphic_delete_buffer: NonZeroUsize,
 }
 
 impl Default for Entities {
@@ -1915,6 +2031,10 @@ impl Default for Entities {
         Self {
             partition_size: default_entities_partition_size(),
             earliest: default_entities_earliest(),
+            duplicate_threshold: default_entities_duplicate_threshold(),
+            aliases: EntityAliases::default(),
+            association_buffer: default_entities_association_buffer(),
+            graphic_delete_buffer: default_entities_graphic_delete_buffer(),
         }
     }
 }
@@ -1986,47 +2106,109 @@ pub struct S3 {
     /// Whether the operator should skip bucket creation or not
     #[serde(default)]
     pub skip_bucket_auto_create: bool,
+    /// The max number of idle multipart upload buffers (~7 MiB each) to keep for reuse
+    #[serde(default = "default_s3_upload_buffers")]
+    pub upload_buffers: usize,
+}
+
+/// Helps serde default the max number of idle s3 upload buffers to keep
+fn default_s3_upload_buffers() -> usize {
+    16
 }
 
 /// Helps serde default the max size an incoming json body can be in mebibytes
 fn default_json_limit() -> u64 {
-    1024
+    16
 }
 
-/// Helps serde default the max size an form (sans files) can be in mebibytes
-fn default_form_limit() -> u64 {
-    1024
+/// Helps serde default the max number of reactions that can be created in one bulk request
+fn default_bulk_reactions_limit() -> usize {
+    10_000
 }
 
-/// Helps serde default the max size an incoming data/file can be in mebibytes
-fn default_data_limit() -> u64 {
-    1024
+/// Helps serde default the max number of users reactions can be created for in one bulk request
+fn default_bulk_reaction_users_limit() -> usize {
+    1000
+}
+
+/// Helps serde default the max number of entities that can be created in one bulk request
+fn default_bulk_entities_limit() -> usize {
+    1000
+}
+
+/// Helps serde default the max number of items that can be tagged in one bulk request
+fn default_bulk_tags_limit() -> usize {
+    1000
 }
 
 /// The request size limits to use in the API
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct RequestSizeLimits {
-    /// The limit to apply to json bodies
+    /// The limit to apply to json and other non upload bodies in mebibytes
     #[serde(default = "default_json_limit")]
     pub json: u64,
-    /// The limit to apply to forms
-    #[serde(default = "default_form_limit")]
-    pub form: u64,
-    /// The limit to apply to data/files
-    #[serde(default = "default_data_limit")]
-    pub data: u64,
+    /// The limit to apply to data/files on upload routes in mebibytes
+    ///
+    /// Uploads are unlimited if this is not set or is 0.
+    #[serde(default)]
+    pub data: Option<u64>,
+    /// The max number of reactions to create in one bulk request (or for one user when
+    /// creating reactions by user)
+    #[serde(default = "default_bulk_reactions_limit")]
+    pub bulk_reactions: usize,
+    /// The max number of users to create reactions for in one bulk request
+    #[serde(default = "default_bulk_reaction_users_limit")]
+    pub bulk_reaction_users: usize,
+    /// The max number of entities to create in one bulk request
+    #[serde(default = "default_bulk_entities_limit")]
+    pub bulk_entities: usize,
+    /// The max number of items to tag in one bulk request
+    #[serde(default = "default_bulk_tags_limit")]
+    pub bulk_tags: usize,
 }
 
 impl Default for RequestSizeLimits {
     fn default() -> Self {
         RequestSizeLimits {
             json: default_json_limit(),
-            form: default_form_limit(),
-            data: default_data_limit(),
+            data: None,
+            bulk_reactions: default_bulk_reactions_limit(),
+            bulk_reaction_users: default_bulk_reaction_users_limit(),
+            bulk_entities: default_bulk_entities_limit(),
+            bulk_tags: default_bulk_tags_limit(),
         }
     }
 }
 
+impl RequestSizeLimits {
+    /// Convert a limit in mebibytes to bytes
+    ///
+    /// # Arguments
+    ///
+    /// * `mebibytes` - The limit to convert
+    fn to_bytes(mebibytes: u64) -> usize {
+        usize::try_from(mebibytes.saturating_mul(1024 * 1024)).unwrap_or(usize::MAX)
+    }
+
+    /// Get the max size of a json body in bytes
+    #[must_use]
+    pub fn json_bytes(&self) -> usize {
+        Self::to_bytes(self.json)
+    }
+
+    /// Get the max size of a body on an upload route in mebibytes if uploads are limited
+    #[must_use]
+    pub fn upload_limit(&self) -> Option<u64> {
+        self.data.filter(|limit| *limit > 0)
+    }
+
+    /// Get the max size of a body on an upload route in bytes if uploads are limited
+    #[must_use]
+    pub fn data_bytes(&self) -> Option<usize> {
+        self.upload_limit().map(Self::to_bytes)
+    }
+}
+
 /// Helps serde default the path to our user facing docs
 fn default_user_docs_path() -> PathBuf {
     PathBuf::from("docs/user")
@@ -2192,6 +2374,11 @@ fn default_api_port() -> u16 {
     80
 }
 
+/// Serde default for how long to wait for in-flight requests to drain on shutdown in seconds
+fn default_shutdown_timeout() -> u64 {
+    30
+}
+
 /// Serde default kubernetes namespace for Thorium pods
 fn default_namespace() -> String {
     "thorium".to_owned()
@@ -2222,6 +2409,9 @@ pub struct Thorium {
     /// The port to bind to
     #[serde(default = "default_api_port")]
     pub port: u16,
+    /// How long to wait for in-flight requests to drain when shutting down in seconds
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout: u64,
     /// The namespace to use in the backend
     #[serde(default = "default_namespace")]
     pub namespace: String,
@@ -2302,6 +2492,363 @@ pub struct Thorium {
     /// A list of namespaces/groups that cannot be created by Thorium or its users
     #[serde(default = "default_namespace_blacklist")]
     pub namespace_blacklist: HashSet<String>,
+    /// The settings for which route modules the API serves
+    #[serde(default)]
+    pub routes: Routes,
+    /// The settings for cursors that list data from Scylla
+    #[serde(default)]
+    pub cursors: Cursors,
+    /// The settings for running jobs
+    #[serde(default)]
+    pub jobs: Jobs,
+    /// The settings for ingesting stage logs
+    #[serde(default)]
+    pub stage_logs: StageLogs,
+    /// The settings for caching groups when authorizing requests
+    #[serde(default)]
+    pub group_cache: GroupCache,
+    /// The settings for caching image and pipeline definitions
+    #[serde(default)]
+    pub definition_cache: DefinitionCache,
+    /// The settings for exposing metrics to Prometheus
+    #[serde(default)]
+    pub metrics: Metrics,
+    /// The limits to enforce when building trees
+    #[serde(default)]
+    pub trees: Trees,
+}
+
+/// The longest namespace we allow since Scylla limits keyspace names to 48 characters
+const MAX_NAMESPACE_LEN: usize = 48;
+
+impl Thorium {
+    /// Make sure a namespace is safe to prefix our Redis keys and name our Scylla keyspace with
+    ///
+    /// Namespaces must start with a letter and only contain ASCII letters, numbers, and
+    /// underscores so they can never collide with the separators in our keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to validate
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing why this namespace is invalid.
+    pub fn validate_namespace(namespace: &str) -> Result<(), config::ConfigError> {
+        // get the reason this namespace is invalid if it is invalid
+        let reason = if namespace.trim().is_empty() {
+            "must not be empty"
+        } else if namespace.len() > MAX_NAMESPACE_LEN {
+            "must be 48 characters or less"
+        } else if !namespace.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            "must start with a letter"
+        } else if !namespace
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            "must only contain letters, numbers, and underscores"
+        } else {
+            return Ok(());
+        };
+        Err(config::ConfigError::Message(format!(
+            "Invalid namespace '{namespace}': {reason}"
+        )))
+    }
+}
+
+/// Default the most partitions non-admins can crawl per cursor round trip to 500
+fn default_max_partition_hint() -> u32 {
+    500
+}
+
+/// Default the most partitions admins can crawl per cursor round trip to 5000
+fn default_admin_max_partition_hint() -> u32 {
+    5000
+}
+
+/// The settings for cursors that list data from Scylla
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct Cursors {
+    /// The most partitions a non-admin can ask a cursor to crawl per round trip
+    #[serde(default = "default_max_partition_hint")]
+    pub max_partition_hint: u32,
+    /// The most partitions an admin can ask a cursor to crawl per round trip
+    #[serde(default = "default_admin_max_partition_hint")]
+    pub admin_max_partition_hint: u32,
+}
+
+impl Default for Cursors {
+    /// Build the default cursor settings
+    fn default() -> Self {
+        Cursors {
+            max_partition_hint: default_max_partition_hint(),
+            admin_max_partition_hint: default_admin_max_partition_hint(),
+        }
+    }
+}
+
+impl Cursors {
+    /// Get the largest partition hint a user can set
+    ///
+    /// Admins can always use at least the non-admin ceiling.
+    ///
+    /// # Arguments
+    ///
+    /// * `admin` - Whether the user setting this hint is an admin
+    #[must_use]
+    pub fn partition_hint_ceiling(&self, admin: bool) -> u32 {
+        if admin {
+            self.admin_max_partition_hint.max(self.max_partition_hint)
+        } else {
+            self.max_partition_hint
+        }
+    }
+}
+
+/// Default the most a job's execution deadline can be extended by heartbeats to 1 day
+fn default_max_heartbeat_extension() -> u64 {
+    86_400
+}
+
+/// The settings for running jobs
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct Jobs {
+    /// The most seconds a job's execution deadline can be pushed back by heartbeats
+    #[serde(default = "default_max_heartbeat_extension")]
+    pub max_heartbeat_extension: u64,
+}
+
+impl Default for Jobs {
+    /// Build the default job settings
+    fn default() -> Self {
+        Jobs {
+            max_heartbeat_extension: default_max_heartbeat_extension(),
+        }
+    }
+}
+
+/// Default the most bytes of stage logs that can be written at once to 64 MiB
+fn default_stage_logs_max_in_flight() -> u32 {
+    67_108_864
+}
+
+/// Default how long to wait for room to write stage logs to 5 seconds
+fn default_stage_logs_max_wait() -> u64 {
+    5
+}
+
+/// The settings for ingesting stage logs
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StageLogs {
+    /// The most bytes of stage log requests that can be read and written to Scylla at once
+    #[serde(default = "default_stage_logs_max_in_flight")]
+    pub max_in_flight: u32,
+    /// How many seconds to wait for room to write stage logs before returning a 429
+    #[serde(default = "default_stage_logs_max_wait")]
+    pub max_wait: u64,
+}
+
+impl Default for StageLogs {
+    /// Build the default stage log settings
+    fn default() -> Self {
+        StageLogs {
+            max_in_flight: default_stage_logs_max_in_flight(),
+            max_wait: default_stage_logs_max_wait(),
+        }
+    }
+}
+
+/// Default the most groups to cache at once to 1000
+fn default_group_cache_capacity() -> usize {
+    1000
+}
+
+/// Default how long cached groups are valid for to 5 seconds
+fn default_group_cache_ttl() -> u64 {
+    5
+}
+
+/// The settings for caching groups when authorizing requests
+///
+/// Groups are invalidated as soon as they are changed through this API instance but
+/// changes made through other instances may not be seen until cached groups expire.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GroupCache {
+    /// The most groups to cache at once (0 disables caching)
+    #[serde(default = "default_group_cache_capacity")]
+    pub capacity: usize,
+    /// How many seconds a cached group is valid for (0 disables caching)
+    #[serde(default = "default_group_cache_ttl")]
+    pub ttl: u64,
+}
+
+impl Default for GroupCache {
+    /// Build the default group cache settings
+    fn default() -> Self {
+        GroupCache {
+            capacity: default_group_cache_capacity(),
+            ttl: default_group_cache_ttl(),
+        }
+    }
+}
+
+/// Default the most images or pipelines to cache at once to 1000
+fn default_definition_cache_capacity() -> usize {
+    1000
+}
+
+/// Default to not caching images and pipelines
+fn default_definition_cache_ttl() -> u64 {
+    0
+}
+
+/// The settings for caching image and pipeline definitions
+///
+/// This is disabled by default. Images and pipelines are only invalidated when they are
+/// changed through this API instance, so when multiple API instances are deployed, changes
+/// made through one instance will not be seen by the others until their cached definitions
+/// expire. Only enable this if jobs can tolerate running against definitions up to `ttl`
+/// seconds old.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DefinitionCache {
+    /// The most images and the most pipelines to cache at once (0 disables caching)
+    #[serde(default = "default_definition_cache_capacity")]
+    pub capacity: usize,
+    /// How many seconds a cached image or pipeline is valid for (0 disables caching)
+    ///
+    /// This is how stale a definition changed through another API instance can be.
+    #[serde(default = "default_definition_cache_ttl")]
+    pub ttl: u64,
+}
+
+impl Default for DefinitionCache {
+    /// Build the default image and pipeline cache settings
+    fn default() -> Self {
+        DefinitionCache {
+            capacity: default_definition_cache_capacity(),
+            ttl: default_definition_cache_ttl(),
+        }
+    }
+}
+
+/// The settings for exposing metrics to Prometheus
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, JsonSchema)]
+pub struct Metrics {
+    /// Whether to track metrics and serve them at `/metrics`
+    #[serde(default = "default_false")]
+    pub enabled: bool,
+    /// The bearer token scrapers must send to get metrics if any
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// Default the most nodes a tree can grow to 100,000
+fn default_max_tree_nodes() -> usize {
+    100_000
+}
+
+/// Default the deepest reaction tree to 25 levels
+fn default_max_reaction_tree_depth() -> usize {
+    25
+}
+
+/// Default the most reactions in a reaction tree to 10,000
+fn default_max_reaction_tree_nodes() -> usize {
+    10_000
+}
+
+/// Default the deepest lineage to 10 generations
+fn default_max_lineage_depth() -> usize {
+    10
+}
+
+/// Default the most samples in a lineage to 1,000
+fn default_max_lineage_nodes() -> usize {
+    1_000
+}
+
+/// The limits to enforce when building trees
+///
+/// Requests for deeper trees are clamped to these limits and anything cut off by them is
+/// marked as truncated instead of erroring.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct Trees {
+    /// The most nodes a tree can grow to
+    #[serde(default = "default_max_tree_nodes")]
+    pub max_tree_nodes: usize,
+    /// The max depth a reaction tree can be built out to
+    #[serde(default = "default_max_reaction_tree_depth")]
+    pub max_reaction_tree_depth: usize,
+    /// The max number of reactions a reaction tree can contain
+    #[serde(default = "default_max_reaction_tree_nodes")]
+    pub max_reaction_tree_nodes: usize,
+    /// The max number of generations of children a lineage can be built out to
+    #[serde(default = "default_max_lineage_depth")]
+    pub max_lineage_depth: usize,
+    /// The max number of samples a lineage can contain
+    #[serde(default = "default_max_lineage_nodes")]
+    pub max_lineage_nodes: usize,
+}
+
+impl Default for Trees {
+    /// Build the default tree limits
+    fn default() -> Self {
+        Trees {
+            max_tree_nodes: default_max_tree_nodes(),
+            max_reaction_tree_depth: default_max_reaction_tree_depth(),
+            max_reaction_tree_nodes: default_max_reaction_tree_nodes(),
+            max_lineage_depth: default_max_lineage_depth(),
+            max_lineage_nodes: default_max_lineage_nodes(),
+        }
+    }
+}
+
+/// The optional route modules that can be disabled in the API
+///
+/// Core modules like users, groups, and files are always served.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteModule {
+    /// The association routes
+    Associations,
+    /// The routes serving Thorium binaries
+    Binaries,
+    /// The entity routes
+    Entities,
+    /// The event routes
+    Events,
+    /// The MCP server
+    Mcp,
+    /// The network policy routes
+    NetworkPolicies,
+    /// The repo routes
+    Repos,
+    /// The search routes
+    Search,
+    /// The stream routes
+    Streams,
+    /// The tree routes
+    Trees,
+}
+
+/// The settings for which route modules the API serves
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, JsonSchema)]
+pub struct Routes {
+    /// The route modules to not mount; requests to them will 404
+    #[serde(default)]
+    pub disabled: HashSet<RouteModule>,
+}
+
+impl Routes {
+    /// Check whether a route module should be mounted
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - The route module to check
+    #[must_use]
+    pub fn enabled(&self, module: RouteModule) -> bool {
+        !self.disabled.contains(&module)
+    }
 }
 
 /// Cross origin request settings
@@ -2315,6 +2862,59 @@ pub struct Cors {
     pub domains: Vec<String>,
 }
 
+impl Cors {
+    /// Check whether these settings will block all cross origin requests
+    #[must_use]
+    pub fn blocks_all(&self) -> bool {
+        !self.insecure && self.domains.is_empty()
+    }
+
+    /// Make sure a single domain is a valid origin
+    ///
+    /// The host can start with a `*.` wildcard to match any of its subdomains.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - The domain to validate
+    pub(crate) fn validate_domain(domain: &str) -> Result<(), &'static str> {
+        // get the host and port after our scheme
+        let host = domain
+            .strip_prefix("https://")
+            .or_else(|| domain.strip_prefix("http://"))
+            .ok_or("must start with http:// or https://")?;
+        // wildcards are only allowed as the first label of our host
+        let host = match host.strip_prefix("*.") {
+            Some("") => return Err("is missing a domain after its wildcard"),
+            Some(host) => host,
+            None => host,
+        };
+        // make sure we actually have a host
+        if host.is_empty() {
+            return Err("is missing a host");
+        }
+        // origins never contain a path so they would never match
+        if host.contains('/') {
+            return Err("must not contain a path or trailing slash");
+        }
+        // make sure we don't have any characters that can't be in an origin
+        if host
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '?' | '#' | '*' | ','))
+        {
+            return Err("contains characters that are not allowed in an origin");
+        }
+        // make sure any port is valid, skipping the colons in ipv6 addresses
+        if !host.ends_with(']') {
+            if let Some((_, port)) = host.rsplit_once(':') {
+                if port.parse::<u16>().is_err() {
+                    return Err("has an invalid port");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Redis settings
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct Redis {
@@ -2356,6 +2956,12 @@ pub struct Scylla {
     pub setup_time: u32,
     /// The auth creds to use when authenticating to scylla
     pub auth: Option<ScyllaAuth>,
+    /// The number of connections to open to each shard on every node (defaults to 1)
+    pub pool_size: Option<NonZeroUsize>,
+    /// The number of connections to open to each node regardless of its shards
+    ///
+    /// This takes precedence over `pool_size` and is useful for clusters that are not shard aware.
+    pub connections_per_node: Option<NonZeroUsize>,
 }
 
 /// The options for Elastic certificate validation
@@ -2561,6 +3167,8 @@ impl Conf {
                 .map(std::borrow::ToOwned::to_owned)
                 .collect();
         }
+        // make sure our namespace is safe to build keys with
+        Thorium::validate_namespace(&conf.thorium.namespace)?;
         Ok(conf)
     }
 
@@ -2576,3 +3184,150 @@ impl Conf {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Cors, Cursors, Entities, RouteModule, Routes, Scylla, Thorium};
+
+    #[test]
+    fn cors_domains() {
+        // valid origins should be accepted
+        let cors = Cors {
+            insecure: false,
+            domains: vec![
+                "https://thorium.example.com".to_owned(),
+                "http://localhost:8080".to_owned(),
+                "http://[::1]:8000".to_owned(),
+                "https://*.example.com".to_owned(),
+                "https://*.example.com:8443".to_owned(),
+            ],
+        };
+        for domain in &cors.domains {
+            assert!(Cors::validate_domain(domain).is_ok(), "{domain}");
+        }
+        assert!(!cors.blocks_all());
+        // malformed origins should be rejected
+        for bad in [
+            "thorium.example.com",
+            "https://",
+            "https://thorium.example.com/",
+            "https://thorium.example.com/api",
+            "https://thorium example.com",
+            "https://*",
+            "https://*.",
+            "https://thorium.*.com",
+            "https://**.example.com",
+            "https://thorium.example.com:port",
+        ] {
+            assert!(Cors::validate_domain(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn cors_empty_domains() {
+        // an empty domain list blocks all cross origin requests
+        let cors = Cors::default();
+        assert!(cors.blocks_all());
+        // insecure mode allows all domains even if none are listed
+        let cors = Cors {
+            insecure: true,
+            domains: Vec::new(),
+        };
+        assert!(!cors.blocks_all());
+    }
+
+    #[test]
+    fn scylla_pool_sizes() {
+        let scylla = |pool_size: u64, per_node: u64| {
+            serde_json::from_value::<Scylla>(serde_json::json!({
+                "nodes": ["127.0.0.1"],
+                "replication": 1,
+                "pool_size": pool_size,
+                "connections_per_node": per_node,
+            }))
+        };
+        // pools must always have at least one connection
+        let conf = scylla(2, 4).unwrap();
+        assert_eq!(conf.pool_size.map(std::num::NonZeroUsize::get), Some(2));
+        assert_eq!(
+            conf.connections_per_node.map(std::num::NonZeroUsize::get),
+            Some(4)
+        );
+        assert!(scylla(0, 4).is_err());
+        assert!(scylla(2, 0).is_err());
+    }
+
+    #[test]
+    fn namespaces() {
+        // valid namespaces should be accepted
+        for namespace in ["thorium", "Thorium_dev", "tenant_2"] {
+            assert!(
+                Thorium::validate_namespace(namespace).is_ok(),
+                "{namespace}"
+            );
+        }
+        // empty and whitespace namespaces should be rejected
+        for bad in ["", " ", "\t\n"] {
+            let error = Thorium::validate_namespace(bad).unwrap_err().to_string();
+            assert!(error.contains("must not be empty"), "{error}");
+        }
+        // namespaces that could break our keys should be rejected
+        for bad in [
+            "thorium:prod",
+            "thorium prod",
+            " thorium",
+            "thorium-prod",
+            "1thorium",
+            "_thorium",
+            "thorium\u{e9}",
+            &"t".repeat(49),
+        ] {
+            let error = Thorium::validate_namespace(bad).unwrap_err().to_string();
+            assert!(error.contains(&format!("'{bad}'")), "{error}");
+        }
+    }
+
+    #[test]
+    fn disabled_routes() {
+        // all route modules are enabled by default
+        let routes = Routes::default();
+        assert!(routes.enabled(RouteModule::Entities));
+        assert!(routes.enabled(RouteModule::Mcp));
+        // disabled modules are parsed from snake case names
+        let routes: Routes =
+            serde_json::from_str(r#"{"disabled": ["entities", "network_policies"]}"#).unwrap();
+        assert!(!routes.enabled(RouteModule::Entities));
+        assert!(!routes.enabled(RouteModule::NetworkPolicies));
+        assert!(routes.enabled(RouteModule::Mcp));
+        // unknown modules are rejected
+        assert!(serde_json::from_str::<Routes>(r#"{"disabled": ["users"]}"#).is_err());
+    }
+
+    #[test]
+    fn partition_hint_ceiling() {
+        // admins get a higher ceiling than users by default
+        let cursors = Cursors::default();
+        assert_eq!(cursors.partition_hint_ceiling(false), 500);
+        assert_eq!(cursors.partition_hint_ceiling(true), 5000);
+        // admins are never held to a lower ceiling than users
+        let cursors = Cursors {
+            max_partition_hint: 1000,
+            admin_max_partition_hint: 200,
+        };
+        assert_eq!(cursors.partition_hint_ceiling(false), 1000);
+        assert_eq!(cursors.partition_hint_ceiling(true), 1000);
+    }
+
+    #[test]
+    fn entity_buffer_widths() {
+        // buffer widths can be tuned
+        let entities: Entities =
+            serde_json::from_str(r#"{"association_buffer": 50, "graphic_delete_buffer": 2}"#)
+                .unwrap();
+        assert_eq!(entities.association_buffer.get(), 50);
+        assert_eq!(entities.graphic_delete_buffer.get(), 2);
+        // buffers with no width would never make progress
+        assert!(serde_json::from_str::<Entities>(r#"{"association_buffer": 0}"#).is_err());
+        assert!(serde_json::from_str::<Entities>(r#"{"graphic_delete_buffer": 0}"#).is_err());
+    }
+}
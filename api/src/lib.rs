@@ -31,7 +31,11 @@ pub mod test_utilities;
 #[cfg(feature = "client")]
 pub mod client;
 #[cfg(feature = "client")]
-pub use client::{CtlConf, Cursor, Error, Keys, SearchDate, Thorium};
+pub use client::{CtlConf, Cursor, Error, Keys, SearchDate, SearchRange, Thorium};
+
+// expose our cart helpers if the client is enabled
+#[cfg(feature = "client")]
+pub mod cart;
 
 // expose the clients if that feature is enabled
 #[cfg(feature = "ai")]
@@ -74,8 +78,8 @@ async fn initial_settings_consistency_scan(
                         Using default system settings: {default_settings:?}"
                     )
                 );
-                // set default settings in the db
-                crate::models::backends::db::system::reset_settings(&shared)
+                // set default settings in the db unless another instance beat us to it
+                let written = crate::models::backends::db::system::init_settings(&shared)
                     .await
                     .map_err(|err| {
                         utils::ApiError::new(
@@ -86,7 +90,17 @@ async fn initial_settings_consistency_scan(
                             )),
                         )
                     })?;
-                default_settings
+                if written {
+                    default_settings
+                } else {
+                    setup!(
+                        log_level,
+                        "Another Thorium instance already set the default system settings"
+                            .to_owned()
+                    );
+                    // use the settings the other instance set
+                    crate::models::backends::db::system::get_settings(&shared).await?
+                }
             } else {
                 return Err(utils::ApiError::new(
                     err.code,
@@ -120,10 +134,45 @@ async fn initial_settings_consistency_scan(
     Ok(())
 }
 
-/// Set a fallback that returns a 404 to disable
+/// Set a fallback that returns a JSON 404 error for unknown api routes
+///
+/// # Arguments
+///
+/// * `method` - The method of the unknown route that was requested
+/// * `uri` - The uri of the unknown route that was requested
 #[cfg(feature = "api")]
-async fn disable_fallback() -> http::StatusCode {
-    http::StatusCode::NOT_FOUND
+async fn disable_fallback(method: Method, uri: http::Uri) -> utils::ApiError {
+    // build a 404 error that clients can parse like any other api error
+    utils::ApiError::new(
+        http::StatusCode::NOT_FOUND,
+        Some(format!("No API route exists for {method} {}", uri.path())),
+    )
+}
+
+#[cfg(feature = "api")]
+/// Mount an optional route module if it is not disabled
+///
+/// # Arguments
+///
+/// * `router` - The router to add this route module to
+/// * `routes` - The route modules that are disabled
+/// * `module` - The route module to mount
+/// * `mount` - The function that mounts this route module
+fn mount_optional<S, F>(
+    router: axum::Router<S>,
+    routes: &conf::Routes,
+    module: conf::RouteModule,
+    mount: F,
+) -> axum::Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    F: FnOnce(axum::Router<S>) -> axum::Router<S>,
+{
+    if routes.enabled(module) {
+        mount(router)
+    } else {
+        router
+    }
 }
 
 #[cfg(feature = "api")]
@@ -140,13 +189,16 @@ fn build_app(
     use axum::{http::Request, response::Response};
     use routes::{
         associations, basic, binaries, docs, entities, events, files, groups, images, jobs, mcp,
-        network_policies, pipelines, reactions, repos, search, streams, system, trees, ui, users,
+        metrics, network_policies, pipelines, reactions, repos, search, streams, system, trees, ui,
+        users,
     };
     use std::time::Duration;
     use tower_http::set_header::SetResponseHeaderLayer;
     use tower_http::trace::{DefaultMakeSpan, TraceLayer};
     use tracing::{Level, Span, event};
 
+    use crate::conf::RouteModule;
+    use crate::utils::cors::CorsOrigins;
     use crate::utils::trace;
 
     // build an axum router
@@ -155,33 +207,72 @@ fn build_app(
     let mut api_router = axum::Router::new()
         // disable the fallback for api routes
         .fallback(disable_fallback);
-    // add all of our api routes to our api router
-    api_router = associations::mount(api_router);
+    // get the route modules we want to serve
+    let routes = &conf.thorium.routes;
+    // add all of our enabled api routes to our api router
+    api_router = mount_optional(
+        api_router,
+        routes,
+        RouteModule::Associations,
+        associations::mount,
+    );
     api_router = basic::mount(api_router);
-    api_router = binaries::mount(api_router, conf);
-    api_router = entities::mount(api_router);
+    api_router = mount_optional(api_router, routes, RouteModule::Binaries, |router| {
+        binaries::mount(router, conf)
+    });
+    api_router = mount_optional(api_router, routes, RouteModule::Entities, |router| {
+        entities::mount(router, conf)
+    });
     api_router = docs::mount(api_router, conf);
-    api_router = events::mount(api_router);
-    api_router = files::mount(api_router);
+    api_router = mount_optional(api_router, routes, RouteModule::Events, events::mount);
+    api_router = files::mount(api_router, conf);
     api_router = groups::mount(api_router);
     api_router = images::mount(api_router);
     api_router = jobs::mount(api_router);
     api_router = pipelines::mount(api_router);
-    api_router = network_policies::mount(api_router);
-    api_router = reactions::mount(api_router);
-    api_router = repos::mount(api_router);
-    api_router = search::mount(api_router);
-    api_router = streams::mount(api_router);
+    api_router = mount_optional(
+        api_router,
+        routes,
+        RouteModule::NetworkPolicies,
+        network_policies::mount,
+    );
+    api_router = reactions::mount(api_router, conf, &state);
+    api_router = mount_optional(api_router, routes, RouteModule::Repos, |router| {
+        repos::mount(router, conf)
+    });
+    api_router = mount_optional(api_router, routes, RouteModule::Search, search::mount);
+    api_router = mount_optional(api_router, routes, RouteModule::Streams, streams::mount);
     api_router = system::mount(api_router);
     api_router = users::mount(api_router);
-    api_router = trees::mount(api_router);
-    api_router = mcp::mount(api_router, &conf);
+    api_router = mount_optional(api_router, routes, RouteModule::Trees, trees::mount);
+    api_router = mount_optional(api_router, routes, RouteModule::Mcp, |router| {
+        mcp::mount(router, conf)
+    });
+    // track metrics for our api routes and serve them if they are enabled
+    if conf.thorium.metrics.enabled {
+        api_router = api_router.route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            utils::metrics::track,
+        ));
+        app = metrics::mount(app);
+    }
     // add our api routes
     app = app.nest("/api", api_router);
     // create a ui router and mount our ui routes then merge it
     app = ui::mount(app);
     // setup our tracing
     let trace_provider = trace::setup("ThoriumAPI", &conf.thorium.tracing);
+    // warn if our cors settings will block all browser clients
+    if conf.thorium.cors.blocks_all() {
+        event!(
+            Level::WARN,
+            msg = "No CORS domains are set and insecure CORS is disabled; all cross origin requests will be blocked"
+        );
+    }
+    // log any route modules we are not serving
+    for module in &routes.disabled {
+        event!(Level::INFO, msg = "Route module disabled", module = ?module);
+    }
     // build cors middleware for our app
     let cors = if conf.thorium.cors.insecure {
         CorsLayer::permissive()
@@ -193,20 +284,15 @@ fn build_app(
             Method::PATCH,
             Method::DELETE,
         ]);
-        // cast the domains we want to add to the correct type
-        let origins = conf
-            .thorium
-            .cors
-            .domains
-            .iter()
-            .map(|domain| domain.parse())
-            .collect::<Result<Vec<HeaderValue>, _>>()
-            .expect("Failed to parse CORS domains");
-        cors.allow_origin(origins)
+        // build the origins to allow skipping any that are invalid
+        let origins = CorsOrigins::new(&conf.thorium.cors);
+        cors.allow_origin(origins.allow_origin())
     };
-    // add middleware to our app
+    // add middleware to our app; upload routes raise this body limit themselves
     app = app
-        .layer(DefaultBodyLimit::disable())
+        .layer(DefaultBodyLimit::max(
+            conf.thorium.request_size_limits.json_bytes(),
+        ))
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
@@ -249,6 +335,146 @@ fn build_app(
     (app.with_state(state), trace_provider)
 }
 
+/// The most times we will try to bind and serve the API before giving up
+#[cfg(feature = "api")]
+const MAX_BIND_ATTEMPTS: u32 = 10;
+
+/// Check if we should try to bind the API again after a failed attempt
+///
+/// # Arguments
+///
+/// * `attempts` - The number of attempts that have failed so far
+#[cfg(feature = "api")]
+fn should_retry_bind(attempts: u32) -> bool {
+    attempts < MAX_BIND_ATTEMPTS
+}
+
+/// How long to wait between attempts to bind the API
+#[cfg(feature = "api")]
+const BIND_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Bind and serve the API retrying failed attempts until we run out of attempts
+///
+/// Returns the number of attempts that failed if we gave up.
+///
+/// # Arguments
+///
+/// * `bind` - Binds and serves the API until it is shutdown
+/// * `delay` - How long to wait between attempts
+/// * `log_level` - The log level configured in the Thorium config
+#[cfg(feature = "api")]
+async fn retry_bind<F, Fut>(
+    mut bind: F,
+    delay: std::time::Duration,
+    log_level: conf::LogLevel,
+) -> Result<(), u32>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = std::io::Result<()>>,
+{
+    // track how many bind attemps we have tried
+    let mut attempts = 0;
+    loop {
+        // try to bind and start handling requests
+        match bind().await {
+            Ok(()) => return Ok(()),
+            Err(error) => {
+                error!(log_level, format!("Failed to bind server: {:#?}", error));
+            }
+        }
+        // increment our attempt count
+        attempts += 1;
+        // check if we reached our attempt limit
+        if !should_retry_bind(attempts) {
+            // we have tried and failed too many times now so abort
+            error!(
+                log_level,
+                format!("Failed to bind server in {attempts} attempts")
+            );
+            return Err(attempts);
+        }
+        // wait a bit before trying again
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Wait until we are told to shutdown with either SIGINT or SIGTERM
+#[cfg(feature = "api")]
+async fn shutdown_signal() {
+    // wait for a ctrl-c/SIGINT
+    let interrupt = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            // we can't listen for SIGINT so only wait for SIGTERM
+            std::future::pending::<()>().await;
+        }
+    };
+    // wait for a SIGTERM
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            // we can't listen for SIGTERM so only wait for SIGINT
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    tokio::select! {
+        () = interrupt => (),
+        () = terminate => (),
+    }
+}
+
+/// Serve the API until we get a shutdown signal and our in-flight requests drain
+///
+/// # Arguments
+///
+/// * `listener` - The listener to serve the API on
+/// * `app` - The app to serve
+/// * `drain_timeout` - How long to wait for in-flight requests after a shutdown signal
+/// * `log_level` - The log level configured in the Thorium config
+#[cfg(feature = "api")]
+async fn serve_until_shutdown(
+    listener: tokio::net::TcpListener,
+    app: axum::Router,
+    drain_timeout: std::time::Duration,
+    log_level: conf::LogLevel,
+) -> std::io::Result<()> {
+    // track when we have been told to shutdown so we can start our drain timer
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::watch::channel(false);
+    // stop accepting new connections once we get a shutdown signal
+    let signal = async move {
+        shutdown_signal().await;
+        info!(
+            log_level,
+            "Received shutdown signal, draining in-flight requests".to_owned()
+        );
+        let _ = shutdown_tx.send(true);
+    };
+    let server = axum::serve(listener, app).with_graceful_shutdown(signal);
+    // give in-flight requests a limited amount of time to finish once we start shutting down
+    let deadline = async move {
+        // if our sender is dropped then our server has already stopped
+        if shutdown_rx.wait_for(|shutdown| *shutdown).await.is_ok() {
+            tokio::time::sleep(drain_timeout).await;
+        } else {
+            std::future::pending::<()>().await;
+        }
+    };
+    tokio::select! {
+        served = server => served,
+        () = deadline => {
+            error!(
+                log_level,
+                format!(
+                    "In-flight requests did not drain within {}s, shutting down anyways",
+                    drain_timeout.as_secs()
+                )
+            );
+            Ok(())
+        }
+    }
+}
+
 #[cfg(feature = "api")]
 /// Launches the Thorium api using axum
 ///
@@ -275,6 +501,8 @@ pub async fn axum(config: Conf) {
         state.shared.clone(),
         log_level,
     ));
+    // keep a reference to our shared objects so we can check our config against them
+    let shared = state.shared.clone();
     // build our app
     let (app, trace_provider) = build_app(state, &config);
     // parse our interface addr
@@ -293,33 +521,106 @@ pub async fn axum(config: Conf) {
         // our scan failed, so don't start the API
         panic!("Error running initial consistency scan: {err}");
     }
-    // track how many bind attemps we have tried
-    let mut attempts = 0;
-    // bind and start handling requests
-    loop {
-        // try to bind the listener for our server
-        let listener = tokio::net::TcpListener::bind(&addr)
-            .await
-            .unwrap_or_else(|_| panic!("Failed to bind to {addr}"));
-        // start handling requests
-        match axum::serve(listener, app.clone()).await {
-            Ok(()) => break,
-            Err(error) => {
-                error!(log_level, format!("Failed to bind server: {:#?}", error));
-            }
-        }
-        // increment our attempt count
-        attempts += 1;
-        // check if we reached our attempt limit
-        if attempts <= 10 {
-            // we have tried and failed 10 times now so abort
-            break;
-        }
-        // sleep for 3 seconds between attempts
-        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    // make sure our config matches the backends we are connected to before serving requests
+    if let Err(err) = crate::models::backends::setup::self_check(&shared).await {
+        panic!("Thorium config failed its startup self-check: {err}");
     }
-    // log that we failed to start
-    error!(log_level, "Failed to bind server in 10 attempts".to_owned());
-    // shutdown our trace provider if we ever exit
+    // get how long to let in-flight requests drain for when shutting down
+    let drain_timeout = std::time::Duration::from_secs(config.thorium.shutdown_timeout);
+    // bind and start handling requests retrying if we fail to bind
+    let serve = || {
+        let app = app.clone();
+        async move {
+            let listener = tokio::net::TcpListener::bind(&addr).await?;
+            serve_until_shutdown(listener, app, drain_timeout, log_level).await
+        }
+    };
+    // we already log each failure so we don't need to do anything if we gave up
+    let _ = retry_bind(serve, BIND_RETRY_DELAY, log_level).await;
+    // shutdown our trace provider so any remaining spans are flushed
     crate::utils::trace::shutdown(trace_provider);
 }
+
+#[cfg(all(test, feature = "api"))]
+mod tests {
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::routing::get;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    use super::{MAX_BIND_ATTEMPTS, mount_optional, retry_bind, should_retry_bind};
+    use crate::conf::{LogLevel, RouteModule, Routes};
+
+    #[test]
+    fn bind_retries() {
+        // the first failures should always be retried
+        assert!(should_retry_bind(0));
+        assert!(should_retry_bind(1));
+        assert!(should_retry_bind(MAX_BIND_ATTEMPTS - 1));
+        // we should give up once we have used all of our attempts
+        assert!(!should_retry_bind(MAX_BIND_ATTEMPTS));
+        assert!(!should_retry_bind(MAX_BIND_ATTEMPTS + 1));
+    }
+
+    #[tokio::test]
+    async fn bind_always_fails() {
+        let attempts = AtomicU32::new(0);
+        // simulate a server that can never bind
+        let bind = || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err::<(), _>(std::io::Error::from(std::io::ErrorKind::AddrInUse)) }
+        };
+        let result = retry_bind(bind, Duration::ZERO, LogLevel::Off).await;
+        // we should have tried every attempt before giving up
+        assert_eq!(result, Err(MAX_BIND_ATTEMPTS));
+        assert_eq!(attempts.load(Ordering::SeqCst), MAX_BIND_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn bind_eventually_succeeds() {
+        let attempts = AtomicU32::new(0);
+        // simulate a server that binds on its fourth attempt
+        let bind = || {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                if attempt < 4 {
+                    Err(std::io::Error::from(std::io::ErrorKind::AddrInUse))
+                } else {
+                    Ok(())
+                }
+            }
+        };
+        let result = retry_bind(bind, Duration::ZERO, LogLevel::Off).await;
+        // we should stop retrying as soon as we bind
+        assert_eq!(result, Ok(()));
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+    }
+
+    /// Build a router with a single optional events route
+    fn router(routes: &Routes) -> Router {
+        mount_optional(Router::new(), routes, RouteModule::Events, |router| {
+            router.route("/events/", get(|| async { "corn" }))
+        })
+    }
+
+    /// Get the status of a request to our optional route
+    async fn status(router: Router) -> StatusCode {
+        let req = Request::get("/events/").body(Body::empty()).unwrap();
+        router.oneshot(req).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn disabled_routes() {
+        // route modules are mounted by default
+        assert_eq!(status(router(&Routes::default())).await, StatusCode::OK);
+        // disabled route modules are not mounted so they 404
+        let routes: Routes = serde_json::from_str(r#"{"disabled": ["events"]}"#).unwrap();
+        assert_eq!(status(router(&routes)).await, StatusCode::NOT_FOUND);
+        // disabling other modules leaves this one mounted
+        let routes: Routes = serde_json::from_str(r#"{"disabled": ["entities"]}"#).unwrap();
+        assert_eq!(status(router(&routes)).await, StatusCode::OK);
+    }
+}
@@ -19,6 +19,26 @@ pub enum AssociationTarget {
     Repo(String),
 }
 
+impl AssociationTarget {
+    /// Check if this target points at the same data as another target
+    ///
+    /// Entities are compared by id only since their names are just for display.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other target to compare against
+    pub fn is_same(&self, other: &AssociationTarget) -> bool {
+        match (self, other) {
+            (AssociationTarget::Entity { id, .. }, AssociationTarget::Entity { id: other, .. }) => {
+                id == other
+            }
+            (AssociationTarget::File(sha256), AssociationTarget::File(other)) => sha256 == other,
+            (AssociationTarget::Repo(url), AssociationTarget::Repo(other)) => url == other,
+            _ => false,
+        }
+    }
+}
+
 /// The different possible associations
 #[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash)]
 #[cfg_attr(
@@ -64,6 +84,8 @@ pub enum AssociationKind {
     FolderIn,
     /// This is a file in a folder in a filesytem
     FileIn,
+    /// This is related to something else without implying a direction
+    RelatedTo,
 }
 
 impl std::fmt::Display for AssociationKind {
@@ -86,6 +108,7 @@ impl std::fmt::Display for AssociationKind {
             AssociationKind::FileSystemIn => write!(f, "FileSystemIn"),
             AssociationKind::FolderIn => write!(f, "FolderIn"),
             AssociationKind::FileIn => write!(f, "FileIn"),
+            AssociationKind::RelatedTo => write!(f, "RelatedTo"),
         }
     }
 }
@@ -110,8 +133,17 @@ impl AssociationKind {
             AssociationKind::FileSystemIn => "FileSystemIn",
             AssociationKind::FolderIn => "FolderIn",
             AssociationKind::FileIn => "FileIn",
+            AssociationKind::RelatedTo => "RelatedTo",
         }
     }
+
+    /// Whether this kind of association is always symmetric
+    ///
+    /// Symmetric associations are always saved as bidirectional so they read the same from
+    /// either side.
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, AssociationKind::RelatedTo)
+    }
 }
 
 impl FromStr for AssociationKind {
@@ -136,6 +168,7 @@ impl FromStr for AssociationKind {
             "FileSystemIn" => Ok(AssociationKind::FileSystemIn),
             "FolderIn" => Ok(AssociationKind::FolderIn),
             "FileIn" => Ok(AssociationKind::FileIn),
+            "RelatedTo" => Ok(AssociationKind::RelatedTo),
             _ => Err(InvalidEnum(format!("Unknown AssociationKind: {raw}"))),
         }
     }
@@ -157,6 +190,17 @@ pub struct Association {
     pub created: DateTime<Utc>,
     /// The direction for this association
     pub direction: Directionality,
+    /// How confident the submitter is in this association from 0 to 100
+    #[serde(default = "default_confidence")]
+    pub confidence: u8,
+    /// When this association was archived if it has been archived
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
+}
+
+/// The confidence to use for associations that do not set one
+pub(crate) fn default_confidence() -> u8 {
+    100
 }
 
 /// A request to associate one piece of data with another
@@ -173,6 +217,9 @@ pub struct AssociationRequest {
     pub groups: Vec<String>,
     /// Whether this is a bidirecitonal relationship or not
     pub is_bidirectional: bool,
+    /// How confident the submitter is in this association from 0 to 100
+    #[serde(default = "default_confidence")]
+    pub confidence: u8,
 }
 
 impl AssociationRequest {
@@ -189,7 +236,8 @@ impl AssociationRequest {
             source,
             targets: Vec::default(),
             groups: Vec::default(),
-            is_bidirectional: false,
+            is_bidirectional: kind.is_symmetric(),
+            confidence: default_confidence(),
         }
     }
 
@@ -211,7 +259,8 @@ impl AssociationRequest {
             source,
             targets: Vec::with_capacity(capacity),
             groups: Vec::with_capacity(capacity),
-            is_bidirectional: false,
+            is_bidirectional: kind.is_symmetric(),
+            confidence: default_confidence(),
         }
     }
 
@@ -243,6 +292,16 @@ impl AssociationRequest {
         self.is_bidirectional = true;
         self
     }
+
+    /// Set how confident we are in this association from 0 to 100
+    ///
+    /// # Arguments
+    ///
+    /// * `confidence` - The confidence to set
+    pub fn confidence(mut self, confidence: u8) -> Self {
+        self.confidence = confidence;
+        self
+    }
 }
 
 pub trait AssociationSupport {
@@ -268,9 +327,9 @@ pub trait AssociationSupport {
 pub struct AssociationListOpts {
     /// The cursor to use to continue this search
     pub cursor: Option<Uuid>,
-    /// The latest date to start listing samples from
+    /// The latest creation date to start listing associations from
     pub start: Option<DateTime<Utc>>,
-    /// The oldest date to stop listing samples from
+    /// The oldest creation date to stop listing associations at
     pub end: Option<DateTime<Utc>>,
     /// The max number of objects to retrieve on a single page
     pub page_size: usize,
@@ -278,6 +337,8 @@ pub struct AssociationListOpts {
     pub limit: Option<usize>,
     /// The groups limit our search to
     pub groups: Vec<String>,
+    /// Whether to include archived associations or not
+    pub include_archived: bool,
 }
 
 impl Default for AssociationListOpts {
@@ -290,6 +351,7 @@ impl Default for AssociationListOpts {
             page_size: 50,
             limit: None,
             groups: Vec::default(),
+            include_archived: false,
         }
     }
 }
@@ -299,7 +361,7 @@ impl AssociationListOpts {
     ///
     /// # Arguments
     ///
-    /// * `start` - The date to start listing samples from
+    /// * `start` - The date to start listing associations from
     #[must_use]
     pub fn start(mut self, start: DateTime<Utc>) -> Self {
         // set the date to start listing associations at
@@ -323,7 +385,7 @@ impl AssociationListOpts {
     ///
     /// # Arguments
     ///
-    /// * `end` - The date to stop listing samples at
+    /// * `end` - The date to stop listing associations at
     #[must_use]
     pub fn end(mut self, end: DateTime<Utc>) -> Self {
         // set the date to end listing associations at
@@ -331,6 +393,20 @@ impl AssociationListOpts {
         self
     }
 
+    /// Only list associations that were created within a specific window
+    ///
+    /// # Arguments
+    ///
+    /// * `oldest` - The oldest creation date to list associations from
+    /// * `newest` - The newest creation date to list associations from
+    #[must_use]
+    pub fn created_between(mut self, oldest: DateTime<Utc>, newest: DateTime<Utc>) -> Self {
+        // we list from newest to oldest so start at the newest date
+        self.start = Some(newest);
+        self.end = Some(oldest);
+        self
+    }
+
     /// The max number of objects to retrieve in a single page
     ///
     /// # Arguments
@@ -367,6 +443,13 @@ impl AssociationListOpts {
             .extend(groups.into_iter().map(|group| group.into()));
         self
     }
+
+    /// Include archived associations in this search
+    #[must_use]
+    pub fn include_archived(mut self) -> Self {
+        self.include_archived = true;
+        self
+    }
 }
 
 /// Default the association list limit to 50
@@ -390,6 +473,9 @@ pub struct AssociationListParams {
     /// The max number of items to return in this response
     #[serde(default = "default_list_limit")]
     pub limit: usize,
+    /// Whether to include archived associations or not
+    #[serde(default)]
+    pub include_archived: bool,
 }
 
 impl Default for AssociationListParams {
@@ -401,6 +487,7 @@ impl Default for AssociationListParams {
             end: None,
             cursor: None,
             limit: default_list_limit(),
+            include_archived: false,
         }
     }
 }
@@ -414,6 +501,7 @@ impl From<AssociationListOpts> for AssociationListParams {
             end: opts.end,
             cursor: opts.cursor,
             limit: opts.limit.unwrap_or_else(|| default_list_limit()),
+            include_archived: opts.include_archived,
         }
     }
 }
@@ -11,6 +11,7 @@ use std::hash::Hasher;
 use uuid::Uuid;
 
 use super::db;
+use crate::bad;
 use crate::models::backends::db::{CursorCore, ScyllaCursor, ScyllaCursorSupport};
 use crate::models::{
     ApiCursor, Association, AssociationListParams, AssociationListRow, AssociationRequest,
@@ -56,8 +57,37 @@ impl AssociationTarget {
 }
 
 impl AssociationRequest {
+    /// Make sure this request doesn't associate data with itself or repeat any targets
+    pub fn validate(&self) -> Result<(), ApiError> {
+        // confidence is a percentage so it can't be above 100
+        if self.confidence > 100 {
+            return bad!(format!(
+                "Association confidence must be between 0 and 100 not {}!",
+                self.confidence
+            ));
+        }
+        for (index, target) in self.targets.iter().enumerate() {
+            // associating data with itself is a meaningless self loop
+            if target.is_same(&self.source) {
+                return bad!(format!("Cannot associate {target:?} with itself!"));
+            }
+            // make sure this target wasn't already listed in this request
+            if self.targets[..index]
+                .iter()
+                .any(|prev| prev.is_same(target))
+            {
+                return bad!(format!(
+                    "{target:?} is listed more than once in this association request!"
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Apply this association request to the desired entities/objects
     pub async fn apply(self, user: &User, shared: &Shared) -> Result<(), ApiError> {
+        // make sure this request is valid
+        self.validate()?;
         // if we don't have any groups set in this request then get source objects groups
         let (groups, groups_set) = if self.groups.is_empty() {
             // we don't have any groups explicitly set so get our source objects groups
@@ -90,8 +120,8 @@ impl AssociationRequest {
             // add our target and its validated editable groups
             target_list.push((target, target_groups));
         }
-        // get the direction for this association
-        let direction = if self.is_bidirectional {
+        // get the direction for this association; symmetric kinds are always bidirectional
+        let direction = if self.is_bidirectional || self.kind.is_symmetric() {
             Directionality::Bidirectional
         } else {
             Directionality::To
@@ -104,6 +134,7 @@ impl AssociationRequest {
             self.source,
             &target_list,
             direction,
+            self.confidence,
             shared,
         )
         .await?;
@@ -483,3 +514,85 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::models::{AssociationKind, AssociationRequest, AssociationTarget};
+
+    /// Build an entity target
+    fn entity(id: Uuid, name: &str) -> AssociationTarget {
+        AssociationTarget::Entity {
+            id,
+            name: name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn self_association() {
+        let id = Uuid::new_v4();
+        // associating an entity with itself should be rejected even if the names differ
+        let req = AssociationRequest::new(AssociationKind::AssociatedWith, entity(id, "Corn"))
+            .target(entity(Uuid::new_v4(), "Wheat"))
+            .target(entity(id, "Maize"));
+        assert!(req.validate().is_err());
+        // files and repos can't be associated with themselves either
+        let sha256 = "a".repeat(64);
+        let req = AssociationRequest::new(
+            AssociationKind::AssociatedWith,
+            AssociationTarget::File(sha256.clone()),
+        )
+        .target(AssociationTarget::File(sha256));
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn duplicate_targets() {
+        let vendor = Uuid::new_v4();
+        // listing the same target twice should be rejected
+        let req = AssociationRequest::new(
+            AssociationKind::DevelopedBy,
+            entity(Uuid::new_v4(), "Harvester"),
+        )
+        .target(entity(vendor, "CornCo"))
+        .target(entity(vendor, "CornCo"));
+        assert!(req.validate().is_err());
+        // distinct targets are fine
+        let req = AssociationRequest::new(
+            AssociationKind::DevelopedBy,
+            entity(Uuid::new_v4(), "Harvester"),
+        )
+        .target(entity(vendor, "CornCo"))
+        .target(AssociationTarget::Repo(
+            "github.com/corn/harvester".to_owned(),
+        ))
+        .target(AssociationTarget::File(vendor.to_string()));
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn confidence() {
+        let target = entity(Uuid::new_v4(), "CornCo");
+        // requests default to full confidence
+        let req = AssociationRequest::new(
+            AssociationKind::DevelopedBy,
+            entity(Uuid::new_v4(), "Harvester"),
+        )
+        .target(target.clone());
+        assert_eq!(req.confidence, 100);
+        assert!(req.validate().is_ok());
+        // confidence is a percentage
+        let req = req.confidence(101);
+        assert!(req.validate().is_err());
+        // requests without a confidence deserialize to full confidence
+        let raw = serde_json::json!({
+            "kind": "DevelopedBy",
+            "source": {"Entity": {"id": Uuid::new_v4(), "name": "Harvester"}},
+            "targets": [{"File": "a".repeat(64)}],
+            "is_bidirectional": false,
+        });
+        let req: AssociationRequest = serde_json::from_value(raw).unwrap();
+        assert_eq!(req.confidence, 100);
+    }
+}
@@ -12,6 +12,7 @@ pub mod images;
 pub mod jobs;
 pub mod keys;
 pub mod logs;
+pub mod namespaces;
 pub mod network_policies;
 pub mod notifications;
 pub mod pipelines;
@@ -30,4 +31,5 @@ pub use cursors::{
     CursorCore, ElasticCursor, ExistsCursor, GroupedScyllaCursor, GroupedScyllaCursorRetain,
     GroupedScyllaCursorSupport, ScyllaCursor, ScyllaCursorRetain, ScyllaCursorSupport,
     ScyllaTagCountCursor, SimpleCursorExt, SimpleScyllaCursor, TagCountCursorSupport,
+    check_partition_hint,
 };
@@ -26,6 +26,7 @@ async fn create_helper(
     extra_source: Option<String>,
     extra_target: Option<String>,
     direction: Directionality,
+    confidence: i32,
     shared: &Shared,
 ) -> Result<(), ExecutionError> {
     // This row is in the source -> target direction
@@ -46,6 +47,7 @@ async fn create_helper(
                 &user.username,
                 &extra_source,
                 &extra_target,
+                confidence,
             ),
         )
         .await?;
@@ -69,6 +71,7 @@ async fn create_helper(
                 &user.username,
                 extra_target,
                 extra_source,
+                confidence,
             ),
         )
         .await?;
@@ -87,6 +90,7 @@ pub async fn create(
     source: AssociationTarget,
     targets: &Vec<(AssociationTarget, Vec<String>)>,
     direction: Directionality,
+    confidence: u8,
     shared: &Shared,
 ) -> Result<(), ApiError> {
     // get the current time for when we are inserting these rows
@@ -143,6 +147,7 @@ pub async fn create(
                 extra_src.clone(),
                 extra_targ.clone(),
                 direction,
+                i32::from(confidence),
                 shared,
             );
             // add this to our futures
@@ -245,6 +250,109 @@ pub async fn delete_many(
     Ok(())
 }
 
+/// Help archive or restore both the regular and inverted rows for this association
+async fn archive_helper(
+    group_chunk: &[String],
+    year: i32,
+    bucket: i32,
+    source_serialized: &String,
+    association: &ListableAssociation,
+    archived_at: Option<DateTime<Utc>>,
+    shared: &Shared,
+) -> Result<(), ExecutionError> {
+    // archive this association
+    shared
+        .scylla
+        .session
+        .execute_unpaged(
+            &shared.scylla.prep.associations.archive,
+            (
+                archived_at,
+                group_chunk,
+                year,
+                bucket,
+                &source_serialized,
+                association.created,
+                &association.other,
+                association.direction,
+            ),
+        )
+        .await?;
+    // get our opposite direction
+    let opposite_dir = association.direction.opposite();
+    // archive the inverted row for this association
+    shared
+        .scylla
+        .session
+        .execute_unpaged(
+            &shared.scylla.prep.associations.archive,
+            (
+                archived_at,
+                group_chunk,
+                year,
+                bucket,
+                &association.other,
+                association.created,
+                &source_serialized,
+                opposite_dir,
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Archive or restore a list of associations from multiple groups
+///
+/// # Arguments
+///
+/// * `source` - The source these associations are from
+/// * `associations` - The associations to archive or restore
+/// * `archived_at` - When these associations were archived or None to restore them
+/// * `shared` - Shared Thorium objects
+pub async fn archive_many(
+    source: &AssociationTargetColumn,
+    associations: &Vec<ListableAssociation>,
+    archived_at: Option<DateTime<Utc>>,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // serialize our source
+    let source_serialized = serialize!(&source);
+    // instance a list of futures for our updates
+    let mut futs = Vec::with_capacity(associations.len());
+    // create the futures for each association to archive
+    for assoc in associations {
+        // get the year and bucket for this association
+        let year = assoc.created.year();
+        // get the partition size to use for files and tags
+        let chunk = shared.config.thorium.associations.partition_size;
+        // calculate the bucket for our timestamp
+        let bucket = helpers::partition(assoc.created, year, chunk);
+        // chunk this associations groups into chunks of 99
+        for group_chunk in assoc.groups.chunks(99) {
+            // build the future to archive this association
+            let fut = archive_helper(
+                group_chunk,
+                year,
+                bucket,
+                &source_serialized,
+                assoc,
+                archived_at,
+                shared,
+            );
+            // add this future to our future set
+            futs.push(fut);
+        }
+    }
+    // execute all of our updates 10 at a time
+    stream::iter(futs)
+        .buffer_unordered(10)
+        .collect::<Vec<Result<(), ExecutionError>>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<()>, ExecutionError>>()?;
+    Ok(())
+}
+
 /// List associations for a specific entity/file/repo
 ///
 /// # Arguments
@@ -259,13 +367,19 @@ pub async fn list<P: Into<AssociationListParams> + std::fmt::Debug>(
     shared: &Shared,
 ) -> Result<ScyllaCursor<ListableAssociation>, ApiError> {
     // convert our params
-    let params = opts.into();
+    let params: AssociationListParams = opts.into();
+    // check if we should return archived associations before our params are consumed
+    let include_archived = params.include_archived;
     // serialize our association target column before list things related to it
     let source_str = serialize!(&source);
     // get our cursor
     let mut cursor = ScyllaCursor::from_params_extra(params, source_str, false, shared).await?;
     // get the next page of data for this cursor
     cursor.next(shared).await?;
+    // drop any archived associations unless they were requested
+    if !include_archived {
+        cursor.data.retain(|assoc| assoc.archived_at.is_none());
+    }
     // save this cursor
     cursor.save(shared).await?;
     Ok(cursor)
@@ -21,6 +21,7 @@ use uuid::Uuid;
 
 use super::elastic::{self, ElasticResponse};
 use super::keys::{cursors, tags};
+use crate::models::elastic::{HIGHLIGHT_POST_TAG, HIGHLIGHT_PRE_TAG};
 use crate::models::{ApiCursor, ElasticDoc, TagCounts, TagKeyCounts, TagListRow, TagMap, User};
 use crate::models::{ElasticSearchParams, TagType};
 use crate::utils::{ApiError, Shared, helpers};
@@ -75,6 +76,9 @@ pub struct ScyllaCursorRetain<D: CursorCore> {
     pub ties: D::Ties,
     /// Tags data to retain if this is a tags cursor
     pub tags_retain: Option<TagsRetain>,
+    /// The number of partitions to crawl at once if one was requested
+    #[serde(default)]
+    pub partition_hint: Option<u32>,
 }
 
 /// Tag data to retain throughout a cursor's life if it's
@@ -133,6 +137,18 @@ pub trait CursorCore: Debug + Serialize + for<'a> Deserialize<'a> {
     /// * `shared` - Shared Thorium objects
     fn partition_size(shared: &Shared) -> u16;
 
+    /// Get the number of partitions to crawl at once that was requested in our params
+    ///
+    /// This replaces [`CursorCore::bucket_limit`] for non tag queries but is clamped to it
+    /// so our queries stay small enough for Scylla. This is `None` by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The params to use to build this cursor
+    fn get_partition_hint(_params: &Self::Params) -> Option<u32> {
+        None
+    }
+
     /// Get our cursor id from params
     ///
     /// # Arguments
@@ -334,23 +350,14 @@ pub trait ScyllaCursorSupport: CursorCore {
         shared: &Shared,
     ) -> Result<QueryResult, ExecutionError>;
 
-    /// Convert [`QueryResult`]s into a sorted Vec of self
+    /// Cast [`QueryResult`]s into intermediate rows
     ///
     /// # Arguments
     ///
-    /// * `queries` - The queries to cast and sort
-    /// * `sorted` - The map to store sorted data in
-    /// * `mapped` - The currently available number of rows we have sorted
-    /// * `tags` - Whether this a tags cursor not not
-    /// * `span` - The span to log traces under
-    async fn sort(
-        queries: Vec<QueryResult>,
-        sorted: &mut BTreeMap<DateTime<Utc>, VecDeque<Self>>,
-        mapped: &mut usize,
-    ) -> Result<(), ApiError>
-    where
-        Self: Sized,
-    {
+    /// * `queries` - The queries to cast
+    fn cast(queries: Vec<QueryResult>) -> Result<Vec<Self::IntermediateRow>, ApiError> {
+        // keep a list of the rows we cast
+        let mut cast = Vec::new();
         // crawl over each stream and cast their rows to partition counts
         for query in queries {
             // enable casting to types for this query
@@ -359,35 +366,53 @@ pub trait ScyllaCursorSupport: CursorCore {
             let typed_stream = query_rows.rows::<Self::IntermediateRow>()?;
             // cast our rows to typed values
             for row in typed_stream {
-                // raise any errors from casting
-                let cast = row?;
-                // get the timestamp and unique key for our intermediate row
-                let timestamp = Self::get_intermediate_timestamp(&cast);
-                let inter_unique = Self::get_intermediate_unique_key(&cast);
-                // get an entry to the list for this timestamp
-                let entry = sorted.entry(timestamp).or_default();
-                // check if this entry has already been added to this vec
-                let pos = entry
-                    .iter()
-                    .position(|item| inter_unique == item.get_unique_key());
-                // drop our reference to our interemdiate unique value
-                drop(inter_unique);
-                // if we found this item already exists then add our intermediate row
-                match pos {
-                    Some(pos) => {
-                        // get the right list line and add our intermediate row
-                        entry.get_mut(pos).unwrap().add_intermediate_to_line(cast);
-                    }
-                    None => {
-                        // cast our row and add it
-                        entry.push_back(cast.into());
-                        // increment our mapped count
-                        *mapped += 1;
-                    }
-                }
+                cast.push(row?);
+            }
+        }
+        Ok(cast)
+    }
+
+    /// Add an intermediate row to our sorted data
+    ///
+    /// If a line with the same unique key already exists at this timestamp then this
+    /// row is merged into it instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `cast` - The intermediate row to add
+    /// * `sorted` - The map to store sorted data in
+    /// * `mapped` - The currently available number of rows we have sorted
+    fn add_sorted(
+        cast: Self::IntermediateRow,
+        sorted: &mut BTreeMap<DateTime<Utc>, VecDeque<Self>>,
+        mapped: &mut usize,
+    ) where
+        Self: Sized,
+    {
+        // get the timestamp and unique key for our intermediate row
+        let timestamp = Self::get_intermediate_timestamp(&cast);
+        let inter_unique = Self::get_intermediate_unique_key(&cast);
+        // get an entry to the list for this timestamp
+        let entry = sorted.entry(timestamp).or_default();
+        // check if this entry has already been added to this vec
+        let pos = entry
+            .iter()
+            .position(|item| inter_unique == item.get_unique_key());
+        // drop our reference to our interemdiate unique value
+        drop(inter_unique);
+        // if we found this item already exists then add our intermediate row
+        match pos {
+            Some(pos) => {
+                // get the right list line and add our intermediate row
+                entry.get_mut(pos).unwrap().add_intermediate_to_line(cast);
+            }
+            None => {
+                // cast our row and add it
+                entry.push_back(cast.into());
+                // increment our mapped count
+                *mapped += 1;
             }
         }
-        Ok(())
     }
 
     /// Sort any possibly ambigous rows by their cluster key
@@ -460,6 +485,232 @@ pub trait ScyllaCursorSupport: CursorCore {
     }
 }
 
+/// Where a [`ScyllaCursor`] finds the buckets with data and pulls rows from
+///
+/// This is implemented by [`Shared`] to crawl our census streams in Redis and our
+/// list tables in Scylla.
+pub(crate) trait ScyllaRowSource<D: ScyllaCursorSupport> {
+    /// Get the rows that tied with the end of the last page
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor to get tied rows for
+    /// * `limit` - The max number of rows to return to the user plus 1
+    async fn ties(
+        &self,
+        cursor: &mut ScyllaCursor<D>,
+        limit: i32,
+    ) -> Result<Vec<D::IntermediateRow>, ApiError>;
+
+    /// Build the keys for each census stream we are going to crawl
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The groups to build census keys for
+    /// * `extra` - Any extra filters for this query
+    /// * `year` - The year to crawl
+    /// * `bucket` - The bucket to start crawling at
+    /// * `keys` - The census keys to add too
+    fn census_keys<'a>(
+        &self,
+        groups: &'a Vec<D::GroupBy>,
+        extra: &D::ExtraFilters,
+        year: i32,
+        bucket: u32,
+        keys: &mut Vec<(&'a D::GroupBy, String, i32)>,
+    );
+
+    /// Find the buckets that have been confirmed to contain data
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor to find buckets for
+    /// * `bucket_limit` - The most buckets to return for each group
+    /// * `stream_keys` - the stream keys to look for data in
+    /// * `found` - The buckets that we found that contain data
+    async fn find_buckets<'a>(
+        &self,
+        cursor: &ScyllaCursor<D>,
+        bucket_limit: u32,
+        stream_keys: &mut Vec<(&'a D::GroupBy, String, i32)>,
+        found: &mut HashMap<&'a D::GroupBy, Vec<i32>>,
+    ) -> Result<(), ApiError>;
+
+    /// Pull the rows from the buckets we found
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor to pull rows for
+    /// * `found` - The buckets that we found that contain data
+    /// * `limit` - The max number of rows to return to the user plus 1
+    async fn pull<'a>(
+        &self,
+        cursor: &ScyllaCursor<D>,
+        found: &mut HashMap<&'a D::GroupBy, Vec<i32>>,
+        limit: i32,
+    ) -> Result<Vec<D::IntermediateRow>, ApiError>;
+}
+
+impl<D> ScyllaRowSource<D> for Shared
+where
+    for<'de> D: Deserialize<'de> + Debug + std::marker::Send,
+    D: Serialize,
+    D: ScyllaCursorSupport,
+    D: Debug,
+{
+    /// Get the rows that tied with the end of the last page
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor to get tied rows for
+    /// * `limit` - The max number of rows to return to the user plus 1
+    #[instrument(name = "ScyllaRowSource::ties", skip(self, cursor), err(Debug))]
+    async fn ties(
+        &self,
+        cursor: &mut ScyllaCursor<D>,
+        limit: i32,
+    ) -> Result<Vec<D::IntermediateRow>, ApiError> {
+        // build our tie query futures
+        let futures = D::ties_query(
+            &mut cursor.retain.ties,
+            &cursor.retain.extra_filter,
+            cursor.year,
+            cursor.bucket as i32,
+            cursor.retain.start,
+            limit,
+            self,
+        )?;
+        // wait for all of our futures to complete 50 at a time
+        let queries = stream::iter(futures)
+            .buffer_unordered(50)
+            .collect::<Vec<Result<QueryResult, ExecutionError>>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<QueryResult>, ExecutionError>>()?;
+        // cast the rows we just retrieved
+        D::cast(queries)
+    }
+
+    /// Build the keys for each census stream we are going to crawl
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The groups to build census keys for
+    /// * `extra` - Any extra filters for this query
+    /// * `year` - The year to crawl
+    /// * `bucket` - The bucket to start crawling at
+    /// * `keys` - The census keys to add too
+    fn census_keys<'a>(
+        &self,
+        groups: &'a Vec<D::GroupBy>,
+        extra: &D::ExtraFilters,
+        year: i32,
+        bucket: u32,
+        keys: &mut Vec<(&'a D::GroupBy, String, i32)>,
+    ) {
+        D::census_keys(groups, extra, year, bucket, keys, self);
+    }
+
+    /// Find the buckets that have been confirmed to contain data
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor to find buckets for
+    /// * `bucket_limit` - The most buckets to return for each group
+    /// * `stream_keys` - the stream keys to look for data in
+    /// * `found` - The buckets that we found that contain data
+    #[rustfmt::skip]
+    async fn find_buckets<'a>(
+        &self,
+        cursor: &ScyllaCursor<D>,
+        bucket_limit: u32,
+        stream_keys: &mut Vec<(&'a D::GroupBy, String, i32)>,
+        found: &mut HashMap<&'a D::GroupBy, Vec<i32>>,
+    ) -> Result<(), ApiError> {
+        // set our end bucket to be f64::MAX or our end bucket if we are the end year
+        let end = if cursor.year == cursor.end_year {
+            cursor.end_bucket
+        } else {
+            0
+        };
+        // have an allocated vec to swap with our stream keys vec
+        let mut swap: Vec<(&D::GroupBy, String, i32)> = Vec::with_capacity(cursor.retain.group_by.len());
+        // build a redis pipeline to get all of the valid buckets for these tags
+        let mut pipe = redis::pipe();
+        // do this for each stream key
+        for (_, stream_key, oldest_first) in stream_keys.iter() {
+            // get the next 100 items
+            pipe.cmd("zrange").arg(stream_key).arg(oldest_first).arg(end)
+                    .arg("byscore").arg("limit").arg(0).arg(bucket_limit).arg("rev");
+        }
+        // execute our queries
+        let bucket_strings: Vec<Vec<String>> = pipe.query_async(conn!(self)).await?;
+        // swap our stream keys
+        std::mem::swap(stream_keys, &mut swap);
+        // convert all of our buckets to signed ints
+        for (buckets_string, (group, stream_key, _)) in bucket_strings.into_iter().zip(swap.into_iter()) {
+            // convert these buckets to an i32
+            let buckets = buckets_string
+                .iter()
+                .map(|val| val.parse::<i32>())
+                .collect::<Result<Vec<i32>, _>>()?;
+            // get the final item in this list
+            if let Some(oldest_first) = buckets.last() {
+                // decrement our oldest first by 1 so we don't loop over buckets
+                let decr_oldest_first = *oldest_first - 1;
+                // add our new oldest first
+                stream_keys.push((group, stream_key, decr_oldest_first));
+                // add these buckets to our found buckets
+                found.insert(group, buckets);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pull the rows from the buckets we found
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor to pull rows for
+    /// * `found` - The buckets that we found that contain data
+    /// * `limit` - The max number of rows to return to the user plus 1
+    #[instrument(name = "ScyllaRowSource::pull", skip(self, cursor, found), err(Debug))]
+    async fn pull<'a>(
+        &self,
+        cursor: &ScyllaCursor<D>,
+        found: &mut HashMap<&'a D::GroupBy, Vec<i32>>,
+        limit: i32,
+    ) -> Result<Vec<D::IntermediateRow>, ApiError> {
+        // allocate space for the futures we are about to spawn
+        let mut futures = Vec::with_capacity(found.len());
+        // query against these buckets
+        for (group, buckets) in found.drain() {
+            // build the future for this group and its set of buckets
+            let future = D::pull(
+                group,
+                &cursor.retain.extra_filter,
+                cursor.year,
+                buckets,
+                cursor.retain.start,
+                cursor.retain.end,
+                limit,
+                self,
+            );
+            // add the futures to our set
+            futures.push(future);
+        }
+        // wait for all of our futures to complete 50 at a time
+        let queries = stream::iter(futures)
+            .buffer_unordered(50)
+            .collect::<Vec<Result<QueryResult, ExecutionError>>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<QueryResult>, ExecutionError>>()?;
+        // cast the rows we just retrieved
+        D::cast(queries)
+    }
+}
+
 /// A row containing a count of objects within a partition
 pub struct CursorCountRow {
     /// The group this row is for
@@ -624,6 +875,36 @@ where
     pub mapped: usize,
     /// whether this cursor has been exhausted or not
     pub buckets_exhausted: bool,
+    /// The number of times this cursor has crawled its census streams since it was loaded
+    pub census_crawls: usize,
+}
+
+/// Make sure a requested partition hint is within the bounds this user can use
+///
+/// # Arguments
+///
+/// * `hint` - The partition hint that was requested
+/// * `user` - The user that is listing data
+/// * `shared` - Shared Thorium objects
+pub fn check_partition_hint(
+    hint: Option<u32>,
+    user: &User,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    if let Some(hint) = hint {
+        // get the largest hint this user is allowed to set
+        let ceiling = shared
+            .config
+            .thorium
+            .cursors
+            .partition_hint_ceiling(user.is_admin());
+        if hint == 0 || hint > ceiling {
+            return bad!(format!(
+                "The partition hint must be between 1 and {ceiling} not {hint}!"
+            ));
+        }
+    }
+    Ok(())
 }
 
 /// lowercase our tags if needed
@@ -745,6 +1026,7 @@ where
                 group_by: groups,
                 ties: D::Ties::default(),
                 tags_retain,
+                partition_hint: D::get_partition_hint(&params),
             };
             // build our cursor
             let cursor = ScyllaCursor {
@@ -762,6 +1044,7 @@ where
                 sorted: BTreeMap::default(),
                 mapped: 0,
                 buckets_exhausted: false,
+                census_crawls: 0,
             };
             Ok(cursor)
         }
@@ -826,6 +1109,7 @@ where
                 group_by: groups,
                 ties: D::Ties::default(),
                 tags_retain,
+                partition_hint: D::get_partition_hint(&params),
             };
             // build our cursor
             let cursor = ScyllaCursor {
@@ -843,6 +1127,7 @@ where
                 sorted: BTreeMap::default(),
                 mapped: 0,
                 buckets_exhausted: false,
+                census_crawls: 0,
             };
             Ok(cursor)
         }
@@ -905,6 +1190,7 @@ where
                     sorted: BTreeMap::default(),
                     mapped: 0,
                     buckets_exhausted: false,
+                    census_crawls: 0,
                 };
                 Ok(cursor)
             }
@@ -912,109 +1198,20 @@ where
         }
     }
 
-    /// Check if we have any ties queries
-    ///
-    /// # Arguments
-    ///
-    /// * `limit` - The max number of rows to return to the user plus 1
-    /// * `shared` - Shared Thorium objects
-    #[instrument(name = "ScyllaCursor::query_ties", skip(self, shared), err(Debug))]
-    async fn query_ties(
-        &mut self,
-        limit: i32,
-        shared: &Shared,
-    ) -> Result<Vec<QueryResult>, ApiError> {
-        // build our tie query futures
-        let futures = D::ties_query(
-            &mut self.retain.ties,
-            &self.retain.extra_filter,
-            self.year,
-            self.bucket as i32,
-            self.retain.start,
-            limit,
-            shared,
-        )?;
-        // wait for all of our futures to complete 50 at a time
-        let queries = stream::iter(futures)
-            .buffer_unordered(50)
-            .collect::<Vec<Result<QueryResult, ExecutionError>>>()
-            .await
-            .into_iter()
-            .collect::<Result<Vec<QueryResult>, ExecutionError>>()?;
-        Ok(queries)
-    }
-
-    /// Find the buckets that have been confirmed to contain data
-    ///
-    /// # Arguments
-    ///
-    /// * `bucket_limit` - The most buckets to return for each group
-    /// * `stream_keys` - the stream keys to look for data in
-    /// * `found` - The buckets that we found that contain data
-    /// * `shared` - Shared Thorium objects
-    #[rustfmt::skip]
-    async fn find_buckets<'a>(
-        &self,
-        bucket_limit: u32,
-        stream_keys: &mut Vec<(&'a D::GroupBy, String, i32)>,
-        found: &mut HashMap<&'a D::GroupBy, Vec<i32>>,
-        shared: &Shared,
-    ) -> Result<(), ApiError> {
-        // set our end bucket to be f64::MAX or our end bucket if we are the end year
-        let end = if self.year == self.end_year {
-            self.end_bucket
-        } else {
-            0
-        };
-        // have an allocated vec to swap with our stream keys vec
-        let mut swap: Vec<(&D::GroupBy, String, i32)> = Vec::with_capacity(self.retain.group_by.len());
-        // build a redis pipeline to get all of the valid buckets for these tags
-        let mut pipe = redis::pipe();
-        // do this for each stream key
-        for (_, stream_key, oldest_first) in stream_keys.iter() {
-            // get the next 100 items
-            pipe.cmd("zrange").arg(stream_key).arg(oldest_first).arg(end)
-                    .arg("byscore").arg("limit").arg(0).arg(bucket_limit).arg("rev");
-        }
-        // execute our queries
-        let bucket_strings: Vec<Vec<String>> = pipe.query_async(conn!(shared)).await?;
-        // swap our stream keys
-        std::mem::swap(stream_keys, &mut swap);
-        // convert all of our buckets to signed ints
-        for (buckets_string, (group, stream_key, _)) in bucket_strings.into_iter().zip(swap.into_iter()) {
-            // convert these buckets to an i32
-            let buckets = buckets_string
-                .iter()
-                .map(|val| val.parse::<i32>())
-                .collect::<Result<Vec<i32>, _>>()?;
-            // get the final item in this list
-            if let Some(oldest_first) = buckets.last() {
-                // decrement our oldest first by 1 so we don't loop over buckets
-                let decr_oldest_first = *oldest_first - 1;
-                // add our new oldest first
-                stream_keys.push((group, stream_key, decr_oldest_first));
-                // add these buckets to our found buckets
-                found.insert(group, buckets);
-            }
-        }
-        Ok(())
-    }
-
     /// Crawl partitions and pull data from them
     ///
     /// # Arguments
     ///
     /// * `limit` - The max number of rows to return to the user plus 1
-    /// * `shared` - Shared Thorium objects
-    #[instrument(name = "ScyllaCursor::query", skip(self, shared), err(Debug))]
-    async fn query(
+    /// * `bucket_limit` - The most buckets to crawl at once for each group
+    /// * `source` - The source to find buckets and pull rows from
+    #[instrument(name = "ScyllaCursor::query", skip(self, source), err(Debug))]
+    async fn query<S: ScyllaRowSource<D>>(
         &mut self,
         limit: i32,
         bucket_limit: u32,
-        shared: &Shared,
+        source: &S,
     ) -> Result<(), ApiError> {
-        // allocate space for the futures we are about to spawn
-        let mut futures = Vec::with_capacity(self.retain.group_by.len());
         // build all of the keys we are getting valid buckets for
         let mut stream_keys = Vec::with_capacity(self.retain.group_by.len());
         // keep a map of the buckets we have found by group
@@ -1024,18 +1221,19 @@ where
             // if stream keys are empty then recreate them
             if stream_keys.is_empty() {
                 // build the keys for each census stream we are going to crawl
-                D::census_keys(
+                source.census_keys(
                     &self.retain.group_by,
                     &self.retain.extra_filter,
                     self.year,
                     self.bucket,
                     &mut stream_keys,
-                    shared,
                 );
             }
             // get the next buckets that contain data
-            self.find_buckets(bucket_limit, &mut stream_keys, &mut found, shared)
+            source
+                .find_buckets(self, bucket_limit, &mut stream_keys, &mut found)
                 .await?;
+            self.census_crawls += 1;
             // if we found no buckets then check if we have exhausted this cursor
             if found.is_empty() {
                 // if we are in the final year then this cursor is exhausted
@@ -1064,30 +1262,11 @@ where
                 continue;
             }
             // query against these buckets
-            for (group, buckets) in found.drain() {
-                // build the future for this group and its set of buckets
-                let future = D::pull(
-                    group,
-                    &self.retain.extra_filter,
-                    self.year,
-                    buckets,
-                    self.retain.start,
-                    self.retain.end,
-                    limit,
-                    shared,
-                );
-                // add the futures to our set
-                futures.push(future);
+            let rows = source.pull(self, &mut found, limit).await?;
+            // sort the rows we just retrieved
+            for row in rows {
+                D::add_sorted(row, &mut self.sorted, &mut self.mapped);
             }
-            // wait for all of our futures to complete 50 at a time
-            let queries = stream::iter(futures.drain(..))
-                .buffer_unordered(50)
-                .collect::<Vec<Result<QueryResult, ExecutionError>>>()
-                .await
-                .into_iter()
-                .collect::<Result<Vec<QueryResult>, ExecutionError>>()?;
-            // cast and sort the rows we just retrieved
-            D::sort(queries, &mut self.sorted, &mut self.mapped).await?;
             // if we found enough data for this page of our cursor then break out
             if self.mapped >= self.limit || self.exhausted_time() {
                 break;
@@ -1314,14 +1493,17 @@ where
                                 None => continue 'outer,
                             }
                         }
-                        // if we have another item then add it to our tie map
-                        if let Some(tied_row) = item.front() {
-                            // if this a tags cursor add this to our tag ties
-                            if let Some(tag_ties) = &mut tag_ties {
+                        // if we have more items then add them to our tie map
+                        if let Some(tag_ties) = &mut tag_ties {
+                            // tag ties are sorted by cluster key so only the next row is a tie
+                            if let Some(tied_row) = item.front() {
                                 // add this tie to our tag tie map
                                 tied_row.add_tag_tie(tag_ties);
-                            } else {
-                                // add this to our regular query tie map
+                            }
+                        } else {
+                            // the remaining rows can be from different groups so add a tie
+                            // for each group; the first row for each group is its tie
+                            for tied_row in &item {
                                 tied_row.add_tie(&mut self.retain.ties);
                             }
                         }
@@ -1357,19 +1539,26 @@ where
     ///
     /// # Arguments
     ///
-    /// * `shared` - Shared Thorium objects
+    /// * `source` - The source to find buckets and pull rows from
     #[instrument(name = "ScyllaCursor::next_general", skip_all, err(Debug))]
-    async fn next_general(&mut self, shared: &Shared) -> Result<(), ApiError> {
+    async fn next_general<S: ScyllaRowSource<D>>(&mut self, source: &S) -> Result<(), ApiError> {
         // Get the limit + 1 of data for each group so we can check if we end on any ties
         let limit = (self.limit + 1) as i32;
-        // get the number of buckets to check at once
-        let bucket_limit = D::bucket_limit(&self.retain.extra_filter);
+        // get the max number of buckets we can check at once for this query
+        let max_buckets = D::bucket_limit(&self.retain.extra_filter);
+        // prefer any requested hint but never crawl more buckets than this query allows
+        let bucket_limit = self
+            .retain
+            .partition_hint
+            .map_or(max_buckets, |hint| hint.min(max_buckets));
         // get data from any previously tied queries
-        let tied_queries = self.query_ties(limit, shared).await?;
-        // if we had any queries based on ties then consume them
-        if !tied_queries.is_empty() {
-            // cast and sort the rows we just retrieved
-            D::sort(tied_queries, &mut self.sorted, &mut self.mapped).await?;
+        let tied = source.ties(self, limit).await?;
+        // if we had any rows based on ties then consume them
+        if !tied.is_empty() {
+            // sort the rows we just retrieved
+            for row in tied {
+                D::add_sorted(row, &mut self.sorted, &mut self.mapped);
+            }
             // consume our sorted data and check if we have enough data to return
             if self.consume_sorted(None) {
                 // we have enough data so return
@@ -1377,7 +1566,7 @@ where
             }
         }
         // determine which partitions have data
-        self.query(limit, bucket_limit, shared).await?;
+        self.query(limit, bucket_limit, source).await?;
         // consume enough of our sorted data
         self.consume_sorted(None);
         Ok(())
@@ -3124,8 +3313,8 @@ impl ElasticCursor {
                 }
             },
             "highlight": {
-                "pre_tags": vec!["@kibana-highlighted-field@"],
-                "post_tags": vec!["@/kibana-highlighted-field@"],
+                "pre_tags": vec![HIGHLIGHT_PRE_TAG],
+                "post_tags": vec![HIGHLIGHT_POST_TAG],
                 "fields": { "*": {}},
                 // set the max analyzed offset to elastic's configured maximum to
                 // avoid errors when fields are larger; highlights are truncated instead
@@ -3193,3 +3382,522 @@ impl ElasticCursor {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    //! Property tests for paging through Scylla cursors
+    //!
+    //! These run [`ScyllaCursor::next_general`] against an in-memory model of a Scylla
+    //! list table so we can check that every row is returned exactly once no matter how
+    //! pages line up with ties.
+
+    use chrono::prelude::*;
+    use futures_util::Future;
+    use proptest::prelude::*;
+    use scylla::DeserializeRow;
+    use scylla::errors::ExecutionError;
+    use scylla::response::query_result::QueryResult;
+    use serde::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+    use uuid::Uuid;
+
+    use super::{
+        CursorCore, ScyllaCursor, ScyllaCursorRetain, ScyllaCursorSupport, ScyllaRowSource,
+    };
+    use crate::utils::{ApiError, Shared, helpers};
+
+    /// The size of the partitions in our test table in seconds
+    const PARTITION_SIZE: u16 = 4;
+
+    /// How many seconds of data our test table can have
+    const WINDOW: i64 = 12;
+
+    /// The groups rows can be in
+    const GROUPS: [&str; 3] = ["corn", "soy", "wheat"];
+
+    /// Get a timestamp some number of seconds into our test window
+    ///
+    /// # Arguments
+    ///
+    /// * `secs` - The number of seconds into our window to get a timestamp for
+    fn timestamp(secs: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap() + chrono::Duration::seconds(secs)
+    }
+
+    /// Get the partition a timestamp is in
+    ///
+    /// # Arguments
+    ///
+    /// * `uploaded` - The timestamp to get a partition for
+    fn bucket(uploaded: DateTime<Utc>) -> i32 {
+        helpers::partition(uploaded, uploaded.year(), PARTITION_SIZE)
+    }
+
+    /// A row in our test list table
+    #[derive(
+        Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, DeserializeRow,
+    )]
+    struct TestLine {
+        /// The group this row is in
+        group: String,
+        /// When this row was uploaded
+        uploaded: DateTime<Utc>,
+        /// The clustering key that breaks ties between rows with the same timestamp
+        id: i32,
+    }
+
+    impl CursorCore for TestLine {
+        type Params = ();
+
+        type ExtraFilters = ();
+
+        type GroupBy = String;
+
+        type Ties = HashMap<String, i32>;
+
+        fn partition_size(_shared: &Shared) -> u16 {
+            PARTITION_SIZE
+        }
+
+        fn get_id(_params: &Self::Params) -> Option<Uuid> {
+            None
+        }
+
+        fn get_start_end(
+            _params: &Self::Params,
+            _shared: &Shared,
+        ) -> Result<(DateTime<Utc>, DateTime<Utc>), ApiError> {
+            unimplemented!("Test cursors are built directly")
+        }
+
+        fn get_group_by(_params: &mut Self::Params) -> Vec<Self::GroupBy> {
+            Vec::default()
+        }
+
+        fn get_extra_filters(_params: &mut Self::Params) -> Self::ExtraFilters {}
+
+        fn get_limit(_params: &Self::Params) -> usize {
+            0
+        }
+
+        fn add_tie(&self, ties: &mut Self::Ties) {
+            // only the first tied row for each group is its tie
+            ties.entry(self.group.clone()).or_insert(self.id);
+        }
+
+        fn dedupe_item(&self, dedupe_set: &mut HashSet<String>) -> bool {
+            // dedupe on just our id so rows in different groups can be duplicates
+            dedupe_set.insert(self.id.to_string())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ScyllaCursorSupport for TestLine {
+        type IntermediateRow = TestLine;
+
+        type UniqueType<'a> = (&'a str, i32);
+
+        fn get_intermediate_timestamp(intermediate: &Self::IntermediateRow) -> DateTime<Utc> {
+            intermediate.uploaded
+        }
+
+        fn get_timestamp(&self) -> DateTime<Utc> {
+            self.uploaded
+        }
+
+        fn get_intermediate_unique_key<'a>(
+            intermediate: &'a Self::IntermediateRow,
+        ) -> Self::UniqueType<'a> {
+            (intermediate.group.as_str(), intermediate.id)
+        }
+
+        fn get_unique_key<'a>(&'a self) -> Self::UniqueType<'a> {
+            (self.group.as_str(), self.id)
+        }
+
+        fn add_intermediate_to_line(&mut self, _intermediate: Self::IntermediateRow) {}
+
+        fn census_keys<'a>(
+            _group_by: &'a Vec<Self::GroupBy>,
+            _extra: &Self::ExtraFilters,
+            _year: i32,
+            _bucket: u32,
+            _keys: &mut Vec<(&'a Self::GroupBy, String, i32)>,
+            _shared: &Shared,
+        ) {
+        }
+
+        fn ties_query(
+            _ties: &mut Self::Ties,
+            _extra: &Self::ExtraFilters,
+            _year: i32,
+            _bucket: i32,
+            _uploaded: DateTime<Utc>,
+            _limit: i32,
+            _shared: &Shared,
+        ) -> Result<Vec<impl Future<Output = Result<QueryResult, ExecutionError>>>, ApiError>
+        {
+            Ok(Vec::<std::future::Ready<Result<QueryResult, ExecutionError>>>::new())
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        async fn pull(
+            _group: &Self::GroupBy,
+            _extra: &Self::ExtraFilters,
+            _year: i32,
+            _buckets: Vec<i32>,
+            _start: DateTime<Utc>,
+            _end: DateTime<Utc>,
+            _limit: i32,
+            _shared: &Shared,
+        ) -> Result<QueryResult, ExecutionError> {
+            unimplemented!("Test cursors pull from an in-memory table")
+        }
+    }
+
+    /// An in-memory model of a list table partitioned by group and bucket
+    ///
+    /// Rows are clustered by `(uploaded DESC, id DESC)` like our Scylla list tables.
+    struct Table {
+        /// The rows in this table
+        rows: Vec<TestLine>,
+    }
+
+    impl Table {
+        /// Get the rows in a partition in clustering order
+        ///
+        /// # Arguments
+        ///
+        /// * `group` - The group to get rows for
+        /// * `bucket` - The bucket to get rows for
+        fn partition(&self, group: &str, bucket: i32) -> Vec<&TestLine> {
+            let mut rows = self
+                .rows
+                .iter()
+                .filter(|row| row.group == group && self::bucket(row.uploaded) == bucket)
+                .collect::<Vec<&TestLine>>();
+            rows.sort_by(|a, b| (b.uploaded, b.id).cmp(&(a.uploaded, a.id)));
+            rows
+        }
+
+        /// Get the newest buckets with data for a group like our census streams
+        ///
+        /// # Arguments
+        ///
+        /// * `group` - The group to get buckets for
+        /// * `oldest_first` - The newest bucket to return
+        /// * `end` - The oldest bucket to return
+        /// * `limit` - The most buckets to return
+        fn census(&self, group: &str, oldest_first: i32, end: i32, limit: usize) -> Vec<i32> {
+            self.rows
+                .iter()
+                .filter(|row| row.group == group)
+                .map(|row| bucket(row.uploaded))
+                .filter(|bucket| (end..=oldest_first).contains(bucket))
+                .collect::<BTreeSet<i32>>()
+                .into_iter()
+                .rev()
+                .take(limit)
+                .collect()
+        }
+
+        /// Get the rest of the rows that tied with the end of a page like `list_ties`
+        ///
+        /// # Arguments
+        ///
+        /// * `group` - The group to get tied rows for
+        /// * `uploaded` - The timestamp the rows tied at
+        /// * `id` - The id of the first tied row
+        /// * `limit` - The most rows to return
+        fn list_ties(
+            &self,
+            group: &str,
+            uploaded: DateTime<Utc>,
+            id: i32,
+            limit: usize,
+        ) -> Vec<TestLine> {
+            self.partition(group, bucket(uploaded))
+                .into_iter()
+                .filter(|row| row.uploaded == uploaded && row.id <= id)
+                .take(limit)
+                .cloned()
+                .collect()
+        }
+
+        /// Get the rows in some buckets between two timestamps like `list_pull`
+        ///
+        /// # Arguments
+        ///
+        /// * `group` - The group to get rows for
+        /// * `buckets` - The buckets to get rows from
+        /// * `start` - The timestamp to get rows older than
+        /// * `end` - The timestamp to get rows newer than
+        /// * `limit` - The most rows to return from each partition
+        fn list_pull(
+            &self,
+            group: &str,
+            buckets: &[i32],
+            start: DateTime<Utc>,
+            end: DateTime<Utc>,
+            limit: usize,
+        ) -> Vec<TestLine> {
+            buckets
+                .iter()
+                .flat_map(|bucket| {
+                    self.partition(group, *bucket)
+                        .into_iter()
+                        .filter(|row| row.uploaded < start && row.uploaded > end)
+                        .take(limit)
+                        .cloned()
+                })
+                .collect()
+        }
+    }
+
+    impl ScyllaRowSource<TestLine> for Table {
+        async fn ties(
+            &self,
+            cursor: &mut ScyllaCursor<TestLine>,
+            limit: i32,
+        ) -> Result<Vec<TestLine>, ApiError> {
+            let limit = usize::try_from(limit).unwrap();
+            let mut tied = Vec::new();
+            for (group, id) in cursor.retain.ties.drain() {
+                tied.extend(self.list_ties(&group, cursor.retain.start, id, limit));
+            }
+            Ok(tied)
+        }
+
+        fn census_keys<'a>(
+            &self,
+            groups: &'a Vec<String>,
+            _extra: &(),
+            _year: i32,
+            bucket: u32,
+            keys: &mut Vec<(&'a String, String, i32)>,
+        ) {
+            for group in groups {
+                keys.push((group, group.clone(), i32::try_from(bucket).unwrap()));
+            }
+        }
+
+        async fn find_buckets<'a>(
+            &self,
+            cursor: &ScyllaCursor<TestLine>,
+            bucket_limit: u32,
+            stream_keys: &mut Vec<(&'a String, String, i32)>,
+            found: &mut HashMap<&'a String, Vec<i32>>,
+        ) -> Result<(), ApiError> {
+            let end = if cursor.year == cursor.end_year {
+                i32::try_from(cursor.end_bucket).unwrap()
+            } else {
+                0
+            };
+            // drop any groups that are out of data like our census streams
+            for (group, key, oldest_first) in std::mem::take(stream_keys) {
+                let buckets = self.census(group, oldest_first, end, bucket_limit as usize);
+                if let Some(last) = buckets.last() {
+                    stream_keys.push((group, key, last - 1));
+                    found.insert(group, buckets);
+                }
+            }
+            Ok(())
+        }
+
+        async fn pull<'a>(
+            &self,
+            cursor: &ScyllaCursor<TestLine>,
+            found: &mut HashMap<&'a String, Vec<i32>>,
+            limit: i32,
+        ) -> Result<Vec<TestLine>, ApiError> {
+            let limit = usize::try_from(limit).unwrap();
+            let rows = found
+                .drain()
+                .flat_map(|(group, buckets)| {
+                    self.list_pull(
+                        group,
+                        &buckets,
+                        cursor.retain.start,
+                        cursor.retain.end,
+                        limit,
+                    )
+                })
+                .collect();
+            Ok(rows)
+        }
+    }
+
+    /// Load a saved cursor like [`ScyllaCursor::get`]
+    ///
+    /// # Arguments
+    ///
+    /// * `saved` - The serialized data this cursor retained
+    /// * `limit` - The max number of rows to return in a page
+    /// * `dedupe` - Whether to dedupe rows
+    fn load(saved: &str, limit: usize, dedupe: bool) -> ScyllaCursor<TestLine> {
+        let retain: ScyllaCursorRetain<TestLine> =
+            serde_json::from_str(saved).expect("Failed to load cursor");
+        let year = retain.start.year();
+        let end_year = retain.end.year();
+        ScyllaCursor {
+            id: Uuid::nil(),
+            year,
+            bucket: u32::try_from(bucket(retain.start)).unwrap(),
+            end_year,
+            end_bucket: u32::try_from(bucket(retain.end)).unwrap(),
+            retain,
+            partition_size: PARTITION_SIZE,
+            limit,
+            dedupe,
+            dedupe_set: HashSet::default(),
+            data: Vec::default(),
+            sorted: BTreeMap::default(),
+            mapped: 0,
+            buckets_exhausted: false,
+            census_crawls: 0,
+        }
+    }
+
+    /// Page through a table until our cursor is exhausted
+    ///
+    /// The cursor is saved and reloaded between pages like it is between requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to page through
+    /// * `groups` - The groups to list rows from
+    /// * `limit` - The max number of rows to return in a page
+    /// * `bucket_limit` - The number of buckets to crawl at once
+    /// * `dedupe` - Whether to dedupe rows
+    fn page_all(
+        table: &Table,
+        groups: Vec<String>,
+        limit: usize,
+        bucket_limit: u32,
+        dedupe: bool,
+    ) -> Vec<Vec<TestLine>> {
+        let retain = ScyllaCursorRetain::<TestLine> {
+            start: timestamp(WINDOW + 1),
+            end: timestamp(0),
+            extra_filter: (),
+            group_by: groups,
+            ties: HashMap::default(),
+            tags_retain: None,
+            partition_hint: Some(bucket_limit),
+        };
+        let mut saved = serde_json::to_string(&retain).unwrap();
+        let mut pages = Vec::new();
+        // every page but the last consumes at least one row
+        for _ in 0..=table.rows.len() + 1 {
+            let mut cursor = load(&saved, limit, dedupe);
+            tokio_test::block_on(cursor.next_general(table)).expect("Failed to get a page");
+            pages.push(std::mem::take(&mut cursor.data));
+            if cursor.exhausted() {
+                return pages;
+            }
+            saved = serde_json::to_string(&cursor.retain).unwrap();
+        }
+        panic!("Cursor was never exhausted after {} pages", pages.len());
+    }
+
+    /// Generate the rows in a table with lots of tied timestamps
+    fn rows() -> impl Strategy<Value = Vec<TestLine>> {
+        // rows are unique by their group, timestamp, and id like our primary keys
+        proptest::collection::btree_set((0..GROUPS.len(), 1..=WINDOW, 0..16i32), 0..48).prop_map(
+            |keys| {
+                keys.into_iter()
+                    .map(|(group, secs, id)| TestLine {
+                        group: GROUPS[group].to_owned(),
+                        uploaded: timestamp(secs),
+                        id,
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Generate the groups to list rows from
+    fn groups() -> impl Strategy<Value = Vec<String>> {
+        proptest::sample::subsequence(GROUPS.to_vec(), 1..=GROUPS.len())
+            .prop_map(|groups| groups.into_iter().map(str::to_owned).collect())
+    }
+
+    proptest! {
+        /// Every row in our groups is returned exactly once and newest first
+        #[test]
+        fn pages_every_row_once(
+            rows in rows(),
+            groups in groups(),
+            limit in 1..8usize,
+            bucket_limit in 1..4u32,
+        ) {
+            let table = Table { rows };
+            let pages = page_all(&table, groups.clone(), limit, bucket_limit, false);
+            // no page should be larger than our limit
+            for page in &pages {
+                prop_assert!(page.len() <= limit);
+            }
+            // rows should be returned newest first
+            let returned = pages.into_iter().flatten().collect::<Vec<TestLine>>();
+            prop_assert!(returned.windows(2).all(|pair| pair[0].uploaded >= pair[1].uploaded));
+            // every row should be returned with no duplicates
+            let mut returned = returned;
+            returned.sort();
+            let mut expected = table
+                .rows
+                .iter()
+                .filter(|row| groups.contains(&row.group))
+                .cloned()
+                .collect::<Vec<TestLine>>();
+            expected.sort();
+            prop_assert_eq!(returned, expected);
+        }
+
+        /// Deduped pages never repeat a row and still return every distinct row
+        #[test]
+        fn dedupe_pages(
+            rows in rows(),
+            groups in groups(),
+            limit in 1..8usize,
+            bucket_limit in 1..4u32,
+        ) {
+            let table = Table { rows };
+            let pages = page_all(&table, groups.clone(), limit, bucket_limit, true);
+            let mut returned = BTreeSet::default();
+            for page in pages {
+                // no page should have duplicates
+                let ids = page.iter().map(|row| row.id).collect::<BTreeSet<i32>>();
+                prop_assert_eq!(ids.len(), page.len());
+                returned.extend(ids);
+            }
+            let expected = table
+                .rows
+                .iter()
+                .filter(|row| groups.contains(&row.group))
+                .map(|row| row.id)
+                .collect::<BTreeSet<i32>>();
+            prop_assert_eq!(returned, expected);
+        }
+    }
+
+    #[test]
+    fn ties_across_groups() {
+        // rows in different groups at the same timestamp so each page ends on a tie
+        let rows = [("corn", 9), ("soy", 7), ("wheat", 3), ("corn", 2)]
+            .into_iter()
+            .map(|(group, id)| TestLine {
+                group: group.to_owned(),
+                uploaded: timestamp(5),
+                id,
+            })
+            .collect::<Vec<TestLine>>();
+        let table = Table { rows };
+        let groups = GROUPS.iter().map(|group| (*group).to_owned()).collect();
+        let pages = page_all(&table, groups, 1, 1, false);
+        let mut returned = pages.into_iter().flatten().collect::<Vec<TestLine>>();
+        returned.sort();
+        let mut expected = table.rows.clone();
+        expected.sort();
+        assert_eq!(returned, expected);
+    }
+}
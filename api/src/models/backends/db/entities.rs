@@ -3,6 +3,7 @@
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap, HashSet};
 
+use bb8_redis::redis::cmd;
 use chrono::{DateTime, Datelike, Utc};
 use futures::stream::{self, StreamExt, TryStreamExt};
 use itertools::Itertools;
@@ -16,7 +17,7 @@ use crate::models::{
     KeySupport, TagDeleteRequest, TagRequest, User,
 };
 use crate::utils::{ApiError, Shared, helpers};
-use crate::{bad, not_found, serialize};
+use crate::{bad, conn, exec_query, not_found, query, serialize};
 
 /// Create a `Entity` in Scylla
 ///
@@ -165,6 +166,10 @@ pub async fn get(groups: &[String], id: Uuid, shared: &Shared) -> Result<Entity,
         Some(mut entity) => {
             // get the tags for this entity
             entity.get_tags(groups, shared).await?;
+            // get the current version of this entity
+            entity.version = get_version(&entity.id, shared).await?;
+            // check if this entity has been archived
+            entity.archived_at = get_archived(&[entity.id], shared).await?.remove(&entity.id);
             // return the entity and its tags
             Ok(entity)
         }
@@ -172,6 +177,118 @@ pub async fn get(groups: &[String], id: Uuid, shared: &Shared) -> Result<Entity,
     }
 }
 
+/// Get the current version of an entity
+///
+/// Entities that have never been updated are at version 0.
+///
+/// # Arguments
+///
+/// * `id` - The id of the entity to get the version for
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::entities::get_version", skip(shared), err(Debug))]
+pub async fn get_version(id: &Uuid, shared: &Shared) -> Result<u64, ApiError> {
+    // build the key to this entities version
+    let key = super::keys::entities::version(id, shared);
+    // get this entities version if one has been set
+    let version: Option<u64> = query!(cmd("get").arg(key), shared).await?;
+    Ok(version.unwrap_or_default())
+}
+
+/// Get when any of the given entities were archived
+///
+/// Entities that have not been archived will not be in the returned map.
+///
+/// # Arguments
+///
+/// * `ids` - The ids of the entities to check
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::entities::get_archived", skip(shared), err(Debug))]
+pub async fn get_archived(
+    ids: &[Uuid],
+    shared: &Shared,
+) -> Result<HashMap<Uuid, DateTime<Utc>>, ApiError> {
+    // hmget requires at least one field
+    if ids.is_empty() {
+        return Ok(HashMap::default());
+    }
+    // build the key to our archived entities map
+    let key = super::keys::entities::archived(shared);
+    // get the timestamps these entities were archived at
+    let fields = ids.iter().map(Uuid::to_string).collect::<Vec<String>>();
+    let timestamps: Vec<Option<i64>> = query!(cmd("hmget").arg(key).arg(fields), shared).await?;
+    // map our archived entities to when they were archived
+    let archived = ids
+        .iter()
+        .zip(timestamps)
+        .filter_map(|(id, timestamp)| {
+            timestamp
+                .and_then(DateTime::from_timestamp_millis)
+                .map(|archived_at| (*id, archived_at))
+        })
+        .collect();
+    Ok(archived)
+}
+
+/// Mark an entity as archived or restore it if no timestamp is given
+///
+/// # Arguments
+///
+/// * `id` - The id of the entity to archive or restore
+/// * `archived_at` - When this entity was archived or None to restore it
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::entities::set_archived", skip(shared), err(Debug))]
+pub async fn set_archived(
+    id: &Uuid,
+    archived_at: Option<DateTime<Utc>>,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // build the key to our archived entities map
+    let key = super::keys::entities::archived(shared);
+    match archived_at {
+        Some(archived_at) => {
+            exec_query!(
+                cmd("hset")
+                    .arg(key)
+                    .arg(id.to_string())
+                    .arg(archived_at.timestamp_millis()),
+                shared
+            )
+            .await?;
+        }
+        None => exec_query!(cmd("hdel").arg(key).arg(id.to_string()), shared).await?,
+    }
+    Ok(())
+}
+
+/// Atomically bump an entity's version if it is still at the expected version
+///
+/// Returns false if another update has already bumped this entity's version.
+///
+/// # Arguments
+///
+/// * `id` - The id of the entity to bump the version for
+/// * `expected` - The version this entity is expected to currently be at
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::entities::claim_version", skip(shared), err(Debug))]
+pub async fn claim_version(id: &Uuid, expected: u64, shared: &Shared) -> Result<bool, ApiError> {
+    // only bump our version if nobody else has bumped it since we read it
+    let script = redis::Script::new(
+        r"
+        local current = tonumber(redis.call('get', KEYS[1]) or '0');
+        if current ~= tonumber(ARGV[1]) then
+            return 0;
+        end
+        redis.call('set', KEYS[1], current + 1);
+        return 1;",
+    );
+    let claimed: bool = script
+        .key(super::keys::entities::version(id, shared))
+        .arg(expected)
+        .invoke_async(conn!(shared))
+        .await?;
+    Ok(claimed)
+}
+
 /// List entities in specific groups and with tags
 ///
 /// # Arguments
@@ -185,6 +302,8 @@ pub async fn list(
     dedupe: bool,
     shared: &Shared,
 ) -> Result<ScyllaCursor<EntityListLine>, ApiError> {
+    // check if we should return archived entities before our params are consumed
+    let include_archived = params.include_archived;
     // get our cursor
     let mut cursor = ScyllaCursor::from_params(params, dedupe, shared).await?;
     // get the next page of data for this cursor
@@ -192,7 +311,23 @@ pub async fn list(
     // if we are searching on tags, we need to supplement the data
     // with names+kinds because tag rows don't have them
     if cursor.retain.tags_retain.is_some() {
-        supplement_tag_lines(&mut cursor.data, shared).await?;
+        // tag rows also don't have submitters so filter on them after supplementing
+        let submitter = cursor.retain.extra_filter.1.as_ref();
+        supplement_tag_lines(&mut cursor.data, submitter, shared).await?;
+    }
+    // drop any archived entities unless they were requested
+    if !include_archived {
+        // get the ids of the entities in this page that were archived
+        let ids = cursor
+            .data
+            .iter()
+            .map(|line| line.id)
+            .collect::<Vec<Uuid>>();
+        let archived = get_archived(&ids, shared).await?;
+        // remove any archived entities
+        if !archived.is_empty() {
+            cursor.data.retain(|line| !archived.contains_key(&line.id));
+        }
     }
     // save this cursor
     cursor.save(shared).await?;
@@ -260,6 +395,12 @@ pub async fn get_many(
             Ok(())
         })
         .await?;
+    // get when any of these entities were archived
+    let ids = data_map.keys().copied().collect::<Vec<Uuid>>();
+    let mut archived = get_archived(&ids, shared).await?;
+    for entity in data_map.values_mut() {
+        entity.archived_at = archived.remove(&entity.id);
+    }
     // compile our details into a final list
     let mut details = Vec::with_capacity(data_map.len());
     // keep creation order by using the sorted map
@@ -280,10 +421,12 @@ pub async fn get_many(
 /// # Arguments
 ///
 /// * `lines` - The lines to supplement
+/// * `submitter` - The submitter to filter our lines to if one was set
 /// * `shared` - Shared Thorium objects
 #[instrument(name = "db::entities::supplement_tag_lines", skip_all, err(Debug))]
 async fn supplement_tag_lines(
-    lines: &mut [EntityListLine],
+    lines: &mut Vec<EntityListLine>,
+    submitter: Option<&String>,
     shared: &Shared,
 ) -> Result<(), ApiError> {
     // make a map of ids to their index in the mutable vec
@@ -321,14 +464,25 @@ async fn supplement_tag_lines(
     .await
     .into_iter()
     .collect::<Result<Vec<_>, _>>()?;
+    // track the entities that were created by a different submitter
+    let mut filtered = HashSet::new();
     // add the supplemental row info to our lines;
     // we'll have duplicate rows due to groups, but that's okay
     for row in supplement_rows.into_iter().flatten() {
+        // skip any entities that weren't created by the requested submitter
+        if submitter.is_some_and(|submitter| *submitter != row.submitter) {
+            filtered.insert(row.id);
+            continue;
+        }
         let index = line_map.get(&row.id).copied().unwrap_or_default();
         let line = &mut lines[index];
         line.name = row.name;
         line.kind = row.kind;
     }
+    // drop any filtered entities from our lines
+    if !filtered.is_empty() {
+        lines.retain(|line| !filtered.contains(&line.id));
+    }
     Ok(())
 }
 
@@ -561,5 +715,10 @@ pub async fn delete(user: &User, entity: &Entity, shared: &Shared) -> Result<(),
     super::keys::entities::census_keys(&mut keys, &entity.groups, year, bucket, grouping, shared);
     // update this samples census cache info
     super::census::decr_cache(keys, shared).await?;
+    // delete this entities version counter
+    let version_key = super::keys::entities::version(&entity.id, shared);
+    exec_query!(cmd("del").arg(version_key), shared).await?;
+    // remove this entity from our archived entities if it was archived
+    set_archived(&entity.id, None, shared).await?;
     Ok(())
 }
@@ -105,6 +105,8 @@ pub async fn create(
     hsetnx_opt_serialize!(pipe, &keys.data, "description", &cast.description);
     // execute pipeline and create our group
     () = pipe.atomic().query_async(conn!(shared)).await?;
+    // make sure we don't have any stale copies of this group cached
+    shared.groups.invalidate(&cast.name);
     Ok(cast)
 }
 
@@ -116,6 +118,12 @@ pub async fn create(
 /// * `shared` - Shared Thorium objects
 #[rustfmt::skip]
 pub async fn get(group: &str, shared: &Shared) -> Result<Group, ApiError> {
+    // use our cached copy of this group if we have a recent one
+    if let Some(cached) = shared.groups.get(group) {
+        return Ok(cached);
+    }
+    // get our caches generation so we don't cache this group if it changes while we get it
+    let generation = shared.groups.generation();
     // get the user lists for this group
     let raw_members: MembersLists = get_members!(redis::pipe(), group, shared)
         .query_async(conn!(shared))
@@ -136,7 +144,10 @@ pub async fn get(group: &str, shared: &Shared) -> Result<Group, ApiError> {
         not_found!(format!("group {} does not exist", group))
     } else {
         let group_data: RawGroupData = (group.to_owned(), raw_members, raw_data, analysts);
-        Group::try_from(group_data)
+        let cast = Group::try_from(group_data)?;
+        // cache this group for the next request that needs it
+        shared.groups.insert(group, cast.clone(), generation);
+        Ok(cast)
     }
 }
 
@@ -199,12 +210,54 @@ pub type RawGroupData = (
 
 /// Lists all groups in the redis backend with their details
 ///
+/// Recently used groups are pulled from our cache instead of Redis.
+///
 /// # Arguments
 ///
 /// * `groups` - The names of the groups to get details for
 /// * `shared` - Shared Thorium objects
 #[instrument(name = "db::groups::list_details", skip_all, err(Debug))]
 pub async fn list_details<'a, T>(groups: T, shared: &Shared) -> Result<Vec<Group>, ApiError>
+where
+    T: Iterator<Item = &'a String> + Clone,
+{
+    // get our caches generation so we don't cache groups that change while we get them
+    let generation = shared.groups.generation();
+    // split our groups into the ones we have cached and the ones we need to get
+    let mut found = HashMap::new();
+    let mut missing = Vec::new();
+    for name in groups.clone() {
+        match shared.groups.get(name) {
+            Some(cached) => {
+                found.insert(name.clone(), cached);
+            }
+            None => missing.push(name),
+        }
+    }
+    // get any groups we didn't have cached from redis
+    if !missing.is_empty() {
+        for group in fetch_details(missing.into_iter(), shared).await? {
+            // only cache groups that actually exist
+            if !group.owners.combined.is_empty() || !group.managers.combined.is_empty() {
+                shared
+                    .groups
+                    .insert(group.name.clone(), group.clone(), generation);
+            }
+            found.insert(group.name.clone(), group);
+        }
+    }
+    // return our groups in the same order they were requested in
+    let details = groups.filter_map(|name| found.get(name).cloned()).collect();
+    Ok(details)
+}
+
+/// Gets groups and their details from redis
+///
+/// # Arguments
+///
+/// * `groups` - The names of the groups to get details for
+/// * `shared` - Shared Thorium objects
+async fn fetch_details<'a, T>(groups: T, shared: &Shared) -> Result<Vec<Group>, ApiError>
 where
     T: Iterator<Item = &'a String> + Clone,
 {
@@ -336,6 +389,8 @@ pub async fn restore(groups: &[Group], shared: &Shared, span: &Span) -> Result<(
     }
     // restore this group to redis
     () = pipe.atomic().query_async(conn!(shared)).await?;
+    // drop any cached groups since they may have changed
+    shared.groups.clear();
     Ok(())
 }
 
@@ -349,6 +404,25 @@ pub async fn exists<'a>(names: &[String], shared: &Shared) -> Result<bool, ApiEr
     helpers::exists_all(names, &GroupKeys::set(shared), shared).await
 }
 
+/// Finds any groups that don't exist in the Redis backend
+///
+/// All groups are checked in a single batched query.
+///
+/// # Arguments
+///
+/// * `names` - The names of the groups to check the existence of
+/// * `shared` - Shared Thorium objects
+pub async fn exist_many(names: &[String], shared: &Shared) -> Result<Vec<String>, ApiError> {
+    // only check each group once
+    let mut seen = HashSet::with_capacity(names.len());
+    let unique = names
+        .iter()
+        .filter(|name| seen.insert(name.as_str()))
+        .cloned()
+        .collect::<Vec<String>>();
+    helpers::missing(&unique, &GroupKeys::set(shared), shared).await
+}
+
 /// update the users for a role in a group
 fn update_role<'a>(pipe: &'a mut redis::Pipeline, users: &HashSet<String>, key: &str) {
     // clear this key first
@@ -410,6 +484,8 @@ pub async fn update(
     pipe.cmd("hset").arg(&keys.data).arg("allowed").arg(serialize!(&group.allowed));
     // execute pipeline and check if it failed
     () = pipe.atomic().query_async(conn!(shared)).await?;
+    // drop our cached copy of this group so its new members and roles are used
+    shared.groups.invalidate(&group.name);
     Ok(())
 }
 
@@ -482,5 +558,7 @@ pub async fn delete(
     // attempt to delete group from redis backend
     // we can't confirm the delete because if a role has no users then it will return false
     () = pipe.atomic().query_async(conn!(shared)).await?;
+    // drop our cached copy of this group
+    shared.groups.invalidate(&group.name);
     Ok(())
 }
@@ -75,17 +75,30 @@ macro_rules! hset_del_opt_serialize {
     };
 }
 
+/// A connection from the Redis connection pool
+#[cfg(not(feature = "namespace-checks"))]
+pub type RedisConn<'a> = bb8::PooledConnection<'a, RedisConnectionManager>;
+
+/// A connection from the Redis connection pool that rejects cross namespace commands
+#[cfg(feature = "namespace-checks")]
+pub type RedisConn<'a> = Box<super::namespaces::CheckedConn<'a>>;
+
 /// Gets a connection from the Redis connection pool
 ///
 /// # Arguments
 ///
 /// * `shared` - Shared Thorium objects
-pub async fn get_conn(
-    shared: &Shared,
-) -> Result<bb8::PooledConnection<'_, RedisConnectionManager>, ApiError> {
+pub async fn get_conn(shared: &Shared) -> Result<RedisConn<'_>, ApiError> {
     // get connection from redis pool
     match shared.redis.get().await {
+        #[cfg(not(feature = "namespace-checks"))]
         Ok(conn) => Ok(conn),
+        // check that all commands on this connection stay in our namespace
+        #[cfg(feature = "namespace-checks")]
+        Ok(conn) => Ok(Box::new(super::namespaces::CheckedConn::new(
+            conn,
+            &shared.config.thorium.namespace,
+        ))),
         Err(error) => unavailable!(format!("Failed to get connection from pool: {:#?}", error)),
     }
 }
@@ -203,11 +216,7 @@ pub async fn exists_all(
     shared: &Shared,
 ) -> Result<bool, ApiError> {
     // check if every value is in the set in one Redis pipeline
-    let checks: Vec<bool> = values
-        .iter()
-        .fold(redis::pipe().atomic(), |pipe, value| {
-            pipe.cmd("sismember").arg(set_key).arg(value)
-        })
+    let checks: Vec<bool> = members_pipe(values, set_key)
         .query_async(conn!(shared))
         .await?;
     // check that all of the values exist
@@ -215,6 +224,62 @@ pub async fn exists_all(
     Ok(all_exist)
 }
 
+/// Build a pipeline that checks if each value is in a Redis set
+///
+/// # Arguments
+///
+/// * `values` - The values to check for
+/// * `set_key` - The key to the Redis set
+fn members_pipe(values: &[String], set_key: &str) -> redis::Pipeline {
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    for value in values {
+        pipe.cmd("sismember").arg(set_key).arg(value);
+    }
+    pipe
+}
+
+/// Get the values that were not found in a Redis set
+///
+/// # Arguments
+///
+/// * `values` - The values that were checked
+/// * `checks` - Whether each value was in the set
+fn collect_missing(values: &[String], checks: Vec<bool>) -> Vec<String> {
+    values
+        .iter()
+        .zip(checks)
+        .filter(|(_, exists)| !exists)
+        .map(|(value, _)| value.clone())
+        .collect()
+}
+
+/// Find any values in a given list that don't exist in the Redis set
+/// at the given key
+///
+/// This checks all values in a single round trip to Redis.
+///
+/// # Arguments
+///
+/// * `values` - The values to check for
+/// * `set_key` - The key to the Redis set
+/// * `shared` - The Thorium shared object
+pub async fn missing(
+    values: &[String],
+    set_key: &str,
+    shared: &Shared,
+) -> Result<Vec<String>, ApiError> {
+    // skip talking to Redis if we have nothing to check
+    if values.is_empty() {
+        return Ok(Vec::default());
+    }
+    // check if every value is in the set in one Redis pipeline
+    let checks: Vec<bool> = members_pipe(values, set_key)
+        .query_async(conn!(shared))
+        .await?;
+    Ok(collect_missing(values, checks))
+}
+
 /// Checks if a user is an admin or not and uses the right groups for the action requested
 ///
 /// # Arguments
@@ -272,3 +337,21 @@ macro_rules! same_vec {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_missing, members_pipe};
+
+    #[test]
+    fn batched_missing() {
+        let values = vec!["corn".to_owned(), "soy".to_owned(), "wheat".to_owned()];
+        let pipe = members_pipe(&values, "thorium:groups");
+        // every value should be checked in one pipeline instead of one query each
+        assert_eq!(pipe.cmd_iter().count(), values.len());
+        assert!(pipe.cmd_iter().all(|cmd| cmd.args_iter().count() == 3));
+        // only the values that weren't found are reported
+        let missing = collect_missing(&values, vec![true, false, true]);
+        assert_eq!(missing, vec!["soy".to_owned()]);
+        assert!(collect_missing(&values, vec![true; 3]).is_empty());
+    }
+}
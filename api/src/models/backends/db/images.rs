@@ -92,18 +92,52 @@ pub async fn create(user: &User, request: ImageRequest, shared: &Shared) -> Resu
             format!("Image {} already exists in group {}", &cast.name, &cast.group)
         )
     } else {
+        // drop any stale cached copy of this image
+        shared.image_cache.invalidate(&cache_key(&cast.group, &cast.name));
         Ok(cast)
     }
 }
 
 /// Gets an images data from the backend
 ///
+/// Recently read images are cached and concurrent gets for the same image
+/// share a single backend read.
+///
 /// # Arguments
 ///
 /// * `group` - The group to get an image From
 /// * `name` - The name of the image to get
 /// * `shared` - Shared objects in Thorium
 pub async fn get(group: &str, name: &str, shared: &Shared) -> Result<Image, ApiError> {
+    // build the key to cache and coalesce gets for this image on
+    let key = cache_key(group, name);
+    // use our cached copy or share a read with any other gets for this image
+    shared
+        .images
+        .get_cached(&shared.image_cache, key, || {
+            get_uncoalesced(group, name, shared)
+        })
+        .await
+}
+
+/// Build the key to cache an image at
+///
+/// # Arguments
+///
+/// * `group` - The group the image is in
+/// * `name` - The name of the image
+pub fn cache_key(group: &str, name: &str) -> String {
+    format!("{group}:{name}")
+}
+
+/// Gets an images data from the backend without sharing the read
+///
+/// # Arguments
+///
+/// * `group` - The group to get an image From
+/// * `name` - The name of the image to get
+/// * `shared` - Shared objects in Thorium
+async fn get_uncoalesced(group: &str, name: &str, shared: &Shared) -> Result<Image, ApiError> {
     // build image keys
     let data_key = ImageKeys::data(group, name, shared);
     let used_by_key = ImageKeys::used_by(group, name, shared);
@@ -205,6 +239,8 @@ pub async fn update(image: &Image, shared: &Shared) -> Result<(), ApiError> {
     pipe.cmd("hset").arg(&syskey.data).arg(image.scaler.cache_key()).arg(true);
     // save image to backend
 () = pipe.atomic().query_async(conn!(shared)).await?;
+    // drop our cached copy of this image
+    shared.image_cache.invalidate(&cache_key(&image.group, &image.name));
     Ok(())
 }
 
@@ -377,6 +413,42 @@ pub async fn list_details(
     Ok(images)
 }
 
+/// Loads all images in a group into our image cache
+///
+/// Nothing is loaded if our image cache is disabled.
+///
+/// # Arguments
+///
+/// * `group` - The group to cache images from
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::images::warm", skip(shared), err(Debug))]
+pub async fn warm(group: &str, shared: &Shared) -> Result<usize, ApiError> {
+    // don't read anything if we aren't caching images
+    if shared.image_cache.disabled() {
+        return Ok(0);
+    }
+    // get our caches generation so we don't cache any images that change while we list them
+    let generation = shared.image_cache.generation();
+    let mut cursor = 0;
+    let mut cached = 0;
+    loop {
+        // get the next page of images in this group
+        let list = list(group, cursor, 1000, shared).await?;
+        // get their details and add them to our cache
+        for image in list_details(group, &list.names, shared).await? {
+            let key = cache_key(&image.group, &image.name);
+            shared.image_cache.insert(key, image, generation);
+            cached += 1;
+        }
+        // check if our cursor has been exhausted
+        match list.cursor {
+            Some(next) => cursor = next,
+            None => break,
+        }
+    }
+    Ok(cached)
+}
+
 /// List all images in all groups with details for backups
 ///
 /// # Arguments
@@ -467,6 +539,8 @@ pub async fn restore(images: &[Image], shared: &Shared) -> Result<(), ApiError>
             "Failed to restore images expected {expected} but found {real}"
         ));
     }
+    // drop any cached images since they may have been replaced
+    shared.image_cache.clear();
     Ok(())
 }
 
@@ -490,6 +564,8 @@ pub async fn delete(image: &Image, shared: &Shared) -> Result<(), ApiError> {
         .cmd("hset").arg(&syskey.data).arg("scaler_cache").arg(true)
         .query_async(conn!(shared)).await?;
     // skip checking for success due to the fact that hset always returns false
+    // drop our cached copy of this image
+    shared.image_cache.invalidate(&cache_key(&image.group, &image.name));
     // delete all of the image's related data
     // delete all of the image's notifications
     image.delete_all_notifications(&ImageKey::from(image), shared).await?;
@@ -551,6 +627,8 @@ pub async fn delete_all(group: &Group, shared: &Shared) -> Result<(), ApiError>
     }
     // delete the images set for this group
     exec_query!(cmd("del").arg(ImageKeys::set(&group.name, shared)), shared).await?;
+    // drop any cached images since this groups images are gone
+    shared.image_cache.clear();
     Ok(())
 }
 
@@ -597,5 +675,7 @@ pub async fn update_runtimes(group: &Group, shared: &Shared) -> Result<(), ApiEr
         // update cursor
         cursor = images.cursor.unwrap();
     }
+    // drop any cached images since their runtimes have changed
+    shared.image_cache.clear();
     Ok(())
 }
@@ -346,8 +346,8 @@ pub async fn pop_job(scaler: ImageScaler, worker: &Worker, src: &str, dest: &str
 #[rustfmt::skip]
 #[instrument(name = "db::jobs::update_reaction", skip_all, err(Debug))]
 async fn update_reaction<'a>(pipe: &'a mut redis::Pipeline, job: &RawJob, reaction: &Reaction, shared: &Shared) -> Result<(), ApiError> {
-    // get the timestamp for this reactions sla
-    let timestamp = reaction.sla.timestamp();
+    // get the score to order this reaction by in its group
+    let timestamp = reaction.group_score();
     // if this reactions status is created then move it to the started set
     if reaction.status == ReactionStatus::Created {
         // build key to this jobs reactions data
@@ -477,6 +477,61 @@ pub async fn set_args(job: &RawJob, shared: &Shared) -> Result<JobHandleStatus,
     Ok(JobHandleStatus::Checkpointed)
 }
 
+/// Saves when a job started executing and when it must finish by
+///
+/// # Arguments
+///
+/// * `job` - The job that started
+/// * `started` - When this job started executing
+/// * `execution_deadline` - When this job must finish executing by if it has a timeout
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::jobs::start", skip_all, err(Debug))]
+pub async fn start(
+    job: &RawJob,
+    started: DateTime<Utc>,
+    execution_deadline: Option<DateTime<Utc>>,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // build key to this jobs data
+    let key = JobKeys::data(&job.id, shared);
+    // build a redis pipeline to set when this job started
+    let mut pipe = redis::pipe();
+    pipe.cmd("hset").arg(&key).arg("started").arg(serialize!(&started));
+    // set or clear our execution deadline
+    match execution_deadline {
+        Some(deadline) => pipe.cmd("hset").arg(&key).arg("execution_deadline").arg(serialize!(&deadline)),
+        None => pipe.cmd("hdel").arg(&key).arg("execution_deadline"),
+    };
+    let _: () = pipe.query_async(conn!(shared)).await?;
+    Ok(())
+}
+
+/// Saves a job's new execution deadline after a heartbeat
+///
+/// # Arguments
+///
+/// * `job` - The job that sent a heartbeat
+/// * `execution_deadline` - When this job must now finish executing by
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::jobs::heartbeat", skip_all, err(Debug))]
+pub async fn heartbeat(
+    job: &RawJob,
+    execution_deadline: DateTime<Utc>,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // build key to this jobs data
+    let key = JobKeys::data(&job.id, shared);
+    // save our new execution deadline
+    let _: () = redis::cmd("hset")
+        .arg(&key)
+        .arg("execution_deadline")
+        .arg(serialize!(&execution_deadline))
+        .query_async(conn!(shared))
+        .await?;
+    Ok(())
+}
+
 /// Sets a jobs status to be sleeping
 ///
 /// This is used to let generator jobs return to Thorium and later be respawned. They must still be
@@ -799,6 +854,9 @@ pub async fn bulk_reset(
         // update jobs status
         pipe.cmd("hset").arg(JobKeys::data(&job.id, shared))
                 .arg("status").arg(force_serialize!(&JobStatus::Created))
+            // this job will start its execution clock over when it is claimed again
+            .cmd("hdel").arg(JobKeys::data(&job.id, shared))
+                .arg("started").arg("execution_deadline")
             // add to created jobs set
             .cmd("zadd").arg(status_queue!(job, &JobStatus::Created, shared))
                 .arg(job.deadline.timestamp()).arg(&job_claim)
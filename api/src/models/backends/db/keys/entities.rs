@@ -1,5 +1,7 @@
 //! Databse keys for entities
 
+use uuid::Uuid;
+
 use crate::models::CensusKeys;
 use crate::utils::Shared;
 
@@ -51,3 +53,28 @@ pub fn census_keys(
         keys.push(key);
     }
 }
+
+/// Build the key to the version counter for an entity
+///
+/// # Arguments
+///
+/// * `id` - The id of the entity to build a version key for
+/// * `shared` - Shared Thorium objects
+pub fn version(id: &Uuid, shared: &Shared) -> String {
+    format!(
+        "{namespace}:entities:version:{id}",
+        namespace = shared.config.thorium.namespace,
+    )
+}
+
+/// Build the key to the map of archived entities to when they were archived
+///
+/// # Arguments
+///
+/// * `shared` - Shared Thorium objects
+pub fn archived(shared: &Shared) -> String {
+    format!(
+        "{namespace}:entities:archived",
+        namespace = shared.config.thorium.namespace,
+    )
+}
@@ -196,6 +196,22 @@ impl ReactionKeys {
         )
     }
 
+    /// Builds key to the [Reaction] id an idempotency key is claimed by in a group
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group the reactions are in
+    /// * `key` - The idempotency key
+    /// * `shared` - Shared Thorium objects
+    pub fn idempotency(group: &str, key: &str, shared: &Shared) -> String {
+        format!(
+            "{ns}:reaction_idempotency:{group}:{key}",
+            ns = shared.config.thorium.namespace,
+            group = group,
+            key = key,
+        )
+    }
+
     /// Builds key to the sorted set of [Reactions] for an entire group
     ///
     /// # Arguments
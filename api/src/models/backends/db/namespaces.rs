@@ -0,0 +1,279 @@
+//! Verifies that Redis commands only touch keys under our namespace
+//!
+//! Every Redis key Thorium uses is prefixed with its namespace so that multiple
+//! Thorium instances can share a single Redis. Building a key without that prefix
+//! lets one instance read or clobber another's data without any errors. When the
+//! `namespace-checks` feature is enabled every command sent to Redis is checked
+//! against our namespace and rejected if it touches a key outside of it.
+
+use redis::{Arg, Cmd, Pipeline};
+
+/// Get the keys an arg list declares with a key count at a specific index
+///
+/// # Arguments
+///
+/// * `args` - The args to get keys from
+/// * `index` - The index of the key count
+fn counted_keys<'a>(args: &[&'a [u8]], index: usize) -> Vec<&'a [u8]> {
+    // parse the number of keys that follow our key count
+    let count = args
+        .get(index)
+        .and_then(|raw| std::str::from_utf8(raw).ok())
+        .and_then(|raw| raw.parse::<usize>().ok())
+        .unwrap_or_default();
+    args.iter().skip(index + 1).take(count).copied().collect()
+}
+
+/// Get the keys a Redis command will touch
+///
+/// Commands that are not known to take multiple keys are assumed to only touch
+/// the key in their first arg.
+///
+/// # Arguments
+///
+/// * `cmd` - The command to get the keys for
+pub fn cmd_keys(cmd: &Cmd) -> Vec<&[u8]> {
+    // get the args for this command skipping any cursors
+    let args = cmd
+        .args_iter()
+        .filter_map(|arg| match arg {
+            Arg::Simple(arg) => Some(arg),
+            Arg::Cursor => None,
+        })
+        .collect::<Vec<&[u8]>>();
+    // split off the name of this command
+    let Some((name, args)) = args.split_first() else {
+        return Vec::new();
+    };
+    match String::from_utf8_lossy(name).to_ascii_uppercase().as_str() {
+        // commands that don't touch any keys
+        "PING" | "INFO" | "TIME" | "DBSIZE" | "SCRIPT" | "MULTI" | "EXEC" | "DISCARD"
+        | "SELECT" | "CLIENT" | "AUTH" | "HELLO" | "FLUSHDB" => Vec::new(),
+        // commands where every arg is a key
+        "DEL" | "UNLINK" | "EXISTS" | "TOUCH" | "MGET" | "WATCH" | "SINTER" | "SUNION"
+        | "SDIFF" | "SINTERSTORE" | "SUNIONSTORE" | "SDIFFSTORE" => args.to_vec(),
+        // commands that move data from one key to another
+        "RENAME" | "RENAMENX" | "SMOVE" | "RPOPLPUSH" | "LMOVE" | "COPY" => {
+            args.iter().take(2).copied().collect()
+        }
+        // commands that set multiple key/value pairs
+        "MSET" | "MSETNX" => args.iter().step_by(2).copied().collect(),
+        // commands that start with a count of keys
+        "ZUNION" | "ZINTER" | "ZDIFF" | "ZINTERCARD" | "SINTERCARD" => counted_keys(args, 0),
+        // commands that store into a destination from a count of keys
+        "ZUNIONSTORE" | "ZINTERSTORE" | "ZDIFFSTORE" => {
+            let mut keys = counted_keys(args, 1);
+            keys.extend(args.first());
+            keys
+        }
+        // scripts declare the keys they touch after their body or sha
+        "EVAL" | "EVALSHA" | "EVAL_RO" | "EVALSHA_RO" => counted_keys(args, 1),
+        // everything else only touches the key in its first arg
+        _ => args.iter().take(1).copied().collect(),
+    }
+}
+
+/// Make sure a Redis command only touches keys in our namespace
+///
+/// # Arguments
+///
+/// * `cmd` - The command to check
+/// * `namespace` - The namespace all keys must be in
+pub fn check_cmd(cmd: &Cmd, namespace: &str) -> Result<(), String> {
+    // build the prefix all of our keys must start with
+    let prefix = format!("{namespace}:");
+    for key in cmd_keys(cmd) {
+        if !key.starts_with(prefix.as_bytes()) {
+            // get the name of this command for our error
+            let name = cmd
+                .args_iter()
+                .next()
+                .and_then(|arg| match arg {
+                    Arg::Simple(name) => Some(String::from_utf8_lossy(name).to_ascii_uppercase()),
+                    Arg::Cursor => None,
+                })
+                .unwrap_or_default();
+            return Err(format!(
+                "{name} touches the key '{key}' outside of the namespace '{namespace}'",
+                key = String::from_utf8_lossy(key),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Make sure every command in a Redis pipeline only touches keys in our namespace
+///
+/// # Arguments
+///
+/// * `pipe` - The pipeline to check
+/// * `namespace` - The namespace all keys must be in
+pub fn check_pipe(pipe: &Pipeline, namespace: &str) -> Result<(), String> {
+    pipe.cmd_iter()
+        .try_for_each(|cmd| check_cmd(cmd, namespace))
+}
+
+#[cfg(feature = "namespace-checks")]
+mod checked {
+    use bb8_redis::{RedisConnectionManager, bb8};
+    use redis::aio::ConnectionLike;
+    use redis::{Cmd, ErrorKind, Pipeline, RedisError, RedisFuture, Value};
+
+    /// A Redis connection that rejects commands touching keys outside of our namespace
+    pub struct CheckedConn<'a> {
+        /// The pooled connection to send commands over
+        conn: bb8::PooledConnection<'a, RedisConnectionManager>,
+        /// The namespace all keys must be in
+        namespace: &'a str,
+    }
+
+    impl<'a> CheckedConn<'a> {
+        /// Wrap a pooled connection so its commands are checked against our namespace
+        ///
+        /// # Arguments
+        ///
+        /// * `conn` - The pooled connection to wrap
+        /// * `namespace` - The namespace all keys must be in
+        pub fn new(
+            conn: bb8::PooledConnection<'a, RedisConnectionManager>,
+            namespace: &'a str,
+        ) -> Self {
+            CheckedConn { conn, namespace }
+        }
+
+        /// Build the error to return for a cross namespace command
+        ///
+        /// # Arguments
+        ///
+        /// * `msg` - The message describing the key that was outside our namespace
+        fn reject<'b, T: Send + 'b>(msg: String) -> RedisFuture<'b, T> {
+            // log this access so it is easy to find even if the error is swallowed
+            tracing::error!("Cross namespace Redis access: {msg}");
+            let err = RedisError::from((ErrorKind::ClientError, "Cross namespace access", msg));
+            Box::pin(std::future::ready(Err(err)))
+        }
+    }
+
+    impl ConnectionLike for CheckedConn<'_> {
+        fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+            match super::check_cmd(cmd, self.namespace) {
+                Ok(()) => self.conn.req_packed_command(cmd),
+                Err(msg) => Self::reject(msg),
+            }
+        }
+
+        fn req_packed_commands<'a>(
+            &'a mut self,
+            pipe: &'a Pipeline,
+            offset: usize,
+            count: usize,
+        ) -> RedisFuture<'a, Vec<Value>> {
+            match super::check_pipe(pipe, self.namespace) {
+                Ok(()) => self.conn.req_packed_commands(pipe, offset, count),
+                Err(msg) => Self::reject(msg),
+            }
+        }
+
+        fn get_db(&self) -> i64 {
+            self.conn.get_db()
+        }
+    }
+}
+
+#[cfg(feature = "namespace-checks")]
+pub use checked::CheckedConn;
+
+#[cfg(test)]
+mod tests {
+    use super::{check_cmd, check_pipe};
+
+    #[test]
+    fn namespaced_pipe() {
+        let mut pipe = redis::pipe();
+        pipe.cmd("hset")
+            .arg("tenant_a:groups:corn")
+            .arg("name")
+            .arg("corn")
+            .cmd("sadd")
+            .arg("tenant_a:groups")
+            .arg("corn")
+            .cmd("sunionstore")
+            .arg("tenant_a:combined")
+            .arg("tenant_a:direct")
+            .arg("tenant_a:meta")
+            .cmd("zunionstore")
+            .arg("tenant_a:merged")
+            .arg(2)
+            .arg("tenant_a:left")
+            .arg("tenant_a:right")
+            .cmd("ping");
+        assert_eq!(check_pipe(&pipe, "tenant_a"), Ok(()));
+    }
+
+    #[test]
+    fn cross_namespace_cmd() {
+        // a key in another namespace is flagged
+        let mut cmd = redis::cmd("hgetall");
+        cmd.arg("tenant_b:groups:corn");
+        let err = check_cmd(&cmd, "tenant_a").unwrap_err();
+        assert!(err.contains("HGETALL"), "{err}");
+        assert!(err.contains("tenant_b:groups:corn"), "{err}");
+        // a namespace that only shares a prefix with ours is flagged
+        let mut cmd = redis::cmd("get");
+        cmd.arg("tenant_ab:settings");
+        assert!(check_cmd(&cmd, "tenant_a").is_err());
+        // only values are allowed to be outside of our namespace
+        let mut cmd = redis::cmd("set");
+        cmd.arg("tenant_a:settings").arg("tenant_b:settings");
+        assert_eq!(check_cmd(&cmd, "tenant_a"), Ok(()));
+    }
+
+    #[test]
+    fn cross_namespace_multi_key() {
+        // a foreign source key in a multi key command is flagged
+        let mut cmd = redis::cmd("sunionstore");
+        cmd.arg("tenant_a:combined")
+            .arg("tenant_a:direct")
+            .arg("tenant_b:meta");
+        assert!(check_cmd(&cmd, "tenant_a").is_err());
+        // a foreign destination key is flagged
+        let mut cmd = redis::cmd("renamenx");
+        cmd.arg("tenant_a:old").arg("tenant_b:new");
+        assert!(check_cmd(&cmd, "tenant_a").is_err());
+        // a foreign counted key is flagged
+        let mut cmd = redis::cmd("zinterstore");
+        cmd.arg("tenant_a:merged")
+            .arg(2)
+            .arg("tenant_a:left")
+            .arg("tenant_b:right");
+        assert!(check_cmd(&cmd, "tenant_a").is_err());
+        // only the declared keys of a script are checked
+        let mut cmd = redis::cmd("evalsha");
+        cmd.arg("abc123")
+            .arg(1)
+            .arg("tenant_a:entities")
+            .arg("tenant_b:value");
+        assert_eq!(check_cmd(&cmd, "tenant_a"), Ok(()));
+        let mut cmd = redis::cmd("evalsha");
+        cmd.arg("abc123")
+            .arg(2)
+            .arg("tenant_a:entities")
+            .arg("tenant_b:entities");
+        assert!(check_cmd(&cmd, "tenant_a").is_err());
+    }
+
+    #[test]
+    fn cross_namespace_pipe() {
+        // a single foreign key anywhere in a pipeline is flagged
+        let mut pipe = redis::pipe();
+        pipe.cmd("hset")
+            .arg("tenant_a:groups:corn")
+            .arg("name")
+            .arg("corn")
+            .cmd("sadd")
+            .arg("tenant_b:groups")
+            .arg("corn");
+        let err = check_pipe(&pipe, "tenant_a").unwrap_err();
+        assert!(err.contains("tenant_b:groups"), "{err}");
+    }
+}
@@ -6,12 +6,13 @@ use super::helpers;
 use super::keys::{EventKeys, GroupKeys, ImageKeys, JobKeys, PipelineKeys};
 use crate::models::backends::NotificationSupport;
 use crate::models::{
-    Group, JobStatus, Pipeline, PipelineKey, PipelineList, PipelineRequest, PipelineStats,
-    Reaction, StageStats, User,
+    EventTrigger, Group, JobStatus, Pipeline, PipelineKey, PipelineList, PipelineRequest,
+    PipelineStats, Reaction, StageStats, User,
 };
 use crate::utils::{ApiError, Shared};
 use crate::{
-    cast, conn, hset_del_opt_serialize, hsetnx_opt_serialize, not_found, query, serialize,
+    cast, conn, deserialize, hset_del_opt_serialize, hsetnx_opt_serialize, not_found, query,
+    serialize,
 };
 
 /// Builds a pipeline creation pipeline for Redis
@@ -75,6 +76,8 @@ pub async fn create(
     build(&mut pipe, &cast, shared)?;
     // create our pipeline
     () = pipe.atomic().query_async(conn!(shared)).await?;
+    // drop any stale cached copies of this pipeline or its images
+    invalidate(&cast, cast.order.iter().flatten(), shared);
     Ok(cast)
 }
 
@@ -132,6 +135,42 @@ pub async fn list_details(
     Ok(pipelines)
 }
 
+/// Loads all pipelines in a group into our pipeline cache
+///
+/// Nothing is loaded if our pipeline cache is disabled.
+///
+/// # Arguments
+///
+/// * `group` - The group to cache pipelines from
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::pipelines::warm", skip(shared), err(Debug))]
+pub async fn warm(group: &str, shared: &Shared) -> Result<usize, ApiError> {
+    // don't read anything if we aren't caching pipelines
+    if shared.pipeline_cache.disabled() {
+        return Ok(0);
+    }
+    // get our caches generation so we don't cache any pipelines that change while we list them
+    let generation = shared.pipeline_cache.generation();
+    let mut cursor = 0;
+    let mut cached = 0;
+    loop {
+        // get the next page of pipelines in this group
+        let list = list(group, cursor, 1000, shared).await?;
+        // get their details and add them to our cache
+        for pipeline in list_details(group, &list.names, shared).await? {
+            let key = cache_key(&pipeline.group, &pipeline.name);
+            shared.pipeline_cache.insert(key, pipeline, generation);
+            cached += 1;
+        }
+        // check if our cursor has been exhausted
+        match list.cursor {
+            Some(next) => cursor = next,
+            None => break,
+        }
+    }
+    Ok(cached)
+}
+
 /// Lists all pipelines in redis for a backup
 ///
 /// # Arguments
@@ -177,11 +216,17 @@ pub async fn restore(pipelines: &[Pipeline], shared: &Shared) -> Result<(), ApiE
         .collect::<Result<Vec<()>, ApiError>>()?;
     // try to save pipelines into redis
     () = pipe.atomic().query_async(conn!(shared)).await?;
+    // drop any cached pipelines and images since they may have been replaced
+    shared.pipeline_cache.clear();
+    shared.image_cache.clear();
     Ok(())
 }
 
 /// Gets a pipelines data if it exists
 ///
+/// Recently read pipelines are cached and concurrent gets for the same
+/// pipeline share a single backend read.
+///
 /// # Arguments
 ///
 /// * `group` - The group the pipeline is in
@@ -189,6 +234,56 @@ pub async fn restore(pipelines: &[Pipeline], shared: &Shared) -> Result<(), ApiE
 /// * `shared` - Shared Thorium objects
 #[instrument(name = "db::pipelines::get", skip(shared), err(Debug))]
 pub async fn get(group: &str, name: &str, shared: &Shared) -> Result<Pipeline, ApiError> {
+    // build the key to cache and coalesce gets for this pipeline on
+    let key = cache_key(group, name);
+    // use our cached copy or share a read with any other gets for this pipeline
+    shared
+        .pipelines
+        .get_cached(&shared.pipeline_cache, key, || {
+            get_uncoalesced(group, name, shared)
+        })
+        .await
+}
+
+/// Build the key to cache a pipeline at
+///
+/// # Arguments
+///
+/// * `group` - The group the pipeline is in
+/// * `name` - The name of the pipeline
+pub fn cache_key(group: &str, name: &str) -> String {
+    format!("{group}:{name}")
+}
+
+/// Drop the cached copies of a pipeline and the images whose used_by lists it changed
+///
+/// # Arguments
+///
+/// * `pipeline` - The pipeline that changed
+/// * `images` - The images whose used_by lists changed
+/// * `shared` - Shared Thorium objects
+fn invalidate<'a, I>(pipeline: &Pipeline, images: I, shared: &Shared)
+where
+    I: Iterator<Item = &'a String>,
+{
+    shared
+        .pipeline_cache
+        .invalidate(&cache_key(&pipeline.group, &pipeline.name));
+    for image in images {
+        shared
+            .image_cache
+            .invalidate(&super::images::cache_key(&pipeline.group, image));
+    }
+}
+
+/// Gets a pipelines data without sharing the read with other requests
+///
+/// # Arguments
+///
+/// * `group` - The group the pipeline is in
+/// * `name` - The name of the pipeline to check
+/// * `shared` - Shared Thorium objects
+async fn get_uncoalesced(group: &str, name: &str, shared: &Shared) -> Result<Pipeline, ApiError> {
     // build key to pipeline data
     let key = PipelineKeys::data(group, name, shared);
     // get pipeline data
@@ -244,9 +339,103 @@ pub async fn update(pipeline: &Pipeline, add: &[String], remove: &[String], shar
     hset_del_opt_serialize!(pipe, &keys.data, "description", &pipeline.description);
     // execute this query
     () = pipe.atomic().query_async(conn!(shared)).await?;
+    // drop our cached copies of this pipeline and the images it was added to or removed from
+    invalidate(pipeline, add.iter().chain(remove.iter()), shared);
     Ok(())
 }
 
+/// Apply an update to the triggers currently saved for a pipeline
+///
+/// # Arguments
+///
+/// * `pipeline` - The pipeline whose triggers are being updated
+/// * `exists` - Whether this pipeline still exists
+/// * `raw` - The pipeline's currently saved triggers
+/// * `update` - The function to apply to the current triggers
+fn apply_triggers<F>(
+    pipeline: &Pipeline,
+    exists: bool,
+    raw: Option<String>,
+    update: &mut F,
+) -> Result<(HashMap<String, EventTrigger>, String), ApiError>
+where
+    F: FnMut(&mut HashMap<String, EventTrigger>) -> Result<(), ApiError>,
+{
+    // make sure this pipeline wasn't deleted
+    if !exists {
+        return not_found!(format!(
+            "Pipeline {}:{} does not exist",
+            pipeline.group, pipeline.name
+        ));
+    }
+    // deserialize our current triggers
+    let mut triggers = match raw {
+        Some(raw) => deserialize!(&raw, "triggers"),
+        None => HashMap::default(),
+    };
+    update(&mut triggers)?;
+    // serialize our updated triggers so they can be saved
+    let serialized = serialize!(&triggers);
+    Ok((triggers, serialized))
+}
+
+/// Atomically updates the triggers for a pipeline in Redis
+///
+/// The current triggers are read and the update is retried if they change before our
+/// update is saved so concurrent trigger updates are never lost.
+///
+/// # Arguments
+///
+/// * `pipeline` - The pipeline to update triggers for
+/// * `update` - The function to apply to the current triggers
+/// * `shared` - Shared objects in Thorium
+#[rustfmt::skip]
+#[instrument(name = "db::pipelines::update_triggers", skip_all, fields(pipeline = &pipeline.name), err(Debug))]
+pub async fn update_triggers<F>(
+    pipeline: &Pipeline,
+    mut update: F,
+    shared: &Shared,
+) -> Result<HashMap<String, EventTrigger>, ApiError>
+where
+    F: FnMut(&mut HashMap<String, EventTrigger>) -> Result<(), ApiError>,
+{
+    // build the keys to this pipeline and our event handler cache status
+    let keys = PipelineKeys::new(pipeline, shared);
+    let cache_status = EventKeys::cache(shared);
+    // use a single connection so our watch covers our transaction
+    let mut conn = helpers::get_conn(shared).await?;
+    loop {
+        // abort our transaction if this pipeline changes before we save our triggers
+        () = cmd("watch").arg(&keys.data).query_async(&mut *conn).await?;
+        // get our current triggers
+        let (exists, raw): (bool, Option<String>) = redis::pipe()
+            .cmd("exists").arg(&keys.data)
+            .cmd("hget").arg(&keys.data).arg("triggers")
+            .query_async(&mut *conn).await?;
+        // apply our update to our current triggers
+        let (triggers, serialized) = match apply_triggers(pipeline, exists, raw, &mut update) {
+            Ok(updated) => updated,
+            Err(error) => {
+                // stop watching this pipeline since we won't be updating it
+                () = cmd("unwatch").query_async(&mut *conn).await?;
+                return Err(error);
+            }
+        };
+        // save our triggers and tell the event handler they changed
+        let saved: Option<(u64, u64)> = redis::pipe()
+            .atomic()
+            .cmd("hset").arg(&keys.data).arg("triggers").arg(serialized)
+            .cmd("hset").arg(&cache_status).arg("status").arg(true)
+            .query_async(&mut *conn).await?;
+        // our transaction is aborted if another update beat us so retry on the new triggers
+        if saved.is_some() {
+            // drop our cached copy of this pipeline
+            invalidate(pipeline, std::iter::empty(), shared);
+            return Ok(triggers);
+        }
+    }
+}
+
 /// Delete a pipeline and all its jobs
 ///
 /// This will also delete all reactions and jobs for this pipeline.
@@ -290,6 +479,8 @@ pub async fn delete(
         });
     // execute this pipeline
     () = pipe.atomic().query_async(conn!(shared)).await?;
+    // drop our cached copies of this pipeline and its images
+    invalidate(&pipeline, pipeline.order.iter().flatten(), shared);
     Ok(())
 }
 
@@ -346,6 +537,8 @@ pub async fn delete_all(
         // update cursor
         cursor = pipelines.cursor.unwrap();
     }
+    // drop any cached pipelines since this groups pipelines are gone
+    shared.pipeline_cache.clear();
     Ok(())
 }
 
@@ -14,16 +14,19 @@ use super::keys::{
 use super::{images, jobs, pipelines, streams};
 use crate::models::backends::reactions::InternalReactionCacheFileUpdates;
 use crate::models::{
-    BulkReactionResponse, Group, JobHandleStatus, JobList, JobResetRequestor, JobResets, Pipeline,
-    RawJob, Reaction, ReactionActions, ReactionCache, ReactionCacheUpdate, ReactionExpire,
-    ReactionList, ReactionRequest, ReactionStatus, StageLogs, StageLogsAdd, StatusRequest,
-    StatusUpdate, SystemComponents, User,
+    BulkReactionResponse, Group, JobHandleStatus, JobList, JobResetRequestor, JobResets, JobStatus,
+    Pipeline, RawJob, Reaction, ReactionActions, ReactionCache, ReactionCacheUpdate,
+    ReactionExpire, ReactionList, ReactionRequest, ReactionStatus, StageLogs, StageLogsAdd,
+    StatusRequest, StatusUpdate, SystemComponents, User,
 };
 use crate::utils::{ApiError, Shared};
 use crate::{
     bad, cast, conflict, conn, force_serialize, log_err, log_scylla_err, query, serialize,
 };
 
+/// The prefix for idempotency key claims whose reaction is still being created
+const IDEMPOTENCY_PENDING: &str = "pending:";
+
 /// build created status update from a reaction
 macro_rules! status_create {
     ($cast:expr) => {
@@ -47,7 +50,7 @@ macro_rules! status_complete {
 /// build the pipeline cache key
 macro_rules! pipe_key {
     ($reaction:expr) => {
-        format!("{}:{}", $reaction.group, $reaction.pipeline)
+        format!("{}:{}", $reaction.group_name(), $reaction.pipeline)
     };
 }
 
@@ -76,8 +79,8 @@ pub async fn build(
     let keys = ReactionKeys::new(&cast, shared);
     // build reaction status update
     let update = status_create!(&cast);
-    // get the timestamp for this reaction
-    let timestamp = cast.sla.timestamp();
+    // get the score to order this reaction by in its group
+    let timestamp = cast.group_score();
     // get our reaction id as a string
     let reaction_id = cast.id.to_string();
     // create reaction
@@ -101,6 +104,7 @@ pub async fn build(
         .cmd("hsetnx").arg(&keys.data).arg("ephemeral").arg(serialize!(&cast.ephemeral))
         .cmd("hsetnx").arg(&keys.data).arg("parent_ephemeral").arg(serialize!(&cast.parent_ephemeral))
         .cmd("hsetnx").arg(&keys.data).arg("repos").arg(serialize!(&cast.repos))
+        .cmd("hsetnx").arg(&keys.data).arg("priority").arg(cast.priority)
         // add to specific status set
         .cmd("sadd").arg(ReactionKeys::status(&cast.group, &cast.pipeline, &cast.status, shared))
             .arg(&reaction_id)
@@ -142,6 +146,13 @@ pub async fn build(
         // set our trigger depth
         pipe.cmd("hsetnx").arg(&keys.data).arg("trigger_depth").arg(trigger_depth);
     }
+    // save our idempotency key if one was set; it was already claimed before this pipeline
+    if let Some(idempotency_key) = cast.idempotency_key.as_ref() {
+        pipe.cmd("hsetnx").arg(&keys.data).arg("idempotency_key").arg(serialize!(idempotency_key))
+            // mark our claim as created so retries are deduplicated to this reaction
+            .cmd("set").arg(ReactionKeys::idempotency(&cast.group, idempotency_key, shared))
+                .arg(&reaction_id).arg("EX").arg(shared.config.thorium.retention.idempotency_keys);
+    }
     // add to any required tag lists
     let pipe = cast.tags.iter()
         .fold(pipe, |pipe, tag|
@@ -190,6 +201,9 @@ pub async fn get_parent_ephemeral(
 
 /// Creates a [`Reaction`] in redis
 ///
+/// If another reaction has already claimed this request's idempotency key then that
+/// reaction is returned instead along with `true`.
+///
 /// # Arguments
 ///
 /// * `user` - The [`User`] creating this [`Reaction`]
@@ -202,18 +216,39 @@ pub async fn create(
     request: ReactionRequest,
     pipeline: &Pipeline,
     shared: &Shared,
-) -> Result<Reaction, ApiError> {
+) -> Result<(Reaction, bool), ApiError> {
     // get any ephemeral files from any parent reactions
     let map = HashMap::default();
-    let ephemeral = get_parent_ephemeral(&request.group, &request.parent, map, shared).await?;
+    let ephemeral = get_parent_ephemeral(&pipeline.group, &request.parent, map, shared).await?;
     // cast to a reaction
     let (cast, cache, _) = request.cast(user, pipeline, ephemeral, shared).await?;
-    // build reaction creation pipeline
-    let mut pipe = redis::pipe();
-    let (reaction, _) = build(&mut pipe, cast.clone(), cache, pipeline, shared).await?;
-    // create reaction along with its jobs in redis
-    let _: () = pipe.atomic().query_async(conn!(shared)).await?;
-    Ok(reaction)
+    // claim our idempotency key or return the reaction that already claimed it
+    if let Some(key) = cast.idempotency_key.as_ref() {
+        if let Some(id) = claim_idempotent(&cast.group, key, &cast.id, shared).await? {
+            return Ok((get(&cast.group, &id, shared).await?, true));
+        }
+    }
+    let (group, id, key) = (cast.group.clone(), cast.id, cast.idempotency_key.clone());
+    // build and run our reaction creation pipeline
+    let created = async {
+        let mut pipe = redis::pipe();
+        let (reaction, _) = build(&mut pipe, cast, cache, pipeline, shared).await?;
+        // create reaction along with its jobs in redis
+        let _: () = pipe.atomic().query_async(conn!(shared)).await?;
+        Ok::<Reaction, ApiError>(reaction)
+    }
+    .await;
+    match created {
+        Ok(reaction) => Ok((reaction, false)),
+        Err(error) => {
+            // release our claim so a retry can create this reaction
+            if let Some(key) = key {
+                let claims = HashMap::from([((group, key), id)]);
+                release_claims(&claims, shared).await;
+            }
+            Err(error)
+        }
+    }
 }
 
 /// Creates [`Reaction`]s in Redis in bulk
@@ -235,49 +270,228 @@ pub async fn create_bulk(
     let mut casts: Vec<(Reaction, ReactionCache, &Pipeline)> = Vec::with_capacity(requests.len());
     // build a response object allocated to the right size
     let mut response = BulkReactionResponse::with_capacity(requests.len());
-    // try to cast all of our requests to a reaction
-    for (index, req) in requests.into_iter().enumerate() {
-        // get any ephemeral files from any parent reactions
-        let map = HashMap::default();
-        let ephemeral = get_parent_ephemeral(&req.group, &req.parent, map, shared).await?;
-        // get a reference to pipeline data and request as a tuple
-        if let Some(pipeline) = pipe_cache.get(&pipe_key!(req)) {
-            // cast this request to a full reaction
-            match req.cast(user, pipeline, ephemeral, shared).await {
-                // we don't continue to track the index because any errors past this point
-                // can lead to malformed redis command pipelines and so are fatal. These
-                // errors should never occur though and when they are it likely means that
-                // all redis operations will fail.
-                Ok(cast) => casts.push(cast),
-                Err(error) => {
-                    // log this error
-                    event!(Level::ERROR, error = error.to_string());
-                    // add this erro to our response
-                    response.errors.insert(index, error.to_string());
+    // track the idempotency keys we claimed for reactions in this batch
+    let mut idempotent: HashMap<(String, String), Uuid> = HashMap::default();
+    // cast our requests and create them, releasing our claims if anything fails
+    let created = async {
+        // try to cast all of our requests to a reaction
+        for (index, req) in requests.into_iter().enumerate() {
+            // skip any requests whose key was already claimed earlier in this batch
+            if let Some(key) = req.idempotency_key.as_ref() {
+                if let Some(id) = idempotent.get(&(req.group_name().to_owned(), key.clone())) {
+                    response.deduplicated.insert(index, *id);
+                    continue;
+                }
+            }
+            // get any ephemeral files from any parent reactions
+            let map = HashMap::default();
+            let ephemeral =
+                get_parent_ephemeral(req.group_name(), &req.parent, map, shared).await?;
+            // get a reference to pipeline data and request as a tuple
+            if let Some(pipeline) = pipe_cache.get(&pipe_key!(req)) {
+                // cast this request to a full reaction
+                match req.cast(user, pipeline, ephemeral, shared).await {
+                    // we don't continue to track the index because any errors past this point
+                    // can lead to malformed redis command pipelines and so are fatal. These
+                    // errors should never occur though and when they are it likely means that
+                    // all redis operations will fail.
+                    Ok(cast) => {
+                        // claim this reactions idempotency key or skip it if its already claimed
+                        if let Some(key) = cast.0.idempotency_key.as_ref() {
+                            match claim_idempotent(&cast.0.group, key, &cast.0.id, shared).await {
+                                Ok(None) => {
+                                    idempotent
+                                        .insert((cast.0.group.clone(), key.clone()), cast.0.id);
+                                }
+                                Ok(Some(id)) => {
+                                    response.deduplicated.insert(index, id);
+                                    continue;
+                                }
+                                Err(error) => {
+                                    response.errors.insert(index, error.to_string());
+                                    continue;
+                                }
+                            }
+                        }
+                        casts.push(cast);
+                    }
+                    Err(error) => {
+                        // log this error
+                        event!(Level::ERROR, error = error.to_string());
+                        // add this erro to our response
+                        response.errors.insert(index, error.to_string());
+                    }
                 }
             }
         }
+        // build and run a pipeline to create all of our reactions
+        let mut pipe = redis::pipe();
+        // add the commands to create all of these reactions to our redis pipeline
+        for (cast, cache, pipeline) in casts {
+            // add this reaction to our redis pipeline
+            let (reaction, _) = build(&mut pipe, cast, cache, pipeline, shared).await?;
+            // add this newly created reactions id to our response object
+            response.created.push(reaction.id);
+        }
+        // create all reactions along with their jobs in redis
+        let _: () = pipe.atomic().query_async(conn!(shared)).await?;
+        Ok::<(), ApiError>(())
     }
-    // build all reactions
-    let mut pipe = redis::pipe();
-    // add the commands to create all of these reactions to our redis pipeline
-    for (cast, cache, pipeline) in casts {
-        // add this reaction to our redis pipeline
-        let (reaction, _) = build(&mut pipe, cast, cache, pipeline, shared).await?;
-        // add this newly created reactions id to our response object
-        response.created.push(reaction.id)
+    .await;
+    if let Err(error) = created {
+        // release our claims so a retry can create these reactions
+        release_claims(&idempotent, shared).await;
+        return Err(error);
     }
-    // create all reactions along with their jobs in redis
-    let _: () = pipe.atomic().query_async(conn!(shared)).await?;
     // log how many reactions were created and how many ran into errors
     event!(
         Level::INFO,
         created = response.created.len(),
+        deduplicated = response.deduplicated.len(),
         errors = response.errors.len()
     );
     Ok(response)
 }
 
+/// Atomically claim an idempotency key for a new reaction
+///
+/// Claims expire after the configured idempotency key retention. Returns the id of the
+/// reaction that already claimed this key if one did. Claims whose reaction has since
+/// expired or been deleted are stale and are taken over by our new reaction instead.
+///
+/// # Arguments
+///
+/// * `group` - The group the reaction is being created in
+/// * `key` - The idempotency key to claim
+/// * `id` - The id of the reaction being created
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::reactions::claim_idempotent", skip(shared), err(Debug))]
+pub async fn claim_idempotent(
+    group: &str,
+    key: &str,
+    id: &Uuid,
+    shared: &Shared,
+) -> Result<Option<Uuid>, ApiError> {
+    // claim this key unless another reaction already has
+    let script = redis::Script::new(
+        r"
+        local existing = redis.call('get', KEYS[1]);
+        if existing then
+            return existing;
+        end
+        redis.call('set', KEYS[1], ARGV[1], 'EX', ARGV[2]);
+        return false;",
+    );
+    let existing: Option<String> = script
+        .key(ReactionKeys::idempotency(group, key, shared))
+        .arg(format!("{IDEMPOTENCY_PENDING}{id}"))
+        .arg(shared.config.thorium.retention.idempotency_keys)
+        .invoke_async(conn!(shared))
+        .await?;
+    // cast the id of the reaction that claimed this key to a uuid
+    let existing = match existing {
+        // the reaction that claimed this key is still being created
+        Some(existing) if existing.starts_with(IDEMPOTENCY_PENDING) => {
+            return conflict!(format!(
+                "A reaction with the idempotency key '{key}' is still being created"
+            ));
+        }
+        Some(existing) => Uuid::parse_str(&existing)?,
+        None => return Ok(None),
+    };
+    // check if the reaction that claimed this key still exists
+    let exists: bool = query!(
+        cmd("exists").arg(ReactionKeys::data(group, &existing, shared)),
+        shared
+    )
+    .await?;
+    if exists {
+        return Ok(Some(existing));
+    }
+    // this claim is stale so take it over unless another reaction beat us to it
+    let script = redis::Script::new(
+        r"
+        if redis.call('get', KEYS[1]) == ARGV[1] then
+            redis.call('set', KEYS[1], ARGV[2], 'EX', ARGV[3]);
+            return 1;
+        end
+        return 0;",
+    );
+    let reclaimed: bool = script
+        .key(ReactionKeys::idempotency(group, key, shared))
+        .arg(existing.to_string())
+        .arg(format!("{IDEMPOTENCY_PENDING}{id}"))
+        .arg(shared.config.thorium.retention.idempotency_keys)
+        .invoke_async(conn!(shared))
+        .await?;
+    if reclaimed {
+        Ok(None)
+    } else {
+        conflict!(format!(
+            "A reaction with the idempotency key '{key}' is still being created"
+        ))
+    }
+}
+
+/// Release an idempotency key if it is still claimed by a specific reaction
+///
+/// # Arguments
+///
+/// * `group` - The group the reaction is in
+/// * `key` - The idempotency key to release
+/// * `id` - The id of the reaction that claimed this key
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::reactions::release_idempotent", skip(shared), err(Debug))]
+pub async fn release_idempotent(
+    group: &str,
+    key: &str,
+    id: &Uuid,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // only delete this key if it wasn't reclaimed by another reaction after it expired
+    let script = redis::Script::new(
+        r"
+        local existing = redis.call('get', KEYS[1]);
+        if existing == ARGV[1] or existing == ARGV[2] then
+            redis.call('del', KEYS[1]);
+        end
+        return 0;",
+    );
+    let _: i64 = script
+        .key(ReactionKeys::idempotency(group, key, shared))
+        .arg(id.to_string())
+        .arg(format!("{IDEMPOTENCY_PENDING}{id}"))
+        .invoke_async(conn!(shared))
+        .await?;
+    Ok(())
+}
+
+/// Release the idempotency keys claimed by reactions that failed to be created
+///
+/// Every claim is released even if releasing an earlier one fails.
+///
+/// # Arguments
+///
+/// * `claims` - The reaction ids that claimed each group and idempotency key
+/// * `shared` - Shared Thorium objects
+async fn release_claims(claims: &HashMap<(String, String), Uuid>, shared: &Shared) {
+    // keep a list of the claims we failed to release
+    let mut failed = Vec::new();
+    for ((group, key), id) in claims {
+        if let Err(error) = release_idempotent(group, key, id, shared).await {
+            failed.push(format!("{group}:{key}: {error}"));
+        }
+    }
+    // these claims will expire on their own so just log them
+    if !failed.is_empty() {
+        event!(
+            Level::ERROR,
+            msg = "Failed to release idempotency keys",
+            failed = failed.join(", ")
+        );
+    }
+}
+
 /// Gets a reaction from the backend
 ///
 /// # Arguments
@@ -413,6 +627,45 @@ pub async fn list_tag(
     }
 }
 
+/// Lists reaction ids that have all of the specified tags
+///
+/// This crawls the set for the first tag and filters out any reactions that are missing the
+/// rest of our tags.
+///
+/// # Arguments
+///
+/// * `group` - The group to list reactions from
+/// * `tags` - The tags reactions must have
+/// * `cursor` - The cursor to use when paging through reactions
+/// * `limit` - The number of reactions to try and return (weakly enforced)
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+pub async fn list_tags(
+    group: &str,
+    tags: &[String],
+    cursor: usize,
+    limit: usize,
+    shared: &Shared,
+) -> Result<ReactionList, ApiError> {
+    // crawl the reactions with our first tag
+    let mut list = list_tag(group, &tags[0], cursor, limit, shared).await?;
+    // filter out reactions without the rest of our tags
+    if tags.len() > 1 && !list.names.is_empty() {
+        // check if each reaction is in each of our other tag sets
+        let mut pipe = redis::pipe();
+        for name in &list.names {
+            for tag in &tags[1..] {
+                pipe.cmd("sismember").arg(ReactionKeys::tag(group, tag, shared)).arg(name);
+            }
+        }
+        let members: Vec<bool> = pipe.query_async(conn!(shared)).await?;
+        // only keep reactions that are members of all of our other tag sets
+        let mut members = members.chunks(tags.len() - 1);
+        list.names.retain(|_| members.next().is_some_and(|chunk| chunk.iter().all(|is_member| *is_member)));
+    }
+    Ok(list)
+}
+
 /// Lists reaction ids in the group wide status sorted set
 ///
 /// # Arguments
@@ -724,8 +977,8 @@ pub async fn complete(
     let dest = ReactionKeys::status(&reaction.group, &reaction.pipeline, &reaction.status, shared);
     // push in our expire orders
     let pipe = build_expire(pipe, &reaction, &keys, &dest, shared);
-    // get the timestamp for this reactions sla
-    let timestamp = reaction.sla.timestamp();
+    // get the score to order this reaction by in its group
+    let timestamp = reaction.group_score();
     // get our reaction id as a string
     let reaction_id = reaction.id.to_string();
     // update reaction
@@ -801,7 +1054,9 @@ pub async fn react(
         // calculate cost to execute this job
         let cost = *next.get(index).unwrap_or(&600.0);
         // get the timestamp we need to start this job by in order to meet the SLA
-        let deadline = reaction.sla - chrono::Duration::seconds((cost + rest).ceil() as i64);
+        // higher priority reactions get earlier deadlines so they are scheduled first
+        let deadline = reaction.sla - chrono::Duration::seconds((cost + rest).ceil() as i64)
+            - chrono::Duration::seconds(reaction.priority_offset());
         // build a raw job object for this stage
         let cast: RawJob = RawJob::build(&reaction, sub, deadline, &info).await?;
         // add job build command onto our redis pipeline
@@ -995,8 +1250,8 @@ pub async fn fail(
     let pipe = build_expire(&mut pipe, &reaction, &keys, &dest, shared);
     // get our reaction id as a string
     let reaction_id = reaction.id.to_string();
-    // get the timestamp for this reactions sla
-    let timestamp = reaction.sla.timestamp();
+    // get the score to order this reaction by in its group
+    let timestamp = reaction.group_score();
     pipe.cmd("hset").arg(&keys.data).arg("status").arg(serialize!(&reaction.status))
         // move from src status to failed status set
         .cmd("srem").arg(src).arg(&reaction_id)
@@ -1024,6 +1279,21 @@ pub async fn fail(
     Ok(ReactionStatus::Failed)
 }
 
+/// Appends status logs to a reaction in the order they were given
+///
+/// # Arguments
+///
+/// * `updates` - The status updates to append
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::reactions::add_status_logs", skip_all, fields(count = updates.len()), err(Debug))]
+pub async fn add_status_logs(updates: &[StatusUpdate], shared: &Shared) -> Result<(), ApiError> {
+    // push all of our updates in a single atomic pipeline so they stay in order
+    let mut pipe = redis::pipe();
+    super::logs::build(&mut pipe, updates, shared)?;
+    let _: () = pipe.atomic().query_async(conn!(shared)).await?;
+    Ok(())
+}
+
 /// Saves stage logs into scylla
 ///
 /// # Arguments
@@ -1258,6 +1528,10 @@ pub async fn delete(reaction: &Reaction, shared: &Shared) -> Result<(), ApiError
                     .arg(&reaction_id)
         });
 
+    // release our idempotency key if we still hold it
+    if let Some(idempotency_key) = reaction.idempotency_key.as_ref() {
+        release_idempotent(&reaction.group, idempotency_key, &reaction.id, shared).await?;
+    }
     // execute pipeline to delete our reaction specific data
     let _: () = pipe.atomic()
         .cmd("del").arg(&keys.data)
@@ -1389,6 +1663,57 @@ pub async fn update(
     Ok(())
 }
 
+/// Saves new samples for a reaction
+///
+/// # Arguments
+///
+/// * `reaction` - The reaction with its new samples and tags already applied
+/// * `added` - The sha256s of the samples that were added
+/// * `update_jobs` - Whether to also add these samples to jobs that have not started yet
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::reactions::add_samples", skip_all, fields(count = added.len()), err(Debug))]
+pub async fn add_samples(
+    reaction: &Reaction,
+    added: &[String],
+    update_jobs: bool,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // build reaction data keys
+    let keys = ReactionKeys::new(reaction, shared);
+    // get our reaction id as a string
+    let reaction_id = reaction.id.to_string();
+    // build pipeline to update our reactions samples and tags
+    let mut pipe = redis::pipe();
+    pipe.cmd("hset").arg(&keys.data).arg("samples").arg(serialize!(&reaction.samples))
+        .cmd("hset").arg(&keys.data).arg("tags").arg(serialize!(&reaction.tags));
+    // list this reaction under each new samples sha256 tag
+    added.iter()
+        .fold(&mut pipe, |pipe, sha256|
+            pipe.cmd("sadd").arg(ReactionKeys::tag(&reaction.group, sha256, shared))
+                .arg(&reaction_id));
+    // add our samples to any jobs that haven't been claimed by an agent yet
+    if update_jobs && !reaction.jobs.is_empty() {
+        // get the current status of all of this reactions jobs
+        let mut status_pipe = redis::pipe();
+        reaction.jobs.iter()
+            .fold(&mut status_pipe, |pipe, id|
+                pipe.cmd("hget").arg(JobKeys::data(id, shared)).arg("status"));
+        let statuses: Vec<Option<String>> = status_pipe.query_async(conn!(shared)).await?;
+        // jobs store the full list of samples to download
+        let created = serialize!(&JobStatus::Created);
+        let samples = serialize!(&reaction.samples);
+        for (id, status) in reaction.jobs.iter().zip(statuses) {
+            if status.as_ref() == Some(&created) {
+                pipe.cmd("hset").arg(JobKeys::data(id, shared)).arg("samples").arg(&samples);
+            }
+        }
+    }
+    // execute pipeline updating this reactions samples
+    let _: () = pipe.atomic().query_async(conn!(shared)).await?;
+    Ok(())
+}
+
 /// Get a reactions cache
 ///
 /// # Arguments
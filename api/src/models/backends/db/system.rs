@@ -1,5 +1,6 @@
 use bb8_redis::redis::cmd;
 use chrono::prelude::*;
+use clap::ValueEnum;
 use futures::stream::{self, StreamExt};
 use std::collections::{HashMap, HashSet};
 use tracing::{event, instrument, Level};
@@ -16,6 +17,7 @@ use crate::models::{
     NodeListParams, NodeRegistration, NodeRow, NodeUpdate, ScalerStats, SystemInfo, SystemSettings,
     SystemStats, User, Worker, WorkerDeleteMap, WorkerRegistrationList, WorkerUpdate,
 };
+use crate::utils::metrics::QueueDepth;
 use crate::utils::{ApiError, Shared};
 use crate::{
     conn, deserialize, exec_query, internal_err, log_scylla_err, not_found, query, serialize,
@@ -194,6 +196,35 @@ pub async fn get_stats(
     })
 }
 
+/// Gets the number of queued and running jobs for each scaler from redis
+///
+/// # Arguments
+///
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+pub async fn queue_depths(shared: &Shared) -> Result<Vec<QueueDepth>, ApiError> {
+    // get every scaler we can have jobs for
+    let scalers = ImageScaler::value_variants();
+    // count the deadlines and running jobs for each scaler
+    let mut pipe = redis::pipe();
+    for scaler in scalers {
+        pipe.cmd("zcard").arg(StreamKeys::system_scaler(*scaler, "deadlines", shared))
+            .cmd("zcard").arg(StreamKeys::system_scaler(*scaler, "running", shared));
+    }
+    let counts: Vec<i64> = pipe.query_async(conn!(shared)).await?;
+    // pair our counts with their scalers
+    let depths = scalers
+        .iter()
+        .zip(counts.chunks_exact(2))
+        .map(|(scaler, counts)| QueueDepth {
+            scaler: *scaler,
+            queued: counts[0],
+            running: counts[1],
+        })
+        .collect();
+    Ok(depths)
+}
+
 /// Resets the [`SystemSettings`] in redis
 ///
 /// # Arguments
@@ -221,6 +252,40 @@ pub async fn reset_settings(shared: &Shared) -> Result<(), ApiError> {
     Ok(())
 }
 
+/// Sets the default [`SystemSettings`] in redis only if they haven't been set yet
+///
+/// Each setting is only set if it doesn't already exist in a single transaction so
+/// multiple API instances starting for the first time can't clobber each other.
+///
+/// Returns true if this call wrote the default settings.
+///
+/// # Arguments
+///
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::system::init_settings", skip_all, err(Debug))]
+pub async fn init_settings(shared: &Shared) -> Result<bool, ApiError> {
+    // build system keys
+    let keys = SystemKeys::new(shared);
+    // build default settings
+    let default = SystemSettings::default();
+    // set any system settings that don't already exist
+    let written: Vec<bool> = redis::pipe()
+        .atomic()
+        .cmd("hsetnx").arg(&keys.settings).arg("reserved_cpu").arg(default.reserved_cpu)
+        .cmd("hsetnx").arg(&keys.settings).arg("reserved_memory").arg(default.reserved_memory)
+        .cmd("hsetnx").arg(&keys.settings).arg("reserved_storage").arg(default.reserved_storage)
+        .cmd("hsetnx").arg(&keys.settings).arg("fairshare_cpu").arg(default.fairshare_cpu)
+        .cmd("hsetnx").arg(&keys.settings).arg("fairshare_memory").arg(default.fairshare_memory)
+        .cmd("hsetnx").arg(&keys.settings).arg("fairshare_storage").arg(default.fairshare_storage)
+        .cmd("hsetnx").arg(&keys.settings).arg("host_path_whitelist").arg(serialize!(&default.host_path_whitelist))
+        .cmd("hsetnx").arg(&keys.settings).arg("allow_unrestricted_host_paths").arg(serialize!(&default.allow_unrestricted_host_paths))
+        .query_async(conn!(shared))
+        .await?;
+    // since this is a transaction only the first caller will have written anything
+    Ok(written.into_iter().any(|set| set))
+}
+
 /// Cast a [`HashMap`] into a [`SystemSettings`] object
 ///
 /// # Arguments
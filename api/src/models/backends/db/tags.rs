@@ -5,13 +5,15 @@
 //! timestamp each tag should be uploaded at.
 
 use chrono::prelude::*;
+use futures::stream::{self, StreamExt};
 use std::collections::{HashMap, HashSet};
 use tracing::{event, instrument, Level};
 
 use super::keys::tags;
 use crate::models::backends::TagSupport;
 use crate::models::{
-    Event, FullTagRow, TagDeleteRequest, TagMap, TagRequest, TagRow, TagSearchEvent, TagType, User,
+    BulkTagResponse, Event, FullTagRow, TagDeleteRequest, TagMap, TagRequest, TagRow,
+    TagSearchEvent, TagType, User,
 };
 use crate::utils::{helpers, ApiError, Shared};
 use crate::{bad, conn, internal_err, log_scylla_err};
@@ -278,6 +280,45 @@ pub async fn create_owned<T: TagSupport>(
     Ok(())
 }
 
+/// Save new tags for many items into scylla at once
+///
+/// Each item is written with [`create_owned`] and any item that fails to be
+/// tagged is added to the response's errors instead of failing the others.
+///
+/// # Arguments
+///
+/// * `user` - The user that is creating tags
+/// * `items` - The key, tag request, and earliest info for each item we are tagging
+/// * `response` - The response to add our tagged items and errors to
+/// * `shared` - Shared Thorium objects
+#[instrument(
+    name = "db::tags::create_bulk",
+    skip_all,
+    fields(kind = T::tag_kind().as_str(), items = items.len())
+)]
+pub async fn create_bulk<T: TagSupport>(
+    user: &User,
+    items: Vec<(String, TagRequest<T>, HashMap<String, DateTime<Utc>>)>,
+    response: &mut BulkTagResponse,
+    shared: &Shared,
+) {
+    // build the writes for each of our items tags
+    let writes = items.into_iter().map(|(key, req, earliest)| async move {
+        let result = create_owned(user, key.clone(), req, &earliest, shared).await;
+        (key, result)
+    });
+    // write our items concurrently and track which ones failed
+    let mut results = stream::iter(writes).buffer_unordered(50);
+    while let Some((key, result)) = results.next().await {
+        match result {
+            Ok(()) => response.tagged.push(key),
+            Err(error) => {
+                response.errors.insert(key, error.to_string());
+            }
+        }
+    }
+}
+
 /// Get the full tag rows for some specific tags
 ///
 /// # Arguments
@@ -84,6 +84,10 @@ pub async fn create(cast: User, shared: &Shared) -> Result<User, ApiError> {
     build(&mut pipe, &cast, shared)?;
     // try to save user into redis
     let _: () = pipe.atomic().query_async(conn!(shared)).await?;
+    // analysts are listed in every group so drop our cached groups
+    if cast.role == UserRole::Analyst {
+        shared.groups.clear();
+    }
     Ok(cast)
 }
 
@@ -228,6 +232,8 @@ pub async fn restore(users: &[User], shared: &Shared) -> Result<(), ApiError> {
         .collect::<Result<Vec<()>, ApiError>>()?;
     // restore all user data
     let _: () = pipe.atomic().query_async(conn!(shared)).await?;
+    // drop our cached groups since their members may have changed
+    shared.groups.clear();
     Ok(())
 }
 
@@ -386,6 +392,8 @@ pub async fn save(user: &User, shared: &Shared) -> Result<(), ApiError> {
     let _: () = pipe.atomic()
         .query_async(conn!(shared))
         .await?;
+    // this users role may have changed which changes the analysts in every group
+    shared.groups.clear();
     Ok(())
 }
 
@@ -515,6 +523,8 @@ pub async fn delete(user: &User, shared: &Shared) -> Result<(), ApiError> {
     build_delete(&mut pipe, user, shared);
     // try to save user into redis
     let _: () = pipe.atomic().query_async(conn!(shared)).await?;
+    // drop our cached groups since this user was removed from them
+    shared.groups.clear();
     Ok(())
 }
 
@@ -4,38 +4,44 @@ use axum::extract::multipart::Field;
 use axum::extract::{FromRequestParts, Multipart};
 use axum::http::request::Parts;
 use chrono::{DateTime, Utc};
-use futures::stream::{self, StreamExt};
+use futures::future::Either;
+use futures::stream::{self, Stream, StreamExt};
 use scylla::errors::ExecutionError;
 use scylla::response::query_result::QueryResult;
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::str::FromStr;
-use tracing::instrument;
+use std::sync::Arc;
+use tracing::{Level, event, instrument};
 use uuid::Uuid;
 
 use super::db;
+use crate::conf::EntityAliases;
 use crate::models::backends::GraphicSupport;
 use crate::models::backends::db::{CursorCore, ScyllaCursor, ScyllaCursorSupport};
 use crate::models::entities::filesystem::FileSystemFolderEntity;
 use crate::models::entities::{EntityMetadata, EntityMetadataForm};
 use crate::models::{
     ApiCursor, AssociationKind, AssociationListOpts, AssociationRequest, AssociationTarget,
-    AssociationTargetColumn, CollectionEntity, Country, CriticalSector, DeviceEntity, Entity,
-    EntityForm, EntityKinds, EntityListLine, EntityListParams, EntityListRow,
-    EntityMetadataUpdateForm, EntityResponse, EntityRow, EntityUpdateForm, FileSystemEntity, Group,
-    GroupAllowAction, ListableAssociation, TagListRow, TagMap, TagType, TreeSupport, User,
-    VendorEntity,
+    AssociationTargetColumn, BulkEntityResponse, CollectionEntity, Country, CriticalSector,
+    DeviceEntity, Entity, EntityForm, EntityKinds, EntityListLine, EntityListParams, EntityListRow,
+    EntityMetadataRequest, EntityMetadataUpdateForm, EntityRequest, EntityResponse, EntityRow,
+    EntityUpdateForm, FileSystemEntity, Group, GroupAllowAction, ListableAssociation,
+    SoftwareEntity, TagListRow, TagMap, TagType, TreeSupport, User, VendorEntity,
 };
 use crate::utils::{ApiError, Shared};
 use crate::{
-    bad, bad_internal, deserialize, ensure_empty_segment, ensure_segments_complete, for_groups,
-    internal_err, not_found, serialize, tag, unauthorized, update, update_add_rem,
+    bad, bad_internal, conflict, deserialize, ensure_empty_segment, ensure_segments_complete,
+    for_groups, internal_err, not_found, serialize, tag, unauthorized, update, update_add_rem,
     update_clear_opt, update_opt,
 };
 
 mod collections;
 mod devices;
+mod duplicates;
+mod graph;
 
 impl Entity {
     /// A helper function for creating an entity by taking a form, validating
@@ -60,10 +66,12 @@ impl Entity {
         let entity_id = Uuid::new_v4();
         // build an entity form to populate
         let mut entity_form = EntityForm::default();
+        // get the aliases to accept for metadata values
+        let aliases = &shared.config.thorium.entities.aliases;
         // crawl the multipart form
         while let Some(field) = form.next_field().await? {
             // try to consume the field
-            if let Some(image_field) = entity_form.add(field).await? {
+            if let Some(image_field) = entity_form.add(field, aliases).await? {
                 // get the base path for this entity
                 let base_path = Self::build_graphic_base_path(&entity_id);
                 // upload the graphic to S3
@@ -74,7 +82,9 @@ impl Entity {
                 entity_form.image = Some(path);
             }
         }
-        // first, make sure we actually have edit access in all requested groups
+        // make sure all required fields were set and the data in the finished form is valid
+        entity_form.validate()?;
+        // make sure we actually have edit access in all requested groups
         let _ = Group::authorize_check_allow_all(
             user,
             &entity_form.groups,
@@ -84,8 +94,6 @@ impl Entity {
             shared,
         )
         .await?;
-        // make sure the data in the finished form is valid
-        entity_form.validate()?;
         // create the entity
         db::entities::create(user, entity_form, entity_id, shared).await?;
         // the entity was created successfully
@@ -132,6 +140,94 @@ impl Entity {
         }
     }
 
+    /// Create a single entity from a JSON request in a bulk create
+    ///
+    /// The groups for this entity must have already been authorized.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The request to create an entity from
+    /// * `user` - The user creating the entity
+    /// * `shared` - Shared Thorium objects
+    async fn create_bulk_helper(
+        req: EntityRequest,
+        user: &User,
+        shared: &Shared,
+    ) -> Result<Uuid, ApiError> {
+        // generate a UUID for this entity
+        let entity_id = Uuid::new_v4();
+        // convert our request to a form
+        let entity_form = EntityForm::from_request(req, &shared.config.thorium.entities.aliases)?;
+        // make sure all required fields were set and the data in the finished form is valid
+        entity_form.validate()?;
+        // create the entity
+        db::entities::create(user, entity_form, entity_id, shared).await?;
+        Ok(entity_id)
+    }
+
+    /// Create many `Entity`s in the db from JSON requests
+    ///
+    /// Entities with images must be created with the multipart create route instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user creating the entities
+    /// * `requests` - The requests to create entities for
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "Entity::create_bulk", skip_all, err(Debug))]
+    pub async fn create_bulk(
+        user: &User,
+        requests: Vec<EntityRequest>,
+        shared: &Shared,
+    ) -> Result<BulkEntityResponse, ApiError> {
+        // make sure this request isn't too large before we do any work
+        let limit = shared.config.thorium.request_size_limits.bulk_entities;
+        if requests.len() > limit {
+            return bad!(format!(
+                "Unable to create {} entities in one request! The max is {limit}.",
+                requests.len()
+            ));
+        }
+        // get all of the groups we are creating entities in
+        let groups = requests
+            .iter()
+            .flat_map(|req| req.groups.iter())
+            .filter(|group| !group.is_empty())
+            .cloned()
+            .collect::<HashSet<String>>()
+            .into_iter()
+            .collect::<Vec<String>>();
+        // make sure we have edit access in all requested groups before creating anything
+        if !groups.is_empty() {
+            Group::authorize_check_allow_all(
+                user,
+                &groups,
+                Group::editable,
+                "edit",
+                Some(GroupAllowAction::Entities),
+                shared,
+            )
+            .await?;
+        }
+        // build the response to return
+        let mut response = BulkEntityResponse::with_capacity(requests.len());
+        // create each of our entities
+        for (index, req) in requests.into_iter().enumerate() {
+            match Self::create_bulk_helper(req, user, shared).await {
+                Ok(id) => {
+                    response.created.insert(index, id);
+                }
+                Err(error) => {
+                    // log this error
+                    event!(Level::ERROR, error = error.to_string());
+                    // add this error to our response
+                    response.errors.insert(index, error.to_string());
+                }
+            }
+        }
+        Ok(response)
+    }
+
     pub fn populate_intrinsic_tags(&self, tags: &mut HashMap<String, HashSet<String>>) {
         match &self.metadata {
             EntityMetadata::Device(device) => {
@@ -146,6 +242,12 @@ impl Entity {
                     tag!(tags, "CriticalSectors", sector.to_string());
                 }
             }
+            EntityMetadata::Software(software) => {
+                // add this softwares cpe to tags if it has one
+                if let Some(cpe) = &software.cpe {
+                    tag!(tags, "Cpe", cpe.clone());
+                }
+            }
             EntityMetadata::Collection(_) => (),
             EntityMetadata::FileSystem(fs) => {
                 tag!(tags, "FsSha256", fs.sha256.clone());
@@ -179,32 +281,12 @@ impl Entity {
             // based on our kind populate any association data
             match &mut self.metadata {
                 EntityMetadata::Device(metadata) => {
-                    // build a set of entities associated with our entity
-                    let mut ids = Vec::with_capacity(3);
-                    // get only the vendor associations
-                    loop {
-                        // filter only to developed by associations
-                        for association in cursor.data.drain(..) {
-                            // skip any associations that are not developed by
-                            if association.kind == AssociationKind::DevelopedBy {
-                                // parse our other value for this association
-                                let other: AssociationTargetColumn =
-                                    deserialize!(&association.other);
-                                // add any entity ids we find to our list
-                                if let AssociationTargetColumn::Entity(id) = other {
-                                    ids.push(id);
-                                }
-                            }
-                        }
-                        // if our cursor is exhausted then stop looping
-                        if cursor.exhausted() {
-                            break;
-                        }
-                        // get the next page of associations
-                        cursor.next(shared).await?;
-                    }
-                    // get all of the entities we found
-                    metadata.vendors = db::entities::get_many(&user.groups, &ids, shared).await?;
+                    // get the vendors that developed this device
+                    metadata.vendors = Self::get_developers(&mut cursor, user, shared).await?;
+                }
+                EntityMetadata::Software(metadata) => {
+                    // get the vendors that developed this software
+                    metadata.vendors = Self::get_developers(&mut cursor, user, shared).await?;
                 }
                 // vendor/collection/other has no data that we need to retrieve
                 EntityMetadata::Vendor(_)
@@ -217,6 +299,45 @@ impl Entity {
         Ok(self)
     }
 
+    /// Get the entities that developed an entity from its associations
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor over this entities associations
+    /// * `user` - The user that is populating assocations for an entity
+    /// * `shared` - Shared thorium objects
+    async fn get_developers(
+        cursor: &mut ScyllaCursor<ListableAssociation>,
+        user: &User,
+        shared: &Shared,
+    ) -> Result<Vec<Entity>, ApiError> {
+        // build a set of entities associated with our entity
+        let mut ids = Vec::with_capacity(3);
+        // get only the vendor associations
+        loop {
+            // filter only to developed by associations
+            for association in cursor.data.drain(..) {
+                // skip any associations that are not developed by
+                if association.kind == AssociationKind::DevelopedBy {
+                    // parse our other value for this association
+                    let other: AssociationTargetColumn = deserialize!(&association.other);
+                    // add any entity ids we find to our list
+                    if let AssociationTargetColumn::Entity(id) = other {
+                        ids.push(id);
+                    }
+                }
+            }
+            // if our cursor is exhausted then stop looping
+            if cursor.exhausted() {
+                break;
+            }
+            // get the next page of associations
+            cursor.next(shared).await?;
+        }
+        // get all of the entities we found
+        db::entities::get_many(&user.groups, &ids, shared).await
+    }
+
     /// Get an `Entity` from the db
     ///
     /// # Arguments
@@ -248,6 +369,19 @@ impl Entity {
         dedupe: bool,
         shared: &Shared,
     ) -> Result<ApiCursor<EntityListLine>, ApiError> {
+        // only admins can list entities created by other users
+        if params
+            .submitter
+            .as_ref()
+            .is_some_and(|submitter| *submitter != user.username)
+            && !user.is_admin()
+        {
+            return unauthorized!(
+                "Only admins can list entities created by other users".to_owned()
+            );
+        }
+        // make sure this user is allowed to use any requested partition hint
+        db::check_partition_hint(params.partition_hint, user, shared)?;
         // authorize the groups to list entities from
         user.authorize_groups(&mut params.groups, shared).await?;
         // get or create a cursor over entities
@@ -331,6 +465,20 @@ impl Entity {
                     .critical_sectors
                     .retain(|sector| !form.remove_critical_sectors.contains(sector));
             }
+            // update our software info
+            EntityMetadata::Software(software) => {
+                // update our version if a new one was set
+                if let Some(version) = form.version.take().filter(|version| !version.is_empty()) {
+                    software.version = version;
+                }
+                // make sure any new cpe looks like a cpe
+                if let Some(cpe) = &form.cpe {
+                    SoftwareEntity::validate_cpe(cpe)?;
+                }
+                // update or clear our cpe
+                update_opt!(software.cpe, form.cpe);
+                update_clear_opt!(software.cpe, form.clear_cpe);
+            }
             // update collection info
             EntityMetadata::Collection(collection) => {
                 collection.update(&mut form)?;
@@ -368,16 +516,23 @@ impl Entity {
     ) -> Result<(), ApiError> {
         // build an entity update form to populate
         let mut update_form = EntityUpdateForm::default();
+        // get the aliases to accept for metadata values
+        let aliases = &shared.config.thorium.entities.aliases;
         // track any images we need to delete
         let mut deletes = vec![];
         // crawl the multipart form
         while let Some(field) = form.next_field().await? {
             // try to consume the field
-            if let Some(image_field) = update_form.add(field).await? {
+            if let Some(image_field) = update_form.add(field, aliases).await? {
                 // get the base path for this entity
                 let base_path = Self::build_graphic_base_path_from_self(&self);
+                // give this upload a unique name so concurrent updates can never overwrite
+                // or clean up each others images
+                let name = image_field
+                    .file_name()
+                    .map(|name| format!("{}-{name}", Uuid::new_v4()));
                 // upload the graphic to S3
-                let path = Self::upload_graphic(base_path, image_field, None, shared).await?;
+                let path = Self::upload_graphic(base_path, image_field, name, shared).await?;
                 // set our new image
                 if let Some(old_path) = self.image.replace(path.clone()) {
                     // add our old path to the list of images to delete
@@ -408,6 +563,23 @@ impl Entity {
                 deletes.push(image_path);
             }
         }
+        // reject this update if it was built from an older version of this entity
+        if let Some(version) = update_form
+            .version
+            .filter(|version| *version != self.version)
+        {
+            return conflict!(format!(
+                "Entity {} is at version {} but this update is for version {version}",
+                self.id, self.version
+            ));
+        }
+        // claim the next version of this entity so any concurrent updates are rejected
+        if !db::entities::claim_version(&self.id, self.version, shared).await? {
+            return conflict!(format!(
+                "Entity {} was updated by another request; please retry this update",
+                self.id
+            ));
+        }
         // update this entity
         db::entities::update(
             user,
@@ -420,7 +592,7 @@ impl Entity {
         // delete any graphics that are no longer needed
         stream::iter(deletes)
             .map(|key| async move { Self::delete_graphic(&key, shared).await })
-            .buffer_unordered(10)
+            .buffer_unordered(shared.config.thorium.entities.graphic_delete_buffer.get())
             .collect::<Vec<Result<(), ApiError>>>()
             .await
             .into_iter()
@@ -481,10 +653,11 @@ impl Entity {
     ///
     /// * `shared` - Shared Thorium objects
     pub(crate) async fn delete_associations(&self, shared: &Shared) -> Result<(), ApiError> {
-        // build the associations list opts for this entity
+        // build the associations list opts for this entity including any archived associations
         let opts = AssociationListOpts::default()
             .groups(self.groups.clone())
-            .limit(500);
+            .limit(500)
+            .include_archived();
         // build the source target for this entity if we have one
         if let Some(source) = self.build_association_target_column() {
             // list all associations for this entity
@@ -506,14 +679,53 @@ impl Entity {
         Ok(())
     }
 
-    /// Delete an `Entity`
+    /// Archive or restore all associations for this entity
     ///
     /// # Arguments
     ///
-    /// * `update` - The update to apply
-    /// * `user` - The user updating the entity
+    /// * `archived_at` - When these associations were archived or None to restore them
+    /// * `shared` - Shared Thorium objects
+    async fn archive_associations(
+        &self,
+        archived_at: Option<DateTime<Utc>>,
+        shared: &Shared,
+    ) -> Result<(), ApiError> {
+        // build the associations list opts for this entity including any archived associations
+        let opts = AssociationListOpts::default()
+            .groups(self.groups.clone())
+            .limit(500)
+            .include_archived();
+        // build the source target for this entity if we have one
+        if let Some(source) = self.build_association_target_column() {
+            // list all associations for this entity
+            let mut cursor = db::associations::list(opts, &source, shared).await?;
+            loop {
+                // only update the associations that aren't already in the state we want
+                cursor
+                    .data
+                    .retain(|assoc| assoc.archived_at.is_some() != archived_at.is_some());
+                // archive or restore this page of associations
+                db::associations::archive_many(&source, &cursor.data, archived_at, shared).await?;
+                // check if this cursor has been exhausted
+                if cursor.exhausted() {
+                    break;
+                }
+                // clear our current cursor
+                cursor.data.clear();
+                // get the next page of data
+                cursor.next(shared).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Make sure a user can delete, archive, or restore this entity
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is modifying this entity
     /// * `shared` - Shared Thorium objects
-    pub async fn delete(self, user: &User, shared: &Shared) -> Result<(), ApiError> {
+    async fn authorize_delete(&self, user: &User, shared: &Shared) -> Result<(), ApiError> {
         // if we are the owner of this entity then we can delete it from all groups
         if self.submitter != user.username && !user.is_admin() {
             // we are not the owner so we can only delete this from groups we are a manager for
@@ -527,7 +739,61 @@ impl Entity {
                     return unauthorized!(format!("Cannot delete data from group {}", group.name));
                 }
             }
-        };
+        }
+        Ok(())
+    }
+
+    /// Archive an `Entity` so it is hidden from listings without losing its history
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user archiving the entity
+    /// * `shared` - Shared Thorium objects
+    pub async fn archive(self, user: &User, shared: &Shared) -> Result<(), ApiError> {
+        // make sure we can archive this entity
+        self.authorize_delete(user, shared).await?;
+        // make sure this entity isn't already archived
+        if self.archived_at.is_some() {
+            return conflict!(format!("Entity {} is already archived", self.id));
+        }
+        // get the time we are archiving this entity at
+        let now = Utc::now();
+        // archive any associations for this entity
+        self.archive_associations(Some(now), shared).await?;
+        // archive the entity
+        db::entities::set_archived(&self.id, Some(now), shared).await
+    }
+
+    /// Restore an archived `Entity`
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user restoring the entity
+    /// * `shared` - Shared Thorium objects
+    pub async fn restore(self, user: &User, shared: &Shared) -> Result<(), ApiError> {
+        // make sure we can restore this entity
+        self.authorize_delete(user, shared).await?;
+        // make sure this entity is actually archived
+        if self.archived_at.is_none() {
+            return bad!(format!("Entity {} is not archived", self.id));
+        }
+        // restore any associations for this entity
+        self.archive_associations(None, shared).await?;
+        // restore the entity
+        db::entities::set_archived(&self.id, None, shared).await
+    }
+
+    /// Permanently delete an `Entity` and its associations
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user purging the entity
+    /// * `shared` - Shared Thorium objects
+    pub async fn purge(self, user: &User, shared: &Shared) -> Result<(), ApiError> {
+        // only admins can permanently delete entities
+        if !user.is_admin() {
+            return unauthorized!("Only admins can purge entities".to_owned());
+        }
         // remove any associations for this entity
         self.delete_associations(shared).await?;
         // delete the entity
@@ -631,6 +897,8 @@ impl Entity {
         match &mut self.metadata {
             // vendors in devices is built by association
             EntityMetadata::Device(device) => device.vendors.clear(),
+            // vendors in software is built by association
+            EntityMetadata::Software(software) => software.vendors.clear(),
             // vendor/collection/other has no association specific data
             EntityMetadata::Vendor(_)
             | EntityMetadata::Collection(_)
@@ -666,6 +934,7 @@ impl EntityMetadata {
         let data = match self {
             EntityMetadata::Device(device_entity) => Some(serialize!(device_entity)),
             EntityMetadata::Vendor(vendor_entity) => Some(serialize!(vendor_entity)),
+            EntityMetadata::Software(software_entity) => Some(serialize!(software_entity)),
             EntityMetadata::Collection(collection_entity) => Some(serialize!(collection_entity)),
             EntityMetadata::FileSystem(fs_entity) => Some(serialize!(fs_entity)),
             EntityMetadata::Folder(folder_entity) => Some(serialize!(folder_entity)),
@@ -676,6 +945,94 @@ impl EntityMetadata {
 }
 
 impl EntityForm {
+    /// Build an entity form from a JSON entity request
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the countries in a vendor request are invalid
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The request to convert
+    /// * `aliases` - The aliases to accept for metadata values
+    pub fn from_request(req: EntityRequest, aliases: &EntityAliases) -> Result<Self, ApiError> {
+        // convert our kind specific metadata
+        let (kind, metadata) = match req.metadata {
+            EntityMetadataRequest::Device(device) => (
+                EntityKinds::Device,
+                EntityMetadataForm {
+                    urls: device.urls,
+                    vendors: device.vendors,
+                    critical_system: device.critical_system,
+                    sensitive_location: device.sensitive_location,
+                    critical_sectors: device.critical_sectors,
+                    ..EntityMetadataForm::default()
+                },
+            ),
+            EntityMetadataRequest::Vendor(vendor) => (
+                EntityKinds::Vendor,
+                EntityMetadataForm {
+                    countries: vendor
+                        .countries
+                        .iter()
+                        .map(|code| Country::normalize(code, &aliases.countries))
+                        .collect::<Result<_, _>>()?,
+                    critical_sectors: vendor.critical_sectors,
+                    ..EntityMetadataForm::default()
+                },
+            ),
+            EntityMetadataRequest::Software(software) => (
+                EntityKinds::Software,
+                EntityMetadataForm {
+                    version: Some(software.version),
+                    cpe: software.cpe,
+                    vendors: software.vendors,
+                    ..EntityMetadataForm::default()
+                },
+            ),
+            EntityMetadataRequest::Collection(collection) => (
+                EntityKinds::Collection,
+                EntityMetadataForm {
+                    collection_kind: Some(collection.collection_kind),
+                    collection_tags: collection.collection_tags,
+                    collection_tags_case_insensitive: collection.tags_case_insensitive,
+                    collection_ignore_groups: collection.ignore_groups,
+                    collection_start: collection.start,
+                    collection_end: collection.end,
+                    ..EntityMetadataForm::default()
+                },
+            ),
+            EntityMetadataRequest::FileSystem(fs) => (
+                EntityKinds::FileSystem,
+                EntityMetadataForm {
+                    sha256: Some(fs.sha256),
+                    tools: fs.tools,
+                    ..EntityMetadataForm::default()
+                },
+            ),
+            EntityMetadataRequest::Folder(folder) => (
+                EntityKinds::Folder,
+                EntityMetadataForm {
+                    filesystem_id: Some(folder.filesystem_id),
+                    names_sha256: Some(folder.names_sha256),
+                    data_sha256: Some(folder.data_sha256),
+                    all_sha256: Some(folder.all_sha256),
+                    ..EntityMetadataForm::default()
+                },
+            ),
+            EntityMetadataRequest::Other => (EntityKinds::Other, EntityMetadataForm::default()),
+        };
+        Ok(EntityForm {
+            name: Some(req.name),
+            kind: Some(kind),
+            metadata,
+            groups: req.groups,
+            tags: req.tags,
+            description: req.description,
+            image: None,
+        })
+    }
+
     /// Adds a multipart field to our entity form
     ///
     /// # Returns
@@ -690,7 +1047,12 @@ impl EntityForm {
     /// # Arguments
     ///
     /// * `field` - The field to try to add
-    pub async fn add<'a>(&'a mut self, field: Field<'a>) -> Result<Option<Field<'a>>, ApiError> {
+    /// * `aliases` - The aliases to accept for metadata values
+    pub async fn add<'a>(
+        &'a mut self,
+        field: Field<'a>,
+        aliases: &EntityAliases,
+    ) -> Result<Option<Field<'a>>, ApiError> {
         // get the name of this field
         if let Some(name) = field.name().map(ToOwned::to_owned) {
             // iterate over the segments ('<NAME>[<KEY1>][<KEY2>]') in the field name
@@ -719,8 +1081,10 @@ impl EntityForm {
                     self.kind = Some(cast);
                 }
                 "metadata" => {
-                    name_segments_iter =
-                        self.metadata.add(field, &name, name_segments_iter).await?;
+                    name_segments_iter = self
+                        .metadata
+                        .add(field, &name, name_segments_iter, aliases)
+                        .await?;
                 }
                 // this could be a list field
                 maybe_list => {
@@ -769,8 +1133,8 @@ impl EntityForm {
         };
         // build the association request for each of our different entities
         match kind {
-            EntityKinds::Device => {
-                // devices to vendor relationships is always developed by
+            EntityKinds::Device | EntityKinds::Software => {
+                // device and software to vendor relationships are always developed by
                 let assoc_kind = AssociationKind::DevelopedBy;
                 // get the numer of associations to make
                 let vendor_len = self.metadata.vendors.len();
@@ -783,6 +1147,10 @@ impl EntityForm {
                 let mut req = AssociationRequest::with_capacity(assoc_kind, source, vendor_len);
                 // step over the vendors we are associating with this device
                 for vendor_id in self.metadata.vendors.drain(..) {
+                    // make sure we aren't associating this entity with itself
+                    if vendor_id == id {
+                        return bad!(format!("Entity {id} cannot be associated with itself!"));
+                    }
                     // get the entity for this vendor if it exists
                     let entity = Entity::get(user, vendor_id, shared).await?;
                     // make sure this is a vendor entity
@@ -797,6 +1165,8 @@ impl EntityForm {
                     // add this link to the other
                     req.targets.push(other);
                 }
+                // make sure we didn't list any vendors more than once
+                req.validate()?;
                 Ok(Some(req))
             }
             _ => Ok(None),
@@ -850,6 +1220,8 @@ impl EntityForm {
             created: Utc::now(),
             tags: HashMap::default(),
             image: self.image,
+            version: 0,
+            archived_at: None,
         };
         Ok(cast)
     }
@@ -867,11 +1239,13 @@ impl EntityMetadataForm {
     /// * `field` - The field to try to add
     /// * `name` - The name of the field to add
     /// * `name_segments` - An iterator over the segments of the field name
+    /// * `aliases` - The aliases to accept for metadata values
     pub async fn add<'a, I: Iterator<Item = &'a str>>(
         &'a mut self,
         field: Field<'a>,
         name: &str,
         mut name_segments: I,
+        aliases: &EntityAliases,
     ) -> Result<I, ApiError> {
         match name_segments.next().ok_or(bad_internal!(
             "Invalid entity metadata field: metadata field name is missing".to_string()
@@ -927,6 +1301,8 @@ impl EntityMetadataForm {
             "names_sha256" => self.names_sha256 = Some(field.text().await?),
             "data_sha256" => self.data_sha256 = Some(field.text().await?),
             "all_sha256" => self.all_sha256 = Some(field.text().await?),
+            "version" => self.version = Some(field.text().await?),
+            "cpe" => self.cpe = Some(field.text().await?),
             maybe_list => {
                 match maybe_list {
                     "urls" => {
@@ -940,13 +1316,14 @@ impl EntityMetadataForm {
                     }
                     "critical_sectors" => {
                         // try to convert this field to a critical sector
-                        let sector = CriticalSector::from_str(&field.text().await?)?;
+                        let sector =
+                            CriticalSector::normalize(&field.text().await?, &aliases.sectors)?;
                         // add this critical sector
                         self.critical_sectors.insert(sector);
                     }
                     "countries" => {
                         // validate and parse this country
-                        let country = Country::new(&field.text().await?)?;
+                        let country = Country::normalize(&field.text().await?, &aliases.countries)?;
                         // add this country to our metadata form
                         self.countries.insert(country);
                     }
@@ -994,6 +1371,7 @@ impl EntityMetadataForm {
                 DeviceEntity::from_form(self, groups, shared).await?,
             )),
             EntityKinds::Vendor => Ok(EntityMetadata::Vendor(VendorEntity::from_form(self))),
+            EntityKinds::Software => Ok(EntityMetadata::Software(SoftwareEntity::from_form(self)?)),
             EntityKinds::Collection => Ok(EntityMetadata::Collection(CollectionEntity::from_form(
                 self,
             )?)),
@@ -1023,7 +1401,12 @@ impl EntityUpdateForm {
     /// # Arguments
     ///
     /// * `field` - The field to try to add
-    pub async fn add<'a>(&'a mut self, field: Field<'a>) -> Result<Option<Field<'a>>, ApiError> {
+    /// * `aliases` - The aliases to accept for metadata values
+    pub async fn add<'a>(
+        &'a mut self,
+        field: Field<'a>,
+        aliases: &EntityAliases,
+    ) -> Result<Option<Field<'a>>, ApiError> {
         // get the name of this field
         if let Some(name) = field.name().map(ToOwned::to_owned) {
             // iterate over the segments ('<NAME>[<KEY1>][<KEY2>]') in the field name
@@ -1038,11 +1421,20 @@ impl EntityUpdateForm {
                 "clear_image" => self.clear_image = Some(field.text().await?.parse()?),
                 "description" => self.description = Some(field.text().await?),
                 "clear_description" => self.clear_description = Some(field.text().await?.parse()?),
+                "version" => {
+                    let raw = field.text().await?;
+                    self.version =
+                        Some(raw.parse().map_err(|_| {
+                            bad_internal!(format!("Invalid entity version '{raw}'"))
+                        })?);
+                }
                 // this is image data so return it so we can stream it to s3
                 "image" => return Ok(Some(field)),
                 "metadata" => {
-                    name_segments_iter =
-                        self.metadata.add(field, &name, name_segments_iter).await?;
+                    name_segments_iter = self
+                        .metadata
+                        .add(field, &name, name_segments_iter, aliases)
+                        .await?;
                 }
                 // this could be a list field
                 maybe_list => {
@@ -1087,17 +1479,17 @@ impl EntityUpdateForm {
             return bad!("One or more of the groups to remove has an empty name!".to_string());
         }
         // make sure all of the added groups exist
-        if !db::groups::exists(&self.add_groups, shared)
+        let missing = db::groups::exist_many(&self.add_groups, shared)
             .await
             .map_err(|err| {
                 ApiError::new(
                     err.code,
                     Some(format!("Unable to verify that added groups exist: {err}")),
                 )
-            })?
-        {
+            })?;
+        if !missing.is_empty() {
             return not_found!(format!(
-                "One or more of the specified groups to add doesn't exist!"
+                "One or more of the specified groups to add doesn't exist: {missing:?}"
             ));
         }
         update_add_rem!(
@@ -1129,11 +1521,13 @@ impl EntityMetadataUpdateForm {
     /// * `field` - The field to try to add
     /// * `name` - The full name of the field
     /// * `name_segments` - An iterator over the parsed segments from the field name
+    /// * `aliases` - The aliases to accept for metadata values
     pub async fn add<'a, I: Iterator<Item = &'a str>>(
         &'a mut self,
         field: Field<'a>,
         name: &str,
         mut name_segments: I,
+        aliases: &EntityAliases,
     ) -> Result<I, ApiError> {
         match name_segments.next().ok_or(bad_internal!(
             "Invalid entity metadata update field: metadata field name is missing".to_string()
@@ -1158,6 +1552,9 @@ impl EntityMetadataUpdateForm {
                     bad_internal!(format!("Invalid collection end datetime '{end_raw}'"))
                 })?);
             }
+            "version" => self.version = Some(field.text().await?),
+            "cpe" => self.cpe = Some(field.text().await?),
+            "clear_cpe" => self.clear_cpe = Some(field.text().await?.parse()?),
             "clear_collection_start" => self.clear_collection_start = Some(true),
             "clear_collection_end" => self.clear_collection_end = Some(true),
             "collection_tags_case_insensitive" => {
@@ -1183,22 +1580,27 @@ impl EntityMetadataUpdateForm {
                     "remove_urls" => self.remove_urls.push(field.text().await?),
                     "add_vendors" => self.add_vendors.push(field.text().await?.parse()?),
                     "remove_vendors" => self.remove_vendors.push(field.text().await?.parse()?),
+                    "add_related" => self.add_related.push(field.text().await?.parse()?),
+                    "remove_related" => self.remove_related.push(field.text().await?.parse()?),
                     "add_critical_sectors" => {
-                        self.add_critical_sectors.push(field.text().await?.parse()?);
+                        let sector =
+                            CriticalSector::normalize(&field.text().await?, &aliases.sectors)?;
+                        self.add_critical_sectors.push(sector);
                     }
                     "remove_critical_sectors" => {
-                        self.remove_critical_sectors
-                            .push(field.text().await?.parse()?);
+                        let sector =
+                            CriticalSector::normalize(&field.text().await?, &aliases.sectors)?;
+                        self.remove_critical_sectors.push(sector);
                     }
                     "add_countries" => {
                         // validate and parse this country
-                        let country = Country::new(&field.text().await?)?;
+                        let country = Country::normalize(&field.text().await?, &aliases.countries)?;
                         // add this country to our metadata form
                         self.add_countries.push(country);
                     }
                     "remove_countries" => {
                         // validate and parse this country
-                        let country = Country::new(&field.text().await?)?;
+                        let country = Country::normalize(&field.text().await?, &aliases.countries)?;
                         // add this country to our metadata form
                         self.remove_countries.push(country);
                     }
@@ -1245,6 +1647,100 @@ impl EntityMetadataUpdateForm {
         Ok(name_segments)
     }
 
+    /// Add or remove any symmetric related to associations in this form
+    ///
+    /// Related to associations are bidirectional so the rows for both entities are always
+    /// written or removed together.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is updating this entity
+    /// * `entity` - The entity that is being updated
+    /// * `shared` - Shared Thorium objects
+    async fn update_related(
+        &mut self,
+        user: &User,
+        entity: &Entity,
+        shared: &Shared,
+    ) -> Result<(), ApiError> {
+        // add any new related entities
+        if !self.add_related.is_empty() {
+            // build our source target
+            let source = AssociationTarget::Entity {
+                id: entity.id,
+                name: entity.name.clone(),
+            };
+            // related to associations are always symmetric
+            let mut req = AssociationRequest::with_capacity(
+                AssociationKind::RelatedTo,
+                source,
+                self.add_related.len(),
+            );
+            // step over the entities we are relating to this entity
+            for related_id in self.add_related.drain(..) {
+                // make sure we aren't relating this entity to itself
+                if related_id == entity.id {
+                    return bad!(format!(
+                        "Entity {related_id} cannot be associated with itself!"
+                    ));
+                }
+                // get the entity we are relating to if it exists
+                let other_entity = Entity::get(user, related_id, shared).await?;
+                // add this link to the other entity
+                req.targets.push(AssociationTarget::Entity {
+                    id: related_id,
+                    name: other_entity.name,
+                });
+            }
+            // create these associations
+            req.apply(user, shared).await?;
+        }
+        // remove any related entities
+        if !self.remove_related.is_empty() {
+            // convert our list of related entities to a list of serialized target columns
+            let serialized = self
+                .remove_related
+                .iter()
+                .filter_map(|id| {
+                    // this should never actually fail
+                    serde_json::to_string(&AssociationTargetColumn::Entity(*id)).ok()
+                })
+                .collect::<Vec<String>>();
+            // build our association target column
+            let Some(source) = entity.build_association_target_column() else {
+                // this should be impossible and never occur
+                return internal_err!(format!(
+                    "Failed to build association target column for {entity:#?}"
+                ));
+            };
+            // list this entities associations in all of its groups
+            let opts = AssociationListOpts::default()
+                .groups(entity.groups.clone())
+                .limit(100);
+            let mut cursor = db::associations::list(opts, &source, shared).await?;
+            // build a list of associations to remove
+            let mut remove_assoc = Vec::with_capacity(self.remove_related.len());
+            loop {
+                // filter down to just the related entities that we want to remove
+                for assoc in cursor.data.drain(..) {
+                    if assoc.kind == AssociationKind::RelatedTo && serialized.contains(&assoc.other)
+                    {
+                        remove_assoc.push(assoc);
+                    }
+                }
+                // check if this cursor is exhausted
+                if cursor.exhausted() {
+                    break;
+                }
+                // get the next page of data in this cusor
+                cursor.next(shared).await?;
+            }
+            // deleting an association always removes the rows for both sides
+            db::associations::delete_many(&source, &remove_assoc, shared).await?;
+        }
+        Ok(())
+    }
+
     /// Build an association request for anything in this entity form
     pub(super) async fn update_associations(
         &mut self,
@@ -1252,12 +1748,14 @@ impl EntityMetadataUpdateForm {
         entity: &mut Entity,
         shared: &Shared,
     ) -> Result<(), ApiError> {
+        // any entity can be related to any other entity
+        self.update_related(user, entity, shared).await?;
         // build the association request for each of our different entities
         match &entity.metadata {
-            EntityMetadata::Device(_) => {
-                // we only have to update vendors for device updates
+            EntityMetadata::Device(_) | EntityMetadata::Software(_) => {
+                // we only have to update vendors for device and software updates
                 if !self.add_vendors.is_empty() {
-                    // devices to vendor relationships is always developed by
+                    // device and software to vendor relationships are always developed by
                     let assoc_kind = AssociationKind::DevelopedBy;
                     // get the numer of associations to make
                     let vendor_len = self.add_vendors.len();
@@ -1270,6 +1768,12 @@ impl EntityMetadataUpdateForm {
                     let mut req = AssociationRequest::with_capacity(assoc_kind, source, vendor_len);
                     // step over the vendors we are associating with this device
                     for vendor_id in self.add_vendors.drain(..) {
+                        // make sure we aren't associating this entity with itself
+                        if vendor_id == entity.id {
+                            return bad!(format!(
+                                "Entity {vendor_id} cannot be associated with itself!"
+                            ));
+                        }
                         // get the entity for this vendor if it exists
                         let other_entity = Entity::get(user, vendor_id, shared).await?;
                         // make sure this is a vendor entity
@@ -1366,6 +1870,8 @@ impl TryFrom<EntityRow> for Entity {
             description: row.description,
             tags: TagMap::with_capacity(1),
             image: row.image,
+            version: 0,
+            archived_at: None,
         })
     }
 }
@@ -1375,8 +1881,8 @@ impl CursorCore for EntityListLine {
     /// The params to build this cursor from
     type Params = EntityListParams;
 
-    /// Filter by entity kind
-    type ExtraFilters = Vec<EntityKinds>;
+    /// Filter by entity kind and optionally the user that created them
+    type ExtraFilters = (Vec<EntityKinds>, Option<String>);
 
     /// The type of data to group our rows by
     type GroupBy = String;
@@ -1390,7 +1896,7 @@ impl CursorCore for EntityListLine {
     fn bucket_limit(extra_filters: &Self::ExtraFilters) -> u32 {
         // keep our cartesian product under 99 by dividing 99 by the number of kinds
         // we are searching against
-        (99 / extra_filters.len()) as u32
+        (99 / extra_filters.0.len()) as u32
     }
 
     fn partition_size(shared: &Shared) -> u16 {
@@ -1415,7 +1921,7 @@ impl CursorCore for EntityListLine {
     }
 
     fn get_extra_filters(params: &mut Self::Params) -> Self::ExtraFilters {
-        std::mem::take(&mut params.kinds)
+        (std::mem::take(&mut params.kinds), params.submitter.take())
     }
 
     fn get_tag_filters(
@@ -1433,6 +1939,10 @@ impl CursorCore for EntityListLine {
         params.limit
     }
 
+    fn get_partition_hint(params: &Self::Params) -> Option<u32> {
+        params.partition_hint
+    }
+
     fn add_tie(&self, ties: &mut Self::Ties) {
         // if its not already in the tie map then add each of its groups to our map
         for group in &self.groups {
@@ -1529,7 +2039,7 @@ impl ScyllaCursorSupport for EntityListLine {
     #[allow(clippy::too_many_arguments)]
     fn ties_query(
         ties: &mut Self::Ties,
-        kinds: &Self::ExtraFilters,
+        (kinds, submitter): &Self::ExtraFilters,
         year: i32,
         bucket: i32,
         uploaded: DateTime<Utc>,
@@ -1541,10 +2051,17 @@ impl ScyllaCursorSupport for EntityListLine {
         // if any ties were found then get the rest of them and add them to data
         for (group, id) in ties.drain() {
             // execute our query
-            let future = shared.scylla.session.execute_unpaged(
-                &shared.scylla.prep.entities.list_ties,
-                (kinds, group, year, bucket, uploaded, id, limit),
-            );
+            let future = match submitter {
+                // only get ties for entities created by this submitter
+                Some(submitter) => Either::Left(shared.scylla.session.execute_unpaged(
+                    &shared.scylla.prep.entities.list_ties_submitter,
+                    (kinds, group, year, bucket, uploaded, id, submitter, limit),
+                )),
+                None => Either::Right(shared.scylla.session.execute_unpaged(
+                    &shared.scylla.prep.entities.list_ties,
+                    (kinds, group, year, bucket, uploaded, id, limit),
+                )),
+            };
             // add this future to our set
             futures.push(future);
         }
@@ -1556,7 +2073,7 @@ impl ScyllaCursorSupport for EntityListLine {
     /// # Arguments
     ///
     /// * `group` - The group to restrict our query too
-    /// * `kinds` - The entity kinds to filter on
+    /// * `kinds` - The entity kinds and submitter to filter on
     /// * `year` - The year to get data for
     /// * `bucket` - The bucket to get data for
     /// * `start` - The earliest timestamp to get data from
@@ -1567,7 +2084,7 @@ impl ScyllaCursorSupport for EntityListLine {
     #[allow(clippy::type_complexity, clippy::type_repetition_in_bounds)]
     async fn pull(
         group: &Self::GroupBy,
-        kinds: &Self::ExtraFilters,
+        (kinds, submitter): &Self::ExtraFilters,
         year: i32,
         buckets: Vec<i32>,
         start: DateTime<Utc>,
@@ -1576,14 +2093,29 @@ impl ScyllaCursorSupport for EntityListLine {
         shared: &Shared,
     ) -> Result<QueryResult, ExecutionError> {
         // execute our query
-        shared
-            .scylla
-            .session
-            .execute_unpaged(
-                &shared.scylla.prep.entities.list_pull,
-                (kinds, group, year, buckets, start, end, limit),
-            )
-            .await
+        match submitter {
+            // only get entities created by this submitter
+            Some(submitter) => {
+                shared
+                    .scylla
+                    .session
+                    .execute_unpaged(
+                        &shared.scylla.prep.entities.list_pull_submitter,
+                        (kinds, group, year, buckets, start, end, submitter, limit),
+                    )
+                    .await
+            }
+            None => {
+                shared
+                    .scylla
+                    .session
+                    .execute_unpaged(
+                        &shared.scylla.prep.entities.list_pull,
+                        (kinds, group, year, buckets, start, end, limit),
+                    )
+                    .await
+            }
+        }
     }
 }
 
@@ -1627,6 +2159,29 @@ impl From<TagListRow> for EntityListLine {
     }
 }
 
+/// Build a stream that populates entities with their association data
+///
+/// At most `width` entities are populated at once and they are returned in the
+/// same order they were given in.
+///
+/// # Arguments
+///
+/// * `entities` - The stream of entities to populate
+/// * `width` - The max number of entities to populate at once
+/// * `populate` - The function to populate each entity with
+fn populate_stream<S, F, Fut>(
+    entities: S,
+    width: NonZeroUsize,
+    populate: F,
+) -> impl Stream<Item = Fut::Output>
+where
+    S: Stream,
+    F: FnMut(S::Item) -> Fut,
+    Fut: Future,
+{
+    entities.map(populate).buffered(width.get())
+}
+
 impl ApiCursor<EntityListLine> {
     /// Turns a cursor of [`EntityListLine`] into a cursor of [`Entity`]
     ///
@@ -1655,9 +2210,11 @@ impl ApiCursor<EntityListLine> {
         // get any required association data for the entities we found
         let mut populated = Vec::with_capacity(data.len());
         // create a stream of entities to populate with any association data
-        let mut populator_stream = stream::iter(data)
-            .map(|entity| entity.populate_associations(user, shared))
-            .buffered(20);
+        let mut populator_stream = populate_stream(
+            stream::iter(data),
+            shared.config.thorium.entities.association_buffer,
+            |entity| entity.populate_associations(user, shared),
+        );
         // get populated items from our stream until we hit a problem or no more exist
         while let Some(entity) = populator_stream.next().await {
             // raise an error if we ran into a problem
@@ -1669,6 +2226,69 @@ impl ApiCursor<EntityListLine> {
             data: populated,
         })
     }
+
+    /// Turns a cursor of [`EntityListLine`] into a stream of [`Entity`] details
+    ///
+    /// Entities are populated with their association data as the stream is polled
+    /// instead of collecting the whole page at once. Entities are still returned in
+    /// the same order as this cursor.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is getting the details for this list
+    /// * `shared` - Shared Thorium objects
+    #[instrument(
+        name = "ApiCursor<EntityListLine>::details_stream",
+        skip_all
+        err(Debug)
+    )]
+    pub(crate) async fn details_stream(
+        self,
+        user: User,
+        shared: Arc<Shared>,
+    ) -> Result<impl Stream<Item = Result<Entity, ApiError>> + Send + 'static, ApiError> {
+        // get the groups this user can get entity details from
+        let groups = if user.is_admin() {
+            // admins can get details from any group like `for_groups!` does
+            db::groups::list(0, 1000, &shared).await?.names
+        } else {
+            user.groups.clone()
+        };
+        let groups = Arc::new(groups);
+        // pull as many entities at once as we populate at once
+        let width = shared.config.thorium.entities.association_buffer;
+        // split the ids of the entities we need to get details on into chunks
+        let chunks = self
+            .data
+            .chunks(width.get())
+            .map(|lines| lines.iter().map(|line| line.id).collect::<Vec<Uuid>>())
+            .collect::<Vec<Vec<Uuid>>>();
+        // query for each chunk of entities as our stream is polled
+        let query_shared = shared.clone();
+        let entities = stream::iter(chunks)
+            .then(move |ids| {
+                // clone our groups and shared objects for this query
+                let groups = groups.clone();
+                let shared = query_shared.clone();
+                async move { db::entities::get_many(&groups, &ids, &shared).await }
+            })
+            .flat_map(|queried| match queried {
+                // return each entity we got details for
+                Ok(entities) => Either::Left(stream::iter(entities.into_iter().map(Ok))),
+                // return the error we ran into
+                Err(err) => Either::Right(stream::once(async { Err(err) })),
+            });
+        // share our user across the futures populating our entities
+        let user = Arc::new(user);
+        // populate our entities with any association data as they are pulled from our stream
+        let populator_stream = populate_stream(entities, width, move |entity| {
+            // clone our user and shared objects for this entity
+            let user = user.clone();
+            let shared = shared.clone();
+            async move { entity?.populate_associations(&user, &shared).await }
+        });
+        Ok(populator_stream)
+    }
 }
 
 impl<S> FromRequestParts<S> for EntityListParams
@@ -1690,3 +2310,42 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::stream::{self, StreamExt};
+    use std::num::NonZeroUsize;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::populate_stream;
+
+    #[tokio::test]
+    async fn populate_width() {
+        for width in [1, 3, 8] {
+            // track how many entities are being populated at once
+            let active = Arc::new(AtomicUsize::new(0));
+            let peak = Arc::new(AtomicUsize::new(0));
+            let stream = populate_stream(
+                stream::iter(0..20),
+                NonZeroUsize::new(width).unwrap(),
+                |item| {
+                    let active = active.clone();
+                    let peak = peak.clone();
+                    async move {
+                        let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(5)).await;
+                        active.fetch_sub(1, Ordering::SeqCst);
+                        item
+                    }
+                },
+            );
+            let populated = stream.collect::<Vec<_>>().await;
+            // entities should be returned in order without exceeding our configured width
+            assert_eq!(populated, (0..20).collect::<Vec<_>>());
+            assert_eq!(peak.load(Ordering::SeqCst), width);
+        }
+    }
+}
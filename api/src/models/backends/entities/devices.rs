@@ -1,7 +1,13 @@
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use tracing::instrument;
 use uuid::Uuid;
 
 use crate::models::backends::db;
-use crate::models::{DeviceEntity, EntityMetadataUpdateForm};
+use crate::models::entities::EntityMetadata;
+use crate::models::{
+    ApiCursor, DeviceEntity, DeviceListParams, Entity, EntityMetadataUpdateForm, User,
+};
 use crate::utils::{ApiError, Shared};
 use crate::{
     bad, internal_err, update_add_rem_ordered_owned, update_add_rem_set_owned, update_clear_opt,
@@ -95,4 +101,52 @@ impl DeviceEntity {
         //}
         Ok(())
     }
+    /// List devices and their details in a specific critical sector
+    ///
+    /// Devices are listed by the intrinsic critical sector tags on their entities
+    /// so pages may contain fewer devices than requested once our device filters
+    /// are applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is listing devices
+    /// * `params` - The params to use when listing devices
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "DeviceEntity::list", skip(user, shared), err(Debug))]
+    pub async fn list(
+        user: &User,
+        params: DeviceListParams,
+        shared: &Shared,
+    ) -> Result<ApiCursor<Entity>, ApiError> {
+        // list the entities tagged with our critical sector in our authorized groups
+        let list = Entity::list(user, params.entity_params(), false, shared).await?;
+        // get the details for the entities we found
+        let mut cursor = list.details(user, shared).await?;
+        // only keep the devices that match our filters
+        cursor.data.retain(|entity| match &entity.metadata {
+            EntityMetadata::Device(device) => params.matches(device),
+            _ => false,
+        });
+        Ok(cursor)
+    }
+}
+
+impl<S> FromRequestParts<S> for DeviceListParams
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // try to extract our query
+        if let Some(query) = parts.uri.query() {
+            // try to deserialize our query string
+            Ok(serde_qs::Config::new()
+                .max_depth(5)
+                .deserialize_str(query)?)
+        } else {
+            // a critical sector is always required to list devices
+            bad!("A critical sector must be set to list devices!".to_owned())
+        }
+    }
 }
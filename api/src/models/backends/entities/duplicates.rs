@@ -0,0 +1,191 @@
+//! Backend logic for detecting likely duplicate entities
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use std::collections::BTreeMap;
+use tracing::instrument;
+
+use crate::models::entities::duplicates::{name_similarity, normalize_name};
+use crate::models::{
+    DuplicateEntity, Entity, EntityDuplicate, EntityDuplicateParams, EntityDuplicateReport,
+    EntityListLine, EntityListParams, User,
+};
+use crate::utils::{ApiError, Shared};
+use crate::{bad, is_admin};
+
+/// The most entities that can be scanned for duplicates at once
+const MAX_SCAN_SIZE: usize = 5000;
+
+/// An entity that has had its name normalized
+struct NormalizedEntity {
+    /// The entity that was listed
+    line: EntityListLine,
+    /// This entity's normalized name
+    normalized: String,
+}
+
+/// Compare entities of the same kind to find pairs with similar names
+///
+/// This compares every pair of entities of the same kind and so should be run on a
+/// blocking thread.
+///
+/// # Arguments
+///
+/// * `lines` - The entities to compare
+/// * `threshold` - How similar two names must be to be reported
+#[allow(clippy::cast_precision_loss)]
+fn find_duplicates(lines: Vec<EntityListLine>, threshold: f64) -> Vec<EntityDuplicate> {
+    // bucket our entities by kind since only entities of the same kind are compared
+    let mut buckets: BTreeMap<String, Vec<NormalizedEntity>> = BTreeMap::new();
+    for line in lines {
+        let normalized = normalize_name(&line.name);
+        buckets
+            .entry(line.kind.to_string())
+            .or_default()
+            .push(NormalizedEntity { line, normalized });
+    }
+    let mut duplicates = Vec::new();
+    for bucket in buckets.values() {
+        for (i, left) in bucket.iter().enumerate() {
+            for right in &bucket[i + 1..] {
+                // skip any pairs whose lengths are too different to ever be similar enough
+                let (left_len, right_len) = (
+                    left.normalized.chars().count(),
+                    right.normalized.chars().count(),
+                );
+                let (shortest, longest) = (left_len.min(right_len), left_len.max(right_len));
+                if longest > 0 && (shortest as f64 / longest as f64) < threshold {
+                    continue;
+                }
+                let similarity = name_similarity(&left.normalized, &right.normalized);
+                if similarity >= threshold {
+                    duplicates.push(EntityDuplicate {
+                        kind: left.line.kind,
+                        left: DuplicateEntity {
+                            id: left.line.id,
+                            name: left.line.name.clone(),
+                        },
+                        right: DuplicateEntity {
+                            id: right.line.id,
+                            name: right.line.name.clone(),
+                        },
+                        similarity,
+                    });
+                }
+            }
+        }
+    }
+    // report the most similar pairs first
+    duplicates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    duplicates
+}
+
+impl EntityDuplicateReport {
+    /// List the entities to scan for duplicates
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is scanning for duplicates
+    /// * `params` - The params for this duplicate scan
+    /// * `shared` - Shared Thorium objects
+    async fn get_entities(
+        user: &User,
+        params: &EntityDuplicateParams,
+        shared: &Shared,
+    ) -> Result<(Vec<EntityListLine>, bool), ApiError> {
+        let mut lines = Vec::with_capacity(params.limit.min(1000));
+        let mut cursor = None;
+        // list entities until we run out or hit our limit
+        let truncated = loop {
+            let mut list_params = EntityListParams {
+                groups: params.groups.clone(),
+                cursor,
+                limit: params.limit.min(1000),
+                ..EntityListParams::default()
+            };
+            // only list the requested kinds if any were set
+            if !params.kinds.is_empty() {
+                list_params.kinds.clone_from(&params.kinds);
+            }
+            let list = Entity::list(user, list_params, true, shared).await?;
+            lines.extend(list.data);
+            // stop if we have hit our limit or there are no more entities
+            match list.cursor {
+                Some(_) if lines.len() >= params.limit => break true,
+                Some(next) => cursor = Some(next),
+                None => break lines.len() > params.limit,
+            }
+        };
+        lines.truncate(params.limit);
+        Ok((lines, truncated))
+    }
+
+    /// Scan entities for likely duplicates based on the similarity of their names
+    ///
+    /// Only entities of the same kind are compared. This does not change any entities
+    /// and only reports pairs that could be merged.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is scanning for duplicates
+    /// * `params` - The params for this duplicate scan
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "EntityDuplicateReport::scan", skip(user, shared), err(Debug))]
+    pub async fn scan(
+        user: &User,
+        mut params: EntityDuplicateParams,
+        shared: &Shared,
+    ) -> Result<Self, ApiError> {
+        // only admins can scan for duplicates
+        is_admin!(user);
+        // make sure our limit is sane
+        if params.limit == 0 || params.limit > MAX_SCAN_SIZE {
+            return bad!(format!(
+                "Duplicate scans must scan between 1 and {MAX_SCAN_SIZE} entities!"
+            ));
+        }
+        // use the configured threshold if one wasn't set
+        let threshold = params
+            .threshold
+            .unwrap_or(shared.config.thorium.entities.duplicate_threshold);
+        if !(0.0..=1.0).contains(&threshold) {
+            return bad!(format!(
+                "The duplicate threshold must be between 0.0 and 1.0 not {threshold}!"
+            ));
+        }
+        // authorize the groups to scan
+        user.authorize_groups(&mut params.groups, shared).await?;
+        // get the entities to scan
+        let (lines, truncated) = Self::get_entities(user, &params, shared).await?;
+        let scanned = lines.len();
+        // compare our entities on a blocking thread so we don't stall the runtime
+        let duplicates =
+            tokio::task::spawn_blocking(move || find_duplicates(lines, threshold)).await?;
+        Ok(EntityDuplicateReport {
+            threshold,
+            scanned,
+            duplicates,
+            truncated,
+        })
+    }
+}
+
+impl<S> FromRequestParts<S> for EntityDuplicateParams
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // try to extract our query
+        if let Some(query) = parts.uri.query() {
+            // try to deserialize our query string
+            Ok(serde_qs::Config::new()
+                .max_depth(5)
+                .deserialize_str(query)?)
+        } else {
+            // provide default params if none were given
+            Ok(Self::default())
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Backend logic for exporting the association graph between entities
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use std::collections::{HashMap, HashSet};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::backends::db;
+use crate::models::{
+    Association, AssociationListOpts, AssociationTarget, AssociationTargetColumn, Directionality,
+    Entity, EntityGraph, EntityGraphParams, EntityListParams, GraphLink, GraphNode, User,
+};
+use crate::utils::{ApiError, Shared};
+use crate::{bad, not_found};
+
+/// The most nodes or links that can be exported in a single graph
+const MAX_GRAPH_SIZE: usize = 10_000;
+
+/// A link whose other node has not been authorized yet
+struct CandidateLink {
+    /// The link to add if its other node is visible
+    link: GraphLink,
+    /// The other node in this link
+    other: AssociationTarget,
+}
+
+impl EntityGraph {
+    /// Get the entities to build a graph around
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is exporting a graph
+    /// * `params` - The params for this graph export
+    /// * `shared` - Shared Thorium objects
+    async fn get_seeds(
+        user: &User,
+        params: &EntityGraphParams,
+        shared: &Shared,
+    ) -> Result<(Vec<Entity>, bool), ApiError> {
+        // if no entities were requested then use all entities in our groups
+        if params.entities.is_empty() {
+            let mut ids = Vec::with_capacity(params.max_nodes.min(1000));
+            let mut cursor = None;
+            // list entities until we run out or hit our node limit
+            let truncated = loop {
+                let list_params = EntityListParams {
+                    groups: params.groups.clone(),
+                    cursor,
+                    limit: params.max_nodes.min(1000),
+                    ..EntityListParams::default()
+                };
+                let list = Entity::list(user, list_params, false, shared).await?;
+                ids.extend(list.data.into_iter().map(|line| line.id));
+                // stop if we have hit our limit or there are no more entities
+                match list.cursor {
+                    Some(_) if ids.len() >= params.max_nodes => break true,
+                    Some(next) => cursor = Some(next),
+                    None => break ids.len() > params.max_nodes,
+                }
+            };
+            ids.truncate(params.max_nodes);
+            // get the details for the entities we found
+            let entities = db::entities::get_many(&params.groups, &ids, shared).await?;
+            Ok((entities, truncated))
+        } else {
+            // get the requested entities in the groups we can see
+            let entities = db::entities::get_many(&params.groups, &params.entities, shared).await?;
+            // make sure we could see all of the requested entities
+            if let Some(missing) = params
+                .entities
+                .iter()
+                .find(|id| !entities.iter().any(|entity| entity.id == **id))
+            {
+                return not_found!(format!("Entity {missing} not found"));
+            }
+            Ok((entities, false))
+        }
+    }
+
+    /// Get the links to all nodes directly associated with our seed entities
+    ///
+    /// # Arguments
+    ///
+    /// * `seeds` - The entities to get links for
+    /// * `params` - The params for this graph export
+    /// * `shared` - Shared Thorium objects
+    async fn get_candidates(
+        seeds: &[Entity],
+        params: &EntityGraphParams,
+        shared: &Shared,
+    ) -> Result<Vec<CandidateLink>, ApiError> {
+        let mut candidates = Vec::new();
+        // track the links we have seen so links between seeds are only added once
+        let mut seen = HashSet::new();
+        for seed in seeds {
+            let seed_id = seed.id.to_string();
+            // list this entities associations in the groups we are exporting from
+            let opts = AssociationListOpts::default()
+                .groups(params.groups.clone())
+                .limit(500);
+            let source = AssociationTargetColumn::Entity(seed.id);
+            let mut cursor = db::associations::list(opts, &source, shared).await?;
+            loop {
+                for listable in cursor.data.drain(..) {
+                    // skip any kinds of associations we weren't asked for
+                    if !params.kinds.is_empty() && !params.kinds.contains(&listable.kind) {
+                        continue;
+                    }
+                    let association = Association::try_from(listable)?;
+                    let other_id = GraphNode::new(association.other.clone(), None).id;
+                    // orient this link based on the direction of its association
+                    let (source, target) = match association.direction {
+                        Directionality::From => (other_id, seed_id.clone()),
+                        Directionality::To | Directionality::Bidirectional => {
+                            (seed_id.clone(), other_id)
+                        }
+                    };
+                    let bidirectional = association.direction == Directionality::Bidirectional;
+                    // bidirectional links are the same no matter which side we found them from
+                    let key = if bidirectional && target < source {
+                        (association.kind, target.clone(), source.clone())
+                    } else {
+                        (association.kind, source.clone(), target.clone())
+                    };
+                    if !seen.insert(key) {
+                        continue;
+                    }
+                    candidates.push(CandidateLink {
+                        link: GraphLink {
+                            source,
+                            target,
+                            kind: association.kind,
+                            bidirectional,
+                            confidence: association.confidence,
+                        },
+                        other: association.other,
+                    });
+                }
+                // check if this cursor is exhausted
+                if cursor.exhausted() {
+                    break;
+                }
+                // get the next page of associations
+                cursor.next(shared).await?;
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// Export the association graph around some entities
+    ///
+    /// Only entities directly associated with the requested entities are included and
+    /// any entities this user can't see are dropped along with their links.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is exporting a graph
+    /// * `params` - The params for this graph export
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "EntityGraph::export", skip(user, shared), err(Debug))]
+    pub async fn export(
+        user: &User,
+        mut params: EntityGraphParams,
+        shared: &Shared,
+    ) -> Result<Self, ApiError> {
+        // make sure our limits are sane
+        if params.max_nodes == 0
+            || params.max_nodes > MAX_GRAPH_SIZE
+            || params.max_links > MAX_GRAPH_SIZE
+        {
+            return bad!(format!(
+                "Graph exports must have between 1 and {MAX_GRAPH_SIZE} nodes and at most {MAX_GRAPH_SIZE} links!"
+            ));
+        }
+        if params.entities.len() > params.max_nodes {
+            return bad!(format!(
+                "Cannot export a graph around {} entities with a limit of {} nodes!",
+                params.entities.len(),
+                params.max_nodes
+            ));
+        }
+        // authorize the groups to export from
+        user.authorize_groups(&mut params.groups, shared).await?;
+        // get the entities to build our graph around
+        let (seeds, mut truncated) = Self::get_seeds(user, &params, shared).await?;
+        let mut graph = EntityGraph::default();
+        let mut node_ids = HashSet::with_capacity(seeds.len());
+        for seed in &seeds {
+            let target = AssociationTarget::Entity {
+                id: seed.id,
+                name: seed.name.clone(),
+            };
+            let node = GraphNode::new(target, Some(seed.kind));
+            node_ids.insert(node.id.clone());
+            graph.nodes.push(node);
+        }
+        // get the links to any directly associated nodes
+        let candidates = Self::get_candidates(&seeds, &params, shared).await?;
+        // get the other entities in our links that aren't already in our graph
+        let other_ids = candidates
+            .iter()
+            .filter_map(|candidate| match &candidate.other {
+                AssociationTarget::Entity { id, .. } if !node_ids.contains(&id.to_string()) => {
+                    Some(*id)
+                }
+                _ => None,
+            })
+            .collect::<HashSet<Uuid>>()
+            .into_iter()
+            .collect::<Vec<Uuid>>();
+        // only keep the entities this user can see in the groups we are exporting from
+        let visible = db::entities::get_many(&params.groups, &other_ids, shared)
+            .await?
+            .into_iter()
+            .map(|entity| (entity.id, entity))
+            .collect::<HashMap<Uuid, Entity>>();
+        for CandidateLink { link, other } in candidates {
+            // stop adding links once we hit our limit
+            if graph.links.len() >= params.max_links {
+                truncated = true;
+                break;
+            }
+            let other_id = GraphNode::new(other.clone(), None).id;
+            // add this links other node if its not already in our graph
+            if !node_ids.contains(&other_id) {
+                let node = match other {
+                    AssociationTarget::Entity { id, .. } => match visible.get(&id) {
+                        Some(entity) => {
+                            let target = AssociationTarget::Entity {
+                                id,
+                                name: entity.name.clone(),
+                            };
+                            GraphNode::new(target, Some(entity.kind))
+                        }
+                        // this user can't see this entity so skip this link
+                        None => continue,
+                    },
+                    other => GraphNode::new(other, None),
+                };
+                // skip this link if we can't add any more nodes
+                if graph.nodes.len() >= params.max_nodes {
+                    truncated = true;
+                    continue;
+                }
+                node_ids.insert(other_id);
+                graph.nodes.push(node);
+            }
+            graph.links.push(link);
+        }
+        graph.truncated = truncated;
+        Ok(graph)
+    }
+}
+
+impl<S> FromRequestParts<S> for EntityGraphParams
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // try to extract our query
+        if let Some(query) = parts.uri.query() {
+            // try to deserialize our query string
+            Ok(serde_qs::Config::new()
+                .max_depth(5)
+                .deserialize_str(query)?)
+        } else {
+            // provide default params if none were given
+            Ok(Self::default())
+        }
+    }
+}
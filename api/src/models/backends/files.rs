@@ -1,12 +1,14 @@
 //! Handles saving and retrieving files objects from the backend
 
 use aws_sdk_s3::primitives::ByteStream;
+use axum::body::Body;
 use axum::extract::multipart::Field;
 use axum::extract::{FromRequestParts, Multipart};
 use axum::http::request::Parts;
+use bytes::Bytes;
 use chrono::prelude::*;
-use futures_util::stream::{self, StreamExt};
-use futures_util::{Future, TryStreamExt};
+use futures::stream::{self, StreamExt};
+use futures_util::Future;
 use scylla::errors::ExecutionError;
 use scylla::response::query_result::QueryResult;
 use std::collections::{HashMap, HashSet};
@@ -18,13 +20,13 @@ use super::CommentSupport;
 use super::db::{self, CursorCore, ScyllaCursorSupport, TagCountCursorSupport};
 use crate::models::backends::db::ScyllaCursor;
 use crate::models::{
-    ApiCursor, CarvedOrigin, CarvedOriginTypes, Comment, CommentForm, CommentResponse, CommentRow,
-    DeleteCommentParams, DeleteSampleParams, Directionality, FileListParams, Group,
-    GroupAllowAction, Origin, OriginForm, OriginRequest, OriginTypes, S3Objects, Sample,
-    SampleCheck, SampleCheckResponse, SampleForm, SampleListLine, SampleSubmissionResponse,
-    Submission, SubmissionChunk, SubmissionListRow, SubmissionRow, SubmissionUpdate, TagCounts,
-    TagListRow, TagMap, TagType, TreeRelationships, TreeSupport, UnhashedTreeBranch, User,
-    ZipDownloadParams,
+    ApiCursor, BulkTagResponse, ByteRange, CarvedOrigin, CarvedOriginTypes, Comment, CommentForm,
+    CommentResponse, CommentRow, DeleteCommentParams, DeleteSampleParams, Directionality,
+    FileListParams, Group, GroupAllowAction, Origin, OriginForm, OriginRequest, OriginTypes,
+    S3Objects, Sample, SampleCheck, SampleCheckResponse, SampleForm, SampleListLine,
+    SampleSubmissionResponse, Submission, SubmissionChunk, SubmissionListRow, SubmissionRow,
+    SubmissionUpdate, TagCounts, TagListRow, TagMap, TagRequest, TagType, TreeRelationships,
+    TreeSupport, UnhashedTreeBranch, User, ZipDownloadParams,
 };
 use crate::utils::{ApiError, Shared};
 use crate::{
@@ -353,6 +355,66 @@ impl Sample {
         shared.s3.files.download(&s3_id.to_string()).await
     }
 
+    /// Download a range of bytes from a carted object by sha256
+    ///
+    /// The range is of the carted object and its `Content-Range` is returned with it.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is getting this sample
+    /// * `sha256` - The sha256 of the sample to get
+    /// * `range` - The range of bytes to download
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "Sample::download_range", skip(user, shared), err(Debug))]
+    pub async fn download_range(
+        user: &User,
+        sha256: String,
+        range: &ByteRange,
+        shared: &Shared,
+    ) -> Result<(String, ByteStream), ApiError> {
+        Sample::authorize(user, &vec![sha256.clone()], shared).await?;
+        // get the s3 id for this object
+        let s3_id = db::s3::get_s3_id(S3Objects::File, &sha256, shared).await?;
+        // this sample exists and we have access to it so download our range
+        shared
+            .s3
+            .files
+            .download_range(&s3_id.to_string(), range)
+            .await
+    }
+
+    /// Download a range of bytes from an uncarted object by sha256
+    ///
+    /// The range is of the uncarted object.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is getting this sample
+    /// * `sha256` - The sha256 of the sample to get
+    /// * `range` - The range of bytes to download
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(
+        name = "Sample::download_uncarted_range",
+        skip(user, shared),
+        err(Debug)
+    )]
+    pub async fn download_uncarted_range(
+        user: &User,
+        sha256: String,
+        range: &ByteRange,
+        shared: &Shared,
+    ) -> Result<(ByteRange, Bytes), ApiError> {
+        Sample::authorize(user, &vec![sha256.clone()], shared).await?;
+        // get the s3 id for this object
+        let s3_id = db::s3::get_s3_id(S3Objects::File, &sha256, shared).await?;
+        // this sample exists and we have access to it so download our range
+        shared
+            .s3
+            .files
+            .download_uncarted_range(&s3_id.to_string(), range)
+            .await
+    }
+
     /// Download an object by sha256 as an encrypted zip
     ///
     /// This is not near as efficient as using CaRT and should not be used for large files.
@@ -368,16 +430,16 @@ impl Sample {
         sha256: String,
         params: ZipDownloadParams,
         shared: &Shared,
-    ) -> Result<Vec<u8>, ApiError> {
+    ) -> Result<Body, ApiError> {
         Sample::authorize(user, &vec![sha256.clone()], shared).await?;
         // get the s3 id for this object
         let s3_id = db::s3::get_s3_id(S3Objects::File, &sha256, shared).await?;
         // this sample exists and we have access to it so download it
-        shared
+        let zip = shared
             .s3
             .files
-            .download_as_zip(&s3_id.to_string(), &sha256, params, shared)
-            .await
+            .download_as_zip(&s3_id.to_string(), &sha256, params, shared);
+        Ok(zip)
     }
 
     /// Updates a submission for a sample
@@ -631,6 +693,111 @@ impl Sample {
         Ok(())
     }
 
+    /// Add tags to many samples at once
+    ///
+    /// Groups are authorized once for all samples and any samples that can't be
+    /// tagged are returned as errors instead of failing the whole request.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is creating tags
+    /// * `reqs` - The tag requests to apply by sha256
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "Sample::tag_bulk", skip_all, fields(samples = reqs.len()), err(Debug))]
+    pub async fn tag_bulk(
+        user: &User,
+        reqs: HashMap<String, TagRequest<Sample>>,
+        shared: &Shared,
+    ) -> Result<BulkTagResponse, ApiError> {
+        // make sure this request isn't too large before we do any work
+        let limit = shared.config.thorium.request_size_limits.bulk_tags;
+        if reqs.len() > limit {
+            return bad!(format!(
+                "Unable to tag {} samples in one request! The max is {limit}.",
+                reqs.len()
+            ));
+        }
+        // build the response to return
+        let mut response = BulkTagResponse::with_capacity(reqs.len());
+        // get all of the samples we are tagging
+        let gets = reqs.into_iter().map(|(sha256, req)| async move {
+            let sample = Sample::get(user, &sha256, shared).await;
+            (sha256, sample, req)
+        });
+        let samples = stream::iter(gets)
+            .buffer_unordered(50)
+            .collect::<Vec<_>>()
+            .await;
+        let mut found = Vec::with_capacity(samples.len());
+        for (sha256, sample, req) in samples {
+            match sample {
+                Ok(sample) => found.push((sample, req)),
+                Err(error) => {
+                    response.errors.insert(sha256, error.to_string());
+                }
+            }
+        }
+        // authorize all of the groups these samples are in at once
+        let names = found
+            .iter()
+            .flat_map(|(sample, _)| sample.groups())
+            .map(ToOwned::to_owned)
+            .collect::<HashSet<String>>()
+            .into_iter()
+            .collect::<Vec<String>>();
+        let groups = Group::authorize_all(user, &names, shared)
+            .await?
+            .into_iter()
+            .map(|group| (group.name.clone(), group))
+            .collect::<HashMap<String, Group>>();
+        // check if a group allows this user to tag samples in it
+        let taggable = |name: &str| {
+            groups.get(name).map_or_else(
+                || unauthorized!(format!("{name} does not exist")),
+                |group| {
+                    group.editable(user)?;
+                    group.allowable(GroupAllowAction::Tags)
+                },
+            )
+        };
+        // validate the groups for each of our tag requests
+        let mut items = Vec::with_capacity(found.len());
+        for (sample, mut req) in found {
+            // get the groups this sample is in that we can see
+            let sample_groups = sample.groups();
+            if req.groups.is_empty() {
+                // no groups were specified so default to the ones we can tag in
+                req.groups = sample_groups
+                    .into_iter()
+                    .filter(|name| taggable(name).is_ok())
+                    .map(ToOwned::to_owned)
+                    .collect();
+            } else if !req
+                .groups
+                .iter()
+                .all(|group| sample_groups.contains(group.as_str()))
+            {
+                let msg = format!("{} is not in all specified groups", sample.sha256);
+                response.errors.insert(sample.sha256, msg);
+                continue;
+            } else if let Some(error) = req.groups.iter().find_map(|name| taggable(name).err()) {
+                response.errors.insert(sample.sha256, error.to_string());
+                continue;
+            }
+            // make sure at least some groups are valid
+            if req.groups.is_empty() {
+                let msg = format!("No groups allow {} to be created!", GroupAllowAction::Tags);
+                response.errors.insert(sample.sha256, msg);
+                continue;
+            }
+            let sha256 = sample.sha256.clone();
+            items.push((sha256, req, sample.earliest_owned()));
+        }
+        // save all of our tags at once
+        db::tags::create_bulk(user, items, &mut response, shared).await;
+        Ok(response)
+    }
+
     /// List all samples sorted by date
     ///
     /// # Arguments
@@ -648,6 +815,8 @@ impl Sample {
     ) -> Result<ApiCursor<SampleListLine>, ApiError> {
         // convert our params if needed
         let mut params = params.into();
+        // make sure this user is allowed to use any requested partition hint
+        db::check_partition_hint(params.partition_hint, user, shared)?;
         // authorize the groups to list files from
         user.authorize_groups(&mut params.groups, shared).await?;
         // get a chunk of the files list
@@ -932,25 +1101,20 @@ impl SubmissionChunk {
     ) -> Result<(), ApiError> {
         // if we are the owner of this submission then we can delete it
         if self.submitter == user.username || user.is_admin() {
-            // ensure that all groups exist concurrently
-            stream::iter(groups)
-                .map(Ok)
-                .try_for_each_concurrent(None, |name| async {
-                    Group::get(user, name, shared).await.map(|_| ())
-                })
-                .await?;
+            // ensure that all groups exist and are visible to us in one batch
+            Group::authorize_all(user, groups, shared).await?;
         } else {
             // we are not the owner of this submission so first check if that all
             // groups exist, then make sure we are a manager or owner in each group
-            stream::iter(groups)
-                .map(Ok)
-                .try_for_each_concurrent(None, |name| async {
-                    Group::get(user, name, shared)
-                        .await?
-                        .modifiable(user)
-                        .map(|()| ())
-                })
-                .await?;
+            Group::authorize_check_allow_all(
+                user,
+                groups,
+                Group::modifiable,
+                "modify",
+                None,
+                shared,
+            )
+            .await?;
         }
         Ok(())
     }
@@ -1410,6 +1574,15 @@ impl CursorCore for SampleListLine {
         params.limit
     }
 
+    /// Get the number of partitions to crawl at once that was requested in our params
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The params to use to build this cursor
+    fn get_partition_hint(params: &Self::Params) -> Option<u32> {
+        params.partition_hint
+    }
+
     /// Get the partition size for this cursor
     ///
     /// # Arguments
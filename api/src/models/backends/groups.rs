@@ -9,9 +9,9 @@ use super::db;
 use super::db::groups::{MembersLists, RawGroupData};
 use crate::models::groups::GroupUsers;
 use crate::models::{
-    Group, GroupAllowAction, GroupAllowed, GroupAllowedUpdate, GroupDetailsList, GroupList,
-    GroupRequest, GroupStats, GroupUpdate, GroupUsersRequest, GroupUsersUpdate, ImageScaler,
-    Pipeline, User,
+    CacheWarmResponse, Group, GroupAllowAction, GroupAllowed, GroupAllowedUpdate, GroupDetailsList,
+    GroupList, GroupRequest, GroupStats, GroupUpdate, GroupUsersRequest, GroupUsersUpdate,
+    ImageScaler, Pipeline, User,
 };
 use crate::utils::{ApiError, Shared, bounder};
 use crate::{
@@ -353,11 +353,11 @@ impl Group {
     /// * `names` - An array of group names to check if they exist
     /// * `shared` - Shared objects in Thorium
     pub async fn exists(names: &[String], shared: &Shared) -> Result<(), ApiError> {
-        // check if group exists
-        let exists = db::groups::exists(names, shared).await?;
-        if !exists {
+        // check if all of these groups exist in one query
+        let missing = db::groups::exist_many(names, shared).await?;
+        if !missing.is_empty() {
             // build error message
-            return not_found!(format!("groups {:?} must exist", &names));
+            return not_found!(format!("groups {missing:?} must exist"));
         }
         Ok(())
     }
@@ -617,10 +617,7 @@ impl Group {
         if user.is_admin() {
             // if we are an admin we need to do a second call to make sure these groups exist
             // this is because non existent groups will still return empty user lists
-            if !db::groups::exists(names, shared).await? {
-                // one or more of the groups don't exist throw an error
-                return not_found!(format!("all of {:?} groups must exist", names));
-            }
+            Self::exists(names, shared).await?;
         } else {
             for group in &groups {
                 group.viewable(user)?;
@@ -677,10 +674,7 @@ impl Group {
             }
             // if we are an admin we need to do a second call to make sure these groups exist
             // this is because non existent groups will still return empty user lists
-            if !db::groups::exists(names, shared).await? {
-                // one or more of the groups don't exist throw an error
-                return not_found!(format!("all of {:?} groups must exist", names));
-            }
+            Self::exists(names, shared).await?;
         } else {
             if let Some(action) = action {
                 for group in &groups {
@@ -918,6 +912,21 @@ impl Group {
         }
         Ok(status)
     }
+
+    /// Load this groups images and pipelines into our caches
+    ///
+    /// # Arguments
+    ///
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "Group::warm_cache", skip_all, fields(group = &self.name), err(Debug))]
+    pub async fn warm_cache(&self, shared: &Shared) -> Result<CacheWarmResponse, ApiError> {
+        // cache this groups images and pipelines at the same time
+        let (images, pipelines) = tokio::try_join!(
+            db::images::warm(&self.name, shared),
+            db::pipelines::warm(&self.name, shared)
+        )?;
+        Ok(CacheWarmResponse { images, pipelines })
+    }
 }
 
 impl TryFrom<RawGroupData> for Group {
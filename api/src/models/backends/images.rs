@@ -5,7 +5,6 @@ use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
 use futures::{StreamExt, TryStreamExt, stream};
 use itertools::Itertools;
-use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use tracing::{Level, event, instrument};
 use uuid::Uuid;
@@ -16,13 +15,14 @@ use crate::models::system::{
     BARE_METAL_CACHE_KEY, EXTERNAL_CACHE_KEY, K8S_CACHE_KEY, KVM_CACHE_KEY, WINDOWS_CACHE_KEY,
 };
 use crate::models::{
-    BurstableResources, BurstableResourcesUpdate, CacheDependencySettings, ChildFilters,
-    ChildFiltersUpdate, Cleanup, CleanupUpdate, Dependencies, DependenciesUpdate, Group,
-    GroupAllowAction, Image, ImageArgs, ImageArgsUpdate, ImageBan, ImageBanKind, ImageBanUpdate,
-    ImageDetailsList, ImageKey, ImageList, ImageListParams, ImageNetworkPolicyUpdate, ImageRequest,
-    ImageScaler, ImageUpdate, Kvm, KvmUpdate, NetworkPolicy, OutputCollection, OutputDisplayType,
-    PipelineBan, PipelineBanKind, PipelineBanUpdate, PipelineKey, Resources, ResourcesUpdate,
-    SecurityContext, SecurityContextUpdate, SpawnLimits, SystemSettings, User,
+    BurstableResources, BurstableResourcesUpdate, CacheDependencySettings, ChildFilterMatcher,
+    ChildFilters, ChildFiltersUpdate, Cleanup, CleanupUpdate, Dependencies, DependenciesUpdate,
+    Group, GroupAllowAction, Image, ImageArgs, ImageArgsUpdate, ImageBan, ImageBanKind,
+    ImageBanUpdate, ImageDetailsList, ImageKey, ImageList, ImageListParams,
+    ImageNetworkPolicyUpdate, ImageRequest, ImageScaler, ImageUpdate, Kvm, KvmUpdate,
+    NetworkPolicy, OutputCollection, OutputDisplayType, PipelineBan, PipelineBanKind,
+    PipelineBanUpdate, PipelineKey, Resources, ResourcesUpdate, SecurityContext,
+    SecurityContextUpdate, SpawnLimits, SystemSettings, User,
 };
 use crate::utils::{ApiError, Shared, bounder};
 use crate::{
@@ -88,7 +88,7 @@ impl ImageDetailsList {
     }
 }
 
-/// Check that all raw regex filters are valid regular expressions
+/// Check that all raw regex filters are valid regular expressions or globs
 ///
 /// # Arguments
 ///
@@ -102,7 +102,11 @@ where
         // iterate only over unique filters in case we have duplicates;
         // we want to avoid compiling more regexes than we need to
         .unique()
-        .filter_map(|raw_regex| Regex::new(raw_regex).err().map(|err| (raw_regex, err)))
+        .filter_map(|raw_regex| {
+            ChildFilterMatcher::compile(raw_regex)
+                .err()
+                .map(|err| (raw_regex, err))
+        })
         .collect();
     if !filter_errors.is_empty() {
         return bad!(format!(
@@ -290,6 +294,7 @@ impl DependenciesUpdate {
         update_clear!(image.dependencies.samples.kwarg, self.samples.clear_kwarg);
         update!(image.dependencies.samples.strategy, self.samples.strategy);
         update!(image.dependencies.samples.naming, self.samples.naming);
+        update!(image.dependencies.samples.verify, self.samples.verify);
         // ephemeral settings
         update!(
             image.dependencies.ephemeral.location,
@@ -9,12 +9,13 @@ use uuid::Uuid;
 use super::db;
 use crate::models::{
     Checkpoint, GenericJob, GenericJobArgs, Group, ImageJobInfo, ImageScaler, JobDetailsList,
-    JobHandleStatus, JobList, JobResets, JobStatus, Pipeline, RawJob, Reaction, RunningJob,
-    StageLogsAdd, Stream, StreamObj, User, WorkerName,
+    JobHandleStatus, JobHeartbeat, JobList, JobResets, JobStarted, JobStatus, Pipeline, RawJob,
+    Reaction, RunningJob, StageLogsAdd, Stream, StreamObj, User, WorkerName,
 };
 use crate::utils::{ApiError, Shared};
 use crate::{
-    deserialize, deserialize_ext, deserialize_opt, extract, is_admin, not_found, serialize,
+    bad, conflict, deserialize, deserialize_ext, deserialize_opt, extract, is_admin, not_found,
+    serialize, unauthorized,
 };
 
 impl JobList {
@@ -87,6 +88,8 @@ impl RawJob {
             args,
             status: JobStatus::Created,
             deadline,
+            started: None,
+            execution_deadline: None,
             worker: None,
             parent: reaction.parent,
             generator: info[&stage.to_owned()].generator,
@@ -132,6 +135,8 @@ impl RawJob {
             args: extract!(raw, "args"),
             status: deserialize_ext!(raw, "status"),
             deadline: deserialize_ext!(raw, "deadline"),
+            started: deserialize_ext!(raw, "started", None),
+            execution_deadline: deserialize_ext!(raw, "execution_deadline", None),
             worker: deserialize_ext!(raw, "worker"),
             parent: deserialize_opt!(raw, "parent", Uuid::parse_str),
             generator: deserialize_ext!(raw, "generator", false),
@@ -201,6 +206,101 @@ impl RawJob {
         db::jobs::proceed(self, runtime, logs, shared).await
     }
 
+    /// Marks that a job has started executing
+    ///
+    /// This starts this job's execution clock based on its image's timeout so that time spent
+    /// waiting in a queue or setting up doesn't count against its runtime. Starting a job again
+    /// resets its execution deadline.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is starting this job
+    /// * `group` - The group this job is tied to
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "RawJob::start", skip_all, fields(job = self.id.to_string()), err(Debug))]
+    pub async fn start(
+        self,
+        user: &User,
+        group: &Group,
+        shared: &Shared,
+    ) -> Result<JobStarted, ApiError> {
+        // make sure this user can start jobs from this group
+        group.editable(user)?;
+        // only jobs that have been claimed can be started
+        if self.status != JobStatus::Running {
+            return conflict!(format!("job {} must be running to start", &self.id));
+        }
+        // get the timeout for this jobs image
+        let image = db::images::get(&group.name, &self.stage, shared).await?;
+        // our execution clock starts now instead of when this job was created
+        let started = Utc::now();
+        let execution_deadline = image
+            .timeout
+            .map(|seconds| started + chrono::Duration::seconds(seconds as i64));
+        db::jobs::start(&self, started, execution_deadline, shared).await?;
+        Ok(JobStarted {
+            started,
+            execution_deadline,
+        })
+    }
+
+    /// Pushes back a running job's execution deadline by its image's timeout
+    ///
+    /// A job can only be extended by up to the configured max heartbeat extension past
+    /// its original execution deadline so that a stuck job can't run forever.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is sending this heartbeat
+    /// * `group` - The group this job is tied to
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "RawJob::heartbeat", skip_all, fields(job = self.id.to_string()), err(Debug))]
+    pub async fn heartbeat(
+        self,
+        user: &User,
+        group: &Group,
+        shared: &Shared,
+    ) -> Result<JobHeartbeat, ApiError> {
+        // make sure this user can edit jobs from this group
+        group.editable(user)?;
+        // only the user that owns this job can keep it alive
+        if self.creator != user.username && !user.is_admin() {
+            return unauthorized!(format!(
+                "Only the owner of job {} can send heartbeats for it",
+                &self.id
+            ));
+        }
+        // only jobs that have been started can be extended
+        let started = match (&self.status, self.started) {
+            (JobStatus::Running, Some(started)) => started,
+            _ => return conflict!(format!("job {} must be started to heartbeat", &self.id)),
+        };
+        // get the timeout for this jobs image
+        let image = db::images::get(&group.name, &self.stage, shared).await?;
+        // jobs without a timeout have no deadline to extend
+        let Some(seconds) = image.timeout else {
+            return Ok(JobHeartbeat {
+                execution_deadline: None,
+            });
+        };
+        let timeout = chrono::Duration::seconds(seconds as i64);
+        // push back our current deadline by another full timeout
+        let original = started + timeout;
+        let execution_deadline = self.execution_deadline.unwrap_or(original) + timeout;
+        // make sure we don't extend this job past our cap
+        let cap = shared.config.thorium.jobs.max_heartbeat_extension;
+        if execution_deadline - original > chrono::Duration::seconds(cap as i64) {
+            return bad!(format!(
+                "job {} cannot be extended more than {cap} seconds past its original deadline",
+                &self.id
+            ));
+        }
+        db::jobs::heartbeat(&self, execution_deadline, shared).await?;
+        Ok(JobHeartbeat {
+            execution_deadline: Some(execution_deadline),
+        })
+    }
+
     /// ApiErrors out a job
     ///
     /// This will set the jobs status to error and fail out the rest of the pipeline.
@@ -425,6 +525,8 @@ impl TryFrom<RawJob> for GenericJob {
             args: deserialize!(&raw.args),
             status: raw.status,
             deadline: raw.deadline,
+            started: raw.started,
+            execution_deadline: raw.execution_deadline,
             parent: raw.parent,
             generator: raw.generator,
             samples: raw.samples,
@@ -46,17 +46,17 @@ impl NetworkPolicy {
             ));
         }
         // make sure the groups exist
-        if !db::groups::exists(&req.groups, shared)
+        let missing = db::groups::exist_many(&req.groups, shared)
             .await
             .map_err(|err| {
                 ApiError::new(
                     err.code,
                     Some(format!("Unable to verify that groups exist: {err}")),
                 )
-            })?
-        {
+            })?;
+        if !missing.is_empty() {
             return not_found!(format!(
-                "One or more of the specified groups doesn't exist!"
+                "One or more of the specified groups doesn't exist: {missing:?}"
             ));
         }
         // check that a network policy with this name doesn't exist in any of the requested groups
@@ -526,12 +526,12 @@ macro_rules! validate_allowed_groups {
             }
             // verify all allowed groups exist
             let all_allowed_groups = all_allowed_groups.into_iter().collect::<Vec<String>>();
-            match db::groups::exists(&all_allowed_groups, $shared).await {
-                Ok(exists) => {
-                    if !exists {
+            match db::groups::exist_many(&all_allowed_groups, $shared).await {
+                Ok(missing) => {
+                    if !missing.is_empty() {
                         return not_found!(format!(
                             "One or more of the specified allowed groups doesn't exist: {:?}",
-                            all_allowed_groups
+                            missing
                         ));
                     }
                 }
@@ -654,17 +654,17 @@ impl NetworkPolicyUpdate {
         // make a set of groups to easily check contains and remove groups
         let mut groups_set: HashSet<String> = network_policy.groups.iter().cloned().collect();
         // make sure all of the added groups exist
-        if !db::groups::exists(&self.add_groups, shared)
+        let missing = db::groups::exist_many(&self.add_groups, shared)
             .await
             .map_err(|err| {
                 ApiError::new(
                     err.code,
                     Some(format!("Unable to verify that added groups exist: {err}")),
                 )
-            })?
-        {
+            })?;
+        if !missing.is_empty() {
             return not_found!(format!(
-                "One or more of the specified groups to add doesn't exist!"
+                "One or more of the specified groups to add doesn't exist: {missing:?}"
             ));
         }
         for group in &self.add_groups {
@@ -7,8 +7,9 @@ use uuid::Uuid;
 
 use crate::models::backends::{db, NotificationSupport};
 use crate::models::{
-    Group, GroupAllowAction, Pipeline, PipelineBanKind, PipelineBanUpdate, PipelineDetailsList,
-    PipelineKey, PipelineList, PipelineRequest, PipelineStats, PipelineUpdate, User,
+    EventTrigger, Group, GroupAllowAction, Pipeline, PipelineBanKind, PipelineBanUpdate,
+    PipelineDetailsList, PipelineKey, PipelineList, PipelinePlan, PipelineRequest, PipelineStats,
+    PipelineTriggerRequest, PipelineUpdate, User,
 };
 use crate::utils::{bounder, ApiError, Shared};
 use crate::{
@@ -199,6 +200,36 @@ impl Pipeline {
         Ok((group_obj, pipeline))
     }
 
+    /// Plan out the jobs this pipeline would create without creating any of them
+    ///
+    /// # Arguments
+    ///
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(
+        name = "Pipeline::get_plan",
+        skip_all,
+        fields(group = &self.group, pipeline = &self.name),
+        err(Debug)
+    )]
+    pub async fn get_plan(&self, shared: &Shared) -> Result<PipelinePlan, ApiError> {
+        // get the unique images in this pipeline
+        let names = self
+            .order
+            .iter()
+            .flatten()
+            .cloned()
+            .collect::<HashSet<String>>()
+            .into_iter()
+            .collect::<Vec<String>>();
+        // get the images whose results each of our images need
+        let results = db::images::list_details(&self.group, &names, shared)
+            .await?
+            .into_iter()
+            .map(|image| (image.name, image.dependencies.results.images))
+            .collect::<HashMap<String, Vec<String>>>();
+        Ok(self.plan(&results))
+    }
+
     /// Lists pipelines in a group
     ///
     /// # Arguments
@@ -348,6 +379,104 @@ impl Pipeline {
         Ok(self)
     }
 
+    /// Adds a trigger to this pipeline
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The trigger to add
+    /// * `user` - The user that is adding this trigger
+    /// * `group` - The group this pipeline is in
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "Pipeline::add_trigger", skip(user, group, shared), err(Debug))]
+    pub async fn add_trigger(
+        mut self,
+        req: PipelineTriggerRequest,
+        user: &User,
+        group: &Group,
+        shared: &Shared,
+    ) -> Result<Self, ApiError> {
+        // make sure this trigger has a name
+        if req.name.is_empty() {
+            return bad!("Trigger names cannot be empty".to_owned());
+        }
+        // make sure we can update this pipeline
+        self.can_update(user, group, shared).await?;
+        // add this trigger checking the saved triggers so concurrent adds can't overwrite it
+        let (name, trigger) = (req.name, req.trigger);
+        let add = |triggers: &mut HashMap<String, EventTrigger>| {
+            // don't silently overwrite an existing trigger
+            if triggers.contains_key(&name) {
+                return conflict!(format!(
+                    "Trigger {name} already exists in {}:{}",
+                    self.group, self.name
+                ));
+            }
+            triggers.insert(name.clone(), trigger.clone());
+            // validate our triggers
+            bounder::triggers(triggers)
+        };
+        self.triggers = db::pipelines::update_triggers(&self, add, shared).await?;
+        Ok(self)
+    }
+
+    /// Deletes a trigger from this pipeline
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the trigger to delete
+    /// * `user` - The user that is deleting this trigger
+    /// * `group` - The group this pipeline is in
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "Pipeline::delete_trigger", skip(user, group, shared), err(Debug))]
+    pub async fn delete_trigger(
+        mut self,
+        name: &str,
+        user: &User,
+        group: &Group,
+        shared: &Shared,
+    ) -> Result<Self, ApiError> {
+        // make sure we can update this pipeline
+        self.can_update(user, group, shared).await?;
+        // remove this trigger from the saved triggers
+        let remove = |triggers: &mut HashMap<String, EventTrigger>| {
+            // make sure this trigger exists
+            if triggers.remove(name).is_none() {
+                return not_found!(format!(
+                    "Trigger {name} does not exist in {}:{}",
+                    self.group, self.name
+                ));
+            }
+            Ok(())
+        };
+        self.triggers = db::pipelines::update_triggers(&self, remove, shared).await?;
+        Ok(self)
+    }
+
+    /// Make sure a user can update this pipeline without changing its images
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is updating this pipeline
+    /// * `group` - The group this pipeline is in
+    /// * `shared` - Shared objects in Thorium
+    async fn can_update(
+        &self,
+        user: &User,
+        group: &Group,
+        shared: &Shared,
+    ) -> Result<(), ApiError> {
+        // make sure we can create pipelines in this group
+        group.allowable(GroupAllowAction::Pipelines)?;
+        // make sure this group is editable
+        group.editable(user)?;
+        // get the scalers for all of our images
+        let images = self.order.iter().flatten().collect::<Vec<&String>>();
+        let scalers = db::images::get_scalers(&self.group, &images, shared).await?;
+        // make sure we can develop for all of these scalers
+        can_develop_many!(user.username, group, &scalers, user);
+        Ok(())
+    }
+
     /// Get the length of a stage in a pipeline
     ///
     /// # Arguments
@@ -2,20 +2,23 @@
 //! Currently only Redis is supported
 
 use aws_sdk_s3::primitives::ByteStream;
+use axum::body::Body;
 use axum::extract::Multipart;
 use chrono::prelude::*;
-use futures::StreamExt;
 use futures::stream;
+use futures::{Stream, StreamExt, TryStreamExt};
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use tracing::{Level, Span, event, instrument, span};
 use uuid::Uuid;
 
 use super::db;
 use crate::models::{
-    BulkReactionResponse, GenericJobArgs, Group, GroupAllowAction, JobList, Pipeline, Reaction,
-    ReactionCache, ReactionCacheUpdate, ReactionDetailsList, ReactionExpire, ReactionList,
-    ReactionRequest, ReactionStatus, ReactionUpdate, Repo, RepoDependency, Sample, StageLogs,
-    StageLogsAdd, StatusUpdate, User,
+    BulkReactionResponse, GenericJobArgs, Group, GroupAllowAction, JobList, Pipeline,
+    REACTION_PRIORITY_MAX, REACTION_PRIORITY_MIN, Reaction, ReactionCache, ReactionCacheUpdate,
+    ReactionDetailsList, ReactionExpire, ReactionList, ReactionRequest, ReactionSamplesAdd,
+    ReactionStatus, ReactionUpdate, Repo, RepoDependency, Sample, StageLogs, StageLogsAdd,
+    StatusUpdate, User, ZipDownloadParams,
 };
 use crate::utils::{ApiError, Shared, bounder};
 use crate::{
@@ -23,6 +26,15 @@ use crate::{
     not_found, unauthorized,
 };
 
+/// The max number of status updates that can be appended in a single batch
+const MAX_STATUS_LOG_BATCH: usize = 1000;
+
+/// The number of reaction details to get at once when streaming details
+const DETAILS_STREAM_CHUNK: usize = 100;
+
+/// The number of seconds each point of priority shifts a reaction by when scheduling
+const PRIORITY_WEIGHT: i64 = 3600;
+
 /// Updates to apply to cache files
 pub struct InternalReactionCacheFileUpdates {
     /// New or updated reaction cache files
@@ -47,6 +59,42 @@ impl InternalReactionCacheFileUpdates {
 }
 
 impl ReactionRequest {
+    /// Set this requests group to the users default group if a group wasn't set
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user creating this reaction
+    pub fn resolve_group(&mut self, user: &User) -> Result<(), ApiError> {
+        if self.group.is_none() {
+            // fall back to this users default group
+            match &user.settings.default_group {
+                // make sure this user wasn't removed from their default group since setting it
+                Some(default) if !user.groups.contains(default) => {
+                    return bad!(format!(
+                        "A group must be set for reactions to {} because {} is no longer a member of their default group {default}",
+                        self.pipeline, user.username
+                    ));
+                }
+                Some(default) => self.group = Some(default.clone()),
+                None => {
+                    return bad!(format!(
+                        "A group must be set for reactions to {} because {} has no default group",
+                        self.pipeline, user.username
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the group this request is for
+    ///
+    /// This is empty if [`ReactionRequest::resolve_group`] has not been called
+    /// on a request without a group.
+    pub(crate) fn group_name(&self) -> &str {
+        self.group.as_deref().unwrap_or_default()
+    }
+
     /// Validate we are allowed to overwrite args for any images we try to
     ///
     /// # Arguments
@@ -74,7 +122,7 @@ impl ReactionRequest {
         // only validate reactions if we found some overrides
         if !overrides.is_empty() {
             // get these images scalers
-            let scalers = db::images::get_scalers(&self.group, &overrides, shared).await?;
+            let scalers = db::images::get_scalers(&group.name, &overrides, shared).await?;
             // make sure we have the right developer roles
             if group.developer_many(user, &scalers).is_err() {
                 return unauthorized!();
@@ -83,6 +131,24 @@ impl ReactionRequest {
         Ok(())
     }
 
+    /// Validate we are allowed to set this reaction's priority
+    ///
+    /// Priority shifts the deadlines of this reaction's jobs which are shared with every
+    /// other group, so only admins can set a nonzero priority.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user trying to set a priority
+    pub fn can_prioritize(&self, user: &User) -> Result<(), ApiError> {
+        if self.priority != 0 && !user.is_admin() {
+            return unauthorized!(format!(
+                "Only admins can set a reaction priority! Got a priority of {}",
+                self.priority
+            ));
+        }
+        Ok(())
+    }
+
     /// Uploads any ephemeral files required for this sample to execute
     ///
     /// # Arguments
@@ -170,7 +236,7 @@ impl ReactionRequest {
         // create job instance
         let cast = Reaction {
             id,
-            group: self.group,
+            group: pipeline.group.clone(),
             pipeline: self.pipeline,
             creator: user.username.clone(),
             status: ReactionStatus::Created,
@@ -191,6 +257,10 @@ impl ReactionRequest {
             repos,
             trigger_depth: self.trigger_depth,
             has_cache: !self.cache.is_empty(),
+            idempotency_key: self.idempotency_key,
+            priority: self
+                .priority
+                .clamp(REACTION_PRIORITY_MIN, REACTION_PRIORITY_MAX),
         };
         Ok((cast, self.cache, pipeline))
     }
@@ -224,6 +294,37 @@ impl ReactionList {
         let details_list = ReactionDetailsList::new(self.cursor, details);
         Ok(details_list)
     }
+
+    /// Turns a [`ReactionList`] into a stream of [`Reaction`] details
+    ///
+    /// Details are retrieved in small chunks as the stream is polled instead of all at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group these reactions are from
+    /// * `shared` - Shared Thorium objects
+    pub(crate) fn details_stream(
+        self,
+        group: String,
+        shared: Arc<Shared>,
+    ) -> impl Stream<Item = Result<Reaction, ApiError>> + Send + 'static {
+        // split our reaction names into chunks to get details for
+        let chunks = self
+            .names
+            .chunks(DETAILS_STREAM_CHUNK)
+            .map(<[String]>::to_vec)
+            .collect::<Vec<Vec<String>>>();
+        stream::iter(chunks)
+            .then(move |chunk| {
+                // clone our group and shared objects for this chunk
+                let group = group.clone();
+                let shared = shared.clone();
+                async move { db::reactions::list_details(&group, &chunk, &shared).await }
+            })
+            // flatten our chunks of details into a stream of single reactions
+            .map_ok(|details| stream::iter(details.into_iter().map(Ok)))
+            .try_flatten()
+    }
 }
 
 impl ReactionDetailsList {
@@ -239,8 +340,24 @@ impl ReactionDetailsList {
 }
 
 impl Reaction {
+    /// Get the number of seconds this reaction's priority shifts it by when scheduling
+    pub(crate) fn priority_offset(&self) -> i64 {
+        i64::from(self.priority) * PRIORITY_WEIGHT
+    }
+
+    /// Get the score to order this reaction by in its group's status sets
+    ///
+    /// Group status sets are listed highest score first so higher priority reactions
+    /// are returned before lower priority reactions with a similar SLA.
+    pub(crate) fn group_score(&self) -> i64 {
+        self.sla.timestamp() + self.priority_offset()
+    }
+
     /// Creates a new reaction
     ///
+    /// If a reaction with the same idempotency key already exists in this group then that
+    /// reaction is returned instead along with `true`.
+    ///
     /// # Arguments
     ///
     /// * `user` - The user that is creating this reaction
@@ -255,7 +372,7 @@ impl Reaction {
         pipeline: &Pipeline,
         request: ReactionRequest,
         shared: &Shared,
-    ) -> Result<Reaction, ApiError> {
+    ) -> Result<(Reaction, bool), ApiError> {
         // log the group and pipeline we are creating a reaction for
         event!(Level::INFO, group = &group.name, pipeline = &pipeline.name);
         // make sure we can create reactions in this group
@@ -269,8 +386,33 @@ impl Reaction {
         }
         // make sure we are allowed to override any args we try too
         request.can_override(user, group, shared).await?;
-        // add reaction to backend
-        db::reactions::create(user, request, pipeline, shared).await
+        // make sure we are allowed to set any priority we try too
+        request.can_prioritize(user)?;
+        // add reaction to backend or get the existing one if this is a retry
+        let (reaction, deduplicated) =
+            db::reactions::create(user, request, pipeline, shared).await?;
+        // track that we created a reaction
+        if !deduplicated {
+            shared.metrics.reactions_created(1);
+        }
+        Ok((reaction, deduplicated))
+    }
+
+    /// Make sure a bulk reaction request is not over our configured limit
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of reactions being created
+    /// * `shared` - Shared objects in Thorium
+    fn check_bulk_limit(count: usize, shared: &Shared) -> Result<(), ApiError> {
+        // get the max number of reactions that can be created at once
+        let limit = shared.config.thorium.request_size_limits.bulk_reactions;
+        if count > limit {
+            return bad!(format!(
+                "Unable to create {count} reactions in one request! The max is {limit}."
+            ));
+        }
+        Ok(())
     }
 
     /// Creates a new reactions in bulk
@@ -283,26 +425,43 @@ impl Reaction {
     #[instrument(name = "Reactions::create_bulk", skip_all, err(Debug))]
     pub async fn create_bulk(
         user: &User,
-        requests: Vec<ReactionRequest>,
+        mut requests: Vec<ReactionRequest>,
         shared: &Shared,
     ) -> Result<BulkReactionResponse, ApiError> {
-        // build cache of all different pipelines and groups we are creating reactions for
+        // make sure this request isn't too large before we do any work
+        Self::check_bulk_limit(requests.len(), shared)?;
+        // use this users default group for any requests without a group
+        for req in &mut requests {
+            req.resolve_group(user)?;
+            // make sure we are allowed to set any priority we try too
+            req.can_prioritize(user)?;
+        }
+        // get the unique groups we are creating reactions in
+        let names = requests
+            .iter()
+            .map(|req| req.group_name().to_owned())
+            .collect::<HashSet<String>>()
+            .into_iter()
+            .collect::<Vec<String>>();
+        // authorize all of these groups at once instead of once per pipeline
+        let group_cache = Group::authorize_all(user, &names, shared)
+            .await?
+            .into_iter()
+            .map(|group| (group.name.clone(), group))
+            .collect::<HashMap<String, Group>>();
+        // build cache of all different pipelines we are creating reactions for
         let mut pipe_cache = HashMap::with_capacity(1);
-        let mut group_cache = HashMap::with_capacity(1);
         // build the map of images we are overriding args for
-        let mut override_cache: HashMap<&String, HashSet<&String>> = HashMap::default();
+        let mut override_cache: HashMap<&str, HashSet<&String>> = HashMap::default();
         for req in &requests {
             // build combined group + pipeline key
-            let key = format!("{}:{}", &req.group, &req.pipeline);
+            let key = format!("{}:{}", req.group_name(), &req.pipeline);
             // get pipeline if we don't have it in the cache
             if let std::collections::hash_map::Entry::Vacant(e) = pipe_cache.entry(key) {
-                // get this group and pipeline
-                let (group, pipeline) =
-                    Pipeline::get(user, &req.group, &req.pipeline, shared).await?;
+                // get this pipeline now that we know we can access its group
+                let pipeline = db::pipelines::get(req.group_name(), &req.pipeline, shared).await?;
                 // insert into cache and set
                 e.insert(pipeline);
-                // check if this group is also not in the cache
-                group_cache.entry(&req.group).or_insert(group);
                 // get the images we are overriding args for
                 let overrides = req
                     .args
@@ -318,7 +477,7 @@ impl Reaction {
                 if !overrides.is_empty() {
                     // get an entry to this groups override set
                     let entry = override_cache
-                        .entry(&req.group)
+                        .entry(req.group_name())
                         .or_insert_with(|| HashSet::with_capacity(overrides.len()));
                     // add our images
                     entry.extend(overrides.into_iter());
@@ -360,7 +519,10 @@ impl Reaction {
             }
         }
         // add reaction to backend
-        db::reactions::create_bulk(user, requests, &pipe_cache, shared).await
+        let resp = db::reactions::create_bulk(user, requests, &pipe_cache, shared).await?;
+        // track the reactions we created
+        shared.metrics.reactions_created(resp.created.len());
+        Ok(resp)
     }
 
     /// Creates a new reactions in bulk for different users
@@ -378,6 +540,21 @@ impl Reaction {
     ) -> Result<HashMap<String, BulkReactionResponse>, ApiError> {
         // only admins can create reactions for other users
         is_admin!(user);
+        // make sure this request isn't too large before we do any work
+        let limit = shared
+            .config
+            .thorium
+            .request_size_limits
+            .bulk_reaction_users;
+        if requests.len() > limit {
+            return bad!(format!(
+                "Unable to create reactions for {} users in one request! The max is {limit}.",
+                requests.len()
+            ));
+        }
+        for reqs in requests.values() {
+            Self::check_bulk_limit(reqs.len(), shared)?;
+        }
         // build a map of reaction creation responses by user
         let mut resp = HashMap::with_capacity(requests.len());
         // create each users reactions
@@ -432,6 +609,45 @@ impl Reaction {
         db::reactions::logs(self, cursor, limit, shared).await
     }
 
+    /// Appends a batch of status logs to this reaction
+    ///
+    /// The group, pipeline, and reaction of each update are set from this reaction but
+    /// their order and timestamps are preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is adding status logs
+    /// * `group` - The group this reaction is in
+    /// * `updates` - The status updates to append
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "Reactions::add_status_logs", skip_all, fields(count = updates.len()), err(Debug))]
+    pub async fn add_status_logs(
+        &self,
+        user: &User,
+        group: &Group,
+        mut updates: Vec<StatusUpdate>,
+        shared: &Shared,
+    ) -> Result<(), ApiError> {
+        // make sure we can edit reactions in this group
+        group.allowable(GroupAllowAction::Reactions)?;
+        // make sure this user can modify this reaction
+        can_modify!(self.creator, group, user);
+        // make sure this batch isn't too large
+        if updates.len() > MAX_STATUS_LOG_BATCH {
+            return bad!(format!(
+                "Status log batches can contain at most {MAX_STATUS_LOG_BATCH} updates"
+            ));
+        }
+        // make sure these updates are all written to this reactions status log
+        let id = self.id.to_string();
+        for update in &mut updates {
+            update.group.clone_from(&self.group);
+            update.pipeline.clone_from(&self.pipeline);
+            update.reaction.clone_from(&id);
+        }
+        db::reactions::add_status_logs(&updates, shared).await
+    }
+
     /// Adds logs for a specific stage within a pipeline
     ///
     /// This is for stage logs not status logs for an entire reaction.
@@ -541,6 +757,46 @@ impl Reaction {
         db::reactions::list_tag(&group.name, tag, cursor, limit, shared).await
     }
 
+    /// Lists reactions that have all of the specified tags
+    ///
+    /// Reaction tags are flat so a tag key without values matches the tag `key` while each
+    /// value matches the tag `key=value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group to list reactions from
+    /// * `tags` - The tags reactions must have
+    /// * `cursor` - The page of reactions to retrieve
+    /// * `limit` - The max number of reactions to retrieve (weakly enforced)
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "Reaction::list_tags", skip(group, shared), fields(group = &group.name), err(Debug))]
+    pub async fn list_tags(
+        group: &Group,
+        tags: &HashMap<String, Vec<String>>,
+        cursor: usize,
+        limit: usize,
+        shared: &Shared,
+    ) -> Result<ReactionList, ApiError> {
+        // flatten our tags into the tags set on reactions
+        let mut flat = Vec::with_capacity(tags.len());
+        for (key, values) in tags {
+            if values.is_empty() {
+                flat.push(key.clone());
+            } else {
+                flat.extend(values.iter().map(|value| format!("{key}={value}")));
+            }
+        }
+        // make sure we have at least one tag to filter on
+        if flat.is_empty() {
+            return bad!("At least one tag must be specified".to_owned());
+        }
+        // sort our tags so our cursor always crawls the same tag set
+        flat.sort_unstable();
+        flat.dedup();
+        // use correct backend to list reaction names
+        db::reactions::list_tags(&group.name, &flat, cursor, limit, shared).await
+    }
+
     /// Lists reactions for an entire group with a set status
     ///
     /// # Arguments
@@ -791,6 +1047,59 @@ impl Reaction {
         Ok(self)
     }
 
+    /// Adds samples to a [`Reaction`] that has not finished yet
+    ///
+    /// Jobs for later stages will download these samples along with the original ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is adding samples to this reaction
+    /// * `group` - The group this reaction is in
+    /// * `add` - The samples to add to this reaction
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "Reaction::add_samples", skip_all, fields(count = add.samples.len()), err(Debug))]
+    pub async fn add_samples(
+        mut self,
+        user: &User,
+        group: &Group,
+        add: ReactionSamplesAdd,
+        shared: &Shared,
+    ) -> Result<Reaction, ApiError> {
+        // make sure we can edit reactions in this group
+        group.allowable(GroupAllowAction::Reactions)?;
+        // make sure this user can modify this reaction
+        can_modify!(self.creator, group, user);
+        // finished reactions will never spawn another job so don't let samples be added to them
+        if matches!(
+            self.status,
+            ReactionStatus::Completed | ReactionStatus::Failed
+        ) {
+            return bad!(format!(
+                "Samples cannot be added to a {} reaction",
+                self.status
+            ));
+        }
+        // skip any samples this reaction already has
+        let mut added: Vec<String> = Vec::with_capacity(add.samples.len());
+        for sample in add.samples {
+            if !self.samples.contains(&sample) && !added.contains(&sample) {
+                added.push(sample);
+            }
+        }
+        // if we have no new samples then there is nothing to do
+        if added.is_empty() {
+            return Ok(self);
+        }
+        // make sure this user has access to all of the samples they are adding
+        Sample::authorize(user, &added, shared).await?;
+        // add our new samples and their sha256 tags to this reaction
+        self.samples.extend(added.iter().cloned());
+        self.tags.extend(added.iter().cloned());
+        // save our new samples to the backend
+        db::reactions::add_samples(&self, &added, add.update_jobs, shared).await?;
+        Ok(self)
+    }
+
     /// Downloads an ephemeral file tied to a reaction
     ///
     /// # Arguments
@@ -972,6 +1281,37 @@ impl Reaction {
         // download this attachment
         shared.s3.reaction_cache.download(&s3_path).await
     }
+
+    /// Downloads all of the cache files tied to a reaction as a single encrypted zip
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The params to use when building this zip
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "Reaction::download_cache_zip", skip(self, shared), err(Debug))]
+    pub async fn download_cache_zip(
+        &self,
+        params: ZipDownloadParams,
+        shared: &Shared,
+    ) -> Result<Body, ApiError> {
+        // get our current cache
+        let cache = self.get_cache(shared).await?;
+        // build the name and path in s3 for each of our cache files
+        let entries = cache
+            .files
+            .into_iter()
+            .map(|name| {
+                let s3_path = format!("{}/files/{name}", self.id);
+                (name, s3_path)
+            })
+            .collect::<Vec<(String, String)>>();
+        // download and zip all of our cache files
+        let zip = shared
+            .s3
+            .reaction_cache
+            .download_many_as_zip(entries, params, shared);
+        Ok(zip)
+    }
 }
 
 /// This should probably a TryFrom but I am unsure how to enforce that ApiError implements Deserialize
@@ -1039,6 +1379,8 @@ impl TryFrom<(HashMap<String, String>, Vec<String>, Vec<String>)> for Reaction {
             repos: deserialize_ext!(map, "repos", Vec::default()),
             trigger_depth: deserialize_opt!(map, "trigger_depth"),
             has_cache: deserialize_ext!(map, "has_cache", false),
+            idempotency_key: deserialize_opt!(map, "idempotency_key"),
+            priority: deserialize_ext!(map, "priority", 0),
         };
         Ok(reaction)
     }
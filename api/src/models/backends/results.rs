@@ -15,7 +15,7 @@ use crate::models::backends::OutputSupport;
 use crate::models::{
     AutoTag, AutoTagUpdate, ImageVersion, Output, OutputChunk, OutputCollection,
     OutputCollectionUpdate, OutputDisplayType, OutputForm, OutputFormBuilder, OutputKind,
-    OutputMap, OutputRow, Repo, ResultGetParams, Sample, User,
+    OutputMap, OutputRenderers, OutputRow, Repo, ResultGetParams, Sample, User,
 };
 use crate::utils::{ApiError, Shared, bounder};
 use crate::{bad, deserialize, update, update_clear, update_opt};
@@ -227,7 +227,8 @@ impl OutputMap {
     ///
     /// * `row` - The output to add to this map
     /// * `groups` - The groups this result is from
-    pub(super) fn add(&mut self, row: OutputRow, groups: Vec<String>) {
+    /// * `renderers` - The renderers to describe how to display this output with
+    pub(super) fn add(&mut self, row: OutputRow, groups: Vec<String>, renderers: &OutputRenderers) {
         // get an entry to this tools command map
         let results = self.results.entry(row.tool.clone()).or_default();
         // try to deserialize our string as a json Value
@@ -235,6 +236,8 @@ impl OutputMap {
             Ok(value) => (value, None),
             Err(e) => (serde_json::Value::String(row.result), Some(e.to_string())),
         };
+        // get the metadata on how to render this output
+        let renderer = renderers.get(row.display_type, &row.tool);
         // build our output object for this row
         let output = Output {
             id: row.id,
@@ -246,6 +249,7 @@ impl OutputMap {
             result,
             files: row.files.unwrap_or_default(),
             display_type: row.display_type,
+            renderer: Some(renderer),
             children: row.children.unwrap_or_default(),
         };
         // push our results
@@ -5,11 +5,15 @@ use axum::http::request::Parts;
 use chrono::{DateTime, TimeZone, Utc};
 
 use super::db;
+use crate::bad;
 use crate::models::{ApiCursor, ElasticDoc, ElasticIndex, ElasticSearchParams, User};
 use crate::utils::{ApiError, Shared};
 
 pub mod events;
 
+/// The most characters that can be in a single search snippet
+const MAX_SNIPPET_SIZE: usize = 1000;
+
 /// Search results in elastic and return a list of sha256s
 ///
 /// # Arguments
@@ -22,10 +26,31 @@ pub async fn search(
     mut params: ElasticSearchParams,
     shared: &Shared,
 ) -> Result<ApiCursor<ElasticDoc>, ApiError> {
+    // make sure our snippet settings are sane
+    if params.snippets && (params.snippet_size == 0 || params.snippet_size > MAX_SNIPPET_SIZE) {
+        return bad!(format!(
+            "Snippets must be between 1 and {MAX_SNIPPET_SIZE} characters!"
+        ));
+    }
     // authorize the groups to list files from
     user.authorize_groups(&mut params.groups, shared).await?;
+    // get our snippet settings before our params are consumed
+    let snippets = params.snippets.then(|| {
+        (
+            params.snippet_size,
+            std::mem::take(&mut params.snippet_pre_tag),
+            std::mem::take(&mut params.snippet_post_tag),
+        )
+    });
     // search for results documents in elastic
-    db::search::search(params, shared).await
+    let mut cursor = db::search::search(params, shared).await?;
+    // build a highlighted snippet for each document if they were requested
+    if let Some((size, pre_tag, post_tag)) = snippets {
+        for doc in &mut cursor.data {
+            doc.build_snippet(size, &pre_tag, &post_tag);
+        }
+    }
+    Ok(cursor)
 }
 
 impl ElasticSearchParams {
@@ -3,7 +3,9 @@
 mod elastic_setup;
 pub mod redis_setup;
 mod scylla_setup;
+mod self_check;
 
 pub use elastic_setup::elastic;
 pub use redis_setup::redis;
 pub use scylla_setup::Scylla;
+pub use self_check::{check_bucket, check_keyspace, check_settings, self_check};
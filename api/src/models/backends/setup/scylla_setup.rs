@@ -4,8 +4,17 @@ use chrono::prelude::*;
 use chrono::Duration;
 use entities::EntitiesPreparedStatements;
 use futures::{poll, task::Poll};
+use scylla::client::PoolSize;
+use scylla::client::pager::QueryPager;
 use scylla::client::session::Session;
 use scylla::client::session_builder::{GenericSessionBuilder, SessionBuilder};
+use scylla::errors::{ExecutionError, PagerExecutionError};
+use scylla::response::query_result::QueryResult;
+use scylla::serialize::row::SerializeRow;
+use scylla::statement::prepared::PreparedStatement;
+use scylla::statement::unprepared::Statement;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration as StdDuration;
 
 mod associations;
@@ -39,6 +48,7 @@ use samples::SamplesPreparedStatements;
 use tags::TagsPreparedStatements;
 //use tools::ToolsPreparedStatements;
 
+use crate::utils::metrics::ScyllaPoolStats;
 use crate::{setup, Conf};
 
 /// The diffferent groups of prepared statements for scylla
@@ -116,12 +126,121 @@ impl ScyllaPreparedStatements {
     }
 }
 
+/// Tracks a request to Scylla as in flight until it is dropped
+struct InFlight<'a>(&'a AtomicU64);
+
+impl<'a> InFlight<'a> {
+    /// Start tracking a request as in flight
+    ///
+    /// # Arguments
+    ///
+    /// * `counter` - The number of requests in flight
+    fn new(counter: &'a AtomicU64) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        InFlight(counter)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    /// Stop tracking this request even if it was cancelled
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A scylla session that tracks how many requests are in flight
+///
+/// The driver doesn't report in flight requests itself so the requests Thorium sends
+/// are counted here. Paged requests are only counted until their first page is returned.
+pub struct TrackedSession {
+    /// The session to send requests with
+    session: Session,
+    /// The number of requests currently in flight
+    in_flight: AtomicU64,
+}
+
+impl TrackedSession {
+    /// Wrap a session to track its requests
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The session to wrap
+    fn new(session: Session) -> Self {
+        TrackedSession {
+            session,
+            in_flight: AtomicU64::new(0),
+        }
+    }
+
+    /// Get the number of requests currently in flight
+    #[must_use]
+    pub fn in_flight(&self) -> u64 {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Execute a prepared statement without paging
+    ///
+    /// # Arguments
+    ///
+    /// * `prepared` - The prepared statement to execute
+    /// * `values` - The values to bind to this statement
+    pub async fn execute_unpaged(
+        &self,
+        prepared: &PreparedStatement,
+        values: impl SerializeRow,
+    ) -> Result<QueryResult, ExecutionError> {
+        let _in_flight = InFlight::new(&self.in_flight);
+        self.session.execute_unpaged(prepared, values).await
+    }
+
+    /// Execute a prepared statement and page through its results
+    ///
+    /// # Arguments
+    ///
+    /// * `prepared` - The prepared statement to execute
+    /// * `values` - The values to bind to this statement
+    pub async fn execute_iter(
+        &self,
+        prepared: impl Into<PreparedStatement>,
+        values: impl SerializeRow,
+    ) -> Result<QueryPager, PagerExecutionError> {
+        let _in_flight = InFlight::new(&self.in_flight);
+        self.session.execute_iter(prepared, values).await
+    }
+
+    /// Execute an unprepared statement without paging
+    ///
+    /// # Arguments
+    ///
+    /// * `statement` - The statement to execute
+    /// * `values` - The values to bind to this statement
+    pub async fn query_unpaged(
+        &self,
+        statement: impl Into<Statement>,
+        values: impl SerializeRow,
+    ) -> Result<QueryResult, ExecutionError> {
+        let _in_flight = InFlight::new(&self.in_flight);
+        self.session.query_unpaged(statement, values).await
+    }
+}
+
+impl Deref for TrackedSession {
+    type Target = Session;
+
+    /// Use the wrapped session for anything we don't track
+    fn deref(&self) -> &Self::Target {
+        &self.session
+    }
+}
+
 /// The scylla client and prepared statments
 pub struct Scylla {
     /// The scylla session object
-    pub session: Session,
+    pub session: TrackedSession,
     /// prepared statements for scylla
     pub prep: ScyllaPreparedStatements,
+    /// The size of the connection pool we keep to each node
+    pub pool_size: PoolSize,
 }
 
 impl Scylla {
@@ -176,6 +295,44 @@ impl Scylla {
         // panic if we fail to connect
         panic!("Failed to connect/setup Scylla");
     }
+
+    /// Get a snapshot of our connection pool and the requests sent through it
+    #[must_use]
+    pub fn pool_stats(&self) -> ScyllaPoolStats {
+        let metrics = self.session.get_metrics();
+        // get the number of connections we keep to each host or shard
+        let (pool_per, pool_size) = match self.pool_size {
+            PoolSize::PerHost(size) => ("host", size.get() as u64),
+            PoolSize::PerShard(size) => ("shard", size.get() as u64),
+        };
+        ScyllaPoolStats {
+            nodes: self.session.get_cluster_state().get_nodes_info().len() as u64,
+            connections: metrics.get_total_connections(),
+            pool_size,
+            pool_per,
+            in_flight: self.session.in_flight(),
+            requests: metrics.get_queries_num() + metrics.get_queries_iter_num(),
+            errors: metrics.get_errors_num() + metrics.get_errors_iter_num(),
+            retries: metrics.get_retries_num(),
+            connection_timeouts: metrics.get_connection_timeouts(),
+            request_timeouts: metrics.get_request_timeouts(),
+            // we have no latency to report until a request has completed
+            latency_avg_ms: metrics.get_latency_avg_ms().unwrap_or_default(),
+        }
+    }
+}
+
+/// Get the size of the connection pool to keep to each node
+///
+/// # Arguments
+///
+/// * `config` - The thorium config
+fn pool_size(config: &Conf) -> PoolSize {
+    match (config.scylla.connections_per_node, config.scylla.pool_size) {
+        (Some(per_node), _) => PoolSize::PerHost(per_node),
+        (None, Some(per_shard)) => PoolSize::PerShard(per_shard),
+        (None, None) => PoolSize::default(),
+    }
 }
 
 /// Create a new session to scylla
@@ -203,6 +360,8 @@ pub async fn new_session(config: &Conf) -> Session {
     // set our request timeout
     let session =
         session.connection_timeout(StdDuration::from_secs(u64::from(config.scylla.setup_time)));
+    // size our connection pools
+    let session = session.pool_size(pool_size(config));
     // build our session
     config
         .scylla
@@ -244,5 +403,9 @@ async fn build(config: Conf) -> Scylla {
     // get our tables/materialized views and prepared statements
     let prep = ScyllaPreparedStatements::new(&session, &config).await;
     // build our scylla client
-    Scylla { session, prep }
+    Scylla {
+        session: TrackedSession::new(session),
+        prep,
+        pool_size: pool_size(&config),
+    }
 }
@@ -11,6 +11,8 @@ pub struct AssociationsPreparedStatements {
     pub insert: PreparedStatement,
     /// Delete an association
     pub delete: PreparedStatement,
+    /// Archive or restore an association
+    pub archive: PreparedStatement,
     /// List the ties for a associations cursor
     pub list_ties: PreparedStatement,
     /// Get a page of data for a associations cursor
@@ -31,12 +33,14 @@ impl AssociationsPreparedStatements {
         // build our prepared statements
         let insert = insert(session, config).await;
         let delete = delete(session, config).await;
+        let archive = archive(session, config).await;
         let list_ties = list_ties(session, config).await;
         let list_pull = list_pull(session, config).await;
         // build our prepared statements object
         AssociationsPreparedStatements {
             insert,
             delete,
+            archive,
             list_ties,
             list_pull,
         }
@@ -61,6 +65,8 @@ async fn setup_associations_table(session: &Session, config: &Conf) {
           submitter TEXT, \
           extra_source TEXT, \
           extra_target TEXT, \
+          confidence INT, \
+          archived_at TIMESTAMP, \
           PRIMARY KEY ((group, year, bucket, source), created, target, direction))
           WITH CLUSTERING ORDER BY (created DESC)",
         ns = &config.thorium.namespace
@@ -69,6 +75,47 @@ async fn setup_associations_table(session: &Session, config: &Conf) {
         .query_unpaged(table_create, &[])
         .await
         .expect("Failed to add assocations table");
+    // make sure tables created before these columns were added have them
+    // existing rows will have a null confidence which is read as full confidence
+    add_column(session, config, "confidence", "INT").await;
+    add_column(session, config, "archived_at", "TIMESTAMP").await;
+}
+
+/// Add a column to an associations table that predates it
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `config` - The Thorium config
+/// * `column` - The name of the column to add
+/// * `kind` - The type of the column to add
+async fn add_column(session: &Session, config: &Conf, column: &str, kind: &str) {
+    // check if our associations table already has this column
+    let query = session
+        .query_unpaged(
+            "SELECT column_name FROM system_schema.columns \
+                WHERE keyspace_name = ? \
+                AND table_name = 'associations' \
+                AND column_name = ?",
+            (&config.thorium.namespace, column),
+        )
+        .await
+        .expect("Failed to check for an associations column");
+    // cast this query to a rows query
+    let query_rows = query
+        .into_rows_result()
+        .expect("Failed to read the associations table columns");
+    // only add our column if it doesn't already exist
+    if query_rows.rows_num() == 0 {
+        let table_alter = format!(
+            "ALTER TABLE {ns}.associations ADD {column} {kind}",
+            ns = &config.thorium.namespace
+        );
+        session
+            .query_unpaged(table_alter, &[])
+            .await
+            .expect("Failed to add a column to the associations table");
+    }
 }
 
 /// build the associations insert prepared statement
@@ -82,8 +129,8 @@ async fn insert(session: &Session, config: &Conf) -> PreparedStatement {
     session
         .prepare(format!(
             "INSERT INTO {}.associations \
-                (group, year, bucket, created, direction, kind, source, target, submitter, extra_source, extra_target) \
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                (group, year, bucket, created, direction, kind, source, target, submitter, extra_source, extra_target, confidence) \
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             &config.thorium.namespace
         ))
         .await
@@ -114,6 +161,31 @@ async fn delete(session: &Session, config: &Conf) -> PreparedStatement {
         .expect("Failed to prepare associations delete statement")
 }
 
+/// build the associations archive prepared statement
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `config` - The Thorium config
+async fn archive(session: &Session, config: &Conf) -> PreparedStatement {
+    // build associations archive prepared statement
+    session
+        .prepare(format!(
+            "UPDATE {}.associations \
+                SET archived_at = ? \
+                WHERE group in ? \
+                AND year = ? \
+                AND bucket = ? \
+                AND source = ? \
+                AND created = ? \
+                AND target = ? \
+                AND direction = ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare associations archive statement")
+}
+
 /// Gets any remaining rows from past ties in listing associations
 ///
 /// # Arguments
@@ -124,7 +196,7 @@ async fn list_ties(session: &Session, config: &Conf) -> PreparedStatement {
     // build associations list ties prepared statement
     session
         .prepare(format!(
-            "SELECT group, kind, source, target, submitter, created, direction, extra_source, extra_target \
+            "SELECT group, kind, source, target, submitter, created, direction, extra_source, extra_target, confidence, archived_at \
                 FROM {}.associations \
                 WHERE group = ? \
                 AND year = ? \
@@ -149,7 +221,7 @@ async fn list_pull(session: &Session, config: &Conf) -> PreparedStatement {
     // build associations list ties prepared statement
     session
         .prepare(format!(
-            "SELECT group, kind, source, target, submitter, created, direction, extra_source, extra_target \
+            "SELECT group, kind, source, target, submitter, created, direction, extra_source, extra_target, confidence, archived_at \
                 FROM {}.associations \
                 WHERE group = ? \
                 AND year = ? \
@@ -22,6 +22,10 @@ pub struct EntitiesPreparedStatements {
     /// Get the remaining rows from a tie where rows have the same primary key
     /// except for their ids
     pub list_ties: PreparedStatement,
+    /// Get info for listing entities created by a specific user
+    pub list_pull_submitter: PreparedStatement,
+    /// Get the remaining rows from a tie when listing entities created by a specific user
+    pub list_ties_submitter: PreparedStatement,
     /// Gets entities' names from their ids
     ///
     /// Used for supplementing name data when listing by tag
@@ -49,6 +53,8 @@ impl EntitiesPreparedStatements {
         let exists_groups = exists_groups(session, config).await;
         let list_pull = list_pull(session, config).await;
         let list_ties = list_ties(session, config).await;
+        let list_pull_submitter = list_pull_submitter(session, config).await;
+        let list_ties_submitter = list_ties_submitter(session, config).await;
         let get_names_kinds_by_ids = get_names_kinds_by_ids(session, config).await;
         Self {
             insert,
@@ -58,6 +64,8 @@ impl EntitiesPreparedStatements {
             exists_groups,
             list_pull,
             list_ties,
+            list_pull_submitter,
+            list_ties_submitter,
             get_names_kinds_by_ids,
         }
     }
@@ -297,6 +305,60 @@ async fn list_pull(session: &Session, config: &Conf) -> PreparedStatement {
         .expect("Failed to prepare scylla entity list pull statement")
 }
 
+/// Gets any remaining rows from past ties when listing a specific user's entities
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn list_ties_submitter(session: &Session, config: &Conf) -> PreparedStatement {
+    // build entity list ties by submitter prepared statement
+    session
+        .prepare(format!(
+            "SELECT kind, group, created, id, name \
+                FROM {}.entities \
+                WHERE kind in ? \
+                AND group = ? \
+                AND year = ? \
+                AND bucket = ? \
+                AND created = ? \
+                AND id <= ? \
+                AND submitter = ? \
+                LIMIT ? \
+                ALLOW FILTERING",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla entity list ties by submitter statement")
+}
+
+/// Pulls the data for listing a specific user's entities in Thorium
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn list_pull_submitter(session: &Session, config: &Conf) -> PreparedStatement {
+    // build entity list pull by submitter prepared statement
+    session
+        .prepare(format!(
+            "SELECT kind, group, created, id, name \
+                FROM {}.entities \
+                WHERE kind in ? \
+                AND group = ? \
+                AND year = ? \
+                AND bucket in ? \
+                AND created < ? \
+                AND created > ? \
+                AND submitter = ? \
+                PER PARTITION LIMIT ? \
+                ALLOW FILTERING",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla entity list pull by submitter statement")
+}
+
 /// Gets entities' names from their ids
 ///
 /// Used for supplementing name data when listing by tag
@@ -308,7 +370,7 @@ async fn list_pull(session: &Session, config: &Conf) -> PreparedStatement {
 async fn get_names_kinds_by_ids(session: &Session, config: &Conf) -> PreparedStatement {
     session
         .prepare(format!(
-            "SELECT id, name, kind FROM {}.entities_by_id \
+            "SELECT id, name, kind, submitter FROM {}.entities_by_id \
                 WHERE id in ?",
             &config.thorium.namespace
         ))
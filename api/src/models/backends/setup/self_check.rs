@@ -0,0 +1,177 @@
+//! Validates the Thorium config against the backends it is connected to
+//!
+//! Misconfigured namespaces or buckets otherwise only show up as confusing errors
+//! once requests start failing, so these checks are run before the API starts serving.
+
+use aws_sdk_s3::error::DisplayErrorContext;
+use scylla::client::session::Session;
+use tracing::instrument;
+
+use crate::conf::RouteModule;
+use crate::models::backends::db;
+use crate::utils::s3::S3Client;
+use crate::utils::{ApiError, Shared};
+use crate::{internal_err, internal_err_unwrapped};
+
+/// Check if a table in a keyspace has any rows in it
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to query with
+/// * `keyspace` - The keyspace the table is in
+/// * `table` - The table to check
+async fn has_rows(session: &Session, keyspace: &str, table: &str) -> Result<bool, ApiError> {
+    // get a single row from this table
+    let query = session
+        .query_unpaged(format!("SELECT * FROM {keyspace}.{table} LIMIT 1"), &[])
+        .await
+        .map_err(|err| {
+            internal_err_unwrapped!(format!(
+                "Failed to read '{table}' in the Scylla keyspace '{keyspace}': {err}"
+            ))
+        })?;
+    Ok(query.into_rows_result()?.rows_num() > 0)
+}
+
+/// Make sure the keyspace for our namespace is the one our data lives in
+///
+/// Our keyspace and its tables are created on startup, so a wrong namespace shows up as a
+/// keyspace without any samples while other Thorium keyspaces in this cluster do have them.
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to check
+/// * `namespace` - The namespace/keyspace to look for
+#[instrument(name = "setup::check_keyspace", skip(session), err(Debug))]
+pub async fn check_keyspace(session: &Session, namespace: &str) -> Result<(), ApiError> {
+    // find every keyspace that has Thorium tables in it
+    let query = session
+        .query_unpaged(
+            "SELECT keyspace_name FROM system_schema.tables WHERE table_name = ? ALLOW FILTERING",
+            ("samples_list",),
+        )
+        .await
+        .map_err(|err| {
+            internal_err_unwrapped!(format!(
+                "Failed to read the schema for the Scylla keyspace '{namespace}': {err}"
+            ))
+        })?;
+    let keyspaces = query
+        .into_rows_result()?
+        .rows::<(String,)>()?
+        .map(|row| row.map(|(keyspace,)| keyspace))
+        .collect::<Result<Vec<String>, _>>()?;
+    // a keyspace without any tables means our tables failed to be created
+    if !keyspaces.iter().any(|keyspace| keyspace == namespace) {
+        return internal_err!(format!(
+            "The Scylla keyspace '{namespace}' does not exist or has no tables! \
+            Make sure 'thorium.namespace' in your Thorium config matches the namespace \
+            this Thorium cluster was deployed with"
+        ));
+    }
+    // a keyspace with data is the one we want
+    if has_rows(session, namespace, "samples_list").await? {
+        return Ok(());
+    }
+    // our keyspace is empty so see if our data is in another Thorium keyspace
+    let mut populated = Vec::new();
+    for keyspace in keyspaces.iter().filter(|keyspace| *keyspace != namespace) {
+        if has_rows(session, keyspace, "samples_list").await? {
+            populated.push(keyspace.as_str());
+        }
+    }
+    if !populated.is_empty() {
+        return internal_err!(format!(
+            "The Scylla keyspace '{namespace}' is empty but other Thorium keyspaces have data: {others}! \
+            Make sure 'thorium.namespace' in your Thorium config matches the namespace \
+            this Thorium cluster was deployed with",
+            others = populated.join(", "),
+        ));
+    }
+    Ok(())
+}
+
+/// Make sure an S3 bucket exists and that we can access it
+///
+/// # Arguments
+///
+/// * `name` - The name of the config section this bucket is set in
+/// * `client` - The client for the bucket to check
+/// * `endpoint` - The s3 endpoint we are talking to
+#[instrument(name = "setup::check_bucket", skip(client), err(Debug))]
+pub async fn check_bucket(name: &str, client: &S3Client, endpoint: &str) -> Result<(), ApiError> {
+    // head our bucket to make sure it exists and that we have access to it
+    if let Err(err) = client
+        .client
+        .head_bucket()
+        .bucket(&client.bucket)
+        .send()
+        .await
+    {
+        return internal_err!(format!(
+            "The S3 bucket '{bucket}' at '{endpoint}' is not reachable: {err}! \
+            Make sure 'thorium.{name}.bucket' and 'thorium.s3' in your Thorium config are \
+            correct and that this bucket has been created",
+            bucket = client.bucket,
+            err = DisplayErrorContext(err),
+        ));
+    }
+    Ok(())
+}
+
+/// Make sure the system settings for our namespace exist in Redis
+///
+/// # Arguments
+///
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "setup::check_settings", skip_all, err(Debug))]
+pub async fn check_settings(shared: &Shared) -> Result<(), ApiError> {
+    if let Err(err) = db::system::get_settings(shared).await {
+        return internal_err!(format!(
+            "Failed to get the system settings for namespace '{ns}' from Redis: {msg}! \
+            Make sure 'thorium.namespace' and 'redis' in your Thorium config are correct",
+            ns = shared.config.thorium.namespace,
+            msg = err.msg.unwrap_or_else(|| err.code.to_string()),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate our config against the backends we are connected to
+///
+/// # Arguments
+///
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "setup::self_check", skip_all, err(Debug))]
+pub async fn self_check(shared: &Shared) -> Result<(), ApiError> {
+    let config = &shared.config;
+    // make sure our keyspace is the one our data is in
+    check_keyspace(&shared.scylla.session, &config.thorium.namespace).await?;
+    // get the buckets we need to access
+    let routes = &config.thorium.routes;
+    let buckets = [
+        ("files", &shared.s3.files, true),
+        ("results", &shared.s3.results, true),
+        ("ephemeral", &shared.s3.ephemeral, true),
+        ("reaction_cache", &shared.s3.reaction_cache, true),
+        ("attachments", &shared.s3.attachments, true),
+        (
+            "repos",
+            &shared.s3.repos,
+            routes.enabled(RouteModule::Repos),
+        ),
+        (
+            "graphics",
+            &shared.s3.graphics,
+            routes.enabled(RouteModule::Entities),
+        ),
+    ];
+    // make sure we can reach all of the buckets for the routes we are serving
+    for (name, client, enabled) in buckets {
+        if enabled {
+            check_bucket(name, client, &config.thorium.s3.endpoint).await?;
+        }
+    }
+    // make sure our system settings exist
+    check_settings(shared).await
+}
@@ -20,6 +20,7 @@ use crate::models::{
     SystemSettingsUpdate, SystemStats, User, VolumeTypes, Worker, WorkerDeleteMap,
     WorkerRegistrationList, WorkerUpdate, conversions,
 };
+use crate::utils::metrics::QueueDepth;
 use crate::utils::{ApiError, Shared};
 use crate::{
     bad, deserialize, deserialize_ext, extract, is_admin, log_scylla_err, not_found, unauthorized,
@@ -37,6 +38,15 @@ pub async fn health(shared: &Shared) -> Result<bool, ApiError> {
     db::system::health(shared).await
 }
 
+/// Gets the number of queued and running jobs for each scaler
+///
+/// # Arguments
+///
+/// * `shared` - Shared Thorium objects
+pub async fn queue_depths(shared: &Shared) -> Result<Vec<QueueDepth>, ApiError> {
+    db::system::queue_depths(shared).await
+}
+
 /// Returns a string denoting this server as a Thorium server
 ///
 /// # Arguments
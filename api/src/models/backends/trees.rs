@@ -5,14 +5,16 @@ use axum::http::request::Parts;
 use dashmap::{DashMap, DashSet};
 use futures::{StreamExt, stream};
 use gxhash::GxHasher;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::hash::{Hash, Hasher};
+use tracing::instrument;
 use uuid::Uuid;
 
 use super::db;
 use crate::models::{
     Association, AssociationListParams, AssociationTargetColumn, Directionality, Entity,
-    FileListOpts, FileListParams, Repo, Sample, Tree, TreeBounds, TreeBranch, TreeNode, TreeParams,
+    FileListOpts, FileListParams, LineageParams, LineageTree, Reaction, ReactionTree,
+    ReactionTreeParams, Repo, Sample, Tree, TreeBounds, TreeBranch, TreeNode, TreeParams,
     TreeQuery, TreeRelationships, TreeSupport, TreeTags, UnhashedTreeBranch, User,
 };
 use crate::utils::{ApiError, Shared};
@@ -848,24 +850,27 @@ impl Tree {
         bounds: TreeBounds,
         shared: &Shared,
     ) -> Result<DashSet<u64>, ApiError> {
-        // track how many times this tree has grown
-        let mut rings = 0;
         // have a tree ring for each growth
         let mut ring = TreeRing::new(params, bounds);
-        // keep growing this tree until we reach the specified depth
-        while rings < ring.params.limit {
-            // if we have no more growable nodes then end early
-            if self.growable.is_empty() {
-                break;
-            }
+        // never grow this tree deeper then requested or larger then our max node limit
+        let limits = TraversalLimits::new(
+            ring.params.limit,
+            shared.config.thorium.trees.max_tree_nodes,
+        );
+        // count the nodes already in this tree towards our node limit
+        let mut traversal = TreeTraversal::resume(self.data_map.keys().copied(), limits);
+        // keep growing this tree until we reach the specified depth or run out of nodes to grow
+        while traversal.descend(!self.growable.is_empty()) {
             // grow our growable nodes in parallel
             self.parallel_grow(user, &ring, shared).await?;
             // get any data missing from any associations
             self.get_association_nodes(user, &mut ring, shared).await?;
             // merge our current ring but not its relationships into our tree
             self.merge_ring(&mut ring)?;
-            // increment our rings counter
-            rings += 1;
+            // only grow from the new nodes that fit within our node limit
+            let growable = std::mem::take(&mut self.growable);
+            let (growable, _) = traversal.visit_all(growable, |hash| *hash);
+            self.growable = growable;
         }
         // replace our relationships in our tree
         self.add_relationships(ring.relationships, ring.hints)?;
@@ -924,3 +929,534 @@ where
         }
     }
 }
+
+/// The limits to enforce when walking a tree one level at a time
+#[derive(Debug, Clone, Copy)]
+pub struct TraversalLimits {
+    /// The max number of levels to crawl below the root
+    pub max_depth: usize,
+    /// The max number of nodes to visit including the root
+    pub max_nodes: usize,
+}
+
+impl TraversalLimits {
+    /// Create new traversal limits
+    ///
+    /// # Arguments
+    ///
+    /// * `max_depth` - The max number of levels to crawl below the root
+    /// * `max_nodes` - The max number of nodes to visit including the root
+    #[must_use]
+    pub fn new(max_depth: usize, max_nodes: usize) -> Self {
+        TraversalLimits {
+            max_depth,
+            max_nodes,
+        }
+    }
+}
+
+/// Tracks the nodes visited while walking a tree so cycles and runaway trees are cut off
+///
+/// Nodes that were already visited are skipped so cycles can't loop forever and the total
+/// number of nodes and levels are capped so a huge tree can't exhaust memory. Anything cut
+/// off due to our limits is reported as truncated instead of being an error.
+#[derive(Debug)]
+pub struct TreeTraversal<K: Hash + Eq> {
+    /// The limits to enforce for this traversal
+    limits: TraversalLimits,
+    /// The nodes we have already visited
+    visited: HashSet<K>,
+    /// The current level of the tree we are at
+    depth: usize,
+    /// Whether any nodes were cut off due to our limits
+    pub truncated: bool,
+}
+
+impl<K: Hash + Eq> TreeTraversal<K> {
+    /// Start a new traversal at a root node
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The key for the root node of this tree
+    /// * `limits` - The limits to enforce for this traversal
+    pub fn new(root: K, limits: TraversalLimits) -> Self {
+        TreeTraversal {
+            limits,
+            visited: HashSet::from([root]),
+            depth: 0,
+            truncated: false,
+        }
+    }
+
+    /// Continue a traversal from nodes that were already visited
+    ///
+    /// # Arguments
+    ///
+    /// * `visited` - The nodes that were already visited
+    /// * `limits` - The limits to enforce for this traversal
+    pub fn resume<I: IntoIterator<Item = K>>(visited: I, limits: TraversalLimits) -> Self {
+        TreeTraversal {
+            limits,
+            visited: visited.into_iter().collect(),
+            depth: 0,
+            truncated: false,
+        }
+    }
+
+    /// Check if a node has already been visited
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key for the node to check
+    pub fn visited(&self, key: &K) -> bool {
+        self.visited.contains(key)
+    }
+
+    /// Check if we are at the deepest level we are allowed to crawl
+    pub fn at_max_depth(&self) -> bool {
+        self.depth >= self.limits.max_depth
+    }
+
+    /// Move down to the next level of the tree if our limits allow it
+    ///
+    /// # Arguments
+    ///
+    /// * `expandable` - Whether any nodes in the current level may have children
+    pub fn descend(&mut self, expandable: bool) -> bool {
+        // there is nothing left to crawl
+        if !expandable {
+            return false;
+        }
+        // we have more to crawl but we are already as deep as we can go
+        if self.at_max_depth() {
+            self.truncated = true;
+            return false;
+        }
+        self.depth += 1;
+        true
+    }
+
+    /// Visit the children of a node returning only the ones that were not already visited
+    ///
+    /// This also returns whether any children were dropped due to our node limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `children` - The children to visit
+    /// * `key` - Get the key for a child
+    pub fn visit_all<T, F>(&mut self, children: Vec<T>, key: F) -> (Vec<T>, bool)
+    where
+        F: Fn(&T) -> K,
+    {
+        let mut limited = false;
+        let mut unvisited = Vec::with_capacity(children.len());
+        for child in children {
+            let child_key = key(&child);
+            // skip any nodes we have already seen to avoid cycles
+            if self.visited.contains(&child_key) {
+                continue;
+            }
+            // drop any nodes that would put us over our node limit
+            if self.visited.len() >= self.limits.max_nodes {
+                limited = true;
+                continue;
+            }
+            self.visited.insert(child_key);
+            unvisited.push(child);
+        }
+        // track that this traversal was truncated
+        self.truncated |= limited;
+        (unvisited, limited)
+    }
+}
+
+/// Get all of the sub reactions for a single reaction
+///
+/// # Arguments
+///
+/// * `reaction` - The reaction to get sub reactions for
+/// * `shared` - Shared Thorium objects
+async fn gather_sub_reactions(
+    reaction: &Reaction,
+    shared: &Shared,
+) -> Result<Vec<Reaction>, ApiError> {
+    // dedupe the ids we get back as scans can return the same id more then once
+    let mut ids = BTreeSet::default();
+    // start at the beginning of this reactions sub reaction set
+    let mut cursor = 0;
+    loop {
+        // get the next page of sub reaction ids
+        let list =
+            db::reactions::list_sub(&reaction.group, &reaction.id, cursor, 1000, shared).await?;
+        ids.extend(list.names);
+        // stop once we have crawled all of our sub reactions
+        match list.cursor {
+            Some(next) => cursor = next,
+            None => break,
+        }
+    }
+    // get the details for all of our sub reactions
+    let ids = ids.into_iter().collect::<Vec<String>>();
+    db::reactions::list_details(&reaction.group, &ids, shared).await
+}
+
+impl ReactionTree {
+    /// Build a tree of all sub reactions under a reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is building this tree
+    /// * `group` - The group the root reaction is in
+    /// * `id` - The id of the root reaction
+    /// * `params` - The params to use when building this tree
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "ReactionTree::build", skip(user, shared), err(Debug))]
+    pub async fn build(
+        user: &User,
+        group: &str,
+        id: &Uuid,
+        params: &ReactionTreeParams,
+        shared: &Shared,
+    ) -> Result<Self, ApiError> {
+        // get our root reaction and make sure this user can see it
+        let (_, root) = Reaction::get(user, group, id, shared).await?;
+        // never build a tree deeper or larger then our max limits
+        let conf = &shared.config.thorium.trees;
+        let limits = TraversalLimits::new(
+            params.depth.min(conf.max_reaction_tree_depth),
+            conf.max_reaction_tree_nodes,
+        );
+        let mut traversal = TreeTraversal::new(root.id, limits);
+        // track the sub reactions we find for each reaction
+        let mut children: HashMap<Uuid, Vec<Reaction>> = HashMap::default();
+        // track the reactions whose sub reactions were cut off by our node limit
+        let mut truncated = HashSet::default();
+        // crawl our tree one level at a time starting at the root
+        let mut frontier = vec![root.clone()];
+        while traversal.descend(frontier.iter().any(|reaction| reaction.sub_reactions > 0)) {
+            let mut next = Vec::default();
+            // get the sub reactions for any reactions in this level that have them
+            for reaction in frontier
+                .iter()
+                .filter(|reaction| reaction.sub_reactions > 0)
+            {
+                let subs = gather_sub_reactions(reaction, shared).await?;
+                // skip any sub reactions we have already seen or that are over our node limit
+                let (subs, limited) = traversal.visit_all(subs, |sub| sub.id);
+                if limited {
+                    truncated.insert(reaction.id);
+                }
+                next.extend(subs.iter().cloned());
+                children.insert(reaction.id, subs);
+            }
+            frontier = next;
+        }
+        // assemble our crawled reactions into a tree
+        Ok(Self::assemble(root, &mut children, &truncated))
+    }
+
+    /// Assemble a tree from the sub reactions we found for each reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `reaction` - The reaction to build a tree node for
+    /// * `children` - The sub reactions we found for each reaction
+    /// * `truncated` - The reactions whose sub reactions were cut off by our node limit
+    fn assemble(
+        reaction: Reaction,
+        children: &mut HashMap<Uuid, Vec<Reaction>>,
+        truncated: &HashSet<Uuid>,
+    ) -> Self {
+        let mut node = ReactionTree::new(reaction);
+        match children.remove(&node.reaction.id) {
+            // add all of our sub reactions to this node
+            Some(subs) => {
+                node.truncated = truncated.contains(&node.reaction.id);
+                node.children = subs
+                    .into_iter()
+                    .map(|sub| Self::assemble(sub, children, truncated))
+                    .collect();
+            }
+            // we didn't crawl this reactions sub reactions so mark it as truncated
+            None => node.truncated = node.reaction.sub_reactions > 0,
+        }
+        node
+    }
+}
+
+/// Get all of the reactions that were run on a sample in specific groups
+///
+/// # Arguments
+///
+/// * `sha256` - The sha256 of the sample to get reactions for
+/// * `groups` - The groups to get reactions from
+/// * `shared` - Shared Thorium objects
+async fn gather_sample_reactions(
+    sha256: &str,
+    groups: &[String],
+    shared: &Shared,
+) -> Result<Vec<Reaction>, ApiError> {
+    let mut reactions = Vec::default();
+    // reactions are automatically tagged with the samples they were run on
+    for group in groups {
+        // dedupe the ids we get back as scans can return the same id more then once
+        let mut ids = BTreeSet::default();
+        // start at the beginning of this samples reaction set
+        let mut cursor = 0;
+        loop {
+            // get the next page of reaction ids for this sample
+            let list = db::reactions::list_tag(group, sha256, cursor, 1000, shared).await?;
+            ids.extend(list.names);
+            // stop once we have crawled all of this samples reactions
+            match list.cursor {
+                Some(next) => cursor = next,
+                None => break,
+            }
+        }
+        // skip any groups this sample has no reactions in
+        if ids.is_empty() {
+            continue;
+        }
+        // get the details for all of the reactions in this group
+        let ids = ids.into_iter().collect::<Vec<String>>();
+        reactions.extend(db::reactions::list_details(group, &ids, shared).await?);
+    }
+    Ok(reactions)
+}
+
+/// Get all of the direct children of a sample in specific groups
+///
+/// # Arguments
+///
+/// * `sha256` - The sha256 of the sample to get children for
+/// * `groups` - The groups to get children from
+/// * `shared` - Shared Thorium objects
+async fn gather_sample_children(
+    sha256: &str,
+    groups: &[String],
+    shared: &Shared,
+) -> Result<Vec<Sample>, ApiError> {
+    let mut children = Vec::default();
+    // skip samples that are not visible in any groups
+    if groups.is_empty() {
+        return Ok(children);
+    }
+    let groups = groups.to_vec();
+    // build the opts to get everything tagged with this parent hash
+    let opts = FileListOpts::default()
+        .tag("Parent", sha256)
+        .groups(groups.clone());
+    // convert our file list opts to params
+    let params = FileListParams::from(opts);
+    // directly list samples in with this parent
+    let mut cursor = db::files::list(params, true, shared).await?;
+    // crawl this cursor and add any direct children
+    loop {
+        let sha256s = cursor
+            .data
+            .drain(..)
+            .map(|line| line.sha256)
+            .collect::<Vec<String>>();
+        // get the details on these samples
+        let details = db::files::list_details(&groups, sha256s, shared).await?;
+        // only keep samples that are direct children of this sample
+        children.extend(
+            details
+                .into_iter()
+                .filter(|child| child.is_direct_to(sha256)),
+        );
+        // if our cursor is exhausted then stop crawling
+        if cursor.exhausted() {
+            break;
+        }
+        // we have more data in this cursor so get the next page
+        cursor.next(shared).await?;
+    }
+    Ok(children)
+}
+
+/// The reactions and children gathered for each sample in a lineage
+#[derive(Default)]
+struct LineageCrawl {
+    /// The reactions run on each sample
+    reactions: HashMap<String, Vec<Reaction>>,
+    /// The children we found for each sample
+    children: HashMap<String, Vec<Sample>>,
+    /// The samples whose children were not gathered due to our depth limit
+    truncated: HashSet<String>,
+}
+
+impl LineageTree {
+    /// Build the lineage of reactions and children for a sample
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is building this lineage
+    /// * `sha256` - The sha256 of the root sample
+    /// * `params` - The params to use when building this lineage
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "LineageTree::build", skip(user, shared), err(Debug))]
+    pub async fn build(
+        user: &User,
+        sha256: &str,
+        params: &LineageParams,
+        shared: &Shared,
+    ) -> Result<Self, ApiError> {
+        // get our root sample and make sure this user can see it
+        let root = Sample::get(user, sha256, shared).await?;
+        // get all of the groups this user can see
+        let mut authorized = Vec::default();
+        user.authorize_groups(&mut authorized, shared).await?;
+        // never build a lineage deeper or larger then our max limits
+        let conf = &shared.config.thorium.trees;
+        let limits = TraversalLimits::new(
+            params.depth.min(conf.max_lineage_depth),
+            conf.max_lineage_nodes,
+        );
+        // track the samples we have already seen so cyclic lineages don't loop forever
+        let mut traversal = TreeTraversal::new(root.sha256.clone(), limits);
+        let mut crawl = LineageCrawl::default();
+        // crawl our lineage one generation at a time starting at the root
+        let mut frontier = vec![root.clone()];
+        loop {
+            let at_max_depth = traversal.at_max_depth();
+            let mut next = Vec::default();
+            for sample in &frontier {
+                // only look in the groups this user can see this sample in
+                let groups = sample
+                    .groups()
+                    .into_iter()
+                    .filter(|group| authorized.iter().any(|auth| auth == *group))
+                    .map(str::to_owned)
+                    .collect::<Vec<String>>();
+                // get the reactions run on this sample
+                let reactions = gather_sample_reactions(&sample.sha256, &groups, shared).await?;
+                crawl.reactions.insert(sample.sha256.clone(), reactions);
+                // get the children of this sample
+                let children = gather_sample_children(&sample.sha256, &groups, shared).await?;
+                // don't crawl past our depth limit but note that this sample has more children
+                if at_max_depth {
+                    if children
+                        .iter()
+                        .any(|child| !traversal.visited(&child.sha256))
+                    {
+                        crawl.truncated.insert(sample.sha256.clone());
+                    }
+                    continue;
+                }
+                // skip any children we have already seen or that are over our node limit
+                let (children, limited) =
+                    traversal.visit_all(children, |child| child.sha256.clone());
+                if limited {
+                    crawl.truncated.insert(sample.sha256.clone());
+                }
+                next.extend(children.iter().cloned());
+                crawl.children.insert(sample.sha256.clone(), children);
+            }
+            // stop once we run out of children to crawl
+            if !traversal.descend(!next.is_empty()) {
+                break;
+            }
+            frontier = next;
+        }
+        // assemble our crawled samples into a lineage
+        Ok(Self::assemble(root, &mut crawl))
+    }
+
+    /// Assemble a lineage from the reactions and children we found for each sample
+    ///
+    /// # Arguments
+    ///
+    /// * `sample` - The sample to build a lineage node for
+    /// * `crawl` - The reactions and children we found for each sample
+    fn assemble(sample: Sample, crawl: &mut LineageCrawl) -> Self {
+        let mut node = LineageTree::new(sample);
+        // add the reactions that were run on this sample
+        node.reactions = crawl
+            .reactions
+            .remove(&node.sample.sha256)
+            .unwrap_or_default();
+        node.truncated = crawl.truncated.contains(&node.sample.sha256);
+        // add the lineage for all of our children
+        if let Some(children) = crawl.children.remove(&node.sample.sha256) {
+            node.children = children
+                .into_iter()
+                .map(|child| Self::assemble(child, crawl))
+                .collect();
+        }
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Walk a synthetic graph the same way our tree builders do
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - The children for each node in our graph
+    /// * `limits` - The limits to enforce while walking this graph
+    fn walk(graph: &HashMap<u32, Vec<u32>>, limits: TraversalLimits) -> (Vec<u32>, bool) {
+        let mut traversal = TreeTraversal::new(0, limits);
+        let mut walked = vec![0];
+        let mut frontier = vec![0];
+        while traversal.descend(frontier.iter().any(|node| graph.contains_key(node))) {
+            let mut next = Vec::default();
+            for node in &frontier {
+                let children = graph.get(node).cloned().unwrap_or_default();
+                let (children, _) = traversal.visit_all(children, |child| *child);
+                next.extend(children);
+            }
+            walked.extend(next.iter().copied());
+            frontier = next;
+        }
+        (walked, traversal.truncated)
+    }
+
+    #[test]
+    fn cycle() {
+        // build a graph where the last node points back at the root
+        let graph = HashMap::from([(0, vec![1]), (1, vec![2]), (2, vec![3]), (3, vec![0, 1])]);
+        let (walked, truncated) = walk(&graph, TraversalLimits::new(100, 100));
+        // every node should be visited exactly once
+        assert_eq!(walked, vec![0, 1, 2, 3]);
+        // once all nodes are visited there is nothing new to crawl so we just run out of nodes
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn too_deep() {
+        // build a chain that is far deeper then our depth limit
+        let graph = (0..1000)
+            .map(|node| (node, vec![node + 1]))
+            .collect::<HashMap<u32, Vec<u32>>>();
+        let (walked, truncated) = walk(&graph, TraversalLimits::new(10, 10_000));
+        // we should stop at our max depth and report that we were truncated
+        assert_eq!(walked, (0..=10).collect::<Vec<u32>>());
+        assert!(truncated);
+    }
+
+    #[test]
+    fn too_many_nodes() {
+        // build a very wide tree
+        let graph = HashMap::from([(0, (1..1000).collect::<Vec<u32>>())]);
+        let (walked, truncated) = walk(&graph, TraversalLimits::new(10, 25));
+        // we should stop at our max node count and report that we were truncated
+        assert_eq!(walked.len(), 25);
+        assert!(truncated);
+    }
+
+    #[test]
+    fn resume() {
+        // resume a traversal where some nodes were already visited
+        let mut traversal = TreeTraversal::resume(0..5, TraversalLimits::new(10, 8));
+        assert!(traversal.descend(true));
+        // already visited nodes are skipped and count towards our node limit
+        let (children, limited) = traversal.visit_all((3..10).collect(), |child| *child);
+        assert_eq!(children, vec![5, 6, 7]);
+        assert!(limited);
+        assert!(traversal.truncated);
+    }
+}
@@ -26,7 +26,7 @@ use crate::models::{
 };
 use crate::utils::shared::EmailClient;
 use crate::utils::{ApiError, AppState, Shared, bounder};
-use crate::{bad, conflict, is_admin, ldap, unauthorized, unavailable, update};
+use crate::{bad, conflict, is_admin, ldap, unauthorized, unavailable, update, update_clear};
 
 /// The header name for our secret key
 static SECRET_KEY_HEADER: HeaderName = HeaderName::from_static("secret-key");
@@ -540,13 +540,24 @@ impl UserSettingsUpdate {
     /// # Arguments
     ///
     /// * `settings` - The user settings to update
-    pub fn apply(self, settings: &mut UserSettings) -> Result<(), ApiError> {
+    /// * `groups` - The groups this user is a member of
+    pub fn apply(self, settings: &mut UserSettings, groups: &[String]) -> Result<(), ApiError> {
         // update our theme if an update was set
         update!(settings.theme, self.theme);
         // apply any AI settings updates
         if let Some(ai_update) = self.ai {
             ai_update.apply(&mut settings.ai)?;
         }
+        // only allow default groups this user is a member of
+        if let Some(default_group) = self.default_group {
+            if !groups.contains(&default_group) {
+                return bad!(format!(
+                    "Unable to set default group to {default_group} as you are not a member of it"
+                ));
+            }
+            settings.default_group = Some(default_group);
+        }
+        update_clear!(settings.default_group, self.clear_default_group);
         Ok(())
     }
 }
@@ -571,6 +582,10 @@ impl User {
         if req.username == "external" {
             return bad!("external is a reserved username".to_owned());
         }
+        // new users aren't in any groups so they can't have a default group yet
+        if req.settings.default_group.is_some() {
+            return bad!("New users cannot set a default group".to_owned());
+        }
         // make sure this user doesn't already exist
         if User::exists(&req.username, shared).await.is_ok() {
             return conflict!(format!("User {} already exists", req.username));
@@ -966,7 +981,7 @@ impl User {
         }
         // apply any settings updates
         if let Some(settings) = update.settings {
-            settings.apply(&mut self.settings)?;
+            settings.apply(&mut self.settings, &self.groups)?;
         }
         // save update user to the backend
         db::users::save(&self, shared).await?;
@@ -1016,7 +1031,7 @@ impl User {
         crate::update!(target.role, update.role);
         // apply any settings updates
         if let Some(settings) = update.settings {
-            settings.apply(&mut target.settings)?;
+            settings.apply(&mut target.settings, &target.groups)?;
         }
         // save update user to the backend
         db::users::save(&target, shared).await?;
@@ -1,8 +1,16 @@
 /// Handles version operations for the Thorium api
 use tracing::instrument;
 
-use crate::models::Version;
-use crate::utils::ApiError;
+use crate::models::{Capabilities, CapabilityLimits, Version};
+use crate::utils::{ApiError, Shared};
+
+/// The features every Thorium server at this version supports
+const STATIC_FEATURES: [&str; 4] = [
+    "lineage-trees",
+    "ordered-kwargs",
+    "output-renderers",
+    "reaction-trees",
+];
 
 impl Version {
     /// Get the current version info
@@ -15,3 +23,44 @@ impl Version {
         Ok(version)
     }
 }
+
+impl Capabilities {
+    /// Get the features and limits for this server
+    ///
+    /// # Arguments
+    ///
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "Capabilities::new", skip_all)]
+    pub fn new(shared: &Shared) -> Self {
+        let conf = &shared.config.thorium;
+        // start with the features that are always supported
+        let mut features = STATIC_FEATURES
+            .iter()
+            .map(|feature| (*feature).to_owned())
+            .collect::<Vec<String>>();
+        // add any features that depend on our config
+        if conf.auth.ldap.is_some() {
+            features.push("ldap".to_owned());
+        }
+        if conf.auth.email.is_some() {
+            features.push("email-verification".to_owned());
+        }
+        features.sort_unstable();
+        // get the limits we enforce
+        let limits = CapabilityLimits {
+            max_upload: conf.request_size_limits.upload_limit(),
+            max_json: conf.request_size_limits.json,
+            max_bulk_reactions: conf.request_size_limits.bulk_reactions,
+            max_reaction_tree_depth: conf.trees.max_reaction_tree_depth,
+            max_reaction_tree_nodes: conf.trees.max_reaction_tree_nodes,
+            max_lineage_depth: conf.trees.max_lineage_depth,
+            max_lineage_nodes: conf.trees.max_lineage_nodes,
+            max_tree_nodes: conf.trees.max_tree_nodes,
+        };
+        Capabilities {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            features,
+            limits,
+        }
+    }
+}
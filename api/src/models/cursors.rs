@@ -46,7 +46,7 @@ cfg_if::cfg_if! {
 
 #[cfg(feature = "client")]
 mod client {
-    use crate::client::Error;
+    use crate::client::{Error, HttpClient};
     use crate::{add_query, send_build};
     use chrono::prelude::*;
     use serde::{Deserialize, Serialize};
@@ -128,7 +128,7 @@ mod client {
         /// The token to authenticate to Thorium with
         token: String,
         /// A reqwest client used to get more data from the API
-        client: reqwest::Client,
+        client: HttpClient,
     }
 
     #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -144,7 +144,7 @@ mod client {
             limit: Option<usize>,
             token: A,
             query: &Q,
-            client: &reqwest::Client,
+            client: &HttpClient,
         ) -> Result<Self, Error>
         where
             U: Into<String>,
@@ -286,7 +286,7 @@ mod client {
         /// The token to authenticate to Thorium with
         token: String,
         /// A reqwest client used to get more data from the API
-        client: reqwest::Client,
+        client: HttpClient,
     }
 
     #[cfg_attr(feature = "sync", thorium_derive::blocking_struct)]
@@ -301,7 +301,7 @@ mod client {
             limit: Option<usize>,
             token: A,
             query: &Q,
-            client: &reqwest::Client,
+            client: &HttpClient,
         ) -> Result<Self, Error>
         where
             U: Into<String>,
@@ -98,6 +98,27 @@ fn default_search_limit() -> u32 {
     50
 }
 
+/// The tag elastic places before highlighted terms
+pub(crate) const HIGHLIGHT_PRE_TAG: &str = "@kibana-highlighted-field@";
+
+/// The tag elastic places after highlighted terms
+pub(crate) const HIGHLIGHT_POST_TAG: &str = "@/kibana-highlighted-field@";
+
+/// Default the number of characters in a search snippet to 120
+fn default_snippet_size() -> usize {
+    120
+}
+
+/// Default the tag placed before matches in a snippet to `<mark>`
+fn default_snippet_pre_tag() -> String {
+    "<mark>".to_owned()
+}
+
+/// Default the tag placed after matches in a snippet to `</mark>`
+fn default_snippet_post_tag() -> String {
+    "</mark>".to_owned()
+}
+
 /// The query params for searching results
 #[derive(Serialize, Deserialize, Debug, Default)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -121,6 +142,18 @@ pub struct ElasticSearchParams {
     /// The max number of items to return in this response
     #[serde(default = "default_search_limit")]
     pub limit: u32,
+    /// Whether to return a highlighted snippet for each result
+    #[serde(default)]
+    pub snippets: bool,
+    /// The max number of characters of context to include in each snippet
+    #[serde(default = "default_snippet_size")]
+    pub snippet_size: usize,
+    /// The tag to place before each match in a snippet
+    #[serde(default = "default_snippet_pre_tag")]
+    pub snippet_pre_tag: String,
+    /// The tag to place after each match in a snippet
+    #[serde(default = "default_snippet_post_tag")]
+    pub snippet_post_tag: String,
 }
 
 /// The query params for searching results
@@ -142,6 +175,12 @@ pub struct ElasticSearchOpts {
     pub page_size: usize,
     /// The max number of items to return with this cursor
     pub limit: Option<usize>,
+    /// Whether to return a highlighted snippet for each result
+    pub snippets: bool,
+    /// The max number of characters of context to include in each snippet
+    pub snippet_size: Option<usize>,
+    /// The tags to place before and after each match in a snippet
+    pub snippet_tags: Option<(String, String)>,
 }
 
 impl ElasticSearchOpts {
@@ -161,6 +200,9 @@ impl ElasticSearchOpts {
             cursor: None,
             page_size: 50,
             limit: None,
+            snippets: false,
+            snippet_size: None,
+            snippet_tags: None,
         }
     }
 
@@ -174,6 +216,46 @@ impl ElasticSearchOpts {
         self.indexes = indexes;
         self
     }
+
+    /// Return a highlighted snippet showing where each result matched
+    #[must_use]
+    pub fn snippets(mut self) -> Self {
+        self.snippets = true;
+        self
+    }
+
+    /// Set the max number of characters of context to include in each snippet
+    ///
+    /// This also enables snippets.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The max number of characters in each snippet
+    #[must_use]
+    pub fn snippet_size(mut self, size: usize) -> Self {
+        self.snippets = true;
+        self.snippet_size = Some(size);
+        self
+    }
+
+    /// Set the tags to place around each match in a snippet
+    ///
+    /// This also enables snippets.
+    ///
+    /// # Arguments
+    ///
+    /// * `pre_tag` - The tag to place before each match
+    /// * `post_tag` - The tag to place after each match
+    #[must_use]
+    pub fn snippet_tags<T: Into<String>, P: Into<String>>(
+        mut self,
+        pre_tag: T,
+        post_tag: P,
+    ) -> Self {
+        self.snippets = true;
+        self.snippet_tags = Some((pre_tag.into(), post_tag.into()));
+        self
+    }
 }
 
 // A specific document in elastic
@@ -194,6 +276,142 @@ pub struct ElasticDoc {
     pub source: Option<serde_json::Value>,
     /// The actual document in elastic
     pub highlight: Option<serde_json::Value>,
+    /// A short snippet showing where this document matched if snippets were requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
     /// The sort values for this document
     pub sort: Vec<i64>,
 }
+
+impl ElasticDoc {
+    /// Build a snippet for this document from the first highlighted fragment elastic returned
+    ///
+    /// The snippet is centered on the first match in that fragment and every match
+    /// that fits in the snippet is wrapped in the requested tags.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The max number of characters of context to include
+    /// * `pre_tag` - The tag to place before each match
+    /// * `post_tag` - The tag to place after each match
+    pub fn build_snippet(&mut self, size: usize, pre_tag: &str, post_tag: &str) {
+        // find the first fragment that actually contains a match
+        let Some(fragment) = self
+            .highlight
+            .as_ref()
+            .and_then(serde_json::Value::as_object)
+            .into_iter()
+            .flat_map(|fields| fields.values())
+            .filter_map(serde_json::Value::as_array)
+            .flatten()
+            .filter_map(serde_json::Value::as_str)
+            .find(|fragment| fragment.contains(HIGHLIGHT_PRE_TAG))
+        else {
+            return;
+        };
+        // split this fragment into plain text and the character ranges that matched
+        let mut text = Vec::with_capacity(fragment.len());
+        let mut matches = Vec::new();
+        let mut remaining = fragment;
+        while let Some(start) = remaining.find(HIGHLIGHT_PRE_TAG) {
+            text.extend(remaining[..start].chars());
+            remaining = &remaining[start + HIGHLIGHT_PRE_TAG.len()..];
+            // get the end of this match or treat the rest of the fragment as matched
+            let end = remaining
+                .find(HIGHLIGHT_POST_TAG)
+                .unwrap_or(remaining.len());
+            let match_start = text.len();
+            text.extend(remaining[..end].chars());
+            matches.push((match_start, text.len()));
+            remaining = remaining
+                .get(end + HIGHLIGHT_POST_TAG.len()..)
+                .unwrap_or_default();
+        }
+        text.extend(remaining.chars());
+        // center our window on the first match
+        let (first_start, first_end) = matches[0];
+        let padding = size.saturating_sub(first_end - first_start) / 2;
+        let mut window_start = first_start.saturating_sub(padding);
+        let window_end = (window_start + size).min(text.len()).max(first_end);
+        // use any unused space at the end of our text for more leading context
+        if window_end - window_start < size {
+            window_start = window_end.saturating_sub(size).min(first_start);
+        }
+        // rebuild our snippet with the requested tags around any matches in our window
+        let mut snippet = String::with_capacity(size + pre_tag.len() + post_tag.len());
+        if window_start > 0 {
+            snippet.push_str("...");
+        }
+        let mut pos = window_start;
+        for (start, end) in matches {
+            // skip any matches outside of our window
+            if end <= window_start || start >= window_end {
+                continue;
+            }
+            let start = start.max(pos);
+            let end = end.min(window_end);
+            snippet.extend(&text[pos..start]);
+            snippet.push_str(pre_tag);
+            snippet.extend(&text[start..end]);
+            snippet.push_str(post_tag);
+            pos = end;
+        }
+        snippet.extend(&text[pos..window_end]);
+        if window_end < text.len() {
+            snippet.push_str("...");
+        }
+        self.snippet = Some(snippet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ElasticDoc;
+
+    /// Build a doc like elastic would return for a search with highlighting
+    ///
+    /// # Arguments
+    ///
+    /// * `fragment` - The highlighted fragment to return
+    fn doc(fragment: &str) -> ElasticDoc {
+        serde_json::from_value(serde_json::json!({
+            "_id": "1",
+            "_index": "results",
+            "_score": 1.0,
+            "highlight": { "results": [fragment] },
+            "sort": [1, 2],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn snippet() {
+        let mut doc = doc("a @kibana-highlighted-field@pe32@/kibana-highlighted-field@ binary");
+        doc.build_snippet(120, "[[", "]]");
+        assert_eq!(doc.snippet.as_deref(), Some("a [[pe32]] binary"));
+    }
+
+    #[test]
+    fn snippet_truncated() {
+        let fragment = format!(
+            "{}@kibana-highlighted-field@x86_64@/kibana-highlighted-field@{}",
+            "a".repeat(100),
+            "b".repeat(100)
+        );
+        let mut doc = doc(&fragment);
+        doc.build_snippet(16, "<mark>", "</mark>");
+        // the match should be centered in our snippet with context on both sides
+        assert_eq!(
+            doc.snippet.as_deref(),
+            Some("...aaaaa<mark>x86_64</mark>bbbbb...")
+        );
+    }
+
+    #[test]
+    fn snippet_without_match() {
+        // docs that weren't highlighted shouldn't get a snippet
+        let mut doc = doc("nothing matched here");
+        doc.build_snippet(120, "<mark>", "</mark>");
+        assert!(doc.snippet.is_none());
+    }
+}
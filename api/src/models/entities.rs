@@ -11,14 +11,17 @@ use uuid::Uuid;
 use super::Association;
 use crate::models::{
     CollectionEntity, CollectionEntityRequest, CollectionKind, Country, DeviceEntityRequest,
-    TagMap, TreeSupport, VendorEntity, VendorEntityRequest,
+    SoftwareEntity, SoftwareEntityRequest, TagMap, TreeSupport, VendorEntity, VendorEntityRequest,
 };
 
 pub mod collections;
 pub mod countries;
 pub mod devices;
+pub mod duplicates;
 pub mod filesystem;
+pub mod graph;
 pub mod shared;
+pub mod software;
 pub mod vendors;
 
 use devices::DeviceEntity;
@@ -35,6 +38,9 @@ cfg_if::cfg_if! {
     }
 }
 
+#[cfg(feature = "client")]
+use crate::multipart_list_conv;
+
 // api/client imports
 cfg_if::cfg_if! {
     if #[cfg(any(feature = "api", feature = "client"))] {
@@ -42,7 +48,7 @@ cfg_if::cfg_if! {
         use super::backends::TagSupport;
         use super::TagType;
         use std::collections::HashMap;
-        use crate::{multipart_list, multipart_text, multipart_set};
+        use crate::{multipart_list, multipart_text, multipart_text_to_string, multipart_set};
     }
 }
 
@@ -71,6 +77,8 @@ cfg_if::cfg_if! {
             pub data_sha256: Option<String>,
             pub all_sha256: Option<String>,
             pub tools: Vec<String>,
+            pub version: Option<String>,
+            pub cpe: Option<String>,
         }
 
         impl EntityMetadataForm {
@@ -113,8 +121,40 @@ cfg_if::cfg_if! {
         }
 
         impl EntityForm {
-            /// Ensure the data in the entity metadata form is valid
+            /// Get the names of any required fields that are missing or empty in this form
+            #[must_use]
+            pub fn missing_fields(&self) -> Vec<&'static str> {
+                let mut missing = Vec::new();
+                // an entity must have a non-empty name
+                if self.name.as_ref().is_none_or(String::is_empty) {
+                    missing.push("name");
+                }
+                // an entity must have a kind
+                if self.kind.is_none() {
+                    missing.push("kind");
+                }
+                // an entity must be in at least one group
+                if self.groups.is_empty() {
+                    missing.push("groups");
+                }
+                missing
+            }
+
+            /// Ensure the data in the entity form is valid
+            ///
+            /// # Errors
+            ///
+            /// - Any required fields are missing or empty
+            /// - The entity metadata form is invalid
             pub fn validate(&self) -> Result<(), ApiError> {
+                // make sure all of our required fields were set
+                let missing = self.missing_fields();
+                if !missing.is_empty() {
+                    return crate::bad!(format!(
+                        "Entity form is missing required fields: {}",
+                        missing.join(", ")
+                    ));
+                }
                 self.metadata.validate()?;
                 Ok(())
             }
@@ -130,7 +170,9 @@ cfg_if::cfg_if! {
             pub clear_image: Option<bool>,
             /// A description of this entity
             pub description: Option<String>,
-            pub clear_description: Option<bool>
+            pub clear_description: Option<bool>,
+            /// The version of the entity this update was built from
+            pub version: Option<u64>,
         }
 
         /// The form for updating entity metadata
@@ -140,6 +182,8 @@ cfg_if::cfg_if! {
             pub remove_urls: Vec<String>,
             pub add_vendors: Vec<Uuid>,
             pub remove_vendors: Vec<Uuid>,
+            pub add_related: Vec<Uuid>,
+            pub remove_related: Vec<Uuid>,
             pub critical_system: Option<bool>,
             pub clear_critical_system: Option<bool>,
             pub sensitive_location: Option<bool>,
@@ -158,6 +202,9 @@ cfg_if::cfg_if! {
             pub clear_collection_end: Option<bool>,
             pub add_tools: Vec<String>,
             pub remove_tools: Vec<String>,
+            pub version: Option<String>,
+            pub cpe: Option<String>,
+            pub clear_cpe: Option<bool>,
         }
     }
 }
@@ -186,6 +233,14 @@ pub struct Entity {
     pub image: Option<String>,
     /// The time this entity was created
     pub created: DateTime<Utc>,
+    /// The version of this entity used to reject conflicting updates
+    ///
+    /// This is only set when getting a single entity.
+    #[serde(default)]
+    pub version: u64,
+    /// When this entity was archived if it has been archived
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl TreeSupport for Entity {
@@ -471,6 +526,8 @@ pub enum EntityMetadata {
     Device(DeviceEntity),
     /// A vendor entity
     Vendor(VendorEntity),
+    /// A software product entity
+    Software(SoftwareEntity),
     /// A collection entity
     ///
     /// Collections are dynamic lists of items in Thorium (e.g. samples, repos, etc.)
@@ -493,6 +550,8 @@ pub enum EntityMetadataRequest {
     Device(DeviceEntityRequest),
     /// A vendor entity
     Vendor(VendorEntityRequest),
+    /// A software product entity
+    Software(SoftwareEntityRequest),
     /// A request to create a collection entity
     Collection(CollectionEntityRequest),
     /// A filesystem entity
@@ -514,6 +573,7 @@ impl EntityMetadataRequest {
         match self {
             EntityMetadataRequest::Device(device) => device.add_to_form(form),
             EntityMetadataRequest::Vendor(vendor) => vendor.add_to_form(form),
+            EntityMetadataRequest::Software(software) => software.add_to_form(form),
             EntityMetadataRequest::Collection(collection) => collection.add_to_form(form),
             EntityMetadataRequest::FileSystem(fs) => fs.add_to_form(form),
             EntityMetadataRequest::Folder(folder) => folder.add_to_form(form),
@@ -532,7 +592,8 @@ impl EntityKinds {
 }
 
 /// A request to create an entity
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
 pub struct EntityRequest {
     /// The entity's name
     pub name: String,
@@ -541,8 +602,10 @@ pub struct EntityRequest {
     /// The groups this entity should be in
     pub groups: Vec<String>,
     /// The tags for this entity
+    #[serde(default)]
     pub tags: HashMap<String, HashSet<String>>,
     /// A description of this entity
+    #[serde(default)]
     pub description: Option<String>,
 }
 
@@ -623,6 +686,31 @@ impl EntityResponse {
     }
 }
 
+/// The response from creating entities in bulk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct BulkEntityResponse {
+    /// Any errors that occured while creating entities by request index
+    pub errors: HashMap<usize, String>,
+    /// The ids of the successfully created entities by request index
+    pub created: HashMap<usize, Uuid>,
+}
+
+impl BulkEntityResponse {
+    /// Create a new bulk entity response with a starting capacity for created entities
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The capacity to allocate
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        BulkEntityResponse {
+            errors: HashMap::default(),
+            created: HashMap::with_capacity(capacity),
+        }
+    }
+}
+
 /// Set default for the entity list limit
 fn default_list_limit() -> usize {
     50
@@ -653,6 +741,12 @@ pub struct EntityListOpts {
     pub tags: HashMap<String, Vec<String>>,
     /// Whether matching on tags should be case-insensitive
     pub tags_case_insensitive: bool,
+    /// Only list entities created by this user
+    pub submitter: Option<String>,
+    /// Whether to include archived entities or not
+    pub include_archived: bool,
+    /// The number of partitions to crawl at once when listing
+    pub partition_hint: Option<u32>,
 }
 
 impl Default for EntityListOpts {
@@ -667,6 +761,9 @@ impl Default for EntityListOpts {
             groups: Vec::default(),
             tags: HashMap::default(),
             tags_case_insensitive: false,
+            submitter: None,
+            include_archived: false,
+            partition_hint: None,
         }
     }
 }
@@ -779,6 +876,38 @@ impl EntityListOpts {
         self.tags_case_insensitive = true;
         self
     }
+
+    /// Only list entities created by a specific user
+    ///
+    /// # Arguments
+    ///
+    /// * `submitter` - The user whose entities to list
+    #[must_use]
+    pub fn submitter<T: Into<String>>(mut self, submitter: T) -> Self {
+        self.submitter = Some(submitter.into());
+        self
+    }
+
+    /// Include archived entities in this search
+    #[must_use]
+    pub fn include_archived(mut self) -> Self {
+        self.include_archived = true;
+        self
+    }
+
+    /// Set how many partitions to crawl at once when listing
+    ///
+    /// Larger hints can reduce round trips when listing large groups but are capped
+    /// by the Thorium config.
+    ///
+    /// # Arguments
+    ///
+    /// * `partition_hint` - The number of partitions to crawl at once
+    #[must_use]
+    pub fn partition_hint(mut self, partition_hint: u32) -> Self {
+        self.partition_hint = Some(partition_hint);
+        self
+    }
 }
 
 /// The params for listing entities
@@ -803,6 +932,13 @@ pub struct EntityListParams {
     pub limit: usize,
     #[serde(default = "default_entity_kinds")]
     pub kinds: Vec<EntityKinds>,
+    /// Only list entities created by this user
+    pub submitter: Option<String>,
+    /// Whether to include archived entities or not
+    #[serde(default)]
+    pub include_archived: bool,
+    /// The number of partitions to crawl at once instead of the default
+    pub partition_hint: Option<u32>,
 }
 
 impl Default for EntityListParams {
@@ -816,6 +952,9 @@ impl Default for EntityListParams {
             cursor: None,
             limit: default_list_limit(),
             kinds: default_entity_kinds(),
+            submitter: None,
+            include_archived: false,
+            partition_hint: None,
         }
     }
 }
@@ -872,6 +1011,59 @@ pub struct EntityUpdate {
     pub add_tools: Vec<String>,
     /// Remoev a tool from this entity
     pub remove_tools: Vec<String>,
+    /// The entities to add a symmetric `RelatedTo` association with
+    pub add_related: Vec<Uuid>,
+    /// The entities to remove a symmetric `RelatedTo` association with
+    pub remove_related: Vec<Uuid>,
+    /// The new image to set for this entity
+    #[serde(skip)]
+    pub image: Option<EntityImage>,
+    /// The version of the entity this update was built from
+    ///
+    /// If this is set and the entity has been updated since then this update will be rejected.
+    pub version: Option<u64>,
+    /// The new version to set for a software entity
+    pub software_version: Option<String>,
+    /// The new CPE identifier to set for a software entity
+    pub cpe: Option<String>,
+    /// Clear this software entities CPE identifier
+    pub clear_cpe: bool,
+}
+
+/// An image to upload for an entity
+#[derive(Debug, Clone)]
+pub struct EntityImage {
+    /// The file name for this image
+    pub name: String,
+    /// The content type for this image (e.g. "image/png")
+    pub content_type: String,
+    /// The image data to upload
+    pub data: Vec<u8>,
+}
+
+impl EntityImage {
+    /// Create a new entity image to upload
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The file name for this image
+    /// * `content_type` - The content type for this image (e.g. "image/png")
+    /// * `data` - The image data to upload
+    pub fn new(name: impl Into<String>, content_type: impl Into<String>, data: Vec<u8>) -> Self {
+        EntityImage {
+            name: name.into(),
+            content_type: content_type.into(),
+            data,
+        }
+    }
+
+    /// Create a multipart part from this image
+    #[cfg(feature = "client")]
+    pub fn to_part(self) -> Result<reqwest::multipart::Part, reqwest::Error> {
+        reqwest::multipart::Part::bytes(self.data)
+            .file_name(self.name)
+            .mime_str(&self.content_type)
+    }
 }
 
 impl EntityUpdate {
@@ -947,6 +1139,72 @@ impl EntityUpdate {
         self
     }
 
+    /// Relate this entity to another entity
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the entity to relate this entity to
+    pub fn related(mut self, id: Uuid) -> Self {
+        self.add_related.push(id);
+        self
+    }
+
+    /// Remove a relation between this entity and another entity
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the entity to no longer relate this entity to
+    pub fn remove_related(mut self, id: Uuid) -> Self {
+        self.remove_related.push(id);
+        self
+    }
+
+    /// Set a new image for this entity
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image to upload
+    pub fn image(mut self, image: EntityImage) -> Self {
+        self.image = Some(image);
+        self
+    }
+
+    /// Only apply this update if the entity is still at this version
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version of the entity this update was built from
+    pub fn version(mut self, version: u64) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Change the version of a software entity
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The software version to change too
+    pub fn software_version(mut self, version: impl Into<String>) -> Self {
+        self.software_version = Some(version.into());
+        self
+    }
+
+    /// Change the CPE identifier of a software entity
+    ///
+    /// # Arguments
+    ///
+    /// * `cpe` - The CPE identifier to change too
+    pub fn cpe(mut self, cpe: impl Into<String>) -> Self {
+        self.cpe = Some(cpe.into());
+        self
+    }
+
+    /// Clear the CPE identifier of a software entity
+    pub fn clear_cpe(mut self) -> Self {
+        self.clear_cpe = true;
+        self
+    }
+
     /// Convert this update to a multipart form
     #[cfg(feature = "client")]
     pub fn to_form(mut self) -> Result<reqwest::multipart::Form, crate::Error> {
@@ -963,6 +1221,146 @@ impl EntityUpdate {
         let form = multipart_list!(form, "remove_groups", self.remove_groups);
         // set our description form field
         let form = multipart_text!(form, "description", self.description);
+        // set the version this update was built from
+        let form = multipart_text_to_string!(form, "version", self.version);
+        // set any software metadata updates
+        let form = multipart_text!(form, "metadata[version]", self.software_version);
+        let form = multipart_text!(form, "metadata[cpe]", self.cpe);
+        let form = if self.clear_cpe {
+            form.text("metadata[clear_cpe]", "true")
+        } else {
+            form
+        };
+        // add the entities to relate or unrelate this entity with
+        let form = multipart_list_conv!(form, "metadata[add_related][]", self.add_related);
+        let form = multipart_list_conv!(form, "metadata[remove_related][]", self.remove_related);
+        // add our new image if we have one
+        let form = match self.image.take() {
+            Some(image) => form.part("image", image.to_part()?),
+            None => form,
+        };
         Ok(form)
     }
 }
+
+#[cfg(all(test, feature = "api"))]
+mod tests {
+    use axum::http::StatusCode;
+    use uuid::Uuid;
+
+    use super::{EntityForm, EntityKinds, EntityMetadataRequest, EntityRequest};
+    use crate::conf::EntityAliases;
+    use crate::models::{Country, DeviceEntityRequest, VendorEntityRequest};
+
+    /// Build an entity form with all required fields set
+    fn full_form() -> EntityForm {
+        EntityForm {
+            name: Some("Corn Harvester".to_owned()),
+            kind: Some(EntityKinds::Device),
+            groups: vec!["Corn".to_owned()],
+            ..EntityForm::default()
+        }
+    }
+
+    #[test]
+    fn missing_name() {
+        // a complete form should be valid
+        assert!(full_form().validate().is_ok());
+        // a form without a name should be rejected with the missing field listed
+        let form = EntityForm {
+            name: None,
+            ..full_form()
+        };
+        let error = form.validate().unwrap_err();
+        assert_eq!(error.code, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            error.msg.as_deref(),
+            Some("Entity form is missing required fields: name")
+        );
+        // an empty name counts as missing
+        let form = EntityForm {
+            name: Some(String::new()),
+            ..full_form()
+        };
+        assert_eq!(form.missing_fields(), vec!["name"]);
+    }
+
+    #[test]
+    fn missing_kind() {
+        // a form without a kind should be rejected with the missing field listed
+        let form = EntityForm {
+            kind: None,
+            ..full_form()
+        };
+        let error = form.validate().unwrap_err();
+        assert_eq!(error.code, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            error.msg.as_deref(),
+            Some("Entity form is missing required fields: kind")
+        );
+        // all missing fields are listed at once
+        let error = EntityForm::default().validate().unwrap_err();
+        assert_eq!(
+            error.msg.as_deref(),
+            Some("Entity form is missing required fields: name, kind, groups")
+        );
+    }
+
+    #[test]
+    fn from_request() {
+        // convert a device request to a form
+        let vendor = Uuid::new_v4();
+        let device = DeviceEntityRequest {
+            urls: vec!["https://corn.com".to_owned()],
+            vendors: vec![vendor],
+            critical_system: Some(true),
+            ..DeviceEntityRequest::default()
+        };
+        let req = EntityRequest::new(
+            "Corn Harvester",
+            EntityMetadataRequest::Device(device),
+            vec!["Corn"],
+        )
+        .tag("Crop", "Corn");
+        let aliases = EntityAliases::default();
+        let form = EntityForm::from_request(req, &aliases).unwrap();
+        assert!(form.validate().is_ok());
+        assert_eq!(form.kind, Some(EntityKinds::Device));
+        assert_eq!(form.metadata.vendors, vec![vendor]);
+        assert_eq!(form.metadata.critical_system, Some(true));
+        assert!(form.tags["Crop"].contains("Corn"));
+        assert!(form.image.is_none());
+        // vendors with invalid countries should be rejected
+        let vendor = VendorEntityRequest {
+            countries: ["Cornland".to_owned()].into(),
+            ..VendorEntityRequest::default()
+        };
+        let req = EntityRequest::new(
+            "CornCo",
+            EntityMetadataRequest::Vendor(vendor),
+            vec!["Corn"],
+        );
+        assert!(EntityForm::from_request(req, &aliases).is_err());
+        // but common country aliases should be normalized
+        let vendor = VendorEntityRequest {
+            countries: ["United States".to_owned(), "uk".to_owned()].into(),
+            ..VendorEntityRequest::default()
+        };
+        let req = EntityRequest::new(
+            "CornCo",
+            EntityMetadataRequest::Vendor(vendor),
+            vec!["Corn"],
+        );
+        let form = EntityForm::from_request(req, &aliases).unwrap();
+        assert!(
+            form.metadata
+                .countries
+                .contains(&Country::from_alpha2("US").unwrap())
+        );
+        assert!(
+            form.metadata
+                .countries
+                .contains(&Country::from_alpha2("GB").unwrap())
+        );
+    }
+}
@@ -1,6 +1,9 @@
 //! The country entity in Thorium
 
 use isocountry::{CountryCode, CountryCodeParseErr};
+use std::collections::HashMap;
+
+use super::shared::{alias_key, find_alias};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -27,6 +30,102 @@ impl Country {
         let country = Country { code, name };
         Ok(country)
     }
+
+    /// Create a new country object from an ISO 3166 alpha-2 code (e.g. "US")
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The alpha-2 code for this country
+    pub fn from_alpha2(code: &str) -> Result<Self, CountryCodeParseErr> {
+        // get this countries code ignoring case
+        let code = CountryCode::for_alpha2(&code.to_ascii_uppercase())?;
+        Ok(Self::from(code))
+    }
+
+    /// Create a new country object from an ISO 3166 alpha-3 code (e.g. "USA")
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The alpha-3 code for this country
+    pub fn from_alpha3(code: &str) -> Result<Self, CountryCodeParseErr> {
+        // get this countries code ignoring case
+        let code = CountryCode::for_alpha3(&code.to_ascii_uppercase())?;
+        Ok(Self::from(code))
+    }
+
+    /// Create a new country object from either an ISO 3166 alpha-2 or alpha-3 code
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The alpha-2 or alpha-3 code for this country
+    pub fn from_code(code: &str) -> Result<Self, CountryCodeParseErr> {
+        // alpha-3 codes are always 3 characters so treat anything else as alpha-2
+        if code.len() == 3 {
+            Self::from_alpha3(code)
+        } else {
+            Self::from_alpha2(code)
+        }
+    }
+
+    /// Parse a country allowing for country names and common synonyms
+    ///
+    /// Codes are tried first, then ISO country names ignoring case and punctuation,
+    /// and finally the configured aliases. Values that match none of these are
+    /// still rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The raw country to parse
+    /// * `aliases` - The aliases to accept mapped to their canonical country codes
+    pub fn normalize(
+        raw: &str,
+        aliases: &HashMap<String, String>,
+    ) -> Result<Self, CountryCodeParseErr> {
+        let raw = raw.trim();
+        // use this country as is if its already a valid code
+        let code_err = match Self::from_code(raw) {
+            Ok(country) => return Ok(country),
+            Err(err) => err,
+        };
+        // check if this is an ISO country name
+        let key = alias_key(raw);
+        if let Some(code) = CountryCode::iter().find(|code| alias_key(code.name()) == key) {
+            return Ok(Self::from(*code));
+        }
+        // check if this is an alias for a country
+        match find_alias(raw, aliases) {
+            Some(canonical) => Self::from_code(canonical),
+            None => Err(code_err),
+        }
+    }
+
+    /// Get the ISO 3166 alpha-2 code for this country
+    pub fn as_alpha2(&self) -> &'static str {
+        self.code.alpha2()
+    }
+
+    /// Get the ISO 3166 alpha-3 code for this country
+    pub fn as_alpha3(&self) -> &'static str {
+        self.code.alpha3()
+    }
+
+    /// Get the name of this country
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl From<CountryCode> for Country {
+    /// Build a country object from a country code
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The code to build a country from
+    fn from(code: CountryCode) -> Self {
+        // get our countries name
+        let name = code.name().to_owned();
+        Country { code, name }
+    }
 }
 
 impl Ord for Country {
@@ -40,3 +139,70 @@ impl PartialOrd for Country {
         Some(self.cmp(other))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::Country;
+
+    #[test]
+    fn alpha2() {
+        let country = Country::from_alpha2("US").unwrap();
+        assert_eq!(country.as_alpha2(), "US");
+        assert_eq!(country.as_alpha3(), "USA");
+        // codes are case insensitive
+        assert_eq!(Country::from_alpha2("us").unwrap(), country);
+        // either form can be parsed
+        assert_eq!(Country::from_code("US").unwrap(), country);
+    }
+
+    #[test]
+    fn alpha3() {
+        let country = Country::from_alpha3("DEU").unwrap();
+        assert_eq!(country.as_alpha3(), "DEU");
+        assert_eq!(country.as_alpha2(), "DE");
+        // both representations round trip to the same country
+        assert_eq!(Country::from_alpha2(country.as_alpha2()).unwrap(), country);
+        assert_eq!(Country::from_code("deu").unwrap(), country);
+    }
+
+    #[test]
+    fn aliases() {
+        let aliases = HashMap::from([
+            ("United States".to_owned(), "US".to_owned()),
+            ("UK".to_owned(), "GB".to_owned()),
+        ]);
+        let us = Country::from_alpha2("US").unwrap();
+        // codes still work when normalizing
+        assert_eq!(Country::normalize("usa", &aliases).unwrap(), us);
+        // ISO names are accepted regardless of case or punctuation
+        assert_eq!(
+            Country::normalize("united states of america", &aliases).unwrap(),
+            us
+        );
+        // configured aliases map to their canonical country
+        assert_eq!(Country::normalize("United States", &aliases).unwrap(), us);
+        assert_eq!(Country::normalize(" united-states ", &aliases).unwrap(), us);
+        assert_eq!(
+            Country::normalize("uk", &aliases).unwrap().as_alpha2(),
+            "GB"
+        );
+        // unknown values are still rejected
+        assert!(Country::normalize("Atlantis", &aliases).is_err());
+        assert!(Country::normalize("", &aliases).is_err());
+        // as are aliases that point at invalid codes
+        let bad = HashMap::from([("Atlantis".to_owned(), "ZZ".to_owned())]);
+        assert!(Country::normalize("Atlantis", &bad).is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Country::from_alpha2("ZZ").is_err());
+        assert!(Country::from_alpha3("ZZZ").is_err());
+        // alpha-3 codes are not valid alpha-2 codes
+        assert!(Country::from_alpha2("USA").is_err());
+        assert!(Country::from_code("").is_err());
+        assert!(Country::from_code("UNITED").is_err());
+    }
+}
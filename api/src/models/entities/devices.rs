@@ -1,9 +1,10 @@
 //! Contains models for device entities
 
-use std::collections::BTreeSet;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeSet, HashMap};
 use uuid::Uuid;
 
-use crate::models::{CriticalSector, Entity, VendorEntity};
+use crate::models::{CriticalSector, Entity, EntityKinds, EntityListParams, VendorEntity};
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "api")] {
@@ -107,7 +108,7 @@ impl DeviceEntityRequest {
         // add our device metadata
         let form = multipart_list!(form, "metadata[urls][]", self.urls);
         // add our vendor ids
-        let form = multipart_list_conv!(form, "metadata[vendors][]", self.vendors);
+        let form = multipart_list_conv!(form, "metadata[vendor][]", self.vendors);
         // add whether this device is in a critical system or not
         let form =
             multipart_text_to_string!(form, "metadata[critical_system]", self.critical_system);
@@ -124,3 +125,206 @@ impl DeviceEntityRequest {
         Ok(form)
     }
 }
+
+/// The options that you can set when listing devices by critical sector in Thorium
+#[derive(Debug, Clone)]
+pub struct DeviceListOpts {
+    /// The critical sector to list devices from
+    pub sector: CriticalSector,
+    /// Only list devices that are or are not critical systems
+    pub critical_system: Option<bool>,
+    /// Only list devices that are or are not in sensitive locations
+    pub sensitive_location: Option<bool>,
+    /// The cursor to use to continue this search
+    pub cursor: Option<Uuid>,
+    /// The latest date to start listing devices from
+    pub start: Option<DateTime<Utc>>,
+    /// The oldest date to stop listing devices from
+    pub end: Option<DateTime<Utc>>,
+    /// The max number of objects to retrieve on a single page
+    pub page_size: usize,
+    /// The total number of objects to return with this cursor
+    pub limit: Option<usize>,
+    /// The groups limit our search to
+    pub groups: Vec<String>,
+}
+
+impl DeviceListOpts {
+    /// Create a new search for devices in a critical sector
+    ///
+    /// # Arguments
+    ///
+    /// * `sector` - The critical sector to list devices from
+    #[must_use]
+    pub fn new(sector: CriticalSector) -> Self {
+        DeviceListOpts {
+            sector,
+            critical_system: None,
+            sensitive_location: None,
+            cursor: None,
+            start: None,
+            end: None,
+            page_size: 50,
+            limit: None,
+            groups: Vec::default(),
+        }
+    }
+
+    /// Only list devices that are or are not critical systems
+    ///
+    /// # Arguments
+    ///
+    /// * `critical_system` - Whether listed devices should be critical systems
+    #[must_use]
+    pub fn critical_system(mut self, critical_system: bool) -> Self {
+        self.critical_system = Some(critical_system);
+        self
+    }
+
+    /// Only list devices that are or are not in sensitive locations
+    ///
+    /// # Arguments
+    ///
+    /// * `sensitive_location` - Whether listed devices should be in sensitive locations
+    #[must_use]
+    pub fn sensitive_location(mut self, sensitive_location: bool) -> Self {
+        self.sensitive_location = Some(sensitive_location);
+        self
+    }
+
+    /// Set the cursor to use when continuing this search
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor id to use for this search
+    #[must_use]
+    pub fn cursor(mut self, cursor: Uuid) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// Restrict the device search to start at a specific date
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The date to start listing devices from
+    #[must_use]
+    pub fn start(mut self, start: DateTime<Utc>) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Restrict the device search to stop at a specific date
+    ///
+    /// # Arguments
+    ///
+    /// * `end` - The date to stop listing devices at
+    #[must_use]
+    pub fn end(mut self, end: DateTime<Utc>) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// The max number of objects to retrieve in a single page
+    ///
+    /// # Arguments
+    ///
+    /// * `page_size` - The max number of documents to return in a single request
+    #[must_use]
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Limit how many devices this search can return at once
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The max number of objects to return over the lifetime of this cursor
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Limit what groups we search in
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The groups to restrict our search to
+    #[must_use]
+    pub fn groups<T: Into<String>>(mut self, groups: Vec<T>) -> Self {
+        self.groups
+            .extend(groups.into_iter().map(|group| group.into()));
+        self
+    }
+}
+
+/// The params for listing devices by critical sector
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct DeviceListParams {
+    /// The critical sector to list devices from
+    pub sector: CriticalSector,
+    /// Only list devices that are or are not critical systems
+    pub critical_system: Option<bool>,
+    /// Only list devices that are or are not in sensitive locations
+    pub sensitive_location: Option<bool>,
+    /// The groups to list data from
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// When to start listing data at
+    #[serde(default = "Utc::now")]
+    pub start: DateTime<Utc>,
+    /// When to stop listing data at
+    pub end: Option<DateTime<Utc>>,
+    /// The cursor id to use if one exists
+    pub cursor: Option<Uuid>,
+    /// The max number of items to return in this response
+    #[serde(default = "default_list_limit")]
+    pub limit: usize,
+}
+
+/// Default the device list limit to 50
+fn default_list_limit() -> usize {
+    50
+}
+
+impl DeviceListParams {
+    /// Check if a device matches the filters in these params
+    ///
+    /// # Arguments
+    ///
+    /// * `device` - The device to check
+    #[must_use]
+    pub fn matches(&self, device: &DeviceEntity) -> bool {
+        // make sure this device is actually in our sector and not just tagged with it
+        device.critical_sectors.contains(&self.sector)
+            && self
+                .critical_system
+                .is_none_or(|flag| device.critical_system == Some(flag))
+            && self
+                .sensitive_location
+                .is_none_or(|flag| device.sensitive_location == Some(flag))
+    }
+
+    /// Build the entity list params for the devices in our critical sector
+    #[must_use]
+    pub fn entity_params(&self) -> EntityListParams {
+        // critical sectors are stored as intrinsic tags so we can list on them directly
+        let mut tags = HashMap::with_capacity(1);
+        tags.insert("CriticalSectors".to_owned(), vec![self.sector.to_string()]);
+        EntityListParams {
+            groups: self.groups.clone(),
+            start: self.start,
+            end: self.end,
+            tags,
+            cursor: self.cursor,
+            limit: self.limit,
+            kinds: vec![EntityKinds::Device],
+            submitter: None,
+            include_archived: false,
+            partition_hint: None,
+        }
+    }
+}
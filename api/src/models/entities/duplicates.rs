@@ -0,0 +1,270 @@
+//! Contains models for detecting likely duplicate entities
+
+use uuid::Uuid;
+
+use crate::models::EntityKinds;
+
+/// The suffixes that are dropped from the end of names before they are compared
+const IGNORED_SUFFIXES: &[&str] = &[
+    "co",
+    "company",
+    "corp",
+    "corporation",
+    "gmbh",
+    "inc",
+    "incorporated",
+    "limited",
+    "llc",
+    "ltd",
+    "plc",
+];
+
+/// Normalize an entity name so that cosmetic differences are ignored when comparing
+///
+/// Names are lowercased, punctuation is dropped, and common corporate suffixes
+/// like `Corp` or `LLC` are removed unless they are the only word in the name.
+///
+/// # Arguments
+///
+/// * `name` - The name to normalize
+#[must_use]
+pub fn normalize_name(name: &str) -> String {
+    // lowercase our name and split it into words on anything that isn't alphanumeric
+    let lowered = name.to_lowercase();
+    let mut words = lowered
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>();
+    // drop a leading 'the' if its not the only word
+    if words.len() > 1 && words[0] == "the" {
+        words.remove(0);
+    }
+    // drop any trailing corporate suffixes as long as we leave at least one word
+    while words.len() > 1 && IGNORED_SUFFIXES.contains(&words[words.len() - 1]) {
+        words.pop();
+    }
+    words.join(" ")
+}
+
+/// Get how similar two normalized names are from 0.0 to 1.0
+///
+/// This is the Levenshtein distance between the two names scaled by the length of
+/// the longest name.
+///
+/// # Arguments
+///
+/// * `left` - The first normalized name to compare
+/// * `right` - The second normalized name to compare
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn name_similarity(left: &str, right: &str) -> f64 {
+    let left = left.chars().collect::<Vec<char>>();
+    let right = right.chars().collect::<Vec<char>>();
+    let longest = left.len().max(right.len());
+    // two empty names are identical
+    if longest == 0 {
+        return 1.0;
+    }
+    // compute the edit distance one row at a time
+    let mut previous = (0..=right.len()).collect::<Vec<usize>>();
+    let mut current = vec![0; right.len() + 1];
+    for (i, left_char) in left.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, right_char) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left_char != right_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    1.0 - (previous[right.len()] as f64 / longest as f64)
+}
+
+/// Default the max number of entities to scan for duplicates to 1000
+fn default_scan_limit() -> usize {
+    1000
+}
+
+/// The options that you can set when scanning for duplicate entities
+#[derive(Debug, Clone)]
+pub struct EntityDuplicateOpts {
+    /// The groups to scan for duplicates in
+    pub groups: Vec<String>,
+    /// The kinds of entities to scan; if empty all kinds are scanned
+    pub kinds: Vec<EntityKinds>,
+    /// The similarity threshold to use instead of the one in the Thorium config
+    pub threshold: Option<f64>,
+    /// The max number of entities to scan
+    pub limit: usize,
+}
+
+impl Default for EntityDuplicateOpts {
+    /// Build a default duplicate scan
+    fn default() -> Self {
+        EntityDuplicateOpts {
+            groups: Vec::default(),
+            kinds: Vec::default(),
+            threshold: None,
+            limit: default_scan_limit(),
+        }
+    }
+}
+
+impl EntityDuplicateOpts {
+    /// Limit what groups we scan for duplicates
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The groups to restrict our scan to
+    #[must_use]
+    pub fn groups<T: Into<String>>(mut self, groups: Vec<T>) -> Self {
+        self.groups
+            .extend(groups.into_iter().map(|group| group.into()));
+        self
+    }
+
+    /// Only scan a specific kind of entity
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of entity to scan
+    #[must_use]
+    pub fn kind(mut self, kind: EntityKinds) -> Self {
+        self.kinds.push(kind);
+        self
+    }
+
+    /// Set the similarity threshold names must meet to be reported
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The similarity threshold from 0.0 to 1.0
+    #[must_use]
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Set the max number of entities to scan
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The max number of entities to scan
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+/// The params for scanning for duplicate entities
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct EntityDuplicateParams {
+    /// The groups to scan for duplicates in
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// The kinds of entities to scan; if empty all kinds are scanned
+    #[serde(default)]
+    pub kinds: Vec<EntityKinds>,
+    /// The similarity threshold to use instead of the one in the Thorium config
+    pub threshold: Option<f64>,
+    /// The max number of entities to scan
+    #[serde(default = "default_scan_limit")]
+    pub limit: usize,
+}
+
+impl Default for EntityDuplicateParams {
+    /// Create default duplicate scan params
+    fn default() -> Self {
+        EntityDuplicateParams {
+            groups: Vec::default(),
+            kinds: Vec::default(),
+            threshold: None,
+            limit: default_scan_limit(),
+        }
+    }
+}
+
+/// An entity that may be a duplicate of another entity
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct DuplicateEntity {
+    /// The entity's unique ID
+    pub id: Uuid,
+    /// The entity's name
+    pub name: String,
+}
+
+/// Two entities that are likely duplicates of each other
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct EntityDuplicate {
+    /// The kind of entity both of these entities are
+    pub kind: EntityKinds,
+    /// The first entity in this pair
+    pub left: DuplicateEntity,
+    /// The second entity in this pair
+    pub right: DuplicateEntity,
+    /// How similar the normalized names of these entities are from 0.0 to 1.0
+    pub similarity: f64,
+}
+
+/// The likely duplicate entities found by a duplicate scan
+///
+/// This is a read-only report and nothing is merged or changed when it is built.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct EntityDuplicateReport {
+    /// The similarity threshold that was used
+    pub threshold: f64,
+    /// The number of entities that were scanned
+    pub scanned: usize,
+    /// The likely duplicates ordered from most to least similar
+    pub duplicates: Vec<EntityDuplicate>,
+    /// Whether more entities existed than were scanned
+    pub truncated: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{name_similarity, normalize_name};
+
+    #[test]
+    fn normalize() {
+        assert_eq!(normalize_name("Acme Corp."), "acme");
+        assert_eq!(normalize_name("ACME Corporation"), "acme");
+        assert_eq!(normalize_name("The Acme Co, LLC"), "acme");
+        assert_eq!(
+            normalize_name("Wayne-Tech Industries"),
+            "wayne tech industries"
+        );
+        // a suffix is kept if its the only word
+        assert_eq!(normalize_name("Corp"), "corp");
+    }
+
+    #[test]
+    fn near_duplicates() {
+        let pairs = [
+            ("Acme Corp", "Acme Corporation"),
+            ("Globex Inc", "Globex, Incorporated"),
+            ("Initech Systems", "Initech Sytems"),
+        ];
+        for (left, right) in pairs {
+            let similarity = name_similarity(&normalize_name(left), &normalize_name(right));
+            assert!(similarity >= 0.85, "{left} vs {right} was {similarity}");
+        }
+    }
+
+    #[test]
+    fn distinct() {
+        let pairs = [
+            ("Acme Corp", "Globex Corp"),
+            ("Harvester 3000", "Tractor Supply"),
+            ("Initech", "Umbrella Corporation"),
+        ];
+        for (left, right) in pairs {
+            let similarity = name_similarity(&normalize_name(left), &normalize_name(right));
+            assert!(similarity < 0.85, "{left} vs {right} was {similarity}");
+        }
+    }
+}
@@ -0,0 +1,387 @@
+//! Contains models for exporting the association graph between entities
+
+use std::fmt::Write;
+use uuid::Uuid;
+
+use crate::models::{AssociationKind, AssociationTarget, EntityKinds};
+
+/// The formats an entity graph can be exported in
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    strum::Display,
+    strum::EnumString,
+    clap::ValueEnum,
+)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub enum GraphFormat {
+    /// A node-link JSON document
+    #[default]
+    NodeLink,
+    /// A GraphML XML document
+    GraphMl,
+}
+
+/// The kinds of nodes in an entity graph
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, strum::Display)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub enum GraphNodeKind {
+    /// This node is an entity
+    Entity,
+    /// This node is a file
+    File,
+    /// This node is a repo
+    Repo,
+}
+
+/// A single node in an entity graph
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct GraphNode {
+    /// The unique id for this node
+    pub id: String,
+    /// A human readable label for this node
+    pub label: String,
+    /// The kind of data this node is for
+    pub kind: GraphNodeKind,
+    /// The kind of entity this node is if its an entity
+    pub entity_kind: Option<EntityKinds>,
+}
+
+impl GraphNode {
+    /// Build a graph node for an association target
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target to build a node for
+    /// * `entity_kind` - The kind of entity this node is if its an entity
+    #[must_use]
+    pub fn new(target: AssociationTarget, entity_kind: Option<EntityKinds>) -> Self {
+        match target {
+            AssociationTarget::Entity { id, name } => GraphNode {
+                id: id.to_string(),
+                label: name,
+                kind: GraphNodeKind::Entity,
+                entity_kind,
+            },
+            AssociationTarget::File(sha256) => GraphNode {
+                id: sha256.clone(),
+                label: sha256,
+                kind: GraphNodeKind::File,
+                entity_kind: None,
+            },
+            AssociationTarget::Repo(url) => GraphNode {
+                id: url.clone(),
+                label: url,
+                kind: GraphNodeKind::Repo,
+                entity_kind: None,
+            },
+        }
+    }
+}
+
+/// A single link between two nodes in an entity graph
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct GraphLink {
+    /// The id of the node this link starts at
+    pub source: String,
+    /// The id of the node this link ends at
+    pub target: String,
+    /// The kind of association this link is for
+    pub kind: AssociationKind,
+    /// Whether this link goes in both directions
+    pub bidirectional: bool,
+    /// How confident the submitter was in this association from 0 to 100
+    pub confidence: u8,
+}
+
+/// The association graph for some entities in node-link form
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct EntityGraph {
+    /// Whether this graph is directed; bidirectional links are marked on the link itself
+    pub directed: bool,
+    /// Whether multiple links can exist between the same two nodes
+    pub multigraph: bool,
+    /// The nodes in this graph
+    pub nodes: Vec<GraphNode>,
+    /// The links between the nodes in this graph
+    pub links: Vec<GraphLink>,
+    /// Whether nodes or links were dropped because a limit was hit
+    pub truncated: bool,
+}
+
+impl Default for EntityGraph {
+    /// Build an empty entity graph
+    fn default() -> Self {
+        EntityGraph {
+            directed: true,
+            multigraph: true,
+            nodes: Vec::default(),
+            links: Vec::default(),
+            truncated: false,
+        }
+    }
+}
+
+/// Escape a value so it can be safely embedded in XML
+///
+/// # Arguments
+///
+/// * `raw` - The value to escape
+fn escape_xml(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl EntityGraph {
+    /// Render this graph as a GraphML document
+    #[must_use]
+    pub fn to_graphml(&self) -> String {
+        // start our document and declare the attributes our nodes and edges have
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+            "  <key id=\"entity_kind\" for=\"node\" attr.name=\"entity_kind\" attr.type=\"string\"/>\n",
+            "  <key id=\"association\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+            "  <key id=\"confidence\" for=\"edge\" attr.name=\"confidence\" attr.type=\"int\"/>\n",
+            "  <graph id=\"entities\" edgedefault=\"directed\">\n",
+        ));
+        // writing to a string can't fail so ignore the results
+        for node in &self.nodes {
+            let _ = writeln!(xml, "    <node id=\"{}\">", escape_xml(&node.id));
+            let _ = writeln!(
+                xml,
+                "      <data key=\"label\">{}</data>",
+                escape_xml(&node.label)
+            );
+            let _ = writeln!(xml, "      <data key=\"kind\">{}</data>", node.kind);
+            if let Some(entity_kind) = &node.entity_kind {
+                let _ = writeln!(
+                    xml,
+                    "      <data key=\"entity_kind\">{}</data>",
+                    entity_kind.as_str()
+                );
+            }
+            xml.push_str("    </node>\n");
+        }
+        for link in &self.links {
+            let _ = writeln!(
+                xml,
+                "    <edge source=\"{}\" target=\"{}\" directed=\"{}\">",
+                escape_xml(&link.source),
+                escape_xml(&link.target),
+                !link.bidirectional
+            );
+            let _ = writeln!(
+                xml,
+                "      <data key=\"association\">{}</data>",
+                link.kind.as_str()
+            );
+            let _ = writeln!(
+                xml,
+                "      <data key=\"confidence\">{}</data>",
+                link.confidence
+            );
+            xml.push_str("    </edge>\n");
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+/// Default the max number of nodes in an exported graph to 1000
+fn default_max_nodes() -> usize {
+    1000
+}
+
+/// Default the max number of links in an exported graph to 5000
+fn default_max_links() -> usize {
+    5000
+}
+
+/// The options that you can set when exporting an entity graph from Thorium
+#[derive(Debug, Clone)]
+pub struct EntityGraphOpts {
+    /// The entities to export the graph around; if empty all entities in our groups are used
+    pub entities: Vec<Uuid>,
+    /// The groups limit our export to
+    pub groups: Vec<String>,
+    /// The kinds of associations to export; if empty all kinds are exported
+    pub kinds: Vec<AssociationKind>,
+    /// The max number of nodes to export
+    pub max_nodes: usize,
+    /// The max number of links to export
+    pub max_links: usize,
+}
+
+impl Default for EntityGraphOpts {
+    /// Build a default graph export
+    fn default() -> Self {
+        EntityGraphOpts {
+            entities: Vec::default(),
+            groups: Vec::default(),
+            kinds: Vec::default(),
+            max_nodes: default_max_nodes(),
+            max_links: default_max_links(),
+        }
+    }
+}
+
+impl EntityGraphOpts {
+    /// Add an entity to export the graph around
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the entity to add
+    #[must_use]
+    pub fn entity(mut self, id: Uuid) -> Self {
+        self.entities.push(id);
+        self
+    }
+
+    /// Limit what groups we export from
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The groups to restrict our export to
+    #[must_use]
+    pub fn groups<T: Into<String>>(mut self, groups: Vec<T>) -> Self {
+        self.groups
+            .extend(groups.into_iter().map(|group| group.into()));
+        self
+    }
+
+    /// Only export a specific kind of association
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of association to export
+    #[must_use]
+    pub fn kind(mut self, kind: AssociationKind) -> Self {
+        self.kinds.push(kind);
+        self
+    }
+
+    /// Set the max number of nodes to export
+    ///
+    /// # Arguments
+    ///
+    /// * `max_nodes` - The max number of nodes to export
+    #[must_use]
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    /// Set the max number of links to export
+    ///
+    /// # Arguments
+    ///
+    /// * `max_links` - The max number of links to export
+    #[must_use]
+    pub fn max_links(mut self, max_links: usize) -> Self {
+        self.max_links = max_links;
+        self
+    }
+}
+
+/// The params for exporting an entity graph
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct EntityGraphParams {
+    /// The entities to export the graph around; if empty all entities in our groups are used
+    #[serde(default)]
+    pub entities: Vec<Uuid>,
+    /// The groups to export from
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// The kinds of associations to export; if empty all kinds are exported
+    #[serde(default)]
+    pub kinds: Vec<AssociationKind>,
+    /// The format to export this graph in
+    #[serde(default)]
+    pub format: GraphFormat,
+    /// The max number of nodes to export
+    #[serde(default = "default_max_nodes")]
+    pub max_nodes: usize,
+    /// The max number of links to export
+    #[serde(default = "default_max_links")]
+    pub max_links: usize,
+}
+
+impl Default for EntityGraphParams {
+    /// Create default entity graph params
+    fn default() -> Self {
+        EntityGraphParams {
+            entities: Vec::default(),
+            groups: Vec::default(),
+            kinds: Vec::default(),
+            format: GraphFormat::default(),
+            max_nodes: default_max_nodes(),
+            max_links: default_max_links(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EntityGraph, GraphLink, GraphNode};
+    use crate::models::{AssociationKind, AssociationTarget, EntityKinds};
+
+    #[test]
+    fn graphml() {
+        // build a small graph with a device developed by a vendor
+        let mut graph = EntityGraph::default();
+        let device = GraphNode::new(
+            AssociationTarget::Entity {
+                id: uuid::Uuid::new_v4(),
+                name: "Harvester <X>".to_owned(),
+            },
+            Some(EntityKinds::Device),
+        );
+        let vendor = GraphNode::new(
+            AssociationTarget::Entity {
+                id: uuid::Uuid::new_v4(),
+                name: "Corn & Co".to_owned(),
+            },
+            Some(EntityKinds::Vendor),
+        );
+        graph.links.push(GraphLink {
+            source: device.id.clone(),
+            target: vendor.id.clone(),
+            kind: AssociationKind::DevelopedBy,
+            bidirectional: false,
+            confidence: 100,
+        });
+        graph.nodes.push(device);
+        graph.nodes.push(vendor);
+        let xml = graph.to_graphml();
+        // make sure all of our nodes and edges were written
+        assert_eq!(xml.matches("<node ").count(), 2);
+        assert_eq!(xml.matches("<edge ").count(), 1);
+        assert!(xml.contains("<data key=\"association\">DevelopedBy</data>"));
+        // make sure our labels were escaped
+        assert!(xml.contains("Harvester &lt;X&gt;"));
+        assert!(xml.contains("Corn &amp; Co"));
+    }
+}
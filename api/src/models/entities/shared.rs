@@ -1,6 +1,8 @@
 //! Contains models shared between multiple entity kinds
 
-use strum::EnumString;
+use std::collections::HashMap;
+use std::str::FromStr;
+use strum::{EnumIter, EnumString, IntoEnumIterator};
 
 /// A critical sector that an entity is associated with
 #[derive(
@@ -14,6 +16,7 @@ use strum::EnumString;
     PartialOrd,
     Ord,
     EnumString,
+    EnumIter,
     strum::Display,
 )]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -35,3 +38,112 @@ pub enum CriticalSector {
     TransportSystems,
     WaterWasteWater,
 }
+
+/// Build the key to compare a raw value against an alias table with
+///
+/// Keys ignore case, punctuation, and whitespace so `United-States` and
+/// `united states` are the same key.
+///
+/// # Arguments
+///
+/// * `raw` - The raw value to build a key for
+pub(crate) fn alias_key(raw: &str) -> String {
+    raw.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Find the canonical value for a raw value in an alias table
+///
+/// # Arguments
+///
+/// * `raw` - The raw value to look up
+/// * `aliases` - The aliases to check mapped to their canonical values
+pub(crate) fn find_alias<'a>(raw: &str, aliases: &'a HashMap<String, String>) -> Option<&'a str> {
+    let key = alias_key(raw);
+    aliases
+        .iter()
+        .find(|(alias, _)| alias_key(alias) == key)
+        .map(|(_, canonical)| canonical.as_str())
+}
+
+impl CriticalSector {
+    /// Parse a critical sector allowing for common synonyms
+    ///
+    /// Sector names are matched ignoring case, punctuation, and whitespace before
+    /// any aliases are checked. Values that match neither are still rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The raw critical sector to parse
+    /// * `aliases` - The aliases to accept mapped to their canonical sector names
+    pub fn normalize(
+        raw: &str,
+        aliases: &HashMap<String, String>,
+    ) -> Result<Self, strum::ParseError> {
+        // use this sector as is if its already a valid sector
+        if let Ok(sector) = Self::from_str(raw.trim()) {
+            return Ok(sector);
+        }
+        // check if this is a sector name with different casing or spacing
+        let key = alias_key(raw);
+        if let Some(sector) = Self::iter().find(|sector| alias_key(&sector.to_string()) == key) {
+            return Ok(sector);
+        }
+        // check if this is an alias for a sector
+        match find_alias(raw, aliases) {
+            Some(canonical) => Self::from_str(canonical),
+            None => Err(strum::ParseError::VariantNotFound),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::CriticalSector;
+
+    /// Build a small alias table to test with
+    fn aliases() -> HashMap<String, String> {
+        HashMap::from([
+            ("IT".to_owned(), "InformationTechnology".to_owned()),
+            ("Telecom".to_owned(), "Communications".to_owned()),
+        ])
+    }
+
+    #[test]
+    fn sector_aliases() {
+        let aliases = aliases();
+        // canonical names are always accepted
+        assert_eq!(
+            CriticalSector::normalize("Energy", &aliases).unwrap(),
+            CriticalSector::Energy
+        );
+        // so are names with different casing or spacing
+        assert_eq!(
+            CriticalSector::normalize("food & agriculture", &aliases).unwrap(),
+            CriticalSector::FoodAgriculture
+        );
+        // aliases map to their canonical sector
+        assert_eq!(
+            CriticalSector::normalize("it", &aliases).unwrap(),
+            CriticalSector::InformationTechnology
+        );
+        assert_eq!(
+            CriticalSector::normalize("TELECOM", &aliases).unwrap(),
+            CriticalSector::Communications
+        );
+    }
+
+    #[test]
+    fn sector_invalid() {
+        let aliases = aliases();
+        assert!(CriticalSector::normalize("Corn", &aliases).is_err());
+        assert!(CriticalSector::normalize("", &aliases).is_err());
+        // aliases pointing at invalid sectors are rejected too
+        let bad = HashMap::from([("Corn".to_owned(), "Cornfields".to_owned())]);
+        assert!(CriticalSector::normalize("Corn", &bad).is_err());
+    }
+}
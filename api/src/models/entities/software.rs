@@ -0,0 +1,139 @@
+//! Contains models for software entities
+
+use uuid::Uuid;
+
+use crate::models::Entity;
+
+#[cfg(feature = "client")]
+use crate::{multipart_list_conv, multipart_text};
+
+/// A software product entity
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct SoftwareEntity {
+    /// The version of this software
+    pub version: String,
+    /// The CPE identifier for this software if one exists
+    pub cpe: Option<String>,
+    /// The vendor entities that developed this software
+    #[serde(default)]
+    pub vendors: Vec<Entity>,
+}
+
+impl SoftwareEntity {
+    /// Create a new software entity with the info in the form
+    ///
+    /// # Errors
+    ///
+    /// * The form does not have a version set
+    /// * The CPE in the form is not a valid CPE identifier
+    ///
+    /// # Arguments
+    ///
+    /// * `form` -  The create form
+    #[cfg(feature = "api")]
+    pub fn from_form(form: super::EntityMetadataForm) -> Result<Self, crate::utils::ApiError> {
+        // if we don't have a version then return an error
+        let version = match form.version {
+            Some(version) if !version.is_empty() => version,
+            _ => return crate::bad!("Software entities must have a version!".to_owned()),
+        };
+        // make sure our cpe looks like a cpe
+        if let Some(cpe) = &form.cpe {
+            Self::validate_cpe(cpe)?;
+        }
+        // build our software entity
+        Ok(SoftwareEntity {
+            version,
+            cpe: form.cpe,
+            vendors: vec![],
+        })
+    }
+
+    /// Make sure a CPE identifier is in either the 2.3 formatted string or URI binding
+    ///
+    /// # Arguments
+    ///
+    /// * `cpe` - The CPE to validate
+    #[cfg(feature = "api")]
+    pub fn validate_cpe(cpe: &str) -> Result<(), crate::utils::ApiError> {
+        if cpe.starts_with("cpe:2.3:") || cpe.starts_with("cpe:/") {
+            Ok(())
+        } else {
+            crate::bad!(format!(
+                "'{cpe}' is not a valid CPE! CPEs must start with 'cpe:2.3:' or 'cpe:/'"
+            ))
+        }
+    }
+}
+
+/// A request for creating a new software entity in Thorium
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct SoftwareEntityRequest {
+    /// The version of this software
+    pub version: String,
+    /// The CPE identifier for this software if one exists
+    pub cpe: Option<String>,
+    /// The vendor entities that developed this software
+    #[serde(default)]
+    pub vendors: Vec<Uuid>,
+}
+
+impl SoftwareEntityRequest {
+    /// Create a new software entity request
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version of this software
+    pub fn new(version: impl Into<String>) -> Self {
+        SoftwareEntityRequest {
+            version: version.into(),
+            cpe: None,
+            vendors: Vec::default(),
+        }
+    }
+
+    /// Set the CPE identifier for this software
+    ///
+    /// # Arguments
+    ///
+    /// * `cpe` - The CPE identifier to set
+    #[must_use]
+    pub fn cpe(mut self, cpe: impl Into<String>) -> Self {
+        self.cpe = Some(cpe.into());
+        self
+    }
+
+    /// Add a vendor that developed this software
+    ///
+    /// # Arguments
+    ///
+    /// * `vendor` - The id of the vendor entity to add
+    #[must_use]
+    pub fn vendor(mut self, vendor: Uuid) -> Self {
+        self.vendors.push(vendor);
+        self
+    }
+
+    /// Add this software entity metadata to a form
+    ///
+    /// # Arguments
+    ///
+    /// * `form` - The form to add too
+    #[cfg(feature = "client")]
+    pub fn add_to_form(
+        mut self,
+        form: reqwest::multipart::Form,
+    ) -> Result<reqwest::multipart::Form, crate::Error> {
+        // always set our entity kind
+        let form = form.text("kind", super::EntityKinds::Software.as_str());
+        // set the version for this software
+        let form = form.text("metadata[version]", self.version);
+        // set our cpe if we have one
+        let form = multipart_text!(form, "metadata[cpe]", self.cpe);
+        // add our vendor ids
+        let form = multipart_list_conv!(form, "metadata[vendor][]", self.vendors);
+        Ok(form)
+    }
+}
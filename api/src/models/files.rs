@@ -37,9 +37,12 @@ cfg_if::cfg_if! {
 // client only imports
 cfg_if::cfg_if! {
     if #[cfg(feature = "client")] {
+        use futures::TryStreamExt;
+        use std::sync::{Arc, Mutex};
         use tokio::fs::{File, OpenOptions};
         use tokio::io::BufReader;
         use crate::client::Error;
+        use crate::utils::hashes::StandardHashers;
         use crate::{multipart_file, multipart_list, multipart_list_conv, multipart_text, multipart_text_to_string, multipart_set};
     }
 }
@@ -535,7 +538,36 @@ impl SampleRequest {
 
     /// Create a multipart form from this sample request
     #[cfg(feature = "client")]
-    pub async fn to_form(mut self) -> Result<reqwest::multipart::Form, Error> {
+    pub async fn to_form(self) -> Result<reqwest::multipart::Form, Error> {
+        self.build_form(None).await
+    }
+
+    /// Create a multipart form from this sample request that hashes our file as it's uploaded
+    ///
+    /// Our file is hashed as it is streamed so our hashers won't be finished until our
+    /// form has been completely sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `hashers` - The hashers to hash our file with
+    #[cfg(feature = "client")]
+    pub async fn to_hashed_form(
+        self,
+        hashers: &Arc<Mutex<StandardHashers>>,
+    ) -> Result<reqwest::multipart::Form, Error> {
+        self.build_form(Some(hashers)).await
+    }
+
+    /// Build a multipart form from this sample request
+    ///
+    /// # Arguments
+    ///
+    /// * `hashers` - The hashers to hash our file with if we are hashing it
+    #[cfg(feature = "client")]
+    async fn build_form(
+        mut self,
+        hashers: Option<&Arc<Mutex<StandardHashers>>>,
+    ) -> Result<reqwest::multipart::Form, Error> {
         // build the form we are going to send
         // disable percent encoding, as the API natively supports UTF-8
         let form = reqwest::multipart::Form::new().percent_encode_noop();
@@ -556,12 +588,22 @@ impl SampleRequest {
         // if a trigger depth was set then add that to our form
         let form = form.text("trigger_depth", format!("{}", self.trigger_depth));
         // read in this file if a path was set
-        let form = if let Some(path) = self.path.take() {
+        let form = match (self.path.take(), hashers) {
+            // a path was set so stream that file and hash it as its read
+            (Some(path), Some(hashers)) => {
+                form.part("data", hashed_file_part(&path, hashers.clone()).await?)
+            }
             // a path was set so read in that file and add it to the form
-            multipart_file!(form, "data", path)
-        } else {
+            (Some(path), None) => multipart_file!(form, "data", path),
             // no path was set so a buffer must have been used
-            form.part("data", self.data.unwrap().to_part()?)
+            (None, hashers) => {
+                let buffer = self.data.unwrap();
+                // our buffer is already in memory so just hash it now
+                if let Some(hashers) = hashers {
+                    lock_hashers(hashers).digest(&buffer.data);
+                }
+                form.part("data", buffer.to_part()?)
+            }
         };
         Ok(form)
     }
@@ -579,6 +621,48 @@ impl SampleRequest {
     }
 }
 
+/// Lock a set of hashers shared with an upload
+///
+/// # Arguments
+///
+/// * `hashers` - The hashers to lock
+#[cfg(feature = "client")]
+pub(crate) fn lock_hashers(
+    hashers: &Mutex<StandardHashers>,
+) -> std::sync::MutexGuard<'_, StandardHashers> {
+    // a poisoned lock only means a hashing thread panicked so keep using our hashers
+    hashers
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Build a form part that streams a file from disk and hashes it as it's read
+///
+/// # Arguments
+///
+/// * `path` - The path to the file to stream
+/// * `hashers` - The hashers to hash our file with
+#[cfg(feature = "client")]
+async fn hashed_file_part(
+    path: &Path,
+    hashers: Arc<Mutex<StandardHashers>>,
+) -> Result<reqwest::multipart::Part, Error> {
+    let file = File::open(path).await?;
+    // get the length of this file so we can size our buffer correctly
+    let len = file.metadata().await?.len();
+    // convert our file into a framed read stream that hashes each chunk as its read
+    let codec = tokio_util::codec::BytesCodec::new();
+    let stream = tokio_util::codec::FramedRead::new(file, codec)
+        .inspect_ok(move |chunk| lock_hashers(&hashers).digest(chunk));
+    // convert our stream to a body to pass to reqwest
+    let body = reqwest::Body::wrap_stream(stream);
+    // build the form part that contains this file
+    let part = reqwest::multipart::Part::stream_with_length(body, len)
+        .mime_str("multipart/form-data")?
+        .file_name(path.to_string_lossy().to_string());
+    Ok(part)
+}
+
 impl std::fmt::Debug for SampleRequest {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SampleRequest")
@@ -2954,6 +3038,8 @@ pub struct FileListParams {
     #[serde(default)]
     /// Whether matching on tags should be case-insensitive
     pub tags_case_insensitive: bool,
+    /// The number of partitions to crawl at once instead of the default
+    pub partition_hint: Option<u32>,
 }
 
 impl Default for FileListParams {
@@ -2967,6 +3053,7 @@ impl Default for FileListParams {
             cursor: None,
             limit: default_list_limit(),
             tags_case_insensitive: false,
+            partition_hint: None,
         }
     }
 }
@@ -2982,6 +3069,7 @@ impl From<FileListOpts> for FileListParams {
             cursor: opts.cursor,
             limit: opts.limit.unwrap_or_else(|| default_list_limit()),
             tags_case_insensitive: opts.tags_case_insensitive,
+            partition_hint: opts.partition_hint,
         }
     }
 }
@@ -3294,6 +3382,8 @@ pub struct FileListOpts {
     pub tags: HashMap<String, Vec<String>>,
     /// Whether matching on tags should be case-insensitive
     pub tags_case_insensitive: bool,
+    /// The number of partitions to crawl at once when listing
+    pub partition_hint: Option<u32>,
 }
 
 impl Default for FileListOpts {
@@ -3308,6 +3398,7 @@ impl Default for FileListOpts {
             groups: Vec::default(),
             tags: HashMap::default(),
             tags_case_insensitive: false,
+            partition_hint: None,
         }
     }
 }
@@ -3420,6 +3511,20 @@ impl FileListOpts {
         self.tags_case_insensitive = true;
         self
     }
+
+    /// Set how many partitions to crawl at once when listing
+    ///
+    /// Larger hints can reduce round trips when listing large groups but are capped
+    /// by the Thorium config.
+    ///
+    /// # Arguments
+    ///
+    /// * `partition_hint` - The number of partitions to crawl at once
+    #[must_use]
+    pub fn partition_hint(mut self, partition_hint: u32) -> Self {
+        self.partition_hint = Some(partition_hint);
+        self
+    }
 }
 
 /// Options for file deletion
@@ -3451,6 +3556,118 @@ pub struct ZipDownloadParams {
     pub password: Option<String>,
 }
 
+impl ZipDownloadParams {
+    /// Set the password to encrypt this zip with
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - The password to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::ZipDownloadParams;
+    ///
+    /// let params = ZipDownloadParams::default().password("corn");
+    /// ```
+    #[must_use]
+    pub fn password<T: Into<String>>(mut self, password: T) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+}
+
+/// An inclusive range of bytes to download from a file
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    /// The offset of the first byte in this range
+    pub start: u64,
+    /// The offset of the last byte in this range or None to read to the end of the file
+    pub end: Option<u64>,
+}
+
+impl ByteRange {
+    /// Create a new byte range
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The offset of the first byte in this range
+    /// * `end` - The offset of the last byte in this range
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::ByteRange;
+    ///
+    /// // the first kibibyte of a file
+    /// let range = ByteRange::new(0, 1023);
+    /// ```
+    #[must_use]
+    pub fn new(start: u64, end: u64) -> Self {
+        ByteRange {
+            start,
+            end: Some(end),
+        }
+    }
+
+    /// Build the value of a `Range` header for this range
+    #[must_use]
+    pub fn header(&self) -> String {
+        match self.end {
+            Some(end) => format!("bytes={}-{}", self.start, end),
+            None => format!("bytes={}-", self.start),
+        }
+    }
+
+    /// Build the value of a `Content-Range` header for this range
+    ///
+    /// The total size of the file is never known because ranges are read from
+    /// streams of uncarted data.
+    #[must_use]
+    pub fn content_range(&self) -> String {
+        format!("bytes {}-{}/*", self.start, self.end.unwrap_or(self.start))
+    }
+
+    /// Parse a byte range from the value of a `Range` header
+    ///
+    /// Only a single range with a start offset is supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The value of the `Range` header to parse
+    #[cfg(feature = "api")]
+    pub fn parse(raw: &str) -> Result<Self, crate::utils::ApiError> {
+        // make sure this is a range of bytes
+        let Some(spec) = raw.trim().strip_prefix("bytes=") else {
+            return crate::bad!(format!("Only byte ranges are supported, got '{raw}'"));
+        };
+        // we only support downloading a single range at once
+        if spec.contains(',') {
+            return crate::bad!("Only a single range can be downloaded at once".to_owned());
+        }
+        let Some((start, end)) = spec.split_once('-') else {
+            return crate::bad!(format!("Invalid byte range '{spec}'"));
+        };
+        // suffix ranges need the size of the file which we don't know until we uncart it
+        if start.trim().is_empty() {
+            return crate::bad!("Ranges must have a start offset".to_owned());
+        }
+        let start = start
+            .trim()
+            .parse()
+            .map_err(|_| crate::bad_internal!(format!("Invalid range start '{start}'")))?;
+        // an empty end means read to the end of the file
+        let end = match end.trim() {
+            "" => None,
+            end => Some(
+                end.parse()
+                    .map_err(|_| crate::bad_internal!(format!("Invalid range end '{end}'")))?,
+            ),
+        };
+        Ok(ByteRange { start, end })
+    }
+}
+
 #[derive(Debug)]
 #[cfg_attr(feature = "scylla-utils", derive(scylla::DeserializeRow))]
 #[cfg_attr(
@@ -1186,3 +1186,13 @@ impl GroupStats {
         self.pipelines.values().map(|map| map.total()).sum()
     }
 }
+
+/// The number of objects loaded into the server's caches for a group
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct CacheWarmResponse {
+    /// The number of images that were cached
+    pub images: usize,
+    /// The number of pipelines that were cached
+    pub pipelines: usize,
+}
@@ -12,7 +12,10 @@ use uuid::Uuid;
 
 use super::bans::Ban;
 use super::conversions::{self, ConversionError};
-use super::{GenericJob, OutputCollection, OutputCollectionUpdate, OutputDisplayType, Volume};
+use super::{
+    GenericJob, GenericJobArgs, OutputCollection, OutputCollectionUpdate, OutputDisplayType,
+    OutputRenderer, OutputRenderers, Volume,
+};
 use crate::conf::BurstableNodeResources;
 use crate::{
     matches_adds, matches_adds_iter, matches_adds_map, matches_clear, matches_clear_opt,
@@ -1555,6 +1558,9 @@ pub struct SampleDependencySettings {
     /// The strategy to when naming any downloaded files
     #[serde(default)]
     pub naming: FileNamingStrategy,
+    /// Whether the agent should verify that downloaded samples match their sha256
+    #[serde(default = "default_as_true")]
+    pub verify: bool,
 }
 
 impl Default for SampleDependencySettings {
@@ -1565,6 +1571,7 @@ impl Default for SampleDependencySettings {
             kwarg: None,
             strategy: DependencyPassStrategy::default(),
             naming: FileNamingStrategy::default(),
+            verify: true,
         }
     }
 }
@@ -1590,6 +1597,7 @@ impl SampleDependencySettings {
             kwarg: None,
             strategy,
             naming: FileNamingStrategy::default(),
+            verify: true,
         }
     }
 
@@ -1674,6 +1682,22 @@ impl SampleDependencySettings {
         self.naming = naming_strategy;
         self
     }
+
+    /// Skip verifying that downloaded samples match their sha256
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::SampleDependencySettings;
+    ///
+    /// SampleDependencySettings::default().skip_verify();
+    /// ```
+    #[must_use]
+    pub fn skip_verify(mut self) -> Self {
+        // disable verifying our downloaded samples
+        self.verify = false;
+        self
+    }
 }
 
 /// The default location the agent should download repos too
@@ -2564,6 +2588,8 @@ pub struct SampleDependencySettingsUpdate {
     pub strategy: Option<DependencyPassStrategy>,
     /// The strategy to when naming any downloaded files
     pub naming: Option<FileNamingStrategy>,
+    /// Whether the agent should verify that downloaded samples match their sha256
+    pub verify: Option<bool>,
 }
 
 impl SampleDependencySettingsUpdate {
@@ -2670,6 +2696,26 @@ impl SampleDependencySettingsUpdate {
         self.naming = Some(naming_strategy);
         self
     }
+
+    /// Set whether the agent should verify that downloaded samples match their sha256
+    ///
+    /// # Arguments
+    ///
+    /// * `verify` - Whether to verify downloaded samples
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::SampleDependencySettingsUpdate;
+    ///
+    /// SampleDependencySettingsUpdate::default().verify(false);
+    /// ```
+    #[must_use]
+    pub fn verify(mut self, verify: bool) -> Self {
+        // update whether we verify downloaded samples
+        self.verify = Some(verify);
+        self
+    }
 }
 
 impl PartialEq<SampleDependencySettingsUpdate> for SampleDependencySettings {
@@ -2684,6 +2730,7 @@ impl PartialEq<SampleDependencySettingsUpdate> for SampleDependencySettings {
         matches_update_opt!(self.kwarg, update.kwarg);
         matches_clear!(self.kwarg, update.clear_kwarg);
         matches_update!(self.strategy, update.strategy);
+        matches_update!(self.verify, update.verify);
         true
     }
 }
@@ -4000,6 +4047,9 @@ impl DependenciesUpdate {
 /// be submitted. If  If `submit_non_matches`is set, only children that
 /// do *not* match *any* of the given filters will be submitted. If
 /// no filters are given, all children will be submitted.
+///
+/// Filters prefixed with `glob:` (e.g. `glob:windows_*.txt`) are treated as
+/// glob patterns that must match the whole value instead of regular expressions.
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
 pub struct ChildFilters {
@@ -4134,6 +4184,103 @@ impl ChildFilters {
     }
 }
 
+/// A compiled child filter ready to be matched against children
+#[derive(Debug, Clone)]
+pub enum ChildFilterMatcher {
+    /// A regular expression from a regex filter or a glob with metacharacters
+    Pattern(regex::Regex),
+    /// A glob without any metacharacters that must match exactly
+    Exact(String),
+}
+
+impl ChildFilterMatcher {
+    /// The prefix that marks a child filter as a glob rather than a regular expression
+    pub const GLOB_PREFIX: &'static str = "glob:";
+
+    /// Compile a raw child filter
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The raw child filter to compile
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the filter is not a valid regular expression or glob.
+    pub fn compile(raw: &str) -> Result<Self, regex::Error> {
+        match raw.strip_prefix(Self::GLOB_PREFIX) {
+            // globs without any metacharacters are just exact matches
+            Some(glob) if !glob.contains(['*', '?', '[']) => Ok(Self::Exact(glob.to_owned())),
+            Some(glob) => Ok(Self::Pattern(regex::Regex::new(&Self::glob_to_regex(
+                glob,
+            ))?)),
+            None => Ok(Self::Pattern(regex::Regex::new(raw)?)),
+        }
+    }
+
+    /// Translate a glob into an anchored regular expression
+    ///
+    /// `*` matches any number of characters, `?` matches a single character, and
+    /// `[...]` matches a character class that can be negated with `[!...]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `glob` - The glob to translate
+    fn glob_to_regex(glob: &str) -> String {
+        let mut pattern = String::with_capacity(glob.len() + 8);
+        pattern.push('^');
+        let mut chars = glob.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => pattern.push_str(".*"),
+                '?' => pattern.push('.'),
+                '[' => {
+                    // copy this character class over, negating it if needed
+                    pattern.push('[');
+                    if chars.next_if(|next| *next == '!' || *next == '^').is_some() {
+                        pattern.push('^');
+                    }
+                    // a leading ']' is a literal in globs
+                    if chars.next_if_eq(&']').is_some() {
+                        pattern.push_str("\\]");
+                    }
+                    for c in chars.by_ref() {
+                        match c {
+                            // close our class; unterminated classes are left open so
+                            // they fail to compile
+                            ']' => {
+                                pattern.push(']');
+                                break;
+                            }
+                            // escape anything that would change the meaning of the class
+                            '\\' | '[' | '&' | '~' => {
+                                pattern.push('\\');
+                                pattern.push(c);
+                            }
+                            _ => pattern.push(c),
+                        }
+                    }
+                }
+                _ => pattern.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        pattern.push('$');
+        pattern
+    }
+
+    /// Check if a value matches this filter
+    ///
+    /// # Arguments
+    ///
+    /// * `haystack` - The value to check
+    #[must_use]
+    pub fn is_match(&self, haystack: &str) -> bool {
+        match self {
+            Self::Pattern(regex) => regex.is_match(haystack),
+            Self::Exact(exact) => exact == haystack,
+        }
+    }
+}
+
 /// An update to an image's child filters
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -6046,6 +6193,213 @@ pub struct Image {
     pub network_policies: HashSet<String>,
 }
 
+impl Image {
+    /// Get the metadata describing how this images results should be rendered
+    ///
+    /// # Arguments
+    ///
+    /// * `renderers` - The renderer registry to resolve our display type with
+    #[must_use]
+    pub fn renderer(&self, renderers: &OutputRenderers) -> OutputRenderer {
+        renderers.get(self.display_type, &self.name)
+    }
+
+    /// Get the kwargs the agent injects into this image's jobs
+    ///
+    /// Each kwarg is returned with what sets it and whether the agent replaces any values
+    /// a job set for it instead of appending to them.
+    fn injected_kwargs(&self) -> Vec<(&str, &'static str, bool)> {
+        let mut injected = Vec::new();
+        // generators are always passed their job and reaction ids
+        if self.generator {
+            injected.push(("--job", "generator job id", true));
+            injected.push(("--reaction", "generator reaction id", true));
+        }
+        // get the kwargs from our image args
+        if let Some(key) = &self.args.reaction {
+            injected.push((key.as_str(), "reaction id arg", true));
+        }
+        if let ArgStrategy::Kwarg(key) = &self.args.output {
+            injected.push((key.as_str(), "output arg", true));
+        }
+        if let ArgStrategy::Kwarg(key) = &self.args.output_files {
+            injected.push((key.as_str(), "output files arg", true));
+        }
+        if let Some(key) = &self.args.repo {
+            injected.push((key.as_str(), "repo url arg", false));
+        }
+        if let Some(key) = &self.args.commit {
+            injected.push((key.as_str(), "repo commit arg", false));
+        }
+        // get the kwargs for any dependencies that are passed in
+        let deps = &self.dependencies;
+        let dep_kwargs = [
+            (&deps.samples.kwarg, &deps.samples.strategy, true, "sample"),
+            (
+                &deps.ephemeral.kwarg,
+                &deps.ephemeral.strategy,
+                true,
+                "ephemeral",
+            ),
+            (&deps.repos.kwarg, &deps.repos.strategy, true, "repo"),
+            (
+                &deps.tags.kwarg,
+                &deps.tags.strategy,
+                deps.tags.enabled,
+                "tag",
+            ),
+            (
+                &deps.children.kwarg,
+                &deps.children.strategy,
+                deps.children.enabled,
+                "children",
+            ),
+            (
+                &deps.cache.generic.kwarg,
+                &deps.cache.generic.strategy,
+                deps.cache.enabled,
+                "cache",
+            ),
+        ];
+        for (kwarg, strategy, enabled, name) in dep_kwargs {
+            // skip any dependencies that are not passed in
+            if !enabled || *strategy == DependencyPassStrategy::Disabled {
+                continue;
+            }
+            if let Some(key) = kwarg {
+                injected.push((key.as_str(), name, false));
+            }
+        }
+        // result dependencies can be passed in with one kwarg or a kwarg per image
+        if deps.results.strategy != DependencyPassStrategy::Disabled {
+            match &deps.results.kwarg {
+                KwargDependency::List(key) => injected.push((key.as_str(), "result", false)),
+                KwargDependency::Map(map) => {
+                    injected.extend(map.values().map(|key| (key.as_str(), "result", false)));
+                }
+                KwargDependency::None => (),
+            }
+        }
+        injected
+    }
+
+    /// Check if a job's args are compatible with this image
+    ///
+    /// This follows the same rules the agent uses when building a job's command so any
+    /// args that would be silently ignored, replaced, or dropped are reported. Kwargs in
+    /// an image's original container entrypoint or command are not known and so are not
+    /// checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The job args to check
+    ///
+    /// # Errors
+    ///
+    /// Returns a human readable description of each problem with these args.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{GenericJobArgs, GenericJobOpts, Image};
+    ///
+    /// # fn exec(image: Image) {
+    /// // an override command ignores any other args
+    /// let args = GenericJobArgs::default()
+    ///     .kwarg("--field", vec!["north"])
+    ///     .opts(GenericJobOpts::new(false, false, Some(vec!["harvest.sh".to_owned()])));
+    /// assert!(image.validate_args(&args).is_err());
+    /// # }
+    /// ```
+    pub fn validate_args(&self, args: &GenericJobArgs) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+        let opts = &args.opts;
+        // check if a kwarg was set by this job
+        let is_set = |key: &str| {
+            args.kwargs.contains_key(key) || args.ordered_kwargs.iter().any(|(set, _)| set == key)
+        };
+        // an override command replaces everything else so no other args are used
+        if let Some(override_cmd) = &opts.override_cmd {
+            if override_cmd.is_empty() {
+                problems.push("The override command must not be empty".to_owned());
+            }
+            if !args.positionals.is_empty()
+                || !args.kwargs.is_empty()
+                || !args.ordered_kwargs.is_empty()
+                || !args.switches.is_empty()
+            {
+                problems.push(
+                    "Positionals, kwargs, and switches are ignored when an override command is set"
+                        .to_owned(),
+                );
+            }
+            if opts.override_positionals || opts.override_kwargs {
+                problems.push(
+                    "Overriding positionals or kwargs conflicts with setting an override command"
+                        .to_owned(),
+                );
+            }
+        }
+        // sorted kwargs take precedence over ordered kwargs with the same key
+        for (key, _) in &args.ordered_kwargs {
+            if args.kwargs.contains_key(key) {
+                problems.push(format!(
+                    "The kwarg '{key}' is set as both a kwarg and an ordered kwarg so its ordered values are ignored"
+                ));
+            }
+        }
+        // make sure we don't set any kwargs the agent injects
+        for (key, source, replaces) in self.injected_kwargs() {
+            if is_set(key) {
+                if replaces {
+                    problems.push(format!(
+                        "The kwarg '{key}' is set by this image's {source} and would be replaced"
+                    ));
+                } else {
+                    problems.push(format!(
+                        "The kwarg '{key}' is used to pass this image's {source} dependencies so its values would be mixed with them"
+                    ));
+                }
+            }
+        }
+        // overriding kwargs drops any kwargs in our image's command that this job does not set
+        if opts.override_kwargs && opts.override_cmd.is_none() {
+            let configured = self
+                .args
+                .entrypoint
+                .iter()
+                .chain(self.args.command.iter())
+                .flatten()
+                .filter(|arg| arg.starts_with('-'))
+                // get the key for any joint key=value kwargs
+                .map(|arg| arg.split_once('=').map_or(arg.as_str(), |(key, _)| key));
+            for key in configured {
+                if !is_set(key) {
+                    problems.push(format!(
+                        "The kwarg '{key}' in this image's command is required when overriding kwargs but is not set"
+                    ));
+                }
+            }
+        }
+        // the agent requires working directories to be absolute paths
+        match &opts.working_dir {
+            Some(working_dir) if !working_dir.is_absolute() => problems.push(format!(
+                "The working directory {} must be an absolute path",
+                working_dir.display()
+            )),
+            None if opts.create_working_dir => {
+                problems.push("A working directory must be set for it to be created".to_owned());
+            }
+            _ => (),
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+}
+
 impl PartialEq<ImageRequest> for Image {
     /// Check if a [`ImageRequest`] and a [`Image`] are equal
     ///
@@ -6202,3 +6556,83 @@ impl Default for ImageListParams {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ChildFilterMatcher;
+
+    /// The candidate child file names to match against
+    const CANDIDATES: [&str; 7] = [
+        "windows_pslist.txt",
+        "windows_pstree.txt",
+        "windows_netscan.json",
+        "linux_pslist.txt",
+        "dump1.bin",
+        "dump2.bin",
+        "dumpA.bin",
+    ];
+
+    /// Get the candidates that match a raw filter
+    fn matches(raw: &str) -> Vec<&'static str> {
+        let matcher = ChildFilterMatcher::compile(raw).unwrap();
+        CANDIDATES
+            .into_iter()
+            .filter(|candidate| matcher.is_match(candidate))
+            .collect()
+    }
+
+    #[test]
+    fn glob_star() {
+        assert_eq!(
+            matches("glob:windows_*.txt"),
+            vec!["windows_pslist.txt", "windows_pstree.txt"]
+        );
+        assert_eq!(
+            matches("glob:*pslist*"),
+            vec!["windows_pslist.txt", "linux_pslist.txt"]
+        );
+        // globs must match the entire value
+        assert_eq!(matches("glob:windows_*").len(), 3);
+        assert!(matches("glob:pslist*").is_empty());
+    }
+
+    #[test]
+    fn glob_question() {
+        assert_eq!(
+            matches("glob:dump?.bin"),
+            vec!["dump1.bin", "dump2.bin", "dumpA.bin"]
+        );
+        assert!(matches("glob:dump?bin").is_empty());
+    }
+
+    #[test]
+    fn glob_class() {
+        assert_eq!(
+            matches("glob:dump[0-9].bin"),
+            vec!["dump1.bin", "dump2.bin"]
+        );
+        assert_eq!(matches("glob:dump[!0-9].bin"), vec!["dumpA.bin"]);
+        assert_eq!(
+            matches("glob:windows_ps[lt]*.txt"),
+            vec!["windows_pslist.txt", "windows_pstree.txt"]
+        );
+        // unterminated classes are invalid
+        assert!(ChildFilterMatcher::compile("glob:dump[0-9.bin").is_err());
+    }
+
+    #[test]
+    fn glob_exact() {
+        // globs without metacharacters fall back to exact matches
+        let matcher = ChildFilterMatcher::compile("glob:dump1.bin").unwrap();
+        assert!(matches!(matcher, ChildFilterMatcher::Exact(_)));
+        assert_eq!(matches("glob:dump1.bin"), vec!["dump1.bin"]);
+        assert!(matches("glob:dump1").is_empty());
+    }
+
+    #[test]
+    fn regex_filters() {
+        // filters without the glob prefix are still unanchored regular expressions
+        assert_eq!(matches(r"^dump\d"), vec!["dump1.bin", "dump2.bin"]);
+        assert_eq!(matches("netscan").len(), 1);
+    }
+}
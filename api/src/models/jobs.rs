@@ -4,6 +4,7 @@
 use chrono::prelude::*;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::path::PathBuf;
 use uuid::Uuid;
 
 use super::{ImageScaler, Reaction, RepoDependency, SystemComponents};
@@ -201,6 +202,26 @@ pub struct HandleJobResponse {
     pub status: JobHandleStatus,
 }
 
+/// The response from reporting that a job has started executing
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(from_py_object))]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct JobStarted {
+    /// When this job started executing
+    pub started: DateTime<Utc>,
+    /// The time this job must finish executing by if its image has a timeout
+    pub execution_deadline: Option<DateTime<Utc>>,
+}
+
+/// The response from sending a heartbeat for a running job
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyclass(from_py_object))]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct JobHeartbeat {
+    /// The time this job must now finish executing by if its image has a timeout
+    pub execution_deadline: Option<DateTime<Utc>>,
+}
+
 /// A checkpoint string for a job
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -233,6 +254,10 @@ pub struct RawJob {
     pub status: JobStatus,
     /// The time this job must be started by
     pub deadline: DateTime<Utc>,
+    /// When the agent reported that this job started executing
+    pub started: Option<DateTime<Utc>>,
+    /// The time this job must finish executing by once it has started
+    pub execution_deadline: Option<DateTime<Utc>>,
     /// The container/node that is working on this job
     pub worker: Option<String>,
     /// The parent reaction to this jobs reaction if it exists
@@ -256,6 +281,12 @@ pub struct RawJob {
 /// Keyword args for generic jobs
 pub type GenericJobKwargs = BTreeMap<String, Vec<String>>;
 
+/// Keyword args for generic jobs that retain the order they were added in
+///
+/// Unlike [`GenericJobKwargs`] which is sorted by key, these are serialized as a list
+/// of key/values pairs so the order callers add them in is what the agent will emit.
+pub type GenericJobOrderedKwargs = Vec<(String, Vec<String>)>;
+
 /// Helps serde default a value to false
 fn default_false() -> bool {
     false
@@ -275,6 +306,13 @@ pub struct GenericJobOpts {
     pub override_kwargs: bool,
     /// The cmd to override the original cmd from the image with in its entirety
     pub override_cmd: Option<Vec<String>>,
+    /// The absolute path to launch this job in instead of the agent's working directory
+    #[serde(default)]
+    #[cfg_attr(feature = "api", schema(value_type = Option<String>))]
+    pub working_dir: Option<PathBuf>,
+    /// Whether to create the working directory if it doesn't already exist
+    #[serde(default = "default_false")]
+    pub create_working_dir: bool,
 }
 
 impl Default for GenericJobOpts {
@@ -284,6 +322,8 @@ impl Default for GenericJobOpts {
             override_positionals: false,
             override_kwargs: false,
             override_cmd: None,
+            working_dir: None,
+            create_working_dir: false,
         }
     }
 }
@@ -313,8 +353,51 @@ impl GenericJobOpts {
             override_positionals: positionals,
             override_kwargs: kwargs,
             override_cmd: cmd,
+            working_dir: None,
+            create_working_dir: false,
         }
     }
+
+    /// Set the directory the agent should launch this job in
+    ///
+    /// This must be an absolute path that exists on the worker unless
+    /// [`GenericJobOpts::create_working_dir`] is also set.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The absolute path to launch this job in
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::GenericJobOpts;
+    ///
+    /// // launch this job in a specific field
+    /// let opts = GenericJobOpts::default().working_dir("/fields/west-3");
+    /// ```
+    #[must_use]
+    pub fn working_dir<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.working_dir = Some(path.into());
+        self
+    }
+
+    /// Create the working directory for this job if it doesn't already exist
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::GenericJobOpts;
+    ///
+    /// // launch this job in a field that may not have been plowed yet
+    /// let opts = GenericJobOpts::default()
+    ///     .working_dir("/fields/west-3")
+    ///     .create_working_dir();
+    /// ```
+    #[must_use]
+    pub fn create_working_dir(mut self) -> Self {
+        self.create_working_dir = true;
+        self
+    }
 }
 
 /// Arguments for a [`GenericJob`]
@@ -329,6 +412,8 @@ impl GenericJobOpts {
 /// let args = GenericJobArgs::default()
 ///     .positionals(vec!("corn"))
 ///     .kwarg("field", vec!("west-3"))
+///     .ordered_kwarg("--plant", vec!("corn"))
+///     .ordered_kwarg("--harvest", vec!("corn"))
 ///     .switches(vec!("--combine"))
 ///     .opts(GenericJobOpts::new(true, false, None));
 /// ```
@@ -343,6 +428,9 @@ pub struct GenericJobArgs {
     /// The keyword arguments to overlay onto the original cmd
     #[serde(default)]
     pub kwargs: GenericJobKwargs,
+    /// The keyword arguments to overlay onto the original cmd in the order they were added
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ordered_kwargs: GenericJobOrderedKwargs,
     /// The switch arguments to overlay onto the original cmd
     #[serde(default)]
     pub switches: Vec<String>,
@@ -374,6 +462,37 @@ impl GenericJobArgs {
         self
     }
 
+    /// Adds a keyword arg to this job while preserving the order kwargs are added in
+    ///
+    /// Kwargs added with [`GenericJobArgs::kwarg`] are stored in a [`BTreeMap`] and so are
+    /// always emitted sorted by key. Kwargs added here are emitted in the order they were
+    /// added after any sorted kwargs. Adding a key that was already added replaces its
+    /// values but keeps its original position.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key for this keyword arg
+    /// * `values` - The values for this keyword arg
+    pub fn ordered_kwarg<K: Into<String>, V: Into<String>>(
+        mut self,
+        key: K,
+        values: Vec<V>,
+    ) -> Self {
+        // convert our key and values to strings
+        let key = key.into();
+        let converted = values.into_iter().map(|value| value.into()).collect();
+        // replace this kwargs values if it already exists or add it to the end
+        match self
+            .ordered_kwargs
+            .iter_mut()
+            .find(|(existing, _)| existing == &key)
+        {
+            Some((_, values)) => *values = converted,
+            None => self.ordered_kwargs.push((key, converted)),
+        }
+        self
+    }
+
     /// Adds a switch to this job
     pub fn switch<T: Into<String>>(mut self, switch: T) -> Self {
         self.switches.push(switch.into());
@@ -397,7 +516,10 @@ impl GenericJobArgs {
     /// Cast all of the args to a Vector
     pub fn to_vec(&self) -> Vec<String> {
         // figure out how large our vec should be
-        let size = self.positionals.len() + self.kwargs.len() + self.switches.len();
+        let size = self.positionals.len()
+            + self.kwargs.len()
+            + self.ordered_kwargs.len()
+            + self.switches.len();
         let mut casts = Vec::with_capacity(size);
         // start with our posiitonals and switches
         casts.extend_from_slice(&self.positionals);
@@ -407,6 +529,11 @@ impl GenericJobArgs {
             // combine and cast our kwargs
             casts.extend(values.iter().map(|val| format!("{}={}", key, val)));
         }
+        // crawl over our ordered kwargs in the order they were added
+        for (key, values) in self.ordered_kwargs.iter() {
+            // combine and cast our kwargs
+            casts.extend(values.iter().map(|val| format!("{}={}", key, val)));
+        }
         casts
     }
 }
@@ -640,6 +767,15 @@ pub struct GenericJob {
     pub status: JobStatus,
     /// The time this job must be started by
     pub deadline: DateTime<Utc>,
+    /// When the agent reported that this job started executing
+    #[serde(default)]
+    pub started: Option<DateTime<Utc>>,
+    /// The time this job must finish executing by once it has started
+    ///
+    /// This is based on the image's timeout and is only set once the agent reports that this
+    /// job has started, so time spent waiting in a queue doesn't eat into a job's runtime.
+    #[serde(default)]
+    pub execution_deadline: Option<DateTime<Utc>>,
     /// The parent reaction to this jobs reaction if it exists
     pub parent: Option<Uuid>,
     /// Whether this job is a generator or not
@@ -1,9 +1,10 @@
 //! Wrappers for interacting with status logs within Thorium with different backends
 //! Currently only Redis is supported
 
-use chrono::DateTime;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
+use super::Reaction;
 use super::jobs::JobResetRequestor;
 
 /// Actions that could occur in the status log from a Job object
@@ -99,3 +100,70 @@ pub struct StatusUpdate {
     /// The update that occurred
     pub update: HashMap<String, String>,
 }
+
+impl StatusUpdate {
+    /// Create a status update for a reaction that occurred now
+    ///
+    /// # Arguments
+    ///
+    /// * `reaction` - The reaction this status update is for
+    /// * `action` - The action that occurred
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{Actions, Reaction, StatusUpdate};
+    ///
+    /// # fn exec(reaction: &Reaction) {
+    /// let update = StatusUpdate::for_reaction(reaction, Actions::ReactionProceeding)
+    ///     .msg("Finished unpacking")
+    ///     .update("unpacked", "12");
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn for_reaction(reaction: &Reaction, action: Actions) -> Self {
+        StatusUpdate {
+            group: reaction.group.clone(),
+            pipeline: reaction.pipeline.clone(),
+            reaction: reaction.id.to_string(),
+            action,
+            timestamp: Utc::now(),
+            msg: None,
+            update: HashMap::default(),
+        }
+    }
+
+    /// Set the message for this status update
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to set
+    #[must_use]
+    pub fn msg<T: Into<String>>(mut self, msg: T) -> Self {
+        self.msg = Some(msg.into());
+        self
+    }
+
+    /// Set when this status update occurred
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - The timestamp to set
+    #[must_use]
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Add a key/value pair describing what changed in this status update
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to add
+    /// * `value` - The value to add
+    #[must_use]
+    pub fn update<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.update.insert(key.into(), value.into());
+        self
+    }
+}
@@ -0,0 +1,131 @@
+//! The models used to talk to Thorium's MCP server
+//!
+//! MCP messages are JSON-RPC so these mirror the subset of the MCP spec that the client uses.
+
+/// The version of JSON-RPC that MCP messages use
+pub const MCP_JSONRPC_VERSION: &str = "2.0";
+
+/// The version of the MCP protocol that the Thorium client speaks
+pub const MCP_PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// The header MCP sessions are tracked with
+pub const MCP_SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// A JSON-RPC request or notification to send to an MCP server
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct McpRequest {
+    /// The version of JSON-RPC this request uses
+    pub jsonrpc: String,
+    /// The id to match our response with (notifications have no id)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    /// The MCP method to call
+    pub method: String,
+    /// The params to call this method with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<serde_json::Value>,
+}
+
+impl McpRequest {
+    /// Create a new MCP request
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to match our response with
+    /// * `method` - The MCP method to call
+    pub fn new<T: Into<String>>(id: u64, method: T) -> Self {
+        McpRequest {
+            jsonrpc: MCP_JSONRPC_VERSION.to_owned(),
+            id: Some(id),
+            method: method.into(),
+            params: None,
+        }
+    }
+
+    /// Create a new MCP notification that does not get a response
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The MCP method to call
+    pub fn notification<T: Into<String>>(method: T) -> Self {
+        McpRequest {
+            jsonrpc: MCP_JSONRPC_VERSION.to_owned(),
+            id: None,
+            method: method.into(),
+            params: None,
+        }
+    }
+
+    /// Set the params to call this method with
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The params to set
+    #[must_use]
+    pub fn params(mut self, params: serde_json::Value) -> Self {
+        self.params = Some(params);
+        self
+    }
+}
+
+/// An error returned by an MCP server
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct McpError {
+    /// The JSON-RPC error code
+    pub code: i64,
+    /// A message describing this error
+    pub message: String,
+    /// Any extra data about this error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+/// A JSON-RPC response from an MCP server
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct McpResponse<T> {
+    /// The version of JSON-RPC this response uses
+    pub jsonrpc: String,
+    /// The id of the request this is a response to
+    #[serde(default)]
+    pub id: Option<u64>,
+    /// The result of our request if it succeeded
+    #[serde(default = "Option::default")]
+    pub result: Option<T>,
+    /// The error for our request if it failed
+    #[serde(default)]
+    pub error: Option<McpError>,
+}
+
+/// A tool exposed by an MCP server
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "python",
+    thorium_derive::pyclass(get_except(input_schema, output_schema))
+)]
+pub struct McpTool {
+    /// The name to call this tool by
+    pub name: String,
+    /// A human readable title for this tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// A description of what this tool does
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The JSON schema for this tool's params
+    pub input_schema: serde_json::Value,
+    /// The JSON schema for this tool's output if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
+}
+
+/// A single page of tools exposed by an MCP server
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolList {
+    /// The tools in this page
+    pub tools: Vec<McpTool>,
+    /// The cursor to use to get the next page of tools if there are more
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
@@ -16,6 +16,7 @@ pub mod helpers;
 pub mod images;
 pub mod jobs;
 pub mod logs;
+pub mod mcp;
 pub mod network_policies;
 pub mod notifications;
 pub mod pipelines;
@@ -40,13 +41,23 @@ pub use deadlines::Deadline;
 pub use elastic::{ElasticDoc, ElasticIndex, ElasticSearchOpts, ElasticSearchParams};
 pub use entities::collections::{CollectionEntity, CollectionEntityRequest, CollectionKind};
 pub use entities::countries::Country;
-pub use entities::devices::{DeviceEntity, DeviceEntityRequest};
+pub use entities::devices::{DeviceEntity, DeviceEntityRequest, DeviceListOpts, DeviceListParams};
+pub use entities::duplicates::{
+    DuplicateEntity, EntityDuplicate, EntityDuplicateOpts, EntityDuplicateParams,
+    EntityDuplicateReport,
+};
 pub use entities::filesystem::{FileSystemEntity, FileSystemEntityBuilder};
+pub use entities::graph::{
+    EntityGraph, EntityGraphOpts, EntityGraphParams, GraphFormat, GraphLink, GraphNode,
+    GraphNodeKind,
+};
 pub use entities::shared::CriticalSector;
+pub use entities::software::{SoftwareEntity, SoftwareEntityRequest};
 pub use entities::vendors::{VendorEntity, VendorEntityRequest};
 pub use entities::{
-    Entity, EntityKinds, EntityListLine, EntityListOpts, EntityListParams, EntityMetadata,
-    EntityMetadataRequest, EntityRequest, EntityResponse, EntityUpdate,
+    BulkEntityResponse, Entity, EntityImage, EntityKinds, EntityListLine, EntityListOpts,
+    EntityListParams, EntityMetadata, EntityMetadataRequest, EntityRequest, EntityResponse,
+    EntityUpdate,
 };
 pub use errors::InvalidEnum;
 pub use events::{
@@ -54,12 +65,12 @@ pub use events::{
     EventRequest, EventTrigger, EventType, TriggerPotential,
 };
 pub use files::{
-    Attachment, Buffer, CartedFile, CarvedOrigin, CarvedOriginTypes, Comment, CommentRequest,
-    CommentResponse, DeleteCommentParams, DeleteSampleParams, DownloadedFile, FileDeleteOpts,
-    FileDownloadOpts, FileListOpts, FileListParams, Origin, OriginRequest, OriginTypes,
-    PcapNetworkProtocol, Sample, SampleCheck, SampleCheckResponse, SampleListLine, SampleRequest,
-    SampleSubmissionResponse, Submission, SubmissionChunk, SubmissionUpdate, Tag, TagMap,
-    ZipDownloadParams,
+    Attachment, Buffer, ByteRange, CartedFile, CarvedOrigin, CarvedOriginTypes, Comment,
+    CommentRequest, CommentResponse, DeleteCommentParams, DeleteSampleParams, DownloadedFile,
+    FileDeleteOpts, FileDownloadOpts, FileListOpts, FileListParams, Origin, OriginRequest,
+    OriginTypes, PcapNetworkProtocol, Sample, SampleCheck, SampleCheckResponse, SampleListLine,
+    SampleRequest, SampleSubmissionResponse, Submission, SubmissionChunk, SubmissionUpdate, Tag,
+    TagMap, ZipDownloadParams,
 };
 pub use git::{
     Branch, BranchDetails, BranchRequest, Commit, CommitDetails, CommitListOpts, CommitRequest,
@@ -70,20 +81,20 @@ pub use git::{
     RepoUrlComponents, TarredRepo,
 };
 pub use groups::{
-    Group, GroupAllowAction, GroupAllowed, GroupAllowedUpdate, GroupDetailsList, GroupList,
-    GroupListParams, GroupMap, GroupRequest, GroupStats, GroupUpdate, GroupUsers,
+    CacheWarmResponse, Group, GroupAllowAction, GroupAllowed, GroupAllowedUpdate, GroupDetailsList,
+    GroupList, GroupListParams, GroupMap, GroupRequest, GroupStats, GroupUpdate, GroupUsers,
     GroupUsersRequest, GroupUsersUpdate, Roles,
 };
 pub use images::{
     ArgStrategy, BurstableResources, BurstableResourcesRequest, BurstableResourcesUpdate,
-    CacheDependencySettings, CacheDependencySettingsUpdate, ChildFilters, ChildFiltersUpdate,
-    ChildrenDependencySettings, ChildrenDependencySettingsUpdate, Cleanup, CleanupUpdate,
-    Dependencies, DependenciesUpdate, DependencyPassStrategy, EphemeralDependencySettings,
-    EphemeralDependencySettingsUpdate, FileNamingStrategy, GenericCacheDependencySettings,
-    GenericCacheDependencySettingsUpdate, Image, ImageArgs, ImageArgsUpdate, ImageBan,
-    ImageBanKind, ImageBanUpdate, ImageDetailsList, ImageJobInfo, ImageLifetime, ImageList,
-    ImageListParams, ImageNetworkPolicyUpdate, ImageRequest, ImageScaler, ImageUpdate,
-    ImageVersion, Kvm, KvmUpdate, KwargDependency, RepoDependencySettings,
+    CacheDependencySettings, CacheDependencySettingsUpdate, ChildFilterMatcher, ChildFilters,
+    ChildFiltersUpdate, ChildrenDependencySettings, ChildrenDependencySettingsUpdate, Cleanup,
+    CleanupUpdate, Dependencies, DependenciesUpdate, DependencyPassStrategy,
+    EphemeralDependencySettings, EphemeralDependencySettingsUpdate, FileNamingStrategy,
+    GenericCacheDependencySettings, GenericCacheDependencySettingsUpdate, Image, ImageArgs,
+    ImageArgsUpdate, ImageBan, ImageBanKind, ImageBanUpdate, ImageDetailsList, ImageJobInfo,
+    ImageLifetime, ImageList, ImageListParams, ImageNetworkPolicyUpdate, ImageRequest, ImageScaler,
+    ImageUpdate, ImageVersion, Kvm, KvmUpdate, KwargDependency, RepoDependencySettings,
     RepoDependencySettingsUpdate, Resources, ResourcesRequest, ResourcesUpdate,
     ResultDependencySettings, ResultDependencySettingsUpdate, SampleDependencySettings,
     SampleDependencySettingsUpdate, SecurityContext, SecurityContextUpdate, SpawnLimits,
@@ -91,10 +102,11 @@ pub use images::{
 };
 pub use jobs::{
     Checkpoint, GenericJob, GenericJobArgs, GenericJobArgsUpdate, GenericJobKwargs, GenericJobOpts,
-    HandleJobResponse, JobDetailsList, JobHandleStatus, JobList, JobListOpts, JobResetRequestor,
-    JobResets, JobStatus, RawJob, RunningJob,
+    GenericJobOrderedKwargs, HandleJobResponse, JobDetailsList, JobHandleStatus, JobHeartbeat,
+    JobList, JobListOpts, JobResetRequestor, JobResets, JobStarted, JobStatus, RawJob, RunningJob,
 };
 pub use logs::{Actions, JobActions, ReactionActions, StatusRequest, StatusUpdate};
+pub use mcp::{McpError, McpRequest, McpResponse, McpTool, McpToolList};
 pub use network_policies::{
     IpBlock, IpBlockRaw, Ipv4Block, Ipv6Block, NetworkPolicy, NetworkPolicyCustomK8sRule,
     NetworkPolicyCustomLabel, NetworkPolicyListLine, NetworkPolicyListOpts,
@@ -103,19 +115,21 @@ pub use network_policies::{
 };
 pub use pipelines::{
     Pipeline, PipelineBan, PipelineBanKind, PipelineBanUpdate, PipelineDetailsList, PipelineList,
-    PipelineListParams, PipelineRequest, PipelineStats, PipelineUpdate, StageStats,
+    PipelineListParams, PipelinePlan, PipelineRequest, PipelineStats, PipelineTriggerRequest,
+    PipelineTriggers, PipelineUpdate, PlanEdge, PlanNode, PlanWarning, PlannedStage, StageStats,
 };
 pub use reactions::{
-    BulkReactionResponse, HandleReactionResponse, Reaction, ReactionArgs, ReactionCache,
-    ReactionCacheFileUpdate, ReactionCacheUpdate, ReactionCreation, ReactionDetailsList,
-    ReactionExpire, ReactionIdResponse, ReactionList, ReactionListParams, ReactionRequest,
-    ReactionStatus, ReactionUpdate, StageLogLine, StageLogs, StageLogsAdd,
+    BulkReactionResponse, HandleReactionResponse, REACTION_PRIORITY_MAX, REACTION_PRIORITY_MIN,
+    Reaction, ReactionArgs, ReactionCache, ReactionCacheFileUpdate, ReactionCacheUpdate,
+    ReactionCreation, ReactionDetailsList, ReactionExpire, ReactionIdResponse, ReactionList,
+    ReactionListParams, ReactionRequest, ReactionSamplesAdd, ReactionStatus, ReactionUpdate,
+    StageLogLine, StageLogs, StageLogsAdd,
 };
 pub use requisitions::{Requisition, ScopedRequisition, SpawnedUpdate};
 pub use results::{
     AutoTag, AutoTagLogic, AutoTagUpdate, FilesHandler, FilesHandlerUpdate, OnDiskFile, Output,
     OutputChunk, OutputCollection, OutputCollectionUpdate, OutputDisplayType, OutputHandler,
-    OutputResponse, ResultGetParams,
+    OutputRenderer, OutputRenderers, OutputResponse, ResultGetParams,
 };
 pub use search::events::{
     ResultSearchEvent, SearchEvent, SearchEventPopOpts, SearchEventStatus, SearchEventType,
@@ -132,14 +146,16 @@ pub use system::{
 };
 pub use tags::{TagCounts, TagKeyCounts};
 pub use trees::{
-    Directionality, Tree, TreeBounds, TreeBranch, TreeGrowQuery, TreeNode, TreeOpts, TreeParams,
-    TreeQuery, TreeRelatedQuery, TreeRelationships, TreeSupport,
+    Directionality, LineageParams, LineageTree, ReactionTree, ReactionTreeParams, Tree, TreeBounds,
+    TreeBranch, TreeGrowQuery, TreeNode, TreeOpts, TreeParams, TreeQuery, TreeRelatedQuery,
+    TreeRelationships, TreeSupport,
 };
 pub use users::{
     AiEndpoint, AiEndpointUpdate, AiSettings, AiSettingsUpdate, AuthResponse, Key, ScrubbedUser,
-    Theme, UnixInfo, User, UserCreate, UserRole, UserSettings, UserSettingsUpdate, UserUpdate,
+    Theme, UnixInfo, User, UserCreate, UserRole, UserSettings, UserSettingsUpdate, UserSummary,
+    UserUpdate,
 };
-pub use version::{Arch, Component, Os, Version};
+pub use version::{Arch, Capabilities, CapabilityLimits, Component, Os, Version};
 pub use volumes::{ConfigMap, HostPath, HostPathTypes, NFS, Secret, Volume, VolumeTypes};
 
 // optional imports
@@ -179,7 +195,7 @@ cfg_if::cfg_if! {
 // api/client reexports
 cfg_if::cfg_if! {
     if #[cfg(any(feature = "api", feature = "client"))] {
-        pub use tags::{TagDeleteRequest, TagRequest, TagType};
+        pub use tags::{BulkTagResponse, TagDeleteRequest, TagRequest, TagType};
         pub use notifications::{
             Notification, NotificationLevel, NotificationParams, NotificationRequest, NotificationType,
         };
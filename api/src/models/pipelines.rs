@@ -226,6 +226,73 @@ impl From<Pipeline> for PipelineRequest {
     }
 }
 
+/// A request to add a trigger to an existing pipeline
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct PipelineTriggerRequest {
+    /// The name of this trigger
+    pub name: String,
+    /// The conditions that will trigger this pipeline
+    pub trigger: EventTrigger,
+}
+
+impl PipelineTriggerRequest {
+    /// Creates a new [`PipelineTriggerRequest`]
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of this trigger
+    /// * `trigger` - The conditions that will trigger this pipeline
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use thorium::models::{EventTrigger, PipelineTriggerRequest, TagType};
+    ///
+    /// // run this pipeline whenever a file is tagged with 'Crop=Corn'
+    /// let trigger = EventTrigger::Tag {
+    ///     tag_types: vec![TagType::Files],
+    ///     required: HashMap::from([("Crop".to_owned(), vec!["Corn".to_owned()])]),
+    ///     not: HashMap::default(),
+    /// };
+    /// PipelineTriggerRequest::new("corn-files", trigger);
+    /// ```
+    pub fn new<T: Into<String>>(name: T, trigger: EventTrigger) -> Self {
+        PipelineTriggerRequest {
+            name: name.into(),
+            trigger,
+        }
+    }
+}
+
+/// The triggers for a single pipeline
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct PipelineTriggers {
+    /// The group this pipeline is in
+    pub group: String,
+    /// The name of this pipeline
+    pub pipeline: String,
+    /// The triggers for this pipeline by name
+    pub triggers: HashMap<String, EventTrigger>,
+}
+
+impl From<Pipeline> for PipelineTriggers {
+    /// Get the triggers for a [`Pipeline`]
+    ///
+    /// # Arguments
+    ///
+    /// * `pipeline` - The pipeline to get triggers from
+    fn from(pipeline: Pipeline) -> Self {
+        PipelineTriggers {
+            group: pipeline.group,
+            pipeline: pipeline.name,
+            triggers: pipeline.triggers,
+        }
+    }
+}
+
 /// A list of pipeline names with a cursor
 #[derive(Serialize, Deserialize, Debug)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -666,6 +733,194 @@ impl PartialEq<PipelineUpdate> for Pipeline {
     }
 }
 
+impl Pipeline {
+    /// Plan out the jobs this pipeline would create without creating any of them
+    ///
+    /// Every image depends on all of the images in the stage before it and on the latest
+    /// earlier stage running any image it needs results from. Images that need results
+    /// from an image that doesn't run in an earlier stage are returned as warnings so the
+    /// rest of the plan can still be reviewed.
+    ///
+    /// # Arguments
+    ///
+    /// * `results` - The images each image in this pipeline needs results from
+    #[must_use]
+    pub fn plan(&self, results: &HashMap<String, Vec<String>>) -> PipelinePlan {
+        // build the stages in this pipeline
+        let stages = self
+            .order
+            .iter()
+            .enumerate()
+            .map(|(index, images)| PlannedStage {
+                index,
+                images: images.clone(),
+            })
+            .collect::<Vec<PlannedStage>>();
+        // link every image to the images in the stage after it
+        let mut edges = Vec::new();
+        for (stage, pair) in self.order.windows(2).enumerate() {
+            for from in &pair[0] {
+                for to in &pair[1] {
+                    edges.push(PlanEdge {
+                        from: PlanNode::new(stage, from),
+                        to: PlanNode::new(stage + 1, to),
+                    });
+                }
+            }
+        }
+        let mut warnings = Vec::new();
+        // link every image to the images it needs results from
+        for (stage, images) in self.order.iter().enumerate() {
+            for image in images {
+                for dependency in results.get(image).into_iter().flatten() {
+                    // use the results from the latest stage before us that runs this image
+                    match self.order[..stage]
+                        .iter()
+                        .rposition(|earlier| earlier.contains(dependency))
+                    {
+                        Some(from) => edges.push(PlanEdge {
+                            from: PlanNode::new(from, dependency),
+                            to: PlanNode::new(stage, image),
+                        }),
+                        None => warnings.push(PlanWarning::MissingResults {
+                            stage,
+                            image: image.clone(),
+                            dependency: dependency.clone(),
+                        }),
+                    }
+                }
+            }
+        }
+        PipelinePlan {
+            group: self.group.clone(),
+            name: self.name.clone(),
+            triggers: self.triggers.clone(),
+            stages,
+            edges,
+            warnings,
+        }
+    }
+}
+
+/// A single stage in a planned pipeline
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct PlannedStage {
+    /// The position of this stage in the pipeline's order
+    pub index: usize,
+    /// The images that would be run in parallel in this stage
+    pub images: Vec<String>,
+}
+
+/// An image in a specific stage of a planned pipeline
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct PlanNode {
+    /// The index of the stage this image runs in
+    pub stage: usize,
+    /// The name of this image
+    pub image: String,
+}
+
+impl PlanNode {
+    /// Create a new plan node
+    ///
+    /// # Arguments
+    ///
+    /// * `stage` - The index of the stage this image runs in
+    /// * `image` - The name of this image
+    fn new(stage: usize, image: &str) -> Self {
+        PlanNode {
+            stage,
+            image: image.to_owned(),
+        }
+    }
+}
+
+impl std::fmt::Display for PlanNode {
+    /// Display this node as its image and stage
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The formatter to write to
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (stage {})", self.image, self.stage)
+    }
+}
+
+/// A dependency between two images in a planned pipeline
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct PlanEdge {
+    /// The image that must complete first
+    pub from: PlanNode,
+    /// The image that waits on the first image
+    pub to: PlanNode,
+}
+
+/// A problem found while planning a pipeline
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlanWarning {
+    /// An image that needs results from an image that doesn't run in an earlier stage
+    MissingResults {
+        /// The index of the stage this image runs in
+        stage: usize,
+        /// The image that needs results
+        image: String,
+        /// The image whose results are needed
+        dependency: String,
+    },
+}
+
+impl std::fmt::Display for PlanWarning {
+    /// Describe this warning
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The formatter to write to
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanWarning::MissingResults {
+                stage,
+                image,
+                dependency,
+            } => write!(
+                f,
+                "{image} in stage {stage} needs results from {dependency} \
+                which does not run in an earlier stage"
+            ),
+        }
+    }
+}
+
+/// The jobs a pipeline would create if it was run without actually creating them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct PipelinePlan {
+    /// The group this pipeline is in
+    pub group: String,
+    /// The name of this pipeline
+    pub name: String,
+    /// The triggers that would run this pipeline
+    pub triggers: HashMap<String, EventTrigger>,
+    /// The stages in this pipeline in the order they would run
+    pub stages: Vec<PlannedStage>,
+    /// The dependencies between the images in this pipeline
+    pub edges: Vec<PlanEdge>,
+    /// Any problems found while planning this pipeline
+    pub warnings: Vec<PlanWarning>,
+}
+
+impl PipelinePlan {
+    /// Check if any problems were found while planning this pipeline
+    #[must_use]
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+}
+
 cfg_if::cfg_if! {
     if #[cfg(any(feature = "api", feature = "client"))] {
         use crate::models::backends::NotificationSupport;
@@ -759,3 +1014,124 @@ pub struct PipelineListParams {
     #[serde(default = "default_list_limit")]
     pub limit: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Pipeline, PlanEdge, PlanNode, PlanWarning};
+
+    /// Build a pipeline with a specific order
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The order of images in this pipeline
+    fn pipeline(order: &[&[&str]]) -> Pipeline {
+        Pipeline {
+            group: "corn".to_owned(),
+            name: "harvest".to_owned(),
+            creator: "farmer".to_owned(),
+            order: order
+                .iter()
+                .map(|stage| stage.iter().map(|image| (*image).to_owned()).collect())
+                .collect(),
+            sla: 604_800,
+            triggers: HashMap::default(),
+            description: None,
+            bans: HashMap::default(),
+        }
+    }
+
+    /// Build an edge between two images in specific stages
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The stage and image that must complete first
+    /// * `to` - The stage and image that waits on the first image
+    fn edge(from: (usize, &str), to: (usize, &str)) -> PlanEdge {
+        PlanEdge {
+            from: PlanNode::new(from.0, from.1),
+            to: PlanNode::new(to.0, to.1),
+        }
+    }
+
+    #[test]
+    fn plan_linear() {
+        let plan = pipeline(&[&["plant"], &["grow"], &["harvest"]]).plan(&HashMap::default());
+        assert_eq!(plan.stages.len(), 3);
+        assert_eq!(plan.stages[1].index, 1);
+        assert_eq!(plan.stages[1].images, vec!["grow"]);
+        assert_eq!(
+            plan.edges,
+            vec![
+                edge((0, "plant"), (1, "grow")),
+                edge((1, "grow"), (2, "harvest"))
+            ]
+        );
+        assert!(!plan.has_warnings());
+    }
+
+    #[test]
+    fn plan_branching() {
+        let plan = pipeline(&[&["plant"], &["water", "fertilize"], &["harvest"]])
+            .plan(&HashMap::default());
+        // every image depends on all of the images in the stage before it
+        assert_eq!(
+            plan.edges,
+            vec![
+                edge((0, "plant"), (1, "water")),
+                edge((0, "plant"), (1, "fertilize")),
+                edge((1, "water"), (2, "harvest")),
+                edge((1, "fertilize"), (2, "harvest")),
+            ]
+        );
+        assert!(!plan.has_warnings());
+    }
+
+    #[test]
+    fn plan_reused_image() {
+        // running an image again in a later stage is a new job and not a cycle
+        let plan = pipeline(&[&["plant"], &["grow"], &["plant"]]).plan(&HashMap::default());
+        assert_eq!(
+            plan.edges,
+            vec![
+                edge((0, "plant"), (1, "grow")),
+                edge((1, "grow"), (2, "plant"))
+            ]
+        );
+        assert!(!plan.has_warnings());
+    }
+
+    #[test]
+    fn plan_results() {
+        let results = HashMap::from([
+            ("harvest".to_owned(), vec!["plant".to_owned()]),
+            ("grow".to_owned(), vec!["harvest".to_owned()]),
+            ("plant".to_owned(), vec!["plant".to_owned()]),
+        ]);
+        let plan = pipeline(&[&["plant"], &["grow"], &["harvest"], &["plant"]]).plan(&results);
+        // images depend on the latest earlier stage running the image they need results from
+        assert!(plan.edges.contains(&edge((0, "plant"), (2, "harvest"))));
+        assert!(plan.edges.contains(&edge((0, "plant"), (3, "plant"))));
+        // images that need results from themselves or a later stage are flagged
+        assert_eq!(
+            plan.warnings,
+            vec![
+                PlanWarning::MissingResults {
+                    stage: 0,
+                    image: "plant".to_owned(),
+                    dependency: "plant".to_owned(),
+                },
+                PlanWarning::MissingResults {
+                    stage: 1,
+                    image: "grow".to_owned(),
+                    dependency: "harvest".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            plan.warnings[1].to_string(),
+            "grow in stage 1 needs results from harvest which does not run in an earlier stage"
+        );
+    }
+}
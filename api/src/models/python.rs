@@ -5,8 +5,10 @@
 mod cursors;
 mod files;
 mod jobs;
+mod mcp;
 mod reactions;
 mod repos;
 mod results;
+mod version;
 
 pub use cursors::{SampleCursor, SampleListLineCursor, TagCountsCursor};
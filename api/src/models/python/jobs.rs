@@ -1,8 +1,9 @@
 //! Python-specific things for jobs
 
 use pyo3::pymethods;
+use std::path::PathBuf;
 
-use crate::models::{GenericJobArgs, GenericJobKwargs, GenericJobOpts};
+use crate::models::{GenericJobArgs, GenericJobKwargs, GenericJobOpts, GenericJobOrderedKwargs};
 
 #[pymethods]
 impl GenericJobArgs {
@@ -11,6 +12,7 @@ impl GenericJobArgs {
         (
             positionals = Vec::new(),
             kwargs = GenericJobKwargs::default(),
+            ordered_kwargs = GenericJobOrderedKwargs::default(),
             switches = Vec::new(),
             opts = GenericJobOpts::default()
         ),
@@ -18,12 +20,14 @@ impl GenericJobArgs {
     fn new(
         positionals: Vec<String>,
         kwargs: GenericJobKwargs,
+        ordered_kwargs: GenericJobOrderedKwargs,
         switches: Vec<String>,
         opts: GenericJobOpts,
     ) -> Self {
         Self {
             positionals,
             kwargs,
+            ordered_kwargs,
             switches,
             opts,
         }
@@ -37,18 +41,24 @@ impl GenericJobOpts {
         (
             override_positionals = false,
             override_kwargs = false,
-            override_cmd = None
+            override_cmd = None,
+            working_dir = None,
+            create_working_dir = false
         )
     )]
     fn new_py(
         override_positionals: bool,
         override_kwargs: bool,
         override_cmd: Option<Vec<String>>,
+        working_dir: Option<PathBuf>,
+        create_working_dir: bool,
     ) -> Self {
         Self {
             override_positionals,
             override_kwargs,
             override_cmd,
+            working_dir,
+            create_working_dir,
         }
     }
 }
@@ -0,0 +1,20 @@
+//! Python-specific things for MCP tools
+
+use pyo3::pymethods;
+
+use crate::models::McpTool;
+
+#[pymethods]
+impl McpTool {
+    #[pyo3(name = "input_schema")]
+    #[getter]
+    fn input_schema_py(&self) -> String {
+        self.input_schema.to_string()
+    }
+
+    #[pyo3(name = "output_schema")]
+    #[getter]
+    fn output_schema_py(&self) -> Option<String> {
+        self.output_schema.as_ref().map(ToString::to_string)
+    }
+}
@@ -28,6 +28,8 @@ impl ReactionRequest {
             repos = Vec::new(),
             trigger_depth = None,
             cache = ReactionCache::default(),
+            idempotency_key = None,
+            priority = 0,
         )
     )]
     #[allow(clippy::too_many_arguments)]
@@ -43,9 +45,11 @@ impl ReactionRequest {
         repos: Vec<RepoDependencyRequest>,
         trigger_depth: Option<u8>,
         cache: ReactionCache,
+        idempotency_key: Option<String>,
+        priority: i32,
     ) -> Self {
         Self {
-            group,
+            group: Some(group),
             pipeline,
             args,
             sla,
@@ -56,8 +60,16 @@ impl ReactionRequest {
             repos,
             trigger_depth,
             cache,
+            idempotency_key,
+            priority,
         }
     }
+
+    /// Build a deterministic key from the contents of this request
+    #[pyo3(name = "canonical_key")]
+    fn canonical_key_py(&self) -> String {
+        self.canonical_key()
+    }
 }
 
 #[pymethods]
@@ -0,0 +1,18 @@
+//! Python-specific things for versions and capabilities
+
+use pyo3::pymethods;
+
+use crate::models::Capabilities;
+
+#[pymethods]
+impl Capabilities {
+    /// Check if this server supports a specific feature
+    ///
+    /// # Arguments
+    ///
+    /// * `feature` - The feature to check for
+    #[pyo3(name = "supports")]
+    fn supports_py(&self, feature: &str) -> bool {
+        self.supports(feature)
+    }
+}
@@ -24,7 +24,7 @@ cfg_if::cfg_if! {
     if #[cfg(feature = "api")] {
         use serde_json::Value;
 
-        use super::GenericJobOpts;
+        use super::{GenericJobOpts, GenericJobOrderedKwargs};
         use crate::{deserialize_value, bad, utils::ApiError};
 
         /// Generic job args containing non statically typed kwargs
@@ -38,6 +38,9 @@ cfg_if::cfg_if! {
             /// The keyword arguments to overlay onto the original cmd
             #[serde(default)]
             pub kwargs: HashMap<String, Value>,
+            /// The keyword arguments to overlay onto the original cmd in the order they were added
+            #[serde(default)]
+            pub ordered_kwargs: GenericJobOrderedKwargs,
             /// The switch arguments to overlay onto the original cmd
             #[serde(default)]
             pub switches: Vec<String>,
@@ -75,6 +78,7 @@ cfg_if::cfg_if! {
                 let converted = GenericJobArgs {
                     positionals: raw.positionals,
                     kwargs,
+                    ordered_kwargs: raw.ordered_kwargs,
                     switches: raw.switches,
                     opts: raw.opts,
                 };
@@ -88,8 +92,8 @@ cfg_if::cfg_if! {
         /// A request to create a new reaction
         #[derive(Serialize, Deserialize, Debug, Clone)]
         pub struct RawReactionRequest {
-            /// The group the reaction is in
-            pub group: String,
+            /// The group the reaction is in or None to use the creators default group
+            pub group: Option<String>,
             /// The pipeline this reaction is build around
             pub pipeline: String,
             /// The args to overlay ontop of the args for images in this reaction
@@ -115,6 +119,12 @@ cfg_if::cfg_if! {
             /// Any initial cache for this reaction
             #[serde(default)]
             pub cache: ReactionCache,
+            /// A key to deduplicate retried creations of this reaction with
+            #[serde(default)]
+            pub idempotency_key: Option<String>,
+            /// The priority of this reaction compared to other reactions in its group
+            #[serde(default)]
+            pub priority: i32,
         }
 
         impl TryFrom<RawReactionRequest> for ReactionRequest {
@@ -144,6 +154,8 @@ cfg_if::cfg_if! {
                     repos: raw.repos,
                     trigger_depth: raw.trigger_depth,
                     cache: raw.cache,
+                    idempotency_key: raw.idempotency_key,
+                    priority: raw.priority,
                 };
                 Ok(converted)
             }
@@ -157,6 +169,8 @@ cfg_if::cfg_if! {
 pub struct ReactionIdResponse {
     /// The uuidv4 of a reaction
     pub id: Uuid,
+    /// Whether an existing reaction with the same idempotency key was returned
+    pub deduplicated: bool,
 }
 
 /// The response from creating reactions in bulk
@@ -168,6 +182,9 @@ pub struct BulkReactionResponse {
     pub errors: HashMap<usize, String>,
     /// The successfully created reactions
     pub created: Vec<Uuid>,
+    /// The existing reactions returned instead of creating duplicates by request index
+    #[serde(default)]
+    pub deduplicated: HashMap<usize, Uuid>,
 }
 
 impl BulkReactionResponse {
@@ -181,8 +198,32 @@ impl BulkReactionResponse {
         BulkReactionResponse {
             errors: HashMap::default(),
             created: Vec::with_capacity(capacity),
+            deduplicated: HashMap::default(),
         }
     }
+
+    /// Merge the response for a chunk of a larger bulk request into this response
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The response for this chunk of requests
+    /// * `offset` - The index of this chunk's first request in the larger bulk request
+    pub fn merge(&mut self, other: BulkReactionResponse, offset: usize) {
+        // shift our error and deduplicated indexes to their place in the larger request
+        self.errors.extend(
+            other
+                .errors
+                .into_iter()
+                .map(|(index, error)| (index + offset, error)),
+        );
+        self.deduplicated.extend(
+            other
+                .deduplicated
+                .into_iter()
+                .map(|(index, id)| (index + offset, id)),
+        );
+        self.created.extend(other.created);
+    }
 }
 
 /// A response for handling the reaction command
@@ -193,6 +234,12 @@ pub struct HandleReactionResponse {
     pub status: ReactionStatus,
 }
 
+/// The lowest priority a reaction can have
+pub const REACTION_PRIORITY_MIN: i32 = -100;
+
+/// The highest priority a reaction can have
+pub const REACTION_PRIORITY_MAX: i32 = 100;
+
 /// The arguments for all images in a reaction
 pub type ReactionArgs = HashMap<String, GenericJobArgs>;
 
@@ -202,7 +249,9 @@ pub type ReactionArgs = HashMap<String, GenericJobArgs>;
 #[cfg_attr(feature = "python", pyclass(from_py_object))]
 pub struct ReactionRequest {
     /// The group the reaction is in
-    pub group: String,
+    ///
+    /// If this is not set then the creators default group is used instead.
+    pub group: Option<String>,
     /// The pipeline this reaction is build around
     pub pipeline: String,
     /// The args to overlay ontop of the args for images in this reaction
@@ -228,6 +277,20 @@ pub struct ReactionRequest {
     /// Any initial cache for this reaction
     #[serde(default)]
     pub cache: ReactionCache,
+    /// A key to deduplicate retried creations of this reaction with
+    ///
+    /// If a reaction with this key already exists in this group then its id is returned
+    /// instead of creating a new reaction. Keys are only retained for the API's configured
+    /// idempotency key retention.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// The priority of this reaction compared to other reactions in its group
+    ///
+    /// Higher priority reactions are scheduled first. This is clamped between
+    /// [`REACTION_PRIORITY_MIN`] and [`REACTION_PRIORITY_MAX`] and only admins can
+    /// set a nonzero priority.
+    #[serde(default)]
+    pub priority: i32,
 }
 
 impl ReactionRequest {
@@ -260,7 +323,33 @@ impl ReactionRequest {
     /// ```
     pub fn new<T: Into<String>>(group: T, pipeline: T) -> Self {
         ReactionRequest {
-            group: group.into(),
+            group: Some(group.into()),
+            ..Self::in_default_group(pipeline)
+        }
+    }
+
+    /// Creates a [`ReactionRequest`] for a new reaction in the creators default group
+    ///
+    /// The group is resolved by the API from the creators settings so they must
+    /// have a default group set.
+    ///
+    /// # Arguments
+    ///
+    /// * `pipeline` - The pipeline this reaction should be based on
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::ReactionRequest;
+    ///
+    /// // create a reaction in our default group with an SLA of 1 week
+    /// let request = ReactionRequest::in_default_group("harvest")
+    ///     .sla(604800)
+    ///     .tag("CornPlants");
+    /// ```
+    pub fn in_default_group<T: Into<String>>(pipeline: T) -> Self {
+        ReactionRequest {
+            group: None,
             pipeline: pipeline.into(),
             sla: None,
             args: HashMap::default(),
@@ -271,6 +360,8 @@ impl ReactionRequest {
             repos: Vec::default(),
             trigger_depth: None,
             cache: ReactionCache::default(),
+            idempotency_key: None,
+            priority: 0,
         }
     }
 
@@ -567,6 +658,129 @@ impl ReactionRequest {
         self.cache = cache;
         self
     }
+
+    /// Set a key to deduplicate retried creations of this reaction with
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The idempotency key to set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::ReactionRequest;
+    ///
+    /// // create a reaction that will not be duplicated if its creation is retried
+    /// let request = ReactionRequest::new("Corn", "harvest").idempotency_key("harvest-2024");
+    /// ```
+    #[must_use]
+    pub fn idempotency_key<T: Into<String>>(mut self, key: T) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Set the priority of this reaction compared to other reactions in its group
+    ///
+    /// Priorities outside of [`REACTION_PRIORITY_MIN`] and [`REACTION_PRIORITY_MAX`] are
+    /// clamped when this reaction is created. Only admins can set a nonzero priority.
+    ///
+    /// # Arguments
+    ///
+    /// * `priority` - The priority to set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::ReactionRequest;
+    ///
+    /// // create a reaction that should be harvested before the rest of the field
+    /// let request = ReactionRequest::new("Corn", "harvest").priority(10);
+    /// ```
+    #[must_use]
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Build a deterministic key from the contents of this request
+    ///
+    /// Args, kwargs, tags, samples, buffers, repos, and cache data are all sorted so logically
+    /// equal requests produce the same key no matter what order they were built in. Duplicate
+    /// tags and samples are ignored. Positionals and ordered kwargs keep their order as it
+    /// changes the command that is executed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::ReactionRequest;
+    ///
+    /// let first = ReactionRequest::new("Corn", "harvest").tags(vec!("Plants", "Corn"));
+    /// let second = ReactionRequest::new("Corn", "harvest").tags(vec!("Corn", "Plants"));
+    /// assert_eq!(first.canonical_key(), second.canonical_key());
+    /// ```
+    #[must_use]
+    pub fn canonical_key(&self) -> String {
+        // sort and dedupe our tags and samples
+        let mut tags = self.tags.iter().collect::<Vec<&String>>();
+        tags.sort_unstable();
+        tags.dedup();
+        let mut samples = self.samples.iter().collect::<Vec<&String>>();
+        samples.sort_unstable();
+        samples.dedup();
+        // sort our repos by their url and then their commitish
+        let mut repos = self.repos.iter().collect::<Vec<&RepoDependencyRequest>>();
+        repos.sort_by(|left, right| {
+            (&left.url, &left.commitish).cmp(&(&right.url, &right.commitish))
+        });
+        // sort our cached files
+        let mut cache_files = self.cache.files.iter().collect::<Vec<&String>>();
+        cache_files.sort_unstable();
+        // build our canonical request
+        let canonical = CanonicalReactionRequest {
+            group: self.group.as_deref(),
+            pipeline: &self.pipeline,
+            args: self.args.iter().collect(),
+            sla: self.sla,
+            tags,
+            parent: self.parent,
+            samples,
+            buffers: self.buffers.iter().collect(),
+            repos,
+            trigger_depth: self.trigger_depth,
+            cache_generic: self.cache.generic.iter().collect(),
+            cache_files,
+        };
+        serde_json::to_string(&canonical).expect("Failed to serialize canonical reaction key!")
+    }
+}
+
+/// A view of a [`ReactionRequest`] with all unordered data sorted
+#[derive(Serialize)]
+struct CanonicalReactionRequest<'a> {
+    /// The group the reaction is in
+    group: Option<&'a str>,
+    /// The pipeline this reaction is built around
+    pipeline: &'a str,
+    /// The args to overlay ontop of the args for images sorted by image
+    args: BTreeMap<&'a String, &'a GenericJobArgs>,
+    /// The number of seconds we have to meet this reactions SLA
+    sla: Option<u64>,
+    /// The sorted and deduped tags for this reaction
+    tags: Vec<&'a String>,
+    /// The parent reaction to set if this is a sub reaction
+    parent: Option<Uuid>,
+    /// The sorted and deduped samples for this reaction
+    samples: Vec<&'a String>,
+    /// The ephemeral buffers for this reaction sorted by name
+    buffers: BTreeMap<&'a String, &'a String>,
+    /// The repos for this reaction sorted by url and commitish
+    repos: Vec<&'a RepoDependencyRequest>,
+    /// This reactions depth in triggers
+    trigger_depth: Option<u8>,
+    /// The generic reaction cache sorted by key
+    cache_generic: BTreeMap<&'a String, &'a String>,
+    /// The sorted files in our reaction cache
+    cache_files: Vec<&'a String>,
 }
 
 /// Helps serde default the reaction list limit to 50
@@ -596,6 +810,17 @@ impl Default for ReactionListParams {
 }
 
 impl ReactionListParams {
+    /// Set the cursor in a builder-like pattern
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor to set
+    #[must_use]
+    pub fn cursor(mut self, cursor: usize) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
     /// Set the limit in a builder-like pattern
     ///
     /// # Arguments
@@ -909,6 +1134,12 @@ pub struct Reaction {
     pub trigger_depth: Option<u8>,
     /// Whether this reaction has any cache data set
     pub has_cache: bool,
+    /// The key used to deduplicate retried creations of this reaction
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// The priority of this reaction compared to other reactions in its group
+    #[serde(default)]
+    pub priority: i32,
 }
 
 impl PartialEq<ReactionRequest> for Reaction {
@@ -922,8 +1153,11 @@ impl PartialEq<ReactionRequest> for Reaction {
         let mut corrected = request.tags.clone();
         corrected.append(&mut request.samples.clone());
         corrected.push(self.creator.clone());
+        // requests without a group are created in the creators default group
+        if let Some(group) = &request.group {
+            same!(&self.group, group);
+        }
         // make sure all fields are the same
-        same!(self.group, request.group);
         same!(self.pipeline, request.pipeline);
         matches_vec!(self.tags, corrected);
         same!(self.args, request.args);
@@ -932,6 +1166,15 @@ impl PartialEq<ReactionRequest> for Reaction {
         same!(self.ephemeral.len(), request.buffers.len());
         // make sure our reaction depth is the same
         same!(self.trigger_depth, request.trigger_depth);
+        // make sure our idempotency key is the same
+        same!(self.idempotency_key, request.idempotency_key);
+        // make sure our clamped priority is the same
+        same!(
+            self.priority,
+            request
+                .priority
+                .clamp(REACTION_PRIORITY_MIN, REACTION_PRIORITY_MAX)
+        );
         true
     }
 }
@@ -942,6 +1185,9 @@ impl PartialEq<ReactionRequest> for Reaction {
 pub struct ReactionCreation {
     /// The uuidv4 of the created reaction
     pub id: Uuid,
+    /// Whether an existing reaction with the same idempotency key was returned
+    #[serde(default)]
+    pub deduplicated: bool,
 }
 
 /// A Reaction expiration object
@@ -1078,6 +1324,73 @@ impl PartialEq<ReactionUpdate> for Reaction {
     }
 }
 
+/// A request to add samples to an existing reaction
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct ReactionSamplesAdd {
+    /// The sha256s of the samples to add to this reaction
+    pub samples: Vec<String>,
+    /// Whether to also add these samples to jobs in this reaction that have not started yet
+    ///
+    /// Jobs for later stages always get the full list of samples when they are created.
+    #[serde(default)]
+    pub update_jobs: bool,
+}
+
+impl ReactionSamplesAdd {
+    /// Adds a sample to this request
+    ///
+    /// # Arguments
+    ///
+    /// * `sample` - The sha256 of the sample to add
+    ///
+    ///  # Example
+    ///
+    ///  ```
+    ///  use thorium::models::ReactionSamplesAdd;
+    ///
+    ///  // add a newly unpacked sample to a reaction
+    ///  let add = ReactionSamplesAdd::default()
+    ///     .sample("63b0490d4736e740f26ea9483d55c254abe032845b70ba84ea463ca6582d106f");
+    ///  ```
+    #[must_use]
+    pub fn sample<T: Into<String>>(mut self, sample: T) -> Self {
+        // add this sample
+        self.samples.push(sample.into());
+        self
+    }
+
+    /// Adds multiple samples to this request
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The sha256s of the samples to add
+    #[must_use]
+    pub fn samples<T: Into<String>>(mut self, samples: Vec<T>) -> Self {
+        // add these samples
+        self.samples.extend(samples.into_iter().map(Into::into));
+        self
+    }
+
+    /// Also add these samples to any jobs in this reaction that have not started yet
+    ///
+    ///  # Example
+    ///
+    ///  ```
+    ///  use thorium::models::ReactionSamplesAdd;
+    ///
+    ///  // add a sample to this reaction and to its not yet started jobs
+    ///  let add = ReactionSamplesAdd::default()
+    ///     .sample("63b0490d4736e740f26ea9483d55c254abe032845b70ba84ea463ca6582d106f")
+    ///     .update_jobs();
+    ///  ```
+    #[must_use]
+    pub fn update_jobs(mut self) -> Self {
+        self.update_jobs = true;
+        self
+    }
+}
+
 /// An ephemeral cache of information across this entire reaction
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "python", pyclass(from_py_object))]
@@ -1251,3 +1564,70 @@ impl ReactionCacheFileUpdate {
         Ok(form)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::ReactionRequest;
+    use crate::models::{GenericJobArgs, RepoDependencyRequest};
+
+    #[test]
+    fn canonical_key() {
+        let parent = Uuid::new_v4();
+        // build a request
+        let first = ReactionRequest::new("Corn", "harvest")
+            .sla(604800)
+            .parent(parent)
+            .args(
+                "plant",
+                GenericJobArgs::default()
+                    .kwarg("--field", vec!["north"])
+                    .kwarg("--crop", vec!["corn"]),
+            )
+            .args(
+                "harvest",
+                GenericJobArgs::default().positionals(vec!["combine"]),
+            )
+            .tags(vec!["Plants", "Corn"])
+            .samples(vec!["sha256-1", "sha256-0"])
+            .buffer("b.txt", "b")
+            .buffer("a.txt", "a")
+            .repo(RepoDependencyRequest::new("github.com/curl/curl"))
+            .repo(RepoDependencyRequest::new("github.com/rust-lang/rust"));
+        // build the same request in a different order
+        let second = ReactionRequest::new("Corn", "harvest")
+            .repo(RepoDependencyRequest::new("github.com/rust-lang/rust"))
+            .repo(RepoDependencyRequest::new("github.com/curl/curl"))
+            .buffer("a.txt", "a")
+            .buffer("b.txt", "b")
+            .samples(vec!["sha256-0", "sha256-1", "sha256-0"])
+            .tags(vec!["Corn", "Plants"])
+            .args(
+                "harvest",
+                GenericJobArgs::default().positionals(vec!["combine"]),
+            )
+            .args(
+                "plant",
+                GenericJobArgs::default()
+                    .kwarg("--crop", vec!["corn"])
+                    .kwarg("--field", vec!["north"]),
+            )
+            .parent(parent)
+            .sla(604800);
+        assert_eq!(first.canonical_key(), second.canonical_key());
+        // changing the order of positionals changes what is executed and so our key
+        let third = second.clone().args(
+            "harvest",
+            GenericJobArgs::default().positionals(vec!["combine", "truck"]),
+        );
+        let fourth = second.clone().args(
+            "harvest",
+            GenericJobArgs::default().positionals(vec!["truck", "combine"]),
+        );
+        assert_ne!(third.canonical_key(), fourth.canonical_key());
+        // different tags should produce a different key
+        let fifth = second.clone().tag("Harvested");
+        assert_ne!(second.canonical_key(), fifth.canonical_key());
+    }
+}
@@ -542,6 +542,9 @@ pub struct Output {
     pub files: Vec<String>,
     /// The display type of this tool output
     pub display_type: OutputDisplayType,
+    /// The metadata describing how to render this output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renderer: Option<OutputRenderer>,
     /// The children that were found when generating this result
     pub children: HashMap<String, Uuid>,
 }
@@ -634,7 +637,18 @@ pub struct OutputChunk {
 }
 
 /// The type of display class to use in the UI for this output
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Copy, clap::ValueEnum)]
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Copy,
+    clap::ValueEnum,
+    schemars::JsonSchema,
+)]
 #[cfg_attr(
     feature = "rkyv-support",
     derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
@@ -670,6 +684,8 @@ pub enum OutputDisplayType {
     /// Result to render is XML formatted
     #[serde(rename = "XML", alias = "Xml")]
     Xml,
+    /// Render this output with a custom template, template will be based on tool name
+    Template,
 }
 
 impl OutputDisplayType {
@@ -687,6 +703,7 @@ impl OutputDisplayType {
             OutputDisplayType::Markdown => "Markdown",
             OutputDisplayType::Hidden => "Hidden",
             OutputDisplayType::Xml => "Xml",
+            OutputDisplayType::Template => "Template",
         }
     }
 
@@ -703,6 +720,35 @@ impl OutputDisplayType {
             OutputDisplayType::Markdown => true,
             OutputDisplayType::Hidden => true,
             OutputDisplayType::Xml => true,
+            OutputDisplayType::Template => true,
+        }
+    }
+
+    /// Whether this display type is rendered with a tool specific class or template
+    pub fn requires_template(&self) -> bool {
+        matches!(
+            self,
+            OutputDisplayType::Custom | OutputDisplayType::Template
+        )
+    }
+
+    /// Get the built in renderer metadata for this display type
+    #[must_use]
+    pub fn default_renderer(&self) -> OutputRenderer {
+        match self {
+            OutputDisplayType::Json => OutputRenderer::new("json", "application/json"),
+            OutputDisplayType::String => OutputRenderer::new("string", "text/plain"),
+            OutputDisplayType::Table => OutputRenderer::new("table", "application/json"),
+            OutputDisplayType::Image => OutputRenderer::new("image", "image/*"),
+            OutputDisplayType::Custom => OutputRenderer::new("custom", "application/json"),
+            OutputDisplayType::Disassembly => OutputRenderer::new("disassembly", "text/plain"),
+            OutputDisplayType::Html => OutputRenderer::new("html", "text/html").sanitize(),
+            OutputDisplayType::Markdown => {
+                OutputRenderer::new("markdown", "text/markdown").sanitize()
+            }
+            OutputDisplayType::Hidden => OutputRenderer::new("hidden", "application/json"),
+            OutputDisplayType::Xml => OutputRenderer::new("xml", "application/xml").sanitize(),
+            OutputDisplayType::Template => OutputRenderer::new("template", "text/html").sanitize(),
         }
     }
 }
@@ -736,6 +782,7 @@ impl From<OutputDisplayType> for Cow<'static, str> {
             OutputDisplayType::Markdown => Cow::Borrowed("Markdown"),
             OutputDisplayType::Hidden => Cow::Borrowed("Hidden"),
             OutputDisplayType::Xml => Cow::Borrowed("Xml"),
+            OutputDisplayType::Template => Cow::Borrowed("Template"),
         }
     }
 }
@@ -755,11 +802,150 @@ impl FromStr for OutputDisplayType {
             "Markdown" => Ok(OutputDisplayType::Markdown),
             "Hidden" => Ok(OutputDisplayType::Hidden),
             "Xml" => Ok(OutputDisplayType::Xml),
+            "Template" => Ok(OutputDisplayType::Template),
             _ => Err(InvalidEnum(format!("Unknown OutputDisplayType: {raw}"))),
         }
     }
 }
 
+/// The metadata a UI needs to render a specific [`OutputDisplayType`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, schemars::JsonSchema)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "python", thorium_derive::pyclass(get_all))]
+pub struct OutputRenderer {
+    /// The name of the renderer to use
+    pub renderer: String,
+    /// The mime type of the output being rendered
+    pub mime: String,
+    /// Whether this output must be sanitized before being rendered
+    #[serde(default)]
+    pub sanitize: bool,
+    /// The custom render class or template to use if one is required
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+impl OutputRenderer {
+    /// Create a new [`OutputRenderer`]
+    ///
+    /// # Arguments
+    ///
+    /// * `renderer` - The name of the renderer to use
+    /// * `mime` - The mime type of the output being rendered
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::OutputRenderer;
+    ///
+    /// OutputRenderer::new("table", "application/json");
+    /// ```
+    pub fn new<R: Into<String>, M: Into<String>>(renderer: R, mime: M) -> Self {
+        OutputRenderer {
+            renderer: renderer.into(),
+            mime: mime.into(),
+            sanitize: false,
+            template: None,
+        }
+    }
+
+    /// Require this output to be sanitized before being rendered
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::OutputRenderer;
+    ///
+    /// OutputRenderer::new("html", "text/html").sanitize();
+    /// ```
+    #[must_use]
+    pub fn sanitize(mut self) -> Self {
+        self.sanitize = true;
+        self
+    }
+
+    /// Set the custom render class or template to use
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - The template to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::OutputRenderer;
+    ///
+    /// OutputRenderer::new("template", "text/html").template("CornReport");
+    /// ```
+    #[must_use]
+    pub fn template<T: Into<String>>(mut self, template: T) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+}
+
+/// A registry of the renderers to use for each [`OutputDisplayType`]
+///
+/// Any display types without an override will use their built in renderer.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, schemars::JsonSchema)]
+#[serde(transparent)]
+pub struct OutputRenderers {
+    /// The renderers to use instead of the built in ones
+    pub overrides: HashMap<OutputDisplayType, OutputRenderer>,
+}
+
+impl OutputRenderers {
+    /// Override the renderer for a specific display type
+    ///
+    /// # Arguments
+    ///
+    /// * `display_type` - The display type to override the renderer for
+    /// * `renderer` - The renderer to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{OutputDisplayType, OutputRenderer, OutputRenderers};
+    ///
+    /// OutputRenderers::default()
+    ///     .renderer(OutputDisplayType::Table, OutputRenderer::new("grid", "application/json"));
+    /// ```
+    #[must_use]
+    pub fn renderer(mut self, display_type: OutputDisplayType, renderer: OutputRenderer) -> Self {
+        self.overrides.insert(display_type, renderer);
+        self
+    }
+
+    /// Get the renderer for a specific tools output
+    ///
+    /// # Arguments
+    ///
+    /// * `display_type` - The display type to get a renderer for
+    /// * `tool` - The tool whose output is being rendered
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{OutputDisplayType, OutputRenderers};
+    ///
+    /// let renderer = OutputRenderers::default().get(OutputDisplayType::Template, "CornReport");
+    /// assert_eq!(renderer.template, Some("CornReport".to_owned()));
+    /// ```
+    #[must_use]
+    pub fn get(&self, display_type: OutputDisplayType, tool: &str) -> OutputRenderer {
+        // use our override if we have one or fall back to the built in renderer
+        let mut renderer = match self.overrides.get(&display_type) {
+            Some(renderer) => renderer.clone(),
+            None => display_type.default_renderer(),
+        };
+        // custom display types are rendered with a template named after their tool by default
+        if renderer.template.is_none() && display_type.requires_template() {
+            renderer.template = Some(tool.to_owned());
+        }
+        renderer
+    }
+}
+
 /// The different type of handlers for collecting results
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -77,6 +77,12 @@ pub struct AssociationListRow {
     pub extra_source: Option<String>,
     /// Any extra info needed for the target column in this row
     pub extra_other: Option<String>,
+    /// How confident the submitter was in this association
+    ///
+    /// This is null for associations created before confidence was tracked.
+    pub confidence: Option<i32>,
+    /// When this association was archived if it has been archived
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 /// An association with a specific piece of data
@@ -96,6 +102,10 @@ pub struct ListableAssociation {
     pub direction: Directionality,
     /// Any extra info needed for the target column in this row
     pub extra_other: Option<String>,
+    /// How confident the submitter was in this association from 0 to 100
+    pub confidence: u8,
+    /// When this association was archived if it has been archived
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl ListableAssociation {
@@ -153,6 +163,12 @@ impl From<AssociationListRow> for ListableAssociation {
             created: row.created,
             direction: row.direction,
             extra_other: row.extra_other,
+            // associations without a confidence are fully trusted
+            confidence: row
+                .confidence
+                .and_then(|confidence| u8::try_from(confidence).ok())
+                .unwrap_or_else(crate::models::associations::default_confidence),
+            archived_at: row.archived_at,
         }
     }
 }
@@ -178,6 +194,8 @@ impl TryFrom<ListableAssociation> for Association {
             groups: row.groups,
             created: row.created,
             direction: row.direction,
+            confidence: row.confidence,
+            archived_at: row.archived_at,
         };
         Ok(association)
     }
@@ -58,4 +58,6 @@ pub struct EntityListSupplementRow {
     pub name: String,
     /// The kind of entity this is
     pub kind: EntityKinds,
+    /// The user who created this entity
+    pub submitter: String,
 }
@@ -4,6 +4,8 @@
 pub enum DeserializationError {
     /// We expected text and got a different row data kind
     ExpectedText,
+    /// We expected an integer and got a different row data kind
+    ExpectedInt,
     /// We expected this column to contain data
     ExpectedNotNull,
     /// The column contained an unknown value
@@ -19,3 +21,148 @@ impl std::fmt::Display for DeserializationError {
 
 // impl the std error trait for our error
 impl std::error::Error for DeserializationError {}
+
+#[cfg(test)]
+mod tests {
+    use scylla::cluster::metadata::NativeType;
+    use scylla::deserialize::FrameSlice;
+    use scylla::deserialize::value::DeserializeValue;
+    use scylla::frame::response::result::ColumnType;
+    use scylla::serialize::value::SerializeValue;
+    use scylla::serialize::writers::CellWriter;
+    use std::str::FromStr;
+
+    /// A custom error with more context on what failed to deserialize
+    #[derive(Debug)]
+    enum TestError {
+        /// A test kind could not be deserialized
+        BadKind,
+    }
+
+    impl std::fmt::Display for TestError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{self:?}")
+        }
+    }
+
+    impl std::error::Error for TestError {}
+
+    /// A kind that returns a custom error when it fails to deserialize
+    #[derive(Debug, PartialEq, thorium_derive::ScyllaStoreAsStr)]
+    #[scylla_store(error = "TestError::BadKind")]
+    enum CustomKind {
+        Valid,
+    }
+
+    /// A kind that uses the default deserialization errors
+    #[derive(Debug, PartialEq, thorium_derive::ScyllaStoreAsStr)]
+    enum DefaultKind {
+        Valid,
+    }
+
+    /// Implement as_str and from_str for our test kinds
+    macro_rules! impl_str {
+        ($kind:ident) => {
+            impl $kind {
+                fn as_str(&self) -> &'static str {
+                    "Valid"
+                }
+            }
+
+            impl FromStr for $kind {
+                type Err = ();
+
+                fn from_str(raw: &str) -> Result<Self, Self::Err> {
+                    match raw {
+                        "Valid" => Ok($kind::Valid),
+                        _ => Err(()),
+                    }
+                }
+            }
+        };
+    }
+
+    impl_str!(CustomKind);
+    impl_str!(DefaultKind);
+
+    /// A status that is stored as its discriminant
+    #[derive(Debug, Clone, Copy, PartialEq, thorium_derive::ScyllaStoreAsInt)]
+    enum IntStatus {
+        Created,
+        Running = 5,
+        Completed,
+    }
+
+    /// A status that is stored as a small int discriminant
+    #[derive(Debug, Clone, Copy, PartialEq, thorium_derive::ScyllaStoreAsInt)]
+    #[repr(i16)]
+    enum SmallIntStatus {
+        Created,
+        Running = 5,
+        Completed,
+    }
+
+    /// Serialize a value and return only its cell contents
+    fn serialize_cell<T: SerializeValue>(value: &T, typ: &ColumnType) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        value.serialize(typ, CellWriter::new(&mut buffer)).unwrap();
+        // skip the length prefix for this cell
+        buffer.split_off(4)
+    }
+
+    #[test]
+    fn scylla_store_custom_error() {
+        let typ = ColumnType::Native(NativeType::Text);
+        // valid values should still deserialize
+        let valid = CustomKind::deserialize(&typ, Some(FrameSlice::new_borrowed(b"Valid")));
+        assert_eq!(valid.unwrap(), CustomKind::Valid);
+        // unknown values should return our custom error
+        let unknown = CustomKind::deserialize(&typ, Some(FrameSlice::new_borrowed(b"Invalid")));
+        assert_eq!(unknown.unwrap_err().to_string(), "BadKind");
+        // non utf8 values should also return our custom error
+        let not_text = CustomKind::deserialize(&typ, Some(FrameSlice::new_borrowed(&[0xff])));
+        assert_eq!(not_text.unwrap_err().to_string(), "BadKind");
+        // types without a custom error should fall back to our defaults
+        let unknown = DefaultKind::deserialize(&typ, Some(FrameSlice::new_borrowed(b"Invalid")));
+        assert_eq!(unknown.unwrap_err().to_string(), "UnknownValue");
+    }
+
+    #[test]
+    fn scylla_store_as_int() {
+        let typ = ColumnType::Native(NativeType::Int);
+        // make sure all of our variants round trip through their discriminant
+        for status in [IntStatus::Created, IntStatus::Running, IntStatus::Completed] {
+            let cell = serialize_cell(&status, &typ);
+            assert_eq!(cell, (status as i32).to_be_bytes());
+            assert!(IntStatus::type_check(&typ).is_ok());
+            let deserialized = IntStatus::deserialize(&typ, Some(FrameSlice::new_borrowed(&cell)));
+            assert_eq!(deserialized.unwrap(), status);
+        }
+        // unknown discriminants should fail to deserialize
+        let cell = serialize_cell(&3_i32, &typ);
+        let unknown = IntStatus::deserialize(&typ, Some(FrameSlice::new_borrowed(&cell)));
+        assert_eq!(unknown.unwrap_err().to_string(), "UnknownValue");
+        // non integer columns should fail our type check
+        assert!(IntStatus::type_check(&ColumnType::Native(NativeType::Text)).is_err());
+        assert!(IntStatus::type_check(&ColumnType::Native(NativeType::SmallInt)).is_err());
+    }
+
+    #[test]
+    fn scylla_store_as_small_int() {
+        let typ = ColumnType::Native(NativeType::SmallInt);
+        // make sure all of our variants round trip through their discriminant
+        for status in [
+            SmallIntStatus::Created,
+            SmallIntStatus::Running,
+            SmallIntStatus::Completed,
+        ] {
+            let cell = serialize_cell(&status, &typ);
+            assert_eq!(cell, (status as i16).to_be_bytes());
+            let deserialized =
+                SmallIntStatus::deserialize(&typ, Some(FrameSlice::new_borrowed(&cell)));
+            assert_eq!(deserialized.unwrap(), status);
+        }
+        // small int statuses should not accept int columns
+        assert!(SmallIntStatus::type_check(&ColumnType::Native(NativeType::Int)).is_err());
+    }
+}
@@ -436,6 +436,32 @@ impl<T: TagSupport> Default for TagDeleteRequest<T> {
     }
 }
 
+/// The response from applying tags to many items in one request
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "python", thorium_derive::pyclass(get_all))]
+pub struct BulkTagResponse {
+    /// Any errors that occured while tagging items by key
+    pub errors: HashMap<String, String>,
+    /// The keys of the items that were successfully tagged
+    pub tagged: Vec<String>,
+}
+
+impl BulkTagResponse {
+    /// Create a new bulk tag response with a starting capacity for tagged items
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The capacity to allocate
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        BulkTagResponse {
+            errors: HashMap::default(),
+            tagged: Vec::with_capacity(capacity),
+        }
+    }
+}
+
 #[derive(Debug)]
 #[cfg_attr(feature = "scylla-utils", derive(scylla::DeserializeRow))]
 #[cfg_attr(
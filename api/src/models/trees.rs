@@ -12,7 +12,7 @@ use uuid::Uuid;
 
 use crate::models::{Association, AssociationKind, Entity, EntityMetadata, InvalidEnum, Repo};
 
-use super::{Origin, Sample};
+use super::{Origin, Reaction, Sample};
 
 /// Help serde default the tree depth to 5
 const fn default_tree_depth() -> usize {
@@ -904,3 +904,194 @@ impl Tree {
         self.sent.insert(hash);
     }
 }
+
+/// Help serde default the reaction tree depth to 10
+const fn default_reaction_tree_depth() -> usize {
+    10
+}
+
+/// The parameters for building a tree of sub reactions
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct ReactionTreeParams {
+    /// The max depth of sub reactions to gather
+    #[serde(default = "default_reaction_tree_depth")]
+    pub depth: usize,
+}
+
+impl Default for ReactionTreeParams {
+    fn default() -> Self {
+        ReactionTreeParams {
+            depth: default_reaction_tree_depth(),
+        }
+    }
+}
+
+impl ReactionTreeParams {
+    /// Set the max depth of sub reactions to gather
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - The max depth to gather sub reactions at
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::ReactionTreeParams;
+    ///
+    /// ReactionTreeParams::default().depth(3);
+    /// ```
+    #[must_use]
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+}
+
+/// A reaction and all of its sub reactions
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct ReactionTree {
+    /// The reaction at this point in the tree
+    pub reaction: Reaction,
+    /// The sub reactions for this reaction
+    #[cfg_attr(feature = "api", schema(no_recursion))]
+    pub children: Vec<ReactionTree>,
+    /// Whether this reaction has sub reactions that were not gathered due to our depth limit
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl ReactionTree {
+    /// Create a new reaction tree node without any sub reactions
+    ///
+    /// # Arguments
+    ///
+    /// * `reaction` - The reaction at this point in the tree
+    #[must_use]
+    pub fn new(reaction: Reaction) -> Self {
+        ReactionTree {
+            reaction,
+            children: Vec::default(),
+            truncated: false,
+        }
+    }
+
+    /// Find a reaction somewhere in this tree
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the reaction to find
+    #[must_use]
+    pub fn find(&self, id: &Uuid) -> Option<&ReactionTree> {
+        // check if we are the reaction being searched for
+        if self.reaction.id == *id {
+            return Some(self);
+        }
+        // check our children for this reaction
+        self.children.iter().find_map(|child| child.find(id))
+    }
+
+    /// Get the total number of reactions in this tree
+    #[must_use]
+    pub fn count(&self) -> usize {
+        1 + self.children.iter().map(ReactionTree::count).sum::<usize>()
+    }
+}
+
+/// Help serde default the lineage depth to 3
+const fn default_lineage_depth() -> usize {
+    3
+}
+
+/// The parameters for building the lineage of a sample
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct LineageParams {
+    /// The max number of generations of children to gather
+    #[serde(default = "default_lineage_depth")]
+    pub depth: usize,
+}
+
+impl Default for LineageParams {
+    fn default() -> Self {
+        LineageParams {
+            depth: default_lineage_depth(),
+        }
+    }
+}
+
+impl LineageParams {
+    /// Set the max number of generations of children to gather
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - The max number of generations of children to gather
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::LineageParams;
+    ///
+    /// LineageParams::default().depth(2);
+    /// ```
+    #[must_use]
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+}
+
+/// A sample, the reactions run on it, and the lineage of its children
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct LineageTree {
+    /// The sample at this point in the lineage
+    pub sample: Sample,
+    /// The reactions that were run on this sample
+    pub reactions: Vec<Reaction>,
+    /// The lineage of the children discovered from this sample
+    #[cfg_attr(feature = "api", schema(no_recursion))]
+    pub children: Vec<LineageTree>,
+    /// Whether this sample has children that were not gathered due to our depth limit
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl LineageTree {
+    /// Create a new lineage node without any reactions or children
+    ///
+    /// # Arguments
+    ///
+    /// * `sample` - The sample at this point in the lineage
+    #[must_use]
+    pub fn new(sample: Sample) -> Self {
+        LineageTree {
+            sample,
+            reactions: Vec::default(),
+            children: Vec::default(),
+            truncated: false,
+        }
+    }
+
+    /// Find a sample somewhere in this lineage
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the sample to find
+    #[must_use]
+    pub fn find(&self, sha256: &str) -> Option<&LineageTree> {
+        // check if we are the sample being searched for
+        if self.sample.sha256 == sha256 {
+            return Some(self);
+        }
+        // check our children for this sample
+        self.children.iter().find_map(|child| child.find(sha256))
+    }
+
+    /// Get the total number of samples in this lineage
+    #[must_use]
+    pub fn count(&self) -> usize {
+        1 + self.children.iter().map(LineageTree::count).sum::<usize>()
+    }
+}
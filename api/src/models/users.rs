@@ -260,6 +260,9 @@ pub struct UserSettings {
     /// The AI settings for this user
     #[serde(default)]
     pub ai: Option<AiSettings>,
+    /// The group to use when a group isn't set for a new reaction
+    #[serde(default)]
+    pub default_group: Option<String>,
 }
 
 impl UserSettings {
@@ -272,6 +275,16 @@ impl UserSettings {
         self.theme = theme;
         self
     }
+
+    /// Set the default group
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group to use when a group isn't set for a new reaction
+    pub fn default_group<T: Into<String>>(mut self, group: T) -> Self {
+        self.default_group = Some(group.into());
+        self
+    }
 }
 
 /// An update to an AI endpoint configuration
@@ -435,6 +448,12 @@ pub struct UserSettingsUpdate {
     pub theme: Option<Theme>,
     /// The AI settings update for this user
     pub ai: Option<AiSettingsUpdate>,
+    /// The default group to set for this user
+    #[serde(default)]
+    pub default_group: Option<String>,
+    /// Clear this users default group
+    #[serde(default)]
+    pub clear_default_group: bool,
 }
 
 impl UserSettingsUpdate {
@@ -467,6 +486,35 @@ impl UserSettingsUpdate {
         self.ai = Some(ai);
         self
     }
+
+    /// Update the default group for this user
+    ///
+    /// This group is used when a group isn't set for a new reaction and must
+    /// be a group this user is a member of.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The default group to set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::UserSettingsUpdate;
+    ///
+    /// UserSettingsUpdate::default().default_group("corn");
+    /// ```
+    #[must_use]
+    pub fn default_group<T: Into<String>>(mut self, group: T) -> Self {
+        self.default_group = Some(group.into());
+        self
+    }
+
+    /// Clear the default group for this user
+    #[must_use]
+    pub fn clear_default_group(mut self) -> Self {
+        self.clear_default_group = true;
+        self
+    }
 }
 
 /// A user within Thorium
@@ -554,6 +602,42 @@ impl PartialEq<ScrubbedUser> for ScrubbedUser {
     }
 }
 
+/// A short summary of a user that is safe to display
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", thorium_derive::pyclass(get_all))]
+pub struct UserSummary {
+    /// The username of this user
+    pub username: String,
+    /// This users email
+    pub email: String,
+    /// Whether this user is an admin or not
+    pub admin: bool,
+    /// The groups this user is in
+    pub groups: Vec<String>,
+    /// When this users token expires
+    pub token_expiration: DateTime<Utc>,
+    /// Whether this user has been verified already or not
+    pub verified: bool,
+}
+
+impl From<ScrubbedUser> for UserSummary {
+    /// Summarize a [`ScrubbedUser`] dropping its token and settings
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user to summarize
+    fn from(user: ScrubbedUser) -> Self {
+        UserSummary {
+            username: user.username,
+            email: user.email,
+            admin: user.role == UserRole::Admin,
+            groups: user.groups,
+            token_expiration: user.token_expiration,
+            verified: user.verified,
+        }
+    }
+}
+
 /// Response to a sucessful auth
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -18,6 +18,61 @@ impl Version {
     }
 }
 
+/// The limits a Thorium server enforces on requests
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "python", thorium_derive::pyclass(get_all))]
+pub struct CapabilityLimits {
+    /// The max size of an uploaded file in mebibytes if uploads are limited
+    pub max_upload: Option<u64>,
+    /// The max size of a json body in mebibytes (this bounds the size of bulk/batch requests)
+    pub max_json: u64,
+    /// The max number of reactions that can be created in one bulk request
+    pub max_bulk_reactions: usize,
+    /// The max depth a reaction tree can be built out to
+    pub max_reaction_tree_depth: usize,
+    /// The max number of reactions a reaction tree can contain
+    #[serde(default)]
+    pub max_reaction_tree_nodes: usize,
+    /// The max number of generations a sample lineage can be built out to
+    pub max_lineage_depth: usize,
+    /// The max number of samples a sample lineage can contain
+    #[serde(default)]
+    pub max_lineage_nodes: usize,
+    /// The max number of nodes a tree can grow to
+    #[serde(default)]
+    pub max_tree_nodes: usize,
+}
+
+/// The version, features, and limits of a Thorium server
+///
+/// Features are plain strings so that older clients can still talk to newer servers that
+/// support features they do not know about yet.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "python", thorium_derive::pyclass(get_all))]
+pub struct Capabilities {
+    /// The version of this Thorium server
+    #[cfg_attr(feature = "api", schema(example = "1.101.0"))]
+    pub version: String,
+    /// The features that are enabled on this server
+    pub features: Vec<String>,
+    /// The limits this server enforces
+    pub limits: CapabilityLimits,
+}
+
+impl Capabilities {
+    /// Check if this server supports a specific feature
+    ///
+    /// # Arguments
+    ///
+    /// * `feature` - The feature to check for
+    #[must_use]
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|enabled| enabled == feature)
+    }
+}
+
 /// The different operating systems Thorium supports
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub enum Os {
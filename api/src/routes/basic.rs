@@ -1,6 +1,6 @@
-use crate::models::Version;
 use crate::models::backends::system;
-use crate::utils::{ApiError, AppState};
+use crate::models::{Capabilities, CapabilityLimits, Version};
+use crate::utils::{ApiError, ApiErrorBody, ApiErrorCode, AppState};
 use axum::Router;
 use axum::extract::{Json, State};
 use axum::http::StatusCode;
@@ -75,6 +75,21 @@ pub async fn health(State(state): State<AppState>) -> StatusCode {
     StatusCode::SERVICE_UNAVAILABLE
 }
 
+/// A cheap route for checking connectivity and latency to the API
+///
+/// This does not touch any backends unlike the health route.
+#[utoipa::path(
+    get,
+    path = "/api/basic/ping",
+    responses(
+        (status = 204, description = "The API is reachable"),
+    )
+)]
+#[instrument(name = "routes::basic::ping", skip_all)]
+pub async fn ping() -> StatusCode {
+    StatusCode::NO_CONTENT
+}
+
 /// Return the current Thorium version
 ///
 /// # Arguments
@@ -98,11 +113,28 @@ pub async fn version() -> Result<Json<Version>, ApiError> {
     Ok(Json(version))
 }
 
+/// Return the features and limits of this Thorium server
+///
+/// # Arguments
+///
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/basic/capabilities",
+    responses(
+        (status = 200, description = "The features and limits of this Thorium server", body = Capabilities),
+    )
+)]
+#[instrument(name = "routes::basic::capabilities", skip_all)]
+pub async fn capabilities(State(state): State<AppState>) -> Json<Capabilities> {
+    Json(Capabilities::new(&state.shared))
+}
+
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
-    paths(identify, banner, health, version),
-    components(schemas(Version, ApiError)),
+    paths(identify, banner, health, ping, version, capabilities),
+    components(schemas(Capabilities, CapabilityLimits, Version, ApiError, ApiErrorBody, ApiErrorCode)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct BasicApiDocs;
@@ -124,4 +156,6 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
         .route("/banner", get(banner))
         .route("/version", get(version))
         .route("/health", get(health))
+        .route("/basic/ping", get(ping))
+        .route("/basic/capabilities", get(capabilities))
 }
@@ -1,22 +1,25 @@
 //! Routes for entities
 
 use axum::extract::{Multipart, Path, Query, State};
-use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
 use axum::routing::post;
 use axum::{Json, Router};
 use axum_extra::body::AsyncReadBody;
 use tracing::instrument;
 use uuid::Uuid;
 
-use super::shared::graphics;
+use super::shared::{graphics, limits};
+use crate::Conf;
 use crate::models::backends::{GraphicSupport, TagSupport};
 use crate::models::{
-    ApiCursor, Entity, EntityListLine, EntityListParams, EntityResponse, TagDeleteRequest,
-    TagRequest, User,
+    ApiCursor, Association, AssociationListParams, AssociationTargetColumn, BulkEntityResponse,
+    DeviceEntity, DeviceListParams, Entity, EntityDuplicateParams, EntityDuplicateReport,
+    EntityGraph, EntityGraphParams, EntityListLine, EntityListParams, EntityRequest,
+    EntityResponse, GraphFormat, TagDeleteRequest, TagRequest, User,
 };
 use crate::not_found;
-use crate::utils::{ApiError, AppState};
+use crate::utils::{ApiError, AppState, ndjson};
 
 /// Creates a new entity
 ///
@@ -49,6 +52,42 @@ async fn create(
     Ok(Json(resp))
 }
 
+/// Creates new entities in bulk
+///
+/// Entities with images must be created one at a time with a multipart form.
+///
+/// # Arguments
+///
+/// * `user` - The user that is creating these entities
+/// * `state` - Shared Thorium objects
+/// * `reqs` - The entities to create in bulk
+#[utoipa::path(
+    post,
+    path = "/api/entities/bulk/",
+    params(
+        ("reqs" = Vec<EntityRequest>, description = "The entities to create in bulk"),
+    ),
+    responses(
+        (status = 200, description = "Entities created", body = BulkEntityResponse),
+        (status = 400, description = "Too many entities were requested at once"),
+        (status = 401, description = "This user is not authorized to create entities in all given groups"),
+        (status = 404, description = "One or more of the given groups does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::entities::create_bulk", skip_all, err(Debug))]
+async fn create_bulk(
+    user: User,
+    State(state): State<AppState>,
+    Json(reqs): Json<Vec<EntityRequest>>,
+) -> Result<Json<BulkEntityResponse>, ApiError> {
+    // create entities in bulk
+    let resp = Entity::create_bulk(&user, reqs, &state.shared).await?;
+    Ok(Json(resp))
+}
+
 /// Creates a new entity
 ///
 /// # Arguments
@@ -118,10 +157,14 @@ async fn list(
 
 /// Lists entities and their details by the given parameters
 ///
+/// If the request accepts `application/x-ndjson` then each entity is streamed as its
+/// own line of JSON and the cursor is returned in the `x-thorium-cursor` header.
+///
 /// # Arguments
 ///
 /// * `user` - The user that is listing entity details
 /// * `params` - The query params to use for this request
+/// * `headers` - The headers for this request
 /// * `state` - Shared Thorium objects
 #[utoipa::path(
     get,
@@ -130,7 +173,7 @@ async fn list(
         ("params" = EntityListParams, description = "Query params to use for this entity list request"),
     ),
     responses(
-        (status = 200, description = "JSON-formatted cursor response containing the entities' details", body = ApiCursor<Entity>),
+        (status = 200, description = "JSON-formatted cursor response containing the entities' details (or a stream of entities when accepting application/x-ndjson)", body = ApiCursor<Entity>),
         (status = 401, description = "This user is not authorized to access this route"),
     ),
     security(
@@ -141,13 +184,54 @@ async fn list(
 async fn list_details(
     user: User,
     params: EntityListParams,
+    headers: HeaderMap,
     State(state): State<AppState>,
-) -> Result<Json<ApiCursor<Entity>>, ApiError> {
+) -> Result<Response, ApiError> {
     // list entities
     let list = Entity::list(&user, params, false, &state.shared).await?;
+    // stream our details if newline delimited json was requested
+    if ndjson::accepts(&headers) {
+        let cursor = list.cursor;
+        let details = list.details_stream(user, state.shared.clone()).await?;
+        return Ok(ndjson::stream(details, cursor));
+    }
     // convert the list to a details list
     let cursor = list.details(&user, &state.shared).await?;
     // return the cursor
+    Ok(Json(cursor).into_response())
+}
+
+/// Lists devices and their details in a critical sector
+///
+/// # Arguments
+///
+/// * `user` - The user that is listing devices
+/// * `params` - The query params to use for this request
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/entities/devices/",
+    params(
+        ("params" = DeviceListParams, description = "Query params to use for this device list request"),
+    ),
+    responses(
+        (status = 200, description = "JSON-formatted cursor response containing the devices' details", body = ApiCursor<Entity>),
+        (status = 400, description = "No critical sector was set"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::entities::list_devices", skip_all, err(Debug))]
+async fn list_devices(
+    user: User,
+    params: DeviceListParams,
+    State(state): State<AppState>,
+) -> Result<Json<ApiCursor<Entity>>, ApiError> {
+    // list the devices in this critical sector
+    let cursor = DeviceEntity::list(&user, params, &state.shared).await?;
+    // return the cursor
     Ok(Json(cursor))
 }
 
@@ -187,13 +271,15 @@ async fn update(
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Update an entity
+/// Archive an entity
+///
+/// Archived entities are hidden from listings but can still be retrieved and restored.
 ///
 /// # Arguments
 ///
-/// * `user` - The user that is updating an entity
-/// * `request` - The entity update request
+/// * `user` - The user that is archiving an entity
 /// * `state` - Shared Thorium objects
+/// * `id` - The id of the entity to archive
 #[utoipa::path(
     delete,
     path = "/api/entities/:id",
@@ -201,9 +287,10 @@ async fn update(
         ("id" = Uuid, Path, description = "The entity id"),
     ),
     responses(
-        (status = 204, description = "Entity deleted"),
+        (status = 204, description = "Entity archived"),
         (status = 401, description = "This user is not authorized to delete this entity or the group does not allow editing entities"),
         (status = 404, description = "One or more of the given groups does not exist or the entity does not exist"),
+        (status = 409, description = "This entity is already archived"),
     ),
     security(
         ("basic" = []),
@@ -217,8 +304,79 @@ async fn delete(
 ) -> Result<StatusCode, ApiError> {
     // get the entity from the backend
     let entity = Entity::get(&user, id, &state.shared).await?;
-    // update the entity
-    entity.delete(&user, &state.shared).await?;
+    // archive the entity
+    entity.archive(&user, &state.shared).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Restore an archived entity
+///
+/// # Arguments
+///
+/// * `user` - The user that is restoring an entity
+/// * `state` - Shared Thorium objects
+/// * `id` - The id of the entity to restore
+#[utoipa::path(
+    post,
+    path = "/api/entities/:id/restore",
+    params(
+        ("id" = Uuid, Path, description = "The entity id"),
+    ),
+    responses(
+        (status = 204, description = "Entity restored"),
+        (status = 400, description = "This entity is not archived"),
+        (status = 401, description = "This user is not authorized to restore this entity"),
+        (status = 404, description = "The entity does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::entities::restore", skip_all, err(Debug))]
+async fn restore(
+    user: User,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    // get the entity from the backend
+    let entity = Entity::get(&user, id, &state.shared).await?;
+    // restore the entity
+    entity.restore(&user, &state.shared).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Permanently delete an entity and its associations
+///
+/// # Arguments
+///
+/// * `user` - The admin that is purging an entity
+/// * `state` - Shared Thorium objects
+/// * `id` - The id of the entity to purge
+#[utoipa::path(
+    delete,
+    path = "/api/entities/:id/purge",
+    params(
+        ("id" = Uuid, Path, description = "The entity id"),
+    ),
+    responses(
+        (status = 204, description = "Entity purged"),
+        (status = 401, description = "Only admins can purge entities"),
+        (status = 404, description = "The entity does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::entities::purge", skip_all, err(Debug))]
+async fn purge(
+    user: User,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    // get the entity from the backend
+    let entity = Entity::get(&user, id, &state.shared).await?;
+    // permanently delete the entity
+    entity.purge(&user, &state.shared).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -296,6 +454,83 @@ async fn delete_tags(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Export the association graph around some entities
+///
+/// # Arguments
+///
+/// * `user` - The user that is exporting a graph
+/// * `params` - The query params to use for this request
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/entities/graph/",
+    params(
+        ("params" = EntityGraphParams, description = "Query params to use for this graph export"),
+    ),
+    responses(
+        (status = 200, description = "The association graph as node-link JSON or GraphML", body = EntityGraph),
+        (status = 400, description = "The requested graph limits are invalid"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "One of the requested entities does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::entities::export_graph", skip_all, err(Debug))]
+async fn export_graph(
+    user: User,
+    params: EntityGraphParams,
+    State(state): State<AppState>,
+) -> Result<Response, ApiError> {
+    // get the format to export our graph in before our params are consumed
+    let format = params.format;
+    // build the graph for these entities
+    let graph = EntityGraph::export(&user, params, &state.shared).await?;
+    // return our graph in the requested format
+    match format {
+        GraphFormat::NodeLink => Ok(Json(graph).into_response()),
+        GraphFormat::GraphMl => Ok((
+            [(header::CONTENT_TYPE, "application/graphml+xml")],
+            graph.to_graphml(),
+        )
+            .into_response()),
+    }
+}
+
+/// Scan entities for likely duplicates based on the similarity of their names
+///
+/// # Arguments
+///
+/// * `user` - The user that is scanning for duplicates
+/// * `params` - The query params to use for this request
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/entities/duplicates/",
+    params(
+        ("params" = EntityDuplicateParams, description = "Query params to use for this duplicate scan"),
+    ),
+    responses(
+        (status = 200, description = "The likely duplicate entities", body = EntityDuplicateReport),
+        (status = 400, description = "The requested threshold or limit is invalid"),
+        (status = 401, description = "This user is not an admin or is not in a requested group"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::entities::find_duplicates", skip_all, err(Debug))]
+async fn find_duplicates(
+    user: User,
+    params: EntityDuplicateParams,
+    State(state): State<AppState>,
+) -> Result<Json<EntityDuplicateReport>, ApiError> {
+    // scan for any likely duplicates
+    let report = EntityDuplicateReport::scan(&user, params, &state.shared).await?;
+    Ok(Json(report))
+}
+
 /// Get an entity's image
 ///
 /// # Arguments
@@ -347,20 +582,73 @@ async fn get_image(
     }
 }
 
+/// List an entity's associations
+///
+/// # Arguments
+///
+/// * `user` - The user that is listing associations
+/// * `params` - The query params to use for this request
+/// * `id` - The id of the entity to list associations for
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/entities/associations/:id",
+    params(
+        ("id" = Uuid, Path, description = "The id of the entity to list associations for"),
+        ("params" = AssociationListParams, description = "Query params to use for this association list request"),
+    ),
+    responses(
+        (status = 200, description = "JSON-formatted cursor response containing this entity's associations", body = ApiCursor<Association>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::entities::list_associations", skip_all, err(Debug))]
+async fn list_associations(
+    user: User,
+    params: AssociationListParams,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiCursor<Association>>, ApiError> {
+    // make sure this user can access this entity
+    Entity::get(&user, id, &state.shared).await?;
+    // create the source to list associations from
+    let source = AssociationTargetColumn::Entity(id);
+    // list the associations for this entity in the requested time window
+    let cursor = Association::list(&user, params, &source, &state.shared).await?;
+    Ok(Json(cursor))
+}
+
 /// Add the entities routes to our router
 ///
 /// # Arguments
 ///
 // * `router` - The router to add routes too
-pub fn mount(router: Router<AppState>) -> Router<AppState> {
+// * `conf` - The Thorium config
+pub fn mount(router: Router<AppState>, conf: &Conf) -> Router<AppState> {
     router
-        .route("/entities/", post(create))
+        .route("/entities/", post(create).layer(limits::upload(conf)))
+        .route("/entities/bulk/", post(create_bulk))
         .route("/entities/", axum::routing::get(list))
         .route("/entities/details/", axum::routing::get(list_details))
+        .route("/entities/devices/", axum::routing::get(list_devices))
+        .route("/entities/graph/", axum::routing::get(export_graph))
+        .route("/entities/duplicates/", axum::routing::get(find_duplicates))
         .route(
             "/entities/{id}",
-            axum::routing::get(get).patch(update).delete(delete),
+            axum::routing::get(get)
+                .patch(update)
+                .delete(delete)
+                .layer(limits::upload(conf)),
         )
         .route("/entities/{id}/image", axum::routing::get(get_image))
+        .route("/entities/{id}/restore", post(restore))
+        .route("/entities/{id}/purge", axum::routing::delete(purge))
         .route("/entities/tags/{id}", post(tag).delete(delete_tags))
+        .route(
+            "/entities/associations/{id}",
+            axum::routing::get(list_associations),
+        )
 }
@@ -2,26 +2,30 @@
 
 use axum::Router;
 use axum::extract::{Json, Multipart, Path, State};
-use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::http::header::{CONTENT_RANGE, RANGE};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{delete, get, patch, post};
 use axum_extra::body::AsyncReadBody;
+use std::collections::HashMap;
 use tracing::instrument;
 use utoipa::OpenApi;
 use uuid::Uuid;
 
 use super::OpenApiSecurity;
+use super::shared::limits;
 use crate::models::backends::{CommentSupport, TagSupport};
 use crate::models::{
-    ApiCursor, Association, AssociationListParams, AssociationTargetColumn, CarvedOrigin, Comment,
-    CommentResponse, DeleteCommentParams, DeleteSampleParams, FileListParams, ImageVersion, Origin,
-    OriginRequest, Output, OutputDisplayType, OutputFormBuilder, OutputHandler, OutputKind,
-    OutputMap, OutputResponse, PcapNetworkProtocol, ResultFileDownloadParams, ResultGetParams,
-    Sample, SampleCheck, SampleCheckResponse, SampleListLine, SampleSubmissionResponse,
-    SubmissionChunk, SubmissionUpdate, TagCounts, TagDeleteRequest, TagRequest, User,
-    ZipDownloadParams,
+    ApiCursor, Association, AssociationListParams, AssociationTargetColumn, BulkTagResponse,
+    ByteRange, CarvedOrigin, Comment, CommentResponse, DeleteCommentParams, DeleteSampleParams,
+    FileListParams, ImageVersion, Origin, OriginRequest, Output, OutputDisplayType,
+    OutputFormBuilder, OutputHandler, OutputKind, OutputMap, OutputResponse, PcapNetworkProtocol,
+    ResultFileDownloadParams, ResultGetParams, Sample, SampleCheck, SampleCheckResponse,
+    SampleListLine, SampleSubmissionResponse, SubmissionChunk, SubmissionUpdate, TagCounts,
+    TagDeleteRequest, TagRequest, User, ZipDownloadParams,
 };
 use crate::utils::{ApiError, AppState};
+use crate::{Conf, bad, bad_internal};
 
 /* TODO_UTOIPA: the '/files/download_result_file/:sha256/:tool/:result_id/\*path'
    route is implemented with a wildcard on the path variable, but
@@ -136,22 +140,47 @@ async fn exists(
     Ok(Json(resp))
 }
 
+/// Get the range of bytes a request asked for if it set one
+///
+/// # Arguments
+///
+/// * `headers` - The headers sent with this request
+fn requested_range(headers: &HeaderMap) -> Result<Option<ByteRange>, ApiError> {
+    match headers.get(RANGE) {
+        Some(raw) => {
+            let raw = raw
+                .to_str()
+                .map_err(|_| bad_internal!("The range header must be ASCII".to_owned()))?;
+            Ok(Some(ByteRange::parse(raw)?))
+        }
+        None => Ok(None),
+    }
+}
+
 /// Download a file by sha256
 ///
+/// Files are always downloaded in the CaRT format. If a range is requested then
+/// only that range of the carted file is returned.
+///
 /// # Arguments
 ///
 /// * `user` - The user that is downloading this file
 /// * `sha256` - The sha256 to download
+/// * `headers` - The headers sent with this request
 /// * `state` - Shared Thorium objects
 #[utoipa::path(
     get,
     path = "/api/files/sample/:sha256/download",
     params(
-        ("sha256" = String, Path, description = "Sha256 of file to download")
+        ("sha256" = String, Path, description = "Sha256 of file to download"),
+        ("Range" = Option<String>, Header, description = "A single range of carted bytes to download (e.g. bytes=0-1023)")
     ),
     responses(
         (status = 200, description = "Download a file by sha256", body = Vec<u8>),
+        (status = 206, description = "The requested range of the carted file", body = Vec<u8>),
+        (status = 400, description = "The requested range is invalid"),
         (status = 401, description = "This user is not authorized to access this route"),
+        (status = 416, description = "The requested range starts past the end of the file"),
     ),
     security(
         ("basic" = []),
@@ -161,13 +190,68 @@ async fn exists(
 async fn download(
     user: User,
     Path(sha256): Path<String>,
+    headers: HeaderMap,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, ApiError> {
+) -> Result<Response, ApiError> {
+    // if a range was requested then only download those bytes
+    if let Some(range) = requested_range(&headers)? {
+        // check if we have access to this sample and download our range if we do
+        let (content_range, stream) =
+            Sample::download_range(&user, sha256, &range, &state.shared).await?;
+        let body = AsyncReadBody::new(stream.into_async_read());
+        let content_range = [(CONTENT_RANGE, content_range)];
+        return Ok((StatusCode::PARTIAL_CONTENT, content_range, body).into_response());
+    }
     // check if we have access to this sample and download it if we do
     let stream = Sample::download(&user, sha256, &state.shared).await?;
     // convert our byte stream to a streamable body
     let body = AsyncReadBody::new(stream.into_async_read());
-    Ok(body)
+    Ok(body.into_response())
+}
+
+/// Download a range of bytes from an uncarted file by sha256
+///
+/// Only a single range is returned and a range must be requested.
+///
+/// # Arguments
+///
+/// * `user` - The user that is downloading this file
+/// * `sha256` - The sha256 to download
+/// * `headers` - The headers sent with this request
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/files/sample/:sha256/download/uncarted",
+    params(
+        ("sha256" = String, Path, description = "Sha256 of file to download"),
+        ("Range" = String, Header, description = "A single range of uncarted bytes to download (e.g. bytes=0-1023)")
+    ),
+    responses(
+        (status = 206, description = "The requested range of the uncarted file", body = Vec<u8>),
+        (status = 400, description = "The requested range is invalid or missing"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 416, description = "The requested range starts past the end of the file or is too large"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::download_uncarted", skip_all, err(Debug))]
+async fn download_uncarted(
+    user: User,
+    Path(sha256): Path<String>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Response, ApiError> {
+    // we never return a whole uncarted file so a range is required
+    let Some(range) = requested_range(&headers)? else {
+        return bad!("A range must be requested to download uncarted bytes".to_owned());
+    };
+    // check if we have access to this sample and download our range if we do
+    let (read, bytes) =
+        Sample::download_uncarted_range(&user, sha256, &range, &state.shared).await?;
+    let content_range = [(CONTENT_RANGE, read.content_range())];
+    Ok((StatusCode::PARTIAL_CONTENT, content_range, bytes).into_response())
 }
 
 /// Download a file by sha2566 as an encrypted zip
@@ -317,6 +401,38 @@ async fn tag(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Adds new tags to many samples at once
+///
+/// # Arguments
+///
+/// * `user` - The user that is adding new tags
+/// * `state` - Shared Thorium objects
+/// * `reqs` - The new tags to apply by sha256
+#[utoipa::path(
+    post,
+    path = "/api/files/tags/bulk/",
+    params(
+        ("reqs" = HashMap<String, TagRequest<Sample>>, description = "JSON-formatted tags to apply by sha256")
+    ),
+    responses(
+        (status = 200, description = "The samples that were tagged and any errors", body = BulkTagResponse),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::tag_bulk", skip_all, err(Debug))]
+async fn tag_bulk(
+    user: User,
+    State(state): State<AppState>,
+    Json(reqs): Json<HashMap<String, TagRequest<Sample>>>,
+) -> Result<Json<BulkTagResponse>, ApiError> {
+    // try to add the new tags to all of these samples
+    let response = Sample::tag_bulk(&user, reqs, &state.shared).await?;
+    Ok(Json(response))
+}
+
 /// Deletes tags from a sample
 ///
 /// # Arguments
@@ -739,8 +855,8 @@ async fn download_result_file(
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
-    paths(list, upload, list_details, get_sample, delete_sample, exists, download, download_as_zip, /*download_result_file,*/ update, tag, delete_tags, create_comment, delete_comment, download_attachment, get_results, upload_results),
-    components(schemas(ApiCursor<Sample>, ApiCursor<SampleListLine>, CarvedOrigin, Comment, CommentResponse, DeleteCommentParams, DeleteSampleParams,FileListParams, ImageVersion, Origin, OriginRequest, Output, OutputDisplayType, OutputHandler, OutputMap, OutputResponse, PcapNetworkProtocol, ResultGetParams, Sample, SampleCheck, SampleCheckResponse, SampleListLine, SampleSubmissionResponse, SubmissionChunk, SubmissionUpdate, TagDeleteRequest<Sample>, TagRequest<Sample>, ZipDownloadParams, TagCounts)),
+    paths(list, upload, list_details, get_sample, delete_sample, exists, download, download_uncarted, download_as_zip, /*download_result_file,*/ update, tag, tag_bulk, delete_tags, create_comment, delete_comment, download_attachment, get_results, upload_results),
+    components(schemas(ApiCursor<Sample>, ApiCursor<SampleListLine>, BulkTagResponse, CarvedOrigin, Comment, CommentResponse, DeleteCommentParams, DeleteSampleParams,FileListParams, ImageVersion, Origin, OriginRequest, Output, OutputDisplayType, OutputHandler, OutputMap, OutputResponse, PcapNetworkProtocol, ResultGetParams, Sample, SampleCheck, SampleCheckResponse, SampleListLine, SampleSubmissionResponse, SubmissionChunk, SubmissionUpdate, TagDeleteRequest<Sample>, TagRequest<Sample>, ZipDownloadParams, TagCounts)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct FileApiDocs;
@@ -756,9 +872,13 @@ async fn openapi() -> Json<utoipa::openapi::OpenApi> {
 /// # Arguments
 ///
 /// * `router` - The router to add routes too
-pub fn mount(router: Router<AppState>) -> Router<AppState> {
+/// * `conf` - The Thorium config
+pub fn mount(router: Router<AppState>, conf: &Conf) -> Router<AppState> {
     router
-        .route("/files/", get(list).post(upload))
+        .route(
+            "/files/",
+            get(list).post(upload).layer(limits::upload(conf)),
+        )
         .route("/files/count/", get(count))
         .route("/files/details/", get(list_details))
         .route("/files/associations/{sha256}", get(list_associations))
@@ -766,10 +886,18 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
         .route("/files/sample/{sha256}/{submission}", delete(delete_sample))
         .route("/files/exists", post(exists))
         .route("/files/sample/{sha256}/download", get(download))
+        .route(
+            "/files/sample/{sha256}/download/uncarted",
+            get(download_uncarted),
+        )
         .route("/files/sample/{sha256}/download/zip", get(download_as_zip))
         .route("/files/sample/{sha256}", patch(update))
+        .route("/files/tags/bulk/", post(tag_bulk))
         .route("/files/tags/{sha256}", post(tag).delete(delete_tags))
-        .route("/files/comment/{sha256}", post(create_comment))
+        .route(
+            "/files/comment/{sha256}",
+            post(create_comment).layer(limits::upload(conf)),
+        )
         .route("/files/comment/{sha256}/{id}", delete(delete_comment))
         .route(
             "/files/comment/download/{sha256}/{comment}/{name}",
@@ -777,7 +905,9 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
         )
         .route(
             "/files/results/{sha256}",
-            get(get_results).post(upload_results),
+            get(get_results)
+                .post(upload_results)
+                .layer(limits::upload(conf)),
         )
         .route(
             "/files/result-files/{sha256}/{tool}/{result_id}",
@@ -10,8 +10,8 @@ use super::OpenApiSecurity;
 // our imports
 use crate::is_admin;
 use crate::models::{
-    Group, GroupAllowAction, GroupAllowed, GroupAllowedUpdate, GroupDetailsList, GroupList,
-    GroupListParams, GroupMap, GroupRequest, GroupStats, GroupUpdate, GroupUsers,
+    CacheWarmResponse, Group, GroupAllowAction, GroupAllowed, GroupAllowedUpdate, GroupDetailsList,
+    GroupList, GroupListParams, GroupMap, GroupRequest, GroupStats, GroupUpdate, GroupUsers,
     GroupUsersRequest, GroupUsersUpdate, PipelineStats, Roles, StageStats, User,
 };
 use crate::utils::{ApiError, AppState};
@@ -282,11 +282,49 @@ async fn get_stats(
     Ok(Json(status))
 }
 
+/// Loads a groups images and pipelines into the server's caches
+///
+/// Only the group is cached if the definition cache is disabled.
+///
+/// # Arguments
+///
+/// * `user` - The user that is warming this groups caches
+/// * `group` - The group to warm caches for
+/// * `shared` - Shared Thorium objects
+#[utoipa::path(
+    post,
+    path = "/api/groups/:group/cache/warm",
+    params(
+        ("group" = String, Path, description = "The group to warm caches for"),
+    ),
+    responses(
+        (status = 200, description = "The number of images and pipelines that were cached", body = CacheWarmResponse),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::groups::warm_cache", skip(user, state), err(Debug))]
+async fn warm_cache(
+    user: User,
+    Path(group): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<CacheWarmResponse>, ApiError> {
+    // make sure we are an admin
+    is_admin!(user);
+    // get the group to warm caches for which also caches the group itself
+    let group = Group::get(&user, &group, &state.shared).await?;
+    // cache this groups images and pipelines
+    let warmed = group.warm_cache(&state.shared).await?;
+    Ok(Json(warmed))
+}
+
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
-    paths(create, list, get_group, list_details, update, delete_group, sync_ldap, get_stats),
-    components(schemas(Group, GroupAllowed, GroupAllowedUpdate, GroupAllowAction, GroupDetailsList, GroupList, GroupListParams, GroupMap, GroupRequest, GroupStats, GroupUpdate, GroupUsersRequest, GroupUsers, GroupUsersUpdate, PipelineStats, Roles, StageStats)),
+    paths(create, list, get_group, list_details, update, delete_group, sync_ldap, get_stats, warm_cache),
+    components(schemas(CacheWarmResponse, Group, GroupAllowed, GroupAllowedUpdate, GroupAllowAction, GroupDetailsList, GroupList, GroupListParams, GroupMap, GroupRequest, GroupStats, GroupUpdate, GroupUsersRequest, GroupUsers, GroupUsersUpdate, PipelineStats, Roles, StageStats)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct GroupApiDocs;
@@ -310,4 +348,5 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
         .route("/groups/{group}", patch(update).delete(delete_group))
         .route("/groups/sync/ldap", post(sync_ldap))
         .route("/groups/{group}/stats", get(get_stats))
+        .route("/groups/{group}/cache/warm", post(warm_cache))
 }
@@ -12,9 +12,9 @@ use super::OpenApiSecurity;
 
 use crate::models::{
     Checkpoint, CommitishKinds, Deadline, GenericJob, GenericJobArgs, GenericJobOpts,
-    HandleJobResponse, ImageScaler, JobHandleStatus, JobListOpts, JobResetRequestor, JobResets,
-    JobStatus, Pipeline, RawJob, RepoDependency, RunningJob, StageLogLine, StageLogsAdd,
-    SystemComponents, User, WorkerName,
+    HandleJobResponse, ImageScaler, JobHandleStatus, JobHeartbeat, JobListOpts, JobResetRequestor,
+    JobResets, JobStarted, JobStatus, Pipeline, RawJob, RepoDependency, RunningJob, StageLogLine,
+    StageLogsAdd, SystemComponents, User, WorkerName,
 };
 use crate::utils::{ApiError, AppState};
 
@@ -125,6 +125,79 @@ async fn proceed(
     Ok((StatusCode::ACCEPTED, response).into_response())
 }
 
+/// Report that a claimed job has started executing
+///
+/// This starts the job's execution deadline so time spent in queue doesn't count against it.
+///
+/// # Arguments
+///
+/// * `user` - The user that is starting this job
+/// * `id` - The uuid of the job that started
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    post,
+    path = "/api/jobs/handle/:id/start",
+    params(
+        ("id" = Uuid, Path, description = "The uuid of the job that started"),
+    ),
+    responses(
+        (status = 200, description = "When this job started and must finish by", body = JobStarted),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 409, description = "This job is not running"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::jobs::start", skip_all, fields(job = id.to_string()), err(Debug))]
+async fn start(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<JobStarted>, ApiError> {
+    // get job object
+    let (group, job) = RawJob::get(&user, &id, &state.shared).await?;
+    // start this jobs execution clock
+    let started = job.start(&user, &group, &state.shared).await?;
+    Ok(Json(started))
+}
+
+/// Push back a running job's execution deadline so it isn't reclaimed
+///
+/// # Arguments
+///
+/// * `user` - The user that is sending this heartbeat
+/// * `id` - The uuid of the job to send a heartbeat for
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    post,
+    path = "/api/jobs/handle/:id/heartbeat",
+    params(
+        ("id" = Uuid, Path, description = "The uuid of the job to send a heartbeat for"),
+    ),
+    responses(
+        (status = 200, description = "When this job must now finish by", body = JobHeartbeat),
+        (status = 400, description = "This job cannot be extended any further"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 409, description = "This job has not been started"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::jobs::heartbeat", skip_all, fields(job = id.to_string()), err(Debug))]
+async fn heartbeat(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<JobHeartbeat>, ApiError> {
+    // get job object
+    let (group, job) = RawJob::get(&user, &id, &state.shared).await?;
+    // push back this jobs execution deadline
+    let heartbeat = job.heartbeat(&user, &group, &state.shared).await?;
+    Ok(Json(heartbeat))
+}
+
 /// ApiError out this job that has just failed
 ///
 /// # Arguments
@@ -381,8 +454,8 @@ async fn bulk_running(
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
-    paths(claim, proceed, error, sleep, checkpoint, bulk_reset, read_deadlines, bulk_running),
-    components(schemas(Checkpoint, CommitishKinds, Deadline, GenericJob, GenericJobArgs, GenericJobOpts, HandleJobResponse, ImageScaler, JobHandleStatus, JobListOpts, JobResetRequestor, JobResets, JobHandleStatus, JobStatus, RepoDependency, RunningJob, StageLogLine, StageLogsAdd, SystemComponents)),
+    paths(claim, start, heartbeat, proceed, error, sleep, checkpoint, bulk_reset, read_deadlines, bulk_running),
+    components(schemas(Checkpoint, CommitishKinds, Deadline, GenericJob, GenericJobArgs, GenericJobOpts, HandleJobResponse, ImageScaler, JobHandleStatus, JobListOpts, JobResetRequestor, JobResets, JobHandleStatus, JobHeartbeat, JobStarted, JobStatus, RepoDependency, RunningJob, StageLogLine, StageLogsAdd, SystemComponents)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct JobApiDocs;
@@ -404,6 +477,8 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
             "/jobs/claim/{group}/{pipeline}/{stage}/{cluster}/{node}/{worker}/{limit}",
             patch(claim),
         )
+        .route("/jobs/handle/{id}/start", post(start))
+        .route("/jobs/handle/{id}/heartbeat", post(heartbeat))
         .route("/jobs/handle/{id}/proceed/{runtime}", post(proceed))
         .route("/jobs/handle/{id}/error", post(error))
         .route("/jobs/handle/{id}/sleep", post(sleep))
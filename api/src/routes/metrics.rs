@@ -0,0 +1,66 @@
+//! Serves the metrics tracked by this API in the Prometheus text format
+
+use axum::Router;
+use axum::extract::State;
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::http::{HeaderMap, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use tracing::instrument;
+
+use crate::models::backends::system;
+use crate::unauthorized;
+use crate::utils::metrics::CacheStats;
+use crate::utils::{ApiError, AppState};
+
+/// The content type for the Prometheus text format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Get the metrics for this API
+///
+/// # Arguments
+///
+/// * `headers` - The headers sent with this request
+/// * `state` - Shared Thorium objects
+#[instrument(name = "routes::metrics::scrape", skip_all, err(Debug))]
+async fn scrape(headers: HeaderMap, State(state): State<AppState>) -> Result<Response, ApiError> {
+    // if a token is set then scrapers must send it as a bearer token
+    if let Some(token) = &state.shared.config.thorium.metrics.token {
+        let sent = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if sent != Some(token.as_str()) {
+            return unauthorized!();
+        }
+    }
+    // get the current depth of our job queues
+    let queues = system::queue_depths(&state.shared).await?;
+    // get the current state of our Scylla connection pool
+    let scylla = state.shared.scylla.pool_stats();
+    // get the current state of our caches
+    let caches = [
+        CacheStats::new("groups", &state.shared.groups),
+        CacheStats::new("images", &state.shared.image_cache),
+        CacheStats::new("pipelines", &state.shared.pipeline_cache),
+    ];
+    // render our metrics
+    let body = state.shared.metrics.render(&queues, &scylla, &caches);
+    Ok((
+        [(
+            CONTENT_TYPE,
+            HeaderValue::from_static(PROMETHEUS_CONTENT_TYPE),
+        )],
+        body,
+    )
+        .into_response())
+}
+
+/// Add the metrics routes to our router
+///
+/// # Arguments
+///
+/// * `router` - The router to add routes too
+pub fn mount(router: Router<AppState>) -> Router<AppState> {
+    router.route("/metrics", get(scrape))
+}
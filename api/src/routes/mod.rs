@@ -12,6 +12,7 @@ mod routes_reexport {
     pub mod images;
     pub mod jobs;
     pub mod mcp;
+    pub mod metrics;
     pub mod network_policies;
     pub mod pipelines;
     pub mod reactions;
@@ -14,7 +14,8 @@ use crate::models::pipelines::{BannedImageBan, GenericBan};
 use crate::models::{
     EventTrigger, Group, Notification, NotificationParams, NotificationRequest, Pipeline,
     PipelineBan, PipelineBanKind, PipelineBanUpdate, PipelineDetailsList, PipelineKey,
-    PipelineList, PipelineListParams, PipelineRequest, PipelineUpdate, TagType, User,
+    PipelineList, PipelineListParams, PipelinePlan, PipelineRequest, PipelineTriggerRequest,
+    PipelineTriggers, PipelineUpdate, PlanEdge, PlanNode, PlanWarning, PlannedStage, TagType, User,
 };
 use crate::utils::{ApiError, AppState};
 
@@ -84,6 +85,41 @@ async fn get_pipeline(
     Ok(Json(pipeline))
 }
 
+/// Plans out the jobs a pipeline would create without creating any of them
+///
+/// # Arguments
+///
+/// * `user` - The user that is planning this pipeline
+/// * `group` - The group this pipeline is in
+/// * `pipeline` - The name of the pipeline to plan
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/pipelines/plan/:group/:pipeline",
+    params(
+        ("group" = String, Path, description = "The group this pipeline is in"),
+        ("pipeline" = String, Path, description = "The name of the pipeline to plan"),
+    ),
+    responses(
+        (status = 200, description = "The jobs this pipeline would create", body = PipelinePlan),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::pipelines::plan", skip_all, err(Debug))]
+async fn plan(
+    user: User,
+    Path((group, pipeline)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<PipelinePlan>, ApiError> {
+    // get our pipeline and plan it out
+    let (_, pipeline) = Pipeline::get(&user, &group, &pipeline, &state.shared).await?;
+    let plan = pipeline.get_plan(&state.shared).await?;
+    Ok(Json(plan))
+}
+
 /// Lists pipelines in a group
 ///
 /// # Arguments
@@ -249,6 +285,125 @@ async fn delete_pipeline(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Lists the triggers for a pipeline
+///
+/// # Arguments
+///
+/// * `user` - The user that is listing triggers
+/// * `group` - The group this pipeline is in
+/// * `pipeline` - The name of the pipeline to list triggers for
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/pipelines/triggers/:group/:pipeline",
+    params(
+        ("group" = String, Path, description = "The group this pipeline is in"),
+        ("pipeline" = String, Path, description = "The name of the pipeline to list triggers for"),
+    ),
+    responses(
+        (status = 200, description = "The triggers for this pipeline", body = PipelineTriggers),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The group or pipeline does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::pipelines::list_triggers", skip_all, err(Debug))]
+async fn list_triggers(
+    user: User,
+    Path((group, pipeline)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<PipelineTriggers>, ApiError> {
+    // get our pipeline
+    let (_, pipeline) = Pipeline::get(&user, &group, &pipeline, &state.shared).await?;
+    Ok(Json(PipelineTriggers::from(pipeline)))
+}
+
+/// Adds a trigger to a pipeline
+///
+/// # Arguments
+///
+/// * `user` - The user that is adding this trigger
+/// * `group` - The group this pipeline is in
+/// * `pipeline` - The name of the pipeline to add a trigger to
+/// * `state` - Shared Thorium objects
+/// * `req` - The trigger to add
+#[utoipa::path(
+    post,
+    path = "/api/pipelines/triggers/:group/:pipeline",
+    params(
+        ("group" = String, Path, description = "The group this pipeline is in"),
+        ("pipeline" = String, Path, description = "The name of the pipeline to add a trigger to"),
+        ("req" = PipelineTriggerRequest, description = "The trigger to add"),
+    ),
+    responses(
+        (status = 204, description = "Trigger added"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The group or pipeline does not exist"),
+        (status = 409, description = "A trigger with this name already exists"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::pipelines::create_trigger", skip_all, err(Debug))]
+async fn create_trigger(
+    user: User,
+    Path((group, pipeline)): Path<(String, String)>,
+    State(state): State<AppState>,
+    Json(req): Json<PipelineTriggerRequest>,
+) -> Result<StatusCode, ApiError> {
+    // get our pipeline and make sure it and its group exist
+    let (group, pipeline) = Pipeline::get(&user, &group, &pipeline, &state.shared).await?;
+    // add this trigger
+    pipeline
+        .add_trigger(req, &user, &group, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Deletes a trigger from a pipeline
+///
+/// # Arguments
+///
+/// * `user` - The user that is deleting this trigger
+/// * `group` - The group this pipeline is in
+/// * `pipeline` - The name of the pipeline to delete a trigger from
+/// * `trigger` - The name of the trigger to delete
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    delete,
+    path = "/api/pipelines/triggers/:group/:pipeline/:trigger",
+    params(
+        ("group" = String, Path, description = "The group this pipeline is in"),
+        ("pipeline" = String, Path, description = "The name of the pipeline to delete a trigger from"),
+        ("trigger" = String, Path, description = "The name of the trigger to delete"),
+    ),
+    responses(
+        (status = 204, description = "Trigger deleted"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The group, pipeline, or trigger does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::pipelines::delete_trigger", skip_all, err(Debug))]
+async fn delete_trigger(
+    user: User,
+    Path((group, pipeline, trigger)): Path<(String, String, String)>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // get our pipeline
+    let (group, pipeline) = Pipeline::get(&user, &group, &pipeline, &state.shared).await?;
+    // delete this trigger
+    pipeline
+        .delete_trigger(&trigger, &user, &group, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Create a notification for a pipeline in scylla
 ///
 /// # Arguments
@@ -374,8 +529,8 @@ async fn delete_notification(
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
-    paths(create, get_pipeline, list, list_details, update, delete_pipeline),
-    components(schemas(BannedImageBan, EventTrigger, GenericBan, Pipeline, PipelineBan, PipelineBanKind, PipelineBanUpdate, PipelineDetailsList, PipelineList, PipelineListParams, PipelineRequest, PipelineUpdate, TagType)),
+    paths(create, get_pipeline, plan, list, list_details, update, delete_pipeline, list_triggers, create_trigger, delete_trigger),
+    components(schemas(BannedImageBan, EventTrigger, GenericBan, Pipeline, PipelineBan, PipelineBanKind, PipelineBanUpdate, PipelineDetailsList, PipelineList, PipelineListParams, PipelinePlan, PipelineRequest, PipelineTriggerRequest, PipelineTriggers, PipelineUpdate, PlanEdge, PlanNode, PlanWarning, PlannedStage, TagType)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct PipelineApiDocs;
@@ -395,12 +550,21 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
     router
         .route("/pipelines/", post(create))
         .route("/pipelines/data/{group}/{pipeline}", get(get_pipeline))
+        .route("/pipelines/plan/{group}/{pipeline}", get(plan))
         .route("/pipelines/list/{group}/", get(list))
         .route("/pipelines/list/{group}/details/", get(list_details))
         .route(
             "/pipelines/{group}/{pipeline}",
             patch(update).delete(delete_pipeline),
         )
+        .route(
+            "/pipelines/triggers/{group}/{pipeline}",
+            get(list_triggers).post(create_trigger),
+        )
+        .route(
+            "/pipelines/triggers/{group}/{pipeline}/{trigger}",
+            delete(delete_trigger),
+        )
         .route(
             "/pipelines/notifications/{group}/{pipeline}",
             get(get_notifications).post(create_notification),
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 
 use axum::Router;
+use axum::body::Body;
 use axum::extract::{Json, Multipart, Path, Query, State};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use axum::response::Response;
 use axum::routing::{get, patch, post};
@@ -12,15 +13,16 @@ use utoipa::OpenApi;
 use uuid::Uuid;
 
 use super::OpenApiSecurity;
-use crate::bad;
+use super::shared::limits;
 use crate::models::{
     Actions, BulkReactionResponse, CommitishKinds, Group, HandleReactionResponse, ImageScaler,
     JobResetRequestor, Pipeline, Reaction, ReactionCache, ReactionCacheUpdate, ReactionDetailsList,
-    ReactionIdResponse, ReactionList, ReactionListParams, ReactionRequest, ReactionStatus,
-    ReactionUpdate, RepoDependency, RepoDependencyRequest, StageLogLine, StageLogs, StageLogsAdd,
-    StatusUpdate, SystemComponents, User,
+    ReactionIdResponse, ReactionList, ReactionListParams, ReactionRequest, ReactionSamplesAdd,
+    ReactionStatus, ReactionUpdate, RepoDependency, RepoDependencyRequest, StageLogLine, StageLogs,
+    StageLogsAdd, StatusUpdate, SystemComponents, User, ZipDownloadParams,
 };
-use crate::utils::{ApiError, AppState};
+use crate::utils::{ApiError, AppState, backpressure, ndjson};
+use crate::{Conf, bad};
 
 /// Creates a new reaction
 ///
@@ -47,21 +49,28 @@ use crate::utils::{ApiError, AppState};
 async fn create(
     user: User,
     State(state): State<AppState>,
-    Json(req): Json<ReactionRequest>,
+    Json(mut req): Json<ReactionRequest>,
 ) -> Result<Json<ReactionIdResponse>, ApiError> {
+    // use this users default group if a group wasn't set
+    req.resolve_group(&user)?;
     // get pipeline
-    let (group, pipeline) = Pipeline::get(&user, &req.group, &req.pipeline, &state.shared).await?;
+    let (group, pipeline) =
+        Pipeline::get(&user, req.group_name(), &req.pipeline, &state.shared).await?;
     // refrain from running the reaction if the pipeline has a ban
     if !pipeline.bans.is_empty() {
         return bad!(format!(
             "The reaction cannot be created because pipeline '{}' in group '{}' has one or more bans! \
             See the pipeline's notifications for more details.",
-            req.pipeline, req.group,
+            req.pipeline, group.name,
         ));
     }
     // build reaction object and inject it into the backend
-    let reaction = Reaction::create(&user, &group, &pipeline, req, &state.shared).await?;
-    Ok(Json(ReactionIdResponse { id: reaction.id }))
+    let (reaction, deduplicated) =
+        Reaction::create(&user, &group, &pipeline, req, &state.shared).await?;
+    Ok(Json(ReactionIdResponse {
+        id: reaction.id,
+        deduplicated,
+    }))
 }
 
 /// Creates new reactions in bulk
@@ -301,6 +310,46 @@ async fn download_cache_file(
     Ok(body)
 }
 
+/// Downloads all cache files for a reaction as an encrypted zip
+///
+/// This route takes precedence over any cache file that is named `zip`.
+///
+/// # Arguments
+///
+/// * `user` - The user that is downloading these cache files
+/// * `group` - The group this reaction is in
+/// * `id` - The uuid of the reaction to download cache files from
+/// * `params` - The params to use when building this zip
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/reactions/:group/:id/cache/files/zip",
+    params(
+        ("group" = String, Path, description = "The group this reaction is in"),
+        ("id" = Uuid, Path, description = "The uuid of the reaction to download cache files from"),
+        ("params" = ZipDownloadParams, description = "Optional password to encrypt the ZIP download")
+    ),
+    responses(
+        (status = 200, description = "An encrypted zip of this reactions cache files", body = Vec<u8>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::reactions::download_cache_zip", skip_all, err(Debug))]
+async fn download_cache_zip(
+    user: User,
+    Path((group, id)): Path<(String, Uuid)>,
+    params: ZipDownloadParams,
+    State(state): State<AppState>,
+) -> Result<Body, ApiError> {
+    // get the reaction whose cache we are downloading
+    let (_, reaction) = Reaction::get(&user, &group, &id, &state.shared).await?;
+    // zip all of this reactions cache files
+    reaction.download_cache_zip(params, &state.shared).await
+}
+
 /// Handle a command for a reaction
 ///
 /// This can be used to proceed or fail a reaction
@@ -390,6 +439,47 @@ async fn logs(
     Ok(Json(logs))
 }
 
+/// Appends a batch of status logs to a reaction
+///
+/// # Arguments
+///
+/// * `user` - The user that is adding status logs
+/// * `group` - The group this reaction is in
+/// * `id` - The uuid of the reaction to add status logs to
+/// * `state` - Shared Thorium objects
+/// * `updates` - The status updates to append in order
+#[utoipa::path(
+    post,
+    path = "/api/reactions/logs/:group/:id",
+    params(
+        ("group" = String, Path, description = "The group this reaction is in"),
+        ("id" = Uuid, Path, description = "The uuid of the reaction to add status logs to"),
+        ("updates" = Vec<StatusUpdate>, description = "The status updates to append in order")
+    ),
+    responses(
+        (status = 204, description = "Status logs added"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::reactions::add_status_logs", skip_all, err(Debug))]
+async fn add_status_logs(
+    user: User,
+    Path((group, id)): Path<(String, Uuid)>,
+    State(state): State<AppState>,
+    Json(updates): Json<Vec<StatusUpdate>>,
+) -> Result<StatusCode, ApiError> {
+    // get reaction object
+    let (group, reaction) = Reaction::get(&user, &group, &id, &state.shared).await?;
+    // append our status logs
+    reaction
+        .add_status_logs(&user, &group, updates, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Adds new stdout/stderr logs for a specific stage
 ///
 /// # Arguments
@@ -412,6 +502,7 @@ async fn logs(
     responses(
         (status = 204, description = "Stage logs added"),
         (status = 401, description = "This user is not authorized to access this route"),
+        (status = 429, description = "Too many stage logs are being written so these logs were shed and should be retried"),
     ),
     security(
         ("basic" = []),
@@ -515,12 +606,16 @@ async fn list(
 
 /// Lists reactions with details for a specific pipeline
 ///
+/// If the request accepts `application/x-ndjson` then each reaction is streamed as its
+/// own line of JSON and the cursor is returned in the `x-thorium-cursor` header.
+///
 /// # Arguments
 ///
 /// * `user` - The user that is listing reactions
 /// * `group` - The group to list reactions from
 /// * `pipeline` - The pipeline to list reactions from
 /// * `params` - The query params to use for this request
+/// * `headers` - The headers for this request
 /// * `state` - Shared Thorium objects
 #[utoipa::path(
     get,
@@ -531,7 +626,7 @@ async fn list(
         ("params" = ReactionListParams, Query, description = "The query params to use for this request")
     ),
     responses(
-        (status = 200, description = "Reaction details for this pipeline", body = ReactionDetailsList),
+        (status = 200, description = "Reaction details for this pipeline (or a stream of reactions when accepting application/x-ndjson)", body = ReactionDetailsList),
         (status = 401, description = "This user is not authorized to access this route"),
     ),
     security(
@@ -543,16 +638,22 @@ async fn list_details(
     user: User,
     Path((group, pipeline)): Path<(String, String)>,
     Query(params): Query<ReactionListParams>,
+    headers: HeaderMap,
     State(state): State<AppState>,
-) -> Result<Json<ReactionDetailsList>, ApiError> {
+) -> Result<Response, ApiError> {
     // get pipeline data
     let (_, pipeline) = Pipeline::get(&user, &group, &pipeline, &state.shared).await?;
-    // list reactions in a group with details
-    let details = Reaction::list(&pipeline, params.cursor, params.limit, &state.shared)
-        .await?
-        .details(&group, &state.shared)
-        .await?;
-    Ok(Json(details))
+    // list reactions in a group
+    let list = Reaction::list(&pipeline, params.cursor, params.limit, &state.shared).await?;
+    // stream our details if newline delimited json was requested
+    if ndjson::accepts(&headers) {
+        let cursor = list.cursor;
+        let details = list.details_stream(group, state.shared.clone());
+        return Ok(ndjson::stream(details, cursor));
+    }
+    // get the details for these reactions
+    let details = list.details(&group, &state.shared).await?;
+    Ok(Json(details).into_response())
 }
 
 /// Lists reactions for a specific pipeline and status
@@ -737,6 +838,92 @@ async fn list_tag_details(
     Ok(Json(details))
 }
 
+/// Lists reactions with all of the specified tags
+///
+/// # Arguments
+///
+/// * `user` - The user that is listing reactions
+/// * `group` - The group to list reactions from
+/// * `params` - The query params to use for this request
+/// * `state` - Shared Thorium objects
+/// * `tags` - The tags reactions must have
+#[utoipa::path(
+    post,
+    path = "/api/reactions/tag/:group/",
+    params(
+        ("group" = String, Path, description = "The group to list reactions from"),
+        ("params" = ReactionListParams, Query, description = "The query params to use for this request"),
+        ("tags" = HashMap<String, Vec<String>>, description = "The tags reactions must have")
+    ),
+    responses(
+        (status = 200, description = "Reactions with all of the specified tags", body = ReactionList),
+        (status = 400, description = "No tags were specified"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::reactions::list_tags", skip_all, err(Debug))]
+async fn list_tags(
+    user: User,
+    Path(group): Path<String>,
+    Query(params): Query<ReactionListParams>,
+    State(state): State<AppState>,
+    Json(tags): Json<HashMap<String, Vec<String>>>,
+) -> Result<Json<ReactionList>, ApiError> {
+    // get the group these reactions are in
+    let group = Group::get(&user, &group, &state.shared).await?;
+    // list reactions with all of these tags
+    let names =
+        Reaction::list_tags(&group, &tags, params.cursor, params.limit, &state.shared).await?;
+    Ok(Json(names))
+}
+
+/// Lists reaction details with all of the specified tags
+///
+/// # Arguments
+///
+/// * `user` - The user that is listing reactions
+/// * `group` - The group to list reactions from
+/// * `params` - The query params to use for this request
+/// * `state` - Shared Thorium objects
+/// * `tags` - The tags reactions must have
+#[utoipa::path(
+    post,
+    path = "/api/reactions/tag/:group/details/",
+    params(
+        ("group" = String, Path, description = "The group to list reactions from"),
+        ("params" = ReactionListParams, Query, description = "The query params to use for this request"),
+        ("tags" = HashMap<String, Vec<String>>, description = "The tags reactions must have")
+    ),
+    responses(
+        (status = 200, description = "Reaction details with all of the specified tags", body = ReactionDetailsList),
+        (status = 400, description = "No tags were specified"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::reactions::list_tags_details", skip_all, err(Debug))]
+async fn list_tags_details(
+    user: User,
+    Path(group): Path<String>,
+    Query(params): Query<ReactionListParams>,
+    State(state): State<AppState>,
+    Json(tags): Json<HashMap<String, Vec<String>>>,
+) -> Result<Json<ReactionDetailsList>, ApiError> {
+    // get the group these reactions are in
+    let group = Group::get(&user, &group, &state.shared).await?;
+    // list reactions with all of these tags
+    let reactions =
+        Reaction::list_tags(&group, &tags, params.cursor, params.limit, &state.shared).await?;
+    // get details on these reactions
+    let details = reactions.details(&group.name, &state.shared).await?;
+    Ok(Json(details))
+}
+
 /// Lists reactions for a specific group and status
 ///
 /// # Arguments
@@ -1049,6 +1236,48 @@ async fn update(
     Ok(Json(reaction))
 }
 
+/// Adds samples to a reaction that has not finished yet
+///
+/// # Arguments
+///
+/// * `user` - The user that is adding samples to this reaction
+/// * `group` - The group this reaction is in
+/// * `reaction` - The uuid of the reaction to add samples to
+/// * `state` - Shared Thorium objects
+/// * `add` - The samples to add to this reaction
+#[utoipa::path(
+    post,
+    path = "/api/reactions/:group/:id/samples",
+    params(
+        ("group" = String, Path, description = "The group this reaction is in"),
+        ("reaction" = Uuid, Path, description = "The uuid of the reaction to add samples to"),
+        ("add" = ReactionSamplesAdd, description = "The samples to add to this reaction")
+    ),
+    responses(
+        (status = 200, description = "Updated reaction", body = Reaction),
+        (status = 400, description = "This reaction has already finished"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::reactions::add_samples", skip_all, err(Debug))]
+async fn add_samples(
+    user: User,
+    Path((group, reaction)): Path<(String, Uuid)>,
+    State(state): State<AppState>,
+    Json(add): Json<ReactionSamplesAdd>,
+) -> Result<Json<Reaction>, ApiError> {
+    // get the reaction we are adding samples to
+    let (group, reaction) = Reaction::get(&user, &group, &reaction, &state.shared).await?;
+    // add our samples to this reaction
+    let reaction = reaction
+        .add_samples(&user, &group, add, &state.shared)
+        .await?;
+    Ok(Json(reaction))
+}
+
 /// Deletes a reaction
 ///
 /// This will only cancel any currently active pods if this is the only reaction causing that pod
@@ -1131,11 +1360,11 @@ async fn download_ephemeral(
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
-    paths(create, create_bulk, get_reaction, update, delete_reaction, handle, logs, stage_logs, add_stage_logs,
-          list, list_details, list_status, list_status_details, list_tag, list_tag_details, list_group_set,
+    paths(create, create_bulk, get_reaction, update, add_samples, delete_reaction, handle, logs, add_status_logs, stage_logs, add_stage_logs,
+          list, list_details, list_status, list_status_details, list_tag, list_tag_details, list_tags, list_tags_details, list_group_set,
           list_group_set_details, list_sub, list_sub_details, list_sub_status_details, list_sub_status,
-          download_ephemeral),
-    components(schemas(Actions, BulkReactionResponse, CommitishKinds, HandleReactionResponse, ImageScaler, JobResetRequestor, Reaction, ReactionIdResponse, ReactionList, ReactionDetailsList, ReactionListParams, ReactionRequest, ReactionStatus, ReactionUpdate, RepoDependency, RepoDependencyRequest, StageLogs, StageLogsAdd, StageLogLine, StatusUpdate, SystemComponents, ReactionCache, ReactionCacheUpdate)),
+          download_ephemeral, download_cache_zip),
+    components(schemas(Actions, BulkReactionResponse, CommitishKinds, HandleReactionResponse, ImageScaler, JobResetRequestor, Reaction, ReactionIdResponse, ReactionList, ReactionDetailsList, ReactionListParams, ReactionRequest, ReactionSamplesAdd, ReactionStatus, ReactionUpdate, RepoDependency, RepoDependencyRequest, StageLogs, StageLogsAdd, StageLogLine, StatusUpdate, SystemComponents, ReactionCache, ReactionCacheUpdate, ZipDownloadParams)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct ReactionApiDocs;
@@ -1151,7 +1380,9 @@ async fn openapi() -> Json<utoipa::openapi::OpenApi> {
 /// # Arguments
 ///
 // * `router` - The router to add routes too
-pub fn mount(router: Router<AppState>) -> Router<AppState> {
+// * `conf` - The Thorium config
+// * `state` - Shared Thorium objects
+pub fn mount(router: Router<AppState>, conf: &Conf, state: &AppState) -> Router<AppState> {
     router
         .route("/reactions/", post(create))
         .route("/reactions/bulk/", post(create_bulk))
@@ -1160,23 +1391,36 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
             "/reactions/{group}/{id}",
             get(get_reaction).patch(update).delete(delete_reaction),
         )
+        .route("/reactions/{group}/{id}/samples", post(add_samples))
         .route(
             "/reactions/{group}/{id}/cache",
             get(get_cache).patch(update_cache),
         )
         .route(
             "/reactions/{group}/{id}/cache/files/",
-            patch(update_cache_files),
+            patch(update_cache_files).layer(limits::upload(conf)),
+        )
+        .route(
+            "/reactions/{group}/{id}/cache/files/zip",
+            get(download_cache_zip),
         )
         .route(
             "/reactions/{group}/{id}/cache/files/{*path}",
             get(download_cache_file),
         )
         .route("/reactions/handle/{group}/{id}/{cmd}", post(handle))
-        .route("/reactions/logs/{group}/{id}", get(logs))
+        .route(
+            "/reactions/logs/{group}/{id}",
+            get(logs).post(add_status_logs),
+        )
         .route(
             "/reactions/logs/{group}/{id}/{stage}",
-            get(stage_logs).post(add_stage_logs),
+            get(stage_logs).merge(post(add_stage_logs).layer(
+                axum::middleware::from_fn_with_state(
+                    state.shared.stage_logs.clone(),
+                    backpressure::limit,
+                ),
+            )),
         )
         .route("/reactions/list/{group}/{pipeline}/", get(list))
         .route(
@@ -1191,6 +1435,8 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
             "/reactions/status/{group}/{pipeline}/{status}/details/",
             get(list_status_details),
         )
+        .route("/reactions/tag/{group}/", post(list_tags))
+        .route("/reactions/tag/{group}/details/", post(list_tags_details))
         .route("/reactions/tag/{group}/{tag}/", get(list_tag))
         .route(
             "/reactions/tag/{group}/{tag}/details/",
@@ -27,6 +27,8 @@ use utoipa::OpenApi;
 */
 
 use super::OpenApiSecurity;
+use super::shared::limits;
+use crate::Conf;
 use crate::models::backends::TagSupport;
 use crate::models::{
     ApiCursor, Branch, BranchDetails, BranchRequest, Commit, CommitDetails, CommitRequest,
@@ -612,11 +614,15 @@ async fn openapi() -> Json<utoipa::openapi::OpenApi> {
 /// # Arguments
 ///
 /// * `router` - The router to add routes too
-pub fn mount(router: Router<AppState>) -> Router<AppState> {
+/// * `conf` - The Thorium config
+pub fn mount(router: Router<AppState>, conf: &Conf) -> Router<AppState> {
     router
         .route("/repos/", get(list).post(create))
         .route("/repos/details/", get(list_details))
-        .route("/repos/data/{*repo_path}", get(get_repo).post(upload))
+        .route(
+            "/repos/data/{*repo_path}",
+            get(get_repo).post(upload).layer(limits::upload(conf)),
+        )
         .route(
             "/repos/commitishes/{data}/{*repo_path}",
             get(commitishes).post(update_commitishes),
@@ -629,7 +635,9 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
         .route("/repos/tags/{*repo_path}", post(tag).delete(delete_tags))
         .route(
             "/repos/results/{*repo_path}",
-            get(get_results).post(upload_results),
+            get(get_results)
+                .post(upload_results)
+                .layer(limits::upload(conf)),
         )
         .route(
             "/repos/result-files/{*repo_path}",
@@ -1,4 +1,5 @@
 //! Contains generic functions defining shared behavior between routes
 
 pub mod graphics;
+pub mod limits;
 pub mod notifications;
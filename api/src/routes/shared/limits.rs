@@ -0,0 +1,20 @@
+//! Contains the request body limits routes are served with
+
+use axum::extract::DefaultBodyLimit;
+
+use crate::Conf;
+
+/// Get the body limit for routes that upload files
+///
+/// This replaces the json body limit that is applied to all other routes. Uploads
+/// are unlimited unless an upload limit is configured.
+///
+/// # Arguments
+///
+/// * `conf` - The Thorium config
+pub fn upload(conf: &Conf) -> DefaultBodyLimit {
+    match conf.thorium.request_size_limits.data_bytes() {
+        Some(limit) => DefaultBodyLimit::max(limit),
+        None => DefaultBodyLimit::disable(),
+    }
+}
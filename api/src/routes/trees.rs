@@ -1,12 +1,15 @@
 //! The route for building a relationship tree out of data in Thorium
 
 use axum::Router;
-use axum::extract::{Json, Path, State};
-use axum::routing::{patch, post};
+use axum::extract::{Json, Path, Query, State};
+use axum::routing::{get, patch, post};
 use tracing::instrument;
 use uuid::Uuid;
 
-use crate::models::{Tree, TreeGrowQuery, TreeParams, TreeQuery, User};
+use crate::models::{
+    LineageParams, LineageTree, ReactionTree, ReactionTreeParams, Tree, TreeGrowQuery, TreeParams,
+    TreeQuery, User,
+};
 use crate::utils::{ApiError, AppState};
 
 /// Start building a tree of data in Thorium from some starting points
@@ -101,6 +104,80 @@ async fn grow_tree(
     Ok(Json(tree))
 }
 
+/// Get a reaction and all of its sub reactions as a nested tree
+///
+/// # Arguments
+///
+/// * `user` - The user that is building a reaction tree
+/// * `group` - The group the root reaction is in
+/// * `id` - The id of the root reaction
+/// * `params` - The params for building this reaction tree
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/trees/reactions/:group/:id",
+    params(
+        ("group" = String, Path, description = "The group the root reaction is in"),
+        ("id" = Uuid, Path, description = "The id of the root reaction"),
+        ("params" = ReactionTreeParams, Query, description = "The params for building this reaction tree")
+    ),
+    responses(
+        (status = 200, description = "A reaction and all of its sub reactions", body = ReactionTree),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The root reaction does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::trees::reaction_tree", skip_all, err(Debug))]
+async fn reaction_tree(
+    user: User,
+    Path((group, id)): Path<(String, Uuid)>,
+    Query(params): Query<ReactionTreeParams>,
+    State(state): State<AppState>,
+) -> Result<Json<ReactionTree>, ApiError> {
+    // build the tree of sub reactions for this reaction
+    let tree = ReactionTree::build(&user, &group, &id, &params, &state.shared).await?;
+    Ok(Json(tree))
+}
+
+/// Get the lineage of reactions and children for a sample
+///
+/// # Arguments
+///
+/// * `user` - The user that is building a lineage
+/// * `sha256` - The sha256 of the root sample
+/// * `params` - The params for building this lineage
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/trees/lineage/:sha256",
+    params(
+        ("sha256" = String, Path, description = "The sha256 of the root sample"),
+        ("params" = LineageParams, Query, description = "The params for building this lineage")
+    ),
+    responses(
+        (status = 200, description = "A sample, its reactions, and the lineage of its children", body = LineageTree),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The root sample does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::trees::lineage", skip_all, err(Debug))]
+async fn lineage(
+    user: User,
+    Path(sha256): Path<String>,
+    Query(params): Query<LineageParams>,
+    State(state): State<AppState>,
+) -> Result<Json<LineageTree>, ApiError> {
+    // build the lineage for this sample
+    let lineage = LineageTree::build(&user, &sha256, &params, &state.shared).await?;
+    Ok(Json(lineage))
+}
+
 /// Add the tree routes to our router
 ///
 /// # Arguments
@@ -110,4 +187,6 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
     router
         .route("/trees/", post(start_tree))
         .route("/trees/{cursor}", patch(grow_tree))
+        .route("/trees/reactions/{group}/{id}", get(reaction_tree))
+        .route("/trees/lineage/{sha256}", get(lineage))
 }
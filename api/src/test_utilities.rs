@@ -1,11 +1,18 @@
 //! Utilties for testing the Thorium API
 
+mod allocator;
 mod api;
+pub mod fixtures;
 pub mod generators;
+#[cfg(feature = "test-harness")]
+pub mod harness;
 mod helpers;
 mod impls;
+mod namespaces;
 
+pub use allocator::CountingAllocator;
 pub use api::{CONF, admin_client};
+pub use namespaces::{assert_namespaced, foreign_redis_keys};
 
 // expose a blocking admin client for sync tests
 #[cfg(all(feature = "sync", not(feature = "python")))]
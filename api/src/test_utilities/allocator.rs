@@ -0,0 +1,36 @@
+//! An allocator that counts allocations for benchmarks
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of allocations made so far
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// An allocator that counts how many allocations have been made
+///
+/// This only counts allocations when it is set as the `#[global_allocator]`.
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    /// Get the number of allocations made so far
+    #[must_use]
+    pub fn allocations() -> usize {
+        ALLOCATIONS.load(Ordering::Relaxed)
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
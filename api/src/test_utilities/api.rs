@@ -222,7 +222,7 @@ async fn init_bucket(
 /// # Arguments
 ///
 /// * `s3_conf` - The S3 configuration from the main Thorium configuration to use
-async fn init_s3_buckets(s3_conf: &S3, conf: &Conf) -> Result<(), Error> {
+pub(super) async fn init_s3_buckets(s3_conf: &S3, conf: &Conf) -> Result<(), Error> {
     // get our s3 credentials
     let creds = aws_credential_types::Credentials::new(
         &s3_conf.access_key,
@@ -0,0 +1,599 @@
+//! Builders for realistic Thorium objects to use as test fixtures
+//!
+//! Unlike the generators these do not talk to the API and only fill in sensible
+//! static defaults so tests can override just the fields they care about.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::models::{
+    ChildFilters, CommitishKinds, Dependencies, Entity, EntityKinds, EntityMetadata, GenericJob,
+    GenericJobArgs, Image, ImageArgs, ImageScaler, ImageVersion, JobStatus, OutputCollection,
+    OutputDisplayType, Reaction, ReactionRequest, ReactionStatus, RepoDependency, Resources,
+    SecurityContext, SpawnLimits,
+};
+
+/// The creator to use for fixtures
+const CREATOR: &str = "mcarson";
+
+/// Builds a [`Reaction`] fixture
+#[derive(Debug, Clone)]
+pub struct ReactionBuilder {
+    /// The reaction being built
+    reaction: Reaction,
+}
+
+impl ReactionBuilder {
+    /// Start building a reaction that has not started yet
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this reaction is in
+    /// * `pipeline` - The pipeline this reaction is for
+    #[must_use]
+    pub fn new<T: Into<String>>(group: T, pipeline: T) -> Self {
+        let reaction = Reaction {
+            id: Uuid::new_v4(),
+            group: group.into(),
+            creator: CREATOR.to_owned(),
+            pipeline: pipeline.into(),
+            status: ReactionStatus::Created,
+            current_stage: 0,
+            current_stage_progress: 0,
+            current_stage_length: 1,
+            args: HashMap::default(),
+            sla: Utc::now() + Duration::weeks(1),
+            jobs: Vec::default(),
+            tags: Vec::default(),
+            parent: None,
+            sub_reactions: 0,
+            completed_sub_reactions: 0,
+            generators: Vec::default(),
+            samples: Vec::default(),
+            ephemeral: Vec::default(),
+            parent_ephemeral: HashMap::default(),
+            repos: Vec::default(),
+            trigger_depth: None,
+            has_cache: false,
+            idempotency_key: None,
+            priority: 0,
+        };
+        ReactionBuilder { reaction }
+    }
+
+    /// Set the user that created this reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `creator` - The creator to set
+    #[must_use]
+    pub fn creator<T: Into<String>>(mut self, creator: T) -> Self {
+        self.reaction.creator = creator.into();
+        self
+    }
+
+    /// Set the args for a specific image in this reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image these args are for
+    /// * `args` - The args to set
+    #[must_use]
+    pub fn args<T: Into<String>>(mut self, image: T, args: GenericJobArgs) -> Self {
+        self.reaction.args.insert(image.into(), args);
+        self
+    }
+
+    /// Set the status of this reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status to set
+    #[must_use]
+    pub fn status(mut self, status: ReactionStatus) -> Self {
+        self.reaction.status = status;
+        self
+    }
+
+    /// Set the stage this reaction is currently on
+    ///
+    /// # Arguments
+    ///
+    /// * `stage` - The index of the current stage
+    /// * `length` - The number of jobs in the current stage
+    #[must_use]
+    pub fn stage(mut self, stage: u64, length: u64) -> Self {
+        self.reaction.current_stage = stage;
+        self.reaction.current_stage_length = length;
+        self
+    }
+
+    /// Set the sla for this reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `sla` - The time this reaction should be completed by
+    #[must_use]
+    pub fn sla(mut self, sla: DateTime<Utc>) -> Self {
+        self.reaction.sla = sla;
+        self
+    }
+
+    /// Add a tag to this reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The tag to add
+    #[must_use]
+    pub fn tag<T: Into<String>>(mut self, tag: T) -> Self {
+        self.reaction.tags.push(tag.into());
+        self
+    }
+
+    /// Add a sample to this reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the sample to add
+    #[must_use]
+    pub fn sample<T: Into<String>>(mut self, sha256: T) -> Self {
+        self.reaction.samples.push(sha256.into());
+        self
+    }
+
+    /// Set the parent of this reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The id of the parent reaction
+    #[must_use]
+    pub fn parent(mut self, parent: Uuid) -> Self {
+        self.reaction.parent = Some(parent);
+        self
+    }
+
+    /// Set the trigger depth of this reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - The trigger depth to set
+    #[must_use]
+    pub fn trigger_depth(mut self, depth: u8) -> Self {
+        self.reaction.trigger_depth = Some(depth);
+        self
+    }
+
+    /// Set the priority of this reaction within its group
+    ///
+    /// # Arguments
+    ///
+    /// * `priority` - The priority to set
+    #[must_use]
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.reaction.priority = priority;
+        self
+    }
+
+    /// Build the request that would create this reaction
+    ///
+    /// Only the fields that can be set when creating a reaction are carried over.
+    #[must_use]
+    pub fn request(&self) -> ReactionRequest {
+        // get the number of seconds until our sla
+        let sla = (self.reaction.sla - Utc::now()).num_seconds().max(1);
+        let mut req = ReactionRequest::new(&self.reaction.group, &self.reaction.pipeline)
+            .sla(sla.unsigned_abs())
+            .tags(self.reaction.tags.clone())
+            .samples(self.reaction.samples.clone())
+            .priority(self.reaction.priority);
+        req.args.clone_from(&self.reaction.args);
+        req.parent = self.reaction.parent;
+        req.trigger_depth = self.reaction.trigger_depth;
+        req
+    }
+
+    /// Build this reaction
+    #[must_use]
+    pub fn build(self) -> Reaction {
+        self.reaction
+    }
+}
+
+/// Builds a [`GenericJob`] fixture
+#[derive(Debug, Clone)]
+pub struct JobBuilder {
+    /// The job being built
+    job: GenericJob,
+}
+
+impl Default for JobBuilder {
+    /// Start building a running job with samples, ephemeral files, and repos
+    fn default() -> Self {
+        let job = GenericJob {
+            reaction: Uuid::new_v4(),
+            id: Uuid::new_v4(),
+            group: "TestGroup".to_owned(),
+            pipeline: "TestPipeline".to_owned(),
+            stage: "TestStage".to_owned(),
+            creator: CREATOR.to_owned(),
+            args: GenericJobArgs::default(),
+            status: JobStatus::Running,
+            deadline: Utc::now(),
+            started: None,
+            execution_deadline: None,
+            parent: None,
+            generator: false,
+            samples: vec!["sample1".to_owned(), "sample2".to_owned()],
+            ephemeral: vec!["file.txt".to_owned(), "other.txt".to_owned()],
+            parent_ephemeral: HashMap::default(),
+            repos: vec![
+                RepoDependency {
+                    url: "github.com/curl/curl".to_owned(),
+                    commitish: Some("main".to_owned()),
+                    kind: Some(CommitishKinds::Branch),
+                },
+                RepoDependency {
+                    url: "github.com/notcurl/notcurl".to_owned(),
+                    commitish: Some("main".to_owned()),
+                    kind: Some(CommitishKinds::Branch),
+                },
+            ],
+            trigger_depth: None,
+        };
+        JobBuilder { job }
+    }
+}
+
+impl JobBuilder {
+    /// Start building a job for a stage in a reaction
+    ///
+    /// The job inherits its group, pipeline, creator, args, and dependencies from the
+    /// reaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `reaction` - The reaction this job is for
+    /// * `stage` - The stage/image this job is for
+    #[must_use]
+    pub fn for_reaction<T: Into<String>>(reaction: &Reaction, stage: T) -> Self {
+        let stage = stage.into();
+        let job = GenericJob {
+            reaction: reaction.id,
+            group: reaction.group.clone(),
+            pipeline: reaction.pipeline.clone(),
+            creator: reaction.creator.clone(),
+            args: reaction.args.get(&stage).cloned().unwrap_or_default(),
+            deadline: reaction.sla,
+            parent: reaction.parent,
+            samples: reaction.samples.clone(),
+            ephemeral: reaction.ephemeral.clone(),
+            parent_ephemeral: reaction.parent_ephemeral.clone(),
+            repos: reaction.repos.clone(),
+            trigger_depth: reaction.trigger_depth,
+            stage,
+            ..Self::default().job
+        };
+        JobBuilder { job }
+    }
+
+    /// Set the stage/image this job is for
+    ///
+    /// # Arguments
+    ///
+    /// * `stage` - The stage to set
+    #[must_use]
+    pub fn stage<T: Into<String>>(mut self, stage: T) -> Self {
+        self.job.stage = stage.into();
+        self
+    }
+
+    /// Set the args for this job
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The args to set
+    #[must_use]
+    pub fn args(mut self, args: GenericJobArgs) -> Self {
+        self.job.args = args;
+        self
+    }
+
+    /// Set the status of this job
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status to set
+    #[must_use]
+    pub fn status(mut self, status: JobStatus) -> Self {
+        self.job.status = status;
+        self
+    }
+
+    /// Set whether this job is for a generator or not
+    ///
+    /// # Arguments
+    ///
+    /// * `generator` - Whether this job is for a generator
+    #[must_use]
+    pub fn generator(mut self, generator: bool) -> Self {
+        self.job.generator = generator;
+        self
+    }
+
+    /// Set the samples this job depends on
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The sha256s of the samples to set
+    #[must_use]
+    pub fn samples(mut self, samples: Vec<String>) -> Self {
+        self.job.samples = samples;
+        self
+    }
+
+    /// Set the ephemeral files this job depends on
+    ///
+    /// # Arguments
+    ///
+    /// * `ephemeral` - The names of the ephemeral files to set
+    #[must_use]
+    pub fn ephemeral(mut self, ephemeral: Vec<String>) -> Self {
+        self.job.ephemeral = ephemeral;
+        self
+    }
+
+    /// Set the repos this job depends on
+    ///
+    /// # Arguments
+    ///
+    /// * `repos` - The repos to set
+    #[must_use]
+    pub fn repos(mut self, repos: Vec<RepoDependency>) -> Self {
+        self.job.repos = repos;
+        self
+    }
+
+    /// Build this job
+    #[must_use]
+    pub fn build(self) -> GenericJob {
+        self.job
+    }
+}
+
+/// Builds an [`Image`] fixture
+#[derive(Debug, Clone)]
+pub struct ImageBuilder {
+    /// The image being built
+    image: Image,
+}
+
+impl Default for ImageBuilder {
+    /// Start building a K8s image in the test group
+    fn default() -> Self {
+        Self::new("TestGroup", "TestImage")
+    }
+}
+
+impl ImageBuilder {
+    /// Start building a K8s image
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this image is in
+    /// * `name` - The name of this image
+    #[must_use]
+    pub fn new<T: Into<String>>(group: T, name: T) -> Self {
+        let image = Image {
+            group: group.into(),
+            name: name.into(),
+            version: Some(ImageVersion::SemVer(semver::Version::new(1, 0, 0))),
+            creator: CREATOR.to_owned(),
+            image: Some("alpine:latest".to_owned()),
+            scaler: ImageScaler::K8s,
+            lifetime: None,
+            timeout: None,
+            resources: Resources::default(),
+            spawn_limit: SpawnLimits::Unlimited,
+            env: HashMap::default(),
+            args: ImageArgs::default(),
+            runtime: 600.0,
+            volumes: Vec::default(),
+            modifiers: None,
+            description: None,
+            security_context: SecurityContext::default(),
+            used_by: Vec::default(),
+            collect_logs: true,
+            generator: false,
+            dependencies: Dependencies::default(),
+            display_type: OutputDisplayType::default(),
+            output_collection: OutputCollection::default(),
+            child_filters: ChildFilters::default(),
+            clean_up: None,
+            kvm: None,
+            bans: HashMap::default(),
+            network_policies: HashSet::default(),
+        };
+        ImageBuilder { image }
+    }
+
+    /// Set the scaler this image is spawned by
+    ///
+    /// # Arguments
+    ///
+    /// * `scaler` - The scaler to set
+    #[must_use]
+    pub fn scaler(mut self, scaler: ImageScaler) -> Self {
+        self.image.scaler = scaler;
+        self
+    }
+
+    /// Set the args for this image
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The args to set
+    #[must_use]
+    pub fn args(mut self, args: ImageArgs) -> Self {
+        self.image.args = args;
+        self
+    }
+
+    /// Set the dependencies for this image
+    ///
+    /// # Arguments
+    ///
+    /// * `dependencies` - The dependencies to set
+    #[must_use]
+    pub fn dependencies(mut self, dependencies: Dependencies) -> Self {
+        self.image.dependencies = dependencies;
+        self
+    }
+
+    /// Set the resources this image requires
+    ///
+    /// # Arguments
+    ///
+    /// * `resources` - The resources to set
+    #[must_use]
+    pub fn resources(mut self, resources: Resources) -> Self {
+        self.image.resources = resources;
+        self
+    }
+
+    /// Set an environment variable for this image
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the environment variable
+    /// * `value` - The value to set or None to unset it
+    #[must_use]
+    pub fn env<T: Into<String>>(mut self, key: T, value: Option<T>) -> Self {
+        self.image.env.insert(key.into(), value.map(Into::into));
+        self
+    }
+
+    /// Set whether this image is a generator or not
+    ///
+    /// # Arguments
+    ///
+    /// * `generator` - Whether this image is a generator
+    #[must_use]
+    pub fn generator(mut self, generator: bool) -> Self {
+        self.image.generator = generator;
+        self
+    }
+
+    /// Set how results from this image are collected
+    ///
+    /// # Arguments
+    ///
+    /// * `output_collection` - The output collection settings to set
+    #[must_use]
+    pub fn output_collection(mut self, output_collection: OutputCollection) -> Self {
+        self.image.output_collection = output_collection;
+        self
+    }
+
+    /// Build this image
+    #[must_use]
+    pub fn build(self) -> Image {
+        self.image
+    }
+}
+
+/// Builds an [`Entity`] fixture
+#[derive(Debug, Clone)]
+pub struct EntityBuilder {
+    /// The entity being built
+    entity: Entity,
+}
+
+impl EntityBuilder {
+    /// Start building an entity of the `Other` kind
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of this entity
+    #[must_use]
+    pub fn new<T: Into<String>>(name: T) -> Self {
+        let entity = Entity {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            kind: EntityKinds::Other,
+            metadata: EntityMetadata::Other,
+            description: None,
+            submitter: CREATOR.to_owned(),
+            groups: vec!["TestGroup".to_owned()],
+            tags: HashMap::default(),
+            image: None,
+            created: Utc::now(),
+            version: 0,
+            archived_at: None,
+        };
+        EntityBuilder { entity }
+    }
+
+    /// Set the kind specific metadata for this entity
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - The metadata to set
+    #[must_use]
+    pub fn metadata(mut self, metadata: EntityMetadata) -> Self {
+        // keep our kind in sync with our metadata
+        self.entity.kind = EntityKinds::from(&metadata);
+        self.entity.metadata = metadata;
+        self
+    }
+
+    /// Set the description for this entity
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - The description to set
+    #[must_use]
+    pub fn description<T: Into<String>>(mut self, description: T) -> Self {
+        self.entity.description = Some(description.into());
+        self
+    }
+
+    /// Set the groups this entity is in
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The groups to set
+    #[must_use]
+    pub fn groups(mut self, groups: Vec<String>) -> Self {
+        self.entity.groups = groups;
+        self
+    }
+
+    /// Add a tag to this entity in all of its groups
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the tag to add
+    /// * `value` - The value of the tag to add
+    #[must_use]
+    pub fn tag<T: Into<String>>(mut self, key: T, value: T) -> Self {
+        let groups = self
+            .entity
+            .groups
+            .iter()
+            .cloned()
+            .collect::<HashSet<String>>();
+        self.entity
+            .tags
+            .entry(key.into())
+            .or_default()
+            .insert(value.into(), groups);
+        self
+    }
+
+    /// Build this entity
+    #[must_use]
+    pub fn build(self) -> Entity {
+        self.entity
+    }
+}
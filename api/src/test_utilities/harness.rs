@@ -0,0 +1,196 @@
+//! A harness that runs the API against ephemeral backend containers
+//!
+//! This starts throwaway Redis, Scylla, and S3 (MinIO) containers so route tests can
+//! run without any manually deployed infrastructure. Elastic is not started so any
+//! search routes will fail when using this harness.
+
+use std::sync::Arc;
+use testcontainers_modules::minio::MinIO;
+use testcontainers_modules::redis::Redis;
+use testcontainers_modules::scylladb::ScyllaDB;
+use testcontainers_modules::testcontainers::core::IntoContainerPort;
+use testcontainers_modules::testcontainers::runners::AsyncRunner;
+use testcontainers_modules::testcontainers::{ContainerAsync, Image};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::api::{CONF, init_s3_buckets};
+use crate::client::ClientSettings;
+use crate::utils::{AppState, Shared};
+use crate::{Conf, Error, Thorium};
+
+/// The username of the admin the harness bootstraps
+const ADMIN: &str = "thorium";
+
+/// Get the address a container port is exposed at on our host
+///
+/// # Arguments
+///
+/// * `container` - The container to get an address for
+/// * `port` - The port within the container
+async fn exposed<I: Image>(container: &ContainerAsync<I>, port: u16) -> Result<String, Error> {
+    let host = container
+        .get_host()
+        .await
+        .map_err(|err| Error::new(format!("Failed to get container host: {err}")))?;
+    let port = container
+        .get_host_port_ipv4(port.tcp())
+        .await
+        .map_err(|err| Error::new(format!("Failed to get container port {port}: {err}")))?;
+    Ok(format!("{host}:{port}"))
+}
+
+/// Start a container for a backend
+///
+/// # Arguments
+///
+/// * `image` - The image to start
+/// * `name` - The name of the backend this container is for
+async fn start<I: Image>(image: I, name: &str) -> Result<ContainerAsync<I>, Error> {
+    image
+        .start()
+        .await
+        .map_err(|err| Error::new(format!("Failed to start {name} container: {err}")))
+}
+
+/// A Thorium API backed by ephemeral containers
+///
+/// The containers are removed when this is dropped or stopped.
+pub struct EphemeralBackend {
+    /// The config the API is using
+    pub config: Conf,
+    /// The state shared by all routes in the API
+    pub state: AppState,
+    /// The url the API is being served at
+    pub host: String,
+    /// The Redis container
+    redis: ContainerAsync<Redis>,
+    /// The Scylla container
+    scylla: ContainerAsync<ScyllaDB>,
+    /// The S3 container
+    s3: ContainerAsync<MinIO>,
+    /// The task serving the API
+    server: JoinHandle<()>,
+}
+
+impl EphemeralBackend {
+    /// Start ephemeral backends and serve the API on a random local port
+    ///
+    /// The testing config is used as a template with its backends replaced by the
+    /// ephemeral containers and a unique namespace.
+    pub async fn start() -> Result<Self, Error> {
+        Self::start_with(|_| {}).await
+    }
+
+    /// Start ephemeral backends and serve the API with a customized config
+    ///
+    /// # Arguments
+    ///
+    /// * `customize` - Changes to make to the testing config before the API is started
+    pub async fn start_with<F: FnOnce(&mut Conf)>(customize: F) -> Result<Self, Error> {
+        // start all of our backends at once
+        let (redis, scylla, s3) = tokio::try_join!(
+            start(Redis::default(), "Redis"),
+            start(ScyllaDB::default(), "Scylla"),
+            start(MinIO::default(), "S3"),
+        )?;
+        // bind to a random port to serve the API on
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|err| Error::new(format!("Failed to bind harness listener: {err}")))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|err| Error::new(format!("Failed to get harness address: {err}")))?;
+        // point our testing config at our containers
+        let mut config = CONF.clone();
+        config.thorium.namespace = format!("harness_{}", Uuid::new_v4().simple());
+        config.thorium.interface = addr.ip().to_string();
+        config.thorium.port = addr.port();
+        let redis_addr = exposed(&redis, 6379).await?;
+        let (redis_host, redis_port) = redis_addr.rsplit_once(':').unwrap_or_default();
+        config.redis.host = redis_host.to_owned();
+        config.redis.port = redis_port
+            .parse()
+            .map_err(|err| Error::new(format!("Invalid Redis port '{redis_port}': {err}")))?;
+        config.redis.username = None;
+        config.redis.password = None;
+        config.scylla.nodes = vec![exposed(&scylla, 9042).await?];
+        config.scylla.replication = 1;
+        config.scylla.auth = None;
+        config.thorium.s3.endpoint = format!("http://{}", exposed(&s3, 9000).await?);
+        config.thorium.s3.access_key = "minioadmin".to_owned();
+        config.thorium.s3.secret_token = "minioadmin".to_owned();
+        config.thorium.s3.use_path_style = true;
+        // apply any test specific settings
+        customize(&mut config);
+        // create the buckets our routes need
+        init_s3_buckets(&config.thorium.s3, &config).await?;
+        // connect to our backends and make sure our system settings are setup
+        let shared = Box::pin(Shared::new(config.clone())).await;
+        let state = AppState::new(shared);
+        crate::initial_settings_consistency_scan(
+            state.shared.clone(),
+            config.thorium.tracing.local.level,
+        )
+        .await
+        .map_err(|err| Error::new(format!("Failed to setup system settings: {err}")))?;
+        // make sure our config matches our containers before serving requests
+        crate::models::backends::setup::self_check(&state.shared)
+            .await
+            .map_err(|err| Error::new(format!("Harness failed its self-check: {err}")))?;
+        // serve our API in the background
+        let (app, _) = crate::build_app(state.clone(), &config);
+        let server = tokio::spawn(async move {
+            // this only returns if the listener fails
+            let _ = axum::serve(listener, app).await;
+        });
+        Ok(EphemeralBackend {
+            config,
+            state,
+            host: format!("http://{addr}"),
+            redis,
+            scylla,
+            s3,
+            server,
+        })
+    }
+
+    /// Get the objects shared by all routes in the API
+    #[must_use]
+    pub fn shared(&self) -> &Arc<Shared> {
+        &self.state.shared
+    }
+
+    /// Bootstrap an admin and get a client for it
+    ///
+    /// This can only be called once per backend since the admin can only be bootstrapped once.
+    pub async fn admin_client(&self) -> Result<Thorium, Error> {
+        let settings = ClientSettings::default();
+        // bootstrap our admin user
+        let resp = Thorium::bootstrap(
+            &self.host,
+            ADMIN,
+            "fake@fake.gov",
+            "password",
+            &self.config.thorium.secret_key,
+            &settings,
+        )
+        .await?;
+        // build our admin client
+        let client = Thorium::build(&self.host)
+            .token(&resp.token)
+            .build()
+            .await?;
+        // make sure Thorium is initialized
+        client.system.init().await?;
+        Ok(client)
+    }
+
+    /// Stop the API and remove all of our containers
+    pub async fn stop(self) {
+        // stop serving our API
+        self.server.abort();
+        // remove our containers ignoring any that are already gone
+        let _ = tokio::join!(self.redis.rm(), self.scylla.rm(), self.s3.rm());
+    }
+}
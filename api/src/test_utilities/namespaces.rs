@@ -0,0 +1,41 @@
+//! Utilities for verifying that tests only touch data in their own namespace
+
+use super::api::{CONF, get_redis_client};
+use crate::Error;
+use crate::models::backends::db::namespaces;
+
+/// Panic if a Redis pipeline touches any keys outside of the namespace under test
+///
+/// # Arguments
+///
+/// * `pipe` - The pipeline to check
+pub fn assert_namespaced(pipe: &redis::Pipeline) {
+    if let Err(msg) = namespaces::check_pipe(pipe, &CONF.thorium.namespace) {
+        panic!("Cross namespace Redis access: {msg}");
+    }
+}
+
+/// Get any keys in Redis that are outside of the namespace under test
+///
+/// Redis is wiped before tests start so any keys outside of our namespace were
+/// written by a query that did not build its key with our namespace.
+pub async fn foreign_redis_keys() -> Result<Vec<String>, Error> {
+    // connect to redis
+    let redis = get_redis_client(&CONF).await;
+    let mut conn = redis
+        .get()
+        .await
+        .map_err(|err| Error::new(format!("Error getting Redis connection from pool: {err}")))?;
+    // get all keys in redis
+    let keys: Vec<String> = redis::cmd("KEYS")
+        .arg("*")
+        .query_async(&mut *conn)
+        .await
+        .map_err(|err| Error::new(format!("Error listing keys in Redis: {err}")))?;
+    // only keep the keys that are not in our namespace
+    let prefix = format!("{}:", CONF.thorium.namespace);
+    Ok(keys
+        .into_iter()
+        .filter(|key| !key.starts_with(&prefix))
+        .collect())
+}
@@ -0,0 +1,249 @@
+//! Bounds how much data can be in flight while ingesting stage logs
+//!
+//! Agents can push logs faster than Scylla can write them. Instead of letting
+//! pending writes pile up in memory we cap the number of bytes being written at
+//! once and shed requests that can't get room quickly with a 429 so agents back
+//! off and retry. Room is reserved based on a request's `Content-Length` before
+//! its body is read so shed requests never have their bodies buffered.
+
+use axum::extract::{Request, State};
+use axum::http::header::CONTENT_LENGTH;
+use axum::middleware::Next;
+use axum::response::Response;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use super::ApiError;
+use crate::too_many_requests;
+
+/// Limits the number of bytes of logs that can be written at once
+#[derive(Debug)]
+pub struct LogLimiter {
+    /// The permits for each byte of logs that can be in flight
+    permits: Semaphore,
+    /// The max number of bytes of logs that can be in flight
+    capacity: u32,
+    /// How long to wait for room before shedding a request
+    max_wait: Duration,
+}
+
+impl LogLimiter {
+    /// Create a new log limiter
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The max number of bytes of logs that can be in flight
+    /// * `max_wait` - How long to wait for room before shedding a request
+    #[must_use]
+    pub fn new(capacity: u32, max_wait: Duration) -> Self {
+        // always allow at least a single byte so requests can make progress
+        let capacity = capacity.max(1);
+        LogLimiter {
+            permits: Semaphore::new(capacity as usize),
+            capacity,
+            max_wait,
+        }
+    }
+
+    /// Reserve room for some logs, waiting for other writes to finish if needed
+    ///
+    /// The room is released when the returned permit is dropped. Requests larger
+    /// than our capacity wait for all in flight logs to be written instead of
+    /// being rejected outright.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The number of bytes of logs to reserve room for
+    pub async fn acquire(&self, bytes: usize) -> Result<SemaphorePermit<'_>, ApiError> {
+        // never ask for more permits than we could ever have
+        let needed = u32::try_from(bytes).unwrap_or(u32::MAX).min(self.capacity);
+        // wait for room to free up but shed this request if it takes too long
+        match tokio::time::timeout(self.max_wait, self.permits.acquire_many(needed)).await {
+            Ok(Ok(permit)) => Ok(permit),
+            // we never close our semaphore but shed this request if we ever do
+            Ok(Err(_)) | Err(_) => Err(too_many_requests!(format!(
+                "Stage log ingestion is backed up, retry in {} seconds",
+                self.max_wait.as_secs().max(1)
+            ))),
+        }
+    }
+
+    /// Get the number of bytes of logs that can currently be reserved
+    #[must_use]
+    pub fn available(&self) -> usize {
+        self.permits.available_permits()
+    }
+}
+
+/// Reserve room for a request's stage logs before its body is read
+///
+/// Requests that don't tell us their size reserve all of our room since we
+/// can't know how much they will buffer.
+///
+/// # Arguments
+///
+/// * `limiter` - The limiter to reserve room from
+/// * `req` - The request to reserve room for
+/// * `next` - The rest of our middleware and the route handler
+pub async fn limit(
+    State(limiter): State<Arc<LogLimiter>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    // get the size of this requests body
+    let bytes = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(usize::MAX);
+    // hold our room until this request has been fully handled
+    let _permit = limiter.acquire(bytes).await?;
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::header::CONTENT_LENGTH;
+    use axum::http::{Request, StatusCode};
+    use axum::routing::post;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    use super::{LogLimiter, limit};
+
+    /// Send a request to a route that reads its body behind our limiter
+    ///
+    /// # Arguments
+    ///
+    /// * `limiter` - The limiter to reserve room from
+    /// * `reads` - The number of bodies our route has read
+    /// * `req` - The request to send
+    async fn send(
+        limiter: &Arc<LogLimiter>,
+        reads: &Arc<AtomicUsize>,
+        req: Request<Body>,
+    ) -> StatusCode {
+        let reads = reads.clone();
+        let router = Router::new().route(
+            "/logs",
+            post(move |body: String| async move {
+                reads.fetch_add(body.len(), Ordering::SeqCst);
+            })
+            .layer(axum::middleware::from_fn_with_state(limiter.clone(), limit)),
+        );
+        router.oneshot(req).await.unwrap().status()
+    }
+
+    /// Build a request to add some logs
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The body to send
+    /// * `sized` - Whether to tell the server how large our body is
+    fn logs(body: &str, sized: bool) -> Request<Body> {
+        let mut req = Request::post("/logs");
+        if sized {
+            req = req.header(CONTENT_LENGTH, body.len());
+        }
+        req.body(Body::from(body.to_owned())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn release_on_drop() {
+        let limiter = LogLimiter::new(100, Duration::from_millis(10));
+        let permit = limiter.acquire(60).await.unwrap();
+        assert_eq!(limiter.available(), 40);
+        // we don't have room for more logs until our first write finishes
+        let err = limiter.acquire(60).await.unwrap_err();
+        assert_eq!(err.code, StatusCode::TOO_MANY_REQUESTS);
+        drop(permit);
+        assert_eq!(limiter.available(), 100);
+        assert!(limiter.acquire(60).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn oversized_request() {
+        let limiter = LogLimiter::new(100, Duration::from_millis(10));
+        // requests larger than our capacity still go through once we are idle
+        let permit = limiter.acquire(1_000).await.unwrap();
+        assert_eq!(limiter.available(), 0);
+        drop(permit);
+        assert_eq!(limiter.available(), 100);
+    }
+
+    #[tokio::test]
+    async fn burst() {
+        // allow 4 requests worth of logs in flight at once
+        let limiter = Arc::new(LogLimiter::new(4_000, Duration::from_millis(50)));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        // send a burst of requests to a writer that is much slower than our max wait
+        let mut handles = Vec::with_capacity(64);
+        for _ in 0..64 {
+            let limiter = limiter.clone();
+            let in_flight = in_flight.clone();
+            let peak = peak.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = limiter.acquire(1_000).await?;
+                // track the most bytes we ever had in flight
+                let current = in_flight.fetch_add(1_000, Ordering::SeqCst) + 1_000;
+                peak.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                in_flight.fetch_sub(1_000, Ordering::SeqCst);
+                Ok::<_, super::ApiError>(())
+            }));
+        }
+        // count how many requests were written and how many were shed
+        let mut written = 0;
+        let mut shed = 0;
+        for handle in handles {
+            match handle.await.unwrap() {
+                Ok(()) => written += 1,
+                Err(err) => {
+                    assert_eq!(err.code, StatusCode::TOO_MANY_REQUESTS);
+                    shed += 1;
+                }
+            }
+        }
+        // we should never have more than our capacity in flight
+        assert!(peak.load(Ordering::SeqCst) <= 4_000);
+        assert_eq!(written, 4);
+        assert_eq!(shed, 60);
+        // all of our room should be free again
+        assert_eq!(limiter.available(), 4_000);
+    }
+
+    #[tokio::test]
+    async fn shed_before_reading() {
+        let limiter = Arc::new(LogLimiter::new(100, Duration::from_millis(10)));
+        let reads = Arc::new(AtomicUsize::new(0));
+        // hold most of our room as if another write was in flight
+        let permit = limiter.acquire(60).await.unwrap();
+        // requests that fit in our remaining room are still handled
+        let status = send(&limiter, &reads, logs("corn", true)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(reads.load(Ordering::SeqCst), 4);
+        // requests that don't fit are shed before their body is read
+        let status = send(&limiter, &reads, logs(&"x".repeat(50), true)).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        // requests that don't tell us their size need all of our room
+        let status = send(&limiter, &reads, logs("corn", false)).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(reads.load(Ordering::SeqCst), 4);
+        // once the other write finishes both requests go through
+        drop(permit);
+        let status = send(&limiter, &reads, logs(&"x".repeat(50), true)).await;
+        assert_eq!(status, StatusCode::OK);
+        let status = send(&limiter, &reads, logs("corn", false)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(reads.load(Ordering::SeqCst), 58);
+        // all of our room should be free again
+        assert_eq!(limiter.available(), 100);
+    }
+}
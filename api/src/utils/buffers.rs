@@ -0,0 +1,178 @@
+//! A pool of reusable buffers for streaming uploads
+//!
+//! Multipart uploads need a few mebibytes of buffer for each part. Reusing these
+//! buffers across parts and uploads keeps concurrent uploads from churning memory.
+
+use bytes::BytesMut;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+/// A pool of reusable buffers
+#[derive(Debug)]
+pub struct BufferPool {
+    /// The buffers that are ready to be reused
+    idle: Mutex<Vec<BytesMut>>,
+    /// The capacity of each buffer in bytes
+    buffer_size: usize,
+    /// The max number of idle buffers to keep around
+    max_idle: usize,
+}
+
+impl BufferPool {
+    /// Create a new buffer pool
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer_size` - The capacity of each buffer in bytes
+    /// * `max_idle` - The max number of idle buffers to keep around
+    #[must_use]
+    pub fn new(buffer_size: usize, max_idle: usize) -> Self {
+        BufferPool {
+            idle: Mutex::new(Vec::with_capacity(max_idle)),
+            buffer_size,
+            max_idle,
+        }
+    }
+
+    /// Get a buffer from this pool or allocate a new one if none are idle
+    ///
+    /// The buffer is returned to this pool when it is dropped.
+    pub fn get(&self) -> PooledBuffer<'_> {
+        // reuse an idle buffer if we have one
+        let buffer = self
+            .lock()
+            .pop()
+            .unwrap_or_else(|| BytesMut::with_capacity(self.buffer_size));
+        PooledBuffer { buffer, pool: self }
+    }
+
+    /// Get the capacity of each buffer in this pool in bytes
+    #[must_use]
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// Get the number of buffers that are ready to be reused
+    #[must_use]
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Lock our idle buffers
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<BytesMut>> {
+        // a poisoned lock only means another upload panicked so keep using our buffers
+        self.idle
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Return a buffer to this pool
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The buffer to return
+    fn put(&self, mut buffer: BytesMut) {
+        // wipe any data left over from whoever used this buffer last
+        buffer.clear();
+        // drop buffers whose memory is still shared with data that hasn't been sent yet
+        if !buffer.try_reclaim(self.buffer_size) {
+            return;
+        }
+        // keep this buffer unless we already have enough idle buffers
+        let mut idle = self.lock();
+        if idle.len() < self.max_idle {
+            idle.push(buffer);
+        }
+    }
+}
+
+/// A buffer that is returned to its pool when dropped
+#[derive(Debug)]
+pub struct PooledBuffer<'a> {
+    /// The buffer to use
+    buffer: BytesMut,
+    /// The pool to return this buffer to
+    pool: &'a BufferPool,
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = BytesMut;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        // return our buffer even if the upload using it failed
+        self.pool.put(std::mem::take(&mut self.buffer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BufferPool;
+
+    #[test]
+    fn reuse() {
+        let pool = BufferPool::new(1024, 2);
+        // fill a buffer and return it to our pool
+        let ptr = {
+            let mut buffer = pool.get();
+            buffer.extend_from_slice(b"corn");
+            buffer.as_ptr()
+        };
+        assert_eq!(pool.idle(), 1);
+        // we should get the same empty buffer back
+        let buffer = pool.get();
+        assert_eq!(buffer.as_ptr(), ptr);
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= 1024);
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn reuse_after_split() {
+        let pool = BufferPool::new(1024, 2);
+        let mut buffer = pool.get();
+        let ptr = buffer.as_ptr();
+        // hand off some bytes like we do when uploading a part
+        buffer.extend_from_slice(&[7; 600]);
+        let part = buffer.split().freeze();
+        // our buffer can't be reused while this part is still being sent
+        assert!(!buffer.try_reclaim(1024));
+        drop(part);
+        // once our part is sent our buffer can be reclaimed and reused
+        assert!(buffer.try_reclaim(1024));
+        drop(buffer);
+        assert_eq!(pool.get().as_ptr(), ptr);
+    }
+
+    #[test]
+    fn shared_buffers_dropped() {
+        let pool = BufferPool::new(1024, 2);
+        let mut buffer = pool.get();
+        buffer.extend_from_slice(&[7; 600]);
+        // drop our buffer while a part is still using its memory
+        let part = buffer.split().freeze();
+        drop(buffer);
+        assert_eq!(pool.idle(), 0);
+        assert_eq!(part.len(), 600);
+    }
+
+    #[test]
+    fn max_idle() {
+        let pool = BufferPool::new(1024, 2);
+        let buffers = (0..4).map(|_| pool.get()).collect::<Vec<_>>();
+        drop(buffers);
+        // only keep as many idle buffers as we were configured too
+        assert_eq!(pool.idle(), 2);
+    }
+}
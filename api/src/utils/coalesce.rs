@@ -0,0 +1,299 @@
+//! Coalesces concurrent identical reads into a single backend fetch
+//!
+//! When many agents request the same image or pipeline at once (like right after
+//! a deploy) each request would normally issue its own backend read. Instead the
+//! first request for a key does the fetch and any requests for that key that
+//! arrive while it is in flight wait for and share its result. Nothing is cached
+//! once the fetch completes so later requests always see fresh data unless the
+//! fetch goes through [`Coalescer::get_cached`].
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::sync::OnceCell;
+
+use super::ApiError;
+use super::lru::LruCache;
+
+/// The shared result of a fetch that is in flight
+type InFlight<V> = Arc<OnceCell<Result<V, ApiError>>>;
+
+/// Shares a single backend fetch between concurrent requests for the same key
+#[derive(Debug)]
+pub struct Coalescer<V: Clone> {
+    /// The fetches that are currently in flight by key
+    in_flight: Mutex<HashMap<String, InFlight<V>>>,
+    /// The number of fetches sent to the backend
+    fetches: AtomicU64,
+    /// The number of requests that shared another request's fetch
+    coalesced: AtomicU64,
+}
+
+impl<V: Clone> Default for Coalescer<V> {
+    fn default() -> Self {
+        Coalescer {
+            in_flight: Mutex::new(HashMap::default()),
+            fetches: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<V: Clone> Coalescer<V> {
+    /// Lock our in flight fetches
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, InFlight<V>>> {
+        // a poisoned lock only means another request panicked so keep going
+        self.in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get a value, sharing the fetch with any other requests for the same key
+    ///
+    /// If the request doing the fetch is dropped before it finishes then one of
+    /// the waiting requests will run its own fetch instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key for the value to get
+    /// * `fetch` - The function to fetch this value from the backend
+    pub async fn get<F, Fut>(&self, key: String, fetch: F) -> Result<V, ApiError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, ApiError>>,
+    {
+        // join an in flight fetch for this key or start a new one
+        let cell = self.lock().entry(key.clone()).or_default().clone();
+        let mut fetched = false;
+        let result = cell
+            .get_or_init(|| {
+                fetched = true;
+                self.fetches.fetch_add(1, Ordering::Relaxed);
+                fetch()
+            })
+            .await
+            .clone();
+        if !fetched {
+            self.coalesced.fetch_add(1, Ordering::Relaxed);
+        }
+        // stop sharing this fetch so later requests get fresh data
+        let mut in_flight = self.lock();
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            in_flight.remove(&key);
+        }
+        result
+    }
+
+    /// Get the number of fetches sent to the backend
+    pub fn fetches(&self) -> u64 {
+        self.fetches.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of requests that shared another request's fetch
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+}
+
+impl<V: Clone> Coalescer<(V, u64)> {
+    /// Get a value from a cache or share a fetch for it and cache what was fetched
+    ///
+    /// The cache's generation is read inside the shared fetch so requests that join
+    /// a fetch started before an invalidation don't cache its stale value.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - The cache to get and insert this value with
+    /// * `key` - The key for the value to get
+    /// * `fetch` - The function to fetch this value from the backend
+    pub async fn get_cached<F, Fut>(
+        &self,
+        cache: &LruCache<V>,
+        key: String,
+        fetch: F,
+    ) -> Result<V, ApiError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, ApiError>>,
+    {
+        // use our cached copy of this value if we have a recent one
+        if let Some(cached) = cache.get(&key) {
+            return Ok(cached);
+        }
+        let (value, generation) = self
+            .get(key.clone(), || async move {
+                // get our caches generation from before this value is read
+                let generation = cache.generation();
+                Ok((fetch().await?, generation))
+            })
+            .await?;
+        // cache this value unless it was invalidated while we were reading it
+        cache.insert(key, value.clone(), generation);
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::Coalescer;
+    use crate::utils::ApiError;
+    use crate::utils::lru::LruCache;
+
+    #[tokio::test]
+    async fn concurrent_gets() {
+        let coalescer = Arc::new(Coalescer::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+        // send a burst of identical requests to a slow backend
+        let mut handles = Vec::with_capacity(32);
+        for _ in 0..32 {
+            let coalescer = coalescer.clone();
+            let calls = calls.clone();
+            handles.push(tokio::spawn(async move {
+                coalescer
+                    .get("corn".to_owned(), || async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        Ok::<_, ApiError>(7)
+                    })
+                    .await
+            }));
+        }
+        // all of our requests should get the same value
+        for handle in handles {
+            assert_eq!(handle.await.unwrap().unwrap(), 7);
+        }
+        // only one request should have hit our backend
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(coalescer.fetches(), 1);
+        assert_eq!(coalescer.coalesced(), 31);
+    }
+
+    #[tokio::test]
+    async fn shared_errors() {
+        let coalescer = Arc::new(Coalescer::<u64>::default());
+        let mut handles = Vec::with_capacity(4);
+        for _ in 0..4 {
+            let coalescer = coalescer.clone();
+            handles.push(tokio::spawn(async move {
+                coalescer
+                    .get("corn".to_owned(), || async {
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        Err(ApiError::new(StatusCode::NOT_FOUND, None))
+                    })
+                    .await
+            }));
+        }
+        // every waiting request should see the same error
+        for handle in handles {
+            let err = handle.await.unwrap().unwrap_err();
+            assert_eq!(err.code, StatusCode::NOT_FOUND);
+        }
+        assert_eq!(coalescer.fetches(), 1);
+    }
+
+    #[tokio::test]
+    async fn no_caching() {
+        let coalescer = Coalescer::default();
+        // requests that don't overlap should each fetch fresh data
+        for value in 0..3 {
+            let got = coalescer
+                .get(
+                    "corn".to_owned(),
+                    || async move { Ok::<_, ApiError>(value) },
+                )
+                .await
+                .unwrap();
+            assert_eq!(got, value);
+        }
+        assert_eq!(coalescer.fetches(), 3);
+        assert_eq!(coalescer.coalesced(), 0);
+    }
+
+    #[tokio::test]
+    async fn separate_keys() {
+        let coalescer = Arc::new(Coalescer::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+        // requests for different keys should never share a fetch
+        let mut handles = Vec::with_capacity(2);
+        for key in ["corn", "soy"] {
+            let coalescer = coalescer.clone();
+            let calls = calls.clone();
+            handles.push(tokio::spawn(async move {
+                coalescer
+                    .get(key.to_owned(), || async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        Ok::<_, ApiError>(key)
+                    })
+                    .await
+            }));
+        }
+        let values = [
+            handles.remove(0).await.unwrap(),
+            handles.remove(0).await.unwrap(),
+        ];
+        assert_eq!(values[0].as_ref().unwrap(), &"corn");
+        assert_eq!(values[1].as_ref().unwrap(), &"soy");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn invalidated_while_in_flight() {
+        let coalescer = Arc::new(Coalescer::default());
+        let cache = Arc::new(LruCache::new(8, Duration::from_secs(60)));
+        let (release, wait) = tokio::sync::oneshot::channel::<()>();
+        // start a slow get that reads our old value
+        let first = {
+            let coalescer = coalescer.clone();
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                coalescer
+                    .get_cached(&cache, "corn".to_owned(), || async move {
+                        wait.await.unwrap();
+                        Ok::<_, ApiError>(1)
+                    })
+                    .await
+            })
+        };
+        while coalescer.fetches() == 0 {
+            tokio::task::yield_now().await;
+        }
+        // update our value while its old value is still being read
+        cache.invalidate("corn");
+        // get our value after the update and join the in flight read
+        let second = {
+            let coalescer = coalescer.clone();
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                coalescer
+                    .get_cached(&cache, "corn".to_owned(), || async { Ok::<_, ApiError>(2) })
+                    .await
+            })
+        };
+        while cache.misses() < 2 {
+            tokio::task::yield_now().await;
+        }
+        release.send(()).unwrap();
+        assert_eq!(first.await.unwrap().unwrap(), 1);
+        assert_eq!(second.await.unwrap().unwrap(), 1);
+        assert_eq!(coalescer.fetches(), 1);
+        assert_eq!(coalescer.coalesced(), 1);
+        // the old value should not have been cached for our update
+        assert_eq!(cache.get("corn"), None);
+        let got = coalescer
+            .get_cached(&cache, "corn".to_owned(), || async { Ok::<_, ApiError>(2) })
+            .await
+            .unwrap();
+        assert_eq!(got, 2);
+        assert_eq!(cache.get("corn"), Some(2));
+    }
+}
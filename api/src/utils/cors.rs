@@ -0,0 +1,200 @@
+//! Builds the origins the API allows cross origin requests from
+
+use axum::http::HeaderValue;
+use tower_http::cors::AllowOrigin;
+use tracing::{Level, event};
+
+use crate::conf::Cors;
+
+/// An origin pattern that matches any subdomain of a domain (e.g. `https://*.example.com`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WildcardOrigin {
+    /// The scheme origins must use including its "://"
+    scheme: String,
+    /// Everything after the wildcard including the leading '.' and any port
+    suffix: String,
+}
+
+impl WildcardOrigin {
+    /// Parse a wildcard origin pattern
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - The domain to parse
+    fn parse(domain: &str) -> Option<Self> {
+        let (scheme, host) = domain.split_once("://")?;
+        let suffix = host.strip_prefix('*')?;
+        Some(WildcardOrigin {
+            scheme: format!("{scheme}://"),
+            suffix: suffix.to_ascii_lowercase(),
+        })
+    }
+
+    /// Check if an origin matches this pattern
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The origin to check
+    #[must_use]
+    pub fn matches(&self, origin: &str) -> bool {
+        // hosts are case insensitive
+        let origin = origin.to_ascii_lowercase();
+        let Some(subdomain) = origin
+            .strip_prefix(&self.scheme)
+            .and_then(|host| host.strip_suffix(&self.suffix))
+        else {
+            return false;
+        };
+        // our wildcard must match at least one label and nothing but labels
+        !subdomain.is_empty()
+            && !subdomain.starts_with('.')
+            && subdomain
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.'))
+    }
+}
+
+/// The origins to allow cross origin requests from
+#[derive(Debug, Clone, Default)]
+pub struct CorsOrigins {
+    /// The origins that must be matched exactly
+    pub exact: Vec<HeaderValue>,
+    /// The wildcard patterns that origins can match
+    pub wildcards: Vec<WildcardOrigin>,
+    /// The domains that were skipped because they are not valid origins
+    pub skipped: Vec<String>,
+}
+
+impl CorsOrigins {
+    /// Build the origins to allow from our CORS settings
+    ///
+    /// Invalid domains are logged and skipped instead of failing.
+    ///
+    /// # Arguments
+    ///
+    /// * `cors` - The CORS settings to build origins from
+    #[must_use]
+    pub fn new(cors: &Cors) -> Self {
+        let mut origins = CorsOrigins::default();
+        for domain in &cors.domains {
+            // make sure this domain is a valid origin or pattern
+            if let Err(reason) = Cors::validate_domain(domain) {
+                event!(
+                    Level::ERROR,
+                    msg = "Skipping invalid CORS domain",
+                    domain = domain.as_str(),
+                    reason = reason
+                );
+                origins.skipped.push(domain.clone());
+                continue;
+            }
+            // add this domain as either a wildcard or an exact origin
+            if let Some(wildcard) = WildcardOrigin::parse(domain) {
+                origins.wildcards.push(wildcard);
+            } else if let Ok(value) = HeaderValue::from_str(domain) {
+                origins.exact.push(value);
+            } else {
+                event!(
+                    Level::ERROR,
+                    msg = "Skipping invalid CORS domain",
+                    domain = domain.as_str(),
+                    reason = "is not a valid header value"
+                );
+                origins.skipped.push(domain.clone());
+            }
+        }
+        origins
+    }
+
+    /// Check if an origin is allowed
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The origin to check
+    #[must_use]
+    pub fn allows(&self, origin: &HeaderValue) -> bool {
+        // check our exact origins first
+        if self.exact.contains(origin) {
+            return true;
+        }
+        // check if this origin matches any of our wildcards
+        origin.to_str().is_ok_and(|origin| {
+            self.wildcards
+                .iter()
+                .any(|wildcard| wildcard.matches(origin))
+        })
+    }
+
+    /// Convert these origins into the origins our CORS layer should allow
+    #[must_use]
+    pub fn allow_origin(self) -> AllowOrigin {
+        // only fall back to a predicate if we have wildcards to match
+        if self.wildcards.is_empty() {
+            AllowOrigin::list(self.exact)
+        } else {
+            AllowOrigin::predicate(move |origin, _| self.allows(origin))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::CorsOrigins;
+    use crate::conf::Cors;
+
+    #[test]
+    fn origins() {
+        let cors = Cors {
+            insecure: false,
+            domains: vec![
+                "https://thorium.example.com".to_owned(),
+                "https://*.corn.example.com".to_owned(),
+                "http://*.localhost:8080".to_owned(),
+                "thorium.example.com".to_owned(),
+                "https://thorium.example.com/api".to_owned(),
+                "https://*.".to_owned(),
+            ],
+        };
+        let origins = CorsOrigins::new(&cors);
+        // malformed domains are skipped instead of panicking
+        assert_eq!(
+            origins.skipped,
+            vec![
+                "thorium.example.com",
+                "https://thorium.example.com/api",
+                "https://*."
+            ]
+        );
+        assert_eq!(origins.exact.len(), 1);
+        assert_eq!(origins.wildcards.len(), 2);
+        // exact origins and subdomains of our wildcards are allowed
+        for allowed in [
+            "https://thorium.example.com",
+            "https://field.corn.example.com",
+            "https://north.field.corn.example.com",
+            "https://FIELD.corn.example.com",
+            "http://ui.localhost:8080",
+        ] {
+            let origin = HeaderValue::from_static(allowed);
+            assert!(origins.allows(&origin), "{allowed}");
+        }
+        // anything else is blocked
+        for blocked in [
+            "https://other.example.com",
+            "http://thorium.example.com",
+            "https://corn.example.com",
+            "https://.corn.example.com",
+            "http://field.corn.example.com",
+            "https://fieldcorn.example.com",
+            "https://evil.com/.corn.example.com",
+            "https://evil.com:443.corn.example.com",
+            "http://ui.localhost",
+            "http://ui.localhost:9090",
+        ] {
+            let origin = HeaderValue::from_static(blocked);
+            assert!(!origins.allows(&origin), "{blocked}");
+        }
+    }
+}
@@ -13,7 +13,7 @@ use crate::models::conversions::ConversionError;
 use crate::utils::trace;
 
 /// Builds an error http response
-#[derive(Debug, ToSchema, Serialize)]
+#[derive(Debug, Clone, ToSchema, Serialize)]
 pub struct ApiError {
     /// The status code to return
     #[serde(skip)]
@@ -35,36 +35,131 @@ impl ApiError {
     }
 }
 
+/// A machine stable identifier for the kind of error the API returned
+///
+/// Unlike the message in an error these will never change so clients can
+/// safely branch on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiErrorCode {
+    /// The request was malformed or invalid
+    BadRequest,
+    /// The request was not authenticated
+    Unauthorized,
+    /// The requester is not allowed to perform this action
+    Forbidden,
+    /// The requested data or route does not exist
+    NotFound,
+    /// The request conflicts with data that already exists
+    Conflict,
+    /// The request body was too large
+    PayloadTooLarge,
+    /// The requested range of data can't be returned
+    RangeNotSatisfiable,
+    /// The request was well formed but could not be processed
+    UnprocessableEntity,
+    /// The requester is being rate limited
+    TooManyRequests,
+    /// The API ran into an unexpected error
+    InternalError,
+    /// The API or one of its backends is currently unavailable
+    Unavailable,
+    /// An error that does not map to any other code
+    Unknown,
+}
+
+impl ApiErrorCode {
+    /// Get this error code as a static str
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiErrorCode::BadRequest => "bad_request",
+            ApiErrorCode::Unauthorized => "unauthorized",
+            ApiErrorCode::Forbidden => "forbidden",
+            ApiErrorCode::NotFound => "not_found",
+            ApiErrorCode::Conflict => "conflict",
+            ApiErrorCode::PayloadTooLarge => "payload_too_large",
+            ApiErrorCode::RangeNotSatisfiable => "range_not_satisfiable",
+            ApiErrorCode::UnprocessableEntity => "unprocessable_entity",
+            ApiErrorCode::TooManyRequests => "too_many_requests",
+            ApiErrorCode::InternalError => "internal_error",
+            ApiErrorCode::Unavailable => "unavailable",
+            ApiErrorCode::Unknown => "unknown",
+        }
+    }
+}
+
+impl From<StatusCode> for ApiErrorCode {
+    /// Get the stable error code for a status code
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The status code to get an error code for
+    fn from(code: StatusCode) -> Self {
+        match code {
+            StatusCode::BAD_REQUEST => ApiErrorCode::BadRequest,
+            StatusCode::UNAUTHORIZED => ApiErrorCode::Unauthorized,
+            StatusCode::FORBIDDEN => ApiErrorCode::Forbidden,
+            StatusCode::NOT_FOUND => ApiErrorCode::NotFound,
+            StatusCode::CONFLICT => ApiErrorCode::Conflict,
+            StatusCode::PAYLOAD_TOO_LARGE => ApiErrorCode::PayloadTooLarge,
+            StatusCode::RANGE_NOT_SATISFIABLE => ApiErrorCode::RangeNotSatisfiable,
+            StatusCode::UNPROCESSABLE_ENTITY => ApiErrorCode::UnprocessableEntity,
+            StatusCode::TOO_MANY_REQUESTS => ApiErrorCode::TooManyRequests,
+            StatusCode::INTERNAL_SERVER_ERROR => ApiErrorCode::InternalError,
+            StatusCode::SERVICE_UNAVAILABLE => ApiErrorCode::Unavailable,
+            _ => ApiErrorCode::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for ApiErrorCode {
+    /// Write this error code as its stable str
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The formatter that is being used
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The JSON body returned for every error from the API
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApiErrorBody {
+    /// The stable identifier for this kind of error
+    pub code: ApiErrorCode,
+    /// A human readable message describing this error
+    pub error: String,
+    /// The trace id for the request that failed if tracing is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<String>,
+}
+
 impl IntoResponse for ApiError {
     /// Allow Axum to build a response from error messages
     fn into_response(self) -> Response {
-        // get our trace id
-        let trace = trace::get_trace();
-        // check if we have an error message or not
-        match self.msg {
-            // we have a message so build our error response
-            Some(msg) => {
-                // log this error msg
-                let span = span!(Level::ERROR, "Error Message");
-                event!(parent: &span, Level::ERROR, msg = &msg,);
-                // wrap our message in a json object with a trace id if we have one
-                let err_json = match trace {
-                    Some(trace) => Json(serde_json::json!({ "error": msg, "trace": &trace })),
-                    None => Json(serde_json::json!({ "error": msg })),
-                };
-                (self.code, err_json).into_response()
-            }
-            // we do not have an error message so just return the trace
-            None => match trace {
-                // we have a trace so return that trace id
-                Some(trace) => {
-                    let body = Json(serde_json::json!({ "trace": &trace }));
-                    (self.code, body).into_response()
-                }
-                // we do not have a trace so just return an empty body
-                None => self.code.into_response(),
-            },
+        // statuses that are not errors (204, 304) must not have a body
+        if !self.code.is_client_error() && !self.code.is_server_error() {
+            return self.code.into_response();
         }
+        // log this error msg if we have one
+        if let Some(msg) = &self.msg {
+            let span = span!(Level::ERROR, "Error Message");
+            event!(parent: &span, Level::ERROR, msg = msg);
+        }
+        // fall back to the reason for our status code if we don't have a message
+        let error = match self.msg {
+            Some(msg) => msg,
+            None => self.code.canonical_reason().unwrap_or_default().to_owned(),
+        };
+        // wrap our message in a json object with a stable code and a trace id if we have one
+        let body = ApiErrorBody {
+            code: ApiErrorCode::from(self.code),
+            error,
+            trace: trace::get_trace(),
+        };
+        (self.code, Json(body)).into_response()
     }
 }
 
@@ -123,6 +218,12 @@ macro_rules! internal_err_unwrapped {
     ($($msg:tt)+) => {$crate::utils::ApiError::new(axum::http::status::StatusCode::INTERNAL_SERVER_ERROR, Some($($msg)+))}
 }
 
+/// 416 range not satisfiable
+#[macro_export]
+macro_rules! range_not_satisfiable {
+    ($($msg:tt)+) => {Err($crate::utils::ApiError::new(axum::http::status::StatusCode::RANGE_NOT_SATISFIABLE, Some($($msg)+)))}
+}
+
 /// 503 service unavailable
 #[macro_export]
 macro_rules! unavailable {
@@ -459,7 +560,9 @@ impl From<strum::ParseError> for ApiError {
 
 impl From<isocountry::CountryCodeParseErr> for ApiError {
     fn from(error: isocountry::CountryCodeParseErr) -> Self {
-        bad_internal!(format!("Failed to parse country code: {error}"))
+        bad_internal!(format!(
+            "Failed to parse country code: {error}; expected an ISO 3166 alpha-2 (e.g. 'US') or alpha-3 (e.g. 'USA') code, an ISO country name, or a configured alias"
+        ))
     }
 }
 
@@ -495,3 +598,66 @@ impl From<ApiError> for rmcp::ErrorData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    use super::{ApiError, ApiErrorBody, ApiErrorCode};
+
+    /// Build the response for an error and parse its body
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error to build a response for
+    async fn respond(error: ApiError) -> (StatusCode, serde_json::Value) {
+        let resp = error.into_response();
+        let status = resp.status();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read error body");
+        let body = serde_json::from_slice(&bytes).expect("Error body is not json");
+        (status, body)
+    }
+
+    #[tokio::test]
+    async fn not_found() {
+        let (status, body) = respond(not_found_unwrapped!("Missing thing".to_owned())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "not_found");
+        assert_eq!(body["error"], "Missing thing");
+        // make sure our body can be parsed back into an error body
+        let parsed: ApiErrorBody = serde_json::from_value(body).unwrap();
+        assert_eq!(parsed.code, ApiErrorCode::NotFound);
+    }
+
+    #[tokio::test]
+    async fn unauthorized() {
+        // unauthorized errors often have no message so make sure we still get one
+        let (status, body) = respond(ApiError::new(StatusCode::UNAUTHORIZED, None)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "unauthorized");
+        assert_eq!(body["error"], "Unauthorized");
+    }
+
+    #[tokio::test]
+    async fn bad_request() {
+        let (status, body) = respond(bad_internal!("Bad input".to_owned())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "bad_request");
+        assert_eq!(body["error"], "Bad input");
+        // make sure we only have the documented fields
+        let keys = body.as_object().unwrap().keys().collect::<Vec<_>>();
+        assert_eq!(keys, vec!["code", "error"]);
+    }
+
+    #[test]
+    fn unmapped_status() {
+        assert_eq!(
+            ApiErrorCode::from(StatusCode::IM_A_TEAPOT),
+            ApiErrorCode::Unknown
+        );
+        assert_eq!(ApiErrorCode::Conflict.to_string(), "conflict");
+    }
+}
@@ -0,0 +1,64 @@
+//! The standard hashes Thorium computes for every file
+//!
+//! These are shared by the API, which hashes files as they are uploaded, and the
+//! client, which can hash files as it uploads them to verify the API's hashes.
+
+use data_encoding::HEXLOWER;
+use md5::Md5;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+/// The standard hashes for a file
+#[derive(Debug)]
+pub struct StandardHashes {
+    /// The sha256 hash
+    pub sha256: String,
+    /// The sha1 hash
+    pub sha1: String,
+    /// The md5 hash
+    pub md5: String,
+}
+
+/// Hashes files with sha256, sha1, and md5
+pub struct StandardHashers {
+    /// The sha256 hasher
+    pub sha256: Sha256,
+    /// The sha1 hasher
+    pub sha1: Sha1,
+    /// The md5 hasher
+    pub md5: Md5,
+}
+
+impl StandardHashers {
+    /// Add a buffer to our hashers
+    ///
+    /// # Arguments
+    ///
+    /// * `buff` - The buffer to digest
+    pub fn digest(&mut self, buff: &[u8]) {
+        // digest this buffer with each of our hashers
+        self.sha256.update(buff);
+        self.sha1.update(buff);
+        self.md5.update(buff);
+    }
+
+    /// Finalize our hashers and get our hashes
+    pub fn finish(self) -> StandardHashes {
+        // build our digests
+        let sha256 = HEXLOWER.encode(&self.sha256.finalize());
+        let sha1 = HEXLOWER.encode(&self.sha1.finalize());
+        let md5 = HEXLOWER.encode(&self.md5.finalize());
+        StandardHashes { sha256, sha1, md5 }
+    }
+}
+
+impl Default for StandardHashers {
+    /// Create default hashers
+    fn default() -> Self {
+        StandardHashers {
+            sha256: Sha256::new(),
+            sha1: Sha1::new(),
+            md5: Md5::new(),
+        }
+    }
+}
@@ -87,6 +87,10 @@ where
 }
 
 /// Get the sha256 of a file on disk
+///
+/// # Arguments
+///
+/// * `path` - The path to the file to hash
 #[cfg(feature = "client")]
 pub async fn sha256_file(
     path: impl AsRef<std::path::Path>,
@@ -111,6 +115,8 @@ pub async fn sha256_file(
         }
         // read only the bytes we wrote to our buffer into the hasher
         hasher.update(&buf[..n]);
+        // clear our buffer so the next read starts at the beginning again
+        buf.clear();
     }
     // get this files hash
     Ok(HEXLOWER.encode(&hasher.finalize()))
@@ -0,0 +1,298 @@
+//! A small least recently used cache whose entries expire after a short time
+//!
+//! This lets hot data like group definitions be reused across requests without
+//! going back to the backend every time. Entries expire quickly so data changed
+//! by other API instances is never stale for long.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// A cached value
+#[derive(Debug)]
+struct Entry<V> {
+    /// The cached value
+    value: V,
+    /// When this value was cached
+    cached: Instant,
+    /// When this value was last used
+    used: u64,
+}
+
+/// The entries in a cache and the order they were last used in
+#[derive(Debug)]
+struct Entries<V> {
+    /// The cached values by key
+    map: HashMap<String, Entry<V>>,
+    /// The keys in our cache from least to most recently used
+    order: BTreeMap<u64, String>,
+    /// The clock used to order our entries by when they were last used
+    clock: u64,
+    /// The number of times our entries have been invalidated
+    generation: u64,
+}
+
+impl<V> Entries<V> {
+    /// Remove an entry from our cache
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to remove
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.map.remove(key) {
+            self.order.remove(&entry.used);
+        }
+    }
+
+    /// Get the next tick of our clock
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+/// A least recently used cache whose entries expire after a set time
+#[derive(Debug)]
+pub struct LruCache<V: Clone> {
+    /// The entries in this cache
+    entries: Mutex<Entries<V>>,
+    /// The max number of entries to cache
+    capacity: usize,
+    /// How long entries are valid for
+    ttl: Duration,
+    /// The number of lookups that were served from this cache
+    hits: AtomicU64,
+    /// The number of lookups that were not in this cache
+    misses: AtomicU64,
+}
+
+impl<V: Clone> LruCache<V> {
+    /// Create a new cache
+    ///
+    /// A cache with a capacity of 0 or no ttl never caches anything.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The max number of entries to cache
+    /// * `ttl` - How long entries are valid for
+    #[must_use]
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        LruCache {
+            entries: Mutex::new(Entries {
+                map: HashMap::with_capacity(capacity),
+                order: BTreeMap::default(),
+                clock: 0,
+                generation: 0,
+            }),
+            capacity,
+            ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Lock our entries
+    fn lock(&self) -> MutexGuard<'_, Entries<V>> {
+        // a poisoned lock only means another request panicked so keep using our cache
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Check if this cache is disabled
+    #[must_use]
+    pub fn disabled(&self) -> bool {
+        self.capacity == 0 || self.ttl.is_zero()
+    }
+
+    /// Get a value from this cache if it hasn't expired
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to get a value for
+    pub fn get(&self, key: &str) -> Option<V> {
+        let mut entries = self.lock();
+        // get the next tick of our clock in case this entry is used
+        let now = entries.tick();
+        let found = match entries.map.get_mut(key) {
+            // this entry is still valid so mark it as recently used
+            Some(entry) if entry.cached.elapsed() < self.ttl => {
+                let last = std::mem::replace(&mut entry.used, now);
+                Some((entry.value.clone(), last))
+            }
+            // this entry has expired or was never cached
+            _ => None,
+        };
+        match found {
+            Some((value, last)) => {
+                // move this entry to the back of our eviction order
+                entries.order.remove(&last);
+                entries.order.insert(now, key.to_owned());
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(value)
+            }
+            None => {
+                // drop this entry if it expired
+                entries.remove(key);
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Get the current generation of this cache
+    ///
+    /// This should be checked before getting a value from the backend and passed
+    /// to [`LruCache::insert`] so values read before an invalidation are not cached.
+    pub fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    /// Cache a value if this cache hasn't been invalidated since it was read
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to cache this value under
+    /// * `value` - The value to cache
+    /// * `generation` - The generation of this cache from before this value was read
+    pub fn insert(&self, key: impl Into<String>, value: V, generation: u64) {
+        // skip caching if we are disabled
+        if self.disabled() {
+            return;
+        }
+        let mut entries = self.lock();
+        // this value may be stale if we were invalidated while it was being read
+        if entries.generation != generation {
+            return;
+        }
+        let key = key.into();
+        // replace any existing entry for this key
+        entries.remove(&key);
+        // evict our least recently used entries until we have room
+        while entries.map.len() >= self.capacity {
+            let Some((_, oldest)) = entries.order.pop_first() else {
+                break;
+            };
+            entries.map.remove(&oldest);
+        }
+        let used = entries.tick();
+        entries.order.insert(used, key.clone());
+        let entry = Entry {
+            value,
+            cached: Instant::now(),
+            used,
+        };
+        entries.map.insert(key, entry);
+    }
+
+    /// Remove a value from this cache
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to invalidate
+    pub fn invalidate(&self, key: &str) {
+        let mut entries = self.lock();
+        entries.generation += 1;
+        entries.remove(key);
+    }
+
+    /// Remove all values from this cache
+    pub fn clear(&self) {
+        let mut entries = self.lock();
+        entries.generation += 1;
+        entries.map.clear();
+        entries.order.clear();
+    }
+
+    /// Get the number of values in this cache
+    pub fn len(&self) -> usize {
+        self.lock().map.len()
+    }
+
+    /// Check if this cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of lookups that were served from this cache
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of lookups that were not in this cache
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::LruCache;
+
+    #[test]
+    fn hit_within_ttl() {
+        let cache = LruCache::new(8, Duration::from_secs(60));
+        assert_eq!(cache.get("corn"), None);
+        cache.insert("corn", 1, cache.generation());
+        // repeated lookups should all be served from our cache
+        for _ in 0..3 {
+            assert_eq!(cache.get("corn"), Some(1));
+        }
+        assert_eq!(cache.hits(), 3);
+        assert_eq!(cache.misses(), 1);
+    }
+
+    #[test]
+    fn expire() {
+        let cache = LruCache::new(8, Duration::from_millis(20));
+        cache.insert("corn", 1, cache.generation());
+        std::thread::sleep(Duration::from_millis(40));
+        // our value is too old to be used
+        assert_eq!(cache.get("corn"), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn invalidate() {
+        let cache = LruCache::new(8, Duration::from_secs(60));
+        cache.insert("corn", 1, cache.generation());
+        cache.insert("soy", 2, cache.generation());
+        cache.invalidate("corn");
+        assert_eq!(cache.get("corn"), None);
+        assert_eq!(cache.get("soy"), Some(2));
+        cache.clear();
+        assert_eq!(cache.get("soy"), None);
+    }
+
+    #[test]
+    fn skip_stale_insert() {
+        let cache = LruCache::new(8, Duration::from_secs(60));
+        // read a value then have it invalidated before we cache it
+        let generation = cache.generation();
+        cache.invalidate("corn");
+        cache.insert("corn", 1, generation);
+        assert_eq!(cache.get("corn"), None);
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let cache = LruCache::new(2, Duration::from_secs(60));
+        cache.insert("corn", 1, cache.generation());
+        cache.insert("soy", 2, cache.generation());
+        // use corn so soy is our least recently used value
+        assert_eq!(cache.get("corn"), Some(1));
+        cache.insert("wheat", 3, cache.generation());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("soy"), None);
+        assert_eq!(cache.get("corn"), Some(1));
+        assert_eq!(cache.get("wheat"), Some(3));
+    }
+
+    #[test]
+    fn disabled() {
+        let cache = LruCache::new(0, Duration::from_secs(60));
+        cache.insert("corn", 1, cache.generation());
+        assert_eq!(cache.get("corn"), None);
+    }
+}
@@ -0,0 +1,535 @@
+//! A lightweight registry of metrics that can be scraped by Prometheus
+//!
+//! Metrics are only tracked when they are enabled in the Thorium config and are
+//! rendered in the Prometheus text format when scraped.
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use super::AppState;
+use super::lru::LruCache;
+use crate::models::ImageScaler;
+
+/// The upper bounds in seconds of the buckets in our request latency histogram
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// A histogram of how long requests took
+#[derive(Debug, Default, Clone)]
+struct Histogram {
+    /// The number of requests in each latency bucket
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    /// The total number of seconds spent on requests
+    sum: f64,
+    /// The total number of requests
+    count: u64,
+}
+
+impl Histogram {
+    /// Add a request's latency to this histogram
+    ///
+    /// # Arguments
+    ///
+    /// * `latency` - How long this request took
+    fn observe(&mut self, latency: Duration) {
+        let secs = latency.as_secs_f64();
+        // increment every bucket this latency falls under
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if secs <= bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += secs;
+        self.count += 1;
+    }
+}
+
+/// The request stats we track
+#[derive(Debug, Default)]
+struct RequestStats {
+    /// The number of requests by method, route, and status code
+    totals: BTreeMap<(String, String, u16), u64>,
+    /// The latency of requests by method and route
+    latency: BTreeMap<(String, String), Histogram>,
+}
+
+/// The number of jobs waiting or running for a scaler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueDepth {
+    /// The scaler these jobs are for
+    pub scaler: ImageScaler,
+    /// The number of jobs waiting to be run
+    pub queued: i64,
+    /// The number of jobs that are currently running
+    pub running: i64,
+}
+
+/// A snapshot of our Scylla connection pool and the requests sent through it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ScyllaPoolStats {
+    /// The number of Scylla nodes we know about
+    pub nodes: u64,
+    /// The number of open connections across all nodes
+    pub connections: u64,
+    /// The number of connections configured for each host or shard
+    pub pool_size: u64,
+    /// Whether our pool size is per "host" or per "shard"
+    pub pool_per: &'static str,
+    /// The number of requests to Scylla that are currently in flight
+    pub in_flight: u64,
+    /// The number of requests sent to Scylla including each page of paged requests
+    pub requests: u64,
+    /// The number of requests that failed
+    pub errors: u64,
+    /// The number of times a request was retried
+    pub retries: u64,
+    /// The number of times opening a connection timed out
+    pub connection_timeouts: u64,
+    /// The number of requests that timed out
+    pub request_timeouts: u64,
+    /// The average latency of requests in milliseconds
+    pub latency_avg_ms: u64,
+}
+
+/// A snapshot of one of our in memory caches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The name of this cache
+    pub cache: &'static str,
+    /// The number of values currently cached
+    pub cached: usize,
+    /// The number of gets that were served from this cache
+    pub hits: u64,
+    /// The number of gets that were not in this cache
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Take a snapshot of a cache
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - The name of this cache
+    /// * `lru` - The cache to snapshot
+    pub fn new<V: Clone>(cache: &'static str, lru: &LruCache<V>) -> Self {
+        CacheStats {
+            cache,
+            cached: lru.len(),
+            hits: lru.hits(),
+            misses: lru.misses(),
+        }
+    }
+}
+
+/// The metrics tracked by this API instance
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    /// The stats for requests to our routes
+    requests: Mutex<RequestStats>,
+    /// The number of reactions that have been created
+    reactions_created: AtomicU64,
+}
+
+impl MetricsRegistry {
+    /// Track a request that was responded to
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The method for this request
+    /// * `route` - The route that matched this request
+    /// * `status` - The status code we responded with
+    /// * `latency` - How long it took to respond
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, latency: Duration) {
+        // a poisoned lock only means another request panicked so keep counting
+        let mut stats = self
+            .requests
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *stats
+            .totals
+            .entry((method.to_owned(), route.to_owned(), status))
+            .or_default() += 1;
+        stats
+            .latency
+            .entry((method.to_owned(), route.to_owned()))
+            .or_default()
+            .observe(latency);
+    }
+
+    /// Track that some reactions were created
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of reactions that were created
+    pub fn reactions_created(&self, count: usize) {
+        self.reactions_created
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Render our metrics in the Prometheus text format
+    ///
+    /// # Arguments
+    ///
+    /// * `queues` - The current depth of each scaler's job queue
+    /// * `scylla` - The current state of our Scylla connection pool
+    /// * `caches` - The current state of our in memory caches
+    #[must_use]
+    pub fn render(
+        &self,
+        queues: &[QueueDepth],
+        scylla: &ScyllaPoolStats,
+        caches: &[CacheStats],
+    ) -> String {
+        let mut out = String::new();
+        // copy our request stats so we don't hold the lock while formatting
+        let (totals, latency) = {
+            let stats = self
+                .requests
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            (stats.totals.clone(), stats.latency.clone())
+        };
+        // writing to a string can't fail so ignore the results
+        let _ = writeln!(
+            out,
+            "# HELP thorium_http_requests_total The number of requests by route and status"
+        );
+        let _ = writeln!(out, "# TYPE thorium_http_requests_total counter");
+        for ((method, route, status), count) in &totals {
+            let _ = writeln!(
+                out,
+                "thorium_http_requests_total{{method=\"{method}\",route=\"{}\",status=\"{status}\"}} {count}",
+                escape(route)
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP thorium_http_request_duration_seconds How long requests took by route"
+        );
+        let _ = writeln!(
+            out,
+            "# TYPE thorium_http_request_duration_seconds histogram"
+        );
+        for ((method, route), histogram) in &latency {
+            let labels = format!("method=\"{method}\",route=\"{}\"", escape(route));
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                let _ = writeln!(
+                    out,
+                    "thorium_http_request_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {count}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "thorium_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "thorium_http_request_duration_seconds_sum{{{labels}}} {}",
+                histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "thorium_http_request_duration_seconds_count{{{labels}}} {}",
+                histogram.count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP thorium_reactions_created_total The number of reactions created"
+        );
+        let _ = writeln!(out, "# TYPE thorium_reactions_created_total counter");
+        let _ = writeln!(
+            out,
+            "thorium_reactions_created_total {}",
+            self.reactions_created.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "# HELP thorium_job_queue_depth The number of jobs by scaler and state"
+        );
+        let _ = writeln!(out, "# TYPE thorium_job_queue_depth gauge");
+        for depth in queues {
+            let scaler = depth.scaler;
+            let _ = writeln!(
+                out,
+                "thorium_job_queue_depth{{scaler=\"{scaler}\",state=\"queued\"}} {}",
+                depth.queued
+            );
+            let _ = writeln!(
+                out,
+                "thorium_job_queue_depth{{scaler=\"{scaler}\",state=\"running\"}} {}",
+                depth.running
+            );
+        }
+        // render our Scylla pool stats
+        let pool = [
+            (
+                "thorium_scylla_nodes",
+                "gauge",
+                "The number of Scylla nodes known to this API",
+                scylla.nodes,
+            ),
+            (
+                "thorium_scylla_connections",
+                "gauge",
+                "The number of open connections to Scylla",
+                scylla.connections,
+            ),
+            (
+                "thorium_scylla_requests_in_flight",
+                "gauge",
+                "The number of requests to Scylla that are currently in flight",
+                scylla.in_flight,
+            ),
+            (
+                "thorium_scylla_requests_total",
+                "counter",
+                "The number of requests sent to Scylla",
+                scylla.requests,
+            ),
+            (
+                "thorium_scylla_request_errors_total",
+                "counter",
+                "The number of requests to Scylla that failed",
+                scylla.errors,
+            ),
+            (
+                "thorium_scylla_retries_total",
+                "counter",
+                "The number of requests to Scylla that were retried",
+                scylla.retries,
+            ),
+            (
+                "thorium_scylla_connection_timeouts_total",
+                "counter",
+                "The number of connections to Scylla that timed out",
+                scylla.connection_timeouts,
+            ),
+            (
+                "thorium_scylla_request_timeouts_total",
+                "counter",
+                "The number of requests to Scylla that timed out",
+                scylla.request_timeouts,
+            ),
+            (
+                "thorium_scylla_request_latency_avg_milliseconds",
+                "gauge",
+                "The average latency of requests to Scylla",
+                scylla.latency_avg_ms,
+            ),
+        ];
+        for (name, kind, help, value) in pool {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            let _ = writeln!(out, "{name} {value}");
+        }
+        let _ = writeln!(
+            out,
+            "# HELP thorium_scylla_pool_size The number of connections configured for each Scylla host or shard"
+        );
+        let _ = writeln!(out, "# TYPE thorium_scylla_pool_size gauge");
+        let _ = writeln!(
+            out,
+            "thorium_scylla_pool_size{{per=\"{}\"}} {}",
+            scylla.pool_per, scylla.pool_size
+        );
+        // render our cache stats
+        let _ = writeln!(
+            out,
+            "# HELP thorium_cache_hits_total The number of gets served from a cache"
+        );
+        let _ = writeln!(out, "# TYPE thorium_cache_hits_total counter");
+        for stats in caches {
+            let _ = writeln!(
+                out,
+                "thorium_cache_hits_total{{cache=\"{}\"}} {}",
+                stats.cache, stats.hits
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP thorium_cache_misses_total The number of gets that missed a cache"
+        );
+        let _ = writeln!(out, "# TYPE thorium_cache_misses_total counter");
+        for stats in caches {
+            let _ = writeln!(
+                out,
+                "thorium_cache_misses_total{{cache=\"{}\"}} {}",
+                stats.cache, stats.misses
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP thorium_cache_entries The number of values currently cached"
+        );
+        let _ = writeln!(out, "# TYPE thorium_cache_entries gauge");
+        for stats in caches {
+            let _ = writeln!(
+                out,
+                "thorium_cache_entries{{cache=\"{}\"}} {}",
+                stats.cache, stats.cached
+            );
+        }
+        out
+    }
+}
+
+/// Escape a label value for the Prometheus text format
+///
+/// # Arguments
+///
+/// * `value` - The label value to escape
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Track the status and latency of every request to a matched route
+///
+/// Unmatched requests are not tracked so random paths can't blow up our label cardinality.
+///
+/// # Arguments
+///
+/// * `state` - Shared Thorium objects
+/// * `req` - The request to track
+/// * `next` - The rest of our middleware and the route handler
+pub async fn track(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    // get the route template this request matched
+    let Some(route) = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+    else {
+        return next.run(req).await;
+    };
+    let method = req.method().to_string();
+    let start = Instant::now();
+    // handle this request
+    let resp = next.run(req).await;
+    // track this request
+    state
+        .shared
+        .metrics
+        .observe_request(&method, &route, resp.status().as_u16(), start.elapsed());
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{CacheStats, MetricsRegistry, QueueDepth, ScyllaPoolStats};
+    use crate::models::ImageScaler;
+
+    #[test]
+    fn render() {
+        let metrics = MetricsRegistry::default();
+        metrics.observe_request("GET", "/api/basic/ping", 204, Duration::from_millis(3));
+        metrics.observe_request("GET", "/api/basic/ping", 204, Duration::from_millis(300));
+        metrics.reactions_created(2);
+        let queues = [QueueDepth {
+            scaler: ImageScaler::K8s,
+            queued: 4,
+            running: 1,
+        }];
+        let rendered = metrics.render(&queues, &ScyllaPoolStats::default(), &[]);
+        let lines = rendered.lines().collect::<Vec<&str>>();
+        // check our counters
+        assert!(lines.contains(
+            &"thorium_http_requests_total{method=\"GET\",route=\"/api/basic/ping\",status=\"204\"} 2"
+        ));
+        assert!(lines.contains(&"thorium_reactions_created_total 2"));
+        // check our histogram buckets are cumulative
+        let labels = "method=\"GET\",route=\"/api/basic/ping\"";
+        for (bound, count) in [("0.005", 1), ("0.25", 1), ("0.5", 2), ("+Inf", 2)] {
+            let line = format!(
+                "thorium_http_request_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {count}"
+            );
+            assert!(lines.contains(&line.as_str()), "{line} is missing");
+        }
+        assert!(lines.contains(&"thorium_http_request_duration_seconds_count{method=\"GET\",route=\"/api/basic/ping\"} 2"));
+        // check our queue depths
+        assert!(lines.contains(&"thorium_job_queue_depth{scaler=\"K8s\",state=\"queued\"} 4"));
+        assert!(lines.contains(&"thorium_job_queue_depth{scaler=\"K8s\",state=\"running\"} 1"));
+    }
+
+    #[test]
+    fn render_scylla_pool() {
+        let metrics = MetricsRegistry::default();
+        let pool = ScyllaPoolStats {
+            nodes: 3,
+            connections: 12,
+            pool_size: 4,
+            pool_per: "shard",
+            in_flight: 5,
+            requests: 40,
+            errors: 2,
+            retries: 1,
+            connection_timeouts: 0,
+            request_timeouts: 1,
+            latency_avg_ms: 7,
+        };
+        let rendered = metrics.render(&[], &pool, &[]);
+        let lines = rendered.lines().collect::<Vec<&str>>();
+        // every stat should be rendered with its type
+        assert!(lines.contains(&"# TYPE thorium_scylla_connections gauge"));
+        assert!(lines.contains(&"# TYPE thorium_scylla_requests_total counter"));
+        for line in [
+            "thorium_scylla_nodes 3",
+            "thorium_scylla_connections 12",
+            "thorium_scylla_requests_in_flight 5",
+            "thorium_scylla_pool_size{per=\"shard\"} 4",
+            "thorium_scylla_requests_total 40",
+            "thorium_scylla_request_errors_total 2",
+            "thorium_scylla_retries_total 1",
+            "thorium_scylla_connection_timeouts_total 0",
+            "thorium_scylla_request_timeouts_total 1",
+            "thorium_scylla_request_latency_avg_milliseconds 7",
+        ] {
+            assert!(lines.contains(&line), "{line} is missing");
+        }
+    }
+
+    #[test]
+    fn render_caches() {
+        let metrics = MetricsRegistry::default();
+        let caches = [
+            CacheStats {
+                cache: "images",
+                cached: 3,
+                hits: 10,
+                misses: 4,
+            },
+            CacheStats {
+                cache: "pipelines",
+                cached: 1,
+                hits: 0,
+                misses: 1,
+            },
+        ];
+        let rendered = metrics.render(&[], &ScyllaPoolStats::default(), &caches);
+        let lines = rendered.lines().collect::<Vec<&str>>();
+        assert!(lines.contains(&"# TYPE thorium_cache_hits_total counter"));
+        assert!(lines.contains(&"# TYPE thorium_cache_entries gauge"));
+        for line in [
+            "thorium_cache_hits_total{cache=\"images\"} 10",
+            "thorium_cache_misses_total{cache=\"images\"} 4",
+            "thorium_cache_entries{cache=\"images\"} 3",
+            "thorium_cache_hits_total{cache=\"pipelines\"} 0",
+            "thorium_cache_misses_total{cache=\"pipelines\"} 1",
+            "thorium_cache_entries{cache=\"pipelines\"} 1",
+        ] {
+            assert!(lines.contains(&line), "{line} is missing");
+        }
+    }
+}
@@ -3,19 +3,31 @@
 #[cfg(feature = "api")]
 #[path = ""]
 mod utils_api_reexport {
+    pub mod backpressure;
     pub mod bounder;
+    pub mod buffers;
+    pub mod coalesce;
+    pub mod cors;
     pub mod errors;
+    pub mod lru;
     pub mod macros;
+    pub mod metrics;
+    pub mod ndjson;
     pub mod s3;
     pub mod shared;
     pub use self::s3::StandardHashes;
-    pub use errors::ApiError;
+    pub use errors::{ApiError, ApiErrorBody, ApiErrorCode};
+    pub use metrics::MetricsRegistry;
     pub use shared::{AppState, Shared};
 }
 
 #[cfg(feature = "api")]
 pub use utils_api_reexport::*;
 
+// the standard hashes are computed by both the API and the client
+#[cfg(any(feature = "api", feature = "client"))]
+pub mod hashes;
+
 #[cfg(feature = "tracing")]
 #[path = ""]
 mod trace_reexport {
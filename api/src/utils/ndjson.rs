@@ -0,0 +1,63 @@
+//! Helpers for streaming newline delimited JSON responses
+
+use axum::body::Body;
+use axum::http::{HeaderMap, HeaderValue, header};
+use axum::response::{IntoResponse, Response};
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+
+use super::ApiError;
+
+/// The content type for newline delimited JSON
+pub const CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// The header the cursor for the next page of a streamed list is returned in
+pub const CURSOR_HEADER: &str = "x-thorium-cursor";
+
+/// Check if a request asked for a newline delimited JSON response
+///
+/// # Arguments
+///
+/// * `headers` - The headers for this request
+pub fn accepts(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        // ignore any params like quality weights
+        .filter_map(|value| value.split(';').next())
+        .any(|mime| mime.trim().eq_ignore_ascii_case(CONTENT_TYPE))
+}
+
+/// Build a response that streams each item as its own line of JSON
+///
+/// If the stream fails partway through then the response body is cut off.
+///
+/// # Arguments
+///
+/// * `items` - The items to stream
+/// * `cursor` - The cursor for the next page of items if one exists
+pub fn stream<T, S, C>(items: S, cursor: Option<C>) -> Response
+where
+    T: Serialize,
+    S: Stream<Item = Result<T, ApiError>> + Send + 'static,
+    C: ToString,
+{
+    // serialize each item to a line of json
+    let lines = items.map(|item| {
+        let item = item.map_err(|err| std::io::Error::other(err.to_string()))?;
+        let mut line = serde_json::to_vec(&item)?;
+        line.push(b'\n');
+        Ok::<Vec<u8>, std::io::Error>(line)
+    });
+    let mut resp = Body::from_stream(lines).into_response();
+    let headers = resp.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE));
+    // add the cursor for the next page if we have one
+    // our cursors are always numbers or uuids so they are valid header values
+    if let Some(value) = cursor.and_then(|cursor| HeaderValue::try_from(cursor.to_string()).ok()) {
+        headers.insert(CURSOR_HEADER, value);
+    }
+    resp
+}
@@ -7,9 +7,10 @@ use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart, Delete, ObjectI
 use aws_sdk_s3::{
     Client, config::Credentials, operation::head_object::HeadObjectError, primitives::ByteStream,
 };
+use axum::body::Body;
 use axum::extract::multipart::Field;
 use base64::Engine as _;
-use bytes::{BytesMut, buf::Buf};
+use bytes::{Bytes, BytesMut, buf::Buf};
 use cart_rs::{CartStreamManual, UncartStream};
 use data_encoding::HEXLOWER;
 use generic_array::{GenericArray, typenum::U16};
@@ -17,70 +18,403 @@ use md5::Md5;
 use sha1::{Digest, Sha1};
 use sha2::Sha256;
 use std::io::Write;
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncReadExt};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
 use tracing::{Level, event, instrument};
 use uuid::Uuid;
 use zip::unstable::write::FileOptionsExt;
 use zip::write::ZipWriter;
 
+use super::buffers::BufferPool;
+pub use super::hashes::{StandardHashers, StandardHashes};
 use super::{ApiError, Shared};
-use crate::models::ZipDownloadParams;
-use crate::{Conf, bad, unavailable};
+use crate::models::{ByteRange, ZipDownloadParams};
+use crate::{Conf, bad, internal_err_unwrapped, range_not_satisfiable, unavailable};
 
 /// A tuple of hashes (sha256, sha1, md5)
 pub type Hashes = (String, String, String);
 
-/// The standard hashes for a file
-#[derive(Debug)]
-pub struct StandardHashes {
-    /// The sha256 hash
-    pub sha256: String,
-    /// The sha1 hash
-    pub sha1: String,
-    /// The md5 hash
-    pub md5: String,
+/// The capacity of the buffers used to upload each part of a multipart upload
+pub const MULTIPART_BUFFER_SIZE: usize = 7_242_880;
+
+/// The number of uncarted chunks that can wait to be zipped before downloads wait on zipping
+const ZIP_QUEUE_DEPTH: usize = 8;
+
+/// The size of the uncarted chunks to send to be zipped
+const ZIP_CHUNK_SIZE: usize = 65_536;
+
+/// The most bytes that can be downloaded in a single uncarted range (64 MiB)
+pub const MAX_RANGE_SIZE: u64 = 67_108_864;
+
+/// The furthest offset an uncarted range can start at (1 GiB)
+///
+/// Everything before an uncarted range has to be uncarted and thrown away so
+/// this bounds how much work a single range request can cause.
+pub const MAX_RANGE_START: u64 = 1_073_741_824;
+
+/// Make sure a range doesn't end before it starts
+///
+/// # Arguments
+///
+/// * `range` - The range to check
+fn check_range(range: &ByteRange) -> Result<(), ApiError> {
+    match range.end {
+        Some(end) if end < range.start => range_not_satisfiable!(format!(
+            "Range end {end} is before its start {}",
+            range.start
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Read a range of bytes from a stream of uncarted data
+///
+/// Carted data is encrypted and compressed so we can't seek to the start of our
+/// range and have to uncart and skip over everything before it instead.
+///
+/// # Arguments
+///
+/// * `reader` - The uncarted data to read from
+/// * `range` - The range of bytes to read
+async fn read_range<R: AsyncRead + Unpin>(
+    mut reader: R,
+    range: &ByteRange,
+) -> Result<(ByteRange, Bytes), ApiError> {
+    check_range(range)?;
+    // don't uncart huge amounts of data just to throw it away
+    if range.start > MAX_RANGE_START {
+        return range_not_satisfiable!(format!(
+            "Uncarted ranges must start within the first {MAX_RANGE_START} bytes of a file"
+        ));
+    }
+    // figure out how many bytes we need to read
+    let size = match range.end {
+        Some(end) if end - range.start >= MAX_RANGE_SIZE => {
+            return range_not_satisfiable!(format!("Ranges can be at most {MAX_RANGE_SIZE} bytes"));
+        }
+        Some(end) => end - range.start + 1,
+        // open ended ranges are capped to our max range size
+        None => MAX_RANGE_SIZE,
+    };
+    // skip to the start of our range
+    let skipped =
+        tokio::io::copy(&mut (&mut reader).take(range.start), &mut tokio::io::sink()).await?;
+    // read our range
+    let mut data = Vec::with_capacity(
+        usize::try_from(size)
+            .unwrap_or(usize::MAX)
+            .min(ZIP_CHUNK_SIZE),
+    );
+    (&mut reader).take(size).read_to_end(&mut data).await?;
+    // make sure our range wasn't past the end of this file
+    if skipped < range.start || data.is_empty() {
+        return range_not_satisfiable!(format!(
+            "Range starts at {} which is past the end of this file",
+            range.start
+        ));
+    }
+    // get the range we actually read in case this file ended early
+    let read = ByteRange::new(range.start, range.start + data.len() as u64 - 1);
+    Ok((read, Bytes::from(data)))
+}
+
+/// A hasher that hashes each chunk on the blocking pool
+///
+/// Only one chunk is hashed at a time so chunks are always hashed in order and
+/// a slow hasher can't buffer an entire upload. No thread is held between chunks.
+struct BackgroundHasher<D: Digest + Send + 'static> {
+    /// Our hasher when it isn't hashing a chunk
+    idle: Option<D>,
+    /// The blocking task hashing our last chunk
+    hashing: Option<JoinHandle<D>>,
 }
 
-/// Hashes files with sha256, sha1, and md5
-pub struct StandardHashers {
+impl<D: Digest + Send + 'static> BackgroundHasher<D> {
+    /// Create a new background hasher
+    fn new() -> Self {
+        BackgroundHasher {
+            idle: Some(D::new()),
+            hashing: None,
+        }
+    }
+
+    /// Wait for our last chunk to be hashed and get our hasher back
+    async fn wait(&mut self) -> Result<D, ApiError> {
+        match self.hashing.take() {
+            Some(handle) => Ok(handle.await?),
+            // our hasher is only missing if a previous chunk failed to be hashed
+            None => self.idle.take().ok_or_else(|| {
+                internal_err_unwrapped!("A hasher was lost while hashing a chunk".to_owned())
+            }),
+        }
+    }
+
+    /// Start hashing a chunk once our last chunk has been hashed
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk` - The chunk to hash
+    async fn digest(&mut self, chunk: Bytes) -> Result<(), ApiError> {
+        let mut hasher = self.wait().await?;
+        // hash this chunk on the blocking pool so we can keep uploading
+        self.hashing = Some(tokio::task::spawn_blocking(move || {
+            hasher.update(&chunk);
+            hasher
+        }));
+        Ok(())
+    }
+
+    /// Wait for all of our chunks to be hashed and get our hash
+    async fn finish(mut self) -> Result<String, ApiError> {
+        let hasher = self.wait().await?;
+        Ok(HEXLOWER.encode(&hasher.finalize()))
+    }
+}
+
+/// Hashes files with sha256, sha1, and md5 on the blocking pool
+///
+/// This lets uploads keep streaming to s3 while they are being hashed instead
+/// of waiting for every chunk to be hashed serially.
+pub struct ParallelHashers {
     /// The sha256 hasher
-    pub sha256: Sha256,
+    sha256: BackgroundHasher<Sha256>,
     /// The sha1 hasher
-    pub sha1: Sha1,
+    sha1: BackgroundHasher<Sha1>,
     /// The md5 hasher
-    pub md5: Md5,
+    md5: BackgroundHasher<Md5>,
 }
 
-impl StandardHashers {
-    /// Add a buffer to our hashers
+impl ParallelHashers {
+    /// Start hashing a chunk with each of our hashers
+    ///
+    /// This only waits for our hashers to finish hashing the previous chunk.
     ///
     /// # Arguments
     ///
-    /// * `buff` - The buffer to digest
-    pub fn digest(&mut self, buff: &[u8]) {
-        // digest this buffer with each of our hashers
-        self.sha256.update(buff);
-        self.sha1.update(buff);
-        self.md5.update(buff);
+    /// * `chunk` - The chunk to hash
+    pub async fn digest(&mut self, chunk: &Bytes) -> Result<(), ApiError> {
+        // cloning our chunk only bumps a ref count
+        tokio::try_join!(
+            self.sha256.digest(chunk.clone()),
+            self.sha1.digest(chunk.clone()),
+            self.md5.digest(chunk.clone()),
+        )?;
+        Ok(())
     }
 
-    /// Finalize our hashers and get our hashes
-    pub fn finish(self) -> StandardHashes {
-        // build our digests
-        let sha256 = HEXLOWER.encode(&self.sha256.finalize());
-        let sha1 = HEXLOWER.encode(&self.sha1.finalize());
-        let md5 = HEXLOWER.encode(&self.md5.finalize());
-        StandardHashes { sha256, sha1, md5 }
+    /// Wait for our hashers to finish and get our hashes
+    pub async fn finish(self) -> Result<StandardHashes, ApiError> {
+        let (sha256, sha1, md5) =
+            tokio::try_join!(self.sha256.finish(), self.sha1.finish(), self.md5.finish(),)?;
+        Ok(StandardHashes { sha256, sha1, md5 })
     }
 }
 
-impl Default for StandardHashers {
+impl Default for ParallelHashers {
     /// Create default hashers
     fn default() -> Self {
-        StandardHashers {
-            sha256: Sha256::new(),
-            sha1: Sha1::new(),
-            md5: Md5::new(),
+        ParallelHashers {
+            sha256: BackgroundHasher::new(),
+            sha1: BackgroundHasher::new(),
+            md5: BackgroundHasher::new(),
+        }
+    }
+}
+
+/// A part of a file to add to a zip
+enum ZipChunk {
+    /// Start a new file in our zip with this name
+    Start(String),
+    /// The next chunk of data for the current file in our zip
+    Data(Bytes),
+}
+
+/// Streams the bytes written to a zip to the user as they are written
+struct ZipSink {
+    /// The channel to send zipped bytes to our response over
+    tx: mpsc::Sender<Result<Bytes, std::io::Error>>,
+    /// The zipped bytes that haven't been sent yet
+    buffer: BytesMut,
+}
+
+impl ZipSink {
+    /// Send any buffered zipped bytes to our response
+    fn send(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
         }
+        let chunk = self.buffer.split().freeze();
+        // our response is only dropped if the user stopped downloading our zip
+        self.tx.blocking_send(Ok(chunk)).map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::BrokenPipe, "Zip download was closed")
+        })
+    }
+}
+
+impl Write for ZipSink {
+    /// Buffer zipped bytes and send them once we have a full chunk
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - The zipped bytes to write
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= ZIP_CHUNK_SIZE {
+            self.send()?;
+        }
+        Ok(buf.len())
+    }
+
+    /// Send any remaining zipped bytes
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.send()
+    }
+}
+
+/// Builds an encrypted zip on a blocking thread and streams it as files are added to it
+struct BackgroundZipper {
+    /// The channel to send the files to zip over
+    tx: mpsc::Sender<ZipChunk>,
+    /// The channel to cut off our response with if we fail to zip our files
+    errors: mpsc::Sender<Result<Bytes, std::io::Error>>,
+    /// The thread building our zip
+    handle: JoinHandle<Result<(), ApiError>>,
+}
+
+impl BackgroundZipper {
+    /// Start building a zip on a blocking thread
+    ///
+    /// Returns our zipper and the body to stream our zip to the user with.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - The password to encrypt our zip with
+    fn spawn(password: String) -> (Self, Body) {
+        // only let a few chunks queue up so we never hold an entire uncarted file in memory
+        let (tx, mut rx) = mpsc::channel::<ZipChunk>(ZIP_QUEUE_DEPTH);
+        // only let a few zipped chunks queue up so we never hold our entire zip in memory
+        let (out_tx, out_rx) = mpsc::channel(ZIP_QUEUE_DEPTH);
+        let errors = out_tx.clone();
+        let handle = tokio::task::spawn_blocking(move || -> Result<(), ApiError> {
+            // setup our zip options
+            let opts = zip::write::SimpleFileOptions::default()
+                .with_deprecated_encryption(password.as_bytes());
+            // build our writer
+            let mut sink = ZipSink {
+                tx: out_tx,
+                buffer: BytesMut::with_capacity(ZIP_CHUNK_SIZE),
+            };
+            let mut writer = ZipWriter::new_stream(&mut sink);
+            // add each chunk to our zip until all of our files have been sent
+            while let Some(chunk) = rx.blocking_recv() {
+                match chunk {
+                    ZipChunk::Start(name) => writer.start_file(name, opts)?,
+                    ZipChunk::Data(data) => writer.write_all(&data)?,
+                }
+            }
+            // finish our zip and send the last of it
+            writer.finish()?;
+            sink.flush()?;
+            Ok(())
+        });
+        let zipper = BackgroundZipper { tx, errors, handle };
+        (zipper, Body::from_stream(ReceiverStream::new(out_rx)))
+    }
+
+    /// Uncart a carted file into our zip as it is read
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name to give this file in our zip
+    /// * `carted` - The carted file to read
+    async fn add<R: AsyncBufRead + Unpin>(&self, name: String, carted: R) -> Result<(), ApiError> {
+        // if our zip thread stopped early then finishing will return its error
+        if self.tx.send(ZipChunk::Start(name)).await.is_err() {
+            return Ok(());
+        }
+        // build our uncart stream object
+        let mut uncart_stream = UncartStream::new(carted);
+        loop {
+            // uncart the next chunk of our file
+            let mut chunk = BytesMut::with_capacity(ZIP_CHUNK_SIZE);
+            if uncart_stream.read_buf(&mut chunk).await? == 0 {
+                return Ok(());
+            }
+            // send this chunk to be zipped
+            if self.tx.send(ZipChunk::Data(chunk.freeze())).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Wait for all of our files to be zipped and streamed
+    async fn finish(self) -> Result<(), ApiError> {
+        // close our channel so our zipper knows there are no more files
+        drop(self.tx);
+        self.handle.await?
+    }
+
+    /// Download and uncart files from s3 into our zip
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client to download our files with
+    /// * `bucket` - The bucket our files are in
+    /// * `entries` - The names to give each file in the zip and the path to it in s3
+    async fn add_objects(
+        &self,
+        client: &Client,
+        bucket: &str,
+        entries: Vec<(String, String)>,
+    ) -> Result<(), ApiError> {
+        for (name, path) in entries {
+            // start downloading this file
+            let body = client
+                .get_object()
+                .bucket(bucket)
+                .key(&path)
+                .send()
+                .await?
+                .body;
+            // uncart this file into our zip as its downloaded
+            self.add(name, body.into_async_read()).await?;
+        }
+        Ok(())
+    }
+
+    /// Zip files from s3 in the background while our zip is streamed to the user
+    ///
+    /// If any file fails to be zipped then our zip is cut off so the user sees a
+    /// failed download instead of an incomplete zip.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client to download our files with
+    /// * `bucket` - The bucket our files are in
+    /// * `entries` - The names to give each file in the zip and the path to it in s3
+    fn zip_objects(self, client: Client, bucket: String, entries: Vec<(String, String)>) {
+        tokio::spawn(async move {
+            let errors = self.errors.clone();
+            // our zip isn't finished until all of our files have been added
+            let result = match self.add_objects(&client, &bucket, entries).await {
+                Ok(()) => self.finish().await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                event!(
+                    Level::ERROR,
+                    msg = "Failed to zip files",
+                    err = err.to_string()
+                );
+                // the user may have already stopped downloading our zip
+                let _ = errors
+                    .send(Err(std::io::Error::other(err.to_string())))
+                    .await;
+            }
+        });
     }
 }
 
@@ -105,47 +439,59 @@ pub struct S3 {
 impl S3 {
     /// Build all of our s3 clients
     pub fn new(config: &Conf) -> Self {
+        // build a pool of upload buffers for all of our clients to share
+        let pool = Arc::new(BufferPool::new(
+            MULTIPART_BUFFER_SIZE,
+            config.thorium.s3.upload_buffers,
+        ));
         // build our clients
-        let files = S3Client::new(
+        let files = S3Client::with_pool(
             &config.thorium.files.bucket,
             &config.thorium.files.password,
             &config.thorium.s3,
+            &pool,
         );
-        let results = S3Client::new(
+        let results = S3Client::with_pool(
             &config.thorium.results.bucket,
             // these aren't password protected so just use the files password
             &config.thorium.files.password,
             &config.thorium.s3,
+            &pool,
         );
-        let ephemeral = S3Client::new(
+        let ephemeral = S3Client::with_pool(
             &config.thorium.ephemeral.bucket,
             // these aren't password protected so just use the files password
             &config.thorium.files.password,
             &config.thorium.s3,
+            &pool,
         );
-        let reaction_cache = S3Client::new(
+        let reaction_cache = S3Client::with_pool(
             &config.thorium.reaction_cache.bucket,
             &config.thorium.reaction_cache.password,
             &config.thorium.s3,
+            &pool,
         );
-        let attachments = S3Client::new(
+        let attachments = S3Client::with_pool(
             &config.thorium.attachments.bucket,
             // these aren't password protected so just use the files password
             &config.thorium.files.password,
             &config.thorium.s3,
+            &pool,
         );
-        let repos = S3Client::new(
+        let repos = S3Client::with_pool(
             &config.thorium.repos.bucket,
             // these aren't password protected so just use the files password
             &config.thorium.files.password,
             &config.thorium.s3,
+            &pool,
         );
         // build all of the graphics s3 clients
-        let graphics = S3Client::new(
+        let graphics = S3Client::with_pool(
             &config.thorium.graphics.bucket,
             // these aren't password protected so just use the files password
             &config.thorium.files.password,
             &config.thorium.s3,
+            &pool,
         );
         S3 {
             files,
@@ -166,6 +512,8 @@ pub struct S3Client {
     password: GenericArray<u8, U16>,
     /// The test aws sdk s3 client
     pub client: Client,
+    /// The buffers to reuse when uploading parts of multipart uploads
+    pub pool: Arc<BufferPool>,
 }
 
 impl S3Client {
@@ -176,6 +524,26 @@ impl S3Client {
     /// * `config` - Thorium config options
     #[must_use]
     pub fn new(bucket: &str, password: &str, conf: &crate::conf::S3) -> Self {
+        // build a pool of upload buffers for just this client
+        let pool = Arc::new(BufferPool::new(MULTIPART_BUFFER_SIZE, conf.upload_buffers));
+        Self::with_pool(bucket, password, conf, &pool)
+    }
+
+    /// builds new s3 clients that share a pool of upload buffers
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket` - The bucket to write files too
+    /// * `password` - The password used to encrypt files
+    /// * `conf` - Thorium's s3 config options
+    /// * `pool` - The pool of upload buffers to use
+    #[must_use]
+    pub fn with_pool(
+        bucket: &str,
+        password: &str,
+        conf: &crate::conf::S3,
+        pool: &Arc<BufferPool>,
+    ) -> Self {
         // build our generic array
         let gen_array: GenericArray<u8, U16> =
             GenericArray::clone_from_slice(&password.as_bytes()[..16]);
@@ -200,9 +568,52 @@ impl S3Client {
             bucket: bucket.to_owned(),
             password: gen_array,
             client,
+            pool: pool.clone(),
         }
     }
 
+    /// Upload a single part of a multipart upload
+    ///
+    /// The buffered bytes are handed to s3 without being copied and the buffer's
+    /// memory is reclaimed once s3 is done with them.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to write this object to in s3
+    /// * `upload_id` - The id of the multipart upload being used
+    /// * `part_num` - The number of the part to upload
+    /// * `buffer` - The buffer containing the bytes for this part
+    async fn upload_part(
+        &self,
+        path: &str,
+        upload_id: &str,
+        part_num: i32,
+        buffer: &mut BytesMut,
+    ) -> Result<CompletedPart, ApiError> {
+        // take the bytes for this part out of our buffer
+        let body = ByteStream::from(SdkBody::from(buffer.split().freeze()));
+        // write this part to s3
+        let part = self
+            .client
+            .upload_part()
+            .bucket(&self.bucket)
+            .key(path)
+            .upload_id(upload_id)
+            .body(body)
+            .part_number(part_num)
+            .send()
+            .await?;
+        // reclaim our buffer's memory now that s3 has dropped this part if we can
+        // if we can't then our buffer will just allocate again when it needs more space
+        let _ = buffer.try_reclaim(self.pool.buffer_size());
+        // build the completed part to finish our upload with
+        let completed = CompletedPart::builder()
+            .e_tag(part.e_tag.unwrap_or_default())
+            .part_number(part_num)
+            .build();
+        Ok(completed)
+    }
+
     /// Check if a file exists in s3 by path
     ///
     /// # Arguments
@@ -301,43 +712,30 @@ impl S3Client {
         mut field: Field<'a>,
     ) -> Result<StandardHashes, ApiError> {
         // init our cart streamer and hashers
-        let mut cart = CartStreamManual::new(&self.password, 7_242_880)?;
-        let mut hashers = StandardHashers::default();
+        let mut cart = CartStreamManual::new(&self.password, MULTIPART_BUFFER_SIZE)?;
+        // get a buffer to upload each part of our carted file with
+        let mut buffer = self.pool.get();
+        let mut hashers = ParallelHashers::default();
         // track what part number we are on
         let mut part_num = 1;
         // keep a list of parts we have uploaded
         let mut parts = Vec::with_capacity(10);
         // stream this fields data through our hashers, cart, and to s3
         while let Some(raw) = field.chunk().await? {
-            // pass this chunk through our hashers
-            hashers.digest(&raw);
+            // pass this chunk to our hashers so they can hash it while we upload
+            hashers.digest(&raw).await?;
             // add this buffer to our cart streamer
             if cart.next_bytes(raw)? {
                 // keep processing these bytes until they are finished
                 while cart.process()? {
                     // if our input buffer is full then pack
                     if cart.ready() >= 5_242_880 {
-                        // get the bytes we are ready to write to s3
-                        let writable = cart.carted_bytes();
-                        // pack our entire input buffer
-                        let carted = ByteStream::from(SdkBody::from(writable));
-                        // write this buffer to s3
-                        let part = self
-                            .client
-                            .upload_part()
-                            .bucket(&self.bucket)
-                            .key(path)
-                            .upload_id(upload_id)
-                            .body(carted)
-                            .part_number(part_num)
-                            .send()
-                            .await?;
-                        // add this chunk to our parts list
+                        // copy the bytes we are ready to write to s3 into our pooled buffer
+                        buffer.extend_from_slice(cart.carted_bytes());
+                        // write this buffer to s3 and add it to our parts list
                         parts.push(
-                            CompletedPart::builder()
-                                .e_tag(part.e_tag.unwrap_or_default())
-                                .part_number(part_num)
-                                .build(),
+                            self.upload_part(path, upload_id, part_num, &mut buffer)
+                                .await?,
                         );
                         // consume the bytes we have written to s3
                         cart.consume();
@@ -347,27 +745,12 @@ impl S3Client {
                 }
             }
         }
-        // finish carting our file
-        let writable = cart.finish()?;
-        // finish our carted file
-        let carted = ByteStream::from(SdkBody::from(writable));
-        // write this final buffer to s3
-        let part = self
-            .client
-            .upload_part()
-            .bucket(&self.bucket)
-            .key(path)
-            .upload_id(upload_id)
-            .body(carted)
-            .part_number(part_num)
-            .send()
-            .await?;
-        // add this chunk to our parts list
+        // finish carting our file and copy the last of it into our pooled buffer
+        buffer.extend_from_slice(cart.finish()?);
+        // write this final buffer to s3 and add it to our parts list
         parts.push(
-            CompletedPart::builder()
-                .e_tag(part.e_tag.unwrap_or_default())
-                .part_number(part_num)
-                .build(),
+            self.upload_part(path, upload_id, part_num, &mut buffer)
+                .await?,
         );
         // build our complete multipart upload object
         let completed_parts = CompletedMultipartUpload::builder()
@@ -382,7 +765,8 @@ impl S3Client {
             .upload_id(upload_id)
             .send()
             .await?;
-        Ok(hashers.finish())
+        // wait for the last of our chunks to be hashed
+        hashers.finish().await
     }
 
     /// Stream a file into s3 while hashing and carting it
@@ -452,44 +836,31 @@ impl S3Client {
         upload_id: &str,
         mut field: Field<'a>,
     ) -> Result<String, ApiError> {
-        // init our cart streamer and hashers
-        let mut cart = CartStreamManual::new(&self.password, 7_242_880)?;
-        let mut sha256 = Sha256::new();
+        // init our cart streamer and hasher
+        let mut cart = CartStreamManual::new(&self.password, MULTIPART_BUFFER_SIZE)?;
+        // get a buffer to upload each part of our carted file with
+        let mut buffer = self.pool.get();
+        let mut sha256 = BackgroundHasher::<Sha256>::new();
         // track what part number we are on
         let mut part_num = 1;
         // keep a list of parts we have uploaded
         let mut parts = Vec::with_capacity(10);
         // stream this fields data through our hashers, cart, and to s3
         while let Some(raw) = field.chunk().await? {
-            // pass this chunk through our hashers
-            sha256.update(&raw);
+            // pass this chunk to our hasher so it can hash it while we upload
+            sha256.digest(raw.clone()).await?;
             // add this buffer to our cart streamer
             if cart.next_bytes(raw)? {
                 // keep processing these bytes until they are finished
                 while cart.process()? {
                     // if our input buffer is full then pack
                     if cart.ready() >= 5_242_880 {
-                        // get the bytes we are ready to write to s3
-                        let writable = cart.carted_bytes();
-                        // pack our entire input buffer
-                        let carted = ByteStream::from(SdkBody::from(writable));
-                        // write this buffer to s3
-                        let part = self
-                            .client
-                            .upload_part()
-                            .bucket(&self.bucket)
-                            .key(path)
-                            .upload_id(upload_id)
-                            .body(carted)
-                            .part_number(part_num)
-                            .send()
-                            .await?;
-                        // add this chunk to our parts list
+                        // copy the bytes we are ready to write to s3 into our pooled buffer
+                        buffer.extend_from_slice(cart.carted_bytes());
+                        // write this buffer to s3 and add it to our parts list
                         parts.push(
-                            CompletedPart::builder()
-                                .e_tag(part.e_tag.unwrap_or_default())
-                                .part_number(part_num)
-                                .build(),
+                            self.upload_part(path, upload_id, part_num, &mut buffer)
+                                .await?,
                         );
                         // consume the bytes we have written to s3
                         cart.consume();
@@ -499,27 +870,12 @@ impl S3Client {
                 }
             }
         }
-        // finish carting our file
-        let writable = cart.finish()?;
-        // finish our carted file
-        let carted = ByteStream::from(SdkBody::from(writable));
-        // write this final buffer to s3
-        let part = self
-            .client
-            .upload_part()
-            .bucket(&self.bucket)
-            .key(path)
-            .upload_id(upload_id)
-            .body(carted)
-            .part_number(part_num)
-            .send()
-            .await?;
-        // add this chunk to our parts list
+        // finish carting our file and copy the last of it into our pooled buffer
+        buffer.extend_from_slice(cart.finish()?);
+        // write this final buffer to s3 and add it to our parts list
         parts.push(
-            CompletedPart::builder()
-                .e_tag(part.e_tag.unwrap_or_default())
-                .part_number(part_num)
-                .build(),
+            self.upload_part(path, upload_id, part_num, &mut buffer)
+                .await?,
         );
         // build our complete multipart upload object
         let completed_parts = CompletedMultipartUpload::builder()
@@ -534,8 +890,8 @@ impl S3Client {
             .upload_id(upload_id)
             .send()
             .await?;
-        // get our final sha256 hash
-        Ok(HEXLOWER.encode(&sha256.finalize()))
+        // wait for the last of our chunks to be hashed and get our sha256
+        sha256.finish().await
     }
 
     /// Stream a file into s3 while getting its sha256 and carting it
@@ -610,7 +966,9 @@ impl S3Client {
         mut field: Field<'a>,
     ) -> Result<(), ApiError> {
         // init our cart streamer and hashers
-        let mut cart = CartStreamManual::new(&self.password, 7_242_880)?;
+        let mut cart = CartStreamManual::new(&self.password, MULTIPART_BUFFER_SIZE)?;
+        // get a buffer to upload each part of our carted file with
+        let mut buffer = self.pool.get();
         // track what part number we are on
         let mut part_num = 1;
         // keep a list of parts we have uploaded
@@ -623,27 +981,12 @@ impl S3Client {
                 while cart.process()? {
                     // if our input buffer is full then pack
                     if cart.ready() >= 5_242_880 {
-                        // get the bytes we are ready to write to s3
-                        let writable = cart.carted_bytes();
-                        // pack our entire input buffer
-                        let carted = ByteStream::from(SdkBody::from(writable));
-                        // write this buffer to s3
-                        let part = self
-                            .client
-                            .upload_part()
-                            .bucket(&self.bucket)
-                            .key(path)
-                            .upload_id(upload_id)
-                            .body(carted)
-                            .part_number(part_num)
-                            .send()
-                            .await?;
-                        // add this chunk to our parts list
+                        // copy the bytes we are ready to write to s3 into our pooled buffer
+                        buffer.extend_from_slice(cart.carted_bytes());
+                        // write this buffer to s3 and add it to our parts list
                         parts.push(
-                            CompletedPart::builder()
-                                .e_tag(part.e_tag.unwrap_or_default())
-                                .part_number(part_num)
-                                .build(),
+                            self.upload_part(path, upload_id, part_num, &mut buffer)
+                                .await?,
                         );
                         // consume the bytes we have written to s3
                         cart.consume();
@@ -653,27 +996,12 @@ impl S3Client {
                 }
             }
         }
-        // finish carting our file
-        let writable = cart.finish()?;
-        // finish our carted file
-        let carted = ByteStream::from(SdkBody::from(writable));
-        // write this final buffer to s3
-        let part = self
-            .client
-            .upload_part()
-            .bucket(&self.bucket)
-            .key(path)
-            .upload_id(upload_id)
-            .body(carted)
-            .part_number(part_num)
-            .send()
-            .await?;
-        // add this chunk to our parts list
+        // finish carting our file and copy the last of it into our pooled buffer
+        buffer.extend_from_slice(cart.finish()?);
+        // write this final buffer to s3 and add it to our parts list
         parts.push(
-            CompletedPart::builder()
-                .e_tag(part.e_tag.unwrap_or_default())
-                .part_number(part_num)
-                .build(),
+            self.upload_part(path, upload_id, part_num, &mut buffer)
+                .await?,
         );
         // build our complete multipart upload object
         let completed_parts = CompletedMultipartUpload::builder()
@@ -755,59 +1083,27 @@ impl S3Client {
         let mut part_num = 1;
         // keep a list of parts we have uploaded
         let mut parts = Vec::with_capacity(1);
-        // build our buffer so we can have at least 5 mebibytes of chunks to send
-        let mut stream = BytesMut::with_capacity(7_242_880);
+        // get a buffer so we can have at least 5 mebibytes of chunks to send
+        let mut stream = self.pool.get();
         // stream this fields data through our hashers, cart, and to s3
         while let Some(raw) = field.chunk().await? {
             // add our chunk to our stream buffer
             stream.extend_from_slice(&raw);
             // add this buffer to our cart streamer
             if stream.remaining() >= 5_242_880 {
-                // pack our entire input buffer
-                let carted = ByteStream::from(SdkBody::from(&stream[..]));
-                // write this buffer to s3
-                let part = self
-                    .client
-                    .upload_part()
-                    .bucket(&self.bucket)
-                    .key(path)
-                    .upload_id(upload_id)
-                    .body(carted)
-                    .part_number(part_num)
-                    .send()
-                    .await?;
-                // add this chunk to our parts list
+                // write this buffer to s3 and add it to our parts list
                 parts.push(
-                    CompletedPart::builder()
-                        .e_tag(part.e_tag.unwrap_or_default())
-                        .part_number(part_num)
-                        .build(),
+                    self.upload_part(path, upload_id, part_num, &mut stream)
+                        .await?,
                 );
-                // reset our packable and writable number of bytes to 0
-                stream.clear();
                 // increment our part number
                 part_num += 1;
             }
         }
-        // finish our stream
-        let carted = ByteStream::from(SdkBody::from(&stream[..]));
-        // write this buffer to s3
-        let part = self
-            .client
-            .upload_part()
-            .bucket(&self.bucket)
-            .key(path)
-            .upload_id(upload_id)
-            .body(carted)
-            .part_number(part_num)
-            .send()
-            .await?;
-        // add this chunk to our parts list
+        // write the rest of our stream to s3 and add it to our parts list
         parts.push(
-            CompletedPart::builder()
-                .e_tag(part.e_tag.unwrap_or_default())
-                .part_number(part_num)
-                .build(),
+            self.upload_part(path, upload_id, part_num, &mut stream)
+                .await?,
         );
         // build our complete multipart upload object
         let completed_parts = CompletedMultipartUpload::builder()
@@ -991,22 +1287,70 @@ impl S3Client {
         Ok(output)
     }
 
-    /// download a file from s3 and convert it to an encrypted zip
+    /// Download a range of bytes from a carted file in s3
     ///
-    /// This is not near as efficient as using CaRT and should not be used for large files.
+    /// Ranges are of the carted object as it is stored in s3 and are passed
+    /// straight through to s3.
     ///
     /// # Arguments
     ///
     /// * `path` - The path to an object in s3
-    #[instrument(name = "S3Client::download_as_zip", skip(self, shared), err(Debug))]
-    pub async fn download_as_zip(
+    /// * `range` - The range of carted bytes to download
+    #[instrument(name = "S3Client::download_range", skip(self), err(Debug))]
+    pub async fn download_range(
         &self,
         path: &str,
-        sha256: &str,
-        params: ZipDownloadParams,
-        shared: &Shared,
-    ) -> Result<Vec<u8>, ApiError> {
-        // start downloading this file and stream it to the user
+        range: &ByteRange,
+    ) -> Result<(String, ByteStream), ApiError> {
+        check_range(range)?;
+        // start downloading just this range of our file
+        let resp = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(path)
+            .range(range.header())
+            .send()
+            .await;
+        let output = match resp {
+            Ok(output) => output,
+            // s3 tells us if our range starts past the end of this object
+            Err(err)
+                if err
+                    .raw_response()
+                    .is_some_and(|resp| resp.status().as_u16() == 416) =>
+            {
+                return range_not_satisfiable!(format!(
+                    "Range starts at {} which is past the end of this file",
+                    range.start
+                ));
+            }
+            Err(err) => return Err(ApiError::from(err)),
+        };
+        // get the range s3 actually returned in case this file ended early
+        let content_range = match output.content_range {
+            Some(content_range) => content_range,
+            None => range.content_range(),
+        };
+        Ok((content_range, output.body))
+    }
+
+    /// Download a range of bytes from the uncarted contents of a file in s3
+    ///
+    /// Carted files can't be seeked so this streams and uncarts everything
+    /// before our range too instead of passing our range to s3.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to an object in s3
+    /// * `range` - The range of uncarted bytes to download
+    #[instrument(name = "S3Client::download_uncarted_range", skip(self), err(Debug))]
+    pub async fn download_uncarted_range(
+        &self,
+        path: &str,
+        range: &ByteRange,
+    ) -> Result<(ByteRange, Bytes), ApiError> {
+        // start downloading this file
         let body = self
             .client
             .get_object()
@@ -1015,33 +1359,57 @@ impl S3Client {
             .send()
             .await?
             .body;
-        // get the password to use
-        let password = params.get_password(shared).as_bytes();
-        // setup our zip options
-        let opts = zip::write::SimpleFileOptions::default().with_deprecated_encryption(password);
-        // build our writer
-        let mut writer = ZipWriter::new(std::io::Cursor::new(vec![]));
-        // start our file
-        writer.start_file(sha256, opts)?;
-        // build our uncart stream object
-        let mut uncart_stream = UncartStream::new(body.into_async_read());
-        // build a vector to store our entire file that defaults to 1 mebibyte in size
-        let mut uncarted = Vec::with_capacity(1_048_576);
-        // uncart the entire file
-        tokio::io::copy(&mut uncart_stream, &mut uncarted).await?;
-        // spawn this task in a tokio task and wait for it to complete
-        tokio::task::spawn_blocking(move || {
-            // zip this file
-            match writer.write_all(&uncarted) {
-                // get our zipped data
-                Ok(_) => match writer.finish() {
-                    Ok(zipped) => Ok(zipped.into_inner()),
-                    Err(err) => Err(ApiError::from(err)),
-                },
-                Err(err) => Err(ApiError::from(err)),
-            }
-        })
-        .await?
+        // uncart our file as its downloaded and read our range from it
+        let uncart_stream = UncartStream::new(body.into_async_read());
+        read_range(uncart_stream, range).await
+    }
+
+    /// download a file from s3 and convert it to an encrypted zip
+    ///
+    /// This is not near as efficient as using CaRT and should not be used for large files.
+    /// Our file is uncarted and zipped as it is streamed from s3 and our zip is streamed
+    /// to the user as it is built.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to an object in s3
+    /// * `sha256` - The name to give this file in the zip
+    /// * `params` - The params to use when building this zip
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "S3Client::download_as_zip", skip(self, shared))]
+    pub fn download_as_zip(
+        &self,
+        path: &str,
+        sha256: &str,
+        params: ZipDownloadParams,
+        shared: &Shared,
+    ) -> Body {
+        let entries = vec![(sha256.to_owned(), path.to_owned())];
+        self.download_many_as_zip(entries, params, shared)
+    }
+
+    /// download multiple files from s3 and bundle them into a single encrypted zip
+    ///
+    /// Like `download_as_zip` our zip is streamed to the user as it is built. If
+    /// any file fails to download then the response body is cut off.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - The names to give each file in the zip and the path to it in s3
+    /// * `params` - The params to use when building this zip
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "S3Client::download_many_as_zip", skip(self, shared))]
+    pub fn download_many_as_zip(
+        &self,
+        entries: Vec<(String, String)>,
+        params: ZipDownloadParams,
+        shared: &Shared,
+    ) -> Body {
+        // start zipping on a blocking thread with the password to use
+        let (zipper, body) = BackgroundZipper::spawn(params.get_password(shared).clone());
+        // download and uncart each of our files into our zip in the background
+        zipper.zip_objects(self.client.clone(), self.bucket.clone(), entries);
+        body
     }
 
     /// deletes a file from s3
@@ -1166,3 +1534,216 @@ impl GraphicsS3Client {
         Self { client }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use bytes::Bytes;
+    use cart_rs::{CartStream, UncartStream};
+    use futures::StreamExt;
+    use generic_array::GenericArray;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, RngCore, SeedableRng};
+    use std::io::Read;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncRead, BufReader, ReadBuf};
+
+    use super::{
+        BackgroundZipper, ByteRange, MAX_RANGE_SIZE, MAX_RANGE_START, ParallelHashers,
+        StandardHashers, read_range,
+    };
+
+    /// Generates a large file on the fly and counts how much of it has been read
+    struct SyntheticReader {
+        /// The rng to generate data with
+        rng: SmallRng,
+        /// The number of bytes left to generate
+        remaining: usize,
+        /// The number of bytes that have been generated so far
+        generated: Arc<AtomicU64>,
+    }
+
+    impl AsyncRead for SyntheticReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            let len = buf.remaining().min(self.remaining);
+            // generate incompressible data so our zip is about as large as our files
+            self.rng.fill_bytes(buf.initialize_unfilled_to(len));
+            buf.advance(len);
+            self.remaining -= len;
+            self.generated.fetch_add(len as u64, Ordering::Relaxed);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn parallel_hashes_match() {
+        let mut rng = SmallRng::seed_from_u64(1550);
+        // send enough chunks that our hashers have to catch up
+        let chunks = (0..64)
+            .map(|_| {
+                let mut chunk = vec![0; rng.random_range(0..16_384)];
+                rng.fill_bytes(&mut chunk);
+                Bytes::from(chunk)
+            })
+            .collect::<Vec<_>>();
+        // hash our chunks serially and in parallel
+        let mut serial = StandardHashers::default();
+        let mut parallel = ParallelHashers::default();
+        for chunk in &chunks {
+            serial.digest(chunk);
+            parallel.digest(chunk).await.unwrap();
+        }
+        let serial = serial.finish();
+        let parallel = parallel.finish().await.unwrap();
+        assert_eq!(parallel.sha256, serial.sha256);
+        assert_eq!(parallel.sha1, serial.sha1);
+        assert_eq!(parallel.md5, serial.md5);
+    }
+
+    #[tokio::test]
+    async fn parallel_hashes_empty() {
+        // hashing nothing should still give us the hashes of an empty file
+        let hashes = ParallelHashers::default().finish().await.unwrap();
+        assert_eq!(
+            hashes.sha256,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(hashes.sha1, "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hashes.md5, "d41d8cd98f00b204e9800998ecf8427e");
+    }
+
+    #[tokio::test]
+    async fn range_from_cart() {
+        let data = (0..200_000_u32).map(|i| i as u8).collect::<Vec<u8>>();
+        // cart our data and read a range from it as it is uncarted
+        let key = GenericArray::clone_from_slice(b"corn_and_soybean");
+        let carted = CartStream::new(BufReader::new(&data[..]), &key).unwrap();
+        let uncarted = UncartStream::new(BufReader::new(carted));
+        let (read, bytes) = read_range(uncarted, &ByteRange::new(150_000, 150_999))
+            .await
+            .unwrap();
+        assert_eq!(read, ByteRange::new(150_000, 150_999));
+        assert_eq!(&bytes[..], &data[150_000..151_000]);
+    }
+
+    #[tokio::test]
+    async fn range_past_end() {
+        let data = vec![7; 100];
+        // ranges that go past the end of our file are cut short
+        let (read, bytes) = read_range(&data[..], &ByteRange::new(90, 200))
+            .await
+            .unwrap();
+        assert_eq!(read, ByteRange::new(90, 99));
+        assert_eq!(bytes.len(), 10);
+        // open ended ranges read to the end of our file
+        let open = ByteRange {
+            start: 50,
+            end: None,
+        };
+        let (read, bytes) = read_range(&data[..], &open).await.unwrap();
+        assert_eq!(read, ByteRange::new(50, 99));
+        assert_eq!(bytes.len(), 50);
+    }
+
+    #[tokio::test]
+    async fn range_out_of_bounds() {
+        let data = vec![7; 100];
+        // ranges that start at or past the end of our file can't be satisfied
+        for range in [
+            ByteRange::new(100, 150),
+            ByteRange::new(1_000, 1_500),
+            ByteRange::new(10, 5),
+            ByteRange::new(0, MAX_RANGE_SIZE),
+            ByteRange::new(MAX_RANGE_START + 1, MAX_RANGE_START + 10),
+        ] {
+            let err = read_range(&data[..], &range).await.unwrap_err();
+            assert_eq!(err.code, StatusCode::RANGE_NOT_SATISFIABLE);
+        }
+    }
+
+    #[tokio::test]
+    async fn zip_round_trip() {
+        let key = GenericArray::clone_from_slice(b"corn_and_soybean");
+        let files = [
+            ("corn", vec![1; 100_000]),
+            ("soybean", (0..300_000_u32).map(|i| i as u8).collect()),
+            ("empty", Vec::new()),
+        ];
+        // zip our carted files while our zip is streamed
+        let (zipper, body) = BackgroundZipper::spawn("corn".to_owned());
+        let add = async {
+            for (name, data) in &files {
+                let carted = CartStream::new(BufReader::new(&data[..]), &key).unwrap();
+                zipper
+                    .add((*name).to_owned(), BufReader::new(carted))
+                    .await
+                    .unwrap();
+            }
+            zipper.finish().await.unwrap();
+        };
+        let ((), zip) = tokio::join!(add, axum::body::to_bytes(body, usize::MAX));
+        // our streamed zip should contain all of our uncarted files
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(zip.unwrap())).unwrap();
+        assert_eq!(archive.len(), files.len());
+        for (name, data) in &files {
+            let mut unzipped = Vec::new();
+            archive
+                .by_name_decrypt(name, b"corn")
+                .unwrap()
+                .read_to_end(&mut unzipped)
+                .unwrap();
+            assert_eq!(&unzipped, data);
+        }
+    }
+
+    #[tokio::test]
+    async fn zip_bounded_memory() {
+        const FILE_SIZE: usize = 8 * 1024 * 1024;
+        const FILES: usize = 4;
+        let key = GenericArray::clone_from_slice(b"corn_and_soybean");
+        let generated = Arc::new(AtomicU64::new(0));
+        let (zipper, body) = BackgroundZipper::spawn("corn".to_owned());
+        // cart and zip our synthetic files without any of them ever existing in full
+        let adder = tokio::spawn({
+            let generated = generated.clone();
+            async move {
+                for i in 0..FILES {
+                    let reader = SyntheticReader {
+                        rng: SmallRng::seed_from_u64(i as u64),
+                        remaining: FILE_SIZE,
+                        generated: generated.clone(),
+                    };
+                    let carted = CartStream::new(BufReader::new(reader), &key).unwrap();
+                    zipper
+                        .add(format!("corn-{i}"), BufReader::new(carted))
+                        .await
+                        .unwrap();
+                }
+                zipper.finish().await.unwrap();
+            }
+        });
+        // stream our zip while tracking how much data is buffered between our files and the user
+        let mut stream = body.into_data_stream();
+        let mut zipped = 0;
+        let mut peak_buffered = 0;
+        while let Some(chunk) = stream.next().await {
+            zipped += chunk.unwrap().len() as u64;
+            let buffered = generated.load(Ordering::Relaxed).saturating_sub(zipped);
+            peak_buffered = peak_buffered.max(buffered);
+        }
+        adder.await.unwrap();
+        assert!(zipped >= (FILE_SIZE * FILES) as u64);
+        // we should never be holding our entire zip in memory
+        assert!(
+            peak_buffered < 2 * FILE_SIZE as u64,
+            "buffered {peak_buffered} bytes while zipping"
+        );
+    }
+}
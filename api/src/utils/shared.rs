@@ -8,10 +8,16 @@ use lettre::transport::smtp::authentication::Credentials;
 use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
 use regex::RegexSet;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs;
 
+use super::MetricsRegistry;
+use super::backpressure::LogLimiter;
+use super::coalesce::Coalescer;
+use super::lru::LruCache;
 use super::s3::S3;
 use crate::info;
+use crate::models::{Group, Image, Pipeline};
 use crate::models::backends::setup::{self, Scylla};
 use crate::utils::ApiError;
 use crate::{conf::Conf, error};
@@ -146,6 +152,20 @@ pub struct Shared {
     pub email: Option<EmailClient>,
     /// A site banner for displaying messages to UI users
     pub banner: String,
+    /// The metrics tracked by this API instance
+    pub metrics: MetricsRegistry,
+    /// Bounds how many bytes of stage logs can be written at once
+    pub stage_logs: Arc<LogLimiter>,
+    /// The groups recently used to authorize requests
+    pub groups: LruCache<Group>,
+    /// The images recently read from the backend
+    pub image_cache: LruCache<Image>,
+    /// The pipelines recently read from the backend
+    pub pipeline_cache: LruCache<Pipeline>,
+    /// Shares reads of the same image and the cache generation they were read at
+    pub images: Coalescer<(Image, u64)>,
+    /// Shares reads of the same pipeline and the cache generation they were read at
+    pub pipelines: Coalescer<(Pipeline, u64)>,
 }
 
 impl Shared {
@@ -174,6 +194,21 @@ impl Shared {
         let banner = fs::read_to_string("banner.txt")
             .await
             .unwrap_or("Add your custom Thorium banner here!".to_owned());
+        // bound how many stage logs we can be writing at once
+        let stage_logs = Arc::new(LogLimiter::new(
+            config.thorium.stage_logs.max_in_flight,
+            Duration::from_secs(config.thorium.stage_logs.max_wait),
+        ));
+        // cache groups so we don't have to get them for every request
+        let groups = LruCache::new(
+            config.thorium.group_cache.capacity,
+            Duration::from_secs(config.thorium.group_cache.ttl),
+        );
+        // cache images and pipelines so the same definitions aren't read for every job
+        let capacity = config.thorium.definition_cache.capacity;
+        let ttl = Duration::from_secs(config.thorium.definition_cache.ttl);
+        let image_cache = LruCache::new(capacity, ttl);
+        let pipeline_cache = LruCache::new(capacity, ttl);
         Shared {
             config,
             redis,
@@ -182,6 +217,13 @@ impl Shared {
             elastic,
             email,
             banner,
+            metrics: MetricsRegistry::default(),
+            stage_logs,
+            groups,
+            image_cache,
+            pipeline_cache,
+            images: Coalescer::default(),
+            pipelines: Coalescer::default(),
         }
     }
 }
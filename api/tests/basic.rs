@@ -1,6 +1,7 @@
 //! Tests the basic routes in Thorium
 
 use thorium::models::system::DEFAULT_IFF;
+use thorium::test_utilities::generators;
 use thorium::{Error, is, test_utilities};
 
 #[tokio::test]
@@ -25,6 +26,52 @@ async fn health() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn capabilities() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // get the capabilities of this server
+    let capabilities = client.basic.capabilities().await?;
+    // make sure the expected features are enabled
+    is!(capabilities.supports("reaction-trees"), true);
+    is!(capabilities.supports("lineage-trees"), true);
+    is!(capabilities.supports("ordered-kwargs"), true);
+    is!(capabilities.supports("not-a-real-feature"), false);
+    // make sure our version and limits were set
+    is!(capabilities.version.is_empty(), false);
+    is!(capabilities.limits.max_reaction_tree_depth > 0, true);
+    is!(capabilities.limits.max_reaction_tree_nodes > 0, true);
+    is!(capabilities.limits.max_lineage_nodes > 0, true);
+    is!(capabilities.limits.max_tree_nodes > 0, true);
+    // our test config limits uploads to 64 MiB
+    is!(capabilities.limits.max_upload, Some(64));
+    Ok(())
+}
+
+#[tokio::test]
+async fn ping() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // ping the API and make sure we got a sane latency back
+    let latency = client.basic.ping().await?;
+    is!(latency < std::time::Duration::from_secs(30), true);
+    Ok(())
+}
+
+#[tokio::test]
+async fn whoami() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // get a summary of our user and the full info for our user
+    let summary = client.basic.whoami().await?;
+    let info = client.users.info().await?;
+    // make sure our summary matches our current user
+    is!(summary.username, info.username);
+    is!(summary.groups, info.groups);
+    is!(summary.admin, true);
+    Ok(())
+}
+
 // Sync tests
 
 #[cfg(all(feature = "sync", not(feature = "python")))]
@@ -38,3 +85,151 @@ fn health_blocking() -> Result<(), Error> {
     is!(health, true);
     Ok(())
 }
+
+#[tokio::test]
+async fn unknown_route() -> Result<(), Error> {
+    // get admin client to make sure the API is running
+    test_utilities::admin_client().await?;
+    // build the url to a route that does not exist
+    let url = format!(
+        "http://{}:{}/api/not/a/real/route",
+        test_utilities::CONF.thorium.interface,
+        test_utilities::CONF.thorium.port
+    );
+    // request our unknown route
+    let resp = reqwest::Client::new().get(&url).send().await?;
+    // make sure we got a 404 with a json body back
+    is!(resp.status(), reqwest::StatusCode::NOT_FOUND);
+    let content_type = resp.headers().get(reqwest::header::CONTENT_TYPE);
+    is!(
+        content_type.and_then(|value| value.to_str().ok()),
+        Some("application/json")
+    );
+    // make sure our error body has a code and a message
+    let body: serde_json::Value = resp.json().await?;
+    is!(body["code"], "not_found");
+    is!(body["error"].is_string(), true);
+    Ok(())
+}
+
+/// Get the value of a metric from a Prometheus scrape
+///
+/// # Arguments
+///
+/// * `scrape` - The scraped metrics
+/// * `metric` - The metric name and labels to get
+fn metric(scrape: &str, metric: &str) -> f64 {
+    scrape
+        .lines()
+        .filter_map(|line| line.strip_prefix(metric))
+        .find_map(|value| value.strip_prefix(' '))
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Scrape the metrics for the API
+async fn scrape() -> Result<String, Error> {
+    // build the url to our metrics
+    let url = format!(
+        "http://{}:{}/metrics",
+        test_utilities::CONF.thorium.interface,
+        test_utilities::CONF.thorium.port
+    );
+    // get our metrics with the token from our config
+    let token = test_utilities::CONF.thorium.metrics.token.as_deref();
+    let resp = reqwest::Client::new()
+        .get(&url)
+        .bearer_auth(token.unwrap_or_default())
+        .send()
+        .await?;
+    is!(resp.status(), reqwest::StatusCode::OK);
+    Ok(resp.text().await?)
+}
+
+#[tokio::test]
+async fn metrics() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // get our metrics before sending any requests
+    let ping =
+        "thorium_http_requests_total{method=\"GET\",route=\"/api/basic/ping\",status=\"204\"}";
+    let reactions = "thorium_reactions_created_total";
+    let before = scrape().await?;
+    // ping the API a few times and create a reaction
+    for _ in 0..3 {
+        client.basic.ping().await?;
+    }
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    let react_req = generators::gen_reaction(&group, &pipe, None);
+    client.reactions.create(&react_req).await?;
+    // make sure our counters were incremented
+    let after = scrape().await?;
+    is!(metric(&after, ping) >= metric(&before, ping) + 3.0, true);
+    is!(
+        metric(&after, reactions) >= metric(&before, reactions) + 1.0,
+        true
+    );
+    // make sure our job queue depths are exposed
+    let queued = "thorium_job_queue_depth{scaler=\"K8s\",state=\"queued\"}";
+    is!(after.contains(queued), true);
+    Ok(())
+}
+
+#[tokio::test]
+async fn scylla_metrics() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    let requests = "thorium_scylla_requests_total";
+    let before = scrape().await?;
+    // upload a few files so we send some requests to Scylla
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    generators::samples(&group, 3, &client).await?;
+    // make sure our Scylla requests were counted
+    let after = scrape().await?;
+    is!(
+        metric(&after, requests) >= metric(&before, requests) + 3.0,
+        true
+    );
+    // make sure we have an open connection pool to Scylla
+    is!(metric(&after, "thorium_scylla_nodes") >= 1.0, true);
+    is!(metric(&after, "thorium_scylla_connections") >= 1.0, true);
+    is!(after.contains("thorium_scylla_requests_in_flight"), true);
+    // our testing config uses the default pool size of one connection per shard
+    is!(
+        after.contains("thorium_scylla_pool_size{per=\"shard\"} 1"),
+        true
+    );
+    is!(
+        after.contains("thorium_scylla_request_timeouts_total"),
+        true
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn metrics_requires_token() -> Result<(), Error> {
+    // get admin client to make sure the API is running
+    test_utilities::admin_client().await?;
+    // make sure our test config requires a token for metrics
+    is!(test_utilities::CONF.thorium.metrics.token.is_some(), true);
+    // build the url to our metrics
+    let url = format!(
+        "http://{}:{}/metrics",
+        test_utilities::CONF.thorium.interface,
+        test_utilities::CONF.thorium.port
+    );
+    // try to get our metrics without a token and with the wrong token
+    let resp = reqwest::Client::new().get(&url).send().await?;
+    is!(resp.status(), reqwest::StatusCode::UNAUTHORIZED);
+    let resp = reqwest::Client::new()
+        .get(&url)
+        .bearer_auth("NotTheToken")
+        .send()
+        .await?;
+    is!(resp.status(), reqwest::StatusCode::UNAUTHORIZED);
+    Ok(())
+}
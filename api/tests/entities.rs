@@ -0,0 +1,876 @@
+//! Tests the entities routes in Thorium
+
+use chrono::{Duration, Utc};
+use futures::TryStreamExt;
+use thorium::models::{
+    AssociationKind, AssociationListOpts, AssociationRequest, AssociationTarget, CriticalSector,
+    DeviceEntityRequest, DeviceListOpts, Directionality, EntityDuplicateOpts, EntityGraphOpts,
+    EntityImage, EntityKinds, EntityListOpts, EntityMetadata, EntityMetadataRequest, EntityRequest,
+    EntityUpdate, GroupUpdate, GroupUsersUpdate, SoftwareEntityRequest, VendorEntityRequest,
+};
+use thorium::test_utilities::{self, generators};
+use thorium::{Error, fail, is, is_not};
+use uuid::Uuid;
+
+/// A minimal 1x1 PNG to use as an entity image
+const PNG: [u8; 67] = [
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f, 0x15, 0xc4,
+    0x89, 0x00, 0x00, 0x00, 0x0a, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x00, 0x01, 0x00, 0x00,
+    0x05, 0x00, 0x01, 0x0d, 0x0a, 0x2d, 0xb4, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae,
+    0x42, 0x60, 0x82,
+];
+
+#[tokio::test]
+async fn update_conflict() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entity to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create an entity
+    let req = EntityRequest::new("CornHarvester", EntityMetadataRequest::Other, vec![group]);
+    let id = client.entities.create(req).await?.id;
+    // get our entity so both of our updates are built from the same version
+    let entity = client.entities.get(id).await?;
+    // build two updates that each replace our entities image
+    let first = EntityUpdate::default()
+        .image(EntityImage::new("first.png", "image/png", PNG.to_vec()))
+        .version(entity.version);
+    let second = EntityUpdate::default()
+        .image(EntityImage::new("second.png", "image/png", PNG.to_vec()))
+        .version(entity.version);
+    // apply our first update which should win
+    client.entities.update(id, first).await?;
+    // our second update is now stale and should be rejected with its uploaded image cleaned up
+    let resp = client.entities.update(id, second).await;
+    fail!(resp, 409);
+    // make sure only the first update was applied
+    let updated = client.entities.get(id).await?;
+    is_not!(updated.version, entity.version);
+    let image = updated.image.unwrap_or_default();
+    is!(image.ends_with("first.png"), true);
+    // updates built from the current version should still succeed
+    let third = EntityUpdate::default()
+        .description("Harvests corn")
+        .version(updated.version);
+    client.entities.update(id, third).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn duplicate_vendors() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a vendor
+    let vendor = EntityMetadataRequest::Vendor(VendorEntityRequest::default());
+    let req = EntityRequest::new("CornCo", vendor, vec![group.clone()]);
+    let vendor_id = client.entities.create(req).await?.id;
+    // try to create a device that lists the same vendor twice
+    let device = DeviceEntityRequest {
+        vendors: vec![vendor_id, vendor_id],
+        ..Default::default()
+    };
+    let req = EntityRequest::new(
+        "CornHarvester",
+        EntityMetadataRequest::Device(device),
+        vec![group.clone()],
+    );
+    let resp = client.entities.create(req).await;
+    fail!(resp, 400);
+    // listing the vendor once should work
+    let device = DeviceEntityRequest {
+        vendors: vec![vendor_id],
+        ..Default::default()
+    };
+    let req = EntityRequest::new(
+        "CornHarvester",
+        EntityMetadataRequest::Device(device),
+        vec![group],
+    );
+    client.entities.create(req).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_device_with_vendors() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a vendor
+    let vendor = EntityMetadataRequest::Vendor(VendorEntityRequest::default());
+    let req = EntityRequest::new("CornCo", vendor, vec![group.clone()]);
+    let vendor_id = client.entities.create(req).await?.id;
+    // create a device made by our vendor
+    let device = DeviceEntityRequest {
+        vendors: vec![vendor_id],
+        ..Default::default()
+    };
+    let req = EntityRequest::new(
+        "CornHarvester",
+        EntityMetadataRequest::Device(device),
+        vec![group],
+    );
+    let id = client.entities.create(req).await?.id;
+    // make sure our vendor was sent in a form field the API parses
+    let entity = client.entities.get(id).await?;
+    match &entity.metadata {
+        EntityMetadata::Device(device) => {
+            is!(device.vendors.len(), 1);
+            is!(device.vendors[0].id, vendor_id);
+        }
+        metadata => {
+            return Err(Error::new(format!(
+                "Expected device metadata but got {metadata:?}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_software() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a vendor
+    let vendor = EntityMetadataRequest::Vendor(VendorEntityRequest::default());
+    let req = EntityRequest::new("CornCo", vendor, vec![group.clone()]);
+    let vendor_id = client.entities.create(req).await?.id;
+    // create a software entity developed by our vendor
+    let software = SoftwareEntityRequest::new("1.2.3")
+        .cpe("cpe:2.3:a:cornco:harvester:1.2.3:*:*:*:*:*:*:*")
+        .vendor(vendor_id);
+    let req = EntityRequest::new(
+        "HarvesterFirmware",
+        EntityMetadataRequest::Software(software),
+        vec![group.clone()],
+    );
+    let id = client.entities.create(req).await?.id;
+    // make sure our software entity was created correctly
+    let entity = client.entities.get(id).await?;
+    is!(entity.kind, EntityKinds::Software);
+    match &entity.metadata {
+        EntityMetadata::Software(software) => {
+            is!(software.version, "1.2.3");
+            is!(
+                software.cpe.as_deref(),
+                Some("cpe:2.3:a:cornco:harvester:1.2.3:*:*:*:*:*:*:*")
+            );
+            // our vendor should be linked through a developed by association
+            is!(software.vendors.len(), 1);
+            is!(software.vendors[0].id, vendor_id);
+        }
+        metadata => {
+            return Err(Error::new(format!(
+                "Expected software metadata but got {metadata:?}"
+            )));
+        }
+    }
+    // software without a version should be rejected
+    let req = EntityRequest::new(
+        "HarvesterFirmware",
+        EntityMetadataRequest::Software(SoftwareEntityRequest::default()),
+        vec![group.clone()],
+    );
+    let resp = client.entities.create(req).await;
+    fail!(resp, 400);
+    // software with an invalid cpe should be rejected
+    let software = SoftwareEntityRequest::new("1.2.3").cpe("harvester");
+    let req = EntityRequest::new(
+        "HarvesterFirmware",
+        EntityMetadataRequest::Software(software),
+        vec![group],
+    );
+    let resp = client.entities.create(req).await;
+    fail!(resp, 400);
+    Ok(())
+}
+
+#[tokio::test]
+async fn update_software() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entity to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a software entity
+    let software = SoftwareEntityRequest::new("1.2.3");
+    let req = EntityRequest::new(
+        "HarvesterFirmware",
+        EntityMetadataRequest::Software(software),
+        vec![group],
+    );
+    let id = client.entities.create(req).await?.id;
+    // update our softwares version and cpe
+    let update = EntityUpdate::default()
+        .software_version("2.0.0")
+        .cpe("cpe:2.3:a:cornco:harvester:2.0.0:*:*:*:*:*:*:*");
+    client.entities.update(id, update).await?;
+    // make sure our update was applied
+    let entity = client.entities.get(id).await?;
+    match &entity.metadata {
+        EntityMetadata::Software(software) => {
+            is!(software.version, "2.0.0");
+            is!(
+                software.cpe.as_deref(),
+                Some("cpe:2.3:a:cornco:harvester:2.0.0:*:*:*:*:*:*:*")
+            );
+        }
+        metadata => {
+            return Err(Error::new(format!(
+                "Expected software metadata but got {metadata:?}"
+            )));
+        }
+    }
+    // an invalid cpe should be rejected
+    let update = EntityUpdate::default().cpe("harvester");
+    let resp = client.entities.update(id, update).await;
+    fail!(resp, 400);
+    // clear our cpe
+    client
+        .entities
+        .update(id, EntityUpdate::default().clear_cpe())
+        .await?;
+    let entity = client.entities.get(id).await?;
+    match &entity.metadata {
+        EntityMetadata::Software(software) => {
+            is!(software.version, "2.0.0");
+            is!(software.cpe, None);
+        }
+        metadata => {
+            return Err(Error::new(format!(
+                "Expected software metadata but got {metadata:?}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_bulk() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // build a batch of entities where the second one is missing its software version
+    let reqs = vec![
+        EntityRequest::new(
+            "CornCo",
+            EntityMetadataRequest::Vendor(VendorEntityRequest::default()),
+            vec![group.clone()],
+        ),
+        EntityRequest::new(
+            "HarvesterFirmware",
+            EntityMetadataRequest::Software(SoftwareEntityRequest::default()),
+            vec![group.clone()],
+        ),
+        EntityRequest::new(
+            "CornField",
+            EntityMetadataRequest::Other,
+            vec![group.clone()],
+        )
+        .tag("Crop", "Corn"),
+    ];
+    // create our entities in bulk
+    let resp = client.entities.create_bulk(&reqs).await?;
+    // only our invalid entity should have failed
+    is!(resp.created.len(), 2);
+    is!(resp.errors.len(), 1);
+    is!(resp.errors.contains_key(&1), true);
+    // make sure our valid entities were actually created
+    let vendor = client.entities.get(resp.created[&0]).await?;
+    is!(vendor.name, "CornCo");
+    is!(vendor.kind, EntityKinds::Vendor);
+    let field = client.entities.get(resp.created[&2]).await?;
+    is!(field.name, "CornField");
+    is!(field.kind, EntityKinds::Other);
+    // a batch containing a group we can't access should be rejected up front
+    let reqs = vec![
+        EntityRequest::new("CornCo", EntityMetadataRequest::Other, vec![group.clone()]),
+        EntityRequest::new(
+            "CornCo",
+            EntityMetadataRequest::Other,
+            vec!["MissingCornGroup"],
+        ),
+    ];
+    let resp = client.entities.create_bulk(&reqs).await;
+    fail!(resp, 404);
+    // batches over our limit should be rejected
+    let reqs = (0..6)
+        .map(|i| {
+            EntityRequest::new(
+                format!("Corn{i}"),
+                EntityMetadataRequest::Other,
+                vec![group.clone()],
+            )
+        })
+        .collect::<Vec<EntityRequest>>();
+    let resp = client.entities.create_bulk(&reqs).await;
+    fail!(resp, 400);
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_submitter() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    let admin = client.users.info().await?.username;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a user and add them to our group
+    let user_client = generators::client(&client).await?;
+    let username = user_client.users.info().await?.username;
+    let group_update =
+        GroupUpdate::default().users(GroupUsersUpdate::default().direct_add(username.clone()));
+    client.groups.update(&group, &group_update).await?;
+    // create an entity as each of our users
+    let req = EntityRequest::new("CornHarvester", EntityMetadataRequest::Other, vec![&group]);
+    let admin_id = client.entities.create(req).await?.id;
+    let req = EntityRequest::new("WheatHarvester", EntityMetadataRequest::Other, vec![&group]);
+    let user_id = user_client.entities.create(req).await?.id;
+    // list only the admins entities
+    let opts = EntityListOpts::default()
+        .groups(vec![&group])
+        .submitter(&admin);
+    let cursor = client.entities.list(&opts).await?;
+    let ids = cursor.data.iter().map(|line| line.id).collect::<Vec<_>>();
+    is!(ids, vec![admin_id]);
+    // admins can list other users entities
+    let opts = EntityListOpts::default()
+        .groups(vec![&group])
+        .submitter(&username);
+    let cursor = client.entities.list(&opts).await?;
+    let ids = cursor.data.iter().map(|line| line.id).collect::<Vec<_>>();
+    is!(ids, vec![user_id]);
+    // users can list their own entities
+    let cursor = user_client.entities.list(&opts).await?;
+    let ids = cursor.data.iter().map(|line| line.id).collect::<Vec<_>>();
+    is!(ids, vec![user_id]);
+    // users can't list other users entities
+    let opts = EntityListOpts::default()
+        .groups(vec![&group])
+        .submitter(&admin);
+    let resp = user_client.entities.list(&opts).await;
+    fail!(resp, 401);
+    // listing without a submitter still returns both entities
+    let opts = EntityListOpts::default().groups(vec![&group]);
+    let cursor = client.entities.list(&opts).await?;
+    is!(cursor.data.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_details_stream() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create 10 entities in our group
+    for i in 0..10 {
+        let req = EntityRequest::new(
+            format!("Harvester{i}"),
+            EntityMetadataRequest::Other,
+            vec![&group],
+        );
+        client.entities.create(req).await?;
+    }
+    // list the details for the entities we just created with a buffered response
+    let opts = EntityListOpts::default().groups(vec![&group]).limit(100);
+    let cursor = client.entities.list_details(&opts).await?;
+    let buffered = cursor
+        .data
+        .iter()
+        .map(|entity| entity.id)
+        .collect::<Vec<Uuid>>();
+    // stream the same page of entities as newline delimited json
+    let stream = client.entities.list_details_stream(&opts).await?;
+    is!(stream.cursor, None);
+    // make sure we streamed all of our entities in the same order
+    let streamed = stream
+        .map_ok(|entity| entity.id)
+        .try_collect::<Vec<Uuid>>()
+        .await?;
+    is!(streamed.len(), 10);
+    is!(streamed, buffered);
+    // a partial page should return a cursor to continue streaming from
+    let opts = EntityListOpts::default().groups(vec![&group]).page_size(5);
+    let stream = client.entities.list_details_stream(&opts).await?;
+    is_not!(stream.cursor, None);
+    let first = stream.try_collect::<Vec<_>>().await?;
+    is!(first.len(), 5);
+    Ok(())
+}
+
+#[tokio::test]
+async fn archive_restore() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create two entities
+    let req = EntityRequest::new("CornHarvester", EntityMetadataRequest::Other, vec![&group]);
+    let archived = client.entities.create(req).await?.id;
+    let req = EntityRequest::new("WheatHarvester", EntityMetadataRequest::Other, vec![&group]);
+    let kept = client.entities.create(req).await?.id;
+    // associate a file with the entity we are going to archive
+    let sha256 = client
+        .files
+        .create(generators::gen_sample(&group))
+        .await?
+        .sha256;
+    let target = AssociationTarget::Entity {
+        id: archived,
+        name: "CornHarvester".to_owned(),
+    };
+    let req = AssociationRequest::new(
+        AssociationKind::FirmwareFor,
+        AssociationTarget::File(sha256.clone()),
+    )
+    .target(target);
+    client.associations.create(&req).await?;
+    // archive our entity
+    client.entities.delete(archived).await?;
+    // archived entities can still be retrieved
+    let entity = client.entities.get(archived).await?;
+    is!(entity.archived_at.is_some(), true);
+    // archiving an archived entity should fail
+    let resp = client.entities.delete(archived).await;
+    fail!(resp, 409);
+    // archived entities are excluded from listings by default
+    let opts = EntityListOpts::default().groups(vec![&group]);
+    let cursor = client.entities.list(&opts).await?;
+    let ids = cursor.data.iter().map(|line| line.id).collect::<Vec<_>>();
+    is!(ids, vec![kept]);
+    // but they can be explicitly included
+    let opts = EntityListOpts::default()
+        .groups(vec![&group])
+        .include_archived();
+    let cursor = client.entities.list(&opts).await?;
+    is!(cursor.data.len(), 2);
+    // our files association with the archived entity should be archived too
+    let opts = AssociationListOpts::default().groups(vec![&group]);
+    let cursor = client.files.list_associations(&sha256, &opts).await?;
+    is!(cursor.data.len(), 0);
+    let opts = AssociationListOpts::default()
+        .groups(vec![&group])
+        .include_archived();
+    let cursor = client.files.list_associations(&sha256, &opts).await?;
+    is!(cursor.data.len(), 1);
+    is!(cursor.data[0].archived_at.is_some(), true);
+    // restore our entity
+    client.entities.restore(archived).await?;
+    let entity = client.entities.get(archived).await?;
+    is!(entity.archived_at, None);
+    // restoring an entity that isn't archived should fail
+    let resp = client.entities.restore(archived).await;
+    fail!(resp, 400);
+    // our restored entity and its association should be listed again
+    let opts = EntityListOpts::default().groups(vec![&group]);
+    let cursor = client.entities.list(&opts).await?;
+    is!(cursor.data.len(), 2);
+    let opts = AssociationListOpts::default().groups(vec![&group]);
+    let cursor = client.files.list_associations(&sha256, &opts).await?;
+    is!(cursor.data.len(), 1);
+    is!(cursor.data[0].archived_at, None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn purge() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a user and add them to our group
+    let user_client = generators::client(&client).await?;
+    let username = user_client.users.info().await?.username;
+    let group_update =
+        GroupUpdate::default().users(GroupUsersUpdate::default().direct_add(username.clone()));
+    client.groups.update(&group, &group_update).await?;
+    // create an entity as our user
+    let req = EntityRequest::new("CornHarvester", EntityMetadataRequest::Other, vec![&group]);
+    let id = user_client.entities.create(req).await?.id;
+    // only admins can purge entities even if they created them
+    let resp = user_client.entities.purge(id).await;
+    fail!(resp, 401);
+    // archived entities can be purged
+    user_client.entities.delete(id).await?;
+    client.entities.purge(id).await?;
+    // purged entities are gone completely
+    let resp = client.entities.get(id).await;
+    fail!(resp, 404);
+    let opts = EntityListOpts::default()
+        .groups(vec![&group])
+        .include_archived();
+    let cursor = client.entities.list(&opts).await?;
+    is!(cursor.data.len(), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_associations_window() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entity and files to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a vendor to associate files with
+    let req = EntityRequest::new("CornCorp", EntityMetadataRequest::Other, vec![&group]);
+    let vendor = client.entities.create(req).await?.id;
+    let target = AssociationTarget::Entity {
+        id: vendor,
+        name: "CornCorp".to_owned(),
+    };
+    // associate an older file with our vendor
+    let old = client
+        .files
+        .create(generators::gen_sample(&group))
+        .await?
+        .sha256;
+    let req = AssociationRequest::new(AssociationKind::DevelopedBy, AssociationTarget::File(old))
+        .target(target.clone());
+    client.associations.create(&req).await?;
+    // get a timestamp between our two associations
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let between = Utc::now();
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    // associate a newer file with our vendor
+    let new = client
+        .files
+        .create(generators::gen_sample(&group))
+        .await?
+        .sha256;
+    let req = AssociationRequest::new(
+        AssociationKind::DevelopedBy,
+        AssociationTarget::File(new.clone()),
+    )
+    .target(target);
+    client.associations.create(&req).await?;
+    // without a window we should see both associations
+    let opts = AssociationListOpts::default().groups(vec![&group]);
+    let cursor = client.entities.list_associations(vendor, &opts).await?;
+    is!(cursor.data.len(), 2);
+    // only the newer association was created after our timestamp
+    let opts = AssociationListOpts::default()
+        .groups(vec![&group])
+        .created_between(between, Utc::now());
+    let cursor = client.entities.list_associations(vendor, &opts).await?;
+    is!(cursor.data.len(), 1);
+    is!(cursor.data[0].other, AssociationTarget::File(new));
+    // only the older association was created before our timestamp
+    let opts = AssociationListOpts::default()
+        .groups(vec![&group])
+        .created_between(between - Duration::days(1), between);
+    let cursor = client.entities.list_associations(vendor, &opts).await?;
+    is!(cursor.data.len(), 1);
+    is_not!(cursor.data[0].other, AssociationTarget::File(new));
+    // windows that end before they start are rejected
+    let opts = AssociationListOpts::default()
+        .groups(vec![&group])
+        .created_between(Utc::now(), between);
+    let resp = client
+        .entities
+        .list_associations(vendor, &opts)
+        .await
+        .map(|cursor| cursor.data);
+    fail!(resp, 400);
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_devices_by_sector() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a critical energy device
+    let device = DeviceEntityRequest {
+        critical_system: Some(true),
+        critical_sectors: [CriticalSector::Energy].into(),
+        ..Default::default()
+    };
+    let req = EntityRequest::new(
+        "PowerPlant",
+        EntityMetadataRequest::Device(device),
+        vec![&group],
+    );
+    let critical = client.entities.create(req).await?.id;
+    // create a non critical energy device in a sensitive location
+    let device = DeviceEntityRequest {
+        critical_system: Some(false),
+        sensitive_location: Some(true),
+        critical_sectors: [CriticalSector::Energy, CriticalSector::Dams].into(),
+        ..Default::default()
+    };
+    let req = EntityRequest::new(
+        "Turbine",
+        EntityMetadataRequest::Device(device),
+        vec![&group],
+    );
+    let sensitive = client.entities.create(req).await?.id;
+    // create a device in a different sector
+    let device = DeviceEntityRequest {
+        critical_sectors: [CriticalSector::WaterWasteWater].into(),
+        ..Default::default()
+    };
+    let req = EntityRequest::new("Pump", EntityMetadataRequest::Device(device), vec![&group]);
+    let water = client.entities.create(req).await?.id;
+    // create an energy vendor which should never be listed as a device
+    let vendor = VendorEntityRequest {
+        critical_sectors: [CriticalSector::Energy].into(),
+        ..Default::default()
+    };
+    let req = EntityRequest::new(
+        "PowerCo",
+        EntityMetadataRequest::Vendor(vendor),
+        vec![&group],
+    );
+    client.entities.create(req).await?;
+    // list all energy devices
+    let opts = DeviceListOpts::new(CriticalSector::Energy).groups(vec![&group]);
+    let cursor = client.entities.list_devices(&opts).await?;
+    let mut ids = cursor
+        .data
+        .iter()
+        .map(|entity| entity.id)
+        .collect::<Vec<_>>();
+    ids.sort();
+    let mut expected = vec![critical, sensitive];
+    expected.sort();
+    is!(ids, expected);
+    // only list critical energy devices
+    let opts = DeviceListOpts::new(CriticalSector::Energy)
+        .groups(vec![&group])
+        .critical_system(true);
+    let cursor = client.entities.list_devices(&opts).await?;
+    let ids = cursor
+        .data
+        .iter()
+        .map(|entity| entity.id)
+        .collect::<Vec<_>>();
+    is!(ids, vec![critical]);
+    // only list energy devices in sensitive locations
+    let opts = DeviceListOpts::new(CriticalSector::Energy)
+        .groups(vec![&group])
+        .sensitive_location(true);
+    let cursor = client.entities.list_devices(&opts).await?;
+    let ids = cursor
+        .data
+        .iter()
+        .map(|entity| entity.id)
+        .collect::<Vec<_>>();
+    is!(ids, vec![sensitive]);
+    // list water devices
+    let opts = DeviceListOpts::new(CriticalSector::WaterWasteWater).groups(vec![&group]);
+    let cursor = client.entities.list_devices(&opts).await?;
+    let ids = cursor
+        .data
+        .iter()
+        .map(|entity| entity.id)
+        .collect::<Vec<_>>();
+    is!(ids, vec![water]);
+    // users can't list devices from groups they aren't in
+    let user_client = generators::client(&client).await?;
+    let opts = DeviceListOpts::new(CriticalSector::Energy).groups(vec![&group]);
+    let resp = user_client
+        .entities
+        .list_devices(&opts)
+        .await
+        .map(|cursor| cursor.data);
+    fail!(resp, 401);
+    Ok(())
+}
+
+#[tokio::test]
+async fn related_to() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create two entities to relate
+    let req = EntityRequest::new("CornField", EntityMetadataRequest::Other, vec![&group]);
+    let field = client.entities.create(req).await?.id;
+    let req = EntityRequest::new("CornSilo", EntityMetadataRequest::Other, vec![&group]);
+    let silo = client.entities.create(req).await?.id;
+    // relate our entities without explicitly making the association bidirectional
+    let source = AssociationTarget::Entity {
+        id: field,
+        name: "CornField".to_owned(),
+    };
+    let target = AssociationTarget::Entity {
+        id: silo,
+        name: "CornSilo".to_owned(),
+    };
+    let req = AssociationRequest::new(AssociationKind::RelatedTo, source).target(target);
+    client.associations.create(&req).await?;
+    // both entities should list this association as bidirectional
+    let opts = AssociationListOpts::default().groups(vec![&group]);
+    for (id, other) in [(field, silo), (silo, field)] {
+        let cursor = client.entities.list_associations(id, &opts).await?;
+        is!(cursor.data.len(), 1);
+        is!(cursor.data[0].kind, AssociationKind::RelatedTo);
+        is!(cursor.data[0].direction, Directionality::Bidirectional);
+        match &cursor.data[0].other {
+            AssociationTarget::Entity { id, .. } => is!(*id, other),
+            other => panic!("Unexpected association with {other:?}"),
+        }
+    }
+    // removing the relation from the other side removes it for both entities
+    client
+        .entities
+        .update(silo, EntityUpdate::default().remove_related(field))
+        .await?;
+    for id in [field, silo] {
+        let cursor = client.entities.list_associations(id, &opts).await?;
+        is!(cursor.data.len(), 0);
+    }
+    // relate our entities again with an entity update
+    client
+        .entities
+        .update(silo, EntityUpdate::default().related(field))
+        .await?;
+    for id in [field, silo] {
+        let cursor = client.entities.list_associations(id, &opts).await?;
+        is!(cursor.data.len(), 1);
+        is!(cursor.data[0].direction, Directionality::Bidirectional);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_graph() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a vendor with two devices
+    let vendor = EntityMetadataRequest::Vendor(VendorEntityRequest::default());
+    let req = EntityRequest::new("CornCo", vendor, vec![&group]);
+    let vendor_id = client.entities.create(req).await?.id;
+    let mut device_ids = Vec::with_capacity(2);
+    for name in ["CornHarvester", "CornPlanter"] {
+        let device = DeviceEntityRequest {
+            vendors: vec![vendor_id],
+            ..Default::default()
+        };
+        let req = EntityRequest::new(name, EntityMetadataRequest::Device(device), vec![&group]);
+        device_ids.push(client.entities.create(req).await?.id);
+    }
+    // add firmware for our first device
+    let sha256 = client
+        .files
+        .create(generators::gen_sample(&group))
+        .await?
+        .sha256;
+    let target = AssociationTarget::Entity {
+        id: device_ids[0],
+        name: "CornHarvester".to_owned(),
+    };
+    let req = AssociationRequest::new(
+        AssociationKind::FirmwareFor,
+        AssociationTarget::File(sha256),
+    )
+    .target(target);
+    client.associations.create(&req).await?;
+    // export the device to vendor graph around our vendor
+    let opts = EntityGraphOpts::default()
+        .entity(vendor_id)
+        .kind(AssociationKind::DevelopedBy);
+    let graph = client.entities.export_graph(&opts).await?;
+    is!(graph.nodes.len(), 3);
+    is!(graph.links.len(), 2);
+    is!(graph.truncated, false);
+    // every link should point from a device to our vendor
+    for link in &graph.links {
+        is!(link.kind, AssociationKind::DevelopedBy);
+        is!(link.target, vendor_id.to_string());
+    }
+    // export the full graph for our group which also has our firmware
+    let opts = EntityGraphOpts::default().groups(vec![&group]);
+    let graph = client.entities.export_graph(&opts).await?;
+    is!(graph.nodes.len(), 4);
+    is!(graph.links.len(), 3);
+    // the same graph can be exported as GraphML
+    let graphml = client.entities.export_graphml(&opts).await?;
+    is!(graphml.matches("<node ").count(), 4);
+    is!(graphml.matches("<edge ").count(), 3);
+    // limit our graph to only 2 nodes
+    let opts = EntityGraphOpts::default().groups(vec![&group]).max_nodes(2);
+    let graph = client.entities.export_graph(&opts).await?;
+    is!(graph.nodes.len(), 2);
+    is!(graph.truncated, true);
+    // users can't export graphs from groups they aren't in
+    let user_client = generators::client(&client).await?;
+    let opts = EntityGraphOpts::default().groups(vec![&group]);
+    let resp = user_client.entities.export_graph(&opts).await;
+    fail!(resp, 401);
+    // or around entities they can't see
+    let opts = EntityGraphOpts::default().entity(vendor_id);
+    let resp = user_client.entities.export_graph(&opts).await;
+    fail!(resp, 404);
+    Ok(())
+}
+
+#[tokio::test]
+async fn find_duplicates() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group to add our entities to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create two vendors that are likely duplicates and a few clearly distinct ones
+    let mut ids = Vec::with_capacity(4);
+    for name in ["Acme Corp", "Acme Corporation", "Globex", "Initech"] {
+        let vendor = EntityMetadataRequest::Vendor(VendorEntityRequest::default());
+        let req = EntityRequest::new(name, vendor, vec![&group]);
+        ids.push(client.entities.create(req).await?.id);
+    }
+    // a device with a duplicate name should not be compared to our vendors
+    let req = EntityRequest::new(
+        "Acme Corp.",
+        EntityMetadataRequest::Device(DeviceEntityRequest::default()),
+        vec![&group],
+    );
+    client.entities.create(req).await?;
+    // scan our vendors for duplicates
+    let opts = EntityDuplicateOpts::default()
+        .groups(vec![&group])
+        .kind(EntityKinds::Vendor);
+    let report = client.entities.find_duplicates(&opts).await?;
+    is!(report.scanned, 4);
+    is!(report.truncated, false);
+    // only our two acme vendors should be reported
+    is!(report.duplicates.len(), 1);
+    let duplicate = &report.duplicates[0];
+    is!(duplicate.kind, EntityKinds::Vendor);
+    let mut found = vec![duplicate.left.id, duplicate.right.id];
+    found.sort();
+    let mut expected = vec![ids[0], ids[1]];
+    expected.sort();
+    is!(found, expected);
+    // scanning all kinds should still only report our vendor duplicates
+    let opts = EntityDuplicateOpts::default().groups(vec![&group]);
+    let report = client.entities.find_duplicates(&opts).await?;
+    is!(report.scanned, 5);
+    is!(report.duplicates.len(), 1);
+    // a strict enough threshold should report distinct names too
+    let opts = EntityDuplicateOpts::default()
+        .groups(vec![&group])
+        .kind(EntityKinds::Vendor)
+        .threshold(0.0);
+    let report = client.entities.find_duplicates(&opts).await?;
+    is!(report.duplicates.len(), 6);
+    // thresholds must be between 0 and 1
+    let opts = EntityDuplicateOpts::default()
+        .groups(vec![&group])
+        .threshold(1.5);
+    let resp = client.entities.find_duplicates(&opts).await;
+    fail!(resp, 400);
+    // only admins can scan for duplicates
+    let user_client = generators::client(&client).await?;
+    let opts = EntityDuplicateOpts::default();
+    let resp = user_client.entities.find_duplicates(&opts).await;
+    fail!(resp, 401);
+    Ok(())
+}
@@ -1,24 +1,34 @@
 //! Test files routes
 
+use axum::body::Body;
+use axum::extract::{FromRequest, Multipart};
+use axum::http::Request;
+use axum::http::header::CONTENT_TYPE;
+use bytes::Bytes;
+use chrono::{Datelike, Utc};
 use data_encoding::HEXLOWER;
 use md5::Md5;
 use rand::RngCore;
 use sha1::{Digest, Sha1};
 use sha2::Sha256;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use thorium::client::ResultsClient;
+use thorium::models::backends::db;
 use thorium::test_utilities::{self, generators};
-use thorium::utils::s3::S3;
+use thorium::utils::Shared;
+use thorium::utils::s3::{MAX_RANGE_START, S3, S3Client};
 use thorium::{
-    contains, fail, has_tag, is, is_desc, is_empty, is_in, is_not, is_not_in, no_tag, starts_with,
-    vec_in_vec,
+    contains, contains_key, fail, has_tag, is, is_desc, is_empty, is_in, is_not, is_not_in, no_tag,
+    starts_with, vec_in_vec,
 };
 use uuid::Uuid;
 
 use thorium::models::{
-    Buffer, CommentRequest, DeleteCommentParams, FileDeleteOpts, FileDownloadOpts, FileListOpts,
-    GroupUpdate, GroupUsersUpdate, ImageVersion, OnDiskFile, OriginRequest, OutputDisplayType,
-    OutputRequest, ResultGetParams, SampleRequest, SubmissionUpdate, TagDeleteRequest, TagRequest,
+    AssociationKind, AssociationListOpts, AssociationRequest, AssociationTarget, Buffer,
+    CommentRequest, DeleteCommentParams, EntityMetadataRequest, EntityRequest, FileDeleteOpts,
+    FileDownloadOpts, FileListOpts, FileListParams, GroupUpdate, GroupUsersUpdate, ImageVersion,
+    LineageParams, OnDiskFile, OriginRequest, OutputDisplayType, OutputRenderers, OutputRequest,
+    ResultGetParams, SampleRequest, SubmissionUpdate, TagDeleteRequest, TagRequest,
 };
 
 #[tokio::test]
@@ -80,6 +90,169 @@ async fn download() -> Result<(), thorium::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn download_range() -> Result<(), thorium::Error> {
+    // generate random data so we upload a new file each run
+    let mut random_data = vec![0u8; 4096];
+    rand::rng().fill_bytes(&mut random_data);
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // upload this file
+    let file_req = SampleRequest::new_buffer(Buffer::new(random_data.clone()), vec![group]);
+    let resp = client.files.create(file_req).await?;
+    // download a range from the middle of our file
+    let range = client
+        .files
+        .download_range(&resp.sha256, 1000, 1999)
+        .await?;
+    is!(range, random_data[1000..2000].to_vec());
+    // ranges past the end of our file should only return the rest of our file
+    let range = client
+        .files
+        .download_range(&resp.sha256, 4000, 9999)
+        .await?;
+    is!(range, random_data[4000..].to_vec());
+    Ok(())
+}
+
+#[tokio::test]
+async fn download_carted_range() -> Result<(), thorium::Error> {
+    // generate random data so we upload a new file each run
+    let mut random_data = vec![0u8; 4096];
+    rand::rng().fill_bytes(&mut random_data);
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // upload this file
+    let file_req = SampleRequest::new_buffer(Buffer::new(random_data), vec![group]);
+    let resp = client.files.create(file_req).await?;
+    // download the whole carted file so we can compare our ranges against it
+    let temp_path = std::env::temp_dir().join(format!("CARTED_{}", Uuid::new_v4()));
+    let mut opts = FileDownloadOpts::default();
+    client
+        .files
+        .download(&resp.sha256, &temp_path, &mut opts)
+        .await?;
+    let carted = tokio::fs::read(&temp_path).await?;
+    tokio::fs::remove_file(&temp_path).await?;
+    // ranges of a normal download should be carted bytes and not uncarted bytes
+    let range = client
+        .files
+        .download_carted_range(&resp.sha256, 0, 99)
+        .await?;
+    is!(range, carted[..100].to_vec());
+    // ranges past the end of our carted file should only return the rest of it
+    let start = carted.len() as u64 - 10;
+    let range = client
+        .files
+        .download_carted_range(&resp.sha256, start, start + 99)
+        .await?;
+    is!(range, carted[carted.len() - 10..].to_vec());
+    Ok(())
+}
+
+#[tokio::test]
+async fn download_range_out_of_bounds() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // upload a small file
+    let file_req = SampleRequest::new_buffer(Buffer::new(vec![7u8; 64]), vec![group]);
+    let resp = client.files.create(file_req).await?;
+    // ranges that start past the end of our file can't be satisfied
+    let range = client.files.download_range(&resp.sha256, 64, 128).await;
+    fail!(range, 416);
+    // neither can ranges that end before they start
+    let range = client.files.download_range(&resp.sha256, 32, 16).await;
+    fail!(range, 416);
+    // or uncarted ranges that start too far into a file
+    let start = MAX_RANGE_START + 1;
+    let range = client
+        .files
+        .download_range(&resp.sha256, start, start + 16)
+        .await;
+    fail!(range, 416);
+    // the same holds for ranges of the carted file
+    let range = client
+        .files
+        .download_carted_range(&resp.sha256, 1 << 20, (1 << 20) + 16)
+        .await;
+    fail!(range, 416);
+    let range = client
+        .files
+        .download_carted_range(&resp.sha256, 32, 16)
+        .await;
+    fail!(range, 416);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_large() -> Result<(), thorium::Error> {
+    // build a file that is larger than the json body limit
+    let limit = test_utilities::CONF
+        .thorium
+        .request_size_limits
+        .json_bytes();
+    let mut data = vec![0u8; limit * 2];
+    rand::rng().fill_bytes(&mut data);
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // make sure upload routes allow larger bodies
+    let file_req = SampleRequest::new_buffer(Buffer::new(data.clone()), vec![group]);
+    let resp = client.files.create(file_req).await?;
+    is!(resp.sha256, HEXLOWER.encode(&Sha256::digest(&data)));
+    Ok(())
+}
+
+/// A known blob to upload and its hashes
+const KNOWN_BLOB: &[u8] = b"The quick brown fox jumps over the lazy dog";
+const KNOWN_SHA256: &str = "d7a8fbb307d7809469ca9abcb0082e4f8d5651e46d3cdb762d02d0bf37c9e592";
+const KNOWN_SHA1: &str = "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12";
+const KNOWN_MD5: &str = "9e107d9d372bb6826bd81d3542a419d6";
+
+#[tokio::test]
+async fn create_verified() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // upload our known blob and hash it locally
+    let file_req = SampleRequest::new_buffer(Buffer::new(KNOWN_BLOB.to_vec()), vec![group]);
+    let (resp, hashes) = client.files.create_verified(file_req).await?;
+    is!(hashes.sha256, KNOWN_SHA256);
+    is!(hashes.sha1, KNOWN_SHA1);
+    is!(hashes.md5, KNOWN_MD5);
+    is!(resp.sha256, KNOWN_SHA256);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_verified_path() -> Result<(), thorium::Error> {
+    // write our known blob to disk so it's streamed from a file
+    let path = std::env::temp_dir().join(format!("known-blob-{}", Uuid::new_v4()));
+    tokio::fs::write(&path, KNOWN_BLOB).await?;
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // upload our known blob and hash it as it's streamed
+    let file_req = SampleRequest::new(&path, vec![group]);
+    let result = client.files.create_verified(file_req).await;
+    tokio::fs::remove_file(&path).await?;
+    let (resp, hashes) = result?;
+    is!(hashes.sha256, KNOWN_SHA256);
+    is!(hashes.sha1, KNOWN_SHA1);
+    is!(hashes.md5, KNOWN_MD5);
+    is!(resp.sha256, KNOWN_SHA256);
+    Ok(())
+}
+
 #[tokio::test]
 async fn get() -> Result<(), thorium::Error> {
     // get admin client
@@ -261,6 +434,42 @@ async fn tag() -> Result<(), thorium::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn tag_bulk() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // upload three files and build the tags to add to each of them
+    let mut reqs = HashMap::with_capacity(4);
+    for plant in ["corn", "soy", "wheat"] {
+        let buffer = Buffer::new(format!("{plant}-{}", Uuid::new_v4()));
+        let file_req = SampleRequest::new_buffer(buffer, vec![&group]);
+        let hashes = client.files.create(file_req).await?;
+        let tag_req = TagRequest::default()
+            .group(&group)
+            .add("plant", plant)
+            .add("healthy", "yes");
+        reqs.insert(hashes.sha256, tag_req);
+    }
+    // add a sample that doesn't exist
+    let missing = HEXLOWER.encode(&Sha256::digest(Uuid::new_v4().as_bytes()));
+    reqs.insert(missing.clone(), TagRequest::default().add("plant", "rice"));
+    // add all of these tags at once
+    let resp = client.files.tag_bulk(&reqs).await?;
+    // only our missing sample should have failed
+    is!(resp.tagged.len(), 3);
+    is!(resp.errors.len(), 1);
+    contains_key!(resp.errors, &missing);
+    // make sure all of our tags were added
+    reqs.remove(&missing);
+    for (sha256, tag_req) in &reqs {
+        let sample = client.files.get(sha256).await?;
+        is!(sample, *tag_req);
+    }
+    Ok(())
+}
+
 #[tokio::test]
 async fn delete_tag() -> Result<(), thorium::Error> {
     // get admin client
@@ -409,6 +618,177 @@ async fn list_details() -> Result<(), thorium::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn list_partition_hint() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create 20 random files in this group
+    let reqs = generators::samples(&group, 20, &client).await?;
+    let (_, sha256s, _) = get_hashes(&reqs);
+    // list our files 5 at a time both with and without a partition hint
+    let mut listed = Vec::with_capacity(2);
+    for hint in [None, Some(500)] {
+        let mut opts = FileListOpts::default().groups(vec![&group]).page_size(5);
+        opts.partition_hint = hint;
+        let mut cursor = client.files.list(&opts).await?;
+        let mut hashes = Vec::with_capacity(20);
+        loop {
+            hashes.extend(cursor.data.drain(..).map(|line| line.sha256));
+            if cursor.exhausted() {
+                break;
+            }
+            cursor.refill().await?;
+        }
+        listed.push(hashes);
+    }
+    // our hint should not change what files are listed or their order
+    is!(listed[0].len(), 20);
+    is!(listed[0], listed[1]);
+    for sha256 in &listed[1] {
+        is_in!(sha256s, *sha256);
+    }
+    // connect to the same backends as the API
+    let shared = Shared::new(test_utilities::CONF.clone()).await;
+    // add 150 empty partitions to last year's census for our group so there is more to crawl
+    let year = Utc::now().year() - 1;
+    let key = db::keys::samples::census_stream(&group, year, &shared);
+    let mut pipe = bb8_redis::redis::pipe();
+    for bucket in 1..=150 {
+        pipe.cmd("zadd").arg(&key).arg(bucket).arg(bucket);
+    }
+    let mut conn = shared.redis.get().await.unwrap();
+    let _: () = pipe.query_async(&mut *conn).await.unwrap();
+    drop(conn);
+    // count how many times we crawl our census for a few different hints
+    let mut crawls = HashMap::with_capacity(3);
+    for hint in [10, 99, 500] {
+        let mut params = FileListParams {
+            groups: vec![group.clone()],
+            limit: 5,
+            partition_hint: Some(hint),
+            ..Default::default()
+        };
+        let mut hashes = Vec::with_capacity(20);
+        let mut crawled = 0;
+        loop {
+            let mut cursor = db::files::list(params, false, &shared).await.unwrap();
+            hashes.extend(cursor.data.drain(..).map(|line| line.sha256));
+            crawled += cursor.census_crawls;
+            if cursor.exhausted() {
+                break;
+            }
+            params = FileListParams {
+                cursor: Some(cursor.id),
+                limit: 5,
+                ..Default::default()
+            };
+        }
+        // our hint should still not change what files are listed
+        is!(hashes, listed[0]);
+        crawls.insert(hint, crawled);
+    }
+    // smaller hints should crawl our census more often
+    is!(crawls[&10] > crawls[&99], true);
+    // hints are clamped to the most buckets a single query can pull
+    is!(crawls[&500], crawls[&99]);
+    // hints must crawl at least one partition
+    let opts = FileListOpts::default()
+        .groups(vec![&group])
+        .partition_hint(0);
+    let resp = client.files.list(&opts).await;
+    fail!(resp, 400);
+    // admins can use larger hints than users
+    let opts = FileListOpts::default()
+        .groups(vec![&group])
+        .partition_hint(5000);
+    client.files.list(&opts).await?;
+    let opts = FileListOpts::default()
+        .groups(vec![&group])
+        .partition_hint(5001);
+    let resp = client.files.list(&opts).await;
+    fail!(resp, 400);
+    // create a user and add them to our group
+    let user_client = generators::client(&client).await?;
+    let username = user_client.users.info().await?.username;
+    let group_update =
+        GroupUpdate::default().users(GroupUsersUpdate::default().direct_add(username));
+    client.groups.update(&group, &group_update).await?;
+    // users can't exceed their own ceiling
+    let opts = FileListOpts::default()
+        .groups(vec![&group])
+        .partition_hint(500);
+    user_client.files.list(&opts).await?;
+    let opts = FileListOpts::default()
+        .groups(vec![&group])
+        .partition_hint(501);
+    let resp = user_client.files.list(&opts).await;
+    fail!(resp, 400);
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_associations_confidence() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a file to associate with our entities
+    let sha256 = client
+        .files
+        .create(generators::gen_sample(&group))
+        .await?
+        .sha256;
+    // create two entities to associate our file with
+    let req = EntityRequest::new("CornHarvester", EntityMetadataRequest::Other, vec![&group]);
+    let unsure = client.entities.create(req).await?.id;
+    let req = EntityRequest::new("WheatHarvester", EntityMetadataRequest::Other, vec![&group]);
+    let sure = client.entities.create(req).await?.id;
+    // associate our file with the first entity with only partial confidence
+    let source = AssociationTarget::File(sha256.clone());
+    let target = AssociationTarget::Entity {
+        id: unsure,
+        name: "CornHarvester".to_owned(),
+    };
+    let req = AssociationRequest::new(AssociationKind::FirmwareFor, source.clone())
+        .target(target)
+        .confidence(60);
+    client.associations.create(&req).await?;
+    // associate our file with the second entity without setting a confidence
+    let target = AssociationTarget::Entity {
+        id: sure,
+        name: "WheatHarvester".to_owned(),
+    };
+    let req = AssociationRequest::new(AssociationKind::FirmwareFor, source.clone()).target(target);
+    client.associations.create(&req).await?;
+    // confidence can't be over 100
+    let target = AssociationTarget::Entity {
+        id: sure,
+        name: "WheatHarvester".to_owned(),
+    };
+    let req = AssociationRequest::new(AssociationKind::FirmwareFor, source)
+        .target(target)
+        .confidence(101);
+    let resp = client.associations.create(&req).await;
+    fail!(resp, 400);
+    // list our files associations
+    let opts = AssociationListOpts::default().groups(vec![&group]);
+    let cursor = client.files.list_associations(&sha256, &opts).await?;
+    is!(cursor.data.len(), 2);
+    // make sure our confidences round tripped
+    for association in &cursor.data {
+        match &association.other {
+            AssociationTarget::Entity { id, .. } if *id == unsure => {
+                is!(association.confidence, 60)
+            }
+            AssociationTarget::Entity { id, .. } if *id == sure => is!(association.confidence, 100),
+            other => panic!("Unexpected association with {other:?}"),
+        }
+    }
+    Ok(())
+}
+
 #[tokio::test]
 async fn list_tag() -> Result<(), thorium::Error> {
     // get admin client
@@ -876,6 +1256,54 @@ async fn get_result() -> Result<(), thorium::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn get_result_renderer() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // build a sample request
+    let file_req = SampleRequest::new_buffer(Buffer::new("GetResultRenderer"), vec![group])
+        .description("test file")
+        .tag("test", "file");
+    // upload this file
+    let hashes = client.files.create(file_req).await?;
+    // create results with different display types for this file
+    let display_types = [
+        ("TableTool", OutputDisplayType::Table),
+        ("HtmlTool", OutputDisplayType::Html),
+        ("TemplateTool", OutputDisplayType::Template),
+    ];
+    for (tool, display_type) in display_types {
+        let output_req = OutputRequest::new(
+            hashes.sha256.clone(),
+            tool,
+            "I am a test result",
+            display_type,
+        );
+        client.files.create_result(output_req).await?;
+    }
+    // get our results back
+    let params = ResultGetParams::default();
+    let output = client.files.get_results(&hashes.sha256, &params).await?;
+    // make sure each result has the right renderer for its display type
+    let renderers = OutputRenderers::default();
+    for (tool, display_type) in display_types {
+        let results = &output.results[tool];
+        is!(results.len(), 1);
+        is!(results[0].display_type, display_type);
+        is!(results[0].renderer, Some(renderers.get(display_type, tool)));
+    }
+    // make sure templated results are rendered with a template named after their tool
+    let template = &output.results["TemplateTool"][0];
+    is!(
+        template.renderer.as_ref().unwrap().template,
+        Some("TemplateTool".to_owned())
+    );
+    is!(template.renderer.as_ref().unwrap().sanitize, true);
+    Ok(())
+}
+
 #[tokio::test]
 async fn create_hidden_result() -> Result<(), thorium::Error> {
     // get admin client
@@ -1360,6 +1788,108 @@ async fn list_tag_key_with_number() -> Result<(), thorium::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn lineage() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a pipeline to run reactions with
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // upload our root sample and run a reaction on it
+    let root = client.files.create(generators::gen_sample(&group)).await?;
+    let root_react = generators::gen_reaction(&group, &pipe, None).sample(&root.sha256);
+    let root_react = client.reactions.create(&root_react).await?;
+    // upload a child of our root sample and run a reaction on it
+    let child_req = generators::gen_sample(&group).origin(OriginRequest::unpacked(
+        &root.sha256,
+        Some("unzip".to_owned()),
+    ));
+    let child = client.files.create(child_req).await?;
+    let child_react = generators::gen_reaction(&group, &pipe, None).sample(&child.sha256);
+    let child_react = client.reactions.create(&child_react).await?;
+    // get the lineage of our root sample
+    let lineage = client
+        .trees
+        .lineage(&root.sha256, &LineageParams::default())
+        .await?;
+    // make sure our lineage has the right structure
+    is!(lineage.sample.sha256, root.sha256);
+    is!(lineage.count(), 2);
+    is!(lineage.truncated, false);
+    is!(lineage.reactions.len(), 1);
+    is!(lineage.reactions[0].id, root_react.id);
+    // make sure our child and its reaction are in our lineage
+    let child_lineage = lineage.find(&child.sha256).unwrap();
+    is!(child_lineage.reactions.len(), 1);
+    is!(child_lineage.reactions[0].id, child_react.id);
+    is!(child_lineage.children.is_empty(), true);
+    // only get our root sample without any children
+    let shallow = client
+        .trees
+        .lineage(&root.sha256, &LineageParams::default().depth(0))
+        .await?;
+    // make sure our lineage was cut off at the root
+    is!(shallow.count(), 1);
+    is!(shallow.reactions.len(), 1);
+    is!(shallow.truncated, true);
+    Ok(())
+}
+
+/// Build a multipart upload that streams a file in small chunks like a real upload
+///
+/// # Arguments
+///
+/// * `data` - The file to upload
+async fn multipart_upload(data: &Bytes) -> Multipart {
+    let head = "--corn\r\nContent-Disposition: form-data; name=\"data\"; filename=\"corn\"\r\n\r\n";
+    // send our file in small chunks so our upload buffers never have to grow
+    let chunks = (0..data.len())
+        .step_by(65_536)
+        .map(|start| data.slice(start..(start + 65_536).min(data.len())));
+    let body = std::iter::once(Bytes::from_static(head.as_bytes()))
+        .chain(chunks)
+        .chain(std::iter::once(Bytes::from_static(b"\r\n--corn--\r\n")))
+        .map(Ok::<Bytes, std::io::Error>);
+    let req = Request::builder()
+        .header(CONTENT_TYPE, "multipart/form-data; boundary=corn")
+        .body(Body::from_stream(futures::stream::iter(body)))
+        .unwrap();
+    Multipart::from_request(req, &()).await.unwrap()
+}
+
+#[tokio::test]
+async fn upload_reuses_pooled_buffers() -> Result<(), thorium::Error> {
+    let conf = &test_utilities::CONF.thorium;
+    // use a client with its own pool so other tests can't take our buffer
+    let s3 = S3Client::new(&conf.files.bucket, &conf.files.password, &conf.s3);
+    // get the buffer that our uploads should reuse
+    let buffer = s3.pool.get().as_ptr();
+    is!(s3.pool.idle(), 1);
+    // upload enough random data to need multiple parts
+    let mut data = vec![0; 12 * 1024 * 1024];
+    rand::rng().fill_bytes(&mut data);
+    let data = Bytes::from(data);
+    for carted in [true, false] {
+        let mut form = multipart_upload(&data).await;
+        let field = form.next_field().await.unwrap().unwrap();
+        let path = Uuid::new_v4().to_string();
+        if carted {
+            s3.cart_and_stream(path.clone(), field).await.unwrap();
+        } else {
+            s3.stream(&path, field).await.unwrap();
+        }
+        // our upload should have returned the same buffer to our pool
+        is!(s3.pool.idle(), 1);
+        is!(s3.pool.get().as_ptr(), buffer);
+        s3.delete(&path).await.unwrap();
+    }
+    Ok(())
+}
+
 // Sync tests
 #[cfg(all(feature = "sync", not(feature = "python")))]
 use thorium::client::ResultsClientBlocking;
@@ -284,3 +284,38 @@ async fn update() -> Result<(), thorium::Error> {
     is!(updated, update);
     Ok(())
 }
+
+#[tokio::test]
+async fn update_invalidates_cache() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group and a user to manage it
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    let user_client = generators::client(&client).await?;
+    let username = user_client.users.info().await?.username;
+    let update = GroupUpdate::default().managers(GroupUsersUpdate::default().direct_add(&username));
+    client.groups.update(&group, &update).await?;
+    // get our group a few times so it is cached
+    for _ in 0..3 {
+        let retrieved = user_client.groups.get(&group).await?;
+        is_in!(retrieved.managers.direct, username);
+    }
+    // our manager should be able to update this group
+    let update = GroupUpdate::default().description("Cached description");
+    user_client.groups.update(&group, &update).await?;
+    let retrieved = user_client.groups.get(&group).await?;
+    is!(retrieved.description, Some("Cached description".to_owned()));
+    // demote our manager to a user
+    let update = GroupUpdate::default()
+        .managers(GroupUsersUpdate::default().direct_remove(&username))
+        .users(GroupUsersUpdate::default().direct_add(&username));
+    client.groups.update(&group, &update).await?;
+    // our cached group should be dropped so our demoted user can't update it anymore
+    let retrieved = user_client.groups.get(&group).await?;
+    is_not_in!(retrieved.managers.direct, username);
+    is_in!(retrieved.users.direct, username);
+    let update = GroupUpdate::default().description("Stale description");
+    let resp = user_client.groups.update(&group, &update).await;
+    fail!(resp, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
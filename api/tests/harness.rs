@@ -0,0 +1,88 @@
+//! Tests Thorium against ephemeral backend containers
+//!
+//! These tests require Docker and are only built with the `test-harness` feature.
+#![cfg(feature = "test-harness")]
+
+use thorium::test_utilities::generators;
+use thorium::test_utilities::harness::EphemeralBackend;
+use thorium::{Error, is};
+
+#[tokio::test]
+async fn reaction_end_to_end() -> Result<(), Error> {
+    // start our backends and the API
+    let backend = EphemeralBackend::start().await?;
+    // our harness should be in its own namespace
+    assert!(
+        backend
+            .shared()
+            .config
+            .thorium
+            .namespace
+            .starts_with("harness_")
+    );
+    // get an admin client for this API
+    let client = backend.admin_client().await?;
+    // create a group and pipeline to test reactions in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // create a reaction and make sure we can get it back
+    let react_req = generators::gen_reaction(&group, &pipe, None);
+    let resp = client.reactions.create(&react_req).await?;
+    let created = client.reactions.get(&group, resp.id).await?;
+    is!(created, react_req);
+    // tear down our backends
+    backend.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn warm_cache() -> Result<(), Error> {
+    // start an API with our image and pipeline caches enabled
+    let backend = EphemeralBackend::start_with(|config| {
+        config.thorium.definition_cache.ttl = 600;
+    })
+    .await?;
+    let client = backend.admin_client().await?;
+    // create a pipeline and its images to cache
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // warm our caches for this group
+    let warmed = client.groups.warm_cache(&group).await?;
+    is!(warmed.images >= 1, true);
+    is!(warmed.pipelines, 1);
+    // get our cache hits before reading anything
+    let shared = backend.shared();
+    let image_hits = shared.image_cache.hits();
+    let pipeline_hits = shared.pipeline_cache.hits();
+    // get our pipeline and one of its images which should both be cached
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    client.images.get(&group, &pipe.order[0][0]).await?;
+    // make sure both reads were served from our caches
+    is!(shared.image_cache.hits() > image_hits, true);
+    is!(shared.pipeline_cache.hits() > pipeline_hits, true);
+    // tear down our backends
+    backend.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn warm_cache_disabled() -> Result<(), Error> {
+    // start an API with the default settings which don't cache images or pipelines
+    let backend = EphemeralBackend::start().await?;
+    let client = backend.admin_client().await?;
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    generators::pipelines(&group, 1, false, &client).await?;
+    // nothing should be cached when our caches are disabled
+    let warmed = client.groups.warm_cache(&group).await?;
+    is!(warmed.images, 0);
+    is!(warmed.pipelines, 0);
+    is!(backend.shared().image_cache.is_empty(), true);
+    // tear down our backends
+    backend.stop().await;
+    Ok(())
+}
@@ -1,9 +1,9 @@
 //! Tests the Jobs routes in Thorium
 
 use chrono::prelude::*;
-use thorium::models::{ImageScaler, JobResets, ReactionListParams, Resources};
+use thorium::models::{ImageScaler, ImageUpdate, JobResets, ReactionListParams, Resources};
 use thorium::test_utilities::{self, generators};
-use thorium::{is, Error};
+use thorium::{fail, is, Error};
 
 /// unwraps the status counts for a specific user and image
 macro_rules! get_stats {
@@ -78,12 +78,12 @@ async fn proceed() -> Result<(), thorium::Error> {
         is!(get_stats!(stats, group, pipe_req.name, stage).sleeping, 0);
         is!(get_stats!(stats, group, pipe_req.name, stage).total, 1);
         // get our reactions data
-        let react = client.reactions.get(&req.group, id.id).await?;
+        let react = client.reactions.get(&group, id.id).await?;
         // try to claim a job
         let job = client
             .jobs
             .claim(
-                &req.group, &pipe.name, stage, "cluster0", "node0", "proceed", 1,
+                &group, &pipe.name, stage, "cluster0", "node0", "proceed", 1,
             )
             .await?;
         // check if any of our deadlines match our external job
@@ -143,7 +143,7 @@ async fn error() -> Result<(), thorium::Error> {
     // make sure that we were able to create a reaction and our jobs
     let id = client.reactions.create(&req).await?;
     // get our reactions data
-    let react = client.reactions.get(&req.group, id.id).await?;
+    let react = client.reactions.get(&group, id.id).await?;
     // get the name of the first stage of this pipeline
     let stage = &pipe.order[0][0];
     // register our test node
@@ -165,7 +165,7 @@ async fn error() -> Result<(), thorium::Error> {
     let job = client
         .jobs
         .claim(
-            &req.group, &pipe.name, stage, "cluster0", "node0", "error", 1,
+            &group, &pipe.name, stage, "cluster0", "node0", "error", 1,
         )
         .await?;
     // make sure this stage updated the stage status counters correctly
@@ -259,7 +259,7 @@ async fn external_reset() -> Result<(), thorium::Error> {
     let found = deadlines.iter().any(|item| item.job_id == id.id);
     is!(found, false);
     // get our reactions data
-    let react = client.reactions.get(&req.group, id.id).await?;
+    let react = client.reactions.get(&group, id.id).await?;
     // get our stage name
     let stage = &pipe.order[0][0];
     // register our test worker
@@ -277,7 +277,7 @@ async fn external_reset() -> Result<(), thorium::Error> {
     let job = client
         .jobs
         .claim(
-            &req.group,
+            &group,
             &pipe.name,
             stage,
             "cluster0",
@@ -300,7 +300,7 @@ async fn external_reset() -> Result<(), thorium::Error> {
     let found = deadlines.iter().any(|item| item.job_id == id.id);
     is!(found, false);
     // delete this reaction so we don't break any remaining tests
-    client.reactions.delete(&req.group, &id.id).await?;
+    client.reactions.delete(&group, &id.id).await?;
     // delete our worker
     generators::delete_worker_ext("external_reset", &client).await?;
     Ok(())
@@ -338,7 +338,7 @@ async fn external_proceed() -> Result<(), thorium::Error> {
     // attempt to claim a job for every stage of our reaction
     for stage in pipe.order.iter().flatten() {
         // get our reactions data
-        let react = client.reactions.get(&req.group, id.id).await?;
+        let react = client.reactions.get(&group, id.id).await?;
         // register our test worker
         generators::worker_ext(
             "cluster0",
@@ -354,7 +354,7 @@ async fn external_proceed() -> Result<(), thorium::Error> {
         let job = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 stage,
                 "cluster0",
@@ -395,7 +395,7 @@ async fn checkpoint() -> Result<(), thorium::Error> {
     for stage in pipe.order.iter().flatten().take(1) {
         for i in 0..3 {
             // get our reactions data
-            let react = client.reactions.get(&req.group, id.id).await?;
+            let react = client.reactions.get(&group, id.id).await?;
             // register our test worker
             generators::worker(
                 "cluster0",
@@ -411,7 +411,7 @@ async fn checkpoint() -> Result<(), thorium::Error> {
             let jobs = client
                 .jobs
                 .claim(
-                    &req.group,
+                    &group,
                     &pipe.name,
                     stage,
                     "cluster0",
@@ -468,7 +468,7 @@ async fn sleep() -> Result<(), thorium::Error> {
     for stage in pipe.order.iter().flatten().take(1) {
         for i in 0..3 {
             // get our reactions data
-            let react = client.reactions.get(&req.group, id.id).await?;
+            let react = client.reactions.get(&group, id.id).await?;
             // register our test worker
             generators::worker(
                 "cluster0", "node0", "sleep", &group, &pipe.name, stage, &client,
@@ -478,7 +478,7 @@ async fn sleep() -> Result<(), thorium::Error> {
             let jobs = client
                 .jobs
                 .claim(
-                    &req.group, &pipe.name, stage, "cluster0", "node0", "sleep", 1,
+                    &group, &pipe.name, stage, "cluster0", "node0", "sleep", 1,
                 )
                 .await?;
             // if this isn't the first loop then make sure our checkpoint was set
@@ -505,3 +505,111 @@ async fn sleep() -> Result<(), thorium::Error> {
     }
     Ok(())
 }
+
+#[tokio::test]
+async fn start() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // get the name of the first stage of this pipeline
+    let stage = &pipe.order[0][0];
+    // give the first stage a known timeout
+    let update = ImageUpdate::default().timeout(300);
+    client.images.update(&group, stage, &update).await?;
+    // Create a random reaction based on our pipeline request
+    let req = generators::gen_reaction(&group, &pipe, None);
+    client.reactions.create(&req).await?;
+    // register our test node
+    generators::node("cluster0", "node0", Resources::default(), &client).await?;
+    // register our test worker
+    generators::worker(
+        "cluster0", "node0", "start", &group, &pipe.name, stage, &client,
+    )
+    .await?;
+    // try to claim a job for the first stage
+    let jobs = client
+        .jobs
+        .claim(
+            &group, &pipe.name, stage, "cluster0", "node0", "start", 1,
+        )
+        .await?;
+    is!(jobs.len(), 1);
+    // claimed jobs should not have an execution deadline until they are started
+    is!(jobs[0].started, None);
+    is!(jobs[0].execution_deadline, None);
+    // simulate this job waiting to be spawned after it was claimed
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    let before = Utc::now();
+    // report that this job has started executing
+    let started = client.jobs.start(&jobs[0].id).await?;
+    // the full runtime should be granted from when the job started and not when it was queued
+    let timeout = chrono::Duration::seconds(300);
+    is!(started.execution_deadline, Some(started.started + timeout));
+    is!(started.started >= before, true);
+    is!(started.execution_deadline.unwrap() >= before + timeout, true);
+    // delete our worker
+    generators::delete_worker("start", &client).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn heartbeat() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // get the name of the first stage of this pipeline
+    let stage = &pipe.order[0][0];
+    // give the first stage a known timeout
+    let update = ImageUpdate::default().timeout(300);
+    client.images.update(&group, stage, &update).await?;
+    // Create a random reaction based on our pipeline request
+    let req = generators::gen_reaction(&group, &pipe, None);
+    client.reactions.create(&req).await?;
+    // register our test node
+    generators::node("cluster0", "node0", Resources::default(), &client).await?;
+    // register our test worker
+    generators::worker(
+        "cluster0", "node0", "heartbeat", &group, &pipe.name, stage, &client,
+    )
+    .await?;
+    // try to claim a job for the first stage
+    let jobs = client
+        .jobs
+        .claim(
+            &group, &pipe.name, stage, "cluster0", "node0", "heartbeat", 1,
+        )
+        .await?;
+    is!(jobs.len(), 1);
+    // jobs that haven't been started can't send heartbeats
+    let resp = client.jobs.heartbeat(&jobs[0].id).await;
+    fail!(resp, 409);
+    // start this job
+    let started = client.jobs.start(&jobs[0].id).await?;
+    let original = started.execution_deadline.unwrap();
+    // each heartbeat should push back our deadline by our images timeout
+    let timeout = chrono::Duration::seconds(300);
+    let heartbeat = client.jobs.heartbeat(&jobs[0].id).await?;
+    is!(heartbeat.execution_deadline, Some(original + timeout));
+    let heartbeat = client.jobs.heartbeat(&jobs[0].id).await?;
+    is!(heartbeat.execution_deadline, Some(original + timeout * 2));
+    // our test config caps extensions at 600 seconds so another heartbeat should fail
+    let resp = client.jobs.heartbeat(&jobs[0].id).await;
+    fail!(resp, 400);
+    // delete our worker
+    generators::delete_worker("heartbeat", &client).await?;
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Tests the MCP routes in Thorium
+
+use std::collections::HashSet;
+use thorium::test_utilities;
+use thorium::{Error, is};
+
+#[tokio::test]
+async fn list_tools() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // list the tools our MCP server exposes
+    let tools = client.mcp.list_tools().await?;
+    let names = tools
+        .iter()
+        .map(|tool| tool.name.as_str())
+        .collect::<HashSet<&str>>();
+    // make sure all of our built in tools are listed
+    for name in [
+        "get_sample",
+        "get_sample_results",
+        "list_sample_result_file_paths",
+        "get_sample_result_file",
+        "list_images",
+        "list_pipelines",
+        "start_tree",
+    ] {
+        is!(names.contains(name), true);
+    }
+    // every tool should describe its params with an object schema
+    for tool in &tools {
+        is!(tool.input_schema.is_object(), true);
+    }
+    Ok(())
+}
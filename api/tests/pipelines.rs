@@ -1,10 +1,12 @@
 //! Tests the Pipelines routes in Thorium
 
 use rand::{rng, seq::SliceRandom};
+use std::collections::HashMap;
 use thorium::models::{
-    ImageBan, ImageBanKind, ImageBanUpdate, ImageUpdate, NotificationLevel, NotificationParams,
-    NotificationRequest, PipelineBan, PipelineBanKind, PipelineBanUpdate, PipelineRequest,
-    PipelineUpdate,
+    Buffer, DependenciesUpdate, EventTrigger, ImageBan, ImageBanKind, ImageBanUpdate, ImageUpdate,
+    NotificationLevel, NotificationParams, NotificationRequest, PipelineBan, PipelineBanKind,
+    PipelineBanUpdate, PipelineRequest, PipelineTriggerRequest, PipelineUpdate, PlanWarning,
+    ResultDependencySettingsUpdate, SampleRequest, TagRequest, TagType,
 };
 use thorium::test_utilities::{self, generators};
 use thorium::{Error, contains, fail, is, is_in, unwrap_variant, vec_in_vec};
@@ -117,6 +119,56 @@ async fn get() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn plan() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // generate a pipeline that runs its first image again in its last stage
+    let mut pipeline = generators::gen_pipe(&group, 3, false, &client).await?;
+    let images: Vec<Vec<String>> = serde_json::from_value(pipeline.order)?;
+    let images = &images[0];
+    pipeline.order = serde_json::json!([[images[0]], [images[1], images[2]], [images[0]]]);
+    let resp = client.pipelines.create(&pipeline).await?;
+    is!(resp.status().as_u16(), 204);
+    // plan this pipeline and make sure it matches its order
+    let plan = client.pipelines.plan(&group, &pipeline.name).await?;
+    is!(plan.stages.len(), 3);
+    is!(
+        &plan.stages[1].images,
+        &vec![images[1].clone(), images[2].clone()]
+    );
+    is!(plan.edges.len(), 4);
+    // running the first image again is a new job in a later stage and not a cycle
+    is!(plan.edges[0].from.stage, 0);
+    is!(&plan.edges[0].from.image, &images[0]);
+    is!(plan.edges[3].to.stage, 2);
+    is!(&plan.edges[3].to.image, &images[0]);
+    is!(plan.warnings.len(), 0);
+    // make the second image need results from the last stage's image
+    let update = ImageUpdate::default().dependencies(
+        DependenciesUpdate::default()
+            .results(ResultDependencySettingsUpdate::default().image(&images[2])),
+    );
+    client.images.update(&group, &images[1], &update).await?;
+    // planning should flag that these results won't exist yet
+    let plan = client.pipelines.plan(&group, &pipeline.name).await?;
+    is!(
+        plan.warnings,
+        vec![PlanWarning::MissingResults {
+            stage: 1,
+            image: images[1].clone(),
+            dependency: images[2].clone(),
+        }]
+    );
+    // planning a pipeline should not create any reactions
+    let mut reactions = client.reactions.list(&group, &pipeline.name);
+    reactions.next().await?;
+    is!(reactions.names.len(), 0);
+    Ok(())
+}
+
 #[tokio::test]
 async fn update() -> Result<(), Error> {
     // get admin client
@@ -159,6 +211,118 @@ async fn update() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn triggers() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group and a pipeline to trigger
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    let pipe_req = generators::gen_pipe(&group, 1, false, &client).await?;
+    client.pipelines.create(&pipe_req).await?;
+    // build a rule to run this pipeline when a file gets a unique tag
+    let value = Uuid::new_v4().to_string();
+    let trigger = EventTrigger::Tag {
+        tag_types: vec![TagType::Files],
+        required: HashMap::from([("TriggerTest".to_owned(), vec![value.clone()])]),
+        not: HashMap::default(),
+    };
+    let req = PipelineTriggerRequest::new("tagged", trigger.clone());
+    let resp = client
+        .pipelines
+        .create_trigger(&group, &pipe_req.name, &req)
+        .await?;
+    is!(resp.status().as_u16(), 204);
+    // make sure our rule is listed
+    let listed = client
+        .pipelines
+        .list_triggers(&group, &pipe_req.name)
+        .await?;
+    is!(listed.triggers.get("tagged"), Some(&trigger));
+    // rule names must be unique within a pipeline
+    let resp = client
+        .pipelines
+        .create_trigger(&group, &pipe_req.name, &req)
+        .await;
+    fail!(resp, 409);
+    // rules can't be added to pipelines or groups that don't exist
+    let resp = client
+        .pipelines
+        .create_trigger(&group, "missing", &req)
+        .await;
+    fail!(resp, 404);
+    let resp = client
+        .pipelines
+        .create_trigger("missing-group", &pipe_req.name, &req)
+        .await;
+    fail!(resp, 404);
+    // upload a file and tag it so our rule is triggered
+    let file_req = SampleRequest::new_buffer(Buffer::new(value.as_bytes().to_vec()), vec![&group]);
+    let hashes = client.files.create(file_req).await?;
+    let tag_req = TagRequest::default()
+        .group(&group)
+        .add("TriggerTest", &value);
+    client.files.tag(&hashes.sha256, &tag_req).await?;
+    // wait for the event handler to create a reaction for our file
+    let mut triggered = false;
+    for _ in 0..60 {
+        let mut reactions = client.reactions.list(&group, &pipe_req.name).details();
+        reactions.next().await?;
+        if reactions
+            .details
+            .iter()
+            .any(|reaction| reaction.samples.contains(&hashes.sha256))
+        {
+            triggered = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    is!(triggered, true);
+    // delete our rule and make sure it is gone
+    let resp = client
+        .pipelines
+        .delete_trigger(&group, &pipe_req.name, "tagged")
+        .await?;
+    is!(resp.status().as_u16(), 204);
+    let listed = client
+        .pipelines
+        .list_triggers(&group, &pipe_req.name)
+        .await?;
+    is!(listed.triggers.is_empty(), true);
+    let resp = client
+        .pipelines
+        .delete_trigger(&group, &pipe_req.name, "tagged")
+        .await;
+    fail!(resp, 404);
+    Ok(())
+}
+
+#[tokio::test]
+async fn triggers_concurrent() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group and a pipeline to add triggers to
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    let pipe_req = generators::gen_pipe(&group, 1, false, &client).await?;
+    client.pipelines.create(&pipe_req).await?;
+    // add a bunch of differently named rules at the same time
+    let reqs = (0..10)
+        .map(|i| PipelineTriggerRequest::new(format!("rule{i}"), EventTrigger::NewSample))
+        .collect::<Vec<PipelineTriggerRequest>>();
+    futures::future::try_join_all(
+        reqs.iter()
+            .map(|req| client.pipelines.create_trigger(&group, &pipe_req.name, req)),
+    )
+    .await?;
+    // make sure none of our rules were lost
+    let listed = client
+        .pipelines
+        .list_triggers(&group, &pipe_req.name)
+        .await?;
+    is!(listed.triggers.len(), reqs.len());
+    Ok(())
+}
+
 #[tokio::test]
 async fn delete() -> Result<(), Error> {
     // get admin client
@@ -1,12 +1,18 @@
 //! Tests the Images routes in Thorium
 
+use futures::TryStreamExt;
+use std::collections::HashMap;
+use std::io::Read;
 use thorium::models::{
-    GenericJobArgsUpdate, ImageBan, ImageBanKind, ImageBanUpdate, ImageUpdate, PipelineBan,
-    PipelineBanKind, PipelineBanUpdate, PipelineRequest, PipelineUpdate, ReactionStatus,
-    ReactionUpdate, Resources,
+    Actions, Buffer, GenericJobArgs, GenericJobArgsUpdate, GroupUpdate, GroupUsersUpdate, ImageBan,
+    ImageBanKind, ImageBanUpdate, ImageUpdate, PipelineBan, PipelineBanKind, PipelineBanUpdate,
+    PipelineRequest, PipelineUpdate, ReactionCacheFileUpdate, ReactionListParams, ReactionRequest,
+    ReactionSamplesAdd, ReactionStatus, ReactionTreeParams, ReactionUpdate, Resources,
+    StatusUpdate, UserSettingsUpdate, UserUpdate, ZipDownloadParams,
 };
+use thorium::test_utilities::fixtures::{JobBuilder, ReactionBuilder};
 use thorium::test_utilities::{self, generators};
-use thorium::{Error, fail, is, is_empty, is_in, is_not_in, vec_in_vec};
+use thorium::{Error, fail, is, is_empty, is_in, is_not, is_not_in, vec_in_vec};
 use uuid::Uuid;
 
 #[tokio::test]
@@ -44,6 +50,365 @@ async fn create_bulk() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn create_idempotent() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // Create a random reaction with an idempotency key
+    let key = Uuid::new_v4().to_string();
+    let react_req = generators::gen_reaction(&group, &pipe, None).idempotency_key(&key);
+    let first = client.reactions.create(&react_req).await?;
+    is!(first.deduplicated, false);
+    // retry creating the same reaction
+    let second = client.reactions.create(&react_req).await?;
+    // make sure we got our original reaction back instead of a new one
+    is!(second.deduplicated, true);
+    is!(second.id, first.id);
+    // make sure our key was saved on our reaction
+    let created = client.reactions.get(&group, first.id).await?;
+    is!(created.idempotency_key, Some(key));
+    // deleting our reaction releases its key so it can be created again
+    client.reactions.delete(&group, &first.id).await?;
+    let recreated = client.reactions.create(&react_req).await?;
+    is!(recreated.deduplicated, false);
+    is_not!(recreated.id, first.id);
+    // a reaction without a key should never be deduplicated
+    let no_key = generators::gen_reaction(&group, &pipe, None);
+    let third = client.reactions.create(&no_key).await?;
+    let fourth = client.reactions.create(&no_key).await?;
+    is!(third.deduplicated, false);
+    is!(fourth.deduplicated, false);
+    is_not!(third.id, fourth.id);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_from_fixture() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // seed a reaction with custom args for the first image in our pipeline
+    let image = pipe.order[0][0].clone();
+    let args = GenericJobArgs::default()
+        .positionals(vec!["corn"])
+        .kwarg("--field", vec!["north"])
+        .switch("--verbose");
+    let fixture = ReactionBuilder::new(&group, &pipe.name)
+        .args(&image, args.clone())
+        .tag("fixture")
+        .priority(5);
+    let react_req = fixture.request();
+    let resp = client.reactions.create(&react_req).await?;
+    // get the created reaction
+    let created = client.reactions.get(&group, resp.id).await?;
+    // make sure our reaction was created with our custom args
+    is!(created, react_req);
+    let created_args = created.args.get(&image).cloned();
+    let expected = Some(args);
+    is!(created_args, expected);
+    // jobs built from this reaction inherit its args
+    let job = JobBuilder::for_reaction(&created, &image).build();
+    is!(job.args, created.args[&image]);
+    is!(job.reaction, created.id);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_bulk_idempotent() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // build two reactions that share a key and one with a different key
+    let key = Uuid::new_v4().to_string();
+    let reqs = vec![
+        generators::gen_reaction(&group, &pipe, None).idempotency_key(&key),
+        generators::gen_reaction(&group, &pipe, None).idempotency_key(&key),
+        generators::gen_reaction(&group, &pipe, None).idempotency_key(Uuid::new_v4().to_string()),
+    ];
+    let resp = client.reactions.create_bulk(&reqs).await?;
+    is_empty!(resp.errors);
+    // only the first request with our shared key should be created
+    is!(resp.created.len(), 2);
+    is!(resp.deduplicated.len(), 1);
+    is!(resp.deduplicated.get(&1), Some(&resp.created[0]));
+    // retry the entire batch
+    let retry = client.reactions.create_bulk(&reqs).await?;
+    is_empty!(retry.errors);
+    // make sure no new reactions were created
+    is_empty!(retry.created);
+    is!(retry.deduplicated.len(), 3);
+    is!(retry.deduplicated.get(&0), Some(&resp.created[0]));
+    is!(retry.deduplicated.get(&1), Some(&resp.created[0]));
+    is!(retry.deduplicated.get(&2), Some(&resp.created[1]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_bulk_missing_group() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create two groups to test reactions creation in
+    let groups = generators::groups(2, &client).await?;
+    // create a random pipeline in each group
+    let mut reqs = Vec::with_capacity(3);
+    for group in &groups {
+        let pipe_req = generators::pipelines(&group.name, 1, false, &client)
+            .await?
+            .remove(0);
+        let pipe = client.pipelines.get(&group.name, &pipe_req.name).await?;
+        reqs.push(generators::gen_reaction(&group.name, &pipe, None));
+    }
+    // add a reaction for a group that doesn't exist
+    let mut missing = reqs[0].clone();
+    missing.group = Some(format!("{}-missing", groups[0].name));
+    reqs.push(missing);
+    // make sure the missing group is reported and no reactions are created
+    let resp = client.reactions.create_bulk(&reqs).await;
+    fail!(resp, 404, &format!("{}-missing", groups[0].name));
+    for group in &groups {
+        let mut cursor = client
+            .reactions
+            .list_group(&group.name, &ReactionStatus::Created);
+        cursor.next().await?;
+        is_empty!(cursor.names);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_default_group() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // create a user to create reactions without a group
+    let user_client = generators::client(&client).await?;
+    let username = user_client.users.info().await?.username;
+    // reactions without a group can't be created without a default group
+    let react_req = ReactionRequest::in_default_group(&pipe_req.name);
+    let resp = user_client.reactions.create(&react_req).await;
+    fail!(resp, 400, "has no default group");
+    // users can't default to groups they aren't a member of
+    let update = UserUpdate {
+        password: None,
+        email: None,
+        role: None,
+        settings: Some(UserSettingsUpdate::default().default_group(&group)),
+    };
+    let resp = client.users.update(&username, update).await;
+    fail!(resp, 400, "not a member");
+    // add our user to our group and set it as their default group
+    let group_update =
+        GroupUpdate::default().users(GroupUsersUpdate::default().direct_add(username.clone()));
+    client.groups.update(&group, &group_update).await?;
+    let update = UserUpdate {
+        password: None,
+        email: None,
+        role: None,
+        settings: Some(UserSettingsUpdate::default().default_group(&group)),
+    };
+    client.users.update(&username, update).await?;
+    let info = user_client.users.info().await?;
+    is!(info.settings.default_group, Some(group.clone()));
+    // our reaction should now be created in our default group
+    let resp = user_client.reactions.create(&react_req).await?;
+    let created = user_client.reactions.get(&group, resp.id).await?;
+    is!(created.group, group);
+    is!(created, react_req);
+    // users removed from their default group must set a group again
+    let group_update =
+        GroupUpdate::default().users(GroupUsersUpdate::default().direct_remove(username.clone()));
+    client.groups.update(&group, &group_update).await?;
+    let resp = user_client.reactions.create(&react_req).await;
+    fail!(resp, 400, "no longer a member of their default group");
+    // clear our default group and make sure a group is required again
+    let update = UserUpdate {
+        password: None,
+        email: None,
+        role: None,
+        settings: Some(UserSettingsUpdate::default().clear_default_group()),
+    };
+    client.users.update(&username, update).await?;
+    let resp = user_client.reactions.create(&react_req).await;
+    fail!(resp, 400, "has no default group");
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_bulk_streaming() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // build more reaction requests than fit in a single chunk with some invalid ones
+    let invalid = [1, 17];
+    let reqs = (0..25)
+        .map(|index| {
+            let req = generators::gen_reaction(&group, &pipe, None);
+            // set args for an image that isn't in our pipeline to make this request fail
+            if invalid.contains(&index) {
+                req.args("NotInPipeline", GenericJobArgs::default())
+            } else {
+                req
+            }
+        })
+        .collect::<Vec<_>>();
+    // create our reactions in chunks and keep going after errors
+    let resp = client
+        .reactions
+        .create_bulk_streaming(&reqs, 10, true)
+        .await?;
+    // make sure all of our valid reactions were created
+    is!(resp.created.len(), reqs.len() - invalid.len());
+    for id in &resp.created {
+        client.reactions.get(&group, *id).await?;
+    }
+    // make sure our errors point at the right requests
+    let mut errors = resp.errors.keys().copied().collect::<Vec<usize>>();
+    errors.sort_unstable();
+    is!(errors, invalid.to_vec());
+    // create our reactions again but stop after the first wave of chunks with errors
+    let resp = client
+        .reactions
+        .create_bulk_streaming(&reqs, 2, false)
+        .await?;
+    // only the first 4 chunks should have been submitted
+    is!(resp.created.len(), 7);
+    is!(resp.errors.keys().copied().collect::<Vec<usize>>(), vec![1]);
+    // a chunk size of 0 is invalid
+    let resp = client.reactions.create_bulk_streaming(&reqs, 0, true).await;
+    is!(resp.is_err(), true);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_bulk_limit() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // get the max number of reactions we can create at once
+    let limit = client.basic.capabilities().await?.limits.max_bulk_reactions;
+    // build one more reaction request than we are allowed to send
+    let mut reqs = (0..=limit)
+        .map(|_| generators::gen_reaction(&group, &pipe, None))
+        .collect::<Vec<_>>();
+    // make sure an over limit request is rejected
+    let resp = client.reactions.create_bulk(&reqs).await;
+    fail!(resp, 400);
+    // make sure a request right at our limit succeeds
+    reqs.pop();
+    let resp = client.reactions.create_bulk(&reqs).await?;
+    is_empty!(resp.errors);
+    is!(resp.created.len(), limit);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_bulk_by_user_limit() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    let username = client.basic.whoami().await?.username;
+    // get our limits
+    let limit = client.basic.capabilities().await?.limits.max_bulk_reactions;
+    let users_limit = test_utilities::CONF
+        .thorium
+        .request_size_limits
+        .bulk_reaction_users;
+    // make sure too many users in one request is rejected before they are looked up
+    let mut map = HashMap::with_capacity(users_limit + 1);
+    for _ in 0..=users_limit {
+        let reqs = vec![generators::gen_reaction(&group, &pipe, None)];
+        map.insert(Uuid::new_v4().to_string(), reqs);
+    }
+    let resp = client.reactions.create_bulk_by_user(&map).await;
+    fail!(resp, 400);
+    // make sure too many reactions for one user is rejected
+    let mut reqs = (0..=limit)
+        .map(|_| generators::gen_reaction(&group, &pipe, None))
+        .collect::<Vec<_>>();
+    let mut map = HashMap::with_capacity(1);
+    map.insert(username.clone(), reqs.clone());
+    let resp = client.reactions.create_bulk_by_user(&map).await;
+    fail!(resp, 400);
+    // make sure a request right at our limit succeeds
+    reqs.pop();
+    map.insert(username.clone(), reqs);
+    let resp = client.reactions.create_bulk_by_user(&map).await?;
+    let created = resp.get(&username).map(|resp| resp.created.len());
+    is!(created, Some(limit));
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_oversized_body() -> Result<(), Error> {
+    use base64::Engine as _;
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // build the auth header for our admin
+    let token = client.users.info().await?.token;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(token.as_bytes());
+    // build a json body just over our json body limit
+    let limit = test_utilities::CONF
+        .thorium
+        .request_size_limits
+        .json_bytes();
+    let body = format!("{{\"group\":\"{}\"}}", "a".repeat(limit));
+    // make sure this body is rejected before it is read into memory
+    let resp = reqwest::Client::new()
+        .post(format!("{}/api/reactions/", client.host))
+        .header("authorization", format!("token {encoded}"))
+        .header("content-type", "application/json")
+        .body(body)
+        .send()
+        .await?;
+    is!(resp.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+    Ok(())
+}
+
 #[tokio::test]
 async fn create_sub_reaction() -> Result<(), Error> {
     // get admin client
@@ -163,6 +528,46 @@ async fn list_details() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn list_details_stream() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // setup 20 random reactions
+    let (reactions, _) = generators::reactions(&group, 20, None, &client).await?;
+    let pipeline = &reactions[0].pipeline;
+    // list the reactions we just created with a buffered response
+    let mut cursor = client
+        .reactions
+        .list(&group, pipeline)
+        .details()
+        .page_size(100);
+    cursor.next().await?;
+    let buffered = cursor
+        .details
+        .iter()
+        .map(|react| react.id)
+        .collect::<Vec<Uuid>>();
+    // stream the same page of reactions as newline delimited json
+    let params = ReactionListParams {
+        cursor: 0,
+        limit: 100,
+    };
+    let stream = client
+        .reactions
+        .list_details_stream(&group, pipeline, &params)
+        .await?;
+    // make sure we streamed the same reactions in the same order
+    let streamed = stream
+        .map_ok(|react| react.id)
+        .try_collect::<Vec<Uuid>>()
+        .await?;
+    is!(streamed.len(), 20);
+    is!(streamed, buffered);
+    Ok(())
+}
+
 #[tokio::test]
 async fn list_status() -> Result<(), Error> {
     // get admin client
@@ -241,6 +646,71 @@ async fn list_tag_details() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn list_tags() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // build reactions with all of our tags and reactions with only some of them
+    let mut reqs = Vec::with_capacity(20);
+    for _ in 0..10 {
+        reqs.push(generators::gen_reaction(&group, &pipe, Some("multi")).tag("crop=corn"));
+        reqs.push(generators::gen_reaction(&group, &pipe, Some("multi")));
+    }
+    let resp = client.reactions.create_bulk(&reqs).await?;
+    is_empty!(resp.errors);
+    // build the tags our reactions must have
+    let mut tags = HashMap::default();
+    tags.insert("multi".to_owned(), vec![]);
+    tags.insert("crop".to_owned(), vec!["corn".to_owned()]);
+    // list the reactions with all of our tags
+    let mut cursor = client.reactions.list_tags(&group, &tags).page_size(100);
+    let mut names = Vec::with_capacity(10);
+    while !cursor.exhausted {
+        cursor.next().await?;
+        names.append(&mut cursor.names);
+    }
+    // make sure only the reactions with all of our tags were listed
+    is!(names.len(), 10);
+    for (index, id) in resp.created.iter().enumerate() {
+        if index % 2 == 0 {
+            is_in!(names, id.to_string());
+        } else {
+            is_not_in!(names, id.to_string());
+        }
+    }
+    // listing without any tags should fail
+    let mut cursor = client.reactions.list_tags(&group, &HashMap::default());
+    fail!(cursor.next().await, 400);
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_tags_details() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // setup 20 random reactions
+    let (reactions, _) = generators::reactions(&group, 20, Some("multi_dets"), &client).await?;
+    // build the tags our reactions must have
+    let mut tags = HashMap::default();
+    tags.insert("multi_dets".to_owned(), vec![]);
+    tags.insert("thorium".to_owned(), vec![]);
+    // list the reactions we just created
+    let mut cursor = client.reactions.list_tags(&group, &tags).details();
+    cursor.next().await?;
+    // make sure all the reactions we tried to create are in our list
+    vec_in_vec!(&cursor.details, &reactions);
+    Ok(())
+}
+
 #[tokio::test]
 async fn list_group_set() -> Result<(), Error> {
     // get admin client
@@ -263,6 +733,54 @@ async fn list_group_set() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn list_group_set_priority() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reaction priorities in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // create a high priority reaction with a shorter sla than our low priority one
+    let high_req = generators::gen_reaction(&group, &pipe, None)
+        .sla(3600)
+        .priority(1000);
+    let high = client.reactions.create(&high_req).await?.id;
+    let low_req = generators::gen_reaction(&group, &pipe, None)
+        .sla(7200)
+        .priority(-10);
+    let low = client.reactions.create(&low_req).await?.id;
+    // make sure our priorities were clamped and saved
+    let high_reaction = client.reactions.get(&group, high).await?;
+    let low_reaction = client.reactions.get(&group, low).await?;
+    is!(high_reaction.priority, 100);
+    is!(low_reaction.priority, -10);
+    // list the reactions we just created
+    let mut cursor = client
+        .reactions
+        .list_group(&group, &ReactionStatus::Created);
+    cursor.next().await?;
+    // make sure our high priority reaction is returned first
+    is!(cursor.names, vec![high.to_string(), low.to_string()]);
+    // priority shifts deadlines for every group so only admins can set it
+    let user_client = generators::client(&client).await?;
+    let username = user_client.users.info().await?.username;
+    let group_update =
+        GroupUpdate::default().users(GroupUsersUpdate::default().direct_add(username));
+    client.groups.update(&group, &group_update).await?;
+    let resp = user_client.reactions.create(&high_req).await;
+    fail!(resp, 401, "Only admins");
+    let resp = user_client.reactions.create_bulk(&[high_req]).await;
+    fail!(resp, 401, "Only admins");
+    // users can still create reactions with the default priority
+    let default_req = generators::gen_reaction(&group, &pipe, None);
+    user_client.reactions.create(&default_req).await?;
+    Ok(())
+}
+
 #[tokio::test]
 async fn list_group_set_details() -> Result<(), Error> {
     // get admin client
@@ -328,6 +846,58 @@ async fn list_sub_details() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn tree() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // setup 1 random parent reactions
+    let (_, resp) = generators::reactions(&group, 1, None, &client).await?;
+    // make sure no errors were returned
+    is_empty!(resp.errors);
+    let root = resp.created[0];
+    // setup 3 sub reactions under our root reaction
+    let (_, subs, _) = generators::sub_reactions(&group, 3, &root, &client).await?;
+    // setup 2 sub reactions under our first sub reaction
+    let (_, grand_subs, _) = generators::sub_reactions(&group, 2, &subs[0].id, &client).await?;
+    // get the full tree for our root reaction
+    let tree = client
+        .trees
+        .reactions(&group, &root, &ReactionTreeParams::default())
+        .await?;
+    // make sure our tree has the right structure
+    is!(tree.reaction.id, root);
+    is!(tree.children.len(), 3);
+    is!(tree.count(), 6);
+    is!(tree.truncated, false);
+    let child_ids = tree
+        .children
+        .iter()
+        .map(|child| child.reaction.id)
+        .collect::<Vec<Uuid>>();
+    for sub in &subs {
+        is_in!(child_ids, sub.id);
+    }
+    // make sure our grand children are under the correct sub reaction
+    let first = tree.find(&subs[0].id).unwrap();
+    is!(first.children.len(), 2);
+    for grand_sub in &grand_subs {
+        is!(first.find(&grand_sub.id).is_some(), true);
+        is!(tree.find(&grand_sub.id).unwrap().children.is_empty(), true);
+    }
+    // only get the first level of our tree
+    let shallow = client
+        .trees
+        .reactions(&group, &root, &ReactionTreeParams::default().depth(1))
+        .await?;
+    // make sure our tree was cut off at the first level
+    is!(shallow.count(), 4);
+    is!(shallow.find(&subs[0].id).unwrap().truncated, true);
+    is!(shallow.find(&subs[1].id).unwrap().truncated, false);
+    Ok(())
+}
+
 #[tokio::test]
 async fn list_sub_status() -> Result<(), Error> {
     // get admin client
@@ -403,12 +973,10 @@ async fn claim() -> Result<(), Error> {
         // try to claim a job
         let jobs = client
             .jobs
-            .claim(
-                &req.group, &pipe.name, stage, "cluster0", "node0", "claim", 1,
-            )
+            .claim(&group, &pipe.name, stage, "cluster0", "node0", "claim", 1)
             .await?;
         // get our reactions data
-        let react = client.reactions.get(&req.group, id.id).await?;
+        let react = client.reactions.get(&group, id.id).await?;
         // make sure this job matches our reaction
         is!(&jobs, react);
         // build random stage logs
@@ -457,12 +1025,10 @@ async fn expires() -> Result<(), Error> {
         // try to claim a job
         let jobs = client
             .jobs
-            .claim(
-                &req.group, &pipe.name, stage, "cluster0", "node0", "expires", 1,
-            )
+            .claim(&group, &pipe.name, stage, "cluster0", "node0", "expires", 1)
             .await?;
         // get our reactions data
-        let react = client.reactions.get(&req.group, id.id).await?;
+        let react = client.reactions.get(&group, id.id).await?;
         // make sure this job matches our reaction
         is!(&jobs, react);
         // build random stage logs
@@ -541,7 +1107,7 @@ async fn claim_generator() -> Result<(), Error> {
         let jobs = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 &stages[0],
                 "cluster0",
@@ -551,7 +1117,7 @@ async fn claim_generator() -> Result<(), Error> {
             )
             .await?;
         // get our reactions data
-        let react = client.reactions.get(&req.group, id.id).await?;
+        let react = client.reactions.get(&group, id.id).await?;
         // make sure this job matches our reaction
         is!(&jobs, react);
         // build random stage logs
@@ -564,7 +1130,7 @@ async fn claim_generator() -> Result<(), Error> {
         let next_stage = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 &stages[1],
                 "cluster0",
@@ -593,7 +1159,7 @@ async fn claim_generator() -> Result<(), Error> {
     let jobs = client
         .jobs
         .claim(
-            &req.group,
+            &group,
             &pipe.name,
             &stages[0],
             "cluster0",
@@ -603,7 +1169,7 @@ async fn claim_generator() -> Result<(), Error> {
         )
         .await?;
     // get our reactions data
-    let react = client.reactions.get(&req.group, id.id).await?;
+    let react = client.reactions.get(&group, id.id).await?;
     // make sure this job matches our reaction
     is!(&jobs, react);
     // build random stage logs
@@ -614,7 +1180,7 @@ async fn claim_generator() -> Result<(), Error> {
     let jobs = client
         .jobs
         .claim(
-            &req.group,
+            &group,
             &pipe.name,
             &stages[1],
             "cluster0",
@@ -624,7 +1190,7 @@ async fn claim_generator() -> Result<(), Error> {
         )
         .await?;
     // get our reactions data
-    let react = client.reactions.get(&req.group, id.id).await?;
+    let react = client.reactions.get(&group, id.id).await?;
     // make sure this job matches our reaction
     is!(&jobs, react);
     // build random stage logs
@@ -680,7 +1246,7 @@ async fn claim_generator_fail() -> Result<(), Error> {
         let jobs = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 &stages[0],
                 "cluster0",
@@ -690,7 +1256,7 @@ async fn claim_generator_fail() -> Result<(), Error> {
             )
             .await?;
         // get our reactions data
-        let react = client.reactions.get(&req.group, id.id).await?;
+        let react = client.reactions.get(&group, id.id).await?;
         // make sure this job matches our reaction
         is!(&jobs, react);
         // build random stage logs
@@ -717,7 +1283,7 @@ async fn claim_generator_fail() -> Result<(), Error> {
     let jobs = client
         .jobs
         .claim(
-            &req.group,
+            &group,
             &pipe.name,
             &stages[0],
             "cluster0",
@@ -727,7 +1293,7 @@ async fn claim_generator_fail() -> Result<(), Error> {
         )
         .await?;
     // get our reactions data
-    let react = client.reactions.get(&req.group, id.id).await?;
+    let react = client.reactions.get(&group, id.id).await?;
     // make sure this job matches our reaction
     is!(&jobs, react);
     // build random stage logs
@@ -738,7 +1304,7 @@ async fn claim_generator_fail() -> Result<(), Error> {
     let jobs = client
         .jobs
         .claim(
-            &req.group,
+            &group,
             &pipe.name,
             &stages[1],
             "cluster0",
@@ -800,7 +1366,7 @@ async fn claim_sub_reacts_status() -> Result<(), Error> {
         let jobs = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &sub_pipe.name,
                 stage,
                 "cluster0",
@@ -859,7 +1425,7 @@ async fn claim_sub_reacts() -> Result<(), Error> {
         let jobs = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 stage,
                 "cluster0",
@@ -869,7 +1435,7 @@ async fn claim_sub_reacts() -> Result<(), Error> {
             )
             .await?;
         // get our reactions data
-        let react = client.reactions.get(&req.group, resp.id).await?;
+        let react = client.reactions.get(&group, resp.id).await?;
         // make sure this job matches our reaction
         is!(&jobs, react);
         // create 3 random sub reactions based on a random pipeline
@@ -883,7 +1449,7 @@ async fn claim_sub_reacts() -> Result<(), Error> {
         let jobs = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 stage,
                 "cluster0",
@@ -915,7 +1481,7 @@ async fn claim_sub_reacts() -> Result<(), Error> {
                 let jobs = client
                     .jobs
                     .claim(
-                        &req.group,
+                        &group,
                         &sub_pipe.name,
                         stage,
                         "cluster0",
@@ -979,7 +1545,7 @@ async fn claim_sub_reacts_fail() -> Result<(), Error> {
         let jobs = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 stage,
                 "cluster0",
@@ -989,7 +1555,7 @@ async fn claim_sub_reacts_fail() -> Result<(), Error> {
             )
             .await?;
         // get our reactions data
-        let react = client.reactions.get(&req.group, resp.id).await?;
+        let react = client.reactions.get(&group, resp.id).await?;
         // make sure this job matches our reaction
         is!(&jobs, react);
         // create 3 random sub reactions based on a random pipeline
@@ -1003,7 +1569,7 @@ async fn claim_sub_reacts_fail() -> Result<(), Error> {
         let jobs = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 stage,
                 "cluster0",
@@ -1035,7 +1601,7 @@ async fn claim_sub_reacts_fail() -> Result<(), Error> {
                 let jobs = client
                     .jobs
                     .claim(
-                        &req.group,
+                        &group,
                         &sub_pipe.name,
                         stage,
                         "cluster0",
@@ -1099,7 +1665,7 @@ async fn claim_sub_reacts_race() -> Result<(), Error> {
         let jobs = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 stage,
                 "cluster0",
@@ -1109,7 +1675,7 @@ async fn claim_sub_reacts_race() -> Result<(), Error> {
             )
             .await?;
         // get our reactions data
-        let react = client.reactions.get(&req.group, resp.id).await?;
+        let react = client.reactions.get(&group, resp.id).await?;
         // make sure this job matches our reaction
         is!(&jobs, react);
         // create 3 random sub reactions based on a random pipeline
@@ -1136,7 +1702,7 @@ async fn claim_sub_reacts_race() -> Result<(), Error> {
                 let jobs = client
                     .jobs
                     .claim(
-                        &req.group,
+                        &group,
                         &sub_pipe.name,
                         stage,
                         "cluster0",
@@ -1157,7 +1723,7 @@ async fn claim_sub_reacts_race() -> Result<(), Error> {
         let check_jobs = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 stage,
                 "cluster0",
@@ -1220,7 +1786,7 @@ async fn claim_sub_reacts_race_fail() -> Result<(), Error> {
         let jobs = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 stage,
                 "cluster0",
@@ -1230,7 +1796,7 @@ async fn claim_sub_reacts_race_fail() -> Result<(), Error> {
             )
             .await?;
         // get our reactions data
-        let react = client.reactions.get(&req.group, resp.id).await?;
+        let react = client.reactions.get(&group, resp.id).await?;
         // make sure this job matches our reaction
         is!(&jobs, react);
         // create 3 random sub reactions based on a random pipeline
@@ -1257,7 +1823,7 @@ async fn claim_sub_reacts_race_fail() -> Result<(), Error> {
                 let jobs = client
                     .jobs
                     .claim(
-                        &req.group,
+                        &group,
                         &sub_pipe.name,
                         stage,
                         "cluster0",
@@ -1289,7 +1855,7 @@ async fn claim_sub_reacts_race_fail() -> Result<(), Error> {
         let check_jobs = client
             .jobs
             .claim(
-                &req.group,
+                &group,
                 &pipe.name,
                 stage,
                 "cluster0",
@@ -1585,3 +2151,228 @@ async fn parent_ephemeral() -> Result<(), Error> {
     is!(download, "I am a parent test file");
     Ok(())
 }
+
+#[tokio::test]
+async fn download_cache_zip() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // Create a random reaction based on our pipeline request
+    let react_req = generators::gen_reaction(&group, &pipe, None);
+    let resp = client.reactions.create(&react_req).await?;
+    // add two files to this reactions cache
+    let update = ReactionCacheFileUpdate::default()
+        .buffer(Buffer::new("I am the first cache file").name("first.txt"))
+        .buffer(Buffer::new("I am the second cache file").name("second.txt"));
+    client
+        .reactions
+        .update_cache_files(&group, resp.id, update)
+        .await?;
+    // download our cache files as a zip with a custom password
+    let params = ZipDownloadParams::default().password("corn");
+    let zipped = client
+        .reactions
+        .download_cache_zip(&group, &resp.id, &params)
+        .await?;
+    // open our zip and make sure it has both of our cache files
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(zipped))
+        .map_err(|err| Error::new(err.to_string()))?;
+    is!(archive.len(), 2);
+    for (name, expected) in [
+        ("first.txt", "I am the first cache file"),
+        ("second.txt", "I am the second cache file"),
+    ] {
+        // decrypt this entry with our custom password
+        let mut entry = archive
+            .by_name_decrypt(name, b"corn")
+            .map_err(|err| Error::new(err.to_string()))?;
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents)?;
+        is!(contents, expected);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_status_logs() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // Create a random reaction based on our pipeline request
+    let react_req = generators::gen_reaction(&group, &pipe, None);
+    let resp = client.reactions.create(&react_req).await?;
+    let reaction = client.reactions.get(&group, resp.id).await?;
+    // build a batch of status updates with increasing timestamps
+    let start = chrono::Utc::now() - chrono::Duration::minutes(10);
+    let updates = (0..5)
+        .map(|i| {
+            StatusUpdate::for_reaction(&reaction, Actions::ReactionProceeding)
+                .msg(format!("batch-{i}"))
+                .update("index", i.to_string())
+                .timestamp(start + chrono::Duration::seconds(i))
+        })
+        .collect::<Vec<StatusUpdate>>();
+    // send all of our updates at once
+    client
+        .reactions
+        .add_status_logs(&group, &resp.id, &updates)
+        .await?;
+    // get our status logs and only keep the ones from our batch
+    let logs = client.reactions.status_logs(&group, &resp.id).await?;
+    let batch = logs
+        .iter()
+        .filter(|log| {
+            log.msg
+                .as_ref()
+                .is_some_and(|msg| msg.starts_with("batch-"))
+        })
+        .collect::<Vec<&StatusUpdate>>();
+    // make sure all of our updates were saved in order with their timestamps
+    is!(batch.len(), updates.len());
+    for (saved, sent) in batch.iter().zip(updates.iter()) {
+        is!(saved.msg, sent.msg);
+        is!(saved.timestamp, sent.timestamp);
+        is!(saved.update, sent.update);
+        is!(saved.reaction, resp.id.to_string());
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_samples() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // register our test node
+    generators::node("cluster0", "node0", Resources::default(), &client).await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // Create a random reaction based on our pipeline request
+    let req = generators::gen_reaction(&group, &pipe, None);
+    let id = client.reactions.create(&req).await?;
+    // claim a job from the first stage so our reaction is running
+    let stage = &pipe.order[0][0];
+    generators::worker(
+        "cluster0",
+        "node0",
+        "add_samples",
+        &group,
+        &pipe.name,
+        stage,
+        &client,
+    )
+    .await?;
+    client
+        .jobs
+        .claim(
+            &group,
+            &pipe.name,
+            stage,
+            "cluster0",
+            "node0",
+            "add_samples",
+            1,
+        )
+        .await?;
+    // upload a sample to add to our reaction
+    let sample = client
+        .files
+        .create(generators::gen_sample(&group))
+        .await?
+        .sha256;
+    // add our sample to this running reaction
+    let add = ReactionSamplesAdd::default().sample(&sample).update_jobs();
+    let reaction = client.reactions.add_samples(&group, &id.id, &add).await?;
+    is!(reaction.status, ReactionStatus::Started);
+    is_in!(reaction.samples, sample);
+    // make sure our new sample was saved
+    let reaction = client.reactions.get(&group, id.id).await?;
+    is_in!(reaction.samples, sample);
+    // make sure our reaction is listed under our new samples tag
+    let mut reactions = client.reactions.list_tag(&group, &sample);
+    reactions.next().await?;
+    is_in!(reactions.names, id.id.to_string());
+    // delete our worker
+    generators::delete_worker("add_samples", &client).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_samples_completed() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // register our test node
+    generators::node("cluster0", "node0", Resources::default(), &client).await?;
+    // Create a group to test reactions creation in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // Create a random reaction based on our pipeline request
+    let req = generators::gen_reaction(&group, &pipe, None);
+    let id = client.reactions.create(&req).await?;
+    // complete a job for every stage of our reaction
+    for stage in pipe.order.iter().flatten() {
+        // register our test worker
+        generators::worker(
+            "cluster0",
+            "node0",
+            "add_samples_completed",
+            &group,
+            &pipe.name,
+            stage,
+            &client,
+        )
+        .await?;
+        // claim and complete a job
+        let jobs = client
+            .jobs
+            .claim(
+                &group,
+                &pipe.name,
+                stage,
+                "cluster0",
+                "node0",
+                "add_samples_completed",
+                1,
+            )
+            .await?;
+        let logs = generators::stage_logs();
+        client.jobs.proceed(&jobs[0], &logs, 2).await?;
+        // delete our worker
+        generators::delete_worker("add_samples_completed", &client).await?;
+    }
+    // upload a sample to try to add to our reaction
+    let sample = client
+        .files
+        .create(generators::gen_sample(&group))
+        .await?
+        .sha256;
+    // make sure we can't add samples to a completed reaction
+    let add = ReactionSamplesAdd::default().sample(&sample);
+    let resp = client.reactions.add_samples(&group, &id.id, &add).await;
+    fail!(resp, 400, "Completed");
+    Ok(())
+}
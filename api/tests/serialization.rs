@@ -0,0 +1,328 @@
+//! Tests that the JSON our models serialize to doesn't drift
+//!
+//! Clients parse these models so renaming a field or variant silently breaks them. If a
+//! change to a model is intentional then review and accept the new snapshot with
+//! `cargo insta review`.
+
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::{HashMap, HashSet};
+use thorium::models::{
+    Association, AssociationKind, AssociationTarget, BulkReactionResponse, Capabilities,
+    CapabilityLimits, Comment, CommitishKinds, Directionality, GenericJobArgs, Group, GroupAllowed,
+    GroupUsers, JobStatus, Origin, Pipeline, ReactionRequest, ReactionStatus, Repo, RepoCheckout,
+    RepoDependency, RepoDependencyRequest, RepoScheme, RepoSubmissionChunk, Sample, ScrubbedUser,
+    SubmissionChunk, Theme, UnixInfo, User, UserRole, UserSettings,
+};
+use thorium::test_utilities::fixtures::{EntityBuilder, ImageBuilder, JobBuilder, ReactionBuilder};
+use thorium::utils::{ApiErrorBody, ApiErrorCode};
+use uuid::Uuid;
+
+/// The sha256 to use for samples in our snapshots
+const SHA256: &str = "63b9a4e38e2bdc9e5c1d2e5a6c1a0b7e0fd1a0e9a9c7d1c8b4e6d3f2a1b0c9d8";
+
+/// Get a static timestamp to use in our snapshots
+fn timestamp() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+}
+
+/// Serialize a model to the pretty JSON that our snapshots store
+macro_rules! snapshot {
+    ($name:expr, $model:expr) => {
+        insta::assert_snapshot!(
+            $name,
+            serde_json::to_string_pretty(&$model).expect("Failed to serialize model")
+        )
+    };
+}
+
+#[test]
+fn reaction() {
+    let mut reaction = ReactionBuilder::new("corn", "harvest")
+        .args(
+            "harvester",
+            GenericJobArgs::default()
+                .positionals(vec!["field"])
+                .kwarg("--rows", vec!["12"])
+                .switch("--combine"),
+        )
+        .status(ReactionStatus::Started)
+        .stage(1, 2)
+        .sla(timestamp())
+        .tag("fall")
+        .sample(SHA256)
+        .trigger_depth(2)
+        .priority(3)
+        .build();
+    reaction.id = Uuid::from_u128(1);
+    reaction.jobs.push(Uuid::from_u128(2));
+    snapshot!("reaction", reaction);
+}
+
+#[test]
+fn generic_job() {
+    let mut job = JobBuilder::default()
+        .stage("harvester")
+        .args(GenericJobArgs::default().ordered_kwarg("--rows", vec!["12"]))
+        .status(JobStatus::Created)
+        .samples(vec!["corn.txt".to_owned()])
+        .ephemeral(Vec::default())
+        .repos(vec![RepoDependency {
+            url: "github.com/curl/curl".to_owned(),
+            commitish: Some("main".to_owned()),
+            kind: Some(CommitishKinds::Branch),
+        }])
+        .build();
+    job.reaction = Uuid::from_u128(1);
+    job.id = Uuid::from_u128(3);
+    job.deadline = timestamp();
+    snapshot!("generic_job", job);
+}
+
+#[test]
+fn image() {
+    let image = ImageBuilder::new("corn", "harvester")
+        .env("SEASON", Some("fall"))
+        .build();
+    snapshot!("image", image);
+}
+
+#[test]
+fn pipeline() {
+    let pipeline = Pipeline {
+        group: "corn".to_owned(),
+        name: "harvest".to_owned(),
+        creator: "mcarson".to_owned(),
+        order: vec![
+            vec!["harvester".to_owned()],
+            vec!["thresher".to_owned(), "sorter".to_owned()],
+        ],
+        sla: 604_800,
+        triggers: HashMap::default(),
+        description: Some("Harvests corn".to_owned()),
+        bans: HashMap::default(),
+    };
+    snapshot!("pipeline", pipeline);
+}
+
+#[test]
+fn group() {
+    // build the users for a role that only has a single user
+    let single = |user: &str| GroupUsers {
+        combined: HashSet::from([user.to_owned()]),
+        direct: HashSet::from([user.to_owned()]),
+        metagroups: HashSet::default(),
+    };
+    let group = Group {
+        name: "corn".to_owned(),
+        owners: single("mcarson"),
+        managers: single("bob"),
+        analysts: HashSet::default(),
+        users: single("alice"),
+        monitors: GroupUsers {
+            combined: HashSet::default(),
+            direct: HashSet::default(),
+            metagroups: HashSet::from(["farmers".to_owned()]),
+        },
+        description: None,
+        allowed: GroupAllowed {
+            comments: false,
+            ..GroupAllowed::default()
+        },
+    };
+    snapshot!("group", group);
+}
+
+#[test]
+fn entity() {
+    let mut entity = EntityBuilder::new("Combine")
+        .description("A corn harvester")
+        .groups(vec!["corn".to_owned()])
+        .tag("crop", "corn")
+        .build();
+    entity.id = Uuid::from_u128(4);
+    entity.created = timestamp();
+    snapshot!("entity", entity);
+}
+
+#[test]
+fn reaction_request() {
+    let request = ReactionRequest::new("corn", "harvest")
+        .args(
+            "harvester",
+            GenericJobArgs::default().positionals(vec!["field"]),
+        )
+        .sla(604_800)
+        .tag("fall")
+        .parent(Uuid::from_u128(1))
+        .sample(SHA256)
+        .buffer("seed.txt", "corn")
+        .repo(
+            RepoDependencyRequest::new("github.com/curl/curl")
+                .commitish("main")
+                .kind(CommitishKinds::Branch),
+        )
+        .trigger_depth(2)
+        .idempotency_key("harvest-2026")
+        .priority(3);
+    snapshot!("reaction_request", request);
+}
+
+#[test]
+fn bulk_reaction_response() {
+    let response = BulkReactionResponse {
+        errors: HashMap::from([(1, "Pipeline thresher does not exist".to_owned())]),
+        created: vec![Uuid::from_u128(1)],
+        deduplicated: HashMap::from([(2, Uuid::from_u128(2))]),
+    };
+    snapshot!("bulk_reaction_response", response);
+}
+
+#[test]
+fn sample() {
+    let sample = Sample {
+        sha256: SHA256.to_owned(),
+        sha1: "4f1c0b3a2e5d6c7b8a9f0e1d2c3b4a5f6e7d8c9b".to_owned(),
+        md5: "9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d".to_owned(),
+        tags: HashMap::from([(
+            "crop".to_owned(),
+            HashMap::from([("corn".to_owned(), HashSet::from(["corn".to_owned()]))]),
+        )]),
+        submissions: vec![SubmissionChunk {
+            id: Uuid::from_u128(5),
+            name: Some("harvest.exe".to_owned()),
+            description: None,
+            groups: vec!["corn".to_owned()],
+            submitter: "mcarson".to_owned(),
+            uploaded: timestamp(),
+            origin: Origin::Downloaded {
+                url: "https://corn.farm/harvest.exe".to_owned(),
+                name: None,
+            },
+        }],
+        comments: vec![Comment {
+            groups: vec!["corn".to_owned()],
+            uploaded: timestamp(),
+            id: Uuid::from_u128(6),
+            author: "mcarson".to_owned(),
+            comment: "Harvests corn".to_owned(),
+            attachments: HashMap::default(),
+        }],
+    };
+    snapshot!("sample", sample);
+}
+
+#[test]
+fn repo() {
+    let repo = Repo {
+        provider: "github.com".to_owned(),
+        user: "curl".to_owned(),
+        name: "curl".to_owned(),
+        url: "github.com/curl/curl".to_owned(),
+        tags: HashMap::default(),
+        default_checkout: Some(RepoCheckout::branch("main")),
+        submissions: vec![RepoSubmissionChunk {
+            groups: vec!["corn".to_owned()],
+            id: Uuid::from_u128(7),
+            creator: "mcarson".to_owned(),
+            uploaded: timestamp(),
+            scheme: RepoScheme::Https,
+            earliest: Some(timestamp()),
+        }],
+        earliest: Some(timestamp()),
+    };
+    snapshot!("repo", repo);
+}
+
+#[test]
+fn user() {
+    let settings = UserSettings {
+        theme: Theme::Dark,
+        ai: None,
+        default_group: Some("corn".to_owned()),
+    };
+    let user = User {
+        username: "mcarson".to_owned(),
+        password: Some("hashed".to_owned()),
+        email: "mcarson@corn.farm".to_owned(),
+        role: UserRole::Developer {
+            k8s: true,
+            bare_metal: false,
+            windows: false,
+            external: false,
+            kvm: false,
+        },
+        groups: vec!["corn".to_owned()],
+        token: "token".to_owned(),
+        token_expiration: timestamp(),
+        unix: Some(UnixInfo {
+            user: 1000,
+            group: 1000,
+        }),
+        settings: settings.clone(),
+        verified: true,
+        verification_token: None,
+        verification_sent: None,
+    };
+    snapshot!("user", user);
+    // scrubbed users skip their unix info if they don't have any
+    let scrubbed = ScrubbedUser {
+        username: "mcarson".to_owned(),
+        role: UserRole::User,
+        email: "mcarson@corn.farm".to_owned(),
+        groups: vec!["corn".to_owned()],
+        token: "token".to_owned(),
+        token_expiration: timestamp(),
+        unix: None,
+        settings,
+        local: true,
+        verified: true,
+    };
+    snapshot!("scrubbed_user", scrubbed);
+}
+
+#[test]
+fn association() {
+    let association = Association {
+        kind: AssociationKind::FileFor,
+        other: AssociationTarget::Entity {
+            id: Uuid::from_u128(4),
+            name: "Combine".to_owned(),
+        },
+        submitter: "mcarson".to_owned(),
+        groups: vec!["corn".to_owned()],
+        created: timestamp(),
+        direction: Directionality::To,
+        confidence: 90,
+        archived_at: None,
+    };
+    snapshot!("association", association);
+}
+
+#[test]
+fn capabilities() {
+    let capabilities = Capabilities {
+        version: "1.101.0".to_owned(),
+        features: vec!["reaction-trees".to_owned()],
+        limits: CapabilityLimits {
+            max_upload: None,
+            max_json: 16,
+            max_bulk_reactions: 1000,
+            max_reaction_tree_depth: 50,
+            max_reaction_tree_nodes: 10_000,
+            max_lineage_depth: 50,
+            max_lineage_nodes: 1_000,
+            max_tree_nodes: 100_000,
+        },
+    };
+    snapshot!("capabilities", capabilities);
+}
+
+#[test]
+fn api_error_body() {
+    let body = ApiErrorBody {
+        code: ApiErrorCode::NotFound,
+        error: "Sample not found".to_owned(),
+        trace: None,
+    };
+    snapshot!("api_error_body", body);
+}
@@ -3,13 +3,18 @@
 use std::collections::HashSet;
 use std::path::PathBuf;
 
+use thorium::models::backends::db::{self, keys::SystemKeys};
+use thorium::models::backends::setup;
 use thorium::models::{
     HostPathWhitelistUpdate, ImageBanKind, PipelineBanKind, PipelineRequest, PipelineUpdate,
     SystemSettings, SystemSettingsResetParams, SystemSettingsUpdate, SystemSettingsUpdateParams,
     Volume, VolumeTypes,
 };
 use thorium::test_utilities::{self, generators};
-use thorium::{contains, fail, is, is_not, unwrap_variant, vec_in_vec, Error};
+use thorium::utils::Shared;
+use thorium::utils::s3::S3Client;
+use thorium::{contains, fail, is, is_empty, is_not, unwrap_variant, vec_in_vec, Error};
+use uuid::Uuid;
 
 #[serial_test::serial]
 #[tokio::test]
@@ -445,3 +450,143 @@ async fn host_path_consistency_scan() -> Result<(), Error> {
     contains!(pipeline_banned_images, &&image.name);
     Ok(())
 }
+
+#[serial_test::serial]
+#[tokio::test]
+async fn init_settings_race() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // connect to the same backends as the API
+    let shared = Shared::new(test_utilities::CONF.clone()).await;
+    // wipe our system settings to simulate Thorium's first start
+    let mut conn = shared.redis.get().await.unwrap();
+    let _: () = bb8_redis::redis::cmd("del")
+        .arg(SystemKeys::settings(&shared))
+        .query_async(&mut *conn)
+        .await
+        .unwrap();
+    drop(conn);
+    // have multiple instances try to set the default settings at the same time
+    let attempts = (0..10).map(|_| db::system::init_settings(&shared));
+    let written = futures::future::join_all(attempts)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<bool>, _>>()
+        .unwrap();
+    // only one instance should have written the default settings
+    is!(written.iter().filter(|written| **written).count(), 1);
+    let settings = client.system.get_settings().await?;
+    is!(&settings, &SystemSettings::default());
+    // settings that already exist should never be overwritten
+    let update = SystemSettingsUpdate::default().reserved_cpu("10");
+    client
+        .system
+        .update_settings(&update, &SystemSettingsUpdateParams::default().no_scan())
+        .await?;
+    is!(db::system::init_settings(&shared).await.unwrap(), false);
+    let settings = client.system.get_settings().await?;
+    is!(&settings, &update);
+    // reset settings to defaults
+    client
+        .system
+        .reset_settings(&SystemSettingsResetParams::default().no_scan())
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn startup_self_check() -> Result<(), Error> {
+    // connect to the same backends as the API
+    let shared = Shared::new(test_utilities::CONF.clone()).await;
+    // our testing config should pass its self check
+    setup::self_check(&shared).await.unwrap();
+    // a namespace without a keyspace should produce an actionable error
+    let session = &shared.scylla.session;
+    let namespace = format!("missing_{}", Uuid::new_v4().simple());
+    let err = setup::check_keyspace(session, &namespace)
+        .await
+        .unwrap_err();
+    let msg = err.msg.unwrap_or_default();
+    is!(msg.contains(&namespace), true);
+    is!(msg.contains("thorium.namespace"), true);
+    // build an empty keyspace next to one that has data
+    let empty = format!("empty_{}", Uuid::new_v4().simple());
+    let populated = format!("populated_{}", Uuid::new_v4().simple());
+    for keyspace in [&empty, &populated] {
+        session
+            .query_unpaged(
+                format!(
+                    "CREATE KEYSPACE {keyspace} WITH REPLICATION = \
+                    {{'class' : 'NetworkTopologyStrategy', 'replication_factor': 1}}"
+                ),
+                &[],
+            )
+            .await
+            .unwrap();
+        session
+            .query_unpaged(
+                format!("CREATE TABLE {keyspace}.samples_list (id UUID PRIMARY KEY)"),
+                &[],
+            )
+            .await
+            .unwrap();
+    }
+    session
+        .query_unpaged(
+            format!("INSERT INTO {populated}.samples_list (id) VALUES (?)"),
+            (Uuid::new_v4(),),
+        )
+        .await
+        .unwrap();
+    // the keyspace with data should pass
+    setup::check_keyspace(session, &populated).await.unwrap();
+    // an empty keyspace while another keyspace has data means we have the wrong namespace
+    let err = setup::check_keyspace(session, &empty).await.unwrap_err();
+    let msg = err.msg.unwrap_or_default();
+    is!(msg.contains(&empty), true);
+    is!(msg.contains(&populated), true);
+    is!(msg.contains("thorium.namespace"), true);
+    // clean up our keyspaces
+    for keyspace in [&empty, &populated] {
+        session
+            .query_unpaged(format!("DROP KEYSPACE {keyspace}"), &[])
+            .await
+            .unwrap();
+    }
+    // a bucket that doesn't exist should also produce an actionable error
+    let conf = &test_utilities::CONF.thorium;
+    let bucket = format!("thorium-missing-{}", Uuid::new_v4().simple());
+    let client = S3Client::new(&bucket, &conf.files.password, &conf.s3);
+    let err = setup::check_bucket("files", &client, &conf.s3.endpoint)
+        .await
+        .unwrap_err();
+    let msg = err.msg.unwrap_or_default();
+    is!(msg.contains(&bucket), true);
+    is!(msg.contains("thorium.files.bucket"), true);
+    Ok(())
+}
+
+#[tokio::test]
+async fn namespace_isolation() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // generate data that touches most of the keys in Redis
+    generators::gen_all(3, &client).await?;
+    // none of the keys we wrote should be outside of our namespace
+    let foreign = test_utilities::foreign_redis_keys().await?;
+    is_empty!(foreign);
+    // a pipeline built from our keys stays in our namespace
+    let shared = Shared::new(test_utilities::CONF.clone()).await;
+    let mut pipe = redis::pipe();
+    pipe.cmd("hgetall").arg(SystemKeys::settings(&shared));
+    test_utilities::assert_namespaced(&pipe);
+    // a pipeline that touches another namespace is flagged
+    let mut pipe = redis::pipe();
+    pipe.cmd("hgetall")
+        .arg(SystemKeys::settings(&shared))
+        .cmd("hgetall")
+        .arg("other_namespace:system:settings");
+    let flagged = std::panic::catch_unwind(|| test_utilities::assert_namespaced(&pipe));
+    is!(flagged.is_err(), true);
+    Ok(())
+}
@@ -257,7 +257,7 @@ mod errors;
 mod libs;
 
 pub use errors::Error;
-pub use libs::{footer, footer::Footer, header, header::Header};
+pub use libs::{footer, footer::Footer, header, header::Header, resume};
 
 /// Packs a Cart file using streaming
 #[pin_project::pin_project]
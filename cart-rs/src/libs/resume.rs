@@ -0,0 +1,522 @@
+//! Cart and uncart data in chunks that can be checkpointed and resumed later
+//!
+//! [`CartStream`](crate::CartStream) and [`UncartStream`](crate::UncartStream) must
+//! always start from the beginning of a file because every carted byte depends on
+//! the zlib and rc4 state built up from the bytes before it. These types instead
+//! expose checkpoints at the points where that state is small enough to save: a full
+//! zlib flush when carting and a deflate block boundary when uncarting. A checkpoint
+//! records how far into the input and output a stream got, so a failed stream can
+//! seek its input and output back to those offsets and continue instead of starting
+//! over.
+//!
+//! Resuming only has to catch the rc4 keystream up to the checkpoint which is far
+//! cheaper than reading, compressing, and encrypting everything before it again.
+
+use crypto::rc4::Rc4;
+use crypto::symmetriccipher::SynchronousStreamCipher;
+use flate2::{Compress, Compression, FlushCompress, Status};
+use generic_array::{ArrayLength, GenericArray};
+use miniz_oxide::inflate::TINFLStatus;
+use miniz_oxide::inflate::core::{
+    BlockBoundaryState, DecompressorOxide, TINFL_LZ_DICT_SIZE, decompress, inflate_flags,
+};
+use rc4::{KeyInit, StreamCipher};
+
+use crate::Error;
+use crate::footer::{self, Footer};
+use crate::header::{self, Header};
+
+/// The zlib header for the default compression level
+const ZLIB_HEADER: [u8; 2] = [0x78, 0x9c];
+
+/// The length of the adler32 checksum at the end of a zlib stream
+const ADLER32_LEN: usize = 4;
+
+/// The number of bytes to reserve for compressed data at a time
+const COMPRESS_CHUNK: usize = 65_536;
+
+/// The number of bytes to decompress at a time past our 32 KiB window
+const DECOMPRESS_CHUNK: usize = 131_072;
+
+/// The number of keystream bytes to skip at a time when resuming
+const SKIP_CHUNK: usize = 65_536;
+
+/// The flags to uncart with when more carted data may still be coming
+const UNCART_FLAGS: u32 = inflate_flags::TINFL_FLAG_PARSE_ZLIB_HEADER
+    | inflate_flags::TINFL_FLAG_COMPUTE_ADLER32
+    | inflate_flags::TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF
+    | inflate_flags::TINFL_FLAG_STOP_ON_BLOCK_BOUNDARY;
+
+/// A point that a [`ResumableCart`] can be resumed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CartCheckpoint {
+    /// The number of input bytes that were carted before this checkpoint
+    pub read: u64,
+    /// The number of carted bytes that were output before this checkpoint
+    pub written: u64,
+    /// The adler32 checksum of the input bytes that were carted before this checkpoint
+    pub adler32: u32,
+}
+
+/// Carts data in chunks and can be resumed from a [`CartCheckpoint`]
+pub struct ResumableCart<T: ArrayLength<u8>> {
+    /// The key used for encryption
+    key: GenericArray<u8, T>,
+    /// The raw deflate compressor to use
+    zlib: Compress,
+    /// The rc4 encryptor to encrypt our data with
+    rc4: rc4::Rc4<T>,
+    /// The adler32 checksum of the input we have carted so far
+    adler32: adler2::Adler32,
+    /// The number of input bytes we have carted so far
+    read: u64,
+    /// The number of carted bytes we have output so far
+    written: u64,
+}
+
+impl<T: ArrayLength<u8>> ResumableCart<T> {
+    /// Start carting new data
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The 16 byte key to use for encryption
+    pub fn new(key: &GenericArray<u8, T>) -> Result<Self, Error> {
+        // check that the given key is valid
+        Header::validate_key(key)?;
+        // our zlib header and checksum are written by us so they can span resumes
+        let zlib = Compress::new(Compression::default(), false);
+        let cart = ResumableCart {
+            key: key.clone(),
+            zlib,
+            rc4: rc4::Rc4::new(key),
+            adler32: adler2::Adler32::new(),
+            read: 0,
+            written: 0,
+        };
+        Ok(cart)
+    }
+
+    /// Resume carting data from a checkpoint
+    ///
+    /// The input must be read from `checkpoint.read` onwards and the output must
+    /// be written after the first `checkpoint.written` bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The 16 byte key that was used before this checkpoint
+    /// * `checkpoint` - The checkpoint to resume from
+    pub fn resume(key: &GenericArray<u8, T>, checkpoint: &CartCheckpoint) -> Result<Self, Error> {
+        // a checkpoint is always after our cart and zlib headers
+        let headers = (header::HEADER_LEN + ZLIB_HEADER.len()) as u64;
+        if checkpoint.written < headers {
+            return Err(Error::new(format!(
+                "Cart checkpoints must be at least {headers} bytes into the carted data"
+            )));
+        }
+        let mut cart = Self::new(key)?;
+        // catch our keystream up to where our checkpoint left off
+        let mut skip = vec![0; SKIP_CHUNK];
+        let mut remaining = checkpoint.written - header::HEADER_LEN as u64;
+        while remaining > 0 {
+            let len = usize::try_from(remaining.min(SKIP_CHUNK as u64))?;
+            cart.rc4.apply_keystream(&mut skip[..len]);
+            remaining -= len as u64;
+        }
+        cart.adler32 = adler2::Adler32::from_checksum(checkpoint.adler32);
+        cart.read = checkpoint.read;
+        cart.written = checkpoint.written;
+        Ok(cart)
+    }
+
+    /// Cart some input and append the carted bytes to our output
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input bytes to cart
+    /// * `output` - The buffer to append carted bytes to
+    pub fn update(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<(), Error> {
+        if input.is_empty() {
+            return Ok(());
+        }
+        self.compress(input, output, FlushCompress::None)
+    }
+
+    /// Flush all pending carted data to our output and return a checkpoint
+    ///
+    /// Flushing resets our compressor's history so carting can be resumed from
+    /// this point without any of the data before it. The checkpoint should only
+    /// be saved once all of the output up to it has been durably written.
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The buffer to append flushed carted bytes to
+    pub fn checkpoint(&mut self, output: &mut Vec<u8>) -> Result<CartCheckpoint, Error> {
+        self.compress(&[], output, FlushCompress::Full)?;
+        Ok(CartCheckpoint {
+            read: self.read,
+            written: self.written,
+            adler32: self.adler32.checksum(),
+        })
+    }
+
+    /// Finish carting our data and append the rest of our carted bytes to our output
+    ///
+    /// Returns the total number of carted bytes that were output.
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The buffer to append the rest of our carted bytes to
+    pub fn finish(mut self, output: &mut Vec<u8>) -> Result<u64, Error> {
+        self.compress(&[], output, FlushCompress::Finish)?;
+        // end our zlib stream with the checksum of everything we carted
+        let start = output.len();
+        output.extend_from_slice(&self.adler32.checksum().to_be_bytes());
+        self.rc4.apply_keystream(&mut output[start..]);
+        // our footer is not encrypted
+        output.extend_from_slice(&Footer::new_buffer());
+        self.written += (ADLER32_LEN + footer::FOOTER_LEN) as u64;
+        Ok(self.written)
+    }
+
+    /// Compress, encrypt, and append some input to our output
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input bytes to cart
+    /// * `output` - The buffer to append carted bytes to
+    /// * `flush` - The flush setting to use
+    fn compress(
+        &mut self,
+        input: &[u8],
+        output: &mut Vec<u8>,
+        flush: FlushCompress,
+    ) -> Result<(), Error> {
+        let start = output.len();
+        // write our headers before any carted data
+        if self.written == 0 {
+            output.extend_from_slice(&Header::new_buffer(&self.key, 0)?);
+        }
+        let encrypt_start = output.len();
+        if self.written == 0 {
+            output.extend_from_slice(&ZLIB_HEADER);
+        }
+        let mut consumed = 0;
+        loop {
+            // make sure our compressor has room to write to
+            output.reserve(COMPRESS_CHUNK);
+            let old_in = self.zlib.total_in();
+            let status = self
+                .zlib
+                .compress_vec(&input[consumed..], output, flush)
+                .map_err(|err| Error::new(format!("Zlib compression failed: {err}")))?;
+            consumed += usize::try_from(self.zlib.total_in() - old_in)?;
+            // keep going until our input is consumed and our compressor stops filling our output
+            let drained = consumed == input.len() && output.len() < output.capacity();
+            match status {
+                Status::StreamEnd => break,
+                Status::Ok | Status::BufError if drained && flush != FlushCompress::Finish => {
+                    break;
+                }
+                Status::Ok => (),
+                Status::BufError => {
+                    return Err(Error::new("Zip Compression Buffer Error"));
+                }
+            }
+        }
+        // encrypt everything but our cart header
+        self.rc4.apply_keystream(&mut output[encrypt_start..]);
+        self.adler32.write_slice(input);
+        self.read += input.len() as u64;
+        self.written += (output.len() - start) as u64;
+        Ok(())
+    }
+}
+
+/// A point that a [`ResumableUncart`] can be resumed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UncartCheckpoint {
+    /// The number of carted bytes that were uncarted before this checkpoint
+    pub read: u64,
+    /// The number of uncarted bytes that were output before this checkpoint
+    pub written: u64,
+    /// The number of bits from the last carted byte that are still needed
+    pub num_bits: u8,
+    /// The bits from the last carted byte that are still needed
+    pub bit_buf: u8,
+    /// The first byte of the zlib header
+    pub zlib_cmf: u32,
+    /// The second byte of the zlib header
+    pub zlib_flg: u32,
+    /// The adler32 checksum of the data uncarted before this checkpoint
+    pub adler32: u32,
+}
+
+impl UncartCheckpoint {
+    /// The number of bytes before this checkpoint that must be passed back in when resuming
+    #[must_use]
+    pub fn window(&self) -> u64 {
+        self.written.min(TINFL_LZ_DICT_SIZE as u64)
+    }
+}
+
+/// Uncarts data in chunks and can be resumed from an [`UncartCheckpoint`]
+///
+/// Like [`UncartStream`](crate::UncartStream), carts with optional headers are not supported.
+pub struct ResumableUncart {
+    /// The cart header we have read so far
+    header: Vec<u8>,
+    /// The rc4 decryptor to use once our header has been read
+    rc4: Option<Rc4>,
+    /// The decrypted data that hasn't been decompressed yet
+    decrypted: Vec<u8>,
+    /// The zlib decompressor to use
+    zlib: Box<DecompressorOxide>,
+    /// Our last 32 KiB of uncarted data followed by room for more
+    window: Vec<u8>,
+    /// Where to write our next uncarted data in our window
+    pos: usize,
+    /// The number of carted bytes that have been decompressed so far
+    read: u64,
+    /// The number of uncarted bytes we have output so far
+    written: u64,
+    /// The latest point this data can be resumed from
+    boundary: Option<UncartCheckpoint>,
+    /// Whether our zlib stream has ended
+    done: bool,
+}
+
+impl Default for ResumableUncart {
+    /// Start uncarting new data
+    fn default() -> Self {
+        ResumableUncart {
+            header: Vec::with_capacity(header::HEADER_LEN),
+            rc4: None,
+            decrypted: Vec::with_capacity(DECOMPRESS_CHUNK),
+            zlib: Box::default(),
+            window: vec![0; TINFL_LZ_DICT_SIZE + DECOMPRESS_CHUNK],
+            pos: 0,
+            read: 0,
+            written: 0,
+            boundary: None,
+            done: false,
+        }
+    }
+}
+
+impl ResumableUncart {
+    /// Start uncarting new data
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resume uncarting data from a checkpoint
+    ///
+    /// The carted input must be read from `checkpoint.read` onwards and the output
+    /// must be written after the first `checkpoint.written` bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The header of the cart file being uncarted
+    /// * `checkpoint` - The checkpoint to resume from
+    /// * `window` - The last [`UncartCheckpoint::window`] bytes output before this checkpoint
+    pub fn resume(
+        header: &[u8],
+        checkpoint: &UncartCheckpoint,
+        window: &[u8],
+    ) -> Result<Self, Error> {
+        if checkpoint.read < header::HEADER_LEN as u64 {
+            return Err(Error::new(format!(
+                "Uncart checkpoints must be at least {} bytes into the carted data",
+                header::HEADER_LEN
+            )));
+        }
+        if window.len() as u64 != checkpoint.window() {
+            return Err(Error::new(format!(
+                "Resuming an uncart requires the last {} bytes that were uncarted",
+                checkpoint.window()
+            )));
+        }
+        let mut uncart = Self::new();
+        uncart.update(
+            &header[..header::HEADER_LEN.min(header.len())],
+            &mut Vec::new(),
+        )?;
+        let Some(rc4) = uncart.rc4.as_mut() else {
+            return Err(Error::new(
+                "Invalid CaRT file! CaRT header is malformed or missing.",
+            ));
+        };
+        // catch our keystream up to where our checkpoint left off
+        let zeros = vec![0; SKIP_CHUNK];
+        let mut skip = vec![0; SKIP_CHUNK];
+        let mut remaining = checkpoint.read - header::HEADER_LEN as u64;
+        while remaining > 0 {
+            let len = usize::try_from(remaining.min(SKIP_CHUNK as u64))?;
+            rc4.process(&zeros[..len], &mut skip[..len]);
+            remaining -= len as u64;
+        }
+        // restore our decompressor and the history it can refer back to
+        let state = BlockBoundaryState {
+            num_bits: checkpoint.num_bits,
+            bit_buf: checkpoint.bit_buf,
+            z_header0: checkpoint.zlib_cmf,
+            z_header1: checkpoint.zlib_flg,
+            check_adler32: checkpoint.adler32,
+        };
+        uncart.zlib = Box::new(DecompressorOxide::from_block_boundary_state(&state));
+        uncart.window[..window.len()].copy_from_slice(window);
+        uncart.pos = window.len();
+        uncart.read = checkpoint.read;
+        uncart.written = checkpoint.written;
+        Ok(uncart)
+    }
+
+    /// Get the key from the header of the cart file being uncarted if it has been read
+    #[must_use]
+    pub fn key(&self) -> Option<&[u8]> {
+        if self.header.len() == header::HEADER_LEN {
+            Some(&self.header[14..30])
+        } else {
+            None
+        }
+    }
+
+    /// Get the latest checkpoint that has been uncarted
+    ///
+    /// The checkpoint should only be saved once all of the output up to it has
+    /// been durably written.
+    #[must_use]
+    pub fn checkpoint(&self) -> Option<&UncartCheckpoint> {
+        self.boundary.as_ref()
+    }
+
+    /// Uncart some carted input and append the uncarted bytes to our output
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The carted bytes to uncart
+    /// * `output` - The buffer to append uncarted bytes to
+    pub fn update(&mut self, mut input: &[u8], output: &mut Vec<u8>) -> Result<(), Error> {
+        // read in our header before anything else
+        if self.rc4.is_none() {
+            let needed = (header::HEADER_LEN - self.header.len()).min(input.len());
+            self.header.extend_from_slice(&input[..needed]);
+            input = &input[needed..];
+            if self.header.len() < header::HEADER_LEN {
+                return Ok(());
+            }
+            let header = Header::get(&self.header)?;
+            self.rc4 = Some(Rc4::new(&header.key));
+            self.read = header::HEADER_LEN as u64;
+        }
+        // anything left after our zlib stream ends is our footer
+        if self.done || input.is_empty() {
+            return Ok(());
+        }
+        // decrypt this input and decompress everything we have so far
+        if let Some(rc4) = self.rc4.as_mut() {
+            let start = self.decrypted.len();
+            self.decrypted.resize(start + input.len(), 0);
+            rc4.process(input, &mut self.decrypted[start..]);
+        }
+        self.decompress(
+            output,
+            UNCART_FLAGS | inflate_flags::TINFL_FLAG_HAS_MORE_INPUT,
+        )
+    }
+
+    /// Finish uncarting our data and append the rest of our uncarted bytes to our output
+    ///
+    /// Returns the total number of uncarted bytes that were output.
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The buffer to append the rest of our uncarted bytes to
+    pub fn finish(mut self, output: &mut Vec<u8>) -> Result<u64, Error> {
+        if self.rc4.is_none() {
+            return Err(Error::new(
+                "Invalid CaRT file! CaRT header is malformed or missing.",
+            ));
+        }
+        if !self.done {
+            self.decompress(output, UNCART_FLAGS)?;
+        }
+        if !self.done {
+            return Err(Error::new(
+                "CaRT file cannot be decompressed because data is missing/corrupted",
+            ));
+        }
+        Ok(self.written)
+    }
+
+    /// Decompress as much of our decrypted data as we can
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The buffer to append uncarted bytes to
+    /// * `flags` - The flags to decompress with
+    fn decompress(&mut self, output: &mut Vec<u8>, flags: u32) -> Result<(), Error> {
+        let mut consumed = 0;
+        loop {
+            // slide our last 32 KiB to the front of our window once it fills up
+            if self.pos == self.window.len() {
+                self.window.copy_within(self.pos - TINFL_LZ_DICT_SIZE.., 0);
+                self.pos = TINFL_LZ_DICT_SIZE;
+            }
+            let (status, read, written) = decompress(
+                &mut self.zlib,
+                &self.decrypted[consumed..],
+                &mut self.window,
+                self.pos,
+                flags,
+            );
+            consumed += read;
+            self.read += read as u64;
+            output.extend_from_slice(&self.window[self.pos..self.pos + written]);
+            self.pos += written;
+            self.written += written as u64;
+            match status {
+                TINFLStatus::Done => {
+                    self.done = true;
+                    break;
+                }
+                TINFLStatus::NeedsMoreInput | TINFLStatus::FailedCannotMakeProgress
+                    if flags & inflate_flags::TINFL_FLAG_HAS_MORE_INPUT != 0 =>
+                {
+                    break;
+                }
+                TINFLStatus::FailedCannotMakeProgress => break,
+                TINFLStatus::HasMoreOutput => (),
+                TINFLStatus::BlockBoundary => {
+                    // we can resume from the start of any block
+                    if let Some(state) = self.zlib.block_boundary_state() {
+                        self.boundary = Some(UncartCheckpoint {
+                            read: self.read,
+                            written: self.written,
+                            num_bits: state.num_bits,
+                            bit_buf: state.bit_buf,
+                            zlib_cmf: state.z_header0,
+                            zlib_flg: state.z_header1,
+                            adler32: state.check_adler32,
+                        });
+                    }
+                }
+                TINFLStatus::Adler32Mismatch => {
+                    return Err(Error::new(
+                        "CaRT file cannot be decompressed because its checksum does not match",
+                    ));
+                }
+                _ => {
+                    return Err(Error::new(
+                        "CaRT file cannot be decompressed because data is missing/corrupted",
+                    ));
+                }
+            }
+        }
+        self.decrypted.drain(..consumed);
+        Ok(())
+    }
+}
@@ -32,7 +32,7 @@ pub async fn create_or_auth_user(
     admin: bool,
     thorium: Option<&thorium::Thorium>,
 ) -> Result<AuthResponse, Error> {
-    let client = reqwest::Client::new();
+    let client = client::HttpClient::from(reqwest::Client::new());
     // build out user request, make user a local auth user with admin role
     let mut user_req = UserCreate::new(username, password, "thorium").skip_verification();
     // set local auth to true in case cluster is LDAP enabled
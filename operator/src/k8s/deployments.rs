@@ -4,7 +4,10 @@ use kube::api::{DeleteParams, ListParams, Patch, PatchParams, PostParams};
 use serde_json::Value;
 use std::time;
 use thorium::conf::K8sHostAliases;
-use thorium::{Error, client::Basic};
+use thorium::{
+    Error,
+    client::{Basic, HttpClient},
+};
 use tokio;
 
 use super::clusters::ClusterMeta;
@@ -840,7 +843,7 @@ pub async fn wait_for_pods(
     }
     // if app is the API, then lets make sure the API is responding
     if app_tag == "api" && host.is_some() {
-        let thorium_client = reqwest::Client::new();
+        let thorium_client = HttpClient::from(reqwest::Client::new());
         let basic = Basic::new(
             host.expect("expected host to be some url but found none"),
             &thorium_client,
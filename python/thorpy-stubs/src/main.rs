@@ -3,9 +3,11 @@
 
 use anyhow::{Context, Error};
 use std::{
+    collections::HashMap,
     fs::File,
     io::{self, BufWriter, Write},
     path::{Path, PathBuf},
+    process::Command,
 };
 use walkdir::WalkDir;
 
@@ -18,6 +20,70 @@ const IMPORTS: &str = r"
 from uuid import UUID
 ";
 
+/// A Python script that dumps the docstrings for every class and method in our module
+///
+/// `PyO3` introspection does not include docs, but `PyO3` still sets `__doc__` from the
+/// Rust doc comments so we can pull them from the built module instead.
+const DOCS_SCRIPT: &str = r#"
+import inspect, json, sys
+import thorium
+
+def add(docs, path, doc):
+    if isinstance(doc, str) and doc.strip():
+        docs[path] = inspect.cleandoc(doc)
+
+def crawl(docs, path, cls):
+    # only use docs set on this class and not ones inherited from a parent
+    add(docs, path, cls.__dict__.get("__doc__"))
+    for name, attr in vars(cls).items():
+        if name.startswith("__") and name != "__new__":
+            continue
+        if isinstance(attr, type):
+            crawl(docs, f"{path}.{name}", attr)
+        elif not isinstance(attr, cls):
+            add(docs, f"{path}.{name}", getattr(attr, "__doc__", None))
+
+docs = {}
+for name, attr in vars(thorium).items():
+    if isinstance(attr, type):
+        crawl(docs, name, attr)
+    elif callable(attr) and not name.startswith("_"):
+        add(docs, name, attr.__doc__)
+json.dump(docs, sys.stdout)
+"#;
+
+/// The kinds of definitions in a stubs file we can add docstrings to
+enum Definition<'a> {
+    /// A class definition
+    Class(&'a str),
+    /// A function or method definition
+    Function(&'a str),
+}
+
+impl<'a> Definition<'a> {
+    /// Parse a class or function definition from a line with its indentation removed
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line to parse
+    fn parse(line: &'a str) -> Option<Self> {
+        if let Some(rest) = line.strip_prefix("class ") {
+            let end = rest.find(['(', ':']).unwrap_or(rest.len());
+            return Some(Definition::Class(&rest[..end]));
+        }
+        let rest = line.strip_prefix("def ")?;
+        let end = rest.find('(')?;
+        Some(Definition::Function(&rest[..end]))
+    }
+
+    /// Get the name of this definition
+    fn name(&self) -> &'a str {
+        match self {
+            Definition::Class(name) | Definition::Function(name) => name,
+        }
+    }
+}
+
 /// Try searching for the module library file we want to introspect in the given directory
 /// recursively
 fn find_lib_file(lib_dir: &Path) -> Result<Option<PathBuf>, io::Error> {
@@ -38,10 +104,115 @@ fn find_lib_file(lib_dir: &Path) -> Result<Option<PathBuf>, io::Error> {
     Ok(None)
 }
 
-/// Write the stubs data to a file
-fn write_stubs(writer: &mut BufWriter<File>, stubs: &str) -> Result<(), io::Error> {
-    // write the generated stubs
-    writer.write_all(stubs.as_bytes())?;
+/// Get the docstrings for every class and method in our module by name
+///
+/// Names are the dotted path to a class or method (e.g. `FilesBlocking.list`).
+///
+/// # Arguments
+///
+/// * `thorpy_root` - The root of the thorpy package with our venv
+fn collect_docs(thorpy_root: &Path) -> Result<HashMap<String, String>, Error> {
+    // use the python in our venv so we import the module we just built
+    let python = thorpy_root.join(".venv").join("bin").join("python");
+    let output = Command::new(&python)
+        .arg("-c")
+        .arg(DOCS_SCRIPT)
+        .output()
+        .with_context(|| format!("Failed to run python at '{}'", python.display()))?;
+    if !output.status.success() {
+        return Err(Error::msg(format!(
+            "Failed to collect docs from {MAIN_MODULE_NAME}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    serde_json::from_slice(&output.stdout).context("Failed to parse collected docs")
+}
+
+/// Write a docstring at the given indentation
+///
+/// # Arguments
+///
+/// * `writer` - The writer to write our docstring to
+/// * `indent` - The number of spaces to indent our docstring by
+/// * `doc` - The docstring to write
+fn write_docstring(
+    writer: &mut BufWriter<File>,
+    indent: usize,
+    doc: &str,
+) -> Result<(), io::Error> {
+    let pad = " ".repeat(indent);
+    // escape anything that would end our docstring early
+    let doc = doc.replace('\\', "\\\\").replace(r#"""""#, r#"\"\"\""#);
+    let mut lines = doc.lines();
+    let first = lines.next().unwrap_or_default();
+    // keep short docs on a single line
+    if doc.lines().count() <= 1 {
+        return writeln!(writer, r#"{pad}"""{first}""""#);
+    }
+    writeln!(writer, r#"{pad}"""{first}"#)?;
+    for line in lines {
+        // don't leave trailing whitespace on empty lines
+        if line.trim().is_empty() {
+            writeln!(writer)?;
+        } else {
+            writeln!(writer, "{pad}{}", line.trim_end())?;
+        }
+    }
+    writeln!(writer, r#"{pad}""""#)
+}
+
+/// Write the stubs data to a file with docstrings added under their signatures
+///
+/// # Arguments
+///
+/// * `writer` - The writer to write our stubs to
+/// * `stubs` - The generated stubs without docs
+/// * `docs` - The docstrings for each class and method by name
+fn write_stubs(
+    writer: &mut BufWriter<File>,
+    stubs: &str,
+    docs: &HashMap<String, String>,
+) -> Result<(), io::Error> {
+    // the classes the current line is nested in and their indentation
+    let mut scopes: Vec<(usize, &str)> = Vec::new();
+    // the decorator on the line before the current one if there was one
+    let mut decorator: Option<&str> = None;
+    for line in stubs.lines() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        // leave any classes we have dedented out of
+        if !trimmed.is_empty() {
+            while scopes.last().is_some_and(|(scope, _)| *scope >= indent) {
+                scopes.pop();
+            }
+        }
+        // setters share a name with their getter so only document the getter
+        let setter = decorator.is_some_and(|decorator| decorator.ends_with(".setter"));
+        decorator = trimmed.starts_with('@').then_some(trimmed);
+        // skip anything that isn't a class or function
+        let Some(definition) = Definition::parse(trimmed) else {
+            writeln!(writer, "{line}")?;
+            continue;
+        };
+        // build the full name of this definition
+        let name = scopes
+            .iter()
+            .map(|(_, name)| *name)
+            .chain(std::iter::once(definition.name()))
+            .collect::<Vec<&str>>()
+            .join(".");
+        if let Definition::Class(class) = definition {
+            scopes.push((indent, class));
+        }
+        match docs.get(&name).filter(|_| !setter) {
+            Some(doc) => {
+                // replace the empty body with our docstring
+                writeln!(writer, "{}", line.strip_suffix(" ...").unwrap_or(line))?;
+                write_docstring(writer, indent + 4, doc)?;
+            }
+            None => writeln!(writer, "{line}")?,
+        }
+    }
     // write the imports
     writer.write_all(IMPORTS.as_bytes())?;
     // flush
@@ -85,6 +256,9 @@ fn main() -> Result<(), Error> {
     let python_module = pyo3_introspection::introspect_cdylib(thorium_lib_path, MAIN_MODULE_NAME)
         .with_context(|| format!("Failed introspection of {MAIN_MODULE_NAME}"))?;
     let type_stubs = pyo3_introspection::module_stub_files(&python_module);
+    // get the docs introspection doesn't give us from the module itself
+    let docs = collect_docs(&thorpy_root)
+        .with_context(|| format!("Failed to collect docs for {MAIN_MODULE_NAME}"))?;
 
     // retrieve the stubs data
     let stubs_string = type_stubs
@@ -95,7 +269,7 @@ fn main() -> Result<(), Error> {
     let stubs_file = File::create(&stubs_path)
         .with_context(|| format!("Failed to create stubs file '{}'", stubs_path.display()))?;
     let mut writer = BufWriter::new(stubs_file);
-    write_stubs(&mut writer, stubs_string)
+    write_stubs(&mut writer, stubs_string, &docs)
         .with_context(|| format!("Failed to write stubs file '{}'", stubs_path.display()))?;
 
     println!("Generated stubs: {}", stubs_path.display());
@@ -17,19 +17,21 @@ pub mod thorium {
     //
     // any exported type must be a `pyo3::pyclass`
     #[pymodule_export]
-    pub use thorium::client::conf::ClientSettings;
+    pub use thorium::client::conf::{ClientSettings, RetryPolicy};
     #[pymodule_export]
     pub use thorium::client::{
-        BasicBlocking, FilesBlocking, JobsBlocking, ReactionsBlocking, ThoriumBlocking,
+        BasicBlocking, FilesBlocking, JobsBlocking, McpBlocking, ReactionsBlocking, SearchDate,
+        SearchRange, ThoriumBlocking,
     };
     #[pymodule_export]
     pub use thorium::models::python::{SampleCursor, SampleListLineCursor, TagCountsCursor};
     #[pymodule_export]
     pub use thorium::models::{
-        Attachment, BulkReactionResponse, CarvedOriginPy, Comment, CommitishKinds, FileListOpts,
-        GenericJob, GenericJobArgs, GenericJobOpts, HandleJobResponse, JobHandleStatus, OriginPy,
-        Output, OutputMap, Reaction, ReactionCache, ReactionCreation, ReactionRequest,
+        Attachment, BulkReactionResponse, Capabilities, CapabilityLimits, CarvedOriginPy, Comment,
+        CommitishKinds, FileListOpts, GenericJob, GenericJobArgs, GenericJobOpts,
+        HandleJobResponse, JobHandleStatus, JobHeartbeat, JobStarted, McpTool, OriginPy, Output,
+        OutputMap, OutputRenderer, Reaction, ReactionCache, ReactionCreation, ReactionRequest,
         ReactionStatus, RepoDependency, RepoDependencyRequest, ResultGetParams, SampleListLine,
-        SamplePy, SubmissionChunkPy, TagCounts, TagKeyCounts,
+        SamplePy, SubmissionChunkPy, TagCounts, TagKeyCounts, UserSummary,
     };
 }
@@ -1,6 +1,6 @@
 from _typeshed import Incomplete
 from collections.abc import Sequence
-from datetime import datetime
+from datetime import datetime, timedelta
 from ipaddress import IPv4Address, IPv6Address
 from os import PathLike
 from pathlib import Path
@@ -13,16 +13,50 @@ class Attachment:
 
 @final
 class BasicBlocking:
+    def capabilities(self, /) -> Capabilities: ...
     def health(self, /) -> bool: ...
     def identify(self, /) -> str: ...
+    def ping(self, /) -> timedelta: ...
+    def whoami(self, /) -> UserSummary: ...
 
 @final
 class BulkReactionResponse:
     @property
     def created(self, /) -> list[Incomplete]: ...
     @property
+    def deduplicated(self, /) -> dict[int, Incomplete]: ...
+    @property
     def errors(self, /) -> dict[int, str]: ...
 
+@final
+class Capabilities:
+    @property
+    def features(self, /) -> list[str]: ...
+    @property
+    def limits(self, /) -> CapabilityLimits: ...
+    @property
+    def version(self, /) -> str: ...
+    def supports(self, /, feature: str) -> bool: ...
+
+@final
+class CapabilityLimits:
+    @property
+    def max_bulk_reactions(self, /) -> int: ...
+    @property
+    def max_json(self, /) -> int: ...
+    @property
+    def max_lineage_depth(self, /) -> int: ...
+    @property
+    def max_lineage_nodes(self, /) -> int: ...
+    @property
+    def max_reaction_tree_depth(self, /) -> int: ...
+    @property
+    def max_reaction_tree_nodes(self, /) -> int: ...
+    @property
+    def max_tree_nodes(self, /) -> int: ...
+    @property
+    def max_upload(self, /) -> int |None: ...
+
 class CarvedOrigin:
     @final
     class Pcap(CarvedOrigin):
@@ -49,15 +83,35 @@ class CarvedOrigin:
 
 @final
 class ClientSettings:
-    def __new__(cls, /, invalid_certs: bool = False, invalid_hostnames: bool = False, certificate_authorities: Sequence[str |PathLike] = ..., timeout: int = ...) -> ClientSettings: ...
+    def __new__(cls, /, invalid_certs: bool = False, invalid_hostnames: bool = False, certificate_authorities: Sequence[str |PathLike] = ..., timeout: int = ..., metadata_timeout: int |None = None, upload_timeout: int |None = None, download_timeout: int |None = None, retry: RetryPolicy = ..., http2_prior_knowledge: bool = False, http2_keep_alive_interval: int |None = None, tcp_keepalive: int |None = None, pool_idle_timeout: int |None = None, pool_max_idle_per_host: int |None = None, strict_version: bool = False) -> ClientSettings: ...
     @property
     def certificate_authorities(self, /) -> list[Path]: ...
     @property
+    def download_timeout(self, /) -> int |None: ...
+    @property
+    def http2_keep_alive_interval(self, /) -> int |None: ...
+    @property
+    def http2_prior_knowledge(self, /) -> bool: ...
+    @property
     def invalid_certs(self, /) -> bool: ...
     @property
     def invalid_hostnames(self, /) -> bool: ...
     @property
+    def metadata_timeout(self, /) -> int |None: ...
+    @property
+    def pool_idle_timeout(self, /) -> int |None: ...
+    @property
+    def pool_max_idle_per_host(self, /) -> int |None: ...
+    @property
+    def retry(self, /) -> RetryPolicy: ...
+    @property
+    def strict_version(self, /) -> bool: ...
+    @property
+    def tcp_keepalive(self, /) -> int |None: ...
+    @property
     def timeout(self, /) -> int: ...
+    @property
+    def upload_timeout(self, /) -> int |None: ...
 
 @final
 class Comment:
@@ -84,12 +138,13 @@ class CommitishKinds:
 
 @final
 class FileListOpts:
-    def __new__(cls, /, cursor: "UUID | None" = None, start: datetime |None = None, end: datetime |None = None, page_size: int = 50, limit: int |None = None, groups: Sequence[str] = ..., tags: dict[str, Sequence[str]] = ..., tags_case_insensitive: bool = False) -> FileListOpts: ...
+    def __new__(cls, /, cursor: "UUID | None" = None, start: datetime |None = None, end: datetime |None = None, page_size: int = 50, limit: int |None = None, groups: Sequence[str] = ..., tags: dict[str, Sequence[str]] = ..., tags_case_insensitive: bool = False, partition_hint: int |None = None) -> FileListOpts: ...
 
 @final
 class FilesBlocking:
     def count(self, /, opts: FileListOpts) -> TagCountsCursor: ...
     def download_result_file(self, /, sha256: "str", tool: "str", result_id: "UUID", path: "Path") -> "Attachment": ...
+    def download_to_path(self, /, sha256: "str", dest: "str | PathLike", uncart: "bool" = False) -> "Path": ...
     def get_results(self, /, sha256: str, params: ResultGetParams) -> OutputMap: ...
     def list(self, /, opts: FileListOpts) -> SampleListLineCursor: ...
     def list_details(self, /, opts: FileListOpts) -> SampleCursor: ...
@@ -99,10 +154,12 @@ class GenericJob: ...
 
 @final
 class GenericJobArgs:
-    def __new__(cls, /, positionals: Sequence[str] = ..., kwargs: dict[str, Sequence[str]] = ..., switches: Sequence[str] = ..., opts: GenericJobOpts = ...) -> GenericJobArgs: ...
+    def __new__(cls, /, positionals: Sequence[str] = ..., kwargs: dict[str, Sequence[str]] = ..., ordered_kwargs: Sequence[tuple[str, Sequence[str]]] = ..., switches: Sequence[str] = ..., opts: GenericJobOpts = ...) -> GenericJobArgs: ...
     @property
     def kwargs(self, /) -> dict[str, list[str]]: ...
     @property
+    def ordered_kwargs(self, /) -> list[tuple[str, list[str]]]: ...
+    @property
     def opts(self, /) -> GenericJobOpts: ...
     @property
     def positionals(self, /) -> list[str]: ...
@@ -111,13 +168,17 @@ class GenericJobArgs:
 
 @final
 class GenericJobOpts:
-    def __new__(cls, /, override_positionals: bool = False, override_kwargs: bool = False, override_cmd: Sequence[str] |None = None) -> GenericJobOpts: ...
+    def __new__(cls, /, override_positionals: bool = False, override_kwargs: bool = False, override_cmd: Sequence[str] |None = None, working_dir: str |PathLike |None = None, create_working_dir: bool = False) -> GenericJobOpts: ...
+    @property
+    def create_working_dir(self, /) -> bool: ...
     @property
     def override_cmd(self, /) -> list[str] |None: ...
     @property
     def override_kwargs(self, /) -> bool: ...
     @property
     def override_positionals(self, /) -> bool: ...
+    @property
+    def working_dir(self, /) -> Path |None: ...
 
 @final
 class HandleJobResponse: ...
@@ -133,10 +194,35 @@ class JobHandleStatus:
     def __int__(self, /) -> int: ...
     def __repr__(self, /) -> str: ...
 
+@final
+class JobHeartbeat: ...
+
+@final
+class JobStarted: ...
+
 @final
 class JobsBlocking:
     def checkpoint(self, /, job: GenericJob, checkpoint: str) -> HandleJobResponse: ...
+    def heartbeat(self, /, id: "UUID") -> JobHeartbeat: ...
     def sleep(self, /, job_id: "UUID", checkpoint: "str") -> "HandleJobResponse": ...
+    def start(self, /, id: "UUID") -> JobStarted: ...
+
+@final
+class McpBlocking:
+    def list_tools(self, /) -> list[McpTool]: ...
+
+@final
+class McpTool:
+    @property
+    def description(self, /) -> str |None: ...
+    @property
+    def input_schema(self, /) -> str: ...
+    @property
+    def name(self, /) -> str: ...
+    @property
+    def output_schema(self, /) -> str |None: ...
+    @property
+    def title(self, /) -> str |None: ...
 
 class Origin:
     @final
@@ -267,6 +353,8 @@ class Output:
     @property
     def id(self, /) -> Incomplete: ...
     @property
+    def renderer(self, /) -> OutputRenderer |None: ...
+    @property
     def result(self, /) -> str: ...
     @property
     def tool_version(self, /) -> str |None: ...
@@ -278,6 +366,17 @@ class OutputMap:
     @property
     def results(self, /) -> dict[str, list[Output]]: ...
 
+@final
+class OutputRenderer:
+    @property
+    def mime(self, /) -> str: ...
+    @property
+    def renderer(self, /) -> str: ...
+    @property
+    def sanitize(self, /) -> bool: ...
+    @property
+    def template(self, /) -> str |None: ...
+
 @final
 class Reaction:
     @property
@@ -303,6 +402,8 @@ class Reaction:
     @property
     def id(self, /) -> Incomplete: ...
     @property
+    def idempotency_key(self, /) -> str |None: ...
+    @property
     def jobs(self, /) -> list[Incomplete]: ...
     @property
     def parent(self, /) -> Incomplete |None: ...
@@ -311,6 +412,8 @@ class Reaction:
     @property
     def pipeline(self, /) -> str: ...
     @property
+    def priority(self, /) -> int: ...
+    @property
     def repos(self, /) -> list[RepoDependency]: ...
     @property
     def samples(self, /) -> list[str]: ...
@@ -331,12 +434,15 @@ class ReactionCache:
 
 @final
 class ReactionCreation:
+    @property
+    def deduplicated(self, /) -> bool: ...
     @property
     def id(self, /) -> Incomplete: ...
 
 @final
 class ReactionRequest:
-    def __new__(cls, /, group: str, pipeline: str, args: dict[str, GenericJobArgs] = ..., sla: int |None = None, tags: Sequence[str] = ..., parent: "UUID | None" = None, samples: Sequence[str] = ..., buffers: dict[str, str] = ..., repos: Sequence[RepoDependencyRequest] = ..., trigger_depth: int |None = None, cache: ReactionCache = ...) -> ReactionRequest: ...
+    def __new__(cls, /, group: str, pipeline: str, args: dict[str, GenericJobArgs] = ..., sla: int |None = None, tags: Sequence[str] = ..., parent: "UUID | None" = None, samples: Sequence[str] = ..., buffers: dict[str, str] = ..., repos: Sequence[RepoDependencyRequest] = ..., trigger_depth: int |None = None, cache: ReactionCache = ..., idempotency_key: str |None = None, priority: int = 0) -> ReactionRequest: ...
+    def canonical_key(self, /) -> str: ...
 
 @final
 class ReactionStatus:
@@ -376,6 +482,18 @@ class RepoDependencyRequest:
 class ResultGetParams:
     def __new__(cls, /, hidden: bool = False, tools: Sequence[str] = ..., groups: Sequence[str] = ...) -> ResultGetParams: ...
 
+@final
+class RetryPolicy:
+    def __new__(cls, /, max_attempts: int = 3, base_delay: int = 250, max_delay: int = 10000, retry_non_idempotent: bool = False) -> RetryPolicy: ...
+    @property
+    def base_delay(self, /) -> int: ...
+    @property
+    def max_attempts(self, /) -> int: ...
+    @property
+    def max_delay(self, /) -> int: ...
+    @property
+    def retry_non_idempotent(self, /) -> bool: ...
+
 @final
 class Sample:
     @property
@@ -414,6 +532,22 @@ class SampleListLineCursor:
     def exhausted(self, /) -> bool: ...
     def refill(self, /) -> None: ...
 
+@final
+class SearchDate:
+    @staticmethod
+    def between(oldest: datetime, newest: datetime) -> SearchRange: ...
+    @staticmethod
+    def last_days(days: int) -> SearchRange: ...
+    @staticmethod
+    def last_hours(hours: int) -> SearchRange: ...
+
+@final
+class SearchRange:
+    @property
+    def end(self, /) -> datetime: ...
+    @property
+    def start(self, /) -> datetime: ...
+
 @final
 class SubmissionChunk:
     @property
@@ -467,7 +601,24 @@ class ThoriumBlocking:
     @property
     def jobs(self, /) -> JobsBlocking: ...
     @property
+    def mcp(self, /) -> McpBlocking: ...
+    @property
     def reactions(self, /) -> ReactionsBlocking: ...
 
+@final
+class UserSummary:
+    @property
+    def admin(self, /) -> bool: ...
+    @property
+    def email(self, /) -> str: ...
+    @property
+    def groups(self, /) -> list[str]: ...
+    @property
+    def token_expiration(self, /) -> datetime: ...
+    @property
+    def username(self, /) -> str: ...
+    @property
+    def verified(self, /) -> bool: ...
+
 
 from uuid import UUID
@@ -47,6 +47,10 @@ pub struct Association {
     pub extra_source: Option<String>,
     /// Any extra info needed for the target column in this row
     pub extra_other: Option<String>,
+    /// How confident the submitter was in this association
+    pub confidence: Option<i32>,
+    /// When this association was archived if it has been archived
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl Utils for Association {
@@ -71,7 +75,7 @@ impl Backup for Association {
         // build logs get prepared statement
         scylla
             .prepare(format!(
-                "SELECT group, year, bucket, created, direction, kind, source, target, submitter, extra_source, extra_target \
+                "SELECT group, year, bucket, created, direction, kind, source, target, submitter, extra_source, extra_target, confidence, archived_at \
                 FROM {}.{} \
                 WHERE token(group, year, bucket, source) >= ? AND token(group, year, bucket, source) <= ?",
                     ns,
@@ -123,8 +127,8 @@ impl Restore for Association {
         scylla
             .prepare(format!(
                 "INSERT INTO {}.{} \
-                (group, year, bucket, created, direction, kind, source, target, submitter, extra_source, extra_target) \
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                (group, year, bucket, created, direction, kind, source, target, submitter, extra_source, extra_target, confidence, archived_at) \
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 ns,
                 Self::name(),
             ))
@@ -168,6 +172,9 @@ impl Restore for Association {
             let kind: AssociationKind = row.kind.deserialize(&mut rkyv::Infallible)?;
             // deserialize this rows uploaded timestamp
             let created = row.created.deserialize(&mut rkyv::Infallible)?;
+            // deserialize when this row was archived if it was
+            let archived_at: Option<DateTime<Utc>> =
+                row.archived_at.deserialize(&mut rkyv::Infallible)?;
             // calculate the new bucket
             let bucket = thorium::utils::helpers::partition(created, row.year, *partition_size);
             let query = scylla.execute_unpaged(
@@ -184,6 +191,8 @@ impl Restore for Association {
                     row.submitter.as_str(),
                     row.extra_source.as_ref().map(ArchivedString::as_str),
                     row.extra_other.as_ref().map(ArchivedString::as_str),
+                    row.confidence.as_ref().copied(),
+                    archived_at,
                 ),
             );
             // add this to our futures
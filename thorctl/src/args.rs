@@ -1,6 +1,7 @@
 //! The command line args for telling Thorctl which actions to take
 
 use clap::Parser;
+use serde::Serialize;
 use std::path::PathBuf;
 use thorium::models::NotificationLevel;
 use uuid::Uuid;
@@ -10,6 +11,7 @@ use self::{
     cart::Cart,
     clusters::{Clusters, Login},
     config::Config,
+    entities::Entities,
     files::Files,
     groups::Groups,
     images::Images,
@@ -28,6 +30,7 @@ pub mod ai;
 pub mod cart;
 pub mod clusters;
 pub mod config;
+pub mod entities;
 pub mod files;
 pub mod groups;
 mod helpers;
@@ -90,6 +93,79 @@ pub struct Args {
     /// Disable progress tracking and only print errors to stderr
     #[clap(short, long)]
     pub quiet: bool,
+    /// Print full error details instead of a short summary
+    #[clap(long)]
+    pub debug: bool,
+    /// The format to print command output in (e.g. `thorctl --output json reactions get`)
+    ///
+    /// This must come before the subcommand as some subcommands use `--output` for paths.
+    #[clap(long, value_enum, default_value_t)]
+    pub output: OutputFormat,
+}
+
+/// The format to print command output in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Print human readable tables
+    #[default]
+    Human,
+    /// Print each item as a single line of JSON
+    Json,
+}
+
+impl std::fmt::Display for OutputFormat {
+    /// write our [`OutputFormat`] to this formatter
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OutputFormat::Human => write!(f, "human"),
+            OutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Print a header only if we are printing human readable output
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The function that prints our header
+    pub fn header<F: FnOnce()>(self, header: F) {
+        if self == OutputFormat::Human {
+            header();
+        }
+    }
+
+    /// Print an item in this format
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The item to print
+    /// * `human` - The function that prints this item in a human readable format
+    pub fn print<T, F>(self, item: &T, human: F) -> Result<(), thorium::Error>
+    where
+        T: Serialize,
+        F: FnOnce(&T),
+    {
+        match self {
+            OutputFormat::Human => human(item),
+            OutputFormat::Json => println!("{}", serde_json::to_string(item)?),
+        }
+        Ok(())
+    }
+
+    /// Print a message that is not part of our output
+    ///
+    /// Messages go to stderr when printing JSON so they don't break parsing.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to print
+    pub fn message<T: std::fmt::Display>(self, msg: T) {
+        match self {
+            OutputFormat::Human => println!("{msg}"),
+            OutputFormat::Json => eprintln!("{msg}"),
+        }
+    }
 }
 
 /// The commands to send to handlers for Thorium
@@ -107,6 +183,9 @@ pub enum SubCommands {
     /// Perform file related tasks
     #[clap(version, author, subcommand)]
     Files(Files),
+    /// Perform entity related tasks
+    #[clap(version, author, subcommand)]
+    Entities(Entities),
     /// Perform image related tasks
     #[clap(version, author, subcommand)]
     Images(Images),
@@ -159,10 +238,10 @@ pub enum Mode {
     Repo,
 }
 
-impl TryFrom<&String> for Mode {
+impl TryFrom<&str> for Mode {
     type Error = thorium::Error;
 
-    fn try_from(sha256_or_repo: &String) -> Result<Self, Self::Error> {
+    fn try_from(sha256_or_repo: &str) -> Result<Self, Self::Error> {
         // check if the given SHA256/Repo is a valid SHA256
         if sha256_or_repo.len() == 64 && sha256_or_repo.chars().all(|c| c.is_ascii_hexdigit()) {
             // if we have a valid SHA256, assume File mode
@@ -180,6 +259,14 @@ impl TryFrom<&String> for Mode {
     }
 }
 
+impl TryFrom<&String> for Mode {
+    type Error = thorium::Error;
+
+    fn try_from(sha256_or_repo: &String) -> Result<Self, Self::Error> {
+        Self::try_from(sha256_or_repo.as_str())
+    }
+}
+
 #[derive(clap::Args, Debug, Clone)]
 #[group(required = false, multiple = true)]
 pub struct GetNotificationOpts {
@@ -0,0 +1,46 @@
+//! Arguments for entity-related Thorctl commands
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use thorium::models::{AssociationKind, GraphFormat};
+use uuid::Uuid;
+
+/// The commands to send to the entities task handler
+#[derive(Parser, Debug)]
+pub enum Entities {
+    /// Export the association graph around entities
+    #[clap(version, author)]
+    Graph(ExportGraph),
+}
+
+/// A command to export the association graph around entities
+#[derive(Parser, Debug)]
+pub struct ExportGraph {
+    /// Any specific entities to export the graph around
+    ///     Note: If no entities are given, all entities in the given groups are exported
+    #[clap(short, long, value_delimiter = ',', verbatim_doc_comment)]
+    pub entities: Vec<Uuid>,
+    /// The groups to export the graph from
+    ///     Note: If no groups are given, all groups the user is apart of are used
+    #[clap(short, long, value_delimiter = ',', verbatim_doc_comment)]
+    pub groups: Vec<String>,
+    /// The kinds of associations to export (e.g. `DevelopedBy`)
+    ///     Note: If no kinds are given, all kinds of associations are exported
+    #[clap(short, long, value_delimiter = ',', verbatim_doc_comment)]
+    pub kinds: Vec<AssociationKind>,
+    /// The format to export the graph in
+    #[clap(short, long, value_enum, default_value_t = GraphFormat::NodeLink)]
+    pub format: GraphFormat,
+    /// The max number of nodes to export
+    #[clap(long, default_value_t = 1000)]
+    pub max_nodes: usize,
+    /// The max number of links to export
+    #[clap(long, default_value_t = 5000)]
+    pub max_links: usize,
+    /// The path to write the graph to; if not provided, the graph will be written to stdout
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
+}
@@ -1,6 +1,8 @@
 //! Helper functions for Thorctl arguments
 
 use std::str::FromStr;
+use thorium::Error;
+use thorium::models::GenericJobKwargs;
 
 /// Provide a possible number range for an argument, producing an error if a user
 /// provides a value not within the range
@@ -27,3 +29,27 @@ where
         Ok(val)
     }
 }
+
+/// Parse raw kwargs into their keys and values split by a delimiter
+///
+/// # Arguments
+///
+/// * `raw_kwargs` - The raw kwargs to parse (i.e. <KEY>=<VALUE1>=<VALUE2>)
+/// * `delimiter` - The delimiter between a kwarg's key and values
+pub fn parse_kwargs(raw_kwargs: &[String], delimiter: char) -> Result<GenericJobKwargs, Error> {
+    let mut kwargs = GenericJobKwargs::new();
+    for raw_kwarg in raw_kwargs {
+        let mut split = raw_kwarg.split(delimiter);
+        let key = split.next().ok_or(Error::new(format!(
+            "Invalid kwarg \"{raw_kwarg}\": kwarg is improperly delimited!",
+        )))?;
+        let values: Vec<String> = split.map(str::to_string).collect();
+        if values.is_empty() {
+            return Err(Error::new(format!(
+                "Invalid kwarg \"{raw_kwarg}\": kwarg has no values!"
+            )));
+        }
+        kwargs.insert(key.to_owned(), values);
+    }
+    Ok(kwargs)
+}
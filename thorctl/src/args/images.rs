@@ -6,11 +6,12 @@ use clap::Parser;
 use clap::builder::NonEmptyStringValueParser;
 use std::path::PathBuf;
 use thorium::client::conf;
-use thorium::models::ImageScaler;
+use thorium::models::{GenericJobArgs, GenericJobOpts, ImageScaler, RepoDependency};
 use uuid::Uuid;
 
 use crate::utils;
 
+use super::repos::RepoTarget;
 use super::traits::describe::{DescribeCommand, DescribeSealed};
 use super::traits::search::{SearchParameterized, SearchParams, SearchSealed};
 use super::{CreateNotification, GetNotificationOpts};
@@ -29,6 +30,12 @@ pub enum Images {
     /// Static/uneditable fields are marked '*<field>*'
     #[clap(version, author)]
     Edit(EditImage),
+    /// Check if job args are compatible with an image before creating reactions with them
+    #[clap(version, author)]
+    ValidateArgs(ValidateImageArgs),
+    /// Render the command the agent would run for a job in an image without running it
+    #[clap(version, author)]
+    RenderCmd(RenderImageCmd),
     /// Manage/list image notifications
     #[clap(subcommand)]
     Notifications(ImageNotifications),
@@ -252,6 +259,141 @@ pub struct EditImage {
     pub editor: Option<String>,
 }
 
+/// Args for checking if job args are compatible with an image
+#[derive(Parser, Debug)]
+pub struct ValidateImageArgs {
+    /// The name of the image to validate args against
+    pub image: String,
+    /// The group the image is in; required if other images have
+    /// the same name
+    pub group: Option<String>,
+    /// The job args to validate
+    #[clap(flatten)]
+    pub args: ImageJobArgs,
+}
+
+/// The job args to build a command from for an image
+#[derive(Parser, Debug)]
+pub struct ImageJobArgs {
+    /// Any positional arguments to set (may be delimited with ',')
+    #[clap(long, conflicts_with = "args_file", value_delimiter = ',')]
+    pub positionals: Vec<String>,
+    /// Any keyword arguments to set with key,values separated by a delimiter
+    ///     Note: The delimiter for kwargs is given by "--delimiter"
+    ///           (e.g. --kwargs --my-kwarg=my-value1=my-value2)
+    #[clap(long, conflicts_with = "args_file", verbatim_doc_comment)]
+    pub kwargs: Vec<String>,
+    /// Any switch arguments to set (may be delimited with ',')
+    ///
+    /// Note: Switch arguments may conflict with Thorctl arguments; using "=" is recommended
+    /// (e.g. "--switches=--arg1,--arg2,-a" or "--switches=--arg1 --switches=-a")
+    #[clap(long, conflicts_with = "args_file", value_delimiter = ',')]
+    pub switches: Vec<String>,
+    /// Override/replace positional arguments in the image rather than simply adding them
+    #[clap(long, conflicts_with = "args_file")]
+    pub override_positionals: bool,
+    /// Override/replace keyword arguments in the image rather than simply adding them
+    #[clap(long, conflicts_with = "args_file")]
+    pub override_kwargs: bool,
+    /// An explicit command to send to the image, overriding any configured commands/arguments
+    #[clap(long, conflicts_with = "args_file")]
+    pub override_cmd: Option<String>,
+    /// The delimiter character to use when splitting kwargs into key/values
+    #[clap(long, default_value = "=")]
+    pub delimiter: char,
+    /// The path to a JSON-formatted file containing the job args to use
+    #[clap(short, long)]
+    pub args_file: Option<PathBuf>,
+}
+
+impl ImageJobArgs {
+    /// Build job args from this command
+    pub fn job_args(&self) -> Result<GenericJobArgs, thorium::Error> {
+        // read our args from a file if one was given
+        if let Some(path) = &self.args_file {
+            let file = std::fs::File::open(path).map_err(|err| {
+                thorium::Error::new(format!(
+                    "Error opening args file at '{}': {err}",
+                    path.to_string_lossy()
+                ))
+            })?;
+            return serde_json::from_reader(file).map_err(|err| {
+                thorium::Error::new(format!(
+                    "Args file at '{}' is not formatted correctly: {err}",
+                    path.to_string_lossy()
+                ))
+            });
+        }
+        // convert our override command to a list of args delimited by spaces
+        let override_cmd = self
+            .override_cmd
+            .as_ref()
+            .map(|cmd| cmd.split(' ').map(str::to_string).collect());
+        Ok(GenericJobArgs::default()
+            .positionals(self.positionals.clone())
+            .set_kwargs(super::helpers::parse_kwargs(&self.kwargs, self.delimiter)?)
+            .switches(self.switches.clone())
+            .opts(GenericJobOpts::new(
+                self.override_positionals,
+                self.override_kwargs,
+                override_cmd,
+            )))
+    }
+}
+
+/// Args for rendering the command the agent would run for a job in an image
+#[derive(Parser, Debug)]
+pub struct RenderImageCmd {
+    /// The name of the image to render a command for
+    pub image: String,
+    /// The group the image is in; required if other images have
+    /// the same name
+    pub group: Option<String>,
+    /// The args for the job to render a command for
+    #[clap(flatten)]
+    pub args: ImageJobArgs,
+    /// The SHA256's of any samples to pass to the job as dependencies (may be delimited with ',')
+    ///
+    /// Note: Samples are assumed to be named by their SHA256 in the image's samples location
+    #[clap(long, value_delimiter = ',')]
+    pub samples: Vec<String>,
+    /// Any repos + optionally commits to pass to the job as dependencies
+    ///
+    /// Note: Repo commits are formatted with a colon after the repo URL
+    ///       (i.e. "<REPO-URL>:<COMMIT-HASH>)"
+    #[clap(long)]
+    pub repos: Vec<String>,
+    /// The original entrypoint of the image's container, delimited by spaces
+    ///
+    /// Note: This is ignored if the image sets its own entrypoint
+    #[clap(long)]
+    pub entrypoint: Option<String>,
+    /// The original command of the image's container, delimited by spaces
+    ///
+    /// Note: This is ignored if the image sets its own command
+    #[clap(long)]
+    pub command: Option<String>,
+    /// Render the command as it would be run on Windows
+    #[clap(long)]
+    pub windows: bool,
+}
+
+impl RenderImageCmd {
+    /// Get the repo dependencies to pass to the job
+    pub fn repo_dependencies(&self) -> Result<Vec<RepoDependency>, thorium::Error> {
+        self.repos
+            .iter()
+            .map(|raw| {
+                RepoTarget::try_from(raw).map(|target| RepoDependency {
+                    url: target.url,
+                    commitish: target.commitish,
+                    kind: target.kind,
+                })
+            })
+            .collect()
+    }
+}
+
 /// The image ban specific subcommands
 #[derive(Parser, Debug, Clone)]
 pub enum ImageBans {
@@ -22,6 +22,9 @@ pub enum Pipelines {
     /// Describe specific pipelines, displaying/saving details in JSON format
     #[clap(version, author)]
     Describe(DescribePipelines),
+    /// Plan out the jobs a pipeline would create without running it
+    #[clap(version, author)]
+    Plan(PlanPipeline),
     /// Manage/list pipeline notifications
     #[clap(subcommand)]
     Notifications(PipelineNotifications),
@@ -120,6 +123,15 @@ impl SearchParameterized for DescribePipelines {
     }
 }
 
+/// A command to plan out the jobs a pipeline would create
+#[derive(Parser, Debug)]
+pub struct PlanPipeline {
+    /// The group the pipeline is in
+    pub group: String,
+    /// The name of the pipeline to plan
+    pub pipeline: String,
+}
+
 /// A specific pipeline target containing an optional group in case
 /// more than one group has an image with the same name
 pub struct PipelineTarget {
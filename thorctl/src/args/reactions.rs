@@ -9,8 +9,10 @@ use std::path::PathBuf;
 use clap::Parser;
 use clap::builder::NonEmptyStringValueParser;
 use itertools::Itertools;
+use thorium::client::Cursor;
 use thorium::models::{
-    GenericJobArgs, GenericJobKwargs, GenericJobOpts, Reaction, ReactionArgs, ReactionStatus,
+    GenericJobArgs, GenericJobKwargs, GenericJobOpts, Reaction, ReactionArgs, ReactionListParams,
+    ReactionStatus,
 };
 use thorium::{Error, Thorium};
 use uuid::Uuid;
@@ -65,17 +67,54 @@ pub struct GetReactions {
     /// Print detailed information on any reactions returned
     #[clap(short, long)]
     pub details: bool,
-    /// The max number of reactions to list
-    #[clap(short, long, default_value = "50")]
-    pub limit: u64,
+    /// The max number of reactions to list (all pages are listed if this is not set)
+    #[clap(short, long)]
+    pub limit: Option<u64>,
     /// The number of reactions to retrieve per request
     #[clap(long, default_value = "50")]
     pub page_size: u64,
-    /// Refrain from setting a limit when listing reactions
+    /// The cursor to start listing reactions from (printed after each listing)
+    #[clap(long, default_value = "0")]
+    pub cursor: u64,
+    /// Only list a single page of reactions
     #[clap(long, conflicts_with = "limit")]
+    pub page: bool,
+    /// Deprecated and ignored since reactions are only limited when `--limit` is set
+    ///
+    /// This is kept hidden so existing scripts that pass it don't break.
+    #[clap(long, hide = true, conflicts_with = "limit")]
     pub no_limit: bool,
 }
 
+impl GetReactions {
+    /// Get the params to use when listing reactions
+    #[must_use]
+    pub fn list_params(&self) -> ReactionListParams {
+        ReactionListParams::default()
+            .cursor(self.cursor as usize)
+            .limit(self.page_size as usize)
+    }
+
+    /// Apply our paging settings to a reaction cursor
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor to apply our paging settings to
+    #[must_use]
+    pub fn configure_cursor(&self, cursor: Cursor<Reaction>) -> Cursor<Reaction> {
+        // start from our cursor and use our limit as the page size for each request
+        let params = self.list_params();
+        let cursor = cursor
+            .cursor(params.cursor as u64)
+            .page_size(params.limit as u64);
+        // only cap the total number of reactions if a limit was set
+        match self.limit {
+            Some(limit) => cursor.limit(limit),
+            None => cursor,
+        }
+    }
+}
+
 /// A command to delete reactions
 #[derive(Parser, Debug, Clone)]
 pub struct DeleteReactions {
@@ -104,6 +143,23 @@ pub struct DeleteReactions {
     pub no_limit: bool,
 }
 
+impl DeleteReactions {
+    /// Apply our paging settings to a reaction cursor
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor to apply our paging settings to
+    #[must_use]
+    pub fn configure_cursor(&self, cursor: Cursor<Reaction>) -> Cursor<Reaction> {
+        let cursor = cursor.page_size(self.page_size);
+        if self.no_limit {
+            cursor
+        } else {
+            cursor.limit(self.limit)
+        }
+    }
+}
+
 /// A command to describe particular reactions in full
 #[derive(Parser, Debug)]
 pub struct DescribeReactions {
@@ -385,6 +441,12 @@ pub struct LogsReactions {
     /// The number of reactions to retrieve per request
     #[clap(long, default_value_t = 50)]
     pub page_size: usize,
+    /// Tail the stage logs for a single reaction until it completes or fails
+    #[clap(short, long, conflicts_with_all = ["output", "reaction_list", "pipelines", "groups", "tags"])]
+    pub follow: bool,
+    /// The number of seconds to wait between polls for new logs when following logs
+    #[clap(long, default_value_t = 2, requires = "follow")]
+    pub poll_interval: u64,
 }
 
 impl SearchSealed for LogsReactions {
@@ -631,21 +693,7 @@ impl CreateReactions {
 
     /// Parse kwargs from the command by the command's delimiter
     pub fn parse_kwargs(&self) -> Result<GenericJobKwargs, Error> {
-        let mut kwargs = GenericJobKwargs::new();
-        for raw_kwarg in &self.kwargs {
-            let mut split = raw_kwarg.split(self.delimiter);
-            let key = split.next().ok_or(Error::new(format!(
-                "Invalid kwarg \"{raw_kwarg}\": kwarg is improperly delimited!",
-            )))?;
-            let values: Vec<String> = split.map(str::to_string).collect();
-            if values.is_empty() {
-                return Err(Error::new(format!(
-                    "Invalid kwarg \"{raw_kwarg}\": kwarg has no values!"
-                )));
-            }
-            kwargs.insert(key.to_owned(), values);
-        }
-        Ok(kwargs)
+        super::helpers::parse_kwargs(&self.kwargs, self.delimiter)
     }
 
     /// Returns true if the command contains any reaction args
@@ -821,11 +869,11 @@ pub async fn params_to_cursors(
             match res {
                 Ok(cursor) => cursors.push(cursor),
                 Err(err) => match err {
-                    Error::Thorium { code, msg } => {
+                    Error::Thorium { code, .. } => {
                         // ignore 404 errors because we're checking for pipelines that may or may not
                         // exist in a given group
                         if code != 404 {
-                            return Err(Error::Thorium { code, msg });
+                            return Err(err);
                         }
                     }
                     _ => return Err(err),
@@ -3,13 +3,19 @@
 use clap::Parser;
 use std::path::PathBuf;
 
+/// Provide a range for the number of reactions to run at once
+fn parallel_range(s: &str) -> Result<usize, String> {
+    super::helpers::number_range(s, 1, 256)
+}
+
 /// The commands to send to the repos task handler
 #[derive(Parser, Debug)]
 pub struct Run {
     /// The pipeline to run
     pub pipeline: String,
-    /// The sample SHA256 or repo to run the pipeline on
-    pub sha256_or_repo: String,
+    /// The sample SHA256s or repos to run the pipeline on
+    #[clap(required = true)]
+    pub targets: Vec<String>,
     /// The group that the pipeline is in (required if a pipeline with the same
     /// name exists in another group)
     #[clap(long)]
@@ -18,6 +24,12 @@ pub struct Run {
     #[clap(short, long, default_value_t = 1)]
     pub sla: u64,
     /// The path to save the results to [default: `<SHA256/REPO>_<PIPELINE>`]
+    ///
+    /// When running on more than one target, each target's results are saved
+    /// in their own directory under this path.
     #[clap(short, long)]
     pub output: Option<PathBuf>,
+    /// The max number of targets to run the pipeline on at once
+    #[clap(long, default_value_t = 1, value_parser = parallel_range)]
+    pub parallel: usize,
 }
@@ -3,6 +3,7 @@ pub mod cart;
 pub mod clusters;
 pub mod config;
 mod controllers;
+pub mod entities;
 pub mod files;
 pub mod groups;
 pub mod images;
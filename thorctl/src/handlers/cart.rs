@@ -1,17 +1,11 @@
-use cart_rs::CartStream;
-use generic_array::{GenericArray, typenum::U16};
 use regex::RegexSet;
 use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
 use thorium::Error;
-use tokio::{
-    fs::{File, OpenOptions},
-    io::BufStream,
-    task::JoinError,
-};
-use uuid::Uuid;
+use thorium::cart::{self, CartOpts, resume};
+use tokio::task::JoinError;
 
 use crate::args::{Args, cart::Cart};
 use crate::utils;
@@ -28,9 +22,8 @@ pub async fn handle(args: &Args, cmd: &Cart) -> Result<(), Error> {
     let skip = RegexSet::new(&cmd.skip)?;
     // prepare data for saving to between tasks
     let cmd = Arc::new(cmd.clone());
-    let password_array: Arc<GenericArray<u8, U16>> = Arc::new(GenericArray::clone_from_slice(
-        &pad_zeroes(cmd.password.as_bytes())?,
-    ));
+    // make sure our password is valid before we start carting files
+    cart::key(&cmd.password)?;
     // construct base output path
     let base_out_path = if cmd.in_place {
         &cmd.temp_dir
@@ -49,11 +42,10 @@ pub async fn handle(args: &Args, cmd: &Cart) -> Result<(), Error> {
             // copy data for this task
             let base_out_path = base_out_path.clone();
             let cmd = cmd.clone();
-            let password = password_array.clone();
             async move {
                 // cart the entry in a new task
                 let cart_result: Result<Result<PathBuf, Error>, JoinError> =
-                    tokio::spawn(cart_path(path, base_out_path, cmd, password)).await;
+                    tokio::spawn(cart_path(path, base_out_path, cmd)).await;
                 // log the result
                 match cart_result {
                     Ok(Ok(out_path)) => CartLine::success(&path_copy, &out_path),
@@ -70,9 +62,9 @@ pub async fn handle(args: &Args, cmd: &Cart) -> Result<(), Error> {
         args.workers,
     )
     .await;
-    // remove temporary drectory after in-place conversion
+    // remove temporary drectory after in-place conversion unless it has partial output to resume
     if cmd.in_place {
-        tokio::fs::remove_dir_all(base_out_path).await?;
+        resume::remove_temp_dir(base_out_path).await?;
     }
     Ok(())
 }
@@ -87,19 +79,11 @@ pub async fn handle(args: &Args, cmd: &Cart) -> Result<(), Error> {
 /// * `target_path` - The path to the target
 /// * `base_out_path` - The base output path
 /// * `cmd` - The cart command including user options
-/// * `password` - The password used to encrypt the cart file
 async fn cart_path(
     path: PathBuf,
     base_out_path: PathBuf,
     cmd: Arc<Cart>,
-    password: Arc<GenericArray<u8, U16>>,
 ) -> Result<PathBuf, Error> {
-    // read input file
-    let input: File = OpenOptions::new()
-        .read(true)
-        .write(false)
-        .open(&path)
-        .await?;
     // generate output path and create necessary directories
     let mut out_path = construct_out_path(
         &path,
@@ -111,22 +95,13 @@ async fn cart_path(
     if let Some(out_path_parent) = out_path.parent() {
         tokio::fs::create_dir_all(&out_path_parent).await?;
     }
-    // open output carted file
-    let mut output_cart = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(&out_path)
-        .await?;
-    // create a stream to cart the file and copy the stream's contents to the output path
-    let mut cart_stream = CartStream::new(BufStream::new(input), &password)?;
-    if let Err(err) = tokio::io::copy(&mut cart_stream, &mut output_cart).await {
-        // if an error occurred while carting, delete the output file and return the error
-        drop(output_cart);
-        tokio::fs::remove_file(&out_path).await?;
-        return Err(Error::from(err));
+    let mut opts = CartOpts::default().password(cmd.password.clone());
+    // keep our progress next to where our output ends up so it outlives our temp dir
+    if cmd.in_place {
+        opts = opts.progress(resume::progress_path(&path));
     }
+    // cart our file, picking up where we left off if a previous attempt failed partway through
+    cart::cart_file(&path, &out_path, &opts).await?;
     // if conversion is in-place, replace the input file with the output cart
     if cmd.in_place {
         // replace the original file
@@ -143,28 +118,6 @@ async fn cart_path(
     Ok(out_path)
 }
 
-/// Pad the given byte array with 0's to create a 16-byte array
-///
-/// # Arguments
-///
-/// * `arr` - The input byte array
-fn pad_zeroes(arr: &[u8]) -> Result<[u8; 16], Error> {
-    match arr.len() {
-        len if len > 16 => Err(Error::new("Password is greater than 16 characters!")),
-        16 => match arr.try_into() {
-            Ok(arr) => Ok(arr),
-            _ => Err(Error::new(
-                "Unable to statically size 16-byte array to 16 bytes",
-            )),
-        },
-        _ => {
-            let mut padded: [u8; 16] = [0; 16];
-            padded[..arr.len()].copy_from_slice(arr);
-            Ok(padded)
-        }
-    }
-}
-
 /// Construct the output path for the carted file
 ///
 /// # Arguments
@@ -202,7 +155,7 @@ fn construct_out_path(
         out_path.push(file_name);
     }
     if in_place {
-        out_path.set_file_name(format!(".temp-{}.cart", Uuid::new_v4()));
+        out_path.set_file_name(format!("{}.cart", resume::temp_name(path)));
     } else if !no_extension {
         out_path.as_mut_os_string().push(".cart");
     }
@@ -0,0 +1,66 @@
+//! Handles entities commands
+
+use std::io::Write;
+use thorium::models::{EntityGraphOpts, GraphFormat};
+use thorium::{Error, Thorium};
+
+use crate::args::Args;
+use crate::args::entities::{Entities, ExportGraph};
+use crate::utils;
+
+/// Export the association graph around entities
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The [`ExportGraph`] command that was run
+async fn graph(thorium: Thorium, cmd: &ExportGraph) -> Result<(), Error> {
+    // build the options for this export
+    let mut opts = EntityGraphOpts::default()
+        .groups(cmd.groups.clone())
+        .max_nodes(cmd.max_nodes)
+        .max_links(cmd.max_links);
+    opts.entities.clone_from(&cmd.entities);
+    opts.kinds.clone_from(&cmd.kinds);
+    // get our graph in the requested format
+    let (document, truncated) = match cmd.format {
+        GraphFormat::NodeLink => {
+            let graph = thorium.entities.export_graph(&opts).await?;
+            (serde_json::to_string_pretty(&graph)?, graph.truncated)
+        }
+        // GraphML documents don't say if they were truncated
+        GraphFormat::GraphMl => (thorium.entities.export_graphml(&opts).await?, false),
+    };
+    // write our graph to a file or stdout
+    match &cmd.output {
+        Some(output_path) => {
+            let mut output_file = std::fs::File::create(output_path)?;
+            writeln!(&mut output_file, "{document}")?;
+        }
+        None => println!("{document}"),
+    }
+    // warn if our graph was truncated
+    if truncated {
+        eprintln!("Graph was truncated! Raise --max-nodes or --max-links to export more of it.");
+    }
+    Ok(())
+}
+
+/// Handle all entities commands
+///
+/// # Arguments
+///
+/// * `args` - The arguments passed to Thorctl
+/// * `cmd` - The entities command to execute
+pub async fn handle(args: &Args, cmd: &Entities) -> Result<(), Error> {
+    // load our config and instance our client
+    let (conf, thorium) = utils::get_client(args).await?;
+    // warn about insecure connections if not set to skip
+    if !conf.skip_insecure_warning.unwrap_or_default() {
+        utils::warn_insecure_conf(&conf)?;
+    }
+    // call the right entities handler
+    match cmd {
+        Entities::Graph(cmd) => graph(thorium, cmd).await,
+    }
+}
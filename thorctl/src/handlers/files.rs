@@ -22,7 +22,7 @@ use super::{Controller, update};
 use crate::args::files::{
     CountFiles, DeleteFiles, DescribeFiles, DownloadFiles, Files, GetFiles, UploadFiles,
 };
-use crate::args::{Args, DescribeCommand, SearchParameterized};
+use crate::args::{Args, DescribeCommand, OutputFormat, SearchParameterized};
 use crate::utils;
 
 /// A single line for an file upload log
@@ -440,12 +440,13 @@ impl GetLine {
 ///
 /// * `thorium` - A Thorium client
 /// * `cmd` - The full get command/args
-async fn get(thorium: &Thorium, cmd: &GetFiles) -> Result<(), Error> {
+/// * `output` - The format to print our files in
+async fn get(thorium: &Thorium, cmd: &GetFiles, output: OutputFormat) -> Result<(), Error> {
     // print the header for getting file info, omitting submission if tags are included
     if cmd.tags.is_empty() {
-        GetLine::header();
+        output.header(GetLine::header);
     } else {
-        GetLine::header_tags();
+        output.header(GetLine::header_tags);
     }
     // build a search object from our args
     let opts = cmd.build_file_opts()?;
@@ -464,10 +465,12 @@ async fn get(thorium: &Thorium, cmd: &GetFiles) -> Result<(), Error> {
             }
         }
         // crawl the files listed and print info about them
-        if cmd.tags.is_empty() {
-            cursor.data.iter().for_each(GetLine::list);
-        } else {
-            cursor.data.iter().for_each(GetLine::list_tags);
+        for line in &cursor.data {
+            if cmd.tags.is_empty() {
+                output.print(line, GetLine::list)?;
+            } else {
+                output.print(line, GetLine::list_tags)?;
+            }
         }
         // check if this cursor has been exhausted
         if cursor.exhausted() {
@@ -486,7 +489,8 @@ async fn get(thorium: &Thorium, cmd: &GetFiles) -> Result<(), Error> {
 ///
 /// * `thorium` - A Thorium client
 /// * `cmd` - The full count command/args
-async fn count(thorium: &Thorium, cmd: &CountFiles) -> Result<(), Error> {
+/// * `output` - The format to print our counts in
+async fn count(thorium: &Thorium, cmd: &CountFiles, output: OutputFormat) -> Result<(), Error> {
     // build a search object from our args
     let opts = cmd.build_file_opts()?;
     // build a cursor object for counting files and their tags
@@ -501,7 +505,7 @@ async fn count(thorium: &Thorium, cmd: &CountFiles) -> Result<(), Error> {
         cursor.refill().await?;
     }
     // print our count info
-    println!("{:#?}", cursor.data);
+    output.print(&cursor.data, |data| println!("{data:#?}"))?;
     Ok(())
 }
 
@@ -796,8 +800,8 @@ pub async fn handle(args: &Args, cmd: &Files) -> Result<(), Error> {
     match cmd {
         Files::Upload(cmd) => upload(&thorium, cmd).await,
         Files::Download(cmd) => download(&thorium, cmd, args, &conf).await,
-        Files::Get(cmd) => get(&thorium, cmd).await,
-        Files::Count(cmd) => count(&thorium, cmd).await,
+        Files::Get(cmd) => get(&thorium, cmd, args.output).await,
+        Files::Count(cmd) => count(&thorium, cmd, args.output).await,
         Files::Describe(cmd) => describe(&thorium, cmd).await,
         Files::Delete(cmd) => delete(&thorium, cmd).await,
     }
@@ -1,9 +1,19 @@
-use thorium::{Error, client::Thorium, models::Image};
+use chrono::Utc;
+use colored::Colorize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thorium::{
+    Error,
+    client::Thorium,
+    models::{GenericJob, GenericJobArgs, Image, JobStatus, RepoDependency},
+};
+use thorium_agent::CmdBuilder;
+use uuid::Uuid;
 
-use crate::args::Args;
+use crate::args::{Args, OutputFormat};
 use crate::args::{
     DescribeCommand,
-    images::{DescribeImages, GetImages, Images},
+    images::{DescribeImages, GetImages, Images, RenderImageCmd, ValidateImageArgs},
 };
 
 use crate::utils;
@@ -83,8 +93,9 @@ impl GetImagesLine {
 ///
 /// * `thorium` - The Thorium client
 /// * `cmd` - The image get command to execute
-async fn get(thorium: Thorium, cmd: &GetImages) -> Result<(), Error> {
-    GetImagesLine::header();
+/// * `output` - The format to print our images in
+async fn get(thorium: Thorium, cmd: &GetImages, output: OutputFormat) -> Result<(), Error> {
+    output.header(GetImagesLine::header);
     // get the current user's groups if no groups were specified
     let groups = if cmd.groups.is_empty() {
         utils::groups::get_all_groups(&thorium).await?
@@ -115,14 +126,18 @@ async fn get(thorium: Thorium, cmd: &GetImages) -> Result<(), Error> {
                 images.append(&mut cursor.details);
             } else {
                 // otherwise print immediately if no need to alphabetize
-                cursor.details.iter().for_each(GetImagesLine::print_image);
+                for image in &cursor.details {
+                    output.print(image, GetImagesLine::print_image)?;
+                }
             }
         }
     }
     // sort and print in alphabetical order if alpha flag was set
     if cmd.alpha {
         images.sort_unstable_by(|a, b| a.name.cmp(&b.name));
-        images.iter().for_each(GetImagesLine::print_image);
+        for image in &images {
+            output.print(image, GetImagesLine::print_image)?;
+        }
     }
     Ok(())
 }
@@ -137,6 +152,138 @@ async fn describe(thorium: Thorium, cmd: &DescribeImages) -> Result<(), Error> {
     cmd.describe(&thorium).await
 }
 
+/// Build a job for an image that is never actually created in Thorium
+///
+/// # Arguments
+///
+/// * `image` - The image to build a job for
+/// * `args` - The args for this job
+/// * `samples` - The samples to pass to this job
+/// * `repos` - The repos to pass to this job
+fn placeholder_job(
+    image: &Image,
+    args: GenericJobArgs,
+    samples: Vec<String>,
+    repos: Vec<RepoDependency>,
+) -> GenericJob {
+    GenericJob {
+        reaction: Uuid::nil(),
+        id: Uuid::nil(),
+        group: image.group.clone(),
+        pipeline: String::default(),
+        stage: image.name.clone(),
+        creator: String::default(),
+        args,
+        status: JobStatus::Created,
+        deadline: Utc::now(),
+        started: None,
+        execution_deadline: None,
+        parent: None,
+        generator: image.generator,
+        samples,
+        ephemeral: Vec::default(),
+        parent_ephemeral: HashMap::default(),
+        repos,
+        trigger_depth: None,
+    }
+}
+
+/// Check if job args are compatible with an image
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The validate args command to execute
+async fn validate_args(thorium: Thorium, cmd: &ValidateImageArgs) -> Result<(), Error> {
+    // build the args to validate
+    let args = cmd.args.job_args()?;
+    // get the image to validate these args against
+    let group = match &cmd.group {
+        Some(group) => group.clone(),
+        None => utils::images::find_image_group(&thorium, &cmd.image).await?,
+    };
+    let image = thorium.images.get(&group, &cmd.image).await?;
+    // check our args locally and print any problems
+    match image.validate_args(&args) {
+        Ok(()) => {
+            println!("Args are valid for image '{}:{}'", image.group, image.name);
+            Ok(())
+        }
+        Err(problems) => {
+            for problem in &problems {
+                println!("{}: {problem}", "Invalid".bright_red());
+            }
+            Err(Error::new(format!(
+                "Found {} problem(s) with args for image '{}:{}'",
+                problems.len(),
+                image.group,
+                image.name
+            )))
+        }
+    }
+}
+
+/// Render the command the agent would run for a job in an image
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The render command to execute
+async fn render_cmd(thorium: Thorium, cmd: &RenderImageCmd) -> Result<(), Error> {
+    // build the args and dependencies for our job
+    let args = cmd.args.job_args()?;
+    let repos = cmd.repo_dependencies()?;
+    // get the image to render a command for
+    let group = match &cmd.group {
+        Some(group) => group.clone(),
+        None => utils::images::find_image_group(&thorium, &cmd.image).await?,
+    };
+    let image = thorium.images.get(&group, &cmd.image).await?;
+    // build a job that is never actually created in Thorium
+    let job = placeholder_job(&image, args, cmd.samples.clone(), repos);
+    // build the paths the agent would download our dependencies to
+    let samples_dir = Path::new(&image.dependencies.samples.location);
+    let samples = job
+        .samples
+        .iter()
+        .map(|sha256| samples_dir.join(sha256))
+        .collect::<Vec<PathBuf>>();
+    let repos_dir = Path::new(&image.dependencies.repos.location);
+    let repos = job
+        .repos
+        .iter()
+        .map(|repo| match Path::new(&repo.url).file_name() {
+            Some(name) => Ok(repos_dir.join(name)),
+            None => Err(Error::new(format!(
+                "Failed to extract repo name from {}",
+                repo.url
+            ))),
+        })
+        .collect::<Result<Vec<PathBuf>, Error>>()?;
+    // split our container's original entrypoint and command by spaces
+    let split = |raw: &Option<String>| -> Vec<String> {
+        raw.as_ref()
+            .map(|raw| raw.split(' ').map(str::to_string).collect())
+            .unwrap_or_default()
+    };
+    let entrypoint = split(&cmd.entrypoint);
+    let command = split(&cmd.command);
+    // build our command the same way the agent does
+    let builder = CmdBuilder::new(&image, &job, &entrypoint, &command)
+        .add_samples(&samples, &image.dependencies.samples)
+        .add_repos(&image, &job.repos, &repos)
+        .windows(cmd.windows);
+    let built = builder.dry_run(&image, None, None)?;
+    // print the directory this command would be launched in if one was set; this is
+    // not checked since it only needs to exist wherever the agent runs
+    if let Some(working_dir) = &job.args.opts.working_dir {
+        println!("{}: {}", "Working Dir".bright_blue(), working_dir.display());
+    }
+    // print our rendered args
+    println!("{}", serde_json::to_string_pretty(&built)?);
+    Ok(())
+}
+
 /// Import images into Thorium
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 pub async fn import(
@@ -222,11 +369,13 @@ pub async fn handle(args: &Args, cmd: &Images) -> Result<(), Error> {
     }
     // call the right reactions handler
     match cmd {
-        Images::Get(cmd) => get(thorium, cmd).await,
+        Images::Get(cmd) => get(thorium, cmd, args.output).await,
         Images::Describe(cmd) => describe(thorium, cmd).await,
         Images::Notifications(cmd) => notifications::handle(thorium, cmd).await,
         Images::Bans(cmd) => bans::handle(thorium, cmd).await,
         Images::Edit(cmd) => edit::edit(thorium, &conf, cmd).await,
+        Images::ValidateArgs(cmd) => validate_args(thorium, cmd).await,
+        Images::RenderCmd(cmd) => render_cmd(thorium, cmd).await,
         #[cfg(any(target_os = "linux", target_os = "macos"))]
         Images::Import(cmd) => import(&thorium, cmd, args, &conf).await,
         #[cfg(any(target_os = "linux", target_os = "macos"))]
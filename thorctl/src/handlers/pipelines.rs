@@ -1,10 +1,10 @@
 use itertools::Itertools;
 use std::collections::HashSet;
 use thorium::CtlConf;
-use thorium::models::PipelineRequest;
+use thorium::models::{PipelinePlan, PipelineRequest};
 use thorium::{Error, Thorium, models::Pipeline};
 
-use crate::args::pipelines::{DescribePipelines, GetPipelines, Pipelines};
+use crate::args::pipelines::{DescribePipelines, GetPipelines, Pipelines, PlanPipeline};
 use crate::args::{Args, DescribeCommand};
 use crate::utils;
 
@@ -134,6 +134,50 @@ async fn describe(thorium: Thorium, cmd: &DescribePipelines) -> Result<(), Error
     cmd.describe(&thorium).await
 }
 
+/// Print a pipeline plan
+///
+/// # Arguments
+///
+/// * `plan` - The plan to print
+fn print_plan(plan: &PipelinePlan) {
+    println!("PIPELINE: {}:{}", plan.group, plan.name);
+    // print the triggers that would run this pipeline
+    if plan.triggers.is_empty() {
+        println!("TRIGGERS: -");
+    } else {
+        println!("TRIGGERS: {}", plan.triggers.keys().sorted().join(", "));
+    }
+    // print each stage and the images it would run
+    println!("STAGES:");
+    for stage in &plan.stages {
+        println!("  {:<5} | {}", stage.index, stage.images.join(", "));
+    }
+    // print the dependencies between our images
+    println!("EDGES:");
+    for edge in &plan.edges {
+        println!("  {} -> {}", edge.from, edge.to);
+    }
+    // print any problems with this pipeline
+    if plan.has_warnings() {
+        println!("WARNINGS:");
+        for warning in &plan.warnings {
+            println!("  {warning}");
+        }
+    }
+}
+
+/// Plan out the jobs a pipeline would create without creating any reactions
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The plan pipeline command to execute
+async fn plan(thorium: Thorium, cmd: &PlanPipeline) -> Result<(), Error> {
+    let plan = thorium.pipelines.plan(&cmd.group, &cmd.pipeline).await?;
+    print_plan(&plan);
+    Ok(())
+}
+
 /// Crawl all of the pipeline requests we want to import to build a list of images to import
 ///
 /// # Arguments
@@ -296,6 +340,7 @@ pub async fn handle(args: &Args, cmd: &Pipelines) -> Result<(), Error> {
     match cmd {
         Pipelines::Get(cmd) => get(thorium, cmd).await,
         Pipelines::Describe(cmd) => describe(thorium, cmd).await,
+        Pipelines::Plan(cmd) => plan(thorium, cmd).await,
         Pipelines::Notifications(cmd) => notifications::handle(thorium, cmd).await,
         Pipelines::Bans(cmd) => bans::handle(thorium, cmd).await,
         #[cfg(any(target_os = "linux", target_os = "macos"))]
@@ -18,7 +18,7 @@ use crate::Error;
 use crate::args::reactions::{
     DeleteReactions, DescribeReactions, GetReactions, LogsReactions, ReactionTarget, Reactions,
 };
-use crate::args::{self, Args, DescribeCommand, SearchParameterized};
+use crate::args::{self, Args, DescribeCommand, OutputFormat, SearchParameterized};
 use crate::handlers::Controller;
 use crate::utils;
 
@@ -159,15 +159,9 @@ macro_rules! crawl_pipeline {
     ($client:expr, $pipe:expr, $cmd:expr) => {
         async {
             // build a cursor object
-            let mut cursor = $client
-                .reactions
-                .list(&$cmd.group, $pipe)
-                .page_size($cmd.page_size)
-                .details();
-            if !$cmd.no_limit {
-                cursor = cursor.limit($cmd.limit);
-            }
-            cursor.exec().await
+            let cursor = $client.reactions.list(&$cmd.group, $pipe).details();
+            // apply this commands paging settings
+            $cmd.configure_cursor(cursor).exec().await
         }
     };
 }
@@ -183,15 +177,9 @@ macro_rules! crawl_tag {
     ($client:expr, $tag:expr, $cmd:expr) => {
         async {
             // build a cursor object
-            let mut cursor = $client
-                .reactions
-                .list_tag(&$cmd.group, $tag)
-                .page_size($cmd.page_size)
-                .details();
-            if !$cmd.no_limit {
-                cursor = cursor.limit($cmd.limit);
-            }
-            cursor.exec().await
+            let cursor = $client.reactions.list_tag(&$cmd.group, $tag).details();
+            // apply this commands paging settings
+            $cmd.configure_cursor(cursor).exec().await
         }
     };
 }
@@ -208,15 +196,12 @@ macro_rules! crawl_status {
     ($client:expr, $pipe:expr, $status:expr, $cmd:expr) => {
         async {
             // build a cursor object
-            let mut cursor = $client
+            let cursor = $client
                 .reactions
                 .list_status(&$cmd.group, $pipe, $status)
-                .page_size($cmd.page_size)
                 .details();
-            if !$cmd.no_limit {
-                cursor = cursor.limit($cmd.limit);
-            }
-            cursor.exec().await
+            // apply this commands paging settings
+            $cmd.configure_cursor(cursor).exec().await
         }
     };
 }
@@ -227,14 +212,23 @@ macro_rules! crawl_status {
 ///
 /// * `thorium` - A Thorium client
 /// * `cmd` - The full get command/args
-async fn info_specific(thorium: &Thorium, cmd: &GetReactions) -> Result<(), Error> {
+/// * `output` - The format to print our reactions in
+async fn info_specific(
+    thorium: &Thorium,
+    cmd: &GetReactions,
+    output: OutputFormat,
+) -> Result<(), Error> {
     // print our info line header
-    InfoLine::header();
+    output.header(InfoLine::header);
     // crawl over all reaction ids and get info on them
     stream::iter(&cmd.targets)
         .map(|target| async move {
             match thorium.reactions.get(&cmd.group, target.clone()).await {
-                Ok(info) => InfoLine::info(&info),
+                Ok(info) => output.print(&info, InfoLine::info)?,
+                // keep errors out of our JSON output
+                Err(err) if output == OutputFormat::Json => {
+                    eprintln!("Failed to get reaction {target}: {err}");
+                }
                 Err(err) => InfoLine::error(target, &err),
             };
             Ok(())
@@ -280,11 +274,12 @@ async fn delete_specific(thorium: &Thorium, cmd: &DeleteReactions) -> Result<(),
 ///
 /// * `thorium` - A Thorium client
 /// * `cmd` - The full get command/args
-async fn get(thorium: &Thorium, cmd: &GetReactions) -> Result<(), Error> {
+/// * `output` - The format to print our reactions in
+async fn get(thorium: &Thorium, cmd: &GetReactions, output: OutputFormat) -> Result<(), Error> {
     // determine the correct action to take based on the args specified
     let mut cursor = match (cmd.targets.is_empty(), &cmd.pipeline, &cmd.status, &cmd.tag) {
         // get info on specific reactions by id
-        (false, None, None, None) => return info_specific(thorium, cmd).await,
+        (false, None, None, None) => return info_specific(thorium, cmd, output).await,
         // get info on reactions for a specific pipeline
         (true, Some(pipe), None, None) => crawl_pipeline!(thorium, pipe, cmd).await?,
         // get info on reactions for a specific pipeline
@@ -296,20 +291,26 @@ async fn get(thorium: &Thorium, cmd: &GetReactions) -> Result<(), Error> {
         }
     };
     // print our header for this return
-    InfoLine::header();
+    output.header(InfoLine::header);
     // crawl over this cursor until its exhausted
     loop {
         // print info for each reaction we pulled
         for reaction in &cursor.details {
-            InfoLine::info(reaction);
+            output.print(reaction, InfoLine::info)?;
         }
-        // check if this cursor has been exhausted
-        if cursor.exhausted {
+        // check if this cursor has been exhausted or we only want a single page
+        if cursor.exhausted || cmd.page {
             break;
         }
         // get the next page of data
         cursor.next().await?;
     }
+    // print the cursor to continue listing from if there are more reactions
+    if let Some(next) = cursor.next_cursor() {
+        output.message(format!(
+            "\nMore reactions remain, continue listing with '--cursor {next}'"
+        ));
+    }
     Ok(())
 }
 
@@ -730,6 +731,108 @@ async fn logs_search(
         .await
 }
 
+/// Tail the stage logs for a single reaction until it reaches a terminal status
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The reaction logs command that was run
+async fn follow_logs(thorium: &Thorium, cmd: &LogsReactions) -> Result<(), Error> {
+    // we can only follow the logs for a single reaction at a time
+    let [reaction] = cmd.reactions.as_slice() else {
+        return Err(Error::new(
+            "Following logs requires exactly one reaction to be specified",
+        ));
+    };
+    // parse a reaction target from the arg and retrieve the reaction
+    let reaction = ReactionTarget::try_from(reaction)?
+        .get_reaction(thorium)
+        .await?;
+    // retrieve information about the reaction's pipeline
+    let pipeline = error_and_return!(
+        thorium
+            .pipelines
+            .get(&reaction.group, &reaction.pipeline)
+            .await,
+        format!("Unable to retrieve pipeline for reaction '{}'", reaction.id)
+    )?;
+    // build a logs cursor for each stage so we only get new lines on each poll
+    let mut cursors = pipeline
+        .order
+        .iter()
+        .flatten()
+        .unique()
+        .map(|stage| {
+            let cursor = thorium
+                .reactions
+                .logs_cursor(&reaction.group, &reaction.id, stage)
+                .page(cmd.log_limit);
+            (stage.clone(), cursor)
+        })
+        .collect::<Vec<_>>();
+    // track the position of the next line to print for each stage so lines are never printed twice
+    let mut printed: HashMap<String, usize> = HashMap::with_capacity(cursors.len());
+    let interval = std::time::Duration::from_secs(cmd.poll_interval);
+    let mut stdout = std::io::BufWriter::new(std::io::stdout());
+    // stop following when the user hits Ctrl-C
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    println!(
+        "{}",
+        format!(
+            "Following reaction '{}' (Pipeline '{}')",
+            reaction.id, pipeline.name
+        )
+        .bright_green()
+    );
+    loop {
+        // check the status before polling so we always get any final lines
+        let status = thorium
+            .reactions
+            .get(&reaction.group, reaction.id)
+            .await?
+            .status;
+        // poll each stage for new log lines
+        for (stage, cursor) in &mut cursors {
+            // get the position our cursor starts at before getting new lines
+            let start = cursor.cursor;
+            cursor.next().await?;
+            let next = printed.entry(stage.clone()).or_default();
+            for (offset, line) in cursor.logs.logs.drain(..).enumerate() {
+                // skip any lines we have already printed
+                let position = start + offset;
+                if position < *next {
+                    continue;
+                }
+                *next = position + 1;
+                error_and_return!(
+                    writeln!(stdout, "[{}] {line}", stage.bright_yellow()),
+                    "Error writing logs to stdout"
+                )?;
+            }
+        }
+        error_and_return!(stdout.flush(), "Error writing logs to stdout")?;
+        // stop once the reaction has finished
+        if matches!(status, ReactionStatus::Completed | ReactionStatus::Failed) {
+            println!(
+                "{}",
+                format!("Reaction '{}' has {status}", reaction.id).bright_green()
+            );
+            break;
+        }
+        // wait before polling again unless the user wants to stop
+        tokio::select! {
+            _ = &mut ctrl_c => {
+                // flush any buffered output before exiting
+                error_and_return!(stdout.flush(), "Error writing logs to stdout")?;
+                break;
+            }
+            () = tokio::time::sleep(interval) => (),
+        }
+    }
+    Ok(())
+}
+
 /// Retrieve reaction logs
 ///
 /// # Arguments
@@ -737,6 +840,10 @@ async fn logs_search(
 /// * `thorium` - The Thorium client
 /// * `cmd` - The reaction logs command to execute
 async fn logs(thorium: &Thorium, cmd: &LogsReactions) -> Result<(), Error> {
+    // tail this reactions logs if we are following them
+    if cmd.follow {
+        return follow_logs(thorium, cmd).await;
+    }
     cmd.validate_search()?;
     // create a progress bar if not printing to stdout
     let progress = cmd
@@ -865,7 +972,7 @@ pub async fn handle(args: &Args, cmd: &Reactions) -> Result<(), Error> {
     }
     // call the right reactions handler
     match cmd {
-        Reactions::Get(cmd) => get(&thorium, cmd).await,
+        Reactions::Get(cmd) => get(&thorium, cmd, args.output).await,
         Reactions::Describe(cmd) => describe(&thorium, cmd).await,
         Reactions::Logs(cmd) => logs(&thorium, cmd).await,
         Reactions::Delete(cmd) => delete(&thorium, cmd, args, &conf).await,
@@ -1,3 +1,4 @@
+use futures::{StreamExt, stream};
 use owo_colors::{OwoColorize, Rgb};
 use rand::seq::SliceRandom;
 use std::{
@@ -100,7 +101,11 @@ async fn forward_logs(
 /// # Arguments
 ///
 /// * `log_rx` - The channel to poll for log information
-async fn log(mut log_rx: mpsc::Receiver<(String, String)>) -> HashMap<String, Rgb> {
+/// * `prefix` - The prefix to add to each log's label if logs from other reactions are interleaved
+async fn log(
+    mut log_rx: mpsc::Receiver<(String, String)>,
+    prefix: Option<&str>,
+) -> HashMap<String, Rgb> {
     // create a vector of shuffled colors to highlight different stages in a reaction
     let colors = {
         let mut colors = vec![
@@ -132,7 +137,10 @@ async fn log(mut log_rx: mpsc::Receiver<(String, String)>) -> HashMap<String, Rg
             new_color
         });
         // print the log
-        let label = format!("{stage}:");
+        let label = match prefix {
+            Some(prefix) => format!("{prefix} {stage}:"),
+            None => format!("{stage}:"),
+        };
         println!("\r{} {}", label.color(*color).bold(), line);
     }
     stage_colors
@@ -239,12 +247,14 @@ async fn write_result(
 ///
 /// * `thorium` - The Thorium client
 /// * `cmd` - The run subcommand
+/// * `target` - The sample SHA256 or repo the reaction was run on
 /// * `pipeline` - The pipeline of the reaction
 /// * `tool_colors` - A map of tools to colors created while logging
 /// * `run_mode` - The mode our run command is in
 async fn write_results(
     thorium: &Thorium,
     cmd: &Run,
+    target: &str,
     pipeline: Pipeline,
     tool_colors: HashMap<String, Rgb>,
     run_mode: &Mode,
@@ -255,7 +265,7 @@ async fn write_results(
             thorium
                 .files
                 .get_results(
-                    &cmd.sha256_or_repo,
+                    target,
                     &ResultGetParams::default()
                         .tools(pipeline.order.into_iter().flatten())
                         .hidden(),
@@ -264,8 +274,7 @@ async fn write_results(
         }
         Mode::Repo => {
             // parse the URL from the repo
-            let repo_url = cmd
-                .sha256_or_repo
+            let repo_url = target
                 .split(':')
                 .next()
                 // this should never occur because we've already run a reaction with the repo
@@ -284,19 +293,21 @@ async fn write_results(
     };
     if !output_map.results.is_empty() {
         println!("Retrieving results...");
-        // generate a base output path if one wasn't given
-        let base_out_path = match run_mode {
-            Mode::File => cmd.output.clone().unwrap_or(PathBuf::from(format!(
-                "{}_{}",
-                &cmd.sha256_or_repo, &cmd.pipeline
-            ))),
+        // generate the default name for this target's results
+        let name = match run_mode {
+            Mode::File => format!("{target}_{}", &cmd.pipeline),
             Mode::Repo => {
-                let repo = cmd.sha256_or_repo.split('/').last().unwrap_or_default();
-                cmd.output
-                    .clone()
-                    .unwrap_or(PathBuf::from(format!("{}_{}", repo, &cmd.pipeline)))
+                let repo = target.split('/').last().unwrap_or_default();
+                format!("{}_{}", repo, &cmd.pipeline)
             }
         };
+        // generate a base output path if one wasn't given
+        let base_out_path = match &cmd.output {
+            // keep each target's results separate if we are running on more than one
+            Some(output) if cmd.targets.len() > 1 => output.join(name),
+            Some(output) => output.clone(),
+            None => PathBuf::from(name),
+        };
         tokio::fs::create_dir_all(&base_out_path).await?;
         // map the results to futures that write the results to disk concurrently
         futures::future::join_all(
@@ -311,15 +322,8 @@ async fn write_results(
                 .map(|(tool, output)| async {
                     let color = tool_colors.get(&tool).unwrap_or(&Rgb(0, 255, 0));
                     let tool_name = tool.clone();
-                    match write_result(
-                        output,
-                        tool,
-                        &cmd.sha256_or_repo,
-                        &base_out_path,
-                        thorium,
-                        run_mode,
-                    )
-                    .await
+                    match write_result(output, tool, target, &base_out_path, thorium, run_mode)
+                        .await
                     {
                         Ok(()) => println!(
                             "Successfully retrieved results from tool {}",
@@ -343,13 +347,14 @@ async fn write_results(
     Ok(())
 }
 
-/// Run the command on Thorium and stream back the logs to the console
+/// Run the pipeline on a single target and stream back the logs to the console
 ///
 /// # Arguments
 ///
 /// * `thorium` - The Thorium client
 /// * `cmd` - The run command to execute
-async fn run(thorium: Arc<Thorium>, cmd: &Run) -> Result<(), Error> {
+/// * `target` - The sample SHA256 or repo to run the pipeline on
+async fn run(thorium: Arc<Thorium>, cmd: &Run, target: &str) -> Result<(), Error> {
     // find the pipeline's group if none was given
     let group = if let Some(group) = &cmd.group {
         group.clone()
@@ -359,7 +364,7 @@ async fn run(thorium: Arc<Thorium>, cmd: &Run) -> Result<(), Error> {
     // generate a request to create a reaction
     let mut req = ReactionRequest::new(group.clone(), cmd.pipeline.clone()).sla(cmd.sla);
     // get our run mode based on the command
-    let run_mode = Mode::try_from(&cmd.sha256_or_repo)?;
+    let run_mode = Mode::try_from(target)?;
     // supply a file or a repo dependency depending on our mode
     req = match &run_mode {
         Mode::File => {
@@ -367,18 +372,18 @@ async fn run(thorium: Arc<Thorium>, cmd: &Run) -> Result<(), Error> {
             println!(
                 "Running {} on sample {}",
                 cmd.pipeline.bright_green().bold(),
-                cmd.sha256_or_repo.bright_yellow().bold()
+                target.bright_yellow().bold()
             );
-            req.sample(&cmd.sha256_or_repo)
+            req.sample(target)
         }
         Mode::Repo => {
             println!(
                 "Running {} on repo {}",
                 cmd.pipeline.bright_green().bold(),
-                cmd.sha256_or_repo.bright_yellow().bold()
+                target.bright_yellow().bold()
             );
             // otherwise supply a repo
-            let repo_target = RepoTarget::try_from(&cmd.sha256_or_repo).map_err(|err| {
+            let repo_target = RepoTarget::try_from(target).map_err(|err| {
                 Error::new(format!(
                     "The given target is neither a valid SHA256 nor a valid repo! {}",
                     err.msg().unwrap_or_default()
@@ -414,15 +419,135 @@ async fn run(thorium: Arc<Thorium>, cmd: &Run) -> Result<(), Error> {
         reaction.id,
         complete.clone(),
     ));
+    // label our logs with our target if other reactions are logging at the same time
+    let prefix = (cmd.targets.len() > 1).then_some(target);
     // log all information in the channel until the reaction is complete
     // save the tool colors to use when printing result logs
-    let tool_colors = log(log_rx).await;
+    let tool_colors = log(log_rx, prefix).await;
     println!("Reaction {} complete!", reaction.id.bright_green().bold());
     // write the results of the reaction to disk
-    write_results(&thorium, cmd, pipeline, tool_colors, &run_mode).await?;
+    write_results(&thorium, cmd, target, pipeline, tool_colors, &run_mode).await?;
     Ok(())
 }
 
+/// The outcome of running a pipeline on many targets
+#[derive(Debug, Default)]
+struct RunSummary {
+    /// The targets the pipeline was successfully run on
+    succeeded: Vec<String>,
+    /// The targets the pipeline failed to run on and why
+    failed: Vec<(String, Error)>,
+    /// The number of targets that were never started because we were stopped
+    skipped: usize,
+}
+
+impl RunSummary {
+    /// Print a summary of our runs
+    fn print(&self) {
+        println!(
+            "\n{} succeeded, {} failed, {} skipped",
+            self.succeeded.len().bright_green().bold(),
+            self.failed.len().bright_red().bold(),
+            self.skipped.bright_yellow().bold()
+        );
+        // print why each failed run failed
+        for (target, err) in &self.failed {
+            println!(
+                "{}: {}",
+                target.bright_red().bold(),
+                err.msg()
+                    .unwrap_or("An unknown error has occurred".to_string())
+            );
+        }
+    }
+}
+
+/// Run something on many targets with a limit on how many can run at once
+///
+/// No new runs are started once `stop` is set but any runs that are already in
+/// flight are allowed to finish.
+///
+/// # Arguments
+///
+/// * `targets` - The targets to run on
+/// * `parallel` - The max number of runs that can be in flight at once
+/// * `stop` - Whether to stop starting new runs
+/// * `run_one` - Runs on a single target
+async fn run_all<'a, F, Fut>(
+    targets: &'a [String],
+    parallel: usize,
+    stop: &AtomicBool,
+    run_one: F,
+) -> RunSummary
+where
+    F: Fn(&'a str) -> Fut,
+    Fut: Future<Output = Result<(), Error>>,
+{
+    // run on each target until we run out of targets or are stopped
+    let outcomes = stream::iter(targets)
+        .take_while(|_| std::future::ready(!stop.load(Ordering::Relaxed)))
+        .map(|target| {
+            let run = run_one(target);
+            async move { (target, run.await) }
+        })
+        .buffer_unordered(parallel)
+        .collect::<Vec<_>>()
+        .await;
+    // sort our runs into successes and failures
+    let mut summary = RunSummary {
+        skipped: targets.len() - outcomes.len(),
+        ..RunSummary::default()
+    };
+    for (target, outcome) in outcomes {
+        match outcome {
+            Ok(()) => summary.succeeded.push(target.clone()),
+            Err(err) => summary.failed.push((target.clone(), err)),
+        }
+    }
+    summary
+}
+
+/// Run the pipeline on all of our targets and print a summary of how it went
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The run command to execute
+async fn run_many(thorium: Arc<Thorium>, cmd: &Run) -> Result<(), Error> {
+    // stop starting new runs when the user hits Ctrl-C
+    let stop = Arc::new(AtomicBool::new(false));
+    let ctrl_c = tokio::spawn({
+        let stop = stop.clone();
+        async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                stop.store(true, Ordering::Relaxed);
+                println!(
+                    "{}",
+                    "Stopping! Waiting for in-flight reactions to finish...".bright_yellow()
+                );
+            }
+        }
+    });
+    // run our pipeline on all of our targets
+    let summary = run_all(&cmd.targets, cmd.parallel, &stop, |target| {
+        run(thorium.clone(), cmd, target)
+    })
+    .await;
+    ctrl_c.abort();
+    summary.print();
+    // fail if any of our runs failed
+    if summary.failed.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::new(format!(
+            "Failed to run {} on {} of {} targets",
+            cmd.pipeline,
+            summary.failed.len(),
+            cmd.targets.len()
+        )))
+    }
+}
+
 /// Handle all run commands or print run info
 ///
 /// # Arguments
@@ -441,5 +566,73 @@ pub async fn handle(args: &Args, cmd: &Run) -> Result<(), Error> {
         update::ask_update(&thorium).await?;
     }
     // call the run handler
-    run(Arc::new(thorium), cmd).await
+    match cmd.targets.as_slice() {
+        [target] => run(Arc::new(thorium), cmd, target).await,
+        _ => run_many(Arc::new(thorium), cmd).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::run_all;
+    use crate::Error;
+
+    /// Build a list of fake targets
+    fn targets(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("target{i}")).collect()
+    }
+
+    #[tokio::test]
+    async fn run_all_limits_parallel() {
+        let targets = targets(20);
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let summary = run_all(&targets, 3, &stop, |target| {
+            let (in_flight, max_in_flight) = (&in_flight, &max_in_flight);
+            async move {
+                // track the most runs we've seen at once
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                // fail every other target
+                if target.ends_with(['1', '3', '5', '7', '9']) {
+                    return Err(Error::new("corn blight"));
+                }
+                Ok(())
+            }
+        })
+        .await;
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 3);
+        assert_eq!(summary.succeeded.len(), 10);
+        assert_eq!(summary.failed.len(), 10);
+        assert_eq!(summary.skipped, 0);
+    }
+
+    #[tokio::test]
+    async fn run_all_stops_launching() {
+        let targets = targets(20);
+        let started = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let summary = run_all(&targets, 2, &stop, |_| {
+            let (started, stop) = (&started, &stop);
+            async move {
+                started.fetch_add(1, Ordering::SeqCst);
+                // pretend the user hit Ctrl-C as soon as the first runs started
+                stop.store(true, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok(())
+            }
+        })
+        .await;
+        // our in-flight runs should finish but no new runs should start
+        let started = started.load(Ordering::SeqCst);
+        assert!(started <= 2, "{started} runs were started");
+        assert_eq!(summary.succeeded.len(), started);
+        assert_eq!(summary.skipped, targets.len() - started);
+    }
 }
@@ -1,16 +1,11 @@
-use cart_rs::UncartStream;
 use regex::RegexSet;
 use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
 use thorium::Error;
-use tokio::{
-    fs::{File, OpenOptions},
-    io::BufStream,
-    task::JoinError,
-};
-use uuid::Uuid;
+use thorium::cart::{self, UncartOpts, resume};
+use tokio::task::JoinError;
 
 use crate::args::{Args, uncart::Uncart};
 use crate::utils;
@@ -68,9 +63,9 @@ pub async fn handle(args: &Args, cmd: &Uncart) -> Result<(), Error> {
         args.workers,
     )
     .await;
-    // remove temporary drectory after in-place conversion
+    // remove temporary drectory after in-place conversion unless it has partial output to resume
     if cmd.in_place {
-        tokio::fs::remove_dir_all(base_out_path).await?;
+        resume::remove_temp_dir(base_out_path).await?;
     }
     Ok(())
 }
@@ -90,12 +85,6 @@ async fn uncart_path(
     base_out_path: PathBuf,
     cmd: Arc<Uncart>,
 ) -> Result<PathBuf, Error> {
-    // read cart file
-    let cart: File = OpenOptions::new()
-        .read(true)
-        .write(false)
-        .open(&path)
-        .await?;
     let mut out_path = construct_out_path(
         &base_out_path,
         &path,
@@ -106,21 +95,13 @@ async fn uncart_path(
     if let Some(out_path_parent) = out_path.parent() {
         tokio::fs::create_dir_all(&out_path_parent).await?;
     }
-    // create a stream to uncart the file and copy the stream's contents to the output path
-    let mut uncart_stream = UncartStream::new(BufStream::new(cart));
-    let mut uncarted = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(&out_path)
-        .await?;
-    if let Err(err) = tokio::io::copy(&mut uncart_stream, &mut uncarted).await {
-        // if an error occurred while uncarting, delete the output file and return the error
-        drop(uncarted);
-        tokio::fs::remove_file(&out_path).await?;
-        return Err(Error::from(err));
+    let mut opts = UncartOpts::default();
+    // keep our progress next to where our output ends up so it outlives our temp dir
+    if cmd.in_place {
+        opts = opts.progress(resume::progress_path(&path));
     }
+    // uncart our file, picking up where we left off if a previous attempt failed partway through
+    cart::uncart_file(&path, &out_path, &opts).await?;
     // if conversion is in-place, replace the carted file with the uncarted output
     if cmd.in_place {
         tokio::fs::rename(&out_path, &path).await?;
@@ -178,7 +159,7 @@ fn construct_out_path(
         out_path.as_mut_os_string().push(POSTFIX);
     }
     if in_place {
-        out_path.set_file_name(format!(".temp-{}{}", Uuid::new_v4(), POSTFIX));
+        out_path.set_file_name(format!("{}{POSTFIX}", resume::temp_name(path)));
     }
     Ok(out_path)
 }
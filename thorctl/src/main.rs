@@ -28,6 +28,7 @@ async fn main() {
         SubCommands::Clusters(clusters) => handlers::clusters::handle(&args, clusters).await,
         SubCommands::Groups(groups) => handlers::groups::handle(&args, groups).await,
         SubCommands::Files(files) => handlers::files::handle(&args, files).await,
+        SubCommands::Entities(entities) => handlers::entities::handle(&args, entities).await,
         SubCommands::Images(images) => handlers::images::handle(&args, images).await,
         SubCommands::Pipelines(pipelines) => handlers::pipelines::handle(&args, pipelines).await,
         SubCommands::Reactions(reactions) => handlers::reactions::handle(&args, reactions).await,
@@ -50,7 +51,8 @@ async fn main() {
         // print our error to stderr nicely if possible
         match error {
             Error::Generic(msg) => eprintln!("{msg}"),
-            _ => eprintln!("{error:#?}"),
+            _ if args.debug => eprintln!("{error:#?}"),
+            _ => eprintln!("{error}"),
         }
         // exit this program with an exit code of 1
         std::process::exit(1);
@@ -94,6 +94,7 @@ fn calculate_sample_dependencies_update(
         kwarg: set_modified_opt!(old.kwarg, new.kwarg),
         strategy: set_modified!(old.strategy, new.strategy),
         naming: set_modified!(old.naming, new.naming),
+        verify: set_modified!(old.verify, new.verify),
     }
 }
 
@@ -35,5 +35,6 @@ pub async fn find_reaction_no_group(
         msg: Some(format!(
             "Reaction {reaction_id} not found in any of the user's groups"
         )),
+        path: None,
     })
 }
@@ -1,8 +1,9 @@
 extern crate proc_macro;
 
+use darling::FromDeriveInput;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::Ident;
+use syn::{Expr, Ident};
 
 #[cfg(feature = "sync")]
 mod sync;
@@ -108,7 +109,25 @@ fn add_as_str_serialize(stream: &mut proc_macro2::TokenStream, name: &Ident) {
 }
 
 /// Add the as str based serialzie impl
-fn add_as_str_deserialize(stream: &mut proc_macro2::TokenStream, name: &Ident) {
+///
+/// # Arguments
+///
+/// * `stream` - The stream to add our impl to
+/// * `name` - The name of the type we are deriving for
+/// * `error` - The error to return instead of our defaults when a value fails to deserialize
+fn add_as_str_deserialize(
+    stream: &mut proc_macro2::TokenStream,
+    name: &Ident,
+    error: Option<&Expr>,
+) {
+    // use our custom error if one was set or fall back to our defaults
+    let (not_text, unknown) = match error {
+        Some(error) => (quote! { #error }, quote! { #error }),
+        None => (
+            quote! { crate::models::scylla_utils::errors::DeserializationError::ExpectedText },
+            quote! { crate::models::scylla_utils::errors::DeserializationError::UnknownValue },
+        ),
+    };
     // extend our token stream
     stream.extend(quote! {
         impl<'frame, 'metadata> scylla::deserialize::value::DeserializeValue<'frame, 'metadata> for #name {
@@ -131,7 +150,7 @@ fn add_as_str_deserialize(stream: &mut proc_macro2::TokenStream, name: &Ident) {
                             Ok(converted) => converted,
                             Err(_) => {
                                 return Err(scylla::deserialize::DeserializationError::new(
-                                    crate::models::scylla_utils::errors::DeserializationError::ExpectedText,
+                                    #not_text,
                                 ))
                             }
                         };
@@ -139,7 +158,7 @@ fn add_as_str_deserialize(stream: &mut proc_macro2::TokenStream, name: &Ident) {
                         match #name::from_str(converted) {
                             Ok(event_type) => Ok(event_type),
                             Err(_) => Err(scylla::deserialize::DeserializationError::new(
-                                crate::models::scylla_utils::errors::DeserializationError::UnknownValue,
+                                #unknown,
                             )),
                         }
                     }
@@ -152,17 +171,212 @@ fn add_as_str_deserialize(stream: &mut proc_macro2::TokenStream, name: &Ident) {
     });
 }
 
-#[proc_macro_derive(ScyllaStoreAsStr)]
+/// Arguments to the `ScyllaStoreAsStr` derive
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(scylla_store))]
+struct ScyllaStoreArgs {
+    /// The name of the type we are deriving for
+    ident: Ident,
+    /// The error to return when a value is not utf8 or fails to parse with `from_str`
+    ///
+    /// This must implement `std::error::Error + Send + Sync`. When this is not set our
+    /// default `ExpectedText` and `UnknownValue` errors are used.
+    error: Option<Expr>,
+}
+
+/// Store a type in scylla as a string using its `as_str` and `from_str` impls
+///
+/// A custom error can be returned on deserialization failures with
+/// `#[scylla_store(error = "MyError::BadTag")]`.
+#[proc_macro_derive(ScyllaStoreAsStr, attributes(scylla_store))]
 pub fn derive_scylla_store_as_str(stream: TokenStream) -> TokenStream {
     // parse our input struct
     let ast = syn::parse_macro_input!(stream as syn::DeriveInput);
-    // get the name of our ident
-    let name = &ast.ident;
+    // parse any scylla store args
+    let args = match ScyllaStoreArgs::from_derive_input(&ast) {
+        Ok(args) => args,
+        Err(error) => return error.write_errors().into(),
+    };
     // start with an empty stream
     let mut output = quote! {};
     // add our json derives
-    add_as_str_serialize(&mut output, name);
-    add_as_str_deserialize(&mut output, name);
+    add_as_str_serialize(&mut output, &args.ident);
+    add_as_str_deserialize(&mut output, &args.ident, args.error.as_ref());
+    output.into()
+}
+
+/// Get the integer type and scylla column type to store a fieldless enum as
+///
+/// This is based on the enum's `repr` and defaults to an `i32` when no `repr` is set.
+///
+/// # Arguments
+///
+/// * `ast` - The enum we are deriving for
+fn as_int_repr(ast: &syn::DeriveInput) -> syn::Result<(Ident, Ident)> {
+    // default to storing our enum as an i32
+    let mut repr = Ident::new("i32", ast.ident.span());
+    // check if a repr was set for this enum
+    for attr in ast.attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            // only use integer reprs
+            if meta.path.is_ident("i16") || meta.path.is_ident("i32") {
+                repr = meta.path.require_ident()?.clone();
+            } else if meta.path.is_ident("C") {
+                // C does not change our discriminant type
+            } else {
+                return Err(meta.error("ScyllaStoreAsInt only supports i16 or i32 reprs"));
+            }
+            Ok(())
+        })?;
+    }
+    // get the scylla column type for our repr
+    let native = if repr == "i16" {
+        Ident::new("SmallInt", repr.span())
+    } else {
+        Ident::new("Int", repr.span())
+    };
+    Ok((repr, native))
+}
+
+/// Add the integer discriminant based serialize impl
+///
+/// # Arguments
+///
+/// * `stream` - The stream to add our impl to
+/// * `name` - The name of the type we are deriving for
+/// * `repr` - The integer type to store our discriminant as
+/// * `variants` - The variants in our enum
+fn add_as_int_serialize(
+    stream: &mut proc_macro2::TokenStream,
+    name: &Ident,
+    repr: &Ident,
+    variants: &[&Ident],
+) {
+    // extend our token stream
+    stream.extend(quote! {
+        impl scylla::serialize::value::SerializeValue for #name {
+            fn serialize<'b>(
+                &self,
+                typ: &scylla::frame::response::result::ColumnType,
+                writer: scylla::serialize::writers::CellWriter<'b>,
+            ) -> Result<scylla::serialize::writers::WrittenCellProof<'b>, scylla::serialize::SerializationError> {
+                // cast our value to its discriminant
+                let value = match self {
+                    #(#name::#variants => #name::#variants as #repr,)*
+                };
+                // serialize our discriminant
+                scylla::serialize::value::SerializeValue::serialize(&value, typ, writer)
+            }
+        }
+    });
+}
+
+/// Add the integer discriminant based deserialize impl
+///
+/// # Arguments
+///
+/// * `stream` - The stream to add our impl to
+/// * `name` - The name of the type we are deriving for
+/// * `repr` - The integer type our discriminant is stored as
+/// * `native` - The scylla column type our discriminant is stored as
+/// * `variants` - The variants in our enum
+/// * `error` - The error to return instead of our default when a discriminant is unknown
+fn add_as_int_deserialize(
+    stream: &mut proc_macro2::TokenStream,
+    name: &Ident,
+    repr: &Ident,
+    native: &Ident,
+    variants: &[&Ident],
+    error: Option<&Expr>,
+) {
+    // use our custom error if one was set or fall back to our default
+    let unknown = match error {
+        Some(error) => quote! { #error },
+        None => quote! { crate::models::scylla_utils::errors::DeserializationError::UnknownValue },
+    };
+    // extend our token stream
+    stream.extend(quote! {
+        impl<'frame, 'metadata> scylla::deserialize::value::DeserializeValue<'frame, 'metadata> for #name {
+            fn type_check(typ: &scylla::frame::response::result::ColumnType) -> Result<(), scylla::deserialize::TypeCheckError> {
+                if let scylla::frame::response::result::ColumnType::Native(scylla::cluster::metadata::NativeType::#native) = typ {
+                    return Ok(());
+                }
+                Err(scylla::deserialize::TypeCheckError::new(crate::models::scylla_utils::errors::DeserializationError::ExpectedInt))
+            }
+
+            fn deserialize(
+                typ: &'metadata scylla::frame::response::result::ColumnType<'metadata>,
+                v: Option<scylla::deserialize::FrameSlice<'frame>>,
+            ) -> Result<Self, scylla::deserialize::DeserializationError> {
+                // get our raw discriminant
+                let value = <#repr as scylla::deserialize::value::DeserializeValue>::deserialize(typ, v)?;
+                // get the variant for this discriminant
+                #(
+                    if value == #name::#variants as #repr {
+                        return Ok(#name::#variants);
+                    }
+                )*
+                Err(scylla::deserialize::DeserializationError::new(#unknown))
+            }
+        }
+    });
+}
+
+/// Store a fieldless enum in scylla as its integer discriminant
+///
+/// Enums with `#[repr(i16)]` are stored as a `SmallInt` and all others are stored as an
+/// `Int`. A custom error can be returned for unknown discriminants with
+/// `#[scylla_store(error = "MyError::BadStatus")]`.
+#[proc_macro_derive(ScyllaStoreAsInt, attributes(scylla_store))]
+pub fn derive_scylla_store_as_int(stream: TokenStream) -> TokenStream {
+    // parse our input enum
+    let ast = syn::parse_macro_input!(stream as syn::DeriveInput);
+    // parse any scylla store args
+    let args = match ScyllaStoreArgs::from_derive_input(&ast) {
+        Ok(args) => args,
+        Err(error) => return error.write_errors().into(),
+    };
+    // make sure this is a fieldless enum
+    let variants = match &ast.data {
+        syn::Data::Enum(data) => {
+            // get the names of our variants
+            let mut variants = Vec::with_capacity(data.variants.len());
+            for variant in &data.variants {
+                if !matches!(variant.fields, syn::Fields::Unit) {
+                    return syn::Error::new_spanned(
+                        variant,
+                        "ScyllaStoreAsInt only supports fieldless enums",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+                variants.push(&variant.ident);
+            }
+            variants
+        }
+        _ => {
+            return syn::Error::new_spanned(&ast.ident, "ScyllaStoreAsInt only supports enums")
+                .to_compile_error()
+                .into();
+        }
+    };
+    // get the type to store our discriminant as
+    let (repr, native) = match as_int_repr(&ast) {
+        Ok(repr) => repr,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    // start with an empty stream
+    let mut output = quote! {};
+    // add our int derives
+    add_as_int_serialize(&mut output, &args.ident, &repr, &variants);
+    add_as_int_deserialize(
+        &mut output,
+        &args.ident,
+        &repr,
+        &native,
+        &variants,
+        args.error.as_ref(),
+    );
     output.into()
 }
 
@@ -3,7 +3,7 @@
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::{format_ident, quote};
-use syn::{Attribute, Block, Error, ImplItemFn, ItemFn, Signature, Visibility};
+use syn::{Attribute, Block, Error, Ident, ImplItemFn, ItemFn, Signature, Visibility};
 
 mod sync_struct;
 mod sync_trait;
@@ -80,13 +80,51 @@ fn blocking_fn_helper(
             #block
         }
     };
+    // block on our async block without deadlocking if we are already in a runtime
+    let block_body = block_on(&new_sig.ident, &async_block);
     // return the regular function and the blocking function
     Ok(quote! {
         #(
             #attrs
         )*
         #vis #new_sig {
-            RUNTIME.block_on(#async_block)
+            #block_body
         }
     })
 }
+
+/// Block on a future with the static runtime
+///
+/// Blocking a multi-threaded runtime's worker thread is done in place so its
+/// other tasks can move to another worker. A current thread runtime has no other
+/// worker to hand its tasks to, so the generated code panics with a message that
+/// says what went wrong instead of deadlocking or panicking deep inside Tokio.
+/// Threads outside of a runtime can block on the static runtime directly.
+///
+/// # Arguments
+///
+/// * `name` - The name of the blocking function being generated
+/// * `future` - The future to block on
+pub(crate) fn block_on(
+    name: &Ident,
+    future: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let name = name.to_string();
+    quote! {
+        {
+            let future = #future;
+            match tokio::runtime::Handle::try_current() {
+                Ok(handle)
+                    if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread =>
+                {
+                    tokio::task::block_in_place(|| RUNTIME.block_on(future))
+                }
+                Ok(_) => panic!(
+                    "`{}` is a blocking method and cannot be called from within a current thread async runtime, use the async client instead",
+                    #name
+                ),
+                Err(_) => RUNTIME.block_on(future),
+            }
+        }
+    }
+}
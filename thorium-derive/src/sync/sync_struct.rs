@@ -11,6 +11,7 @@ use syn::{
     Meta, ReturnType, Signature, Type, TypePath, parse_quote,
 };
 
+use super::block_on;
 use crate::utils;
 
 /// Arguments to the blocking struct attribute
@@ -302,10 +303,18 @@ fn blocking_new(new: &ImplItemFn, self_ty: &TypePath) -> Result<proc_macro2::Tok
     // instance of the async struct
     let body = match (is_async, returns_result) {
         (true, true) => {
-            quote! { Ok(Self { inner: RUNTIME.block_on(<#self_ty>::#method_name(#(#args),*))? }) }
+            let inner = block_on(
+                method_name,
+                &quote! { <#self_ty>::#method_name(#(#args),*) },
+            );
+            quote! { Ok(Self { inner: #inner? }) }
         }
         (true, false) => {
-            quote! { Self { inner: RUNTIME.block_on(<#self_ty>::#method_name(#(#args),*)) }}
+            let inner = block_on(
+                method_name,
+                &quote! { <#self_ty>::#method_name(#(#args),*) },
+            );
+            quote! { Self { inner: #inner }}
         }
         (false, true) => quote! { Ok(Self { inner: <#self_ty>::#method_name(#(#args),*)? }) },
         (false, false) => quote! { Self { inner: <#self_ty>::#method_name(#(#args),*) }},
@@ -408,9 +417,7 @@ fn blocking_impl_fn(
     let call_args = quote! { #(#args),* };
     let block_body = if is_async {
         // if the method is async, block it on the static runtime
-        quote! {
-            RUNTIME.block_on(#target(#call_args))
-        }
+        block_on(method_name, &quote! { #target(#call_args) })
     } else {
         // otherwise just call the function on the inner
         quote! {
@@ -7,6 +7,8 @@ use syn::{
     Error, Ident, ImplItemFn, ItemImpl, ItemTrait, Path, TraitItemFn, TypePath, parse_quote,
 };
 
+use super::block_on;
+
 /// Create a wrapping, synchronous version of the given trait with the
 /// suffix `Blocking` appended
 ///
@@ -173,9 +175,7 @@ fn blocking_fn_trait(
         };
         // if the method is async, block it on the static runtime
         let block_body = if is_async {
-            quote! {
-                RUNTIME.block_on(#target(#call_args))
-            }
+            block_on(method_name, &quote! { #target(#call_args) })
         } else {
             quote! {
                 #target(#call_args)
@@ -344,9 +344,7 @@ fn blocking_impl_fn_trait(
     };
     // if the method is async, block it on the static runtime
     let block_body = if is_async {
-        quote! {
-            RUNTIME.block_on(#target(#call_args))
-        }
+        block_on(method_name, &quote! { #target(#call_args) })
     } else {
         quote! {
             #target(#call_args)
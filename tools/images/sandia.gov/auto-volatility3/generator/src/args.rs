@@ -9,6 +9,8 @@ use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 use uuid::Uuid;
 
+use crate::OsKinds;
+
 /// Provide a default config path
 fn default_config_path() -> PathBuf {
     let mut default_config_path = dirs::home_dir().unwrap_or_default();
@@ -43,4 +45,9 @@ pub struct Args {
     /// The path to the keys to use when talking to thorium
     #[clap(long, conflicts_with = "config")]
     pub keys: Option<String>,
+    /// The os kinds to run modules for if no rules match this memory image
+    ///
+    /// If this is empty then the job will be failed when no os can be detected
+    #[clap(long, value_enum, value_delimiter = ',')]
+    pub fallback: Vec<OsKinds>,
 }
@@ -1,8 +1,8 @@
 use args::Args;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thorium::models::{Reaction, ReactionRequest};
 use thorium::{Thorium, models::GenericJobArgs};
 use uuid::Uuid;
@@ -15,9 +15,11 @@ pub struct VolFan {
     thorium: Thorium,
     /// The rules to scan with
     rules: Rules,
+    /// The os kinds to fall back to if no rules match
+    fallback: Vec<OsKinds>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum OsKinds {
     // A Windows memory image
     Windows,
@@ -161,18 +163,81 @@ impl OsKinds {
     }
 }
 
-impl From<Rule<'_, '_>> for OsKinds {
-    /// Convert a rule hit to an ``OsKind``
-    fn from(rule: Rule) -> Self {
+impl TryFrom<Rule<'_, '_>> for OsKinds {
+    type Error = String;
+
+    /// Try to convert a rule hit to an ``OsKind``
+    fn try_from(rule: Rule) -> Result<Self, Self::Error> {
         match rule.identifier() {
-            "Windows_Memory_Image" => OsKinds::Windows,
-            "Linux_Memory_Image" => OsKinds::Linux,
-            "Mac_Memory_Image" => OsKinds::Mac,
-            unknown => panic!("Unknown Rule: {unknown}"),
+            "Windows_Memory_Image" => Ok(OsKinds::Windows),
+            "Linux_Memory_Image" => Ok(OsKinds::Linux),
+            "Mac_Memory_Image" => Ok(OsKinds::Mac),
+            unknown => Err(format!("Unknown Rule: {unknown}")),
         }
     }
 }
 
+/// Compile our yara rules from disk
+fn load_rules() -> Rules {
+    // compile our yara rules from disk
+    let mut compiler = yara_x::Compiler::new();
+    // go through and read in all of our rule files
+    let win_rule =
+        std::fs::read_to_string("rules/windows.yar").expect("Failed to load windows rules");
+    compiler.add_source(win_rule.as_str()).unwrap();
+    let linux_rule =
+        std::fs::read_to_string("rules/linux.yar").expect("Failed to load linux rules");
+    compiler.add_source(linux_rule.as_str()).unwrap();
+    // compile our rules
+    compiler.build()
+}
+
+/// Scan a memory image with our rules and get the os kinds that matched
+///
+/// # Arguments
+///
+/// * `rules` - The rules to scan with
+/// * `target` - The memory image to scan
+fn probe_rules(rules: &Rules, target: &Path) -> Vec<OsKinds> {
+    // build a yara scanner with our rules
+    let mut scanner = yara_x::Scanner::new(rules);
+    // scan our target memory image and try to determine what OS its from
+    let scan_res = scanner
+        .scan_file(target)
+        .unwrap_or_else(|_| panic!("Failed to scan {}", target.display()));
+    // convert our rule hits into the os kinds skipping any rules we don't know about
+    scan_res
+        .matching_rules()
+        .filter_map(|rule| match OsKinds::try_from(rule) {
+            Ok(os_kind) => Some(os_kind),
+            Err(msg) => {
+                println!("Skipping rule hit: {msg}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Pick the os kinds to spawn modules for, falling back if none were detected
+///
+/// # Arguments
+///
+/// * `detected` - The os kinds our rules detected
+/// * `fallback` - The os kinds to use if none were detected
+fn select_os_kinds(detected: Vec<OsKinds>, fallback: &[OsKinds]) -> Result<Vec<OsKinds>, String> {
+    // if we detected any os kinds then use those
+    if !detected.is_empty() {
+        return Ok(detected);
+    }
+    // we didn't detect anything so use our fallback if we have one
+    if fallback.is_empty() {
+        Err("Failed to detect an OS for this memory image and no fallback was set".to_owned())
+    } else {
+        println!("No OS detected! Falling back to {fallback:?}");
+        Ok(fallback.to_vec())
+    }
+}
+
 impl VolFan {
     /// Create a new Volaltility Fan Outer
     async fn new(args: &Args) -> Self {
@@ -186,18 +251,13 @@ impl VolFan {
                 .expect("Failed to create Thorium client from ctlconf"),
         };
         // compile our yara rules from disk
-        let mut compiler = yara_x::Compiler::new();
-        // go through and read in all of our rule files
-        let win_rule =
-            std::fs::read_to_string("rules/windows.yar").expect("Failed to load windows rules");
-        compiler.add_source(win_rule.as_str()).unwrap();
-        let linux_rule =
-            std::fs::read_to_string("rules/linux.yar").expect("Failed to load linux rules");
-        compiler.add_source(linux_rule.as_str()).unwrap();
-        // compile our rules
-        let rules = compiler.build();
+        let rules = load_rules();
         // build our volatility fanner
-        VolFan { thorium, rules }
+        VolFan {
+            thorium,
+            rules,
+            fallback: args.fallback.clone(),
+        }
     }
 
     /// Get our reaction info
@@ -213,14 +273,8 @@ impl VolFan {
     /// Probe a memory image for the correct os
     fn probe(&self, target: &PathBuf) -> Vec<OsKinds> {
         println!("Probing {}", target.display());
-        // build a yara scanner with our rules
-        let mut scanner = yara_x::Scanner::new(&self.rules);
         // scan our target memory image and try to determine what OS its from
-        let scan_res = scanner
-            .scan_file(target)
-            .unwrap_or_else(|_| panic!("Failed to scan {}", target.display()));
-        // convert our rule hits into the os kinds
-        scan_res.matching_rules().map(OsKinds::from).collect()
+        probe_rules(&self.rules, target)
     }
 
     /// Write our detected os kinds to disk
@@ -242,10 +296,19 @@ impl VolFan {
 
     /// Analyze and fan out jobs for this memory image
     ///
+    /// # Errors
+    ///
+    /// Returns an error if no os was detected and no fallback was set.
+    ///
     /// # Panics
     ///
     /// Panics if we can't create reactions.
-    pub async fn analyze(&self, target: &PathBuf, reaction: Reaction, job: Uuid) {
+    pub async fn analyze(
+        &self,
+        target: &PathBuf,
+        reaction: Reaction,
+        job: Uuid,
+    ) -> Result<(), String> {
         // get our target sha2x56
         let sha256 = reaction.samples.first().expect("Reaction has no samples?");
         // pre allocate a vec for our bulk spawned reactions
@@ -256,7 +319,9 @@ impl VolFan {
             .tag(sha256)
             .parent(reaction.id);
         // probe this memory image to determine the os kind
-        let os_kinds = self.probe(target);
+        let detected = self.probe(target);
+        // fall back to our default os kinds if we didn't detect any or fail this job
+        let os_kinds = select_os_kinds(detected, &self.fallback)?;
         // spawn jobs for all of our detected os kind
         for os_kind in &os_kinds {
             println!("Detected: {os_kind:?}");
@@ -273,7 +338,6 @@ impl VolFan {
                 // add this specific request to our list of reactions to create
                 reqs.push(specific);
             }
-
         }
         println!("Creating {} reactions", reqs.len());
         // create sub reactions for all of the requested modules
@@ -308,6 +372,7 @@ impl VolFan {
             .sleep(&job, "Analyzed")
             .await
             .expect("Failed to sleep reaction");
+        Ok(())
     }
 
     /// Write our results to disk for Thorium to pickup
@@ -355,7 +420,17 @@ impl VolFan {
     }
 
     /// Analyze or submit results for a specific reaction
-    pub async fn process(&self, target: &PathBuf, reaction: Uuid, job: Uuid, group: &str) {
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if we couldn't determine which modules to run for this memory image.
+    pub async fn process(
+        &self,
+        target: &PathBuf,
+        reaction: Uuid,
+        job: Uuid,
+        group: &str,
+    ) -> Result<(), String> {
         // get our reaction info
         let reaction = self.get_reaction_info(reaction, group).await;
         // if we have cache info then we have already ran
@@ -363,8 +438,9 @@ impl VolFan {
             self.submit().await;
         } else {
             // determine the right os type for thismemory image and then start up analysis jobs
-            self.analyze(target, reaction, job).await;
+            self.analyze(target, reaction, job).await?;
         }
+        Ok(())
     }
 }
 
@@ -374,6 +450,37 @@ async fn main() {
     let args = Args::parse();
     // build a volatility fan outer
     let volfan = VolFan::new(&args).await;
-    // process this memory image
-    volfan.process(&args.target, args.reaction, args.job, &args.group).await;
+    // process this memory image and fail this job if we couldn't
+    if let Err(msg) = volfan
+        .process(&args.target, args.reaction, args.job, &args.group)
+        .await
+    {
+        eprintln!("{msg}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OsKinds, load_rules, probe_rules, select_os_kinds};
+
+    /// Make sure a memory image that matches no rules falls back correctly
+    #[test]
+    fn non_matching_fallback() {
+        // write a target that won't match any of our rules
+        let target = std::env::temp_dir().join(format!("volfan-{}.raw", std::process::id()));
+        std::fs::write(&target, vec![0; 4096]).unwrap();
+        // probe this target with our real rules
+        let detected = probe_rules(&load_rules(), &target);
+        std::fs::remove_file(&target).unwrap();
+        assert!(detected.is_empty());
+        // without a fallback we should fail instead of spawning nothing
+        assert!(select_os_kinds(detected.clone(), &[]).is_err());
+        // with a fallback we should use our fallback os kinds
+        let selected = select_os_kinds(detected, &[OsKinds::Linux]).unwrap();
+        assert_eq!(selected, vec![OsKinds::Linux]);
+        // detected os kinds should always win over our fallback
+        let selected = select_os_kinds(vec![OsKinds::Windows], &[OsKinds::Linux]).unwrap();
+        assert_eq!(selected, vec![OsKinds::Windows]);
+    }
 }
@@ -59,7 +59,7 @@ const ImageFieldsErrorTemplate = {
 };
 
 // All possible Enums for the display_type field and scaler
-const DisplayTypes = ['JSON', 'String', 'Table', 'Markdown', 'XML', 'HTML', 'Image', 'Disassembly', 'Hidden', 'Custom'];
+const DisplayTypes = ['JSON', 'String', 'Table', 'Markdown', 'XML', 'HTML', 'Image', 'Disassembly', 'Hidden', 'Custom', 'Template'];
 const ScalerTypes = ['K8s', 'BareMetal', 'External', 'Windows', 'Kvm'];
 
 const formatLifetimeDisplay = (lifetime) => {
@@ -22,6 +22,8 @@ export enum AssociationKind {
   /// This campaign was performed by
   PerformedBy = 'Performed By',
   AssociatedWith = 'Associated With',
+  /// This is related to something else without implying a direction
+  RelatedTo = 'Related To',
 }
 
 export type AssociationTarget = {
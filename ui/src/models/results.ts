@@ -26,8 +26,22 @@ export enum OutputDisplayType {
   Hidden = 'Hidden',
   /// Result to render is XML formatted
   Xml = 'XML',
+  /// Render this output with a custom template, template will be based on tool name
+  Template = 'Template',
 }
 
+/// The metadata describing how to render an output
+export type OutputRenderer = {
+  /// The name of the renderer to use
+  renderer: string;
+  /// The mime type of the output being rendered
+  mime: string;
+  /// Whether this output must be sanitized before being rendered
+  sanitize: boolean;
+  /// The custom render class or template to use if one is required
+  template?: string;
+};
+
 /// A single result for a single run of a tool with a specific command
 export type Output = {
   /// The id for this result
@@ -48,6 +62,8 @@ export type Output = {
   files?: string[];
   /// The display type of this tool output
   display_type: OutputDisplayType;
+  /// The metadata describing how to render this output
+  renderer?: OutputRenderer;
   /// The children that were found when generating this result
   children: { [child: string]: string };
 };